@@ -2,6 +2,7 @@ use agent::ThreadId;
 use anyhow::{Context as _, Result, bail};
 use file_icons::FileIcons;
 use prompt_store::{PromptId, UserPromptId};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::{
     fmt,
     ops::Range,
@@ -41,6 +42,11 @@ pub enum MentionUri {
     Fetch {
         url: Url,
     },
+    McpResource {
+        server_id: String,
+        uri: String,
+        name: String,
+    },
 }
 
 impl MentionUri {
@@ -109,6 +115,21 @@ impl MentionUri {
                         id: rule_id.into(),
                         name,
                     })
+                } else if let Some(server_id) = path.strip_prefix("/agent/mcp-resource/") {
+                    let mut uri = None;
+                    let mut name = None;
+                    for (key, value) in url.query_pairs() {
+                        match key.as_ref() {
+                            "uri" => uri = Some(value.to_string()),
+                            "name" => name = Some(value.to_string()),
+                            _ => bail!("invalid query parameter"),
+                        }
+                    }
+                    Ok(Self::McpResource {
+                        server_id: server_id.to_string(),
+                        uri: uri.context("Missing resource uri")?,
+                        name: name.context("Missing resource name")?,
+                    })
                 } else {
                     bail!("invalid zed url: {:?}", input);
                 }
@@ -133,6 +154,7 @@ impl MentionUri {
                 path, line_range, ..
             } => selection_name(path, line_range),
             MentionUri::Fetch { url } => url.to_string(),
+            MentionUri::McpResource { name, .. } => name.clone(),
         }
     }
 
@@ -156,6 +178,7 @@ impl MentionUri {
             MentionUri::Rule { .. } => IconName::Reader.path().into(),
             MentionUri::Selection { .. } => IconName::Reader.path().into(),
             MentionUri::Fetch { .. } => IconName::ToolWeb.path().into(),
+            MentionUri::McpResource { .. } => IconName::Server.path().into(),
         }
     }
 
@@ -221,6 +244,18 @@ impl MentionUri {
                 url
             }
             MentionUri::Fetch { url } => url.clone(),
+            MentionUri::McpResource {
+                server_id,
+                uri,
+                name,
+            } => {
+                let mut url = Url::parse("zed:///").unwrap();
+                url.set_path(&format!("/agent/mcp-resource/{server_id}"));
+                url.query_pairs_mut()
+                    .append_pair("uri", uri)
+                    .append_pair("name", name);
+                url
+            }
         }
     }
 }
@@ -233,6 +268,19 @@ impl FromStr for MentionUri {
     }
 }
 
+impl Serialize for MentionUri {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.to_uri().as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for MentionUri {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let uri = String::deserialize(deserializer)?;
+        Self::parse(&uri).map_err(serde::de::Error::custom)
+    }
+}
+
 pub struct MentionLink<'a>(&'a MentionUri);
 
 impl fmt::Display for MentionLink<'_> {
@@ -413,6 +461,26 @@ mod tests {
         assert_eq!(parsed.to_uri().to_string(), https_uri);
     }
 
+    #[test]
+    fn test_parse_mcp_resource_uri() {
+        let resource_uri =
+            "zed:///agent/mcp-resource/github?uri=repo%3A%2F%2Fzed%2Fissues%2F1&name=Issue+1";
+        let parsed = MentionUri::parse(resource_uri).unwrap();
+        match &parsed {
+            MentionUri::McpResource {
+                server_id,
+                uri,
+                name,
+            } => {
+                assert_eq!(server_id, "github");
+                assert_eq!(uri, "repo://zed/issues/1");
+                assert_eq!(name, "Issue 1");
+            }
+            _ => panic!("Expected McpResource variant"),
+        }
+        assert_eq!(parsed.to_uri().to_string(), resource_uri);
+    }
+
     #[test]
     fn test_invalid_scheme() {
         assert!(MentionUri::parse("ftp://example.com").is_err());
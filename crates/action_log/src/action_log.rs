@@ -542,6 +542,22 @@ impl ActionLog {
         tracked_buffer.schedule_diff_update(ChangeAuthor::Agent, cx);
     }
 
+    /// Track a buffer again after the thread that edited it was restored, diffing it against
+    /// the base text of the agent's unreviewed edits, if there were any.
+    pub fn restore_buffer(
+        &mut self,
+        buffer: Entity<Buffer>,
+        diff_base: Option<String>,
+        cx: &mut Context<Self>,
+    ) {
+        let tracked_buffer = self.track_buffer_internal(buffer, false, cx);
+        if let Some(diff_base) = diff_base {
+            tracked_buffer.diff_base = Rope::from(diff_base.as_str());
+            tracked_buffer.schedule_diff_update(ChangeAuthor::Agent, cx);
+        }
+        cx.notify();
+    }
+
     pub fn will_delete_buffer(&mut self, buffer: Entity<Buffer>, cx: &mut Context<Self>) {
         let tracked_buffer = self.track_buffer_internal(buffer.clone(), false, cx);
         match tracked_buffer.status {
@@ -792,6 +808,11 @@ impl ActionLog {
         })
     }
 
+    /// Returns every buffer the model has read, created or edited.
+    pub fn tracked_buffers(&self) -> impl Iterator<Item = &Entity<Buffer>> {
+        self.tracked_buffers.keys()
+    }
+
     /// Returns the set of buffers that contain edits that haven't been reviewed by the user.
     pub fn changed_buffers(&self, cx: &App) -> BTreeMap<Entity<Buffer>, Entity<BufferDiff>> {
         self.tracked_buffers
@@ -801,6 +822,14 @@ impl ActionLog {
             .collect()
     }
 
+    /// Returns the base text that the agent's unreviewed edits to `buffer` are diffed against.
+    pub fn unreviewed_diff_base(&self, buffer: &Entity<Buffer>, cx: &App) -> Option<String> {
+        let tracked_buffer = self.tracked_buffers.get(buffer)?;
+        tracked_buffer
+            .has_edits(cx)
+            .then(|| tracked_buffer.diff_base.to_string())
+    }
+
     /// Iterate over buffers changed since last read or edited by the model
     pub fn stale_buffers<'a>(&'a self, cx: &'a App) -> impl Iterator<Item = &'a Entity<Buffer>> {
         self.tracked_buffers
@@ -2452,6 +2481,55 @@ mod tests {
         assert_eq!(unreviewed_hunks(&action_log, cx), vec![]);
     }
 
+    #[gpui::test]
+    async fn test_restore_buffer(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/dir"), json!({"file": "abc\ndef\nghi"}))
+            .await;
+        let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+        let action_log = cx.new(|_| ActionLog::new(project.clone()));
+        let file_path = project
+            .read_with(cx, |project, cx| project.find_project_path("dir/file", cx))
+            .unwrap();
+        let buffer = project
+            .update(cx, |project, cx| project.open_buffer(file_path, cx))
+            .await
+            .unwrap();
+
+        cx.update(|cx| {
+            action_log.update(cx, |log, cx| log.buffer_read(buffer.clone(), cx));
+            buffer.update(cx, |buffer, cx| {
+                buffer
+                    .edit([(Point::new(1, 0)..Point::new(1, 3), "DEF")], None, cx)
+                    .unwrap()
+            });
+            action_log.update(cx, |log, cx| log.buffer_edited(buffer.clone(), cx));
+        });
+        cx.run_until_parked();
+        let diff_base = action_log.read_with(cx, |log, cx| log.unreviewed_diff_base(&buffer, cx));
+        assert_eq!(diff_base.as_deref(), Some("abc\ndef\nghi"));
+
+        // A new action log picks up the unreviewed edits again.
+        let restored_log = cx.new(|_| ActionLog::new(project.clone()));
+        restored_log.update(cx, |log, cx| {
+            log.restore_buffer(buffer.clone(), diff_base, cx)
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            unreviewed_hunks(&restored_log, cx),
+            vec![(
+                buffer.clone(),
+                vec![HunkStatus {
+                    range: Point::new(1, 0)..Point::new(2, 0),
+                    diff_status: DiffHunkStatusKind::Modified,
+                    old_text: "def\n".into(),
+                }],
+            )]
+        );
+    }
+
     #[derive(Debug, Clone, PartialEq, Eq)]
     struct HunkStatus {
         range: Range<Point>,
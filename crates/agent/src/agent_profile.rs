@@ -45,7 +45,15 @@ impl AgentProfile {
                 .map(|profile| profile.enable_all_context_servers)
                 .unwrap_or_default(),
             context_servers: base_profile
-                .map(|profile| profile.context_servers)
+                .as_ref()
+                .map(|profile| profile.context_servers.clone())
+                .unwrap_or_default(),
+            tool_permissions: base_profile
+                .as_ref()
+                .map(|profile| profile.tool_permissions.clone())
+                .unwrap_or_default(),
+            terminal_sandbox: base_profile
+                .map(|profile| profile.terminal_sandbox)
                 .unwrap_or_default(),
         };
 
@@ -251,6 +259,8 @@ mod tests {
                     tools: IndexMap::default(),
                     enable_all_context_servers: false,
                     context_servers: IndexMap::from_iter([("mcp".into(), context_server_preset())]),
+                    tool_permissions: IndexMap::default(),
+                    terminal_sandbox: Default::default(),
                 },
             );
             AgentSettings::override_global(agent_settings, cx);
@@ -2,6 +2,7 @@ use crate::thread::Thread;
 use assistant_context::AssistantContext;
 use assistant_tool::outline;
 use collections::HashSet;
+use context_server::ContextServerId;
 use futures::future;
 use futures::{FutureExt, future::Shared};
 use gpui::{App, AppContext as _, ElementId, Entity, SharedString, Task};
@@ -32,6 +33,7 @@ pub enum ContextKind {
     TextThread,
     Rules,
     Image,
+    McpResource,
 }
 
 impl ContextKind {
@@ -46,6 +48,7 @@ impl ContextKind {
             ContextKind::TextThread => IconName::TextThread,
             ContextKind::Rules => RULES_ICON,
             ContextKind::Image => IconName::Image,
+            ContextKind::McpResource => IconName::Server,
         }
     }
 }
@@ -66,6 +69,7 @@ pub enum AgentContextHandle {
     TextThread(TextThreadContextHandle),
     Rules(RulesContextHandle),
     Image(ImageContext),
+    McpResource(McpResourceContextHandle),
 }
 
 impl AgentContextHandle {
@@ -80,6 +84,7 @@ impl AgentContextHandle {
             Self::TextThread(context) => context.context_id,
             Self::Rules(context) => context.context_id,
             Self::Image(context) => context.context_id,
+            Self::McpResource(context) => context.context_id,
         }
     }
 
@@ -101,6 +106,7 @@ pub enum AgentContext {
     TextThread(TextThreadContext),
     Rules(RulesContext),
     Image(ImageContext),
+    McpResource(McpResourceContext),
 }
 
 impl AgentContext {
@@ -121,6 +127,9 @@ impl AgentContext {
             }
             AgentContext::Rules(context) => AgentContextHandle::Rules(context.handle.clone()),
             AgentContext::Image(context) => AgentContextHandle::Image(context.clone()),
+            AgentContext::McpResource(context) => {
+                AgentContextHandle::McpResource(context.handle.clone())
+            }
         }
     }
 }
@@ -558,6 +567,71 @@ impl Display for FetchedUrlContext {
     }
 }
 
+/// A resource provided by a context server, which is read when the context is loaded.
+#[derive(Debug, Clone)]
+pub struct McpResourceContextHandle {
+    pub server_id: ContextServerId,
+    pub uri: SharedString,
+    pub name: SharedString,
+    pub context_id: ContextId,
+}
+
+#[derive(Debug, Clone)]
+pub struct McpResourceContext {
+    pub handle: McpResourceContextHandle,
+    pub text: SharedString,
+}
+
+impl McpResourceContextHandle {
+    pub fn eq_for_key(&self, other: &Self) -> bool {
+        self.server_id == other.server_id && self.uri == other.uri
+    }
+
+    pub fn hash_for_key<H: Hasher>(&self, state: &mut H) {
+        self.server_id.hash(state);
+        self.uri.hash(state);
+    }
+
+    pub fn lookup_key(server_id: ContextServerId, uri: SharedString) -> AgentContextKey {
+        AgentContextKey(AgentContextHandle::McpResource(McpResourceContextHandle {
+            server_id,
+            uri,
+            name: "".into(),
+            context_id: ContextId::for_lookup(),
+        }))
+    }
+
+    fn load(
+        self,
+        project: &Entity<Project>,
+        cx: &App,
+    ) -> Task<Option<(AgentContext, Vec<Entity<Buffer>>)>> {
+        let read_task = project
+            .read(cx)
+            .context_server_store()
+            .read(cx)
+            .read_resource(&self.server_id, &self.uri, cx);
+        cx.background_spawn(async move {
+            let text = read_task.await.log_err()?;
+            let context = AgentContext::McpResource(McpResourceContext {
+                handle: self,
+                text: text.into(),
+            });
+            Some((context, vec![]))
+        })
+    }
+}
+
+impl Display for McpResourceContext {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let code_block = MarkdownCodeBlock {
+            tag: &self.handle.uri,
+            text: &self.text,
+        };
+        write!(f, "{code_block}")
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ThreadContextHandle {
     pub thread: Entity<Thread>,
@@ -840,6 +914,7 @@ pub fn load_context(
             AgentContextHandle::TextThread(context) => context.load(cx),
             AgentContextHandle::Rules(context) => context.load(prompt_store, cx),
             AgentContextHandle::Image(context) => context.load(cx),
+            AgentContextHandle::McpResource(context) => context.load(project, cx),
         })
         .collect();
 
@@ -865,6 +940,7 @@ pub fn load_context(
         let mut thread_context = Vec::new();
         let mut text_thread_context = Vec::new();
         let mut rules_context = Vec::new();
+        let mut mcp_resource_context = Vec::new();
         let mut images = Vec::new();
         for context in &contexts {
             match context {
@@ -877,6 +953,7 @@ pub fn load_context(
                 AgentContext::TextThread(context) => text_thread_context.push(context),
                 AgentContext::Rules(context) => rules_context.push(context),
                 AgentContext::Image(context) => images.extend(context.image()),
+                AgentContext::McpResource(context) => mcp_resource_context.push(context),
             }
         }
 
@@ -890,6 +967,7 @@ pub fn load_context(
             && thread_context.is_empty()
             && text_thread_context.is_empty()
             && rules_context.is_empty()
+            && mcp_resource_context.is_empty()
         {
             return ContextLoadResult {
                 loaded_context: LoadedContext {
@@ -952,6 +1030,15 @@ pub fn load_context(
             text.push_str("</fetched_urls>\n");
         }
 
+        if !mcp_resource_context.is_empty() {
+            text.push_str("<mcp_resources>");
+            for context in mcp_resource_context {
+                text.push('\n');
+                let _ = write!(text, "{context}");
+            }
+            text.push_str("</mcp_resources>\n");
+        }
+
         if !thread_context.is_empty() {
             text.push_str("<conversation_threads>");
             for context in thread_context {
@@ -1091,6 +1178,11 @@ impl PartialEq for AgentContextKey {
                     return context.eq_for_key(other_context);
                 }
             }
+            AgentContextHandle::McpResource(context) => {
+                if let AgentContextHandle::McpResource(other_context) = &other.0 {
+                    return context.eq_for_key(other_context);
+                }
+            }
         }
         false
     }
@@ -1108,6 +1200,7 @@ impl Hash for AgentContextKey {
             AgentContextHandle::TextThread(context) => context.hash_for_key(state),
             AgentContextHandle::Rules(context) => context.hash_for_key(state),
             AgentContextHandle::Image(context) => context.hash_for_key(state),
+            AgentContextHandle::McpResource(context) => context.hash_for_key(state),
         }
     }
 }
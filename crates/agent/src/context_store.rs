@@ -1,8 +1,9 @@
 use crate::{
     context::{
         AgentContextHandle, AgentContextKey, ContextId, ContextKind, DirectoryContextHandle,
-        FetchedUrlContext, FileContextHandle, ImageContext, RulesContextHandle,
-        SelectionContextHandle, SymbolContextHandle, TextThreadContextHandle, ThreadContextHandle,
+        FetchedUrlContext, FileContextHandle, ImageContext, McpResourceContextHandle,
+        RulesContextHandle, SelectionContextHandle, SymbolContextHandle, TextThreadContextHandle,
+        ThreadContextHandle,
     },
     thread::{MessageId, Thread, ThreadId},
     thread_store::ThreadStore,
@@ -10,11 +11,16 @@ use crate::{
 use anyhow::{Context as _, Result, anyhow};
 use assistant_context::AssistantContext;
 use collections::{HashSet, IndexSet};
+use context_server::ContextServerId;
 use futures::{self, FutureExt};
-use gpui::{App, Context, Entity, EventEmitter, Image, SharedString, Task, WeakEntity};
+use gpui::{
+    App, Context, Entity, EventEmitter, Image, SharedString, Subscription, Task, WeakEntity,
+};
 use language::{Buffer, File as _};
 use language_model::LanguageModelImage;
-use project::{Project, ProjectItem, ProjectPath, Symbol, image_store::is_image_file};
+use project::{
+    Project, ProjectItem, ProjectPath, Symbol, context_server_store, image_store::is_image_file,
+};
 use prompt_store::UserPromptId;
 use ref_cast::RefCast as _;
 use std::{
@@ -31,6 +37,7 @@ pub struct ContextStore {
     context_set: IndexSet<AgentContextKey>,
     context_thread_ids: HashSet<ThreadId>,
     context_text_thread_paths: HashSet<Arc<Path>>,
+    context_server_subscription: Option<Subscription>,
 }
 
 pub enum ContextStoreEvent {
@@ -51,6 +58,7 @@ impl ContextStore {
             context_set: IndexSet::default(),
             context_thread_ids: HashSet::default(),
             context_text_thread_paths: HashSet::default(),
+            context_server_subscription: None,
         }
     }
 
@@ -59,6 +67,11 @@ impl ContextStore {
     }
 
     pub fn clear(&mut self, cx: &mut Context<Self>) {
+        for context in self.context_set.iter() {
+            if let AgentContextHandle::McpResource(resource) = context.as_ref() {
+                self.unsubscribe_from_resource(resource, cx);
+            }
+        }
         self.context_set.clear();
         self.context_thread_ids.clear();
         cx.notify();
@@ -292,6 +305,35 @@ impl ContextStore {
         context
     }
 
+    /// Adds a resource provided by a context server, and subscribes to its updates.
+    pub fn add_mcp_resource(
+        &mut self,
+        server_id: ContextServerId,
+        uri: SharedString,
+        name: SharedString,
+        remove_if_exists: bool,
+        cx: &mut Context<ContextStore>,
+    ) -> Option<AgentContextHandle> {
+        let context = AgentContextHandle::McpResource(McpResourceContextHandle {
+            server_id,
+            uri,
+            name,
+            context_id: self.next_context_id.post_inc(),
+        });
+
+        if let Some(existing) = self.context_set.get(AgentContextKey::ref_cast(&context)) {
+            if remove_if_exists {
+                self.remove_context(&context, cx);
+                None
+            } else {
+                Some(existing.as_ref().clone())
+            }
+        } else {
+            self.insert_context(context.clone(), cx);
+            Some(context)
+        }
+    }
+
     pub fn add_image_from_path(
         &mut self,
         project_path: ProjectPath,
@@ -426,13 +468,58 @@ impl ContextStore {
             }
             _ => {}
         }
-        let inserted = self.context_set.insert(AgentContextKey(context));
+        let inserted = self.context_set.insert(AgentContextKey(context.clone()));
         if inserted {
+            if let AgentContextHandle::McpResource(resource) = &context {
+                self.subscribe_to_resource(resource, cx);
+            }
             cx.notify();
         }
         inserted
     }
 
+    fn subscribe_to_resource(
+        &mut self,
+        resource: &McpResourceContextHandle,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(project) = self.project.upgrade() else {
+            return;
+        };
+        let context_server_store = project.read(cx).context_server_store();
+        if self.context_server_subscription.is_none() {
+            self.context_server_subscription =
+                Some(cx.subscribe(&context_server_store, |this, _, event, cx| {
+                    if let context_server_store::Event::ResourceUpdated { server_id, uri } = event {
+                        // Resources are read when the context is loaded, so observers only need
+                        // to know that the context changed.
+                        if this.includes_mcp_resource(server_id, uri.clone()) {
+                            cx.notify();
+                        }
+                    }
+                }));
+        }
+        context_server_store.update(cx, |store, cx| {
+            store.subscribe_to_resource(resource.server_id.clone(), resource.uri.to_string(), cx)
+        });
+    }
+
+    fn unsubscribe_from_resource(&self, resource: &McpResourceContextHandle, cx: &mut App) {
+        let Some(project) = self.project.upgrade() else {
+            return;
+        };
+        project
+            .read(cx)
+            .context_server_store()
+            .update(cx, |store, cx| {
+                store.unsubscribe_from_resource(
+                    resource.server_id.clone(),
+                    resource.uri.to_string(),
+                    cx,
+                )
+            });
+    }
+
     pub fn remove_context(&mut self, context: &AgentContextHandle, cx: &mut Context<Self>) {
         if let Some((_, key)) = self
             .context_set
@@ -448,6 +535,9 @@ impl ContextStore {
                         self.context_text_thread_paths.remove(path);
                     }
                 }
+                AgentContextHandle::McpResource(resource) => {
+                    self.unsubscribe_from_resource(resource, cx);
+                }
                 _ => {}
             }
             cx.emit(ContextStoreEvent::ContextRemoved(key));
@@ -531,6 +621,18 @@ impl ContextStore {
             .contains(&FetchedUrlContext::lookup_key(url.into()))
     }
 
+    pub fn includes_mcp_resource(
+        &self,
+        server_id: &ContextServerId,
+        uri: impl Into<SharedString>,
+    ) -> bool {
+        self.context_set
+            .contains(&McpResourceContextHandle::lookup_key(
+                server_id.clone(),
+                uri.into(),
+            ))
+    }
+
     pub fn get_url_context(&self, url: SharedString) -> Option<AgentContextHandle> {
         self.context_set
             .get(&FetchedUrlContext::lookup_key(url))
@@ -551,7 +653,8 @@ impl ContextStore {
                 | AgentContextHandle::Thread(_)
                 | AgentContextHandle::TextThread(_)
                 | AgentContextHandle::Rules(_)
-                | AgentContextHandle::Image(_) => None,
+                | AgentContextHandle::Image(_)
+                | AgentContextHandle::McpResource(_) => None,
             })
             .collect()
     }
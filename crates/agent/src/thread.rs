@@ -9,7 +9,9 @@ use crate::{
     tool_use::{PendingToolUse, ToolUse, ToolUseMetadata, ToolUseState},
 };
 use action_log::ActionLog;
-use agent_settings::{AgentProfileId, AgentSettings, CompletionMode, SUMMARIZE_THREAD_PROMPT};
+use agent_settings::{
+    AgentProfileId, AgentSettings, CompletionMode, SUMMARIZE_THREAD_PROMPT, ThreadProfiles,
+};
 use anyhow::{Result, anyhow};
 use assistant_tool::{AnyToolCard, Tool, ToolWorkingSet};
 use chrono::{DateTime, Utc};
@@ -25,12 +27,12 @@ use gpui::{
 };
 use http_client::StatusCode;
 use language_model::{
-    ConfiguredModel, LanguageModel, LanguageModelCompletionError, LanguageModelCompletionEvent,
-    LanguageModelExt as _, LanguageModelId, LanguageModelRegistry, LanguageModelRequest,
-    LanguageModelRequestMessage, LanguageModelRequestTool, LanguageModelToolResult,
-    LanguageModelToolResultContent, LanguageModelToolUse, LanguageModelToolUseId, MessageContent,
-    ModelRequestLimitReachedError, PaymentRequiredError, Role, SelectedModel, StopReason,
-    TokenUsage,
+    BudgetExceeded, ConfiguredModel, LanguageModel, LanguageModelCompletionError,
+    LanguageModelCompletionEvent, LanguageModelExt as _, LanguageModelId, LanguageModelRegistry,
+    LanguageModelRequest, LanguageModelRequestMessage, LanguageModelRequestTool,
+    LanguageModelToolResult, LanguageModelToolResultContent, LanguageModelToolUse,
+    LanguageModelToolUseId, MessageContent, ModelRequestLimitReachedError, PaymentRequiredError,
+    Role, SelectedModel, StopReason, TokenUsage, UsageLedger, UsageRecord, UsageSource,
 };
 use postage::stream::Stream as _;
 use project::{
@@ -1692,14 +1694,15 @@ impl Thread {
         self.last_received_chunk_at = Some(Instant::now());
 
         let task = cx.spawn(async move |thread, cx| {
-            let stream_completion_future = model.stream_completion(request, &cx);
             let initial_token_usage =
                 thread.read_with(cx, |thread, _cx| thread.cumulative_token_usage);
+            // Declared outside of the stream so that the usage of failed requests is still recorded.
+            let mut current_token_usage = TokenUsage::default();
             let stream_completion = async {
-                let mut events = stream_completion_future.await?;
+                cx.update(|cx| UsageLedger::check_global_budget(cx))??;
+                let mut events = model.stream_completion(request, &cx).await?;
 
                 let mut stop_reason = StopReason::EndTurn;
-                let mut current_token_usage = TokenUsage::default();
 
                 thread
                     .update(cx, |_thread, cx| {
@@ -1937,6 +1940,18 @@ impl Thread {
 
             thread
                 .update(cx, |thread, cx| {
+                    if current_token_usage != TokenUsage::default() {
+                        UsageLedger::record_global(
+                            UsageRecord {
+                                source: UsageSource::AgentThread,
+                                thread_id: Some(thread.id.to_string()),
+                                provider: model.provider_id(),
+                                model: model.id(),
+                                usage: current_token_usage,
+                            },
+                            cx,
+                        );
+                    }
                     thread.finalize_pending_checkpoint(cx);
                     match result.as_ref() {
                         Ok(stop_reason) => {
@@ -2006,6 +2021,11 @@ impl Thread {
 
                             if error.is::<PaymentRequiredError>() {
                                 cx.emit(ThreadEvent::ShowError(ThreadError::PaymentRequired));
+                            } else if let Some(error) = error.downcast_ref::<BudgetExceeded>() {
+                                cx.emit(ThreadEvent::ShowError(ThreadError::Message {
+                                    header: "Budget exceeded".into(),
+                                    message: error.to_string().into(),
+                                }));
                             } else if let Some(error) =
                                 error.downcast_ref::<ModelRequestLimitReachedError>()
                             {
@@ -2696,6 +2716,8 @@ impl Thread {
     ) -> Task<()> {
         let tool_name: Arc<str> = tool.name().into();
 
+        // Let tools that depend on the profile, like the terminal tool, find this thread's.
+        ThreadProfiles::set(self.id.to_string(), self.profile.id().clone(), cx);
         let tool_result = tool.run(
             input,
             request,
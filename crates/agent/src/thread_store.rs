@@ -563,6 +563,7 @@ impl ThreadStore {
                     }
                 }
             }
+            _ => {}
         }
     }
 
@@ -1,16 +1,17 @@
 use crate::{
-    AgentResponseEvent, ContextServerRegistry, CopyPathTool, CreateDirectoryTool, DeletePathTool,
-    DiagnosticsTool, EditFileTool, FetchTool, FindPathTool, GrepTool, ListDirectoryTool,
-    MovePathTool, NowTool, OpenTool, ReadFileTool, TerminalTool, ThinkingTool, Thread,
-    ToolCallAuthorization, UserMessageContent, WebSearchTool, templates::Templates,
+    AgentResponseEvent, ContextServerRegistry, DbThread, DbThreadMetadata, Thread,
+    ThreadExportFormat, ThreadFilter, ThreadId, ThreadsDatabase, ToolCallAuthorization,
+    UserMessageContent, templates::Templates,
 };
 use acp_thread::AgentModelSelector;
 use agent_client_protocol as acp;
 use agent_settings::AgentSettings;
 use anyhow::{Context as _, Result, anyhow};
+use chrono::Utc;
 use collections::{HashSet, IndexMap};
 use fs::Fs;
 use futures::channel::mpsc;
+use futures::future::Shared;
 use futures::{StreamExt, future};
 use gpui::{
     App, AppContext, AsyncApp, Context, Entity, SharedString, Subscription, Task, WeakEntity,
@@ -77,8 +78,8 @@ impl LanguageModels {
     }
 
     fn refresh_list(&mut self, cx: &App) {
-        let providers = LanguageModelRegistry::global(cx)
-            .read(cx)
+        let registry = LanguageModelRegistry::read_global(cx);
+        let providers = registry
             .providers()
             .into_iter()
             .filter(|provider| provider.is_authenticated(cx))
@@ -110,7 +111,7 @@ impl LanguageModels {
                 if !recommended_models.contains(&model.id()) {
                     provider_models.push(model_info);
                 }
-                models.insert(model_id, model);
+                models.insert(model_id, registry.with_request_cache(model));
             }
             if !provider_models.is_empty() {
                 language_model_list.insert(
@@ -167,6 +168,7 @@ pub struct NativeAgent {
     project: Entity<Project>,
     prompt_store: Option<Entity<PromptStore>>,
     fs: Arc<dyn Fs>,
+    thread_database: Shared<Task<Result<Arc<ThreadsDatabase>, Arc<anyhow::Error>>>>,
     _subscriptions: Vec<Subscription>,
 }
 
@@ -213,6 +215,7 @@ impl NativeAgent {
                 project,
                 prompt_store,
                 fs,
+                thread_database: ThreadsDatabase::connect(cx),
                 _subscriptions: subscriptions,
             }
         })
@@ -222,6 +225,20 @@ impl NativeAgent {
         &self.models
     }
 
+    fn save_thread(&self, thread: &Entity<Thread>, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let thread = thread.read(cx);
+        if thread.messages().is_empty() {
+            return Task::ready(Ok(()));
+        }
+        let id = thread.id().clone();
+        let saved_thread = thread.to_db(cx);
+        let database = self.thread_database.clone();
+        cx.background_spawn(async move {
+            let database = database.await.map_err(|error| anyhow!(error))?;
+            database.save_thread(id, saved_thread).await
+        })
+    }
+
     async fn maintain_project_context(
         this: WeakEntity<Self>,
         mut needs_refresh: watch::Receiver<()>,
@@ -239,7 +256,7 @@ impl NativeAgent {
         Ok(())
     }
 
-    fn build_project_context(
+    pub(crate) fn build_project_context(
         project: &Entity<Project>,
         prompt_store: Option<&Entity<PromptStore>>,
         cx: &mut App,
@@ -449,6 +466,201 @@ impl NativeAgentConnection {
             .map(|session| session.thread.clone())
     }
 
+    /// Creates a session for a new thread, or for a thread that was saved earlier.
+    fn create_session(
+        self: Rc<Self>,
+        project: Entity<Project>,
+        saved_thread: Option<(ThreadId, DbThread)>,
+        cx: &mut App,
+    ) -> Task<Result<Entity<acp_thread::AcpThread>>> {
+        let agent = self.0.clone();
+
+        cx.spawn(async move |cx| {
+            log::debug!("Starting thread creation in async context");
+
+            // Generate session ID
+            let session_id = acp::SessionId(uuid::Uuid::new_v4().to_string().into());
+            log::info!("Created session with ID: {}", session_id);
+
+            // Create AcpThread
+            let title = saved_thread
+                .as_ref()
+                .map_or_else(|| "agent2".into(), |(_, thread)| thread.title.clone());
+            let acp_thread = cx.update(|cx| {
+                cx.new(|cx| {
+                    acp_thread::AcpThread::new(
+                        title,
+                        self.clone(),
+                        project.clone(),
+                        session_id.clone(),
+                        cx,
+                    )
+                })
+            })?;
+            let action_log = cx.update(|cx| acp_thread.read(cx).action_log().clone())?;
+
+            // Create Thread
+            let thread = agent.update(
+                cx,
+                |agent, cx: &mut gpui::Context<NativeAgent>| -> Result<_> {
+                    // Fetch default model from registry settings
+                    let registry = LanguageModelRegistry::read_global(cx);
+
+                    // Log available models for debugging
+                    let available_count = registry.available_models(cx).count();
+                    log::debug!("Total available models: {}", available_count);
+
+                    // Prefer the model a saved thread was using, if it's still available.
+                    let saved_model = saved_thread
+                        .as_ref()
+                        .and_then(|(_, thread)| thread.model.as_ref())
+                        .and_then(|model| {
+                            agent
+                                .models
+                                .model_from_id(&acp_thread::AgentModelId(model.to_string().into()))
+                        });
+                    let default_model = saved_model
+                        .or_else(|| {
+                            registry.default_model().and_then(|default_model| {
+                                agent
+                                    .models
+                                    .model_from_id(&LanguageModels::model_id(&default_model.model))
+                            })
+                        })
+                        .ok_or_else(|| {
+                            log::warn!("No default model configured in settings");
+                            anyhow!(
+                                "No default model. Please configure a default model in settings."
+                            )
+                        })?;
+
+                    let thread = cx.new(|cx| {
+                        let mut thread = if let Some((id, saved_thread)) = saved_thread {
+                            Thread::from_db(
+                                id,
+                                saved_thread,
+                                project.clone(),
+                                agent.project_context.clone(),
+                                agent.context_server_registry.clone(),
+                                action_log.clone(),
+                                agent.templates.clone(),
+                                default_model,
+                                cx,
+                            )
+                        } else {
+                            Thread::new(
+                                project.clone(),
+                                agent.project_context.clone(),
+                                agent.context_server_registry.clone(),
+                                action_log.clone(),
+                                agent.templates.clone(),
+                                default_model,
+                                cx,
+                            )
+                        };
+                        thread.add_default_tools(cx);
+                        thread
+                    });
+
+                    Ok(thread)
+                },
+            )??;
+
+            // Show the messages of a saved thread
+            thread.update(cx, |thread, cx| {
+                acp_thread.update(cx, |acp_thread, cx| thread.replay(acp_thread, cx))
+            })??;
+
+            // Store the session
+            agent.update(cx, |agent, cx| {
+                agent.sessions.insert(
+                    session_id,
+                    Session {
+                        thread,
+                        acp_thread: acp_thread.downgrade(),
+                        _subscription: cx.observe_release(&acp_thread, |this, acp_thread, _cx| {
+                            this.sessions.remove(acp_thread.session_id());
+                        }),
+                    },
+                );
+            })?;
+
+            Ok(acp_thread)
+        })
+    }
+
+    /// Resumes a thread that was saved earlier, in a new session.
+    pub fn open_thread(
+        self: Rc<Self>,
+        id: ThreadId,
+        project: Entity<Project>,
+        cx: &mut App,
+    ) -> Task<Result<Entity<acp_thread::AcpThread>>> {
+        let database = self.0.read(cx).thread_database.clone();
+        cx.spawn(async move |cx| {
+            let database = database.await.map_err(|error| anyhow!(error))?;
+            let saved_thread = database
+                .load_thread(id.clone())
+                .await?
+                .with_context(|| format!("thread {id} not found"))?;
+            cx.update(|cx| self.create_session(project, Some((id, saved_thread)), cx))?
+                .await
+        })
+    }
+
+    /// Saves a thread exported with [`Self::export_thread`] and resumes it in a new session.
+    pub fn import_thread(
+        self: Rc<Self>,
+        text: String,
+        format: ThreadExportFormat,
+        project: Entity<Project>,
+        cx: &mut App,
+    ) -> Task<Result<Entity<acp_thread::AcpThread>>> {
+        let database = self.0.read(cx).thread_database.clone();
+        cx.spawn(async move |cx| {
+            let mut saved_thread = DbThread::import(&text, format)?;
+            saved_thread.updated_at = Utc::now();
+            let id = ThreadId::new();
+            let database = database.await.map_err(|error| anyhow!(error))?;
+            database
+                .save_thread(id.clone(), saved_thread.clone())
+                .await?;
+            cx.update(|cx| self.create_session(project, Some((id, saved_thread)), cx))?
+                .await
+        })
+    }
+
+    pub fn export_thread(
+        &self,
+        session_id: &acp::SessionId,
+        format: ThreadExportFormat,
+        cx: &App,
+    ) -> Result<String> {
+        let thread = self.thread(session_id, cx).context("Session not found")?;
+        thread.read(cx).to_db(cx).export(format)
+    }
+
+    /// Returns the saved threads matching `filter`, most recently updated first.
+    pub fn search_threads(
+        &self,
+        filter: ThreadFilter,
+        cx: &App,
+    ) -> Task<Result<Vec<DbThreadMetadata>>> {
+        let database = self.0.read(cx).thread_database.clone();
+        cx.background_spawn(async move {
+            let database = database.await.map_err(|error| anyhow!(error))?;
+            database.search_threads(filter).await
+        })
+    }
+
+    pub fn delete_thread(&self, id: ThreadId, cx: &App) -> Task<Result<()>> {
+        let database = self.0.read(cx).thread_database.clone();
+        cx.background_spawn(async move {
+            let database = database.await.map_err(|error| anyhow!(error))?;
+            database.delete_thread(id).await
+        })
+    }
+
     fn run_turn(
         &self,
         session_id: acp::SessionId,
@@ -469,92 +681,108 @@ impl NativeAgentConnection {
         };
         log::debug!("Found session for: {}", session_id);
 
-        let mut response_stream = match f(thread, cx) {
+        let mut response_stream = match f(thread.clone(), cx) {
             Ok(stream) => stream,
             Err(err) => return Task::ready(Err(err)),
         };
+        let agent = self.0.clone();
         cx.spawn(async move |cx| {
-            // Handle response stream and forward to session.acp_thread
-            while let Some(result) = response_stream.next().await {
-                match result {
-                    Ok(event) => {
-                        log::trace!("Received completion event: {:?}", event);
-
-                        match event {
-                            AgentResponseEvent::Text(text) => {
-                                acp_thread.update(cx, |thread, cx| {
-                                    thread.push_assistant_content_block(
-                                        acp::ContentBlock::Text(acp::TextContent {
-                                            text,
-                                            annotations: None,
-                                        }),
-                                        false,
-                                        cx,
-                                    )
-                                })?;
-                            }
-                            AgentResponseEvent::Thinking(text) => {
-                                acp_thread.update(cx, |thread, cx| {
-                                    thread.push_assistant_content_block(
-                                        acp::ContentBlock::Text(acp::TextContent {
-                                            text,
-                                            annotations: None,
-                                        }),
-                                        true,
-                                        cx,
-                                    )
-                                })?;
-                            }
-                            AgentResponseEvent::ToolCallAuthorization(ToolCallAuthorization {
-                                tool_call,
-                                options,
-                                response,
-                            }) => {
-                                let recv = acp_thread.update(cx, |thread, cx| {
-                                    thread.request_tool_call_authorization(tool_call, options, cx)
-                                })?;
-                                cx.background_spawn(async move {
-                                    if let Some(recv) = recv.log_err()
-                                        && let Some(option) = recv
-                                            .await
-                                            .context("authorization sender was dropped")
-                                            .log_err()
-                                    {
-                                        response
-                                            .send(option)
-                                            .map(|_| anyhow!("authorization receiver was dropped"))
-                                            .log_err();
-                                    }
-                                })
-                                .detach();
-                            }
-                            AgentResponseEvent::ToolCall(tool_call) => {
-                                acp_thread.update(cx, |thread, cx| {
-                                    thread.upsert_tool_call(tool_call, cx)
-                                })??;
-                            }
-                            AgentResponseEvent::ToolCallUpdate(update) => {
-                                acp_thread.update(cx, |thread, cx| {
-                                    thread.update_tool_call(update, cx)
-                                })??;
-                            }
-                            AgentResponseEvent::Stop(stop_reason) => {
-                                log::debug!("Assistant message complete: {:?}", stop_reason);
-                                return Ok(acp::PromptResponse { stop_reason });
+            let response = async {
+                // Handle response stream and forward to session.acp_thread
+                while let Some(result) = response_stream.next().await {
+                    match result {
+                        Ok(event) => {
+                            log::trace!("Received completion event: {:?}", event);
+
+                            match event {
+                                AgentResponseEvent::Text(text) => {
+                                    acp_thread.update(cx, |thread, cx| {
+                                        thread.push_assistant_content_block(
+                                            acp::ContentBlock::Text(acp::TextContent {
+                                                text,
+                                                annotations: None,
+                                            }),
+                                            false,
+                                            cx,
+                                        )
+                                    })?;
+                                }
+                                AgentResponseEvent::Thinking(text) => {
+                                    acp_thread.update(cx, |thread, cx| {
+                                        thread.push_assistant_content_block(
+                                            acp::ContentBlock::Text(acp::TextContent {
+                                                text,
+                                                annotations: None,
+                                            }),
+                                            true,
+                                            cx,
+                                        )
+                                    })?;
+                                }
+                                AgentResponseEvent::ToolCallAuthorization(
+                                    ToolCallAuthorization {
+                                        tool_call,
+                                        options,
+                                        response,
+                                    },
+                                ) => {
+                                    let recv = acp_thread.update(cx, |thread, cx| {
+                                        thread
+                                            .request_tool_call_authorization(tool_call, options, cx)
+                                    })?;
+                                    cx.background_spawn(async move {
+                                        if let Some(recv) = recv.log_err()
+                                            && let Some(option) = recv
+                                                .await
+                                                .context("authorization sender was dropped")
+                                                .log_err()
+                                        {
+                                            response
+                                                .send(option)
+                                                .map(|_| {
+                                                    anyhow!("authorization receiver was dropped")
+                                                })
+                                                .log_err();
+                                        }
+                                    })
+                                    .detach();
+                                }
+                                AgentResponseEvent::ToolCall(tool_call) => {
+                                    acp_thread.update(cx, |thread, cx| {
+                                        thread.upsert_tool_call(tool_call, cx)
+                                    })??;
+                                }
+                                AgentResponseEvent::ToolCallUpdate(update) => {
+                                    acp_thread.update(cx, |thread, cx| {
+                                        thread.update_tool_call(update, cx)
+                                    })??;
+                                }
+                                AgentResponseEvent::Stop(stop_reason) => {
+                                    log::debug!("Assistant message complete: {:?}", stop_reason);
+                                    return Ok(acp::PromptResponse { stop_reason });
+                                }
                             }
                         }
-                    }
-                    Err(e) => {
-                        log::error!("Error in model response stream: {:?}", e);
-                        return Err(e);
+                        Err(e) => {
+                            log::error!("Error in model response stream: {:?}", e);
+                            return Err(e);
+                        }
                     }
                 }
+
+                log::info!("Response stream completed");
+                anyhow::Ok(acp::PromptResponse {
+                    stop_reason: acp::StopReason::EndTurn,
+                })
             }
+            .await;
 
-            log::info!("Response stream completed");
-            anyhow::Ok(acp::PromptResponse {
-                stop_reason: acp::StopReason::EndTurn,
-            })
+            // Save the thread whenever a turn ends, so it can be resumed after a restart.
+            agent
+                .update(cx, |agent, cx| agent.save_thread(&thread, cx))?
+                .await
+                .log_err();
+            response
         })
     }
 }
@@ -644,104 +872,8 @@ impl acp_thread::AgentConnection for NativeAgentConnection {
         cwd: &Path,
         cx: &mut App,
     ) -> Task<Result<Entity<acp_thread::AcpThread>>> {
-        let agent = self.0.clone();
         log::info!("Creating new thread for project at: {:?}", cwd);
-
-        cx.spawn(async move |cx| {
-            log::debug!("Starting thread creation in async context");
-
-            // Generate session ID
-            let session_id = acp::SessionId(uuid::Uuid::new_v4().to_string().into());
-            log::info!("Created session with ID: {}", session_id);
-
-            // Create AcpThread
-            let acp_thread = cx.update(|cx| {
-                cx.new(|cx| {
-                    acp_thread::AcpThread::new(
-                        "agent2",
-                        self.clone(),
-                        project.clone(),
-                        session_id.clone(),
-                        cx,
-                    )
-                })
-            })?;
-            let action_log = cx.update(|cx| acp_thread.read(cx).action_log().clone())?;
-
-            // Create Thread
-            let thread = agent.update(
-                cx,
-                |agent, cx: &mut gpui::Context<NativeAgent>| -> Result<_> {
-                    // Fetch default model from registry settings
-                    let registry = LanguageModelRegistry::read_global(cx);
-
-                    // Log available models for debugging
-                    let available_count = registry.available_models(cx).count();
-                    log::debug!("Total available models: {}", available_count);
-
-                    let default_model = registry
-                        .default_model()
-                        .and_then(|default_model| {
-                            agent
-                                .models
-                                .model_from_id(&LanguageModels::model_id(&default_model.model))
-                        })
-                        .ok_or_else(|| {
-                            log::warn!("No default model configured in settings");
-                            anyhow!(
-                                "No default model. Please configure a default model in settings."
-                            )
-                        })?;
-
-                    let thread = cx.new(|cx| {
-                        let mut thread = Thread::new(
-                            project.clone(),
-                            agent.project_context.clone(),
-                            agent.context_server_registry.clone(),
-                            action_log.clone(),
-                            agent.templates.clone(),
-                            default_model,
-                            cx,
-                        );
-                        thread.add_tool(CopyPathTool::new(project.clone()));
-                        thread.add_tool(CreateDirectoryTool::new(project.clone()));
-                        thread.add_tool(DeletePathTool::new(project.clone(), action_log.clone()));
-                        thread.add_tool(DiagnosticsTool::new(project.clone()));
-                        thread.add_tool(EditFileTool::new(cx.entity()));
-                        thread.add_tool(FetchTool::new(project.read(cx).client().http_client()));
-                        thread.add_tool(FindPathTool::new(project.clone()));
-                        thread.add_tool(GrepTool::new(project.clone()));
-                        thread.add_tool(ListDirectoryTool::new(project.clone()));
-                        thread.add_tool(MovePathTool::new(project.clone()));
-                        thread.add_tool(NowTool);
-                        thread.add_tool(OpenTool::new(project.clone()));
-                        thread.add_tool(ReadFileTool::new(project.clone(), action_log));
-                        thread.add_tool(TerminalTool::new(project.clone(), cx));
-                        thread.add_tool(ThinkingTool);
-                        thread.add_tool(WebSearchTool); // TODO: Enable this only if it's a zed model.
-                        thread
-                    });
-
-                    Ok(thread)
-                },
-            )??;
-
-            // Store the session
-            agent.update(cx, |agent, cx| {
-                agent.sessions.insert(
-                    session_id,
-                    Session {
-                        thread,
-                        acp_thread: acp_thread.downgrade(),
-                        _subscription: cx.observe_release(&acp_thread, |this, acp_thread, _cx| {
-                            this.sessions.remove(acp_thread.session_id());
-                        }),
-                    },
-                );
-            })?;
-
-            Ok(acp_thread)
-        })
+        self.create_session(project, None, cx)
     }
 
     fn auth_methods(&self) -> &[acp::AuthMethod] {
@@ -1,4 +1,6 @@
 mod agent;
+mod db;
+mod headless;
 mod native_agent_server;
 mod templates;
 mod thread;
@@ -8,6 +10,8 @@ mod tools;
 mod tests;
 
 pub use agent::*;
+pub use db::*;
+pub use headless::*;
 pub use native_agent_server::NativeAgentServer;
 pub use templates::*;
 pub use thread::*;
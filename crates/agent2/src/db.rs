@@ -0,0 +1,758 @@
+use crate::{
+    AgentMessage, AgentMessageContent, CompactedMessage, Message, ThreadId, UserMessage,
+    UserMessageContent,
+};
+use acp_thread::UserMessageId;
+use agent_settings::{AgentProfileId, CompletionMode};
+use anyhow::{Context as _, Result, anyhow};
+use chrono::{DateTime, Utc};
+use futures::{FutureExt as _, future::Shared};
+use gpui::{App, BackgroundExecutor, Global, SharedString, Task};
+use indoc::indoc;
+use language_model::{LanguageModelToolResultContent, TokenUsage};
+use serde::{Deserialize, Serialize};
+use sqlez::{
+    bindable::{Bind, Column},
+    connection::Connection,
+    statement::Statement,
+};
+use std::{
+    fmt::Write as _,
+    path::{Path, PathBuf},
+    sync::{Arc, LazyLock, Mutex},
+};
+
+static ZED_STATELESS: LazyLock<bool> =
+    LazyLock::new(|| std::env::var("ZED_STATELESS").map_or(false, |v| !v.is_empty()));
+
+/// The title of threads that don't have a user message yet.
+const DEFAULT_TITLE: &str = "New Thread";
+/// How many characters of the first user message make up a thread's title.
+const MAX_TITLE_LEN: usize = 80;
+
+/// A native agent thread, as it gets persisted and exported.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DbThread {
+    pub title: SharedString,
+    pub messages: Vec<Message>,
+    pub updated_at: DateTime<Utc>,
+    /// The absolute paths of the project's worktrees when the thread was saved.
+    #[serde(default)]
+    pub worktree_paths: Vec<PathBuf>,
+    #[serde(default)]
+    pub model: Option<DbLanguageModel>,
+    #[serde(default)]
+    pub profile: Option<AgentProfileId>,
+    #[serde(default)]
+    pub completion_mode: Option<CompletionMode>,
+    #[serde(default)]
+    pub latest_token_usage: Option<TokenUsage>,
+    /// The files the agent read or edited, so its action log can keep tracking them.
+    #[serde(default)]
+    pub tracked_buffers: Vec<DbTrackedBuffer>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DbLanguageModel {
+    pub provider: String,
+    pub model: String,
+}
+
+impl std::fmt::Display for DbLanguageModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.provider, self.model)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DbTrackedBuffer {
+    pub path: PathBuf,
+    /// The base text of the agent's unreviewed edits to this file, if there are any.
+    pub diff_base: Option<String>,
+}
+
+/// The format that threads are exported to and imported from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThreadExportFormat {
+    /// Preserves everything, so the thread can be resumed after importing it.
+    Json,
+    /// A readable transcript. Importing one only restores the text of its messages.
+    Markdown,
+}
+
+impl ThreadExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ThreadExportFormat::Json => "json",
+            ThreadExportFormat::Markdown => "md",
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("md" | "markdown") => ThreadExportFormat::Markdown,
+            _ => ThreadExportFormat::Json,
+        }
+    }
+}
+
+impl DbThread {
+    pub fn export(&self, format: ThreadExportFormat) -> Result<String> {
+        match format {
+            ThreadExportFormat::Json => Ok(serde_json::to_string_pretty(self)?),
+            ThreadExportFormat::Markdown => Ok(self.to_markdown()),
+        }
+    }
+
+    pub fn import(text: &str, format: ThreadExportFormat) -> Result<Self> {
+        match format {
+            ThreadExportFormat::Json => {
+                serde_json::from_str(text).context("failed to parse exported thread")
+            }
+            ThreadExportFormat::Markdown => Self::from_markdown(text),
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!("# {}\n\n", self.title);
+        for (ix, message) in self.messages.iter().enumerate() {
+            if ix > 0 {
+                markdown.push('\n');
+            }
+            markdown.push_str(&message.to_markdown());
+        }
+        markdown
+    }
+
+    /// Parses a transcript exported by [`Self::to_markdown`]. Tool calls and their results are
+    /// kept as part of the assistant's text.
+    pub fn from_markdown(markdown: &str) -> Result<Self> {
+        enum Section {
+            User,
+            Assistant,
+            Summary,
+        }
+
+        fn push_message(messages: &mut Vec<Message>, section: Option<Section>, text: &str) {
+            let text = text.trim().to_string();
+            let message = match section {
+                Some(Section::User) => Message::User(UserMessage {
+                    id: UserMessageId::new(),
+                    content: vec![UserMessageContent::Text(text)],
+                }),
+                Some(Section::Assistant) => Message::Agent(AgentMessage {
+                    content: vec![AgentMessageContent::Text(text)],
+                    tool_results: Default::default(),
+                }),
+                Some(Section::Summary) => Message::Compacted(CompactedMessage {
+                    summary: text,
+                    touched_paths: Vec::new(),
+                    tool_results: Vec::new(),
+                }),
+                None => return,
+            };
+            messages.push(message);
+        }
+
+        let mut title = None;
+        let mut messages = Vec::new();
+        let mut section = None;
+        let mut text = String::new();
+        let mut in_code_block = false;
+        for line in markdown.lines() {
+            if line.trim_start().starts_with("```") {
+                in_code_block = !in_code_block;
+            }
+            let next_section = match line.trim_end() {
+                _ if in_code_block => None,
+                "## User" => Some(Section::User),
+                "## Assistant" => Some(Section::Assistant),
+                "## Summary" => Some(Section::Summary),
+                _ => None,
+            };
+            if let Some(next_section) = next_section {
+                push_message(&mut messages, section.replace(next_section), &text);
+                text.clear();
+            } else if section.is_none() {
+                if let Some(heading) = line.strip_prefix("# ") {
+                    title.get_or_insert_with(|| heading.trim().to_string());
+                }
+            } else {
+                text.push_str(line);
+                text.push('\n');
+            }
+        }
+        push_message(&mut messages, section, &text);
+        anyhow::ensure!(
+            !messages.is_empty(),
+            "the transcript doesn't contain any messages"
+        );
+
+        Ok(Self {
+            title: title
+                .map(SharedString::from)
+                .unwrap_or_else(|| title_for_messages(&messages)),
+            messages,
+            updated_at: Utc::now(),
+            worktree_paths: Vec::new(),
+            model: None,
+            profile: None,
+            completion_mode: None,
+            latest_token_usage: None,
+            tracked_buffers: Vec::new(),
+        })
+    }
+
+    /// The text that the thread can be found by: its messages and tool calls.
+    fn search_text(&self) -> String {
+        let mut text = String::new();
+        for message in &self.messages {
+            match message {
+                Message::User(message) => {
+                    for content in &message.content {
+                        match content {
+                            UserMessageContent::Text(content) => {
+                                writeln!(text, "{content}").ok();
+                            }
+                            UserMessageContent::Mention { uri, .. } => {
+                                writeln!(text, "{}", uri.name()).ok();
+                            }
+                            UserMessageContent::Image(_) => {}
+                        }
+                    }
+                }
+                Message::Agent(message) => {
+                    for content in &message.content {
+                        match content {
+                            AgentMessageContent::Text(content)
+                            | AgentMessageContent::Thinking { text: content, .. } => {
+                                writeln!(text, "{content}").ok();
+                            }
+                            AgentMessageContent::ToolUse(tool_use) => {
+                                writeln!(text, "{} {}", tool_use.name, tool_use.input).ok();
+                            }
+                            AgentMessageContent::RedactedThinking(_)
+                            | AgentMessageContent::Image(_) => {}
+                        }
+                    }
+                    for tool_result in message.tool_results.values() {
+                        if let LanguageModelToolResultContent::Text(content) = &tool_result.content
+                        {
+                            writeln!(text, "{content}").ok();
+                        }
+                    }
+                }
+                Message::Compacted(message) => {
+                    writeln!(text, "{}", message.summary).ok();
+                }
+                Message::Resume => {}
+            }
+        }
+        text
+    }
+}
+
+/// Names a thread after the start of its first user message.
+pub fn title_for_messages(messages: &[Message]) -> SharedString {
+    let text = messages.iter().find_map(|message| {
+        let Message::User(message) = message else {
+            return None;
+        };
+        message.content.iter().find_map(|content| match content {
+            UserMessageContent::Text(text) if !text.trim().is_empty() => Some(text.trim()),
+            _ => None,
+        })
+    });
+    let Some(text) = text else {
+        return DEFAULT_TITLE.into();
+    };
+
+    let first_line = text.lines().next().unwrap_or_default();
+    if first_line.chars().count() > MAX_TITLE_LEN {
+        let mut title = first_line.chars().take(MAX_TITLE_LEN).collect::<String>();
+        title.push('…');
+        title.into()
+    } else {
+        first_line.to_string().into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DbThreadMetadata {
+    pub id: ThreadId,
+    pub title: SharedString,
+    pub updated_at: DateTime<Utc>,
+    pub model: Option<String>,
+    pub worktree_paths: Vec<PathBuf>,
+}
+
+/// Narrows down the threads returned by [`ThreadsDatabase::search_threads`].
+#[derive(Debug, Clone, Default)]
+pub struct ThreadFilter {
+    /// Words that must all appear in the thread's messages or tool calls.
+    pub query: Option<String>,
+    /// Only include threads from a project with this worktree.
+    pub worktree_path: Option<PathBuf>,
+    /// Only include threads using this model, formatted as `provider/model`.
+    pub model: Option<String>,
+    pub updated_after: Option<DateTime<Utc>>,
+    pub updated_before: Option<DateTime<Utc>>,
+}
+
+impl ThreadFilter {
+    /// Quotes every word of the query, so punctuation isn't interpreted as FTS5 syntax, and
+    /// matches them as prefixes.
+    fn fts_query(&self) -> Option<String> {
+        let query = self
+            .query
+            .as_deref()?
+            .split_whitespace()
+            .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+            .collect::<Vec<_>>()
+            .join(" ");
+        (!query.is_empty()).then_some(query)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DataType {
+    Json,
+    Zstd,
+}
+
+impl Bind for DataType {
+    fn bind(&self, statement: &Statement, start_index: i32) -> Result<i32> {
+        let value = match self {
+            DataType::Json => "json",
+            DataType::Zstd => "zstd",
+        };
+        value.bind(statement, start_index)
+    }
+}
+
+impl Column for DataType {
+    fn column(statement: &mut Statement, start_index: i32) -> Result<(Self, i32)> {
+        let (value, next_index) = String::column(statement, start_index)?;
+        let data_type = match value.as_str() {
+            "json" => DataType::Json,
+            "zstd" => DataType::Zstd,
+            _ => anyhow::bail!("Unknown data type: {}", value),
+        };
+        Ok((data_type, next_index))
+    }
+}
+
+impl Bind for ThreadId {
+    fn bind(&self, statement: &Statement, start_index: i32) -> Result<i32> {
+        self.to_string().bind(statement, start_index)
+    }
+}
+
+impl Column for ThreadId {
+    fn column(statement: &mut Statement, start_index: i32) -> Result<(Self, i32)> {
+        let (id, next_index) = String::column(statement, start_index)?;
+        Ok((ThreadId::from(id.as_str()), next_index))
+    }
+}
+
+struct GlobalThreadsDatabase(Shared<Task<Result<Arc<ThreadsDatabase>, Arc<anyhow::Error>>>>);
+
+impl Global for GlobalThreadsDatabase {}
+
+/// Persists native agent threads in SQLite, with a full-text index over their messages.
+pub struct ThreadsDatabase {
+    executor: BackgroundExecutor,
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl ThreadsDatabase {
+    const COMPRESSION_LEVEL: i32 = 3;
+
+    /// Returns the database shared by every native agent, opening it the first time.
+    pub fn connect(cx: &mut App) -> Shared<Task<Result<Arc<ThreadsDatabase>, Arc<anyhow::Error>>>> {
+        if let Some(database) = cx.try_global::<GlobalThreadsDatabase>() {
+            return database.0.clone();
+        }
+
+        let executor = cx.background_executor().clone();
+        let database = executor
+            .spawn({
+                let executor = executor.clone();
+                async move {
+                    let path = if *ZED_STATELESS || cfg!(test) {
+                        None
+                    } else {
+                        Some(paths::data_dir().join("threads").join("native-threads.db"))
+                    };
+                    Self::new(path, executor).map(Arc::new).map_err(Arc::new)
+                }
+            })
+            .shared();
+        cx.set_global(GlobalThreadsDatabase(database.clone()));
+        database
+    }
+
+    /// Opens the database at `path`, or an in-memory one if there's no path.
+    pub fn new(path: Option<PathBuf>, executor: BackgroundExecutor) -> Result<Self> {
+        let connection = if let Some(path) = path {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            Connection::open_file(&path.to_string_lossy())
+        } else {
+            Connection::open_memory(None)
+        };
+
+        connection.exec(indoc! {"
+            CREATE TABLE IF NOT EXISTS threads (
+                id TEXT PRIMARY KEY,
+                title TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                model TEXT,
+                worktree_paths TEXT NOT NULL,
+                data_type TEXT NOT NULL,
+                data BLOB NOT NULL
+            )
+        "})?()
+        .map_err(|e| anyhow!("Failed to create threads table: {}", e))?;
+        connection.exec(indoc! {"
+            CREATE VIRTUAL TABLE IF NOT EXISTS threads_search USING fts5(
+                id UNINDEXED,
+                title,
+                content
+            )
+        "})?()
+        .map_err(|e| anyhow!("Failed to create threads search index: {}", e))?;
+
+        Ok(Self {
+            executor,
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    /// Returns the threads matching `filter`, most recently updated first.
+    pub fn search_threads(&self, filter: ThreadFilter) -> Task<Result<Vec<DbThreadMetadata>>> {
+        let connection = self.connection.clone();
+
+        self.executor.spawn(async move {
+            let connection = connection.lock().unwrap();
+            let mut select = connection.select_bound::<(
+                Option<String>,
+                Option<String>,
+                Option<String>,
+                Option<String>,
+            ), (ThreadId, String, String, Option<String>, String)>(
+                indoc! {"
+                SELECT id, title, updated_at, model, worktree_paths FROM threads
+                WHERE (?1 IS NULL OR id IN (
+                    SELECT id FROM threads_search WHERE threads_search MATCH ?1
+                ))
+                AND (?2 IS NULL OR model = ?2)
+                AND (?3 IS NULL OR updated_at >= ?3)
+                AND (?4 IS NULL OR updated_at <= ?4)
+                ORDER BY updated_at DESC
+            "}
+            )?;
+
+            let rows = select((
+                filter.fts_query(),
+                filter.model.clone(),
+                filter.updated_after.map(|date| date.to_rfc3339()),
+                filter.updated_before.map(|date| date.to_rfc3339()),
+            ))?;
+
+            let mut threads = Vec::new();
+            for (id, title, updated_at, model, worktree_paths) in rows {
+                let worktree_paths = worktree_paths
+                    .lines()
+                    .map(PathBuf::from)
+                    .collect::<Vec<_>>();
+                if let Some(worktree_path) = filter.worktree_path.as_ref()
+                    && !worktree_paths.contains(worktree_path)
+                {
+                    continue;
+                }
+                threads.push(DbThreadMetadata {
+                    id,
+                    title: title.into(),
+                    updated_at: DateTime::parse_from_rfc3339(&updated_at)?.with_timezone(&Utc),
+                    model,
+                    worktree_paths,
+                });
+            }
+
+            Ok(threads)
+        })
+    }
+
+    pub fn load_thread(&self, id: ThreadId) -> Task<Result<Option<DbThread>>> {
+        let connection = self.connection.clone();
+
+        self.executor.spawn(async move {
+            let connection = connection.lock().unwrap();
+            let mut select = connection.select_bound::<ThreadId, (DataType, Vec<u8>)>(indoc! {"
+                SELECT data_type, data FROM threads WHERE id = ? LIMIT 1
+            "})?;
+
+            let rows = select(id)?;
+            if let Some((data_type, data)) = rows.into_iter().next() {
+                let json_data = match data_type {
+                    DataType::Zstd => zstd::decode_all(&data[..])?,
+                    DataType::Json => data,
+                };
+                Ok(Some(serde_json::from_slice(&json_data)?))
+            } else {
+                Ok(None)
+            }
+        })
+    }
+
+    pub fn save_thread(&self, id: ThreadId, thread: DbThread) -> Task<Result<()>> {
+        let connection = self.connection.clone();
+
+        self.executor.spawn(async move {
+            let json_data = serde_json::to_vec(&thread)?;
+            let data = zstd::encode_all(&json_data[..], Self::COMPRESSION_LEVEL)?;
+            let worktree_paths = thread
+                .worktree_paths
+                .iter()
+                .map(|path| path.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("\n");
+            let search_text = thread.search_text();
+
+            let connection = connection.lock().unwrap();
+            connection.with_savepoint("save_thread", || {
+                let mut insert = connection.exec_bound::<(
+                    ThreadId,
+                    String,
+                    String,
+                    Option<String>,
+                    String,
+                    DataType,
+                    Vec<u8>,
+                )>(indoc! {"
+                    INSERT OR REPLACE INTO threads
+                        (id, title, updated_at, model, worktree_paths, data_type, data)
+                    VALUES (?, ?, ?, ?, ?, ?, ?)
+                "})?;
+                insert((
+                    id.clone(),
+                    thread.title.to_string(),
+                    thread.updated_at.to_rfc3339(),
+                    thread.model.as_ref().map(|model| model.to_string()),
+                    worktree_paths,
+                    DataType::Zstd,
+                    data,
+                ))?;
+
+                let mut delete = connection.exec_bound::<ThreadId>(indoc! {"
+                    DELETE FROM threads_search WHERE id = ?
+                "})?;
+                delete(id.clone())?;
+
+                let mut index = connection.exec_bound::<(ThreadId, String, String)>(indoc! {"
+                    INSERT INTO threads_search (id, title, content) VALUES (?, ?, ?)
+                "})?;
+                index((id, thread.title.to_string(), search_text))
+            })
+        })
+    }
+
+    pub fn delete_thread(&self, id: ThreadId) -> Task<Result<()>> {
+        let connection = self.connection.clone();
+
+        self.executor.spawn(async move {
+            let connection = connection.lock().unwrap();
+            connection.with_savepoint("delete_thread", || {
+                let mut delete = connection.exec_bound::<ThreadId>(indoc! {"
+                    DELETE FROM threads WHERE id = ?
+                "})?;
+                delete(id.clone())?;
+
+                let mut delete = connection.exec_bound::<ThreadId>(indoc! {"
+                    DELETE FROM threads_search WHERE id = ?
+                "})?;
+                delete(id)
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use language_model::{LanguageModelToolResult, LanguageModelToolUse, LanguageModelToolUseId};
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn thread(title: &str, model: &str, worktree: &str, updated_at: &str) -> DbThread {
+        let tool_use_id = LanguageModelToolUseId::from("tool_1");
+        DbThread {
+            title: title.to_string().into(),
+            messages: vec![
+                Message::User(UserMessage {
+                    id: UserMessageId::new(),
+                    content: vec![UserMessageContent::Text(format!("Please fix {title}"))],
+                }),
+                Message::Agent(AgentMessage {
+                    content: vec![
+                        AgentMessageContent::Text("Let me look.".into()),
+                        AgentMessageContent::ToolUse(LanguageModelToolUse {
+                            id: tool_use_id.clone(),
+                            name: "grep".into(),
+                            raw_input: json!({"regex": "fn main"}).to_string(),
+                            input: json!({"regex": "fn main"}),
+                            is_input_complete: true,
+                        }),
+                    ],
+                    tool_results: [(
+                        tool_use_id.clone(),
+                        LanguageModelToolResult {
+                            tool_use_id,
+                            tool_name: "grep".into(),
+                            is_error: false,
+                            content: LanguageModelToolResultContent::Text(
+                                format!("src/{title}.rs").into(),
+                            ),
+                            output: None,
+                        },
+                    )]
+                    .into_iter()
+                    .collect(),
+                }),
+            ],
+            updated_at: DateTime::parse_from_rfc3339(updated_at)
+                .unwrap()
+                .with_timezone(&Utc),
+            worktree_paths: vec![PathBuf::from(worktree)],
+            model: Some(DbLanguageModel {
+                provider: "fake".into(),
+                model: model.into(),
+            }),
+            profile: None,
+            completion_mode: None,
+            latest_token_usage: None,
+            tracked_buffers: vec![DbTrackedBuffer {
+                path: PathBuf::from(worktree).join("src/main.rs"),
+                diff_base: Some("fn main() {}\n".into()),
+            }],
+        }
+    }
+
+    #[gpui::test]
+    async fn test_search_threads(cx: &mut TestAppContext) {
+        let database = ThreadsDatabase::new(None, cx.executor()).unwrap();
+        let parser = thread("parser", "fast", "/code/zed", "2025-01-01T00:00:00Z");
+        let renderer = thread("renderer", "smart", "/code/zed", "2025-02-01T00:00:00Z");
+        let website = thread("website", "fast", "/code/site", "2025-03-01T00:00:00Z");
+        for (id, thread) in [("1", &parser), ("2", &renderer), ("3", &website)] {
+            database
+                .save_thread(ThreadId::from(id), thread.clone())
+                .await
+                .unwrap();
+        }
+
+        let search = |filter: ThreadFilter| {
+            let threads = database.search_threads(filter);
+            async move {
+                threads
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|thread| thread.title.to_string())
+                    .collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(
+            search(ThreadFilter::default()).await,
+            vec!["website", "renderer", "parser"]
+        );
+        // Tool results are searchable too.
+        assert_eq!(
+            search(ThreadFilter {
+                query: Some("src/render".into()),
+                ..Default::default()
+            })
+            .await,
+            vec!["renderer"]
+        );
+        assert_eq!(
+            search(ThreadFilter {
+                query: Some("grep \"fix".into()),
+                worktree_path: Some("/code/zed".into()),
+                ..Default::default()
+            })
+            .await,
+            vec!["renderer", "parser"]
+        );
+        assert_eq!(
+            search(ThreadFilter {
+                model: Some("fake/fast".into()),
+                updated_before: Some(
+                    DateTime::parse_from_rfc3339("2025-02-15T00:00:00Z")
+                        .unwrap()
+                        .with_timezone(&Utc)
+                ),
+                ..Default::default()
+            })
+            .await,
+            vec!["parser"]
+        );
+
+        assert_eq!(
+            database
+                .load_thread(ThreadId::from("2"))
+                .await
+                .unwrap()
+                .unwrap(),
+            renderer
+        );
+        database.delete_thread(ThreadId::from("2")).await.unwrap();
+        assert_eq!(
+            database.load_thread(ThreadId::from("2")).await.unwrap(),
+            None
+        );
+        assert_eq!(
+            search(ThreadFilter {
+                query: Some("renderer".into()),
+                ..Default::default()
+            })
+            .await,
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn test_export_and_import() {
+        let thread = thread("parser", "fast", "/code/zed", "2025-01-01T00:00:00Z");
+
+        let json = thread.export(ThreadExportFormat::Json).unwrap();
+        assert_eq!(
+            DbThread::import(&json, ThreadExportFormat::Json).unwrap(),
+            thread
+        );
+
+        let markdown = thread.export(ThreadExportFormat::Markdown).unwrap();
+        let imported = DbThread::import(&markdown, ThreadExportFormat::Markdown).unwrap();
+        assert_eq!(imported.title, thread.title);
+        assert_eq!(imported.messages.len(), 2);
+        let Message::User(message) = &imported.messages[0] else {
+            panic!("expected a user message");
+        };
+        assert_eq!(
+            message.content,
+            vec![UserMessageContent::Text("Please fix parser".into())]
+        );
+        // The tool call is kept as text, along with the rest of the assistant's message.
+        let Message::Agent(message) = &imported.messages[1] else {
+            panic!("expected an agent message");
+        };
+        let [AgentMessageContent::Text(text)] = message.content.as_slice() else {
+            panic!("expected a single text chunk");
+        };
+        assert!(text.starts_with("Let me look.\n**Tool Use**: grep"));
+        assert!(text.ends_with("src/parser.rs"));
+    }
+}
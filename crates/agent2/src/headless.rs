@@ -0,0 +1,231 @@
+use crate::{AgentResponseEvent, ContextServerRegistry, NativeAgent, Templates, Thread};
+use acp_thread::UserMessageId;
+use action_log::ActionLog;
+use agent_client_protocol as acp;
+use agent_settings::AgentProfileId;
+use anyhow::Result;
+use collections::IndexMap;
+use futures::StreamExt as _;
+use gpui::{App, AppContext as _, Entity, Task};
+use language::DiskState;
+use language_model::{LanguageModel, TokenUsage};
+use project::Project;
+use serde::Serialize;
+use std::{cell::RefCell, path::PathBuf, rc::Rc, sync::Arc};
+
+/// What to ask of the agent in [`run_headless`].
+#[derive(Debug, Default)]
+pub struct HeadlessRunOptions {
+    pub prompt: String,
+    /// The profile whose tools the agent can use. Defaults to the default profile.
+    pub profile: Option<AgentProfileId>,
+    /// Whether to allow the tool calls that would ask the user for permission, instead of
+    /// rejecting them.
+    pub allow_tools: bool,
+}
+
+/// A machine-readable account of what the agent did in [`run_headless`].
+#[derive(Debug, Serialize)]
+pub struct HeadlessRunReport {
+    /// Whether the agent ended its turn normally, with no failed or rejected tool calls and all
+    /// of its edits saved.
+    pub success: bool,
+    pub stop_reason: Option<acp::StopReason>,
+    pub error: Option<String>,
+    /// The agent's last message.
+    pub response: String,
+    pub tool_calls: Vec<HeadlessToolCall>,
+    pub diffs: Vec<HeadlessFileDiff>,
+    pub token_usage: TokenUsage,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HeadlessToolCall {
+    pub id: String,
+    pub title: String,
+    pub kind: acp::ToolKind,
+    pub status: acp::ToolCallStatus,
+    pub input: Option<serde_json::Value>,
+    pub output: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HeadlessFileDiff {
+    pub path: PathBuf,
+    /// The agent's changes to the file, as a unified diff.
+    pub diff: String,
+    /// Why the changes couldn't be saved to disk, if they weren't.
+    pub save_error: Option<String>,
+}
+
+impl From<acp::ToolCall> for HeadlessToolCall {
+    fn from(tool_call: acp::ToolCall) -> Self {
+        Self {
+            id: tool_call.id.0.to_string(),
+            title: tool_call.title,
+            kind: tool_call.kind,
+            status: tool_call.status,
+            input: tool_call.raw_input,
+            output: tool_call.raw_output,
+        }
+    }
+}
+
+impl HeadlessToolCall {
+    fn update(&mut self, fields: acp::ToolCallUpdateFields) {
+        if let Some(title) = fields.title {
+            self.title = title;
+        }
+        if let Some(kind) = fields.kind {
+            self.kind = kind;
+        }
+        if let Some(status) = fields.status {
+            self.status = status;
+        }
+        if let Some(input) = fields.raw_input {
+            self.input = Some(input);
+        }
+        if let Some(output) = fields.raw_output {
+            self.output = Some(output);
+        }
+    }
+}
+
+/// Runs a single agent turn on `project` without any UI, for scripts and CI.
+///
+/// Tool calls that need permission are answered according to
+/// [`HeadlessRunOptions::allow_tools`], and the agent's edits are saved to disk once the turn
+/// ends.
+pub fn run_headless(
+    project: Entity<Project>,
+    model: Arc<dyn LanguageModel>,
+    options: HeadlessRunOptions,
+    cx: &mut App,
+) -> Task<Result<HeadlessRunReport>> {
+    let project_context = NativeAgent::build_project_context(&project, None, cx);
+    cx.spawn(async move |cx| {
+        let project_context = Rc::new(RefCell::new(project_context.await));
+        let thread = cx.new(|cx| {
+            let context_server_registry = cx
+                .new(|cx| ContextServerRegistry::new(project.read(cx).context_server_store(), cx));
+            let action_log = cx.new(|_| ActionLog::new(project.clone()));
+            let mut thread = Thread::new(
+                project.clone(),
+                project_context,
+                context_server_registry,
+                action_log,
+                Templates::new(),
+                model,
+                cx,
+            );
+            thread.add_default_tools(cx);
+            if let Some(profile) = options.profile {
+                thread.set_profile(profile);
+            }
+            thread
+        })?;
+        let mut events = thread.update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), [options.prompt.as_str()], cx)
+        })?;
+
+        let mut tool_calls = IndexMap::default();
+        let mut response = String::new();
+        let mut stop_reason = None;
+        let mut error = None;
+        while let Some(event) = events.next().await {
+            match event {
+                Ok(AgentResponseEvent::Text(text)) => response.push_str(&text),
+                Ok(AgentResponseEvent::ToolCall(tool_call)) => {
+                    // Only the text after the last tool call is the agent's last message.
+                    response.clear();
+                    tool_calls.insert(tool_call.id.clone(), HeadlessToolCall::from(tool_call));
+                }
+                Ok(AgentResponseEvent::ToolCallUpdate(
+                    acp_thread::ToolCallUpdate::UpdateFields(update),
+                )) => {
+                    if let Some(tool_call) = tool_calls.get_mut(&update.id) {
+                        tool_call.update(update.fields);
+                    }
+                }
+                Ok(AgentResponseEvent::ToolCallAuthorization(authorization)) => {
+                    let option = authorization.options.into_iter().find(|option| {
+                        if options.allow_tools {
+                            matches!(option.kind, acp::PermissionOptionKind::AllowOnce)
+                        } else {
+                            matches!(option.kind, acp::PermissionOptionKind::RejectOnce)
+                        }
+                    });
+                    if let Some(option) = option {
+                        authorization.response.send(option.id).ok();
+                    }
+                }
+                Ok(AgentResponseEvent::Stop(reason)) => stop_reason = Some(reason),
+                Ok(AgentResponseEvent::Thinking(_) | AgentResponseEvent::ToolCallUpdate(_)) => {}
+                Err(err) => {
+                    error = Some(format!("{err:#}"));
+                    break;
+                }
+            }
+        }
+
+        let action_log = thread.read_with(cx, |thread, _| thread.action_log().clone())?;
+        let changed_buffers = action_log.read_with(cx, |action_log, cx| {
+            action_log
+                .changed_buffers(cx)
+                .into_keys()
+                .collect::<Vec<_>>()
+        })?;
+        let mut diffs = Vec::new();
+        for buffer in changed_buffers {
+            let old_text = action_log
+                .read_with(cx, |action_log, cx| {
+                    action_log.unreviewed_diff_base(&buffer, cx)
+                })?
+                .unwrap_or_default();
+            let (path, new_text, needs_save) = buffer.read_with(cx, |buffer, cx| {
+                let file = buffer.file();
+                (
+                    file.map(|file| file.full_path(cx)),
+                    buffer.text(),
+                    buffer.is_dirty()
+                        && file.is_none_or(|file| file.disk_state() != DiskState::Deleted),
+                )
+            })?;
+            let mut save_error = None;
+            if needs_save {
+                if let Err(err) = project
+                    .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))?
+                    .await
+                {
+                    save_error = Some(format!("{err:#}"));
+                }
+            }
+            if let Some(path) = path {
+                diffs.push(HeadlessFileDiff {
+                    path,
+                    diff: language::unified_diff(&old_text, &new_text),
+                    save_error,
+                });
+            } else if let Some(save_error) = save_error {
+                error.get_or_insert(save_error);
+            }
+        }
+
+        let tool_calls = tool_calls.into_values().collect::<Vec<_>>();
+        let success = error.is_none()
+            && stop_reason == Some(acp::StopReason::EndTurn)
+            && tool_calls
+                .iter()
+                .all(|tool_call| tool_call.status != acp::ToolCallStatus::Failed)
+            && diffs.iter().all(|diff| diff.save_error.is_none());
+        Ok(HeadlessRunReport {
+            success,
+            stop_reason,
+            error,
+            response: response.trim().to_string(),
+            tool_calls,
+            diffs,
+            token_usage: thread.read_with(cx, |thread, _| thread.cumulative_token_usage())?,
+        })
+    })
+}
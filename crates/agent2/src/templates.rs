@@ -44,6 +44,15 @@ impl Template for SystemPromptTemplate<'_> {
     const TEMPLATE_NAME: &'static str = "system_prompt.hbs";
 }
 
+#[derive(Serialize)]
+pub struct CompactionPromptTemplate {
+    pub touched_paths: Vec<String>,
+}
+
+impl Template for CompactionPromptTemplate {
+    const TEMPLATE_NAME: &'static str = "compaction_prompt.hbs";
+}
+
 /// Handlebars helper for checking if an item is in a list
 fn contains(
     h: &handlebars::Helper,
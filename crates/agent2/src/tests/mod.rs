@@ -5,6 +5,7 @@ use agent_client_protocol::{self as acp};
 use agent_settings::AgentProfileId;
 use anyhow::Result;
 use client::{Client, UserStore};
+use cloud_llm_client::CompletionIntent;
 use fs::{FakeFs, Fs};
 use futures::channel::mpsc::UnboundedReceiver;
 use gpui::{
@@ -14,7 +15,7 @@ use indoc::indoc;
 use language_model::{
     LanguageModel, LanguageModelCompletionEvent, LanguageModelId, LanguageModelRegistry,
     LanguageModelRequestMessage, LanguageModelToolResult, LanguageModelToolUse, MessageContent,
-    Role, StopReason, fake_provider::FakeLanguageModel,
+    Role, StopReason, UsageLedger, fake_provider::FakeLanguageModel,
 };
 use pretty_assertions::assert_eq;
 use project::Project;
@@ -496,6 +497,97 @@ async fn test_tool_authorization(cx: &mut TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_tool_permission_rules(cx: &mut TestAppContext) {
+    let ThreadTest {
+        model, thread, fs, ..
+    } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+
+    fs.insert_file(
+        paths::settings_file(),
+        json!({
+            "agent": {
+                "default_profile": "test-profile",
+                "profiles": {
+                    "test-profile": {
+                        "name": "Test Profile",
+                        "tools": {
+                            EchoTool.name(): true,
+                            ToolRequiringPermission.name(): true,
+                        },
+                        "tool_permissions": {
+                            EchoTool.name(): { "default": "ask" },
+                            ToolRequiringPermission.name(): { "default": "deny" },
+                        }
+                    }
+                }
+            }
+        })
+        .to_string()
+        .into_bytes(),
+    )
+    .await;
+    cx.run_until_parked();
+
+    let mut events = thread.update(cx, |thread, cx| {
+        thread.add_tool(EchoTool);
+        thread.add_tool(ToolRequiringPermission);
+        thread.send(UserMessageId::new(), ["abc"], cx)
+    });
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+        LanguageModelToolUse {
+            id: "tool_id_1".into(),
+            name: ToolRequiringPermission.name().into(),
+            raw_input: "{}".into(),
+            input: json!({}),
+            is_input_complete: true,
+        },
+    ));
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+        LanguageModelToolUse {
+            id: "tool_id_2".into(),
+            name: EchoTool.name().into(),
+            raw_input: json!({"text": "hello"}).to_string(),
+            input: json!({"text": "hello"}),
+            is_input_complete: true,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+
+    // The echo tool never asks on its own, but the rules require it.
+    let tool_call_auth = next_tool_call_authorization(&mut events).await;
+    assert_eq!(tool_call_auth.tool_call.id.0.as_ref(), "tool_id_2");
+    tool_call_auth
+        .response
+        .send(tool_call_auth.options[1].id.clone())
+        .unwrap();
+    cx.run_until_parked();
+
+    let completion = fake_model.pending_completions().pop().unwrap();
+    let message = completion.messages.last().unwrap();
+    assert_eq!(
+        message.content,
+        vec![
+            language_model::MessageContent::ToolResult(LanguageModelToolResult {
+                tool_use_id: "tool_id_1".into(),
+                tool_name: ToolRequiringPermission.name().into(),
+                is_error: true,
+                content: "Calling tool_requiring_permission with this input is denied by the user's tool permissions".into(),
+                output: None
+            }),
+            language_model::MessageContent::ToolResult(LanguageModelToolResult {
+                tool_use_id: "tool_id_2".into(),
+                tool_name: EchoTool.name().into(),
+                is_error: false,
+                content: "hello".into(),
+                output: Some("hello".into())
+            })
+        ]
+    );
+}
+
 #[gpui::test]
 async fn test_tool_hallucination(cx: &mut TestAppContext) {
     let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
@@ -891,6 +983,326 @@ async fn test_profiles(cx: &mut TestAppContext) {
     assert_eq!(tool_names, vec![InfiniteTool.name()]);
 }
 
+#[gpui::test]
+async fn test_delegate_tool(cx: &mut TestAppContext) {
+    let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+
+    let mut events = thread.update(cx, |thread, cx| {
+        thread.add_tool(EchoTool);
+        thread.add_tool(DelayTool);
+        thread.add_tool(DelegateTool::new(cx.weak_entity()));
+        thread.send(UserMessageId::new(), ["Check whether echo works"], cx)
+    });
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+        LanguageModelToolUse {
+            id: "delegate_1".into(),
+            name: DelegateTool::NAME.into(),
+            raw_input: "{}".into(),
+            input: json!({
+                "title": "Try echo",
+                "task": "Call the echo tool with 'hi'",
+                "tools": [EchoTool.name()],
+            }),
+            is_input_complete: true,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+    let tool_call = expect_tool_call(&mut events).await;
+    assert_eq!(tool_call.title, "Try echo");
+    thread.read_with(cx, |thread, _| {
+        assert!(thread.is_delegation(&tool_call.id));
+    });
+
+    // The sub-agent starts from the task alone, and only gets the tools it was given.
+    let completion = fake_model.pending_completions().pop().unwrap();
+    let tool_names = completion
+        .tools
+        .iter()
+        .map(|tool| tool.name.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(tool_names, vec![EchoTool.name()]);
+    assert_eq!(completion.messages.len(), 2);
+    assert!(
+        completion.messages[1]
+            .string_contents()
+            .starts_with("Call the echo tool with 'hi'")
+    );
+
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+        LanguageModelToolUse {
+            id: "echo_1".into(),
+            name: EchoTool.name().into(),
+            raw_input: "{}".into(),
+            input: json!({"text": "hi"}),
+            is_input_complete: true,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_text_chunk("The echo tool works.");
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    // Only the sub-agent's reply makes it into the parent thread.
+    let completion = fake_model.pending_completions().pop().unwrap();
+    assert_eq!(
+        completion.messages.last().unwrap().content,
+        vec![language_model::MessageContent::ToolResult(
+            LanguageModelToolResult {
+                tool_use_id: "delegate_1".into(),
+                tool_name: DelegateTool::NAME.into(),
+                is_error: false,
+                content: "The echo tool works.".into(),
+                output: Some("The echo tool works.".into())
+            }
+        )]
+    );
+}
+
+#[gpui::test]
+async fn test_delegate_tool_keeps_parent_restrictions(cx: &mut TestAppContext) {
+    let ThreadTest {
+        model, thread, fs, ..
+    } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+
+    fs.insert_file(
+        paths::settings_file(),
+        json!({
+            "agent": {
+                "default_profile": "test-profile",
+                "profiles": {
+                    "test-profile": {
+                        "name": "Test Profile",
+                        "tools": {
+                            EchoTool.name(): true,
+                            DelegateTool::NAME: true,
+                        },
+                        "tool_permissions": { EchoTool.name(): { "default": "deny" } }
+                    },
+                    "loose": {
+                        "name": "Loose",
+                        "tools": { EchoTool.name(): true },
+                        "tool_permissions": { EchoTool.name(): { "default": "allow" } }
+                    }
+                }
+            }
+        })
+        .to_string()
+        .into_bytes(),
+    )
+    .await;
+    cx.run_until_parked();
+
+    thread.update(cx, |thread, cx| {
+        thread.add_tool(EchoTool);
+        thread.add_tool(DelegateTool::new(cx.weak_entity()));
+        thread.send(UserMessageId::new(), ["Echo something"], cx)
+    });
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+        LanguageModelToolUse {
+            id: "delegate_1".into(),
+            name: DelegateTool::NAME.into(),
+            raw_input: "{}".into(),
+            input: json!({
+                "title": "Echo",
+                "task": "Call the echo tool with 'hi'",
+                "profile": "loose",
+            }),
+            is_input_complete: true,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    // The sub-agent's profile can't loosen the rules of the profile that delegated to it.
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+        LanguageModelToolUse {
+            id: "echo_1".into(),
+            name: EchoTool.name().into(),
+            raw_input: "{}".into(),
+            input: json!({"text": "hi"}),
+            is_input_complete: true,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    let completion = fake_model.pending_completions().pop().unwrap();
+    let language_model::MessageContent::ToolResult(tool_result) =
+        &completion.messages.last().unwrap().content[0]
+    else {
+        panic!("expected the result of the echo tool");
+    };
+    assert_eq!(tool_result.tool_use_id.to_string(), "echo_1");
+    assert!(tool_result.is_error);
+}
+
+#[gpui::test]
+async fn test_headless_run(cx: &mut TestAppContext) {
+    let ThreadTest {
+        model, thread, fs, ..
+    } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+    let project = thread.read_with(cx, |thread, _| thread.project().clone());
+
+    fs.insert_file(path!("/test/a.txt"), b"hello\n".to_vec())
+        .await;
+    fs.insert_file(
+        paths::settings_file(),
+        json!({
+            "agent": {
+                "profiles": {
+                    "cleanup": {
+                        "name": "Cleanup",
+                        "tools": { "delete_path": true },
+                        "tool_permissions": { "delete_path": { "default": "ask" } }
+                    }
+                }
+            }
+        })
+        .to_string()
+        .into_bytes(),
+    )
+    .await;
+    cx.run_until_parked();
+
+    let report = cx.update(|cx| {
+        run_headless(
+            project,
+            model.clone(),
+            HeadlessRunOptions {
+                prompt: "Delete a.txt".into(),
+                profile: Some(AgentProfileId("cleanup".into())),
+                allow_tools: true,
+            },
+            cx,
+        )
+    });
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::UsageUpdate(
+        language_model::TokenUsage {
+            input_tokens: 100,
+            output_tokens: 10,
+            ..Default::default()
+        },
+    ));
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+        LanguageModelToolUse {
+            id: "delete_1".into(),
+            name: "delete_path".into(),
+            raw_input: "{}".into(),
+            input: json!({"path": "test/a.txt"}),
+            is_input_complete: true,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    // The permission request was answered without anyone around to ask.
+    fake_model.send_last_completion_stream_text_chunk("Deleted a.txt.");
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::UsageUpdate(
+        language_model::TokenUsage {
+            input_tokens: 150,
+            output_tokens: 5,
+            ..Default::default()
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    let report = report.await.unwrap();
+    assert!(report.success);
+    assert_eq!(report.response, "Deleted a.txt.");
+    assert_eq!(report.tool_calls.len(), 1);
+    assert_eq!(report.tool_calls[0].id, "delete_1");
+    assert_eq!(report.tool_calls[0].status, acp::ToolCallStatus::Completed);
+    assert_eq!(
+        report.token_usage,
+        language_model::TokenUsage {
+            input_tokens: 250,
+            output_tokens: 15,
+            ..Default::default()
+        }
+    );
+    assert_eq!(report.diffs.len(), 1);
+    assert_eq!(report.diffs[0].path, Path::new("test/a.txt"));
+    assert!(report.diffs[0].diff.contains("-hello"));
+    assert!(!fs.is_file(path!("/test/a.txt").as_ref()).await);
+}
+
+#[gpui::test]
+async fn test_headless_run_with_rejected_tool_call(cx: &mut TestAppContext) {
+    let ThreadTest {
+        model, thread, fs, ..
+    } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+    let project = thread.read_with(cx, |thread, _| thread.project().clone());
+
+    fs.insert_file(path!("/test/a.txt"), b"hello\n".to_vec())
+        .await;
+    fs.insert_file(
+        paths::settings_file(),
+        json!({
+            "agent": {
+                "profiles": {
+                    "cleanup": {
+                        "name": "Cleanup",
+                        "tools": { "delete_path": true },
+                        "tool_permissions": { "delete_path": { "default": "ask" } }
+                    }
+                }
+            }
+        })
+        .to_string()
+        .into_bytes(),
+    )
+    .await;
+    cx.run_until_parked();
+
+    let report = cx.update(|cx| {
+        run_headless(
+            project,
+            model.clone(),
+            HeadlessRunOptions {
+                prompt: "Delete a.txt".into(),
+                profile: Some(AgentProfileId("cleanup".into())),
+                allow_tools: false,
+            },
+            cx,
+        )
+    });
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+        LanguageModelToolUse {
+            id: "delete_1".into(),
+            name: "delete_path".into(),
+            raw_input: "{}".into(),
+            input: json!({"path": "test/a.txt"}),
+            is_input_complete: true,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    // The agent ends its turn normally, but the run still fails because of the rejected call.
+    fake_model.send_last_completion_stream_text_chunk("I wasn't allowed to delete a.txt.");
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    let report = report.await.unwrap();
+    assert_eq!(report.stop_reason, Some(acp::StopReason::EndTurn));
+    assert!(!report.success);
+    assert_eq!(report.tool_calls.len(), 1);
+    assert_eq!(report.tool_calls[0].status, acp::ToolCallStatus::Failed);
+    assert!(report.diffs.is_empty());
+    assert!(fs.is_file(path!("/test/a.txt").as_ref()).await);
+}
+
 #[gpui::test]
 #[ignore = "can't run on CI yet"]
 async fn test_cancellation(cx: &mut TestAppContext) {
@@ -1091,6 +1503,210 @@ async fn test_truncate(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_compaction(cx: &mut TestAppContext) {
+    let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+
+    thread.update(cx, |thread, cx| {
+        thread.send(UserMessageId::new(), ["Hello"], cx)
+    });
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_text_chunk("Hey!");
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::UsageUpdate(
+        language_model::TokenUsage {
+            input_tokens: 900_000,
+            ..Default::default()
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+    thread.read_with(cx, |thread, _| assert!(!thread.needs_compaction()));
+
+    // Once there's an earlier turn to summarize, sending a message compacts it first.
+    let message_id = UserMessageId::new();
+    thread.update(cx, |thread, cx| {
+        thread.send(message_id.clone(), ["Goodbye"], cx)
+    });
+    cx.run_until_parked();
+    let completion = fake_model.pending_completions().pop().unwrap();
+    assert_eq!(
+        completion.intent,
+        Some(CompletionIntent::ThreadContextSummarization)
+    );
+    fake_model.send_last_completion_stream_text_chunk("The user greeted the agent.");
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    let completion = fake_model.pending_completions().pop().unwrap();
+    assert_eq!(completion.intent, Some(CompletionIntent::UserPrompt));
+    assert_eq!(completion.messages.len(), 3);
+    assert!(
+        completion.messages[1]
+            .string_contents()
+            .contains("The user greeted the agent.")
+    );
+    assert_eq!(completion.messages[2].string_contents(), "Goodbye");
+    fake_model.send_last_completion_stream_text_chunk("Bye!");
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    thread.read_with(cx, |thread, _| {
+        assert_eq!(
+            thread.compaction_boundaries().collect::<Vec<_>>(),
+            vec![&message_id]
+        );
+        assert_eq!(
+            thread.to_markdown(),
+            indoc! {"
+                ## User
+
+                Hello
+
+                ## Assistant
+
+                Hey!
+
+                ## Summary
+
+                The user greeted the agent.
+
+                ## User
+
+                Goodbye
+
+                ## Assistant
+
+                Bye!
+            "}
+        );
+    });
+
+    // There's nothing left to compact before the latest user message.
+    let result = thread.update(cx, |thread, cx| thread.compact(cx)).await;
+    assert!(result.is_err());
+}
+
+#[gpui::test]
+async fn test_refusal_after_concurrent_compaction(cx: &mut TestAppContext) {
+    let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+
+    for (message, response) in [("Hello", "Hey!"), ("Again", "Sure.")] {
+        thread.update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), [message], cx)
+        });
+        cx.run_until_parked();
+        fake_model.send_last_completion_stream_text_chunk(response);
+        fake_model.end_last_completion_stream();
+        cx.run_until_parked();
+    }
+
+    // Send a message while the earlier turns are being compacted.
+    let compaction = thread.update(cx, |thread, cx| thread.compact(cx));
+    cx.run_until_parked();
+    let summary_request = fake_model.pending_completions().pop().unwrap();
+    let events = thread.update(cx, |thread, cx| {
+        thread.send(UserMessageId::new(), ["Third"], cx)
+    });
+    cx.run_until_parked();
+    fake_model.send_completion_stream_text_chunk(&summary_request, "The user greeted the agent.");
+    fake_model.end_completion_stream(&summary_request);
+    compaction.await.unwrap();
+
+    // The refusal only removes the turn it ends, even though compacting inserted a message before it.
+    fake_model
+        .send_last_completion_stream_event(LanguageModelCompletionEvent::Stop(StopReason::Refusal));
+    let events = events.collect::<Vec<_>>().await;
+    assert_eq!(stop_events(events), vec![acp::StopReason::Refusal]);
+    thread.read_with(cx, |thread, _| {
+        assert_eq!(
+            thread.to_markdown(),
+            indoc! {"
+                ## User
+
+                Hello
+
+                ## Assistant
+
+                Hey!
+
+                ## Summary
+
+                The user greeted the agent.
+
+                ## User
+
+                Again
+
+                ## Assistant
+
+                Sure.
+            "}
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_usage_budget(cx: &mut TestAppContext) {
+    let ThreadTest {
+        model, thread, fs, ..
+    } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+
+    fs.insert_file(
+        paths::settings_file(),
+        json!({
+            "language_models": {
+                "pricing": { "fake": { "*": { "input": 1.0, "output": 0.0 } } },
+                "budget": { "daily": 1.0, "when_exceeded": "block" }
+            }
+        })
+        .to_string()
+        .into_bytes(),
+    )
+    .await;
+    cx.run_until_parked();
+
+    let events = thread.update(cx, |thread, cx| {
+        thread.send(UserMessageId::new(), ["Hello"], cx)
+    });
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_text_chunk("Hey!");
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::UsageUpdate(
+        language_model::TokenUsage {
+            input_tokens: 1_000_000,
+            ..Default::default()
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+    assert!(events.collect::<Vec<_>>().await.iter().all(Result::is_ok));
+
+    let thread_id = thread.read_with(cx, |thread, _| thread.id().to_string());
+    let totals = cx
+        .update(|cx| {
+            UsageLedger::global(cx)
+                .unwrap()
+                .read(cx)
+                .thread_totals(thread_id, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(totals.requests, 1);
+    assert_eq!(totals.cost, 1.0);
+
+    // Once the budget is spent, requests fail without reaching the model.
+    let events = thread.update(cx, |thread, cx| {
+        thread.send(UserMessageId::new(), ["Hello again"], cx)
+    });
+    cx.run_until_parked();
+    assert!(fake_model.pending_completions().is_empty());
+    let events = events.collect::<Vec<_>>().await;
+    let error = events.last().unwrap().as_ref().unwrap_err();
+    assert!(error.to_string().contains("daily language model budget"));
+}
+
 #[gpui::test]
 async fn test_agent_connection(cx: &mut TestAppContext) {
     cx.update(settings::init);
@@ -1221,6 +1837,131 @@ async fn test_agent_connection(cx: &mut TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_thread_persistence(cx: &mut TestAppContext) {
+    cx.update(settings::init);
+    cx.update(|cx| {
+        gpui_tokio::init(cx);
+        client::init_settings(cx);
+
+        let http_client = FakeHttpClient::with_404_response();
+        let clock = Arc::new(clock::FakeSystemClock::new());
+        let client = Client::new(clock, http_client, cx);
+        let user_store = cx.new(|cx| UserStore::new(client.clone(), cx));
+        language_model::init(client.clone(), cx);
+        language_models::init(user_store.clone(), client.clone(), cx);
+        Project::init_settings(cx);
+        LanguageModelRegistry::test(cx);
+        agent_settings::init(cx);
+    });
+
+    let fake_fs = cx.update(|cx| fs::FakeFs::new(cx.background_executor().clone()));
+    fake_fs.insert_tree(path!("/test"), json!({})).await;
+    let project = Project::test(fake_fs.clone(), [Path::new("/test")], cx).await;
+    let agent = NativeAgent::new(
+        project.clone(),
+        Templates::new(),
+        None,
+        fake_fs.clone(),
+        &mut cx.to_async(),
+    )
+    .await
+    .unwrap();
+    let connection = Rc::new(NativeAgentConnection(agent.clone()));
+
+    let acp_thread = cx
+        .update(|cx| {
+            connection
+                .clone()
+                .new_thread(project.clone(), Path::new("/test"), cx)
+        })
+        .await
+        .unwrap();
+    let session_id = acp_thread.read_with(cx, |thread, _| thread.session_id().clone());
+    let request = acp_thread.update(cx, |thread, cx| thread.send(vec!["abc".into()], cx));
+    cx.run_until_parked();
+    let model = cx.update(|cx| {
+        connection
+            .thread(&session_id, cx)
+            .unwrap()
+            .read(cx)
+            .model()
+            .clone()
+    });
+    let model = model.as_fake();
+    model.send_last_completion_stream_text_chunk("def");
+    model.end_last_completion_stream();
+    request.await.unwrap();
+    cx.run_until_parked();
+
+    // The thread was saved when its turn ended.
+    let saved_threads = cx
+        .update(|cx| {
+            connection.search_threads(
+                ThreadFilter {
+                    query: Some("def".into()),
+                    worktree_path: Some(path!("/test").into()),
+                    model: Some("fake/fake".into()),
+                    ..Default::default()
+                },
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    assert_eq!(saved_threads.len(), 1);
+    assert_eq!(saved_threads[0].title, "abc");
+    let thread_id = cx.update(|cx| {
+        connection
+            .thread(&session_id, cx)
+            .unwrap()
+            .read(cx)
+            .id()
+            .clone()
+    });
+    assert_eq!(saved_threads[0].id, thread_id);
+
+    // Opening the saved thread shows its messages again.
+    let markdown = acp_thread.read_with(cx, |thread, cx| thread.to_markdown(cx));
+    let restored_thread = cx
+        .update(|cx| {
+            connection
+                .clone()
+                .open_thread(thread_id.clone(), project.clone(), cx)
+        })
+        .await
+        .unwrap();
+    restored_thread.read_with(cx, |thread, cx| {
+        assert_eq!(thread.title(), "abc");
+        assert_eq!(thread.to_markdown(cx), markdown);
+    });
+
+    // Exported threads can be imported as new threads.
+    let exported = cx
+        .update(|cx| connection.export_thread(&session_id, ThreadExportFormat::Markdown, cx))
+        .unwrap();
+    assert!(exported.starts_with("# abc\n\n## User\n\nabc\n"));
+    let imported_thread = cx
+        .update(|cx| {
+            connection.clone().import_thread(
+                exported,
+                ThreadExportFormat::Markdown,
+                project.clone(),
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    imported_thread.read_with(cx, |thread, cx| {
+        assert_eq!(thread.to_markdown(cx), markdown);
+    });
+    let saved_threads = cx
+        .update(|cx| connection.search_threads(ThreadFilter::default(), cx))
+        .await
+        .unwrap();
+    assert_eq!(saved_threads.len(), 2);
+}
+
 #[gpui::test]
 async fn test_tool_updates_to_completion(cx: &mut TestAppContext) {
     let ThreadTest { thread, model, .. } = setup(cx, TestModel::Fake).await;
@@ -1376,6 +2117,7 @@ async fn setup(cx: &mut TestAppContext, model: TestModel) -> ThreadTest {
                             WordListTool.name(): true,
                             ToolRequiringPermission.name(): true,
                             InfiniteTool.name(): true,
+                            DelegateTool::NAME: true,
                         }
                     }
                 }
@@ -1,35 +1,54 @@
-use crate::{ContextServerRegistry, SystemPromptTemplate, Template, Templates};
-use acp_thread::{MentionUri, UserMessageId};
+use crate::{
+    CompactionPromptTemplate, ContextServerRegistry, CopyPathTool, CreateDirectoryTool,
+    DbLanguageModel, DbThread, DbTrackedBuffer, DelegateTool, DeletePathTool, DiagnosticsTool,
+    EditFileTool, FetchTool, FindPathTool, GrepTool, ListDirectoryTool, MovePathTool, NowTool,
+    OpenTool, ReadFileTool, SystemPromptTemplate, Template, Templates, TerminalTool, ThinkingTool,
+    ToolPermissionGrant, WebSearchTool, title_for_messages, tool_permission,
+};
+use acp_thread::{AcpThread, MentionUri, UserMessageId};
 use action_log::ActionLog;
 use agent_client_protocol as acp;
-use agent_settings::{AgentProfileId, AgentSettings, CompletionMode};
+use agent_settings::{
+    AgentProfileId, AgentProfileSettings, AgentSettings, CompletionMode, ToolPermission,
+};
 use anyhow::{Context as _, Result, anyhow};
 use assistant_tool::adapt_schema_to_format;
+use chrono::Utc;
 use cloud_llm_client::{CompletionIntent, CompletionRequestStatus};
-use collections::IndexMap;
+use collections::{HashMap, HashSet, IndexMap};
+use context_server::ContextServerId;
 use fs::Fs;
 use futures::{
     channel::{mpsc, oneshot},
     stream::FuturesUnordered,
 };
-use gpui::{App, Context, Entity, SharedString, Task};
+use gpui::{App, Context, Entity, SharedString, Subscription, Task};
 use language_model::{
-    LanguageModel, LanguageModelCompletionEvent, LanguageModelImage, LanguageModelProviderId,
-    LanguageModelRequest, LanguageModelRequestMessage, LanguageModelRequestTool,
-    LanguageModelToolResult, LanguageModelToolResultContent, LanguageModelToolSchemaFormat,
-    LanguageModelToolUse, LanguageModelToolUseId, Role, StopReason,
+    LanguageModel, LanguageModelCompletionEvent, LanguageModelExt as _, LanguageModelImage,
+    LanguageModelProviderId, LanguageModelRegistry, LanguageModelRequest,
+    LanguageModelRequestMessage, LanguageModelRequestTool, LanguageModelToolResult,
+    LanguageModelToolResultContent, LanguageModelToolSchemaFormat, LanguageModelToolUse,
+    LanguageModelToolUseId, Role, StopReason, TokenUsage, UsageLedger, UsageRecord, UsageSource,
 };
-use project::Project;
+use project::context_server_store::{self, ContextServerStore};
+use project::{Project, ProjectItem as _};
 use prompt_store::ProjectContext;
 use schemars::{JsonSchema, Schema};
 use serde::{Deserialize, Serialize};
 use settings::{Settings, update_settings_file};
 use smol::stream::StreamExt;
-use std::{cell::RefCell, collections::BTreeMap, path::Path, rc::Rc, sync::Arc};
+use std::{cell::RefCell, collections::BTreeMap, mem, path::Path, rc::Rc, sync::Arc};
 use std::{fmt::Write, ops::Range};
 use util::{ResultExt, markdown::MarkdownCodeBlock};
 use uuid::Uuid;
 
+/// The fraction of the context window after which older turns get compacted.
+const COMPACTION_THRESHOLD: f64 = 0.8;
+/// How many tool results to carry over into a compacted message.
+const MAX_COMPACTED_TOOL_RESULTS: usize = 8;
+/// Tool results longer than this are too expensive to carry over into a compacted message.
+const MAX_COMPACTED_TOOL_RESULT_LEN: usize = 8 * 1024;
+
 #[derive(
     Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Serialize, Deserialize, JsonSchema,
 )]
@@ -71,11 +90,12 @@ impl std::fmt::Display for PromptId {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Message {
     User(UserMessage),
     Agent(AgentMessage),
     Resume,
+    Compacted(CompactedMessage),
 }
 
 impl Message {
@@ -91,17 +111,80 @@ impl Message {
             Message::User(message) => message.to_markdown(),
             Message::Agent(message) => message.to_markdown(),
             Message::Resume => "[resumed after tool use limit was reached]".into(),
+            Message::Compacted(message) => message.to_markdown(),
+        }
+    }
+}
+
+/// Stands in for every message before it when building requests, after older turns were
+/// summarized to free up space in the context window.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompactedMessage {
+    pub summary: String,
+    /// Paths of the files the agent read or edited before compaction.
+    pub touched_paths: Vec<String>,
+    /// Tool results about touched files that are carried over verbatim.
+    pub tool_results: Vec<LanguageModelToolResult>,
+}
+
+impl CompactedMessage {
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::from("## Summary\n\n");
+        markdown.push_str(&self.summary);
+        markdown.push('\n');
+        for tool_result in &self.tool_results {
+            if let LanguageModelToolResultContent::Text(text) = &tool_result.content {
+                let _ = write!(
+                    &mut markdown,
+                    "\n**Tool Result**: {} (ID: {})\n\n{}\n",
+                    tool_result.tool_name, tool_result.tool_use_id, text
+                );
+            }
+        }
+        markdown
+    }
+
+    fn to_request(&self) -> LanguageModelRequestMessage {
+        let mut text = String::from(
+            "<compacted_conversation>\n\
+            The earlier part of this conversation was summarized to save space:\n\n",
+        );
+        text.push_str(&self.summary);
+        text.push('\n');
+
+        if !self.touched_paths.is_empty() {
+            text.push_str("\nFiles read or edited so far:\n");
+            for path in &self.touched_paths {
+                let _ = writeln!(&mut text, "- {path}");
+            }
+        }
+
+        for tool_result in &self.tool_results {
+            if let LanguageModelToolResultContent::Text(content) = &tool_result.content {
+                let _ = write!(
+                    &mut text,
+                    "\nEarlier result of the {} tool:\n{}\n",
+                    tool_result.tool_name, content
+                );
+            }
+        }
+        text.push_str("</compacted_conversation>");
+
+        LanguageModelRequestMessage {
+            role: Role::User,
+            content: vec![text.into()],
+            cache: false,
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct UserMessage {
     pub id: UserMessageId,
     pub content: Vec<UserMessageContent>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum UserMessageContent {
     Text(String),
     Mention { uri: MentionUri, content: String },
@@ -149,6 +232,7 @@ impl UserMessage {
         const OPEN_SYMBOLS_TAG: &str = "<symbols>";
         const OPEN_THREADS_TAG: &str = "<threads>";
         const OPEN_FETCH_TAG: &str = "<fetched_urls>";
+        const OPEN_RESOURCES_TAG: &str = "<mcp_resources>";
         const OPEN_RULES_TAG: &str =
             "<rules>\nThe user has specified the following rules that should be applied:\n";
 
@@ -156,6 +240,7 @@ impl UserMessage {
         let mut symbol_context = OPEN_SYMBOLS_TAG.to_string();
         let mut thread_context = OPEN_THREADS_TAG.to_string();
         let mut fetch_context = OPEN_FETCH_TAG.to_string();
+        let mut resource_context = OPEN_RESOURCES_TAG.to_string();
         let mut rules_context = OPEN_RULES_TAG.to_string();
 
         for chunk in &self.content {
@@ -215,6 +300,14 @@ impl UserMessage {
                         MentionUri::Fetch { url } => {
                             write!(&mut fetch_context, "\nFetch: {}\n\n{}", url, content).ok();
                         }
+                        MentionUri::McpResource { server_id, uri, .. } => {
+                            write!(
+                                &mut resource_context,
+                                "\nResource: {} (from {})\n\n{}",
+                                uri, server_id, content
+                            )
+                            .ok();
+                        }
                     }
 
                     language_model::MessageContent::Text(uri.as_link().to_string())
@@ -254,6 +347,13 @@ impl UserMessage {
                 .push(language_model::MessageContent::Text(fetch_context));
         }
 
+        if resource_context.len() > OPEN_RESOURCES_TAG.len() {
+            resource_context.push_str("</mcp_resources>\n");
+            message
+                .content
+                .push(language_model::MessageContent::Text(resource_context));
+        }
+
         if rules_context.len() > OPEN_RULES_TAG.len() {
             rules_context.push_str("</user_rules>\n");
             message
@@ -418,13 +518,13 @@ impl AgentMessage {
     }
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AgentMessage {
     pub content: Vec<AgentMessageContent>,
     pub tool_results: IndexMap<LanguageModelToolUseId, LanguageModelToolResult>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AgentMessageContent {
     Text(String),
     Thinking {
@@ -464,14 +564,30 @@ pub struct Thread {
     running_turn: Option<Task<()>>,
     pending_message: Option<AgentMessage>,
     tools: BTreeMap<SharedString, Arc<dyn AnyAgentTool>>,
+    /// When set, only these tools are offered to the model, on top of the profile's settings.
+    tool_allowlist: Option<HashSet<SharedString>>,
     tool_use_limit_reached: bool,
     context_server_registry: Entity<ContextServerRegistry>,
     profile_id: AgentProfileId,
+    /// The profiles of the threads that delegated a task to this one, whose restrictions apply to
+    /// this thread too.
+    restricting_profile_ids: Vec<AgentProfileId>,
     project_context: Rc<RefCell<ProjectContext>>,
     templates: Arc<Templates>,
     model: Arc<dyn LanguageModel>,
     project: Entity<Project>,
     action_log: Entity<ActionLog>,
+    /// The token usage reported for the latest completion request.
+    latest_token_usage: Option<TokenUsage>,
+    /// The token usage of every completion request in this thread, added up.
+    cumulative_token_usage: TokenUsage,
+    /// The token usage reported so far for the completion request in progress.
+    request_token_usage: TokenUsage,
+    /// MCP resources mentioned in this thread, which we're subscribed to.
+    mcp_resources: HashMap<(ContextServerId, String), MentionUri>,
+    /// The latest contents of subscribed MCP resources that changed since they were last sent.
+    updated_mcp_resources: HashMap<(ContextServerId, String), String>,
+    _context_server_store_subscription: Subscription,
 }
 
 impl Thread {
@@ -485,6 +601,19 @@ impl Thread {
         cx: &mut Context<Self>,
     ) -> Self {
         let profile_id = AgentSettings::get_global(cx).default_profile.clone();
+        let context_server_store = project.read(cx).context_server_store();
+        let context_server_store_subscription = cx.subscribe(
+            &context_server_store,
+            Self::handle_context_server_store_event,
+        );
+        cx.on_release(move |this: &mut Self, cx| {
+            context_server_store.update(cx, |store, cx| {
+                for (server_id, uri) in this.mcp_resources.drain().map(|(key, _)| key) {
+                    store.unsubscribe_from_resource(server_id, uri, cx);
+                }
+            });
+        })
+        .detach();
         Self {
             id: ThreadId::new(),
             prompt_id: PromptId::new(),
@@ -493,17 +622,257 @@ impl Thread {
             running_turn: None,
             pending_message: None,
             tools: BTreeMap::default(),
+            tool_allowlist: None,
             tool_use_limit_reached: false,
             context_server_registry,
             profile_id,
+            restricting_profile_ids: Vec::new(),
             project_context,
             templates,
             model,
             project,
             action_log,
+            latest_token_usage: None,
+            cumulative_token_usage: TokenUsage::default(),
+            request_token_usage: TokenUsage::default(),
+            mcp_resources: HashMap::default(),
+            updated_mcp_resources: HashMap::default(),
+            _context_server_store_subscription: context_server_store_subscription,
+        }
+    }
+
+    /// Restores a thread that was saved with [`Self::to_db`].
+    pub fn from_db(
+        id: ThreadId,
+        db_thread: DbThread,
+        project: Entity<Project>,
+        project_context: Rc<RefCell<ProjectContext>>,
+        context_server_registry: Entity<ContextServerRegistry>,
+        action_log: Entity<ActionLog>,
+        templates: Arc<Templates>,
+        model: Arc<dyn LanguageModel>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let mut this = Self::new(
+            project,
+            project_context,
+            context_server_registry,
+            action_log,
+            templates,
+            model,
+            cx,
+        );
+        this.id = id;
+        this.messages = db_thread.messages;
+        if let Some(profile_id) = db_thread.profile {
+            this.profile_id = profile_id;
+        }
+        if let Some(completion_mode) = db_thread.completion_mode {
+            this.completion_mode = completion_mode;
+        }
+        this.latest_token_usage = db_thread.latest_token_usage;
+        this.restore_tracked_buffers(db_thread.tracked_buffers, cx);
+        this
+    }
+
+    fn restore_tracked_buffers(
+        &self,
+        tracked_buffers: Vec<DbTrackedBuffer>,
+        cx: &mut Context<Self>,
+    ) {
+        for tracked_buffer in tracked_buffers {
+            let Some(project_path) = self
+                .project
+                .read(cx)
+                .project_path_for_absolute_path(&tracked_buffer.path, cx)
+            else {
+                continue;
+            };
+            let open_buffer = self
+                .project
+                .update(cx, |project, cx| project.open_buffer(project_path, cx));
+            let action_log = self.action_log.clone();
+            cx.spawn(async move |_, cx| {
+                let buffer = open_buffer.await?;
+                action_log.update(cx, |action_log, cx| {
+                    action_log.restore_buffer(buffer, tracked_buffer.diff_base, cx)
+                })
+            })
+            .detach_and_log_err(cx);
         }
     }
 
+    /// Captures everything needed to resume the thread after a restart.
+    pub fn to_db(&self, cx: &App) -> DbThread {
+        let project = self.project.read(cx);
+        let action_log = self.action_log.read(cx);
+        let tracked_buffers = action_log
+            .tracked_buffers()
+            .filter_map(|buffer| {
+                let project_path = buffer.read(cx).project_path(cx)?;
+                Some(DbTrackedBuffer {
+                    path: project.absolute_path(&project_path, cx)?,
+                    diff_base: action_log.unreviewed_diff_base(buffer, cx),
+                })
+            })
+            .collect();
+
+        DbThread {
+            title: self.title(),
+            messages: self.messages.clone(),
+            updated_at: Utc::now(),
+            worktree_paths: project
+                .visible_worktrees(cx)
+                .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
+                .collect(),
+            model: Some(DbLanguageModel {
+                provider: self.model.provider_id().0.to_string(),
+                model: self.model.id().0.to_string(),
+            }),
+            profile: Some(self.profile_id.clone()),
+            completion_mode: Some(self.completion_mode),
+            latest_token_usage: self.latest_token_usage,
+            tracked_buffers,
+        }
+    }
+
+    /// Shows the thread's messages in an [`AcpThread`], e.g. after restoring it.
+    pub fn replay(&self, acp_thread: &mut AcpThread, cx: &mut Context<AcpThread>) -> Result<()> {
+        for message in &self.messages {
+            match message {
+                Message::User(message) => {
+                    for content in &message.content {
+                        acp_thread.push_user_content_block(
+                            Some(message.id.clone()),
+                            content.to_acp(),
+                            cx,
+                        );
+                    }
+                }
+                Message::Agent(message) => {
+                    for content in &message.content {
+                        match content {
+                            AgentMessageContent::Text(text) => {
+                                acp_thread.push_assistant_content_block(text_block(text), false, cx)
+                            }
+                            AgentMessageContent::Thinking { text, .. } => {
+                                acp_thread.push_assistant_content_block(text_block(text), true, cx)
+                            }
+                            AgentMessageContent::ToolUse(tool_use) => acp_thread.upsert_tool_call(
+                                self.replayed_tool_call(
+                                    tool_use,
+                                    message.tool_results.get(&tool_use.id),
+                                ),
+                                cx,
+                            )?,
+                            AgentMessageContent::RedactedThinking(_)
+                            | AgentMessageContent::Image(_) => {}
+                        }
+                    }
+                }
+                Message::Resume | Message::Compacted(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn replayed_tool_call(
+        &self,
+        tool_use: &LanguageModelToolUse,
+        tool_result: Option<&LanguageModelToolResult>,
+    ) -> acp::ToolCall {
+        let (title, kind) = match self.tools.get(tool_use.name.as_ref()) {
+            Some(tool) => (tool.initial_title(tool_use.input.clone()), tool.kind()),
+            None => (SharedString::from(&tool_use.name), acp::ToolKind::Other),
+        };
+        let mut tool_call = AgentResponseEventStream::initial_tool_call(
+            &tool_use.id,
+            title.to_string(),
+            kind,
+            tool_use.input.clone(),
+        );
+        if let Some(tool_result) = tool_result {
+            tool_call.status = if tool_result.is_error {
+                acp::ToolCallStatus::Failed
+            } else {
+                acp::ToolCallStatus::Completed
+            };
+            if let LanguageModelToolResultContent::Text(text) = &tool_result.content {
+                tool_call.content = vec![text.to_string().into()];
+            }
+            tool_call.raw_output = tool_result.output.clone();
+        }
+        tool_call
+    }
+
+    fn handle_context_server_store_event(
+        &mut self,
+        context_server_store: Entity<ContextServerStore>,
+        event: &context_server_store::Event,
+        cx: &mut Context<Self>,
+    ) {
+        let context_server_store::Event::ResourceUpdated { server_id, uri } = event else {
+            return;
+        };
+        let key = (server_id.clone(), uri.clone());
+        if !self.mcp_resources.contains_key(&key) {
+            return;
+        }
+
+        let read_task = context_server_store
+            .read(cx)
+            .read_resource(server_id, uri, cx);
+        cx.spawn(async move |this, cx| {
+            let content = read_task.await?;
+            this.update(cx, |this, _| {
+                this.updated_mcp_resources.insert(key, content);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Subscribes to the MCP resources mentioned in `content`, and appends the latest contents of
+    /// previously mentioned resources that changed since they were last sent.
+    fn track_mcp_resources(
+        &mut self,
+        content: &mut Vec<UserMessageContent>,
+        cx: &mut Context<Self>,
+    ) {
+        for chunk in content.iter() {
+            let UserMessageContent::Mention {
+                uri: mention @ MentionUri::McpResource { server_id, uri, .. },
+                ..
+            } = chunk
+            else {
+                continue;
+            };
+            let key = (ContextServerId(server_id.as_str().into()), uri.clone());
+            self.updated_mcp_resources.remove(&key);
+            if self.mcp_resources.contains_key(&key) {
+                continue;
+            }
+            self.project
+                .read(cx)
+                .context_server_store()
+                .update(cx, |store, cx| {
+                    store.subscribe_to_resource(key.0.clone(), key.1.clone(), cx)
+                });
+            self.mcp_resources.insert(key, mention.clone());
+        }
+
+        let mcp_resources = &self.mcp_resources;
+        content.extend(
+            self.updated_mcp_resources
+                .drain()
+                .filter_map(|(key, content)| {
+                    Some(UserMessageContent::Mention {
+                        uri: mcp_resources.get(&key)?.clone(),
+                        content,
+                    })
+                }),
+        );
+    }
+
     pub fn project(&self) -> &Entity<Project> {
         &self.project
     }
@@ -512,6 +881,19 @@ impl Thread {
         &self.action_log
     }
 
+    pub fn id(&self) -> &ThreadId {
+        &self.id
+    }
+
+    pub fn messages(&self) -> &[Message] {
+        &self.messages
+    }
+
+    /// Names the thread after its first user message.
+    pub fn title(&self) -> SharedString {
+        title_for_messages(&self.messages)
+    }
+
     pub fn model(&self) -> &Arc<dyn LanguageModel> {
         &self.model
     }
@@ -520,6 +902,10 @@ impl Thread {
         self.model = model;
     }
 
+    pub fn cumulative_token_usage(&self) -> TokenUsage {
+        self.cumulative_token_usage
+    }
+
     pub fn completion_mode(&self) -> CompletionMode {
         self.completion_mode
     }
@@ -541,6 +927,112 @@ impl Thread {
         self.tools.insert(tool.name(), tool.erase());
     }
 
+    /// Adds the built-in tools that every native agent thread gets.
+    pub fn add_default_tools(&mut self, cx: &mut Context<Self>) {
+        let project = self.project.clone();
+        let action_log = self.action_log.clone();
+        self.add_tool(CopyPathTool::new(project.clone()));
+        self.add_tool(CreateDirectoryTool::new(project.clone()));
+        self.add_tool(DelegateTool::new(cx.weak_entity()));
+        self.add_tool(DeletePathTool::new(project.clone(), action_log.clone()));
+        self.add_tool(DiagnosticsTool::new(project.clone()));
+        self.add_tool(EditFileTool::new(cx.entity()));
+        self.add_tool(FetchTool::new(project.read(cx).client().http_client()));
+        self.add_tool(FindPathTool::new(project.clone()));
+        self.add_tool(GrepTool::new(project.clone()));
+        self.add_tool(ListDirectoryTool::new(project.clone()));
+        self.add_tool(MovePathTool::new(project.clone()));
+        self.add_tool(NowTool);
+        self.add_tool(OpenTool::new(project.clone()));
+        self.add_tool(ReadFileTool::new(project.clone(), action_log));
+        self.add_tool(TerminalTool::new(project, cx));
+        self.add_tool(ThinkingTool);
+        self.add_tool(WebSearchTool); // TODO: Enable this only if it's a zed model.
+    }
+
+    /// Creates a thread that works on a delegated task in its own context window.
+    ///
+    /// The sub-agent shares this thread's project, model and action log, so its edits show up
+    /// for review like any other. It can only use the tools that this thread can use, further
+    /// narrowed down by `profile_id` and `tools`, and it can't delegate tasks itself.
+    pub fn new_subagent(
+        &self,
+        profile_id: Option<AgentProfileId>,
+        tools: Option<HashSet<SharedString>>,
+        cx: &mut Context<Self>,
+    ) -> Result<Entity<Thread>> {
+        let profile_id = profile_id.unwrap_or_else(|| self.profile_id.clone());
+        anyhow::ensure!(
+            AgentSettings::get_global(cx)
+                .profiles
+                .contains_key(&profile_id),
+            "there is no profile named {profile_id}"
+        );
+        let mut allowed_tools = self
+            .tools(cx)?
+            .map(|tool| tool.name())
+            .filter(|name| name != DelegateTool::NAME)
+            .collect::<HashSet<_>>();
+        if let Some(tools) = tools {
+            allowed_tools.retain(|name| tools.contains(name));
+        }
+
+        Ok(cx.new(|cx| {
+            let mut thread = Thread::new(
+                self.project.clone(),
+                self.project_context.clone(),
+                self.context_server_registry.clone(),
+                self.action_log.clone(),
+                self.templates.clone(),
+                self.model.clone(),
+                cx,
+            );
+            thread.tools = self
+                .tools
+                .iter()
+                .filter(|(name, _)| allowed_tools.contains(*name))
+                .map(|(name, tool)| (name.clone(), tool.clone()))
+                .collect();
+            // The edit tool works off the conversation of the thread it belongs to.
+            if thread.tools.contains_key(EditFileTool::NAME) {
+                thread.add_tool(EditFileTool::new(cx.entity()));
+            }
+            thread.profile_id = profile_id;
+            thread.restricting_profile_ids = self
+                .restricting_profile_ids
+                .iter()
+                .cloned()
+                .chain([self.profile_id.clone()])
+                .collect();
+            thread.completion_mode = self.completion_mode;
+            thread.tool_allowlist = Some(allowed_tools);
+            thread
+        }))
+    }
+
+    /// Whether the given tool call delegated a task to a sub-agent.
+    pub fn is_delegation(&self, tool_call_id: &acp::ToolCallId) -> bool {
+        self.messages
+            .iter()
+            .filter_map(Message::as_agent_message)
+            .chain(self.pending_message.as_ref())
+            .flat_map(|message| &message.content)
+            .any(|content| {
+                matches!(
+                    content,
+                    AgentMessageContent::ToolUse(tool_use)
+                        if tool_use.name.as_ref() == DelegateTool::NAME
+                            && tool_use.id.to_string() == tool_call_id.0.as_ref()
+                )
+            })
+    }
+
+    fn is_tool_allowed(&self, name: &str) -> bool {
+        self.tool_allowlist
+            .as_ref()
+            .is_none_or(|allowlist| allowlist.contains(name))
+    }
+
     pub fn remove_tool(&mut self, name: &str) -> bool {
         self.tools.remove(name).is_some()
     }
@@ -567,9 +1059,205 @@ impl Thread {
             return Err(anyhow!("Message not found"));
         };
         self.messages.truncate(position);
+        self.latest_token_usage = None;
         Ok(())
     }
 
+    /// Summarizes every turn before the latest user message into a [`CompactedMessage`], which
+    /// replaces them in subsequent requests.
+    pub fn compact(&mut self, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let Some(range) = self.compaction_range() else {
+            return Task::ready(Err(anyhow!("there are no earlier messages to compact")));
+        };
+        let Some(Message::User(boundary)) = self.messages.get(range.end) else {
+            return Task::ready(Err(anyhow!("compaction must end at a user message")));
+        };
+        let boundary_id = boundary.id.clone();
+
+        let model = LanguageModelRegistry::read_global(cx)
+            .thread_summary_model()
+            .map(|configured| configured.model)
+            .unwrap_or_else(|| self.model.clone());
+        let touched_paths = self.touched_paths(cx);
+        let tool_results = self.compacted_tool_results(range.clone(), &touched_paths);
+        let prompt = match (CompactionPromptTemplate {
+            touched_paths: touched_paths.clone(),
+        })
+        .render(&self.templates)
+        {
+            Ok(prompt) => prompt,
+            Err(error) => return Task::ready(Err(error)),
+        };
+
+        // Keep the most recent part of the conversation if it doesn't fit in the summary model.
+        let mut conversation = self.messages[range]
+            .iter()
+            .map(Message::to_markdown)
+            .collect::<Vec<_>>()
+            .join("\n");
+        let max_len = (model.max_token_count() as usize).saturating_mul(3);
+        if conversation.len() > max_len {
+            let mut start = conversation.len() - max_len;
+            while !conversation.is_char_boundary(start) {
+                start += 1;
+            }
+            conversation.replace_range(..start, "");
+        }
+
+        let request = LanguageModelRequest {
+            thread_id: Some(self.id.to_string()),
+            prompt_id: Some(self.prompt_id.to_string()),
+            intent: Some(CompletionIntent::ThreadContextSummarization),
+            messages: vec![LanguageModelRequestMessage {
+                role: Role::User,
+                content: vec![
+                    format!("<conversation>\n{conversation}\n</conversation>").into(),
+                    prompt.into(),
+                ],
+                cache: false,
+            }],
+            ..Default::default()
+        };
+
+        cx.spawn(async move |this, cx| {
+            cx.update(|cx| UsageLedger::check_global_budget(cx))??;
+            let mut response = model.stream_completion_text(request, cx).await?;
+            let mut summary = String::new();
+            while let Some(chunk) = response.stream.next().await {
+                summary.push_str(&chunk?);
+            }
+            let usage = *response.last_token_usage.lock();
+            this.update(cx, |this, cx| this.record_usage(&model, usage, cx))?;
+            anyhow::ensure!(
+                !summary.trim().is_empty(),
+                "the model returned an empty summary"
+            );
+
+            this.update(cx, |this, cx| {
+                let position = this
+                    .messages
+                    .iter()
+                    .position(|message| {
+                        matches!(message, Message::User(UserMessage { id, .. }) if id == &boundary_id)
+                    })
+                    .context("the thread was truncated while it was being compacted")?;
+                this.messages.insert(
+                    position,
+                    Message::Compacted(CompactedMessage {
+                        summary: summary.trim().to_string(),
+                        touched_paths,
+                        tool_results,
+                    }),
+                );
+                this.latest_token_usage = None;
+                cx.notify();
+                Ok(())
+            })?
+        })
+    }
+
+    /// Returns whether the latest request used enough of the context window that older turns
+    /// should be compacted before sending the next one.
+    pub fn needs_compaction(&self) -> bool {
+        let Some(usage) = self.latest_token_usage else {
+            return false;
+        };
+        let max_tokens = self
+            .model
+            .max_token_count_for_mode(self.completion_mode.into());
+        usage.total_tokens() as f64 >= max_tokens as f64 * COMPACTION_THRESHOLD
+            && self.compaction_range().is_some()
+    }
+
+    /// The IDs of the user messages that directly follow a compacted message, i.e. where the
+    /// context the model sees starts over.
+    pub fn compaction_boundaries(&self) -> impl Iterator<Item = &UserMessageId> {
+        self.messages.windows(2).filter_map(|pair| match pair {
+            [Message::Compacted(_), Message::User(message)] => Some(&message.id),
+            _ => None,
+        })
+    }
+
+    /// The messages that would be summarized by [`Self::compact`]: everything from the latest
+    /// compacted message up to, but excluding, the latest user message.
+    fn compaction_range(&self) -> Option<Range<usize>> {
+        let end = self
+            .messages
+            .iter()
+            .rposition(|message| matches!(message, Message::User(_)))?;
+        let start = self.messages[..end]
+            .iter()
+            .rposition(|message| matches!(message, Message::Compacted(_)));
+        let first_uncompacted = start.map_or(0, |start| start + 1);
+        (end > first_uncompacted).then(|| start.unwrap_or(0)..end)
+    }
+
+    fn touched_paths(&self, cx: &App) -> Vec<String> {
+        let mut paths = self
+            .action_log
+            .read(cx)
+            .tracked_buffers()
+            .filter_map(|buffer| {
+                let file = buffer.read(cx).file()?;
+                Some(file.full_path(cx).to_string_lossy().into_owned())
+            })
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths.dedup();
+        paths
+    }
+
+    /// Picks the latest successful tool result for each touched path, so the model doesn't
+    /// need to call those tools again after compaction.
+    fn compacted_tool_results(
+        &self,
+        range: Range<usize>,
+        touched_paths: &[String],
+    ) -> Vec<LanguageModelToolResult> {
+        let mut seen_paths = Vec::new();
+        let mut tool_results = Vec::new();
+        for message in self.messages[range].iter().rev() {
+            let Message::Agent(message) = message else {
+                continue;
+            };
+            for content in message.content.iter().rev() {
+                let AgentMessageContent::ToolUse(tool_use) = content else {
+                    continue;
+                };
+                let Some(path) = tool_use.input.get("path").and_then(|path| path.as_str()) else {
+                    continue;
+                };
+                if seen_paths.iter().any(|seen| seen == path)
+                    || !touched_paths.iter().any(|touched| touched == path)
+                {
+                    continue;
+                }
+                let Some(tool_result) = message.tool_results.get(&tool_use.id) else {
+                    continue;
+                };
+                let LanguageModelToolResultContent::Text(text) = &tool_result.content else {
+                    continue;
+                };
+                if tool_result.is_error || text.len() > MAX_COMPACTED_TOOL_RESULT_LEN {
+                    continue;
+                }
+                seen_paths.push(path.to_string());
+                tool_results.push(LanguageModelToolResult {
+                    output: None,
+                    ..tool_result.clone()
+                });
+                if tool_results.len() == MAX_COMPACTED_TOOL_RESULTS {
+                    break;
+                }
+            }
+            if tool_results.len() == MAX_COMPACTED_TOOL_RESULTS {
+                break;
+            }
+        }
+        tool_results.reverse();
+        tool_results
+    }
+
     pub fn resume(
         &mut self,
         cx: &mut Context<Self>,
@@ -601,7 +1289,8 @@ impl Thread {
         log::info!("Thread::send called with model: {:?}", self.model.name());
         self.advance_prompt_id();
 
-        let content = content.into_iter().map(Into::into).collect::<Vec<_>>();
+        let mut content = content.into_iter().map(Into::into).collect::<Vec<_>>();
+        self.track_mcp_resources(&mut content, cx);
         log::debug!("Thread::send content: {:?}", content);
 
         self.messages
@@ -619,18 +1308,25 @@ impl Thread {
         let model = self.model.clone();
         let (events_tx, events_rx) = mpsc::unbounded::<Result<AgentResponseEvent>>();
         let event_stream = AgentResponseEventStream(events_tx);
-        let message_ix = self.messages.len().saturating_sub(1);
         self.tool_use_limit_reached = false;
         self.running_turn = Some(cx.spawn(async move |this, cx| {
             log::info!("Starting agent turn execution");
             let turn_result: Result<()> = async {
                 let mut completion_intent = CompletionIntent::UserPrompt;
                 loop {
+                    if this.read_with(cx, |this, _| this.needs_compaction())? {
+                        log::info!("Compacting older turns before they exceed the context window");
+                        let compaction = this.update(cx, |this, cx| this.compact(cx))?;
+                        compaction.await.log_err();
+                    }
+
                     log::debug!(
                         "Building completion request with intent: {:?}",
                         completion_intent
                     );
+                    cx.update(|cx| UsageLedger::check_global_budget(cx))??;
                     let request = this.update(cx, |this, cx| {
+                        this.request_token_usage = TokenUsage::default();
                         this.build_completion_request(completion_intent, cx)
                     })?;
 
@@ -652,7 +1348,7 @@ impl Thread {
                                 if reason == StopReason::Refusal {
                                     this.update(cx, |this, _cx| {
                                         this.flush_pending_message();
-                                        this.messages.truncate(message_ix);
+                                        this.truncate_turn();
                                     })?;
                                     return Ok(());
                                 }
@@ -671,6 +1367,8 @@ impl Thread {
                         }
                     }
 
+                    this.update(cx, |this, cx| this.record_request_usage(cx))?;
+
                     let used_tools = tool_uses.is_empty();
                     while let Some(tool_result) = tool_uses.next().await {
                         log::info!("Tool finished {:?}", tool_result);
@@ -710,7 +1408,12 @@ impl Thread {
             }
             .await;
 
-            this.update(cx, |this, _| this.flush_pending_message()).ok();
+            this.update(cx, |this, cx| {
+                // Requests that failed or were refused still used tokens.
+                this.record_request_usage(cx);
+                this.flush_pending_message();
+            })
+            .ok();
             if let Err(error) = turn_result {
                 log::error!("Turn execution failed: {:?}", error);
                 event_stream.send_error(error);
@@ -721,11 +1424,37 @@ impl Thread {
         events_rx
     }
 
+    /// Records the usage of the completion request in progress in the usage ledger, once.
+    fn record_request_usage(&mut self, cx: &mut App) {
+        let usage = mem::take(&mut self.request_token_usage);
+        if usage != TokenUsage::default() {
+            self.record_usage(&self.model, usage, cx);
+        }
+    }
+
+    fn record_usage(&self, model: &Arc<dyn LanguageModel>, usage: TokenUsage, cx: &mut App) {
+        UsageLedger::record_global(
+            UsageRecord {
+                source: UsageSource::AgentThread,
+                thread_id: Some(self.id.to_string()),
+                provider: model.provider_id(),
+                model: model.id(),
+                usage,
+            },
+            cx,
+        );
+    }
+
     pub fn build_system_message(&self) -> LanguageModelRequestMessage {
         log::debug!("Building system message");
         let prompt = SystemPromptTemplate {
             project: &self.project_context.borrow(),
-            available_tools: self.tools.keys().cloned().collect(),
+            available_tools: self
+                .tools
+                .keys()
+                .filter(|name| self.is_tool_allowed(name))
+                .cloned()
+                .collect(),
         }
         .render(&self.templates)
         .context("failed to build system prompt")
@@ -776,7 +1505,15 @@ impl Thread {
                     json_parse_error,
                 )));
             }
-            UsageUpdate(_) | StatusUpdate(_) => {}
+            UsageUpdate(usage) => {
+                // Providers report the usage of the whole request so far, so only add the
+                // difference to the previous report.
+                self.cumulative_token_usage =
+                    self.cumulative_token_usage + usage - self.request_token_usage;
+                self.request_token_usage = usage;
+                self.latest_token_usage = Some(usage);
+            }
+            StatusUpdate(_) => {}
             Stop(_) => unreachable!(),
         }
 
@@ -899,15 +1636,62 @@ impl Thread {
             }));
         };
 
+        let profile = self.profile_settings(cx);
+        let permission = profile
+            .as_ref()
+            .and_then(|profile| tool_permission(profile, &tool_use.name, &tool_use.input));
+        if permission == Some(ToolPermission::Deny) {
+            let content = format!(
+                "Calling {} with this input is denied by the user's tool permissions",
+                tool_use.name
+            );
+            return Some(Task::ready(LanguageModelToolResult {
+                content: LanguageModelToolResultContent::Text(Arc::from(content)),
+                tool_use_id: tool_use.id,
+                tool_name: tool_use.name,
+                is_error: true,
+                output: None,
+            }));
+        }
+
         let fs = self.project.read(cx).fs().clone();
-        let tool_event_stream =
+        let mut tool_event_stream =
             ToolCallEventStream::new(tool_use.id.clone(), event_stream.clone(), Some(fs));
+        if let Some(profile) = profile {
+            tool_event_stream.profile = Some(profile);
+            // "Always Allow" adds to this thread's own profile, not to the restrictions on it.
+            if let Some(own_profile) = AgentSettings::get_global(cx).profiles.get(&self.profile_id)
+            {
+                tool_event_stream.grant = ToolPermissionGrant::new(
+                    self.profile_id.clone(),
+                    own_profile.clone(),
+                    &tool_use.name,
+                    &tool_use.input,
+                );
+            }
+        }
         tool_event_stream.update_fields(acp::ToolCallUpdateFields {
             status: Some(acp::ToolCallStatus::InProgress),
             ..Default::default()
         });
         let supports_images = self.model.supports_images();
-        let tool_result = tool.run(tool_use.input, tool_event_stream, cx);
+        let tool_result = match permission {
+            Some(ToolPermission::Allow) => {
+                tool.run(tool_use.input, tool_event_stream.pre_authorized(), cx)
+            }
+            // The rules require asking even when the tool wouldn't, so ask before running it.
+            Some(ToolPermission::Ask) => {
+                let authorize =
+                    tool_event_stream.authorize(tool.initial_title(tool_use.input.clone()), cx);
+                let input = tool_use.input.clone();
+                cx.spawn(async move |_, cx| {
+                    authorize.await?;
+                    cx.update(|cx| tool.run(input, tool_event_stream.pre_authorized(), cx))?
+                        .await
+                })
+            }
+            Some(ToolPermission::Deny) | None => tool.run(tool_use.input, tool_event_stream, cx),
+        };
         log::info!("Running tool {}", tool_use.name);
         Some(cx.foreground_executor().spawn(async move {
             let tool_result = tool_result.await.and_then(|output| {
@@ -961,6 +1745,20 @@ impl Thread {
         self.pending_message.get_or_insert_default()
     }
 
+    /// Removes the message that started the running turn, and everything after it.
+    ///
+    /// The message is looked up rather than remembered by index, since compacting, which can
+    /// finish while the turn is running, inserts a message before it.
+    fn truncate_turn(&mut self) {
+        if let Some(turn_start) = self
+            .messages
+            .iter()
+            .rposition(|message| matches!(message, Message::User(_) | Message::Resume))
+        {
+            self.messages.truncate(turn_start);
+        }
+    }
+
     fn flush_pending_message(&mut self) {
         let Some(mut message) = self.pending_message.take() else {
             return;
@@ -1039,28 +1837,42 @@ impl Thread {
         request
     }
 
+    /// The settings of this thread's profile, restricted by the profiles of the threads that
+    /// delegated to it.
+    fn profile_settings(&self, cx: &App) -> Option<AgentProfileSettings> {
+        let profiles = &AgentSettings::get_global(cx).profiles;
+        let mut profile = profiles.get(&self.profile_id)?.clone();
+        for profile_id in &self.restricting_profile_ids {
+            profile.restrict_with(profiles.get(profile_id)?.clone());
+        }
+        Some(profile)
+    }
+
     fn tools<'a>(&'a self, cx: &'a App) -> Result<impl Iterator<Item = &'a Arc<dyn AnyAgentTool>>> {
-        let profile = AgentSettings::get_global(cx)
-            .profiles
-            .get(&self.profile_id)
-            .context("profile not found")?;
+        let profile = Rc::new(self.profile_settings(cx).context("profile not found")?);
         let provider_id = self.model.provider_id();
 
         Ok(self
             .tools
             .iter()
             .filter(move |(_, tool)| tool.supported_provider(&provider_id))
-            .filter_map(|(tool_name, tool)| {
-                if profile.is_tool_enabled(tool_name) {
-                    Some(tool)
-                } else {
-                    None
+            .filter_map({
+                let profile = profile.clone();
+                move |(tool_name, tool)| {
+                    if profile.is_tool_enabled(tool_name) && self.is_tool_allowed(tool_name) {
+                        Some(tool)
+                    } else {
+                        None
+                    }
                 }
             })
             .chain(self.context_server_registry.read(cx).servers().flat_map(
-                |(server_id, tools)| {
-                    tools.iter().filter_map(|(tool_name, tool)| {
-                        if profile.is_context_server_tool_enabled(&server_id.0, tool_name) {
+                move |(server_id, tools)| {
+                    let profile = profile.clone();
+                    tools.iter().filter_map(move |(tool_name, tool)| {
+                        if profile.is_context_server_tool_enabled(&server_id.0, tool_name)
+                            && self.is_tool_allowed(tool_name)
+                        {
                             Some(tool)
                         } else {
                             None
@@ -1076,7 +1888,13 @@ impl Thread {
             self.messages.len()
         );
         let mut messages = vec![self.build_system_message()];
-        for message in &self.messages {
+        // A compacted message stands in for everything before it.
+        let start = self
+            .messages
+            .iter()
+            .rposition(|message| matches!(message, Message::Compacted(_)))
+            .unwrap_or(0);
+        for message in &self.messages[start..] {
             match message {
                 Message::User(message) => messages.push(message.to_request()),
                 Message::Agent(message) => messages.extend(message.to_request()),
@@ -1085,6 +1903,7 @@ impl Thread {
                     content: vec!["Continue where you left off".into()],
                     cache: false,
                 }),
+                Message::Compacted(message) => messages.push(message.to_request()),
             }
         }
 
@@ -1346,6 +2165,13 @@ pub struct ToolCallEventStream {
     tool_use_id: LanguageModelToolUseId,
     stream: AgentResponseEventStream,
     fs: Option<Arc<dyn Fs>>,
+    /// The settings of the profile this tool call runs under.
+    profile: Option<AgentProfileSettings>,
+    /// Where to persist "Always Allow" answers. Without a profile, they allow every tool action,
+    /// and without a grant under a profile, they aren't offered.
+    grant: Option<ToolPermissionGrant>,
+    /// Whether the tool permission rules already allowed this call.
+    pre_authorized: bool,
 }
 
 impl ToolCallEventStream {
@@ -1368,6 +2194,21 @@ impl ToolCallEventStream {
             tool_use_id,
             stream,
             fs,
+            profile: None,
+            grant: None,
+            pre_authorized: false,
+        }
+    }
+
+    /// The settings of the profile this tool call runs under.
+    pub fn profile(&self) -> Option<&AgentProfileSettings> {
+        self.profile.as_ref()
+    }
+
+    fn pre_authorized(&self) -> Self {
+        Self {
+            pre_authorized: true,
+            ..self.clone()
         }
     }
 
@@ -1403,11 +2244,39 @@ impl ToolCallEventStream {
     }
 
     pub fn authorize(&self, title: impl Into<String>, cx: &mut App) -> Task<Result<()>> {
-        if agent_settings::AgentSettings::get_global(cx).always_allow_tool_actions {
+        if self.pre_authorized
+            || agent_settings::AgentSettings::get_global(cx).always_allow_tool_actions
+        {
             return Task::ready(Ok(()));
         }
 
+        self.ask(title, cx)
+    }
+
+    /// Asks the user to authorize the tool call, even when tool actions are always allowed or
+    /// the profile's rules already allowed it.
+    pub fn ask(&self, title: impl Into<String>, cx: &mut App) -> Task<Result<()>> {
         let (response_tx, response_rx) = oneshot::channel();
+        let mut options = vec![
+            acp::PermissionOption {
+                id: acp::PermissionOptionId("always_allow".into()),
+                name: "Always Allow".into(),
+                kind: acp::PermissionOptionKind::AllowAlways,
+            },
+            acp::PermissionOption {
+                id: acp::PermissionOptionId("allow".into()),
+                name: "Allow".into(),
+                kind: acp::PermissionOptionKind::AllowOnce,
+            },
+            acp::PermissionOption {
+                id: acp::PermissionOptionId("deny".into()),
+                name: "Deny".into(),
+                kind: acp::PermissionOptionKind::RejectOnce,
+            },
+        ];
+        if self.profile.is_some() && self.grant.is_none() {
+            options.remove(0);
+        }
         self.stream
             .0
             .unbounded_send(Ok(AgentResponseEvent::ToolCallAuthorization(
@@ -1419,34 +2288,42 @@ impl ToolCallEventStream {
                             ..Default::default()
                         },
                     },
-                    options: vec![
-                        acp::PermissionOption {
-                            id: acp::PermissionOptionId("always_allow".into()),
-                            name: "Always Allow".into(),
-                            kind: acp::PermissionOptionKind::AllowAlways,
-                        },
-                        acp::PermissionOption {
-                            id: acp::PermissionOptionId("allow".into()),
-                            name: "Allow".into(),
-                            kind: acp::PermissionOptionKind::AllowOnce,
-                        },
-                        acp::PermissionOption {
-                            id: acp::PermissionOptionId("deny".into()),
-                            name: "Deny".into(),
-                            kind: acp::PermissionOptionKind::RejectOnce,
-                        },
-                    ],
+                    options,
                     response: response_tx,
                 },
             )))
             .ok();
         let fs = self.fs.clone();
+        let grant = self.grant.clone();
+        let has_profile = self.profile.is_some();
         cx.spawn(async move |cx| match response_rx.await?.0.as_ref() {
             "always_allow" => {
                 if let Some(fs) = fs.clone() {
                     cx.update(|cx| {
                         update_settings_file::<AgentSettings>(fs, cx, |settings, _| {
-                            settings.set_always_allow_tool_actions(true);
+                            let Some(grant) = grant else {
+                                if !has_profile {
+                                    settings.set_always_allow_tool_actions(true);
+                                }
+                                return;
+                            };
+                            if grant.patterns.is_empty() {
+                                settings.allow_tool_action(
+                                    grant.profile_id,
+                                    grant.profile,
+                                    grant.tool_name,
+                                    None,
+                                );
+                            } else {
+                                for pattern in grant.patterns {
+                                    settings.allow_tool_action(
+                                        grant.profile_id.clone(),
+                                        grant.profile.clone(),
+                                        grant.tool_name.clone(),
+                                        Some(pattern),
+                                    );
+                                }
+                            }
                         });
                     })?;
                 }
@@ -1457,6 +2334,23 @@ impl ToolCallEventStream {
             _ => Err(anyhow!("Permission to run tool denied by user")),
         })
     }
+
+    /// Asks the user to authorize a tool call that a sub-agent wants to make, on behalf of this
+    /// tool call.
+    pub fn forward_authorization(&self, authorization: ToolCallAuthorization) {
+        self.stream
+            .0
+            .unbounded_send(Ok(AgentResponseEvent::ToolCallAuthorization(
+                ToolCallAuthorization {
+                    tool_call: acp::ToolCallUpdate {
+                        id: acp::ToolCallId(self.tool_use_id.to_string().into()),
+                        fields: authorization.tool_call.fields,
+                    },
+                    ..authorization
+                },
+            )))
+            .ok();
+    }
 }
 
 #[cfg(test)]
@@ -1502,6 +2396,31 @@ impl std::ops::DerefMut for ToolCallEventStreamReceiver {
     }
 }
 
+impl UserMessageContent {
+    fn to_acp(&self) -> acp::ContentBlock {
+        match self {
+            UserMessageContent::Text(text) => text_block(text),
+            UserMessageContent::Mention { uri, .. } => {
+                acp::ContentBlock::ResourceLink(acp::ResourceLink {
+                    uri: uri.to_uri().to_string(),
+                    name: uri.name(),
+                    annotations: None,
+                    description: None,
+                    mime_type: None,
+                    size: None,
+                    title: None,
+                })
+            }
+            UserMessageContent::Image(image) => acp::ContentBlock::Image(acp::ImageContent {
+                annotations: None,
+                data: image.source.to_string(),
+                mime_type: "image/png".into(),
+                uri: None,
+            }),
+        }
+    }
+}
+
 impl From<&str> for UserMessageContent {
     fn from(text: &str) -> Self {
         Self::Text(text.into())
@@ -1557,6 +2476,13 @@ impl From<acp::ContentBlock> for UserMessageContent {
     }
 }
 
+fn text_block(text: &str) -> acp::ContentBlock {
+    acp::ContentBlock::Text(acp::TextContent {
+        text: text.to_string(),
+        annotations: None,
+    })
+}
+
 fn convert_image(image_content: acp::ImageContent) -> LanguageModelImage {
     LanguageModelImage {
         source: image_content.data.into(),
@@ -1,6 +1,7 @@
 mod context_server_registry;
 mod copy_path_tool;
 mod create_directory_tool;
+mod delegate_tool;
 mod delete_path_tool;
 mod diagnostics_tool;
 mod edit_file_tool;
@@ -14,11 +15,13 @@ mod open_tool;
 mod read_file_tool;
 mod terminal_tool;
 mod thinking_tool;
+mod tool_permissions;
 mod web_search_tool;
 
 pub use context_server_registry::*;
 pub use copy_path_tool::*;
 pub use create_directory_tool::*;
+pub use delegate_tool::*;
 pub use delete_path_tool::*;
 pub use diagnostics_tool::*;
 pub use edit_file_tool::*;
@@ -32,4 +35,5 @@ pub use open_tool::*;
 pub use read_file_tool::*;
 pub use terminal_tool::*;
 pub use thinking_tool::*;
+pub use tool_permissions::*;
 pub use web_search_tool::*;
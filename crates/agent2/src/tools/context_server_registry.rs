@@ -108,6 +108,7 @@ impl ContextServerRegistry {
                     }
                 }
             }
+            _ => {}
         }
     }
 }
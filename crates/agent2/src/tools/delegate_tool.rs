@@ -0,0 +1,170 @@
+use crate::{AgentResponseEvent, AgentTool, Thread, ToolCallAuthorization, ToolCallEventStream};
+use acp_thread::UserMessageId;
+use agent_client_protocol as acp;
+use agent_settings::AgentProfileId;
+use anyhow::{Result, anyhow};
+use collections::IndexMap;
+use futures::{StreamExt as _, channel::oneshot};
+use gpui::{App, SharedString, Task, WeakEntity};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::sync::Arc;
+
+/// Delegates a self-contained task to a sub-agent, which works on it in its own context window and reports back a summary of the outcome.
+///
+/// - Use this for broad investigations, like searching a large codebase, whose intermediate results you don't need to see.
+/// - The sub-agent can't see this conversation, so describe everything it needs to know in `task`.
+/// - Delegating several tasks in the same response runs them in parallel.
+/// - Sub-agents can't delegate tasks themselves.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DelegateToolInput {
+    /// A short description of the task, shown to the user while the sub-agent works on it.
+    pub title: String,
+
+    /// Detailed instructions for the sub-agent, including what it should report back.
+    pub task: String,
+
+    /// The names of the tools the sub-agent may use. Defaults to all of your tools, except this one.
+    #[serde(default)]
+    pub tools: Option<Vec<String>>,
+
+    /// The profile the sub-agent runs under. Defaults to your current profile. The restrictions of your current profile still apply to the sub-agent.
+    #[serde(default)]
+    pub profile: Option<String>,
+}
+
+pub struct DelegateTool {
+    thread: WeakEntity<Thread>,
+}
+
+impl DelegateTool {
+    pub const NAME: &str = "delegate";
+
+    pub fn new(thread: WeakEntity<Thread>) -> Self {
+        Self { thread }
+    }
+}
+
+impl AgentTool for DelegateTool {
+    type Input = DelegateToolInput;
+    type Output = String;
+
+    fn name(&self) -> SharedString {
+        Self::NAME.into()
+    }
+
+    fn kind(&self) -> acp::ToolKind {
+        acp::ToolKind::Think
+    }
+
+    fn initial_title(&self, input: Result<Self::Input, serde_json::Value>) -> SharedString {
+        match input {
+            Ok(input) => input.title.into(),
+            Err(_) => "Delegate task".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<String>> {
+        let thread = self.thread.clone();
+        let profile_id = input.profile.map(|profile| AgentProfileId(profile.into()));
+        let tools = input
+            .tools
+            .map(|tools| tools.into_iter().map(SharedString::from).collect());
+        let prompt = format!(
+            "{}\n\nWhen you're done, reply with a concise summary of what you found or changed, \
+            including the relevant file paths. Only that reply is passed back.",
+            input.task
+        );
+
+        cx.spawn(async move |cx| {
+            // Tools run while the thread is being updated, so create the sub-agent afterwards.
+            let subagent = thread
+                .upgrade()
+                .ok_or_else(|| anyhow!("thread was dropped"))?
+                .update(cx, |thread, cx| thread.new_subagent(profile_id, tools, cx))??;
+            let mut events = subagent.update(cx, |subagent, cx| {
+                subagent.send(UserMessageId::new(), [prompt.as_str()], cx)
+            })?;
+
+            let mut steps = IndexMap::default();
+            let mut response = String::new();
+            while let Some(event) = events.next().await {
+                match event? {
+                    AgentResponseEvent::Text(text) => response.push_str(&text),
+                    AgentResponseEvent::ToolCall(tool_call) => {
+                        // Only the text after the last tool call is the sub-agent's reply.
+                        response.clear();
+                        steps.insert(tool_call.id, (tool_call.title, tool_call.status));
+                        report_progress(&steps, &event_stream);
+                    }
+                    AgentResponseEvent::ToolCallUpdate(
+                        acp_thread::ToolCallUpdate::UpdateFields(update),
+                    ) => {
+                        if let Some((title, status)) = steps.get_mut(&update.id) {
+                            if let Some(new_title) = update.fields.title {
+                                *title = new_title;
+                            }
+                            if let Some(new_status) = update.fields.status {
+                                *status = new_status;
+                            }
+                            report_progress(&steps, &event_stream);
+                        }
+                    }
+                    AgentResponseEvent::ToolCallAuthorization(authorization) => {
+                        // Ask one question at a time, as they all go through this tool call.
+                        let (response_tx, response_rx) = oneshot::channel();
+                        event_stream.forward_authorization(ToolCallAuthorization {
+                            tool_call: authorization.tool_call,
+                            options: authorization.options,
+                            response: response_tx,
+                        });
+                        let option = response_rx.await?;
+                        authorization.response.send(option).ok();
+                        event_stream.update_fields(acp::ToolCallUpdateFields {
+                            title: Some(input.title.clone()),
+                            status: Some(acp::ToolCallStatus::InProgress),
+                            ..Default::default()
+                        });
+                        report_progress(&steps, &event_stream);
+                    }
+                    AgentResponseEvent::Thinking(_)
+                    | AgentResponseEvent::ToolCallUpdate(_)
+                    | AgentResponseEvent::Stop(_) => {}
+                }
+            }
+
+            let response = response.trim();
+            if response.is_empty() {
+                Ok("The sub-agent finished without replying.".to_string())
+            } else {
+                Ok(response.to_string())
+            }
+        })
+    }
+}
+
+/// Lists the sub-agent's tool calls in the delegating tool call's card.
+fn report_progress(
+    steps: &IndexMap<acp::ToolCallId, (String, acp::ToolCallStatus)>,
+    event_stream: &ToolCallEventStream,
+) {
+    let mut markdown = String::new();
+    for (title, status) in steps.values() {
+        let status = match status {
+            acp::ToolCallStatus::Pending | acp::ToolCallStatus::InProgress => " …",
+            acp::ToolCallStatus::Completed => "",
+            acp::ToolCallStatus::Failed => " (failed)",
+        };
+        writeln!(markdown, "- {title}{status}").ok();
+    }
+    event_stream.update_fields(acp::ToolCallUpdateFields {
+        content: Some(vec![markdown.into()]),
+        ..Default::default()
+    });
+}
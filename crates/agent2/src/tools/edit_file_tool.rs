@@ -126,6 +126,8 @@ pub struct EditFileTool {
 }
 
 impl EditFileTool {
+    pub const NAME: &str = "edit_file";
+
     pub fn new(thread: Entity<Thread>) -> Self {
         Self { thread }
     }
@@ -185,7 +187,7 @@ impl AgentTool for EditFileTool {
     type Output = EditFileToolOutput;
 
     fn name(&self) -> SharedString {
-        "edit_file".into()
+        Self::NAME.into()
     }
 
     fn kind(&self) -> acp::ToolKind {
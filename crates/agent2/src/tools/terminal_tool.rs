@@ -1,5 +1,6 @@
 use agent_client_protocol as acp;
-use anyhow::Result;
+use anyhow::{Result, anyhow};
+use assistant_tools::terminal_sandbox::TerminalSandbox;
 use futures::{FutureExt as _, future::Shared};
 use gpui::{App, AppContext, Entity, SharedString, Task};
 use project::{Project, terminals::TerminalKind};
@@ -120,6 +121,9 @@ impl AgentTool for TerminalTool {
             format!("({}) </dev/null", input.command)
         };
         let args = vec!["-c".into(), command];
+        let sandbox = event_stream.profile().and_then(|profile| {
+            TerminalSandbox::for_project(&profile.terminal_sandbox, &self.project, cx)
+        });
 
         let env = match &working_dir {
             Some(dir) => self.project.update(cx, |project, cx| {
@@ -136,13 +140,24 @@ impl AgentTool for TerminalTool {
             env
         });
 
-        let authorize = event_stream.authorize(self.initial_title(Ok(input.clone())), cx);
+        let title = self.initial_title(Ok(input.clone()));
+        // Without a profile, there's no telling whether the command should run in a sandbox, so
+        // let the user decide whether to run it at all.
+        let authorize = if event_stream.profile().is_some() {
+            event_stream.authorize(title, cx)
+        } else {
+            event_stream.ask(title, cx)
+        };
 
         cx.spawn({
             async move |cx| {
                 authorize.await?;
 
                 let program = program.await;
+                let (program, args) = match &sandbox {
+                    Some(sandbox) => sandbox.wrap(program, args)?,
+                    None => (program, args),
+                };
                 let env = env.await;
                 let terminal = self
                     .project
@@ -196,6 +211,15 @@ impl AgentTool for TerminalTool {
                     })
                     .log_err();
 
+                if let Some(violation) = sandbox.as_ref().and_then(|sandbox| {
+                    sandbox.violation(
+                        &content,
+                        exit_status
+                            .map(|status| portable_pty::ExitStatus::from(status).exit_code()),
+                    )
+                }) {
+                    return Err(anyhow!("{violation}\n\n{processed_content}"));
+                }
                 Ok(processed_content)
             }
         })
@@ -339,6 +363,43 @@ mod tests {
         });
     }
 
+    #[gpui::test]
+    async fn test_asks_without_profile(executor: BackgroundExecutor, cx: &mut TestAppContext) {
+        if cfg!(windows) {
+            return;
+        }
+
+        init_test(&executor, cx);
+        cx.update(|cx| {
+            let mut settings = AgentSettings::get_global(cx).clone();
+            settings.always_allow_tool_actions = true;
+            AgentSettings::override_global(settings, cx);
+        });
+
+        let fs = Arc::new(RealFs::new(None, executor));
+        let tree = TempTree::new(json!({
+            "project": {},
+        }));
+        let project: Entity<Project> =
+            Project::test(fs, [tree.path().join("project").as_path()], cx).await;
+
+        let input = TerminalToolInput {
+            command: "true".to_owned(),
+            cd: ".".into(),
+        };
+        let (event_stream_tx, mut event_stream_rx) = ToolCallEventStream::test();
+        let result = cx
+            .update(|cx| Arc::new(TerminalTool::new(project, cx)).run(input, event_stream_tx, cx));
+
+        // The stream has no profile to tell whether to sandbox the command, so the user is asked
+        // even though tool actions are always allowed.
+        let auth = event_stream_rx.expect_authorization().await;
+        auth.response
+            .send(acp::PermissionOptionId("deny".into()))
+            .unwrap();
+        assert!(result.await.is_err());
+    }
+
     #[gpui::test]
     async fn test_interactive_command(executor: BackgroundExecutor, cx: &mut TestAppContext) {
         if cfg!(windows) {
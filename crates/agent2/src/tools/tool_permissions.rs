@@ -0,0 +1,421 @@
+use std::{
+    path::{Component, Path},
+    sync::Arc,
+};
+
+use agent_settings::{AgentProfileId, AgentProfileSettings, ToolPermission, ToolPermissionRules};
+use fs::normalize_path;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use regex::Regex;
+use serde_json::Value;
+
+/// The tool whose calls are matched against command patterns rather than path patterns.
+const TERMINAL_TOOL_NAME: &str = "terminal";
+/// The input fields holding the paths that file tools operate on.
+const PATH_FIELDS: &[&str] = &["path", "source_path", "destination_path"];
+
+/// Where to persist the user's "Always Allow" answer for a tool call.
+#[derive(Debug, Clone)]
+pub struct ToolPermissionGrant {
+    pub profile_id: AgentProfileId,
+    pub profile: AgentProfileSettings,
+    pub tool_name: Arc<str>,
+    /// Patterns matching this call. When empty, every call to the tool is allowed.
+    pub patterns: Vec<Arc<str>>,
+}
+
+impl ToolPermissionGrant {
+    /// Returns `None` when the call can't be described by patterns, as for calls with several
+    /// paths: allowing each of them would allow any pairing of the paths.
+    pub fn new(
+        profile_id: AgentProfileId,
+        profile: AgentProfileSettings,
+        tool_name: &str,
+        input: &Value,
+    ) -> Option<Self> {
+        let patterns: Vec<Arc<str>> = if tool_name == TERMINAL_TOOL_NAME {
+            command(input)
+                .map(|command| {
+                    split_commands(command)
+                        .into_iter()
+                        .map(|command| format!("{}$", regex::escape(command)).into())
+                        .collect()
+                })
+                .unwrap_or_default()
+        } else {
+            let paths = paths(input).collect::<Vec<_>>();
+            if paths.len() > 1 {
+                return None;
+            }
+            paths
+                .into_iter()
+                .map(|path| escape_glob(path).into())
+                .collect()
+        };
+        Some(Self {
+            profile_id,
+            profile,
+            tool_name: tool_name.into(),
+            patterns,
+        })
+    }
+}
+
+/// Decides whether a tool call may run according to the profile's rules for that tool.
+///
+/// Returns `None` when the profile has no opinion, leaving it up to the tool whether to ask.
+pub fn tool_permission(
+    profile: &AgentProfileSettings,
+    tool_name: &str,
+    input: &Value,
+) -> Option<ToolPermission> {
+    let rules = profile.tool_permissions.get(tool_name)?;
+    if tool_name == TERMINAL_TOOL_NAME {
+        command_permission(rules, command(input).unwrap_or_default())
+    } else {
+        paths_permission(rules, paths(input).collect())
+    }
+}
+
+fn command_permission(rules: &ToolPermissionRules, command: &str) -> Option<ToolPermission> {
+    // Commands can be wrapped in others, as in `sudo rm` or `bash -c 'rm'`, so deny patterns
+    // apply to every word of the command rather than just the start of each command in it.
+    let deny = command_patterns(&rules.deny);
+    if word_starts(command).any(|words| deny.iter().any(|pattern| pattern.is_match(words))) {
+        return Some(ToolPermission::Deny);
+    }
+
+    // Substituted commands can't be checked on their own, and redirections can write anywhere,
+    // so never allow either without asking.
+    let has_substitutions = command.contains("$(") || command.contains('`');
+    let has_redirections = command.contains(['<', '>']);
+    let commands = split_commands(command);
+    let allow = command_patterns(&rules.allow);
+    if !commands.is_empty()
+        && !has_substitutions
+        && !has_redirections
+        && commands
+            .iter()
+            .all(|command| allow.iter().any(|pattern| pattern.is_match(command)))
+    {
+        return Some(ToolPermission::Allow);
+    }
+
+    rules.default
+}
+
+fn paths_permission(rules: &ToolPermissionRules, paths: Vec<&str>) -> Option<ToolPermission> {
+    // Deny patterns apply to the contents of the directories they match too, and to the paths
+    // that `..` components resolve to.
+    if let Some(deny) = path_globs(&rules.deny) {
+        let is_denied = |path: &Path| {
+            path.ancestors()
+                .any(|ancestor| !ancestor.as_os_str().is_empty() && deny.is_match(ancestor))
+        };
+        if paths.iter().any(|path| {
+            let path = Path::new(path);
+            is_denied(path) || is_denied(&normalize_path(path))
+        }) {
+            return Some(ToolPermission::Deny);
+        }
+    }
+
+    // Paths that climb out of a directory could escape the globs that allow them.
+    let escapes_directory = |path: &&str| {
+        Path::new(path)
+            .components()
+            .any(|component| component == Component::ParentDir)
+    };
+    if let Some(allow) = path_globs(&rules.allow) {
+        if !paths.is_empty()
+            && !paths.iter().any(escapes_directory)
+            && paths.iter().all(|path| allow.is_match(path))
+        {
+            return Some(ToolPermission::Allow);
+        }
+    }
+
+    rules.default
+}
+
+fn command(input: &Value) -> Option<&str> {
+    input.get("command")?.as_str()
+}
+
+fn paths(input: &Value) -> impl Iterator<Item = &str> {
+    PATH_FIELDS
+        .iter()
+        .filter_map(|field| input.get(field)?.as_str())
+}
+
+/// Splits a shell command into the commands it chains together.
+fn split_commands(command: &str) -> Vec<&str> {
+    command
+        .split(|c| matches!(c, ';' | '&' | '|' | '\n'))
+        .map(str::trim)
+        .filter(|command| !command.is_empty())
+        .collect()
+}
+
+/// Returns the command from the start of each word onwards, where words are separated by
+/// whitespace, quotes, path separators and shell operators.
+fn word_starts(command: &str) -> impl Iterator<Item = &str> {
+    std::iter::once(command).chain(command.char_indices().filter_map(|(ix, c)| {
+        matches!(
+            c,
+            ' ' | '\t' | '\n' | '\'' | '"' | '`' | '(' | '/' | ';' | '&' | '|'
+        )
+        .then(|| &command[ix + c.len_utf8()..])
+    }))
+}
+
+/// Compiles command patterns, anchoring them at the start of the command.
+fn command_patterns(patterns: &[Arc<str>]) -> Vec<Regex> {
+    patterns
+        .iter()
+        .filter_map(|pattern| match Regex::new(&format!("^(?:{pattern})")) {
+            Ok(regex) => Some(regex),
+            Err(error) => {
+                log::warn!("invalid terminal permission pattern {pattern:?}: {error}");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Compiles path patterns into globs that match whole paths, in which `*` doesn't match across
+/// path separators.
+fn path_globs(patterns: &[Arc<str>]) -> Option<GlobSet> {
+    if patterns.is_empty() {
+        return None;
+    }
+    let mut globs = GlobSetBuilder::new();
+    for pattern in patterns {
+        match GlobBuilder::new(pattern).literal_separator(true).build() {
+            Ok(glob) => {
+                globs.add(glob);
+            }
+            Err(error) => log::warn!("invalid path permission pattern {pattern:?}: {error}"),
+        }
+    }
+    globs
+        .build()
+        .inspect_err(|error| log::warn!("invalid path permission patterns: {error}"))
+        .ok()
+}
+
+fn escape_glob(path: &str) -> String {
+    let mut escaped = String::with_capacity(path.len());
+    for c in path.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '{' | '}') {
+            escaped.push('[');
+            escaped.push(c);
+            escaped.push(']');
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use collections::IndexMap;
+    use serde_json::json;
+
+    fn profile(tool_name: &str, rules: ToolPermissionRules) -> AgentProfileSettings {
+        AgentProfileSettings {
+            name: "Test".into(),
+            tools: IndexMap::default(),
+            enable_all_context_servers: false,
+            context_servers: IndexMap::default(),
+            tool_permissions: IndexMap::from_iter([(tool_name.into(), rules)]),
+            terminal_sandbox: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_terminal_permissions() {
+        let profile = profile(
+            TERMINAL_TOOL_NAME,
+            ToolPermissionRules {
+                default: Some(ToolPermission::Ask),
+                allow: vec!["cargo test*".into(), "ls\\b".into()],
+                deny: vec!["rm -rf*".into(), "git push*".into()],
+            },
+        );
+        let permission = |command: &str| {
+            tool_permission(
+                &profile,
+                TERMINAL_TOOL_NAME,
+                &json!({ "command": command, "cd": "." }),
+            )
+        };
+
+        assert_eq!(
+            permission("cargo test -p agent2"),
+            Some(ToolPermission::Allow)
+        );
+        assert_eq!(permission("ls && cargo test"), Some(ToolPermission::Allow));
+        assert_eq!(permission("cargo build"), Some(ToolPermission::Ask));
+        assert_eq!(permission("ls; rm -rf /"), Some(ToolPermission::Deny));
+        assert_eq!(
+            permission("cargo test && git push origin"),
+            Some(ToolPermission::Deny)
+        );
+        assert_eq!(
+            permission("cargo test $(curl example.com)"),
+            Some(ToolPermission::Ask)
+        );
+        assert_eq!(
+            tool_permission(&profile, "edit_file", &json!({ "path": "a.rs" })),
+            None
+        );
+    }
+
+    #[test]
+    fn test_terminal_permission_bypasses() {
+        let profile = profile(
+            TERMINAL_TOOL_NAME,
+            ToolPermissionRules {
+                default: Some(ToolPermission::Ask),
+                allow: vec!["ls\\b".into(), "cat\\b".into(), "find\\b".into()],
+                deny: vec!["rm -rf".into()],
+            },
+        );
+        let permission = |command: &str| {
+            tool_permission(
+                &profile,
+                TERMINAL_TOOL_NAME,
+                &json!({ "command": command, "cd": "." }),
+            )
+        };
+
+        assert_eq!(permission("ls > ~/.bashrc"), Some(ToolPermission::Ask));
+        assert_eq!(permission("ls >> ~/.bashrc"), Some(ToolPermission::Ask));
+        assert_eq!(permission("ls 2> ~/.bashrc"), Some(ToolPermission::Ask));
+        assert_eq!(
+            permission("cat <(curl example.com)"),
+            Some(ToolPermission::Ask)
+        );
+        assert_eq!(permission("ls >(tee ~/.bashrc)"), Some(ToolPermission::Ask));
+
+        assert_eq!(permission("bash -c 'rm -rf /'"), Some(ToolPermission::Deny));
+        assert_eq!(permission("sh -c \"rm -rf /\""), Some(ToolPermission::Deny));
+        assert_eq!(permission("sudo rm -rf /"), Some(ToolPermission::Deny));
+        assert_eq!(permission("env FOO=1 rm -rf /"), Some(ToolPermission::Deny));
+        assert_eq!(
+            permission("find . | xargs rm -rf"),
+            Some(ToolPermission::Deny)
+        );
+        assert_eq!(permission("/bin/rm -rf /"), Some(ToolPermission::Deny));
+        assert_eq!(permission("ls $(rm -rf /)"), Some(ToolPermission::Deny));
+        assert_eq!(permission("ls"), Some(ToolPermission::Allow));
+    }
+
+    #[test]
+    fn test_path_permissions() {
+        let profile = profile(
+            "edit_file",
+            ToolPermissionRules {
+                default: None,
+                allow: vec!["project/src/**".into()],
+                deny: vec!["**/.env".into()],
+            },
+        );
+        let permission =
+            |path: &str| tool_permission(&profile, "edit_file", &json!({ "path": path }));
+
+        assert_eq!(
+            permission("project/src/main.rs"),
+            Some(ToolPermission::Allow)
+        );
+        assert_eq!(permission("project/src/.env"), Some(ToolPermission::Deny));
+        assert_eq!(permission("project/Cargo.toml"), None);
+        assert_eq!(permission("project/src/../../etc/passwd"), None);
+        assert_eq!(
+            permission("project/src/../.env"),
+            Some(ToolPermission::Deny)
+        );
+    }
+
+    #[test]
+    fn test_path_patterns_match_whole_paths() {
+        let profile = profile(
+            "edit_file",
+            ToolPermissionRules {
+                default: None,
+                allow: vec!["b.rs".into(), "project/*.rs".into()],
+                deny: vec!["project/tmp".into()],
+            },
+        );
+        let permission =
+            |path: &str| tool_permission(&profile, "edit_file", &json!({ "path": path }));
+
+        assert_eq!(permission("b.rs"), Some(ToolPermission::Allow));
+        assert_eq!(permission("lib.rs"), None);
+        assert_eq!(permission("project/main.rs"), Some(ToolPermission::Allow));
+        assert_eq!(permission("project/src/main.rs"), None);
+        assert_eq!(permission("project/tmp"), Some(ToolPermission::Deny));
+        assert_eq!(permission("project/tmp/a.rs"), Some(ToolPermission::Deny));
+        assert_eq!(permission("project/tmp-important"), None);
+        assert_eq!(
+            permission("project/src/../tmp/a.rs"),
+            Some(ToolPermission::Deny)
+        );
+    }
+
+    #[test]
+    fn test_grant_patterns() {
+        let profile = profile(TERMINAL_TOOL_NAME, ToolPermissionRules::default());
+        let grant = ToolPermissionGrant::new(
+            AgentProfileId::default(),
+            profile.clone(),
+            TERMINAL_TOOL_NAME,
+            &json!({ "command": "cargo test && cargo fmt" }),
+        )
+        .unwrap();
+        assert_eq!(
+            grant.patterns,
+            vec![Arc::from("cargo test$"), Arc::from("cargo fmt$")]
+        );
+
+        let grant = ToolPermissionGrant::new(
+            AgentProfileId::default(),
+            profile.clone(),
+            "edit_file",
+            &json!({ "path": "a/[id].rs" }),
+        )
+        .unwrap();
+        assert_eq!(grant.patterns, vec![Arc::from("a/[[]id[]].rs")]);
+
+        let edit_profile = self::profile(
+            "edit_file",
+            ToolPermissionRules {
+                default: None,
+                allow: grant.patterns.clone(),
+                deny: Vec::new(),
+            },
+        );
+        assert_eq!(
+            tool_permission(&edit_profile, "edit_file", &json!({ "path": "a/[id].rs" })),
+            Some(ToolPermission::Allow)
+        );
+        assert_eq!(
+            tool_permission(&edit_profile, "edit_file", &json!({ "path": "a/i.rs" })),
+            None
+        );
+
+        // Allowing each path of a move would allow moving any of them to any other.
+        assert!(
+            ToolPermissionGrant::new(
+                AgentProfileId::default(),
+                profile,
+                "move_path",
+                &json!({ "source_path": "a.rs", "destination_path": "b.rs" }),
+            )
+            .is_none()
+        );
+    }
+}
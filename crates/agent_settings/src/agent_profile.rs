@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
-use collections::IndexMap;
-use gpui::SharedString;
+use collections::{HashMap, IndexMap};
+use gpui::{App, Global, SharedString};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -38,6 +38,23 @@ impl Default for AgentProfileId {
     }
 }
 
+/// The profile each agent thread runs under, keyed by the thread id that tools receive in
+/// `LanguageModelRequest::thread_id`.
+#[derive(Default)]
+pub struct ThreadProfiles(HashMap<String, AgentProfileId>);
+
+impl Global for ThreadProfiles {}
+
+impl ThreadProfiles {
+    pub fn set(thread_id: String, profile_id: AgentProfileId, cx: &mut App) {
+        cx.default_global::<Self>().0.insert(thread_id, profile_id);
+    }
+
+    pub fn get<'a>(thread_id: &str, cx: &'a App) -> Option<&'a AgentProfileId> {
+        cx.try_global::<Self>()?.0.get(thread_id)
+    }
+}
+
 /// A profile for the Zed Agent that controls its behavior.
 #[derive(Debug, Clone)]
 pub struct AgentProfileSettings {
@@ -46,6 +63,10 @@ pub struct AgentProfileSettings {
     pub tools: IndexMap<Arc<str>, bool>,
     pub enable_all_context_servers: bool,
     pub context_servers: IndexMap<Arc<str>, ContextServerPreset>,
+    /// Rules deciding whether tool calls may run without asking, keyed by tool name.
+    pub tool_permissions: IndexMap<Arc<str>, ToolPermissionRules>,
+    /// Restrictions for the commands the agent runs in the terminal.
+    pub terminal_sandbox: TerminalSandboxSettings,
 }
 
 impl AgentProfileSettings {
@@ -60,9 +81,219 @@ impl AgentProfileSettings {
                 .get(server_id)
                 .map_or(false, |preset| preset.tools.get(tool_name) == Some(&true))
     }
+
+    /// Applies a profile with the same id from an untrusted source, such as a project's settings,
+    /// taking only the parts that make this profile more restrictive: disabled tools, deny rules,
+    /// stricter defaults and sandbox restrictions.
+    pub fn restrict_with(&mut self, other: AgentProfileSettings) {
+        for (tool_name, enabled) in other.tools {
+            if !enabled {
+                self.tools.insert(tool_name, false);
+            }
+        }
+
+        self.enable_all_context_servers &= other.enable_all_context_servers;
+        for (server_id, preset) in other.context_servers {
+            for (tool_name, enabled) in preset.tools {
+                if !enabled {
+                    self.context_servers
+                        .entry(server_id.clone())
+                        .or_default()
+                        .tools
+                        .insert(tool_name, false);
+                }
+            }
+        }
+
+        for (tool_name, rules) in other.tool_permissions {
+            let existing = self.tool_permissions.entry(tool_name).or_default();
+            if matches!(
+                rules.default,
+                Some(ToolPermission::Ask | ToolPermission::Deny)
+            ) && existing.default != Some(ToolPermission::Deny)
+            {
+                existing.default = rules.default;
+            }
+            for pattern in rules.deny {
+                if !existing.deny.contains(&pattern) {
+                    existing.deny.push(pattern);
+                }
+            }
+        }
+
+        let sandbox = other.terminal_sandbox;
+        self.terminal_sandbox.enabled |= sandbox.enabled;
+        self.terminal_sandbox.allow_network &= sandbox.allow_network;
+        self.terminal_sandbox.max_cpu_seconds = min_limit(
+            self.terminal_sandbox.max_cpu_seconds,
+            sandbox.max_cpu_seconds,
+        );
+        self.terminal_sandbox.max_memory_mb =
+            min_limit(self.terminal_sandbox.max_memory_mb, sandbox.max_memory_mb);
+    }
+}
+
+fn min_limit(a: Option<u64>, b: Option<u64>) -> Option<u64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct ContextServerPreset {
     pub tools: IndexMap<Arc<str>, bool>,
 }
+
+/// What happens when the agent wants to call a tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolPermission {
+    /// Run the tool without asking.
+    Allow,
+    /// Ask for confirmation before running the tool.
+    Ask,
+    /// Never run the tool.
+    Deny,
+}
+
+/// Permission rules for a single tool.
+///
+/// Patterns are matched against the paths a tool operates on (as globs) or, for the terminal
+/// tool, against the commands it runs (as regexes anchored at the start of a command, or for
+/// `deny`, at the start of any word in it).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ToolPermissionRules {
+    /// The permission for calls that don't match any pattern. When unset, the tool decides
+    /// whether to ask, as it does without any rules.
+    pub default: Option<ToolPermission>,
+    /// Calls matching these patterns run without asking.
+    pub allow: Vec<Arc<str>>,
+    /// Calls matching these patterns never run. Takes precedence over `allow`.
+    pub deny: Vec<Arc<str>>,
+}
+
+/// Restrictions for the commands the agent runs in the terminal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TerminalSandboxSettings {
+    /// Whether to run commands in a sandbox that can only write to the project's worktrees and a
+    /// temporary directory. Only supported on Linux.
+    pub enabled: bool,
+    /// Whether sandboxed commands may access the network.
+    pub allow_network: bool,
+    /// The CPU time, in seconds, after which sandboxed commands are killed.
+    pub max_cpu_seconds: Option<u64>,
+    /// The virtual memory, in megabytes, that sandboxed commands may allocate.
+    ///
+    /// Runtimes that reserve a lot of address space up front, like the JVM and Go, may not start
+    /// under this limit.
+    pub max_memory_mb: Option<u64>,
+}
+
+impl Default for TerminalSandboxSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            allow_network: true,
+            max_cpu_seconds: None,
+            max_memory_mb: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restrict_with_only_tightens_profile() {
+        let mut profile = AgentProfileSettings {
+            name: "Write".into(),
+            tools: IndexMap::from_iter([("terminal".into(), true), ("fetch".into(), false)]),
+            enable_all_context_servers: false,
+            context_servers: IndexMap::default(),
+            tool_permissions: IndexMap::from_iter([(
+                "terminal".into(),
+                ToolPermissionRules {
+                    default: Some(ToolPermission::Ask),
+                    allow: vec!["^cargo test".into()],
+                    deny: vec!["^rm ".into()],
+                },
+            )]),
+            terminal_sandbox: TerminalSandboxSettings {
+                enabled: true,
+                allow_network: false,
+                max_cpu_seconds: Some(60),
+                max_memory_mb: None,
+            },
+        };
+
+        profile.restrict_with(AgentProfileSettings {
+            name: "Project".into(),
+            tools: IndexMap::from_iter([("terminal".into(), false), ("fetch".into(), true)]),
+            enable_all_context_servers: true,
+            context_servers: IndexMap::default(),
+            tool_permissions: IndexMap::from_iter([
+                (
+                    "terminal".into(),
+                    ToolPermissionRules {
+                        default: None,
+                        allow: Vec::new(),
+                        deny: vec!["^curl ".into()],
+                    },
+                ),
+                (
+                    "edit_file".into(),
+                    ToolPermissionRules {
+                        default: Some(ToolPermission::Deny),
+                        allow: vec!["**".into()],
+                        deny: Vec::new(),
+                    },
+                ),
+                (
+                    "fetch".into(),
+                    ToolPermissionRules {
+                        default: Some(ToolPermission::Allow),
+                        allow: Vec::new(),
+                        deny: Vec::new(),
+                    },
+                ),
+            ]),
+            terminal_sandbox: TerminalSandboxSettings::default(),
+        });
+
+        assert_eq!(profile.name.as_ref(), "Write");
+        assert!(!profile.is_tool_enabled("terminal"));
+        assert!(!profile.is_tool_enabled("fetch"));
+        assert!(!profile.enable_all_context_servers);
+        assert_eq!(
+            profile.tool_permissions["terminal"],
+            ToolPermissionRules {
+                default: Some(ToolPermission::Ask),
+                allow: vec!["^cargo test".into()],
+                deny: vec!["^rm ".into(), "^curl ".into()],
+            }
+        );
+        assert_eq!(
+            profile.tool_permissions["edit_file"],
+            ToolPermissionRules {
+                default: Some(ToolPermission::Deny),
+                allow: Vec::new(),
+                deny: Vec::new(),
+            }
+        );
+        assert_eq!(
+            profile.tool_permissions["fetch"],
+            ToolPermissionRules::default()
+        );
+        assert_eq!(
+            profile.terminal_sandbox,
+            TerminalSandboxSettings {
+                enabled: true,
+                allow_network: false,
+                max_cpu_seconds: Some(60),
+                max_memory_mb: None,
+            }
+        );
+    }
+}
@@ -63,6 +63,7 @@ pub struct AgentSettings {
     pub default_view: DefaultView,
     pub profiles: IndexMap<AgentProfileId, AgentProfileSettings>,
     pub always_allow_tool_actions: bool,
+    pub context_server_sampling: ContextServerSamplingSettings,
     pub notify_when_agent_waiting: NotifyWhenAgentWaiting,
     pub play_sound_when_agent_done: bool,
     pub stream_edits: bool,
@@ -75,6 +76,29 @@ pub struct AgentSettings {
     pub use_modifier_to_send: bool,
 }
 
+#[derive(Clone, Debug)]
+pub struct ContextServerSamplingSettings {
+    pub allowed_servers: Vec<Arc<str>>,
+    pub max_tokens: u64,
+}
+
+impl Default for ContextServerSamplingSettings {
+    fn default() -> Self {
+        Self {
+            allowed_servers: Vec::new(),
+            max_tokens: 4096,
+        }
+    }
+}
+
+impl ContextServerSamplingSettings {
+    pub fn is_allowed(&self, server_id: &str) -> bool {
+        self.allowed_servers
+            .iter()
+            .any(|allowed| allowed.as_ref() == server_id)
+    }
+}
+
 impl AgentSettings {
     pub fn temperature_for_model(model: &Arc<dyn LanguageModel>, cx: &App) -> Option<f32> {
         let settings = Self::get_global(cx);
@@ -170,6 +194,17 @@ impl AgentSettingsContent {
         self.always_allow_tool_actions = Some(allow);
     }
 
+    pub fn allow_context_server_sampling(&mut self, server_id: Arc<str>) {
+        let allowed_servers = self
+            .context_server_sampling
+            .get_or_insert_default()
+            .allowed_servers
+            .get_or_insert_default();
+        if !allowed_servers.contains(&server_id) {
+            allowed_servers.push(server_id);
+        }
+    }
+
     pub fn set_play_sound_when_agent_done(&mut self, allow: bool) {
         self.play_sound_when_agent_done = Some(allow);
     }
@@ -196,29 +231,37 @@ impl AgentSettingsContent {
             bail!("profile with ID '{profile_id}' already exists");
         }
 
-        profiles.insert(
-            profile_id,
-            AgentProfileContent {
-                name: profile_settings.name.into(),
-                tools: profile_settings.tools,
-                enable_all_context_servers: Some(profile_settings.enable_all_context_servers),
-                context_servers: profile_settings
-                    .context_servers
-                    .into_iter()
-                    .map(|(server_id, preset)| {
-                        (
-                            server_id,
-                            ContextServerPresetContent {
-                                tools: preset.tools,
-                            },
-                        )
-                    })
-                    .collect(),
-            },
-        );
+        profiles.insert(profile_id, profile_settings.into());
 
         Ok(())
     }
+
+    /// Lets the agent call a tool without asking in the given profile, either for calls matching
+    /// `pattern` or, without one, for every call.
+    pub fn allow_tool_action(
+        &mut self,
+        profile_id: AgentProfileId,
+        profile_settings: AgentProfileSettings,
+        tool_name: Arc<str>,
+        pattern: Option<Arc<str>>,
+    ) {
+        let rules = self
+            .profiles
+            .get_or_insert_default()
+            .entry(profile_id)
+            .or_insert_with(|| profile_settings.into())
+            .tool_permissions
+            .entry(tool_name)
+            .or_default();
+        match pattern {
+            Some(pattern) => {
+                if !rules.allow.contains(&pattern) {
+                    rules.allow.push(pattern);
+                }
+            }
+            None => rules.default = Some(ToolPermission::Allow),
+        }
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize, JsonSchema, Debug, Default)]
@@ -268,6 +311,8 @@ pub struct AgentSettingsContent {
     ///
     /// Default: false
     always_allow_tool_actions: Option<bool>,
+    /// Settings for MCP sampling, which lets context servers request completions from your model.
+    context_server_sampling: Option<ContextServerSamplingContent>,
     /// Where to show a popup notification when the agent is waiting for user input.
     ///
     /// Default: "primary_screen"
@@ -315,6 +360,19 @@ pub struct AgentSettingsContent {
     use_modifier_to_send: Option<bool>,
 }
 
+#[derive(Clone, Serialize, Deserialize, JsonSchema, Debug, Default)]
+pub struct ContextServerSamplingContent {
+    /// Context servers whose sampling requests are answered without asking for your approval.
+    /// Only read from your user settings.
+    ///
+    /// Default: []
+    allowed_servers: Option<Vec<Arc<str>>>,
+    /// The maximum number of tokens a context server may request for a single completion.
+    ///
+    /// Default: 4096
+    max_tokens: Option<u64>,
+}
+
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum CompletionMode {
@@ -388,6 +446,57 @@ pub struct AgentProfileContent {
     pub enable_all_context_servers: Option<bool>,
     #[serde(default)]
     pub context_servers: IndexMap<Arc<str>, ContextServerPresetContent>,
+    /// Rules deciding whether tool calls may run without asking, keyed by tool name.
+    ///
+    /// Rules that allow tool calls are only read from your user settings.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub tool_permissions: IndexMap<Arc<str>, ToolPermissionRulesContent>,
+    /// Restrictions for the commands the agent runs in the terminal.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub terminal_sandbox: Option<TerminalSandboxContent>,
+}
+
+impl From<AgentProfileSettings> for AgentProfileContent {
+    fn from(profile: AgentProfileSettings) -> Self {
+        Self {
+            name: profile.name.into(),
+            tools: profile.tools,
+            enable_all_context_servers: Some(profile.enable_all_context_servers),
+            context_servers: profile
+                .context_servers
+                .into_iter()
+                .map(|(server_id, preset)| {
+                    (
+                        server_id,
+                        ContextServerPresetContent {
+                            tools: preset.tools,
+                        },
+                    )
+                })
+                .collect(),
+            tool_permissions: profile
+                .tool_permissions
+                .into_iter()
+                .map(|(tool_name, rules)| {
+                    (
+                        tool_name,
+                        ToolPermissionRulesContent {
+                            default: rules.default,
+                            allow: rules.allow,
+                            deny: rules.deny,
+                        },
+                    )
+                })
+                .collect(),
+            terminal_sandbox: (profile.terminal_sandbox != TerminalSandboxSettings::default())
+                .then(|| TerminalSandboxContent {
+                    enabled: Some(profile.terminal_sandbox.enabled),
+                    allow_network: Some(profile.terminal_sandbox.allow_network),
+                    max_cpu_seconds: profile.terminal_sandbox.max_cpu_seconds,
+                    max_memory_mb: profile.terminal_sandbox.max_memory_mb,
+                }),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize, JsonSchema)]
@@ -395,6 +504,47 @@ pub struct ContextServerPresetContent {
     pub tools: IndexMap<Arc<str>, bool>,
 }
 
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ToolPermissionRulesContent {
+    /// The permission for calls that don't match any pattern. When unset, the tool decides
+    /// whether to ask.
+    pub default: Option<ToolPermission>,
+    /// Calls matching these patterns run without asking. Patterns are globs matched against the
+    /// paths a tool operates on, or regexes matched against the start of each terminal command.
+    #[serde(default)]
+    pub allow: Vec<Arc<str>>,
+    /// Calls matching these patterns never run, even if they also match an `allow` pattern.
+    /// Terminal command patterns are matched against the start of every word in the command,
+    /// so that wrapped commands like `sudo rm` are denied too.
+    #[serde(default)]
+    pub deny: Vec<Arc<str>>,
+}
+
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct TerminalSandboxContent {
+    /// Whether to run the agent's terminal commands in a sandbox that can only write to the
+    /// project's worktrees and a private `/tmp`. Only supported on Linux, where it requires
+    /// bubblewrap (`bwrap`).
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// Whether sandboxed commands may access the network.
+    ///
+    /// Default: true
+    pub allow_network: Option<bool>,
+    /// The CPU time, in seconds, after which sandboxed commands are killed.
+    ///
+    /// Default: unlimited
+    pub max_cpu_seconds: Option<u64>,
+    /// The virtual memory, in megabytes, that sandboxed commands may allocate.
+    ///
+    /// Runtimes that reserve a lot of address space up front, like the JVM and Go, may not start
+    /// under this limit.
+    ///
+    /// Default: unlimited
+    pub max_memory_mb: Option<u64>,
+}
+
 impl Settings for AgentSettings {
     const KEY: Option<&'static str> = Some("agent");
 
@@ -411,6 +561,11 @@ impl Settings for AgentSettings {
         let mut settings = AgentSettings::default();
 
         for value in sources.defaults_and_customizations() {
+            // For the same reason as `always_allow_tool_actions` below, only trust rules that
+            // allow tool calls when they come from the user's global settings.
+            let trust_tool_permissions = std::ptr::eq(value, sources.default)
+                || sources.user.is_some_and(|user| std::ptr::eq(value, user));
+
             merge(&mut settings.enabled, value.enabled);
             merge(&mut settings.button, value.button);
             merge(&mut settings.dock, value.dock);
@@ -468,38 +623,86 @@ impl Settings for AgentSettings {
                 &mut settings.use_modifier_to_send,
                 value.use_modifier_to_send,
             );
+            merge(
+                &mut settings.context_server_sampling.max_tokens,
+                value
+                    .context_server_sampling
+                    .as_ref()
+                    .and_then(|sampling| sampling.max_tokens),
+            );
 
             settings
                 .model_parameters
                 .extend_from_slice(&value.model_parameters);
 
             if let Some(profiles) = value.profiles.as_ref() {
-                settings
-                    .profiles
-                    .extend(profiles.into_iter().map(|(id, profile)| {
-                        (
-                            id.clone(),
-                            AgentProfileSettings {
-                                name: profile.name.clone().into(),
-                                tools: profile.tools.clone(),
-                                enable_all_context_servers: profile
-                                    .enable_all_context_servers
-                                    .unwrap_or_default(),
-                                context_servers: profile
-                                    .context_servers
-                                    .iter()
-                                    .map(|(context_server_id, preset)| {
-                                        (
-                                            context_server_id.clone(),
-                                            ContextServerPreset {
-                                                tools: preset.tools.clone(),
-                                            },
-                                        )
-                                    })
-                                    .collect(),
-                            },
-                        )
-                    }));
+                for (id, profile) in profiles {
+                    let profile = AgentProfileSettings {
+                        name: profile.name.clone().into(),
+                        tools: profile.tools.clone(),
+                        enable_all_context_servers: profile
+                            .enable_all_context_servers
+                            .unwrap_or_default(),
+                        context_servers: profile
+                            .context_servers
+                            .iter()
+                            .map(|(context_server_id, preset)| {
+                                (
+                                    context_server_id.clone(),
+                                    ContextServerPreset {
+                                        tools: preset.tools.clone(),
+                                    },
+                                )
+                            })
+                            .collect(),
+                        tool_permissions: profile
+                            .tool_permissions
+                            .iter()
+                            .map(|(tool_name, rules)| {
+                                (
+                                    tool_name.clone(),
+                                    ToolPermissionRules {
+                                        default: rules.default.filter(|permission| {
+                                            trust_tool_permissions
+                                                || *permission != ToolPermission::Allow
+                                        }),
+                                        allow: if trust_tool_permissions {
+                                            rules.allow.clone()
+                                        } else {
+                                            Vec::new()
+                                        },
+                                        deny: rules.deny.clone(),
+                                    },
+                                )
+                            })
+                            .collect(),
+                        terminal_sandbox: profile
+                            .terminal_sandbox
+                            .as_ref()
+                            .map(|sandbox| {
+                                let default = TerminalSandboxSettings::default();
+                                TerminalSandboxSettings {
+                                    enabled: sandbox.enabled.unwrap_or(default.enabled),
+                                    allow_network: sandbox
+                                        .allow_network
+                                        .unwrap_or(default.allow_network),
+                                    max_cpu_seconds: sandbox.max_cpu_seconds,
+                                    max_memory_mb: sandbox.max_memory_mb,
+                                }
+                            })
+                            .unwrap_or_default(),
+                    };
+                    // Project settings may only tighten a profile the user already has, so that
+                    // checking out a repository can't loosen the restrictions the user chose.
+                    match settings.profiles.get_mut(id) {
+                        Some(existing) if !trust_tool_permissions => {
+                            existing.restrict_with(profile)
+                        }
+                        _ => {
+                            settings.profiles.insert(id.clone(), profile);
+                        }
+                    }
+                }
             }
         }
 
@@ -517,6 +720,14 @@ impl Settings for AgentSettings {
             .and_then(|setting| setting.always_allow_tool_actions)
             .unwrap_or(false);
 
+        // Likewise, only trust the user's global settings for which context servers may use the
+        // user's model without asking.
+        settings.context_server_sampling.allowed_servers = sources
+            .user
+            .and_then(|setting| setting.context_server_sampling.as_ref())
+            .and_then(|sampling| sampling.allowed_servers.clone())
+            .unwrap_or_default();
+
         Ok(settings)
     }
 
@@ -3,8 +3,10 @@ mod entry_view_state;
 mod message_editor;
 mod model_selector;
 mod model_selector_popover;
+mod thread_history;
 mod thread_view;
 
 pub use model_selector::AcpModelSelector;
 pub use model_selector_popover::AcpModelSelectorPopover;
-pub use thread_view::AcpThreadView;
+pub use thread_history::AcpThreadHistory;
+pub use thread_view::{AcpThreadView, ThreadSource};
@@ -22,6 +22,7 @@ use agent::thread_store::{TextThreadStore, ThreadStore};
 
 use crate::acp::message_editor::MessageEditor;
 use crate::context_picker::file_context_picker::{FileMatch, search_files};
+use crate::context_picker::mcp_resource_context_picker::{McpResourceMatch, search_mcp_resources};
 use crate::context_picker::rules_context_picker::{RulesContextEntry, search_rules};
 use crate::context_picker::symbol_context_picker::SymbolMatch;
 use crate::context_picker::symbol_context_picker::search_symbols;
@@ -39,6 +40,7 @@ pub(crate) enum Match {
     Thread(ThreadMatch),
     Fetch(SharedString),
     Rules(RulesContextEntry),
+    McpResource(McpResourceMatch),
     Entry(EntryMatch),
 }
 
@@ -55,6 +57,7 @@ impl Match {
             Match::Thread(_) => 1.,
             Match::Symbol(_) => 1.,
             Match::Rules(_) => 1.,
+            Match::McpResource(_) => 1.,
             Match::Fetch(_) => 1.,
         }
     }
@@ -144,6 +147,18 @@ fn search(
             }
         }
 
+        Some(ContextPickerMode::McpResources) => {
+            let search_resources_task =
+                search_mcp_resources(query, cancellation_flag, &workspace, cx);
+            cx.background_spawn(async move {
+                search_resources_task
+                    .await
+                    .into_iter()
+                    .map(Match::McpResource)
+                    .collect()
+            })
+        }
+
         None => {
             if query.is_empty() {
                 let mut matches = recent_entries
@@ -424,6 +439,63 @@ impl ContextPickerCompletionProvider {
         }
     }
 
+    fn completion_for_mcp_resource(
+        resource: McpResourceMatch,
+        source_range: Range<Anchor>,
+        editor: WeakEntity<MessageEditor>,
+        cx: &mut App,
+    ) -> Completion {
+        let resource = match resource {
+            McpResourceMatch::Resource(resource) => resource,
+            McpResourceMatch::Template(template) => {
+                return Completion {
+                    replace_range: source_range,
+                    new_text: format!(
+                        "@{} {}",
+                        ContextPickerMode::McpResources.keyword(),
+                        template.query
+                    ),
+                    label: build_code_label_for_full_path(
+                        &template.label,
+                        template.description.as_deref(),
+                        cx,
+                    ),
+                    documentation: None,
+                    insert_text_mode: None,
+                    source: project::CompletionSource::Custom,
+                    icon_path: Some(IconName::Server.path().into()),
+                    // Keep the completion menu open to fill in the template's next argument.
+                    confirm: Some(Arc::new(|_, _, _| true)),
+                };
+            }
+        };
+
+        let uri = MentionUri::McpResource {
+            server_id: resource.server_id.0.to_string(),
+            uri: resource.uri.to_string(),
+            name: resource.name.to_string(),
+        };
+        let new_text = format!("{} ", uri.as_link());
+        let new_text_len = new_text.len();
+        let icon_path = uri.icon_path(cx);
+        Completion {
+            replace_range: source_range.clone(),
+            new_text,
+            label: build_code_label_for_full_path(&resource.name, Some(&resource.uri), cx),
+            documentation: None,
+            insert_text_mode: None,
+            source: project::CompletionSource::Custom,
+            icon_path: Some(icon_path),
+            confirm: Some(confirm_completion_callback(
+                resource.name,
+                source_range.start,
+                new_text_len - 1,
+                editor,
+                uri,
+            )),
+        }
+    }
+
     pub(crate) fn completion_for_path(
         project_path: ProjectPath,
         path_prefix: &str,
@@ -685,6 +757,13 @@ impl CompletionProvider for ContextPickerCompletionProvider {
                             cx,
                         )),
 
+                        Match::McpResource(resource) => Some(Self::completion_for_mcp_resource(
+                            resource,
+                            source_range.clone(),
+                            editor.clone(),
+                            cx,
+                        )),
+
                         Match::Fetch(url) => Self::completion_for_fetch(
                             source_range.clone(),
                             url,
@@ -815,6 +894,13 @@ impl MentionCompletion {
                 argument = Some(mode_text.to_string());
             }
             match rest_of_line[mode_text.len()..].find(|c: char| !c.is_whitespace()) {
+                // Resource template arguments are separated by whitespace, so the
+                // argument spans the rest of the line.
+                Some(whitespace_count) if mode == Some(ContextPickerMode::McpResources) => {
+                    let argument_text = &rest_of_line[mode_text.len() + whitespace_count..];
+                    argument = Some(argument_text.to_string());
+                    end += whitespace_count + argument_text.len();
+                }
                 Some(whitespace_count) => {
                     if let Some(argument_text) = parts.next() {
                         argument = Some(argument_text.to_string());
@@ -908,5 +994,14 @@ mod tests {
         );
 
         assert_eq!(MentionCompletion::try_parse("test@", 0), None);
+
+        assert_eq!(
+            MentionCompletion::try_parse("Lorem @resource issues zed 4", 0),
+            Some(MentionCompletion {
+                source_range: 6..28,
+                mode: Some(ContextPickerMode::McpResources),
+                argument: Some("issues zed 4".to_string()),
+            })
+        );
     }
 }
@@ -7,6 +7,7 @@ use agent::{TextThreadStore, ThreadId, ThreadStore};
 use agent_client_protocol as acp;
 use anyhow::{Context as _, Result, anyhow};
 use collections::{HashMap, HashSet};
+use context_server::ContextServerId;
 use editor::{
     Anchor, AnchorRangeExt, ContextMenuOptions, ContextMenuPlacement, Editor, EditorElement,
     EditorMode, EditorStyle, ExcerptId, FoldPlaceholder, MultiBuffer, ToOffset,
@@ -211,6 +212,7 @@ impl MessageEditor {
             | MentionUri::Thread { .. }
             | MentionUri::TextThread { .. }
             | MentionUri::Rule { .. }
+            | MentionUri::McpResource { .. }
             | MentionUri::Selection { .. } => {
                 self.mention_set.insert_uri(crease_id, mention_uri.clone());
             }
@@ -1049,6 +1051,26 @@ impl MentionSet {
                             anyhow::Ok((crease_id, Mention::Text { uri, content: text }))
                         })
                     }
+                    MentionUri::McpResource {
+                        server_id,
+                        uri: resource_uri,
+                        ..
+                    } => {
+                        let text_task = project
+                            .read(cx)
+                            .context_server_store()
+                            .read(cx)
+                            .read_resource(
+                                &ContextServerId(server_id.as_str().into()),
+                                resource_uri,
+                                cx,
+                            );
+                        let uri = uri.clone();
+                        cx.spawn(async move |_| {
+                            let content = text_task.await?;
+                            anyhow::Ok((crease_id, Mention::Text { uri, content }))
+                        })
+                    }
                     MentionUri::Fetch { url } => {
                         let Some(content) = self.fetch_results.get(&url).cloned() else {
                             return Task::ready(Err(anyhow!("missing fetch result")));
@@ -0,0 +1,303 @@
+use std::{
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::Arc,
+};
+
+use agent2::{DbThreadMetadata, NativeAgentConnection, ThreadFilter};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use gpui::{
+    App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Subscription, Task,
+    WeakEntity, Window,
+};
+use picker::{Picker, PickerDelegate};
+use time::{OffsetDateTime, UtcOffset};
+use ui::{ListItem, ListItemSpacing, Tooltip, prelude::*};
+use util::ResultExt as _;
+use workspace::ModalView;
+
+use crate::AgentPanel;
+
+/// A modal listing the native agent's saved threads, which can be searched, filtered, and resumed.
+pub struct AcpThreadHistory {
+    picker: Entity<Picker<AcpThreadHistoryDelegate>>,
+    _subscription: Subscription,
+}
+
+impl ModalView for AcpThreadHistory {}
+
+impl AcpThreadHistory {
+    pub fn new(
+        connection: Rc<NativeAgentConnection>,
+        agent_panel: WeakEntity<AgentPanel>,
+        current_worktree: Option<PathBuf>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let delegate = AcpThreadHistoryDelegate {
+            connection,
+            agent_panel,
+            current_worktree,
+            threads: Vec::new(),
+            selected_index: 0,
+        };
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+        let _subscription = cx.subscribe(&picker, |_, _, _, cx| cx.emit(DismissEvent));
+        Self {
+            picker,
+            _subscription,
+        }
+    }
+}
+
+impl EventEmitter<DismissEvent> for AcpThreadHistory {}
+
+impl Focusable for AcpThreadHistory {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for AcpThreadHistory {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+pub struct AcpThreadHistoryDelegate {
+    connection: Rc<NativeAgentConnection>,
+    agent_panel: WeakEntity<AgentPanel>,
+    /// The first worktree of the current project, which `project:.` filters by.
+    current_worktree: Option<PathBuf>,
+    threads: Vec<DbThreadMetadata>,
+    selected_index: usize,
+}
+
+impl AcpThreadHistoryDelegate {
+    fn delete_thread(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(thread) = self.threads.get(ix) else {
+            return;
+        };
+        let delete = self.connection.delete_thread(thread.id.clone(), cx);
+        cx.spawn_in(window, async move |picker, cx| {
+            delete.await?;
+            picker.update_in(cx, |picker, window, cx| picker.refresh(window, cx))
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn format_timestamp(&self, timestamp: DateTime<Utc>, cx: &App) -> String {
+        let Some(timestamp) = OffsetDateTime::from_unix_timestamp(timestamp.timestamp()).log_err()
+        else {
+            return String::new();
+        };
+        let timezone = self
+            .agent_panel
+            .read_with(cx, |panel, _| panel.local_timezone())
+            .unwrap_or(UtcOffset::UTC);
+        time_format::format_localized_timestamp(
+            timestamp,
+            OffsetDateTime::now_utc(),
+            timezone,
+            time_format::TimestampFormat::EnhancedAbsolute,
+        )
+    }
+}
+
+impl PickerDelegate for AcpThreadHistoryDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        self.threads.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.selected_index = ix.min(self.threads.len().saturating_sub(1));
+        cx.notify();
+    }
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Search threads, or filter with model:, project:., after: and before:…".into()
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<SharedString> {
+        Some("No saved threads match.".into())
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let filter = parse_filter(&query, self.current_worktree.as_deref(), Utc::now());
+        let search = self.connection.search_threads(filter, cx);
+        cx.spawn_in(window, async move |picker, cx| {
+            let threads = search.await.log_err().unwrap_or_default();
+            picker
+                .update(cx, |picker, cx| {
+                    picker.delegate.threads = threads;
+                    picker.delegate.selected_index = 0;
+                    cx.notify();
+                })
+                .ok();
+        })
+    }
+
+    fn confirm(&mut self, _secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(thread) = self.threads.get(self.selected_index) else {
+            return;
+        };
+        let id = thread.id.clone();
+        self.agent_panel
+            .update(cx, |panel, cx| panel.open_saved_thread(id, window, cx))
+            .log_err();
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let thread = self.threads.get(ix)?;
+        let mut details = vec![self.format_timestamp(thread.updated_at, cx)];
+        details.extend(thread.model.clone());
+        details.extend(thread.worktree_paths.iter().filter_map(|path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        }));
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(
+                    v_flex()
+                        .min_w_0()
+                        .child(Label::new(thread.title.clone()).truncate())
+                        .child(
+                            Label::new(details.join(" · "))
+                                .size(LabelSize::Small)
+                                .color(Color::Muted)
+                                .truncate(),
+                        ),
+                )
+                .end_hover_slot(
+                    IconButton::new(("delete-thread", ix), IconName::Trash)
+                        .icon_size(IconSize::Small)
+                        .icon_color(Color::Muted)
+                        .tooltip(Tooltip::text("Delete Thread"))
+                        .on_click(cx.listener(move |picker, _, window, cx| {
+                            picker.delegate.delete_thread(ix, window, cx);
+                            cx.stop_propagation();
+                        })),
+                ),
+        )
+    }
+}
+
+/// Turns a search query into a filter, taking out the words that filter threads:
+///
+/// - `model:provider/model` only includes threads using that model.
+/// - `project:path` only includes threads from a project with that worktree, or with the current
+///   project's first worktree for `project:.`.
+/// - `after:` and `before:` only include threads updated since or until a date, given as
+///   `YYYY-MM-DD` or a number of days ago, like `7d`.
+///
+/// The remaining words are searched for in the threads' messages.
+fn parse_filter(query: &str, current_worktree: Option<&Path>, now: DateTime<Utc>) -> ThreadFilter {
+    let mut filter = ThreadFilter::default();
+    let mut words = Vec::new();
+    for word in query.split_whitespace() {
+        if let Some(model) = word
+            .strip_prefix("model:")
+            .filter(|model| !model.is_empty())
+        {
+            filter.model = Some(model.to_string());
+        } else if let Some(project) = word.strip_prefix("project:") {
+            filter.worktree_path = match project {
+                "" | "." => current_worktree.map(Path::to_path_buf),
+                path => Some(PathBuf::from(path)),
+            };
+        } else if let Some(date) = word
+            .strip_prefix("after:")
+            .and_then(|date| parse_date(date, now))
+        {
+            filter.updated_after = Some(date);
+        } else if let Some(date) = word
+            .strip_prefix("before:")
+            .and_then(|date| parse_date(date, now))
+        {
+            filter.updated_before = Some(date);
+        } else {
+            words.push(word);
+        }
+    }
+    if !words.is_empty() {
+        filter.query = Some(words.join(" "));
+    }
+    filter
+}
+
+fn parse_date(date: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    if let Some(days) = date.strip_suffix('d') {
+        return Some(now - Duration::days(days.parse().ok()?));
+    }
+    Some(
+        NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .ok()?
+            .and_hms_opt(0, 0, 0)?
+            .and_utc(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_filter() {
+        let now = DateTime::parse_from_rfc3339("2025-06-15T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let current_worktree = Path::new("/home/user/zed");
+
+        let filter = parse_filter(
+            "flaky test model:anthropic/claude-sonnet-4 project:. after:7d before:2025-06-14",
+            Some(current_worktree),
+            now,
+        );
+        assert_eq!(filter.query.as_deref(), Some("flaky test"));
+        assert_eq!(filter.model.as_deref(), Some("anthropic/claude-sonnet-4"));
+        assert_eq!(filter.worktree_path.as_deref(), Some(current_worktree));
+        assert_eq!(filter.updated_after, Some(now - Duration::days(7)));
+        assert_eq!(
+            filter.updated_before.map(|date| date.to_rfc3339()),
+            Some("2025-06-14T00:00:00+00:00".to_string())
+        );
+
+        let filter = parse_filter("project:/tmp/other after:someday", None, now);
+        assert_eq!(filter.query.as_deref(), Some("after:someday"));
+        assert_eq!(
+            filter.worktree_path.as_deref(),
+            Some(Path::new("/tmp/other"))
+        );
+        assert_eq!(filter.updated_after, None);
+
+        let filter = parse_filter("  ", None, now);
+        assert_eq!(filter.query, None);
+        assert_eq!(filter.model, None);
+    }
+}
@@ -8,7 +8,7 @@ use agent::{TextThreadStore, ThreadStore};
 use agent_client_protocol::{self as acp};
 use agent_servers::AgentServer;
 use agent_settings::{AgentProfileId, AgentSettings, CompletionMode, NotifyWhenAgentWaiting};
-use anyhow::bail;
+use anyhow::{anyhow, bail};
 use audio::{Audio, Sound};
 use buffer_diff::BufferDiff;
 use client::zed_urls;
@@ -51,8 +51,9 @@ use crate::agent_diff::AgentDiff;
 use crate::profile_selector::{ProfileProvider, ProfileSelector};
 use crate::ui::{AgentNotification, AgentNotificationEvent, BurnModeTooltip};
 use crate::{
-    AgentDiffPane, AgentPanel, ContinueThread, ContinueWithBurnMode, ExpandMessageEditor, Follow,
-    KeepAll, OpenAgentDiff, RejectAll, ToggleBurnMode, ToggleProfileSelector,
+    AgentDiffPane, AgentPanel, CompactThread, ContinueThread, ContinueWithBurnMode,
+    ExpandMessageEditor, ExportThread, Follow, KeepAll, OpenAgentDiff, RejectAll, ToggleBurnMode,
+    ToggleProfileSelector,
 };
 
 const RESPONSE_PADDING_X: Pixels = px(19.);
@@ -102,6 +103,7 @@ pub struct AcpThreadView {
     agent: Rc<dyn AgentServer>,
     workspace: WeakEntity<Workspace>,
     project: Entity<Project>,
+    thread_source: ThreadSource,
     thread_state: ThreadState,
     entry_view_state: Entity<EntryViewState>,
     message_editor: Entity<MessageEditor>,
@@ -124,6 +126,21 @@ pub struct AcpThreadView {
     _subscriptions: [Subscription; 3],
 }
 
+/// Where the thread shown by an [`AcpThreadView`] comes from.
+#[derive(Clone, Default)]
+pub enum ThreadSource {
+    /// A new, empty thread.
+    #[default]
+    New,
+    /// A native agent thread that was previously exported with [`ExportThread`].
+    Imported {
+        text: Arc<str>,
+        format: agent2::ThreadExportFormat,
+    },
+    /// A native agent thread saved in the thread database.
+    Saved(agent2::ThreadId),
+}
+
 enum ThreadState {
     Loading {
         _task: Task<()>,
@@ -144,6 +161,7 @@ enum ThreadState {
 impl AcpThreadView {
     pub fn new(
         agent: Rc<dyn AgentServer>,
+        thread_source: ThreadSource,
         workspace: WeakEntity<Workspace>,
         project: Entity<Project>,
         thread_store: Entity<ThreadStore>,
@@ -188,7 +206,15 @@ impl AcpThreadView {
             workspace: workspace.clone(),
             project: project.clone(),
             entry_view_state,
-            thread_state: Self::initial_state(agent, workspace, project, window, cx),
+            thread_state: Self::initial_state(
+                agent,
+                thread_source.clone(),
+                workspace,
+                project,
+                window,
+                cx,
+            ),
+            thread_source,
             message_editor,
             model_selector: None,
             profile_selector: None,
@@ -212,6 +238,7 @@ impl AcpThreadView {
 
     fn initial_state(
         agent: Rc<dyn AgentServer>,
+        thread_source: ThreadSource,
         workspace: WeakEntity<Workspace>,
         project: Entity<Project>,
         window: &mut Window,
@@ -253,10 +280,39 @@ impl AcpThreadView {
             // .ok();
 
             let Some(result) = cx
-                .update(|_, cx| {
-                    connection
-                        .clone()
-                        .new_thread(project.clone(), &root_dir, cx)
+                .update(|_, cx| match thread_source {
+                    ThreadSource::New => {
+                        connection
+                            .clone()
+                            .new_thread(project.clone(), &root_dir, cx)
+                    }
+                    ThreadSource::Imported { text, format } => {
+                        match connection
+                            .clone()
+                            .downcast::<agent2::NativeAgentConnection>()
+                        {
+                            Some(connection) => connection.import_thread(
+                                text.to_string(),
+                                format,
+                                project.clone(),
+                                cx,
+                            ),
+                            None => Task::ready(Err(anyhow!(
+                                "only native agent threads can be imported"
+                            ))),
+                        }
+                    }
+                    ThreadSource::Saved(id) => {
+                        match connection
+                            .clone()
+                            .downcast::<agent2::NativeAgentConnection>()
+                        {
+                            Some(connection) => connection.open_thread(id, project.clone(), cx),
+                            None => Task::ready(Err(anyhow!(
+                                "only native agent threads can be resumed"
+                            ))),
+                        }
+                    }
                 })
                 .log_err()
             else {
@@ -692,6 +748,7 @@ impl AcpThreadView {
                     } else {
                         this.thread_state = Self::initial_state(
                             agent,
+                            this.thread_source.clone(),
                             this.workspace.clone(),
                             project.clone(),
                             window,
@@ -744,6 +801,24 @@ impl AcpThreadView {
                 .id(("user_message", entry_ix))
                 .py_4()
                 .px_2()
+                .when(
+                    self.is_compaction_boundary(message.id.as_ref(), cx),
+                    |this| {
+                        this.child(
+                            h_flex()
+                                .pb_4()
+                                .gap_2()
+                                .child(Divider::horizontal().color(DividerColor::Border))
+                                .child(
+                                    Label::new("Earlier messages were summarized to save context")
+                                        .size(LabelSize::XSmall)
+                                        .color(Color::Muted)
+                                        .flex_none(),
+                                )
+                                .child(Divider::horizontal().color(DividerColor::Border)),
+                        )
+                    },
+                )
                 .children(message.id.clone().and_then(|message_id| {
                     message.checkpoint.as_ref()?.show.then(|| {
                         Button::new("restore-checkpoint", "Restore Checkpoint")
@@ -1088,13 +1163,18 @@ impl AcpThreadView {
             ToolCallContent::Diff(diff) => diff.read(cx).has_revealed_range(cx),
             _ => false,
         });
-        let use_card_layout = needs_confirmation || is_edit || has_diff;
+        // Delegated tasks show the sub-agent's progress in a card of their own.
+        let is_delegation = self
+            .as_native_thread(cx)
+            .is_some_and(|thread| thread.read(cx).is_delegation(&tool_call.id));
+        let use_card_layout = needs_confirmation || is_edit || has_diff || is_delegation;
 
         let is_collapsible = !tool_call.content.is_empty() && !use_card_layout;
 
         let is_open = tool_call.content.is_empty()
             || needs_confirmation
             || has_nonempty_diff
+            || is_delegation
             || self.expanded_tool_calls.contains(&tool_call.id);
 
         let gradient_overlay = |color: Hsla| {
@@ -1236,11 +1316,7 @@ impl AcpThreadView {
                                             .overflow_x_scroll()
                                             .child(self.render_markdown(
                                                 tool_call.label.clone(),
-                                                default_markdown_style(
-                                                    needs_confirmation || is_edit || has_diff,
-                                                    window,
-                                                    cx,
-                                                ),
+                                                default_markdown_style(use_card_layout, window, cx),
                                             )),
                                     )
                                     .child(gradient_overlay(gradient_color))
@@ -2453,6 +2529,67 @@ impl AcpThreadView {
             .thread(acp_thread.session_id(), cx)
     }
 
+    fn is_compaction_boundary(&self, message_id: Option<&UserMessageId>, cx: &App) -> bool {
+        let Some(message_id) = message_id else {
+            return false;
+        };
+        self.as_native_thread(cx).is_some_and(|thread| {
+            thread
+                .read(cx)
+                .compaction_boundaries()
+                .any(|boundary| boundary == message_id)
+        })
+    }
+
+    fn compact_thread(&mut self, _: &CompactThread, _window: &mut Window, cx: &mut Context<Self>) {
+        let Some(acp_thread) = self.thread() else {
+            return;
+        };
+        // Compacting inserts a message before the latest user message, so wait for the turn to end.
+        if acp_thread.read(cx).status() != ThreadStatus::Idle {
+            return;
+        }
+        let Some(thread) = self.as_native_thread(cx) else {
+            return;
+        };
+
+        let compaction = thread.update(cx, |thread, cx| thread.compact(cx));
+        cx.spawn(async move |this, cx| {
+            let result = compaction.await;
+            this.update(cx, |this, cx| match result {
+                Ok(()) => cx.notify(),
+                Err(error) => this.handle_thread_error(error, cx),
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn export_thread(&mut self, _: &ExportThread, _window: &mut Window, cx: &mut Context<Self>) {
+        let Some(thread) = self.thread() else {
+            return;
+        };
+        let Some(connection) = self.as_native_connection(cx) else {
+            return;
+        };
+        let session_id = thread.read(cx).session_id().clone();
+        let suggested_name = format!("{}.json", thread.read(cx).title());
+        let directory = paths::home_dir().clone();
+        let path = cx.prompt_for_new_path(&directory, Some(&suggested_name));
+        let fs = <dyn Fs>::global(cx);
+
+        cx.spawn(async move |this, cx| {
+            let Some(path) = path.await?? else {
+                return Ok(());
+            };
+            let format = agent2::ThreadExportFormat::from_path(&path);
+            let text = this.update(cx, |_, cx| {
+                connection.export_thread(&session_id, format, cx)
+            })??;
+            fs.atomic_write(path, text).await
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn toggle_burn_mode(
         &mut self,
         _: &ToggleBurnMode,
@@ -2765,6 +2902,11 @@ impl AcpThreadView {
                 MentionUri::Fetch { url } => {
                     cx.open_url(url.as_str());
                 }
+                MentionUri::McpResource { uri, .. } => {
+                    if uri.starts_with("http://") || uri.starts_with("https://") {
+                        cx.open_url(&uri);
+                    }
+                }
             })
         } else {
             cx.open_url(&url);
@@ -3330,6 +3472,8 @@ impl Render for AcpThreadView {
             .key_context("AcpThread")
             .on_action(cx.listener(Self::open_agent_diff))
             .on_action(cx.listener(Self::toggle_burn_mode))
+            .on_action(cx.listener(Self::compact_thread))
+            .on_action(cx.listener(Self::export_thread))
             .bg(cx.theme().colors().panel_background)
             .child(match &self.thread_state {
                 ThreadState::Unauthenticated { connection } => v_flex()
@@ -3717,6 +3861,7 @@ pub(crate) mod tests {
             cx.new(|cx| {
                 AcpThreadView::new(
                     Rc::new(agent),
+                    ThreadSource::New,
                     workspace.downgrade(),
                     project,
                     thread_store.clone(),
@@ -3912,6 +4057,7 @@ pub(crate) mod tests {
             cx.new(|cx| {
                 AcpThreadView::new(
                     Rc::new(StubAgentServer::new(connection.as_ref().clone())),
+                    ThreadSource::New,
                     workspace.downgrade(),
                     project.clone(),
                     thread_store.clone(),
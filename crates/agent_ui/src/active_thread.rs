@@ -747,6 +747,11 @@ fn open_markdown_link(
             }),
             cx,
         ),
+        Some(MentionLink::McpResource(uri)) => {
+            if uri.starts_with("http://") || uri.starts_with("https://") {
+                cx.open_url(&uri);
+            }
+        }
         None => cx.open_url(&text),
     }
 }
@@ -3714,7 +3719,7 @@ pub(crate) fn open_context(
             cx,
         ),
 
-        AgentContextHandle::Image(_) => {}
+        AgentContextHandle::Image(_) | AgentContextHandle::McpResource(_) => {}
     }
 }
 
@@ -19,7 +19,8 @@ use gpui::{
 };
 use language::LanguageRegistry;
 use language_model::{
-    LanguageModelProvider, LanguageModelProviderId, LanguageModelRegistry, ZED_CLOUD_PROVIDER_ID,
+    LanguageModelProvider, LanguageModelProviderId, LanguageModelRegistry, UsageGrouping,
+    UsageLedger, UsageTotals, ZED_CLOUD_PROVIDER_ID, start_of_month,
 };
 use notifications::status_toast::{StatusToast, ToastIcon};
 use project::{
@@ -54,6 +55,9 @@ pub struct AgentConfiguration {
     expanded_provider_configurations: HashMap<LanguageModelProviderId, bool>,
     tools: Entity<ToolWorkingSet>,
     _registry_subscription: Subscription,
+    usage_this_month: Vec<(String, UsageTotals)>,
+    load_usage_task: Task<()>,
+    _usage_ledger_subscription: Option<Subscription>,
     scroll_handle: ScrollHandle,
     scrollbar_state: ScrollbarState,
 }
@@ -90,6 +94,9 @@ impl AgentConfiguration {
         cx.subscribe(&context_server_store, |_, _, _, cx| cx.notify())
             .detach();
 
+        let usage_ledger_subscription = UsageLedger::global(cx)
+            .map(|ledger| cx.observe(&ledger, |this, _, cx| this.load_usage(cx)));
+
         let scroll_handle = ScrollHandle::new();
         let scrollbar_state = ScrollbarState::new(scroll_handle.clone());
 
@@ -112,13 +119,37 @@ impl AgentConfiguration {
             expanded_provider_configurations,
             tools,
             _registry_subscription: registry_subscription,
+            usage_this_month: Vec::new(),
+            load_usage_task: Task::ready(()),
+            _usage_ledger_subscription: usage_ledger_subscription,
             scroll_handle,
             scrollbar_state,
         };
         this.build_provider_configuration_views(window, cx);
+        this.load_usage(cx);
         this
     }
 
+    fn load_usage(&mut self, cx: &mut Context<Self>) {
+        let Some(ledger) = UsageLedger::global(cx) else {
+            return;
+        };
+        let since = start_of_month(chrono::Local::now().date_naive());
+        let totals = ledger
+            .read(cx)
+            .totals(UsageGrouping::Model, Some(since), cx);
+        self.load_usage_task = cx.spawn(async move |this, cx| {
+            if let Some(mut totals) = totals.await.log_err() {
+                totals.sort_by(|(_, a), (_, b)| b.cost.total_cmp(&a.cost));
+                this.update(cx, |this, cx| {
+                    this.usage_this_month = totals;
+                    cx.notify();
+                })
+                .ok();
+            }
+        });
+    }
+
     fn build_provider_configuration_views(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         let providers = LanguageModelRegistry::read_global(cx).providers();
         for provider in providers {
@@ -492,6 +523,72 @@ impl AgentConfiguration {
             .child(self.render_modifier_to_send(cx))
     }
 
+    fn render_usage_section(&mut self, cx: &mut Context<Self>) -> impl IntoElement {
+        let Some(ledger) = UsageLedger::global(cx) else {
+            return div().into_any_element();
+        };
+        let ledger = ledger.read(cx);
+        let (spent_today, spent_this_month) = ledger.spent();
+        let budget = ledger.budget();
+        let spent_label = |label: &str, spent: f64, limit: Option<f64>| {
+            let amount = match limit {
+                Some(limit) => format!("${spent:.2} of ${limit:.2}"),
+                None => format!("${spent:.2}"),
+            };
+            let color = match limit {
+                Some(limit) if spent >= limit => Color::Error,
+                _ => Color::Default,
+            };
+            h_flex()
+                .justify_between()
+                .child(Label::new(label.to_string()).color(Color::Muted))
+                .child(Label::new(amount).color(color))
+        };
+
+        v_flex()
+            .p(DynamicSpacing::Base16.rems(cx))
+            .pr(DynamicSpacing::Base20.rems(cx))
+            .gap_2p5()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(
+                v_flex()
+                    .gap_0p5()
+                    .child(Headline::new("Usage"))
+                    .child(
+                        Label::new(
+                            "What language model requests cost, based on the `language_models.pricing` settings.",
+                        )
+                        .color(Color::Muted),
+                    ),
+            )
+            .child(spent_label("Today", spent_today, budget.daily))
+            .child(spent_label("This month", spent_this_month, budget.monthly))
+            .when(!self.usage_this_month.is_empty(), |this| {
+                this.child(Divider::horizontal().color(DividerColor::BorderFaded))
+            })
+            .children(self.usage_this_month.iter().map(|(model, totals)| {
+                let tokens = totals.usage.total_tokens();
+                let cost = if totals.unpriced_requests == totals.requests {
+                    "no price".to_string()
+                } else {
+                    format!("${:.2}", totals.cost)
+                };
+                h_flex()
+                    .justify_between()
+                    .child(Label::new(model.clone()).size(LabelSize::Small))
+                    .child(
+                        Label::new(format!(
+                            "{} requests, {tokens} tokens, {cost}",
+                            totals.requests
+                        ))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                    )
+            }))
+            .into_any_element()
+    }
+
     fn render_zed_plan_info(&self, plan: Option<Plan>, cx: &mut Context<Self>) -> impl IntoElement {
         if let Some(plan) = plan {
             let free_chip_bg = cx
@@ -996,6 +1093,7 @@ impl Render for AgentConfiguration {
                     .size_full()
                     .overflow_y_scroll()
                     .child(self.render_general_settings_section(cx))
+                    .child(self.render_usage_section(cx))
                     .child(self.render_context_servers_section(window, cx))
                     .child(self.render_provider_configuration_section(cx)),
             )
@@ -1038,6 +1136,10 @@ fn extension_only_provides_context_server(manifest: &ExtensionManifest) -> bool
         && manifest.slash_commands.is_empty()
         && manifest.snippets.is_none()
         && manifest.debug_locators.is_empty()
+        && manifest.formatters.is_empty()
+        && manifest.code_action_providers.is_empty()
+        && manifest.task_providers.is_empty()
+        && manifest.language_model_providers.is_empty()
 }
 
 pub(crate) fn resolve_extension_for_context_server(
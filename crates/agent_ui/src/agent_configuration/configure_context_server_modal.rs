@@ -310,6 +310,11 @@ impl ConfigureContextServerModal {
                     id: server_id,
                     command,
                 }),
+                ContextServerSettings::Remote { .. } => {
+                    return Err(anyhow::anyhow!(
+                        "Remote context servers can only be configured in the settings file"
+                    ));
+                }
                 ContextServerSettings::Extension { .. } => {
                     match workspace
                         .update(cx, |workspace, cx| {
@@ -739,6 +744,7 @@ fn wait_for_context_server(
                 _ => {}
             }
         }
+        _ => {}
     });
 
     cx.spawn(async move |_cx| {
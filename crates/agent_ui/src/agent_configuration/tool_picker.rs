@@ -1,9 +1,6 @@
 use std::{collections::BTreeMap, sync::Arc};
 
-use agent_settings::{
-    AgentProfileContent, AgentProfileId, AgentProfileSettings, AgentSettings, AgentSettingsContent,
-    ContextServerPresetContent,
-};
+use agent_settings::{AgentProfileId, AgentProfileSettings, AgentSettings, AgentSettingsContent};
 use assistant_tool::{ToolSource, ToolWorkingSet};
 use fs::Fs;
 use gpui::{App, Context, DismissEvent, Entity, EventEmitter, Focusable, Task, WeakEntity, Window};
@@ -275,25 +272,7 @@ impl PickerDelegate for ToolPickerDelegate {
                 let profiles = settings.profiles.get_or_insert_default();
                 let profile = profiles
                     .entry(profile_id)
-                    .or_insert_with(|| AgentProfileContent {
-                        name: default_profile.name.into(),
-                        tools: default_profile.tools,
-                        enable_all_context_servers: Some(
-                            default_profile.enable_all_context_servers,
-                        ),
-                        context_servers: default_profile
-                            .context_servers
-                            .into_iter()
-                            .map(|(server_id, preset)| {
-                                (
-                                    server_id,
-                                    ContextServerPresetContent {
-                                        tools: preset.tools,
-                                    },
-                                )
-                            })
-                            .collect(),
-                    });
+                    .or_insert_with(|| default_profile.into());
 
                 if let Some(server_id) = server_id {
                     let preset = profile.context_servers.entry(server_id).or_default();
@@ -12,11 +12,11 @@ use crate::NewExternalAgentThread;
 use crate::agent_diff::AgentDiffThread;
 use crate::{
     AddContextServer, AgentDiffPane, ContinueThread, ContinueWithBurnMode,
-    DeleteRecentlyOpenThread, ExpandMessageEditor, Follow, InlineAssistant, NewTextThread,
-    NewThread, OpenActiveThreadAsMarkdown, OpenAgentDiff, OpenHistory, ResetTrialEndUpsell,
-    ResetTrialUpsell, ToggleBurnMode, ToggleContextPicker, ToggleNavigationMenu,
-    ToggleNewThreadMenu, ToggleOptionsMenu,
-    acp::AcpThreadView,
+    DeleteRecentlyOpenThread, ExpandMessageEditor, Follow, ImportThread, InlineAssistant,
+    NewTextThread, NewThread, OpenActiveThreadAsMarkdown, OpenAgentDiff, OpenHistory,
+    ResetTrialEndUpsell, ResetTrialUpsell, SearchThreads, ToggleBurnMode, ToggleContextPicker,
+    ToggleNavigationMenu, ToggleNewThreadMenu, ToggleOptionsMenu,
+    acp::{AcpThreadHistory, AcpThreadView},
     active_thread::{self, ActiveThread, ActiveThreadEvent},
     agent_configuration::{AgentConfiguration, AssistantConfigurationEvent},
     agent_diff::AgentDiff,
@@ -37,7 +37,7 @@ use agent::{
 };
 use agent_settings::{AgentDockPosition, AgentSettings, CompletionMode, DefaultView};
 use ai_onboarding::AgentPanelOnboarding;
-use anyhow::{Result, anyhow};
+use anyhow::{Context as _, Result, anyhow};
 use assistant_context::{AssistantContext, ContextEvent, ContextSummary};
 use assistant_slash_command::SlashCommandWorkingSet;
 use assistant_tool::ToolWorkingSet;
@@ -49,12 +49,13 @@ use fs::Fs;
 use gpui::{
     Action, Animation, AnimationExt as _, AnyElement, App, AsyncWindowContext, ClipboardItem,
     Corner, DismissEvent, Entity, EventEmitter, ExternalPaths, FocusHandle, Focusable, Hsla,
-    KeyContext, Pixels, Subscription, Task, UpdateGlobal, WeakEntity, prelude::*,
-    pulsating_between,
+    KeyContext, PathPromptOptions, Pixels, Subscription, Task, UpdateGlobal, WeakEntity,
+    prelude::*, pulsating_between,
 };
 use language::LanguageRegistry;
 use language_model::{
     ConfigurationError, ConfiguredModel, LanguageModelProviderTosView, LanguageModelRegistry,
+    UsageLedger, UsageLedgerEvent,
 };
 use project::{DisableAiSettings, Project, ProjectPath, Worktree};
 use prompt_store::{PromptBuilder, PromptStore, UserPromptId};
@@ -70,8 +71,9 @@ use ui::{
 };
 use util::ResultExt as _;
 use workspace::{
-    CollaboratorId, DraggedSelection, DraggedTab, ToggleZoom, ToolbarItemView, Workspace,
+    CollaboratorId, DraggedSelection, DraggedTab, Toast, ToggleZoom, ToolbarItemView, Workspace,
     dock::{DockPosition, Panel, PanelEvent},
+    notifications::NotificationId,
 };
 use zed_actions::{
     DecreaseBufferFontSize, IncreaseBufferFontSize, ResetBufferFontSize,
@@ -123,6 +125,17 @@ pub fn init(cx: &mut App) {
                         });
                     }
                 })
+                .register_action(|workspace, _: &ImportThread, window, cx| {
+                    if let Some(panel) = workspace.panel::<AgentPanel>(cx) {
+                        workspace.focus_panel::<AgentPanel>(window, cx);
+                        panel.update(cx, |panel, cx| panel.import_thread(window, cx));
+                    }
+                })
+                .register_action(|workspace, _: &SearchThreads, window, cx| {
+                    if let Some(panel) = workspace.panel::<AgentPanel>(cx) {
+                        panel.update(cx, |panel, cx| panel.search_threads(window, cx));
+                    }
+                })
                 .register_action(|workspace, action: &OpenRulesLibrary, window, cx| {
                     if let Some(panel) = workspace.panel::<AgentPanel>(cx) {
                         workspace.focus_panel::<AgentPanel>(window, cx);
@@ -468,6 +481,7 @@ pub struct AgentPanel {
     language_registry: Arc<LanguageRegistry>,
     thread_store: Entity<ThreadStore>,
     _default_model_subscription: Subscription,
+    _usage_ledger_subscription: Option<Subscription>,
     context_store: Entity<TextThreadStore>,
     prompt_store: Option<Entity<PromptStore>>,
     inline_assist_context_store: Entity<ContextStore>,
@@ -732,6 +746,28 @@ impl AgentPanel {
             },
         );
 
+        let _usage_ledger_subscription = UsageLedger::global(cx).map(|ledger| {
+            cx.subscribe(
+                &ledger,
+                |this, _, event: &UsageLedgerEvent, cx| match event {
+                    UsageLedgerEvent::BudgetExceeded(exceeded) => {
+                        this.workspace
+                            .update(cx, |workspace, cx| {
+                                struct BudgetExceededToast;
+                                workspace.show_toast(
+                                    Toast::new(
+                                        NotificationId::unique::<BudgetExceededToast>(),
+                                        exceeded.to_string(),
+                                    ),
+                                    cx,
+                                );
+                            })
+                            .ok();
+                    }
+                },
+            )
+        });
+
         let onboarding = cx.new(|cx| {
             AgentPanelOnboarding::new(
                 user_store.clone(),
@@ -752,6 +788,7 @@ impl AgentPanel {
             language_registry,
             thread_store: thread_store.clone(),
             _default_model_subscription,
+            _usage_ledger_subscription,
             context_store,
             prompt_store,
             configuration: None,
@@ -1006,6 +1043,7 @@ impl AgentPanel {
                 let thread_view = cx.new(|cx| {
                     crate::acp::AcpThreadView::new(
                         server,
+                        crate::acp::ThreadSource::New,
                         workspace.clone(),
                         project,
                         thread_store.clone(),
@@ -1021,6 +1059,99 @@ impl AgentPanel {
         .detach_and_log_err(cx);
     }
 
+    fn import_thread(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let workspace = self.workspace.clone();
+        let project = self.project.clone();
+        let fs = self.fs.clone();
+        let thread_store = self.thread_store.clone();
+        let text_thread_store = self.context_store.clone();
+        let paths = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+        });
+
+        cx.spawn_in(window, async move |this, cx| {
+            let Some(path) = paths.await??.and_then(|paths| paths.into_iter().next()) else {
+                return Ok(());
+            };
+            let text = fs.load(&path).await?;
+            let thread_source = crate::acp::ThreadSource::Imported {
+                text: text.into(),
+                format: agent2::ThreadExportFormat::from_path(&path),
+            };
+            let server: Rc<dyn AgentServer> = Rc::new(agent2::NativeAgentServer::new(fs));
+
+            this.update_in(cx, |this, window, cx| {
+                let thread_view = cx.new(|cx| {
+                    crate::acp::AcpThreadView::new(
+                        server,
+                        thread_source,
+                        workspace.clone(),
+                        project,
+                        thread_store.clone(),
+                        text_thread_store.clone(),
+                        window,
+                        cx,
+                    )
+                });
+
+                this.set_active_view(ActiveView::ExternalAgentThread { thread_view }, window, cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn search_threads(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let workspace = self.workspace.clone();
+        let server = agent2::NativeAgentServer::new(self.fs.clone());
+        let current_worktree = self
+            .project
+            .read(cx)
+            .visible_worktrees(cx)
+            .next()
+            .map(|worktree| worktree.read(cx).abs_path().to_path_buf());
+        let root_dir = current_worktree
+            .clone()
+            .unwrap_or_else(|| paths::home_dir().clone());
+        let connect = server.connect(&root_dir, &self.project, cx);
+
+        cx.spawn_in(window, async move |this, cx| {
+            let connection = connect
+                .await?
+                .downcast::<agent2::NativeAgentConnection>()
+                .context("not a native agent connection")?;
+            workspace.update_in(cx, |workspace, window, cx| {
+                workspace.toggle_modal(window, cx, |window, cx| {
+                    AcpThreadHistory::new(connection, this, current_worktree, window, cx)
+                });
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    pub(crate) fn open_saved_thread(
+        &mut self,
+        id: agent2::ThreadId,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let server: Rc<dyn AgentServer> = Rc::new(agent2::NativeAgentServer::new(self.fs.clone()));
+        let thread_view = cx.new(|cx| {
+            crate::acp::AcpThreadView::new(
+                server,
+                crate::acp::ThreadSource::Saved(id),
+                self.workspace.clone(),
+                self.project.clone(),
+                self.thread_store.clone(),
+                self.context_store.clone(),
+                window,
+                cx,
+            )
+        });
+        self.set_active_view(ActiveView::ExternalAgentThread { thread_view }, window, cx);
+    }
+
     fn deploy_rules_library(
         &mut self,
         action: &OpenRulesLibrary,
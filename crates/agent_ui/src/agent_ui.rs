@@ -7,6 +7,7 @@ mod agent_panel;
 mod buffer_codegen;
 mod context_picker;
 mod context_server_configuration;
+mod context_server_sampling;
 mod context_strip;
 mod debug;
 mod inline_assistant;
@@ -125,6 +126,14 @@ actions!(
         ContinueWithBurnMode,
         /// Toggles burn mode for faster responses.
         ToggleBurnMode,
+        /// Summarizes the earlier messages of the current thread to free up context.
+        CompactThread,
+        /// Exports the current thread to a JSON or Markdown file.
+        ExportThread,
+        /// Imports a thread from a JSON or Markdown file exported earlier.
+        ImportThread,
+        /// Searches the saved threads of Zed's agent, to resume one.
+        SearchThreads,
     ]
 );
 
@@ -227,6 +236,7 @@ pub fn init(
     agent::init(cx);
     agent_panel::init(cx);
     context_server_configuration::init(language_registry.clone(), fs.clone(), cx);
+    context_server_sampling::init(fs.clone(), cx);
     TextThreadEditor::init(cx);
 
     register_slash_commands(cx);
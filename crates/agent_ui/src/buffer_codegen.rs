@@ -15,8 +15,9 @@ use futures::{
 use gpui::{App, AppContext as _, Context, Entity, EventEmitter, Subscription, Task, WeakEntity};
 use language::{Buffer, IndentKind, Point, TransactionId, line_diff};
 use language_model::{
-    LanguageModel, LanguageModelRegistry, LanguageModelRequest, LanguageModelRequestMessage,
-    LanguageModelTextStream, Role, report_assistant_event,
+    LanguageModel, LanguageModelId, LanguageModelProviderId, LanguageModelRegistry,
+    LanguageModelRequest, LanguageModelRequestMessage, LanguageModelTextStream, Role, UsageLedger,
+    UsageRecord, UsageSource, report_assistant_event,
 };
 use multi_buffer::MultiBufferRow;
 use parking_lot::Mutex;
@@ -256,6 +257,8 @@ pub struct CodegenAlternative {
     line_operations: Vec<LineOperation>,
     elapsed_time: Option<f64>,
     completion: Option<String>,
+    /// The model of the last request, which its token usage is recorded against.
+    usage_model: Option<(LanguageModelProviderId, LanguageModelId)>,
     pub message_id: Option<String>,
 }
 
@@ -321,6 +324,7 @@ impl CodegenAlternative {
             range,
             elapsed_time: None,
             completion: None,
+            usage_model: None,
         }
     }
 
@@ -380,12 +384,14 @@ impl CodegenAlternative {
         self.edit_position = Some(self.range.start.bias_right(&self.snapshot));
 
         let api_key = model.api_key(cx);
+        self.usage_model = Some((model.provider_id(), model.id()));
         let telemetry_id = model.telemetry_id();
         let provider_id = model.provider_id();
         let stream: LocalBoxFuture<Result<LanguageModelTextStream>> =
             if user_prompt.trim().to_lowercase() == "delete" {
                 async { Ok(LanguageModelTextStream::default()) }.boxed_local()
             } else {
+                UsageLedger::check_global_budget(cx)?;
                 let request = self.build_request(&model, user_prompt, cx)?;
                 cx.spawn(async move |_, cx| {
                     Ok(model.stream_completion_text(request.await, &cx).await?)
@@ -532,6 +538,7 @@ impl CodegenAlternative {
         let mut edit_start = self.range.start.to_offset(&snapshot);
         let completion = Arc::new(Mutex::new(String::new()));
         let completion_clone = completion.clone();
+        let usage_model = self.usage_model.clone();
 
         self.generation = cx.spawn(async move |codegen, cx| {
             let stream = stream.await;
@@ -739,14 +746,26 @@ impl CodegenAlternative {
                     this.elapsed_time = Some(elapsed_time);
                     this.completion = Some(completion.lock().clone());
                     if let Some(usage) = token_usage {
-                        let usage = usage.lock();
+                        let usage = *usage.lock();
                         telemetry::event!(
                             "Inline Assistant Completion",
                             model = model_telemetry_id,
                             model_provider = model_provider_id,
                             input_tokens = usage.input_tokens,
                             output_tokens = usage.output_tokens,
-                        )
+                        );
+                        if let Some((provider, model)) = usage_model {
+                            UsageLedger::record_global(
+                                UsageRecord {
+                                    source: UsageSource::InlineAssist,
+                                    thread_id: None,
+                                    provider,
+                                    model,
+                                    usage,
+                                },
+                                cx,
+                            );
+                        }
                     }
                     cx.emit(CodegenEvent::Finished);
                     cx.notify();
@@ -1,6 +1,7 @@
 mod completion_provider;
 pub(crate) mod fetch_context_picker;
 pub(crate) mod file_context_picker;
+pub(crate) mod mcp_resource_context_picker;
 pub(crate) mod rules_context_picker;
 pub(crate) mod symbol_context_picker;
 pub(crate) mod thread_context_picker;
@@ -22,6 +23,7 @@ use gpui::{
     WeakEntity,
 };
 use language::Buffer;
+use mcp_resource_context_picker::{McpResourceContextPicker, has_mcp_resources};
 use multi_buffer::MultiBufferRow;
 use paths::contexts_dir;
 use project::{Entry, ProjectPath};
@@ -81,6 +83,7 @@ pub(crate) enum ContextPickerMode {
     Fetch,
     Thread,
     Rules,
+    McpResources,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -118,6 +121,7 @@ impl TryFrom<&str> for ContextPickerMode {
             "fetch" => Ok(Self::Fetch),
             "thread" => Ok(Self::Thread),
             "rule" => Ok(Self::Rules),
+            "resource" => Ok(Self::McpResources),
             _ => Err(format!("Invalid context picker mode: {}", value)),
         }
     }
@@ -131,6 +135,7 @@ impl ContextPickerMode {
             Self::Fetch => "fetch",
             Self::Thread => "thread",
             Self::Rules => "rule",
+            Self::McpResources => "resource",
         }
     }
 
@@ -141,6 +146,7 @@ impl ContextPickerMode {
             Self::Fetch => "Fetch",
             Self::Thread => "Threads",
             Self::Rules => "Rules",
+            Self::McpResources => "MCP Resources",
         }
     }
 
@@ -151,6 +157,7 @@ impl ContextPickerMode {
             Self::Fetch => IconName::ToolWeb,
             Self::Thread => IconName::Thread,
             Self::Rules => RULES_ICON,
+            Self::McpResources => IconName::Server,
         }
     }
 }
@@ -163,6 +170,7 @@ enum ContextPickerState {
     Fetch(Entity<FetchContextPicker>),
     Thread(Entity<ThreadContextPicker>),
     Rules(Entity<RulesContextPicker>),
+    McpResources(Entity<McpResourceContextPicker>),
 }
 
 pub(super) struct ContextPicker {
@@ -337,6 +345,17 @@ impl ContextPicker {
                         }));
                     }
                 }
+                ContextPickerMode::McpResources => {
+                    self.mode = ContextPickerState::McpResources(cx.new(|cx| {
+                        McpResourceContextPicker::new(
+                            context_picker.clone(),
+                            self.workspace.clone(),
+                            self.context_store.clone(),
+                            window,
+                            cx,
+                        )
+                    }));
+                }
                 ContextPickerMode::Fetch => {
                     self.mode = ContextPickerState::Fetch(cx.new(|cx| {
                         FetchContextPicker::new(
@@ -550,6 +569,7 @@ impl ContextPicker {
             ContextPickerState::Fetch(entity) => entity.update(cx, |_, cx| cx.notify()),
             ContextPickerState::Thread(entity) => entity.update(cx, |_, cx| cx.notify()),
             ContextPickerState::Rules(entity) => entity.update(cx, |_, cx| cx.notify()),
+            ContextPickerState::McpResources(entity) => entity.update(cx, |_, cx| cx.notify()),
         }
     }
 }
@@ -565,6 +585,7 @@ impl Focusable for ContextPicker {
             ContextPickerState::Fetch(fetch_picker) => fetch_picker.focus_handle(cx),
             ContextPickerState::Thread(thread_picker) => thread_picker.focus_handle(cx),
             ContextPickerState::Rules(user_rules_picker) => user_rules_picker.focus_handle(cx),
+            ContextPickerState::McpResources(resource_picker) => resource_picker.focus_handle(cx),
         }
     }
 }
@@ -583,6 +604,9 @@ impl Render for ContextPicker {
                 ContextPickerState::Rules(user_rules_picker) => {
                     parent.child(user_rules_picker.clone())
                 }
+                ContextPickerState::McpResources(resource_picker) => {
+                    parent.child(resource_picker.clone())
+                }
             })
     }
 }
@@ -627,6 +651,10 @@ pub(crate) fn available_context_picker_entries(
         entries.push(ContextPickerEntry::Mode(ContextPickerMode::Rules));
     }
 
+    if has_mcp_resources(workspace, cx) {
+        entries.push(ContextPickerEntry::Mode(ContextPickerMode::McpResources));
+    }
+
     entries.push(ContextPickerEntry::Mode(ContextPickerMode::Fetch));
 
     entries
@@ -890,6 +918,7 @@ pub enum MentionLink {
     Thread(ThreadId),
     TextThread(Arc<Path>),
     Rule(UserPromptId),
+    McpResource(String),
 }
 
 impl MentionLink {
@@ -899,6 +928,7 @@ impl MentionLink {
     const THREAD: &str = "@thread";
     const FETCH: &str = "@fetch";
     const RULE: &str = "@rule";
+    const RESOURCE: &str = "@resource";
 
     const TEXT_THREAD_URL_PREFIX: &str = "text-thread://";
 
@@ -911,6 +941,7 @@ impl MentionLink {
             || url.starts_with(Self::SELECTION)
             || url.starts_with(Self::THREAD)
             || url.starts_with(Self::RULE)
+            || url.starts_with(Self::RESOURCE)
     }
 
     pub fn for_file(file_name: &str, full_path: &str) -> String {
@@ -967,6 +998,10 @@ impl MentionLink {
         format!("[@{}]({}:{})", rule.title, Self::RULE, rule.prompt_id.0)
     }
 
+    pub fn for_mcp_resource(name: &str, uri: &str) -> String {
+        format!("[@{}]({}:{})", name, Self::RESOURCE, uri)
+    }
+
     pub fn try_parse(link: &str, workspace: &Entity<Workspace>, cx: &App) -> Option<Self> {
         fn extract_project_path_from_link(
             path: &str,
@@ -1033,6 +1068,7 @@ impl MentionLink {
                 let prompt_id = UserPromptId(Uuid::try_parse(argument).ok()?);
                 Some(MentionLink::Rule(prompt_id))
             }
+            Self::RESOURCE => Some(MentionLink::McpResource(argument.to_string())),
             _ => None,
         }
     }
@@ -29,6 +29,9 @@ use agent::{
 
 use super::fetch_context_picker::fetch_url_content;
 use super::file_context_picker::{FileMatch, search_files};
+use super::mcp_resource_context_picker::{
+    McpResourceEntry, McpResourceMatch, search_mcp_resources,
+};
 use super::rules_context_picker::{RulesContextEntry, search_rules};
 use super::symbol_context_picker::SymbolMatch;
 use super::symbol_context_picker::search_symbols;
@@ -45,6 +48,7 @@ pub(crate) enum Match {
     Thread(ThreadMatch),
     Fetch(SharedString),
     Rules(RulesContextEntry),
+    McpResource(McpResourceMatch),
     Entry(EntryMatch),
 }
 
@@ -62,6 +66,7 @@ impl Match {
             Match::Symbol(_) => 1.,
             Match::Fetch(_) => 1.,
             Match::Rules(_) => 1.,
+            Match::McpResource(_) => 1.,
         }
     }
 }
@@ -151,6 +156,18 @@ fn search(
             }
         }
 
+        Some(ContextPickerMode::McpResources) => {
+            let search_resources_task =
+                search_mcp_resources(query, cancellation_flag, &workspace, cx);
+            cx.background_spawn(async move {
+                search_resources_task
+                    .await
+                    .into_iter()
+                    .map(Match::McpResource)
+                    .collect()
+            })
+        }
+
         None => {
             if query.is_empty() {
                 let mut matches = recent_entries
@@ -523,6 +540,76 @@ impl ContextPickerCompletionProvider {
         }
     }
 
+    fn completion_for_mcp_resource(
+        resource: McpResourceMatch,
+        excerpt_id: ExcerptId,
+        source_range: Range<Anchor>,
+        editor: Entity<Editor>,
+        context_store: Entity<ContextStore>,
+        cx: &App,
+    ) -> Completion {
+        let icon_path: SharedString = IconName::Server.path().into();
+        let resource = match resource {
+            McpResourceMatch::Resource(resource) => resource,
+            McpResourceMatch::Template(template) => {
+                return Completion {
+                    replace_range: source_range,
+                    new_text: format!(
+                        "@{} {}",
+                        ContextPickerMode::McpResources.keyword(),
+                        template.query
+                    ),
+                    label: build_code_label_for_full_path(
+                        &template.label,
+                        template.description.as_deref(),
+                        cx,
+                    ),
+                    documentation: None,
+                    insert_text_mode: None,
+                    source: project::CompletionSource::Custom,
+                    icon_path: Some(icon_path),
+                    // Keep the completion menu open to fill in the template's next argument.
+                    confirm: Some(Arc::new(|_, _, _| true)),
+                };
+            }
+        };
+
+        let new_text = format!(
+            "{} ",
+            MentionLink::for_mcp_resource(&resource.name, &resource.uri)
+        );
+        let new_text_len = new_text.len();
+        Completion {
+            replace_range: source_range.clone(),
+            new_text,
+            label: build_code_label_for_full_path(&resource.name, Some(&resource.uri), cx),
+            documentation: None,
+            insert_text_mode: None,
+            source: project::CompletionSource::Custom,
+            icon_path: Some(icon_path.clone()),
+            confirm: Some(confirm_completion_callback(
+                icon_path,
+                resource.name.clone(),
+                excerpt_id,
+                source_range.start,
+                new_text_len - 1,
+                editor.clone(),
+                context_store.clone(),
+                move |_, cx| {
+                    let McpResourceEntry {
+                        server_id,
+                        uri,
+                        name,
+                    } = resource.clone();
+                    let context = context_store.update(cx, |context_store, cx| {
+                        context_store.add_mcp_resource(server_id, uri, name, false, cx)
+                    });
+                    Task::ready(context)
+                },
+            )),
+        }
+    }
+
     fn completion_for_fetch(
         source_range: Range<Anchor>,
         url_to_fetch: SharedString,
@@ -884,6 +971,15 @@ impl CompletionProvider for ContextPickerCompletionProvider {
                             context_store.clone(),
                         )),
 
+                        Match::McpResource(resource) => Some(Self::completion_for_mcp_resource(
+                            resource,
+                            excerpt_id,
+                            source_range.clone(),
+                            editor.clone(),
+                            context_store.clone(),
+                            cx,
+                        )),
+
                         Match::Fetch(url) => Some(Self::completion_for_fetch(
                             source_range.clone(),
                             url,
@@ -1041,6 +1137,13 @@ impl MentionCompletion {
                 argument = Some(mode_text.to_string());
             }
             match rest_of_line[mode_text.len()..].find(|c: char| !c.is_whitespace()) {
+                // Resource template arguments are separated by whitespace, so the
+                // argument spans the rest of the line.
+                Some(whitespace_count) if mode == Some(ContextPickerMode::McpResources) => {
+                    let argument_text = &rest_of_line[mode_text.len() + whitespace_count..];
+                    argument = Some(argument_text.to_string());
+                    end += whitespace_count + argument_text.len();
+                }
                 Some(whitespace_count) => {
                     if let Some(argument_text) = parts.next() {
                         argument = Some(argument_text.to_string());
@@ -0,0 +1,456 @@
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+
+use collections::HashMap;
+use context_server::protocol::{InitializedContextServerProtocol, ServerCapability};
+use context_server::types::{
+    CompletionArgument, CompletionCompleteParams, CompletionReference, PromptReferenceType,
+    ResourceReference, ResourceTemplate, requests,
+};
+use context_server::{ContextServer, ContextServerId};
+use fuzzy::StringMatchCandidate;
+use gpui::{App, DismissEvent, Entity, FocusHandle, Focusable, Task, WeakEntity};
+use picker::{Picker, PickerDelegate};
+use ui::{ListItem, prelude::*};
+use util::ResultExt as _;
+use workspace::Workspace;
+
+use crate::context_picker::ContextPicker;
+use agent::context::ContextKind;
+use agent::context_store::ContextStore;
+
+pub struct McpResourceContextPicker {
+    picker: Entity<Picker<McpResourceContextPickerDelegate>>,
+}
+
+impl McpResourceContextPicker {
+    pub fn new(
+        context_picker: WeakEntity<ContextPicker>,
+        workspace: WeakEntity<Workspace>,
+        context_store: WeakEntity<ContextStore>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let delegate =
+            McpResourceContextPickerDelegate::new(context_picker, workspace, context_store);
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+
+        McpResourceContextPicker { picker }
+    }
+}
+
+impl Focusable for McpResourceContextPicker {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for McpResourceContextPicker {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        self.picker.clone()
+    }
+}
+
+/// A resource provided by a context server.
+#[derive(Debug, Clone)]
+pub struct McpResourceEntry {
+    pub server_id: ContextServerId,
+    pub uri: SharedString,
+    pub name: SharedString,
+}
+
+/// A resource template whose arguments are being filled in.
+#[derive(Debug, Clone)]
+pub struct McpResourceTemplateEntry {
+    pub server_id: ContextServerId,
+    pub label: SharedString,
+    pub description: Option<SharedString>,
+    /// The query that continues with the template's next argument once this entry is confirmed.
+    pub query: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum McpResourceMatch {
+    Resource(McpResourceEntry),
+    Template(McpResourceTemplateEntry),
+}
+
+pub struct McpResourceContextPickerDelegate {
+    context_picker: WeakEntity<ContextPicker>,
+    workspace: WeakEntity<Workspace>,
+    context_store: WeakEntity<ContextStore>,
+    matches: Vec<McpResourceMatch>,
+    selected_index: usize,
+}
+
+impl McpResourceContextPickerDelegate {
+    pub fn new(
+        context_picker: WeakEntity<ContextPicker>,
+        workspace: WeakEntity<Workspace>,
+        context_store: WeakEntity<ContextStore>,
+    ) -> Self {
+        McpResourceContextPickerDelegate {
+            context_picker,
+            workspace,
+            context_store,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for McpResourceContextPickerDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Search resources…".into()
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return Task::ready(());
+        };
+
+        let search_task =
+            search_mcp_resources(query, Arc::new(AtomicBool::default()), &workspace, cx);
+        cx.spawn_in(window, async move |this, cx| {
+            let matches = search_task.await;
+            this.update(cx, |this, cx| {
+                this.delegate.matches = matches;
+                this.delegate.selected_index = 0;
+                cx.notify();
+            })
+            .ok();
+        })
+    }
+
+    fn confirm_update_query(
+        &mut self,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) -> Option<String> {
+        match self.matches.get(self.selected_index)? {
+            McpResourceMatch::Template(template) => Some(template.query.clone()),
+            McpResourceMatch::Resource(_) => None,
+        }
+    }
+
+    fn confirm(&mut self, _secondary: bool, _window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(McpResourceMatch::Resource(resource)) = self.matches.get(self.selected_index)
+        else {
+            return;
+        };
+
+        self.context_store
+            .update(cx, |context_store, cx| {
+                context_store.add_mcp_resource(
+                    resource.server_id.clone(),
+                    resource.uri.clone(),
+                    resource.name.clone(),
+                    true,
+                    cx,
+                )
+            })
+            .log_err();
+    }
+
+    fn dismissed(&mut self, _window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.context_picker
+            .update(cx, |_, cx| {
+                cx.emit(DismissEvent);
+            })
+            .ok();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let entry = &self.matches[ix];
+
+        Some(ListItem::new(ix).inset(true).toggle_state(selected).child(
+            render_mcp_resource_context_entry(entry, self.context_store.clone(), cx),
+        ))
+    }
+}
+
+pub fn render_mcp_resource_context_entry(
+    entry: &McpResourceMatch,
+    context_store: WeakEntity<ContextStore>,
+    cx: &mut App,
+) -> Div {
+    let (label, detail, added) = match entry {
+        McpResourceMatch::Resource(resource) => {
+            let added = context_store.upgrade().map_or(false, |context_store| {
+                context_store
+                    .read(cx)
+                    .includes_mcp_resource(&resource.server_id, resource.uri.clone())
+            });
+            (resource.name.clone(), Some(resource.uri.clone()), added)
+        }
+        McpResourceMatch::Template(template) => {
+            (template.label.clone(), template.description.clone(), false)
+        }
+    };
+
+    h_flex()
+        .gap_1p5()
+        .w_full()
+        .justify_between()
+        .child(
+            h_flex()
+                .gap_1p5()
+                .max_w_72()
+                .child(
+                    Icon::new(ContextKind::McpResource.icon())
+                        .size(IconSize::XSmall)
+                        .color(Color::Muted),
+                )
+                .child(Label::new(label).truncate())
+                .children(detail.map(|detail| {
+                    Label::new(detail)
+                        .size(LabelSize::Small)
+                        .color(Color::Muted)
+                        .truncate()
+                })),
+        )
+        .when(added, |el| {
+            el.child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Icon::new(IconName::Check)
+                            .size(IconSize::Small)
+                            .color(Color::Success),
+                    )
+                    .child(Label::new("Added").size(LabelSize::Small)),
+            )
+        })
+}
+
+/// Whether any running context server provides resources.
+pub(crate) fn has_mcp_resources(workspace: &Entity<Workspace>, cx: &App) -> bool {
+    resource_servers(workspace, cx).next().is_some()
+}
+
+fn resource_servers(
+    workspace: &Entity<Workspace>,
+    cx: &App,
+) -> impl Iterator<Item = (Arc<ContextServer>, Arc<InitializedContextServerProtocol>)> {
+    workspace
+        .read(cx)
+        .project()
+        .read(cx)
+        .context_server_store()
+        .read(cx)
+        .running_servers()
+        .into_iter()
+        .filter_map(|server| {
+            let client = server.client()?;
+            client
+                .capable(ServerCapability::Resources)
+                .then_some((server, client))
+        })
+}
+
+/// Searches the resources and resource templates of the running context servers.
+///
+/// Once the query starts with the name of a resource template followed by a space, the rest of
+/// the query is taken as the template's whitespace-separated arguments, and the matches are the
+/// server's completions for the argument being typed.
+pub(crate) fn search_mcp_resources(
+    query: String,
+    cancellation_flag: Arc<AtomicBool>,
+    workspace: &Entity<Workspace>,
+    cx: &mut App,
+) -> Task<Vec<McpResourceMatch>> {
+    let servers = resource_servers(workspace, cx).collect::<Vec<_>>();
+    let executor = cx.background_executor().clone();
+    cx.background_spawn(async move {
+        let mut resources = Vec::new();
+        let mut templates = Vec::new();
+        for (server, client) in servers {
+            if let Some(response) = client
+                .request::<requests::ResourcesList>(())
+                .await
+                .log_err()
+            {
+                resources.extend(
+                    response
+                        .resources
+                        .into_iter()
+                        .map(|resource| McpResourceEntry {
+                            server_id: server.id(),
+                            uri: resource.uri.to_string().into(),
+                            name: resource.name.into(),
+                        }),
+                );
+            }
+            if let Some(response) = client
+                .request::<requests::ListResourceTemplates>(())
+                .await
+                .log_err()
+            {
+                templates.extend(
+                    response
+                        .resource_templates
+                        .into_iter()
+                        .map(|template| (server.id(), client.clone(), template)),
+                );
+            }
+        }
+
+        for (server_id, client, template) in &templates {
+            if let Some(arguments) = query
+                .strip_prefix(template.name.as_str())
+                .and_then(|rest| rest.strip_prefix(' '))
+            {
+                return complete_template_arguments(server_id, client, template, arguments).await;
+            }
+        }
+
+        let candidates = resources
+            .iter()
+            .map(|resource| resource.name.as_ref())
+            .chain(
+                templates
+                    .iter()
+                    .map(|(_, _, template)| template.name.as_str()),
+            )
+            .enumerate()
+            .map(|(ix, name)| StringMatchCandidate::new(ix, name))
+            .collect::<Vec<_>>();
+        let string_matches = if query.is_empty() {
+            None
+        } else {
+            Some(
+                fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    true,
+                    100,
+                    &cancellation_flag,
+                    executor,
+                )
+                .await,
+            )
+        };
+
+        let entry = |ix: usize| {
+            if let Some(resource) = resources.get(ix) {
+                McpResourceMatch::Resource(resource.clone())
+            } else {
+                let (server_id, _, template) = &templates[ix - resources.len()];
+                McpResourceMatch::Template(McpResourceTemplateEntry {
+                    server_id: server_id.clone(),
+                    label: template.name.clone().into(),
+                    description: Some(template.uri_template.clone().into()),
+                    query: format!("{} ", template.name),
+                })
+            }
+        };
+        match string_matches {
+            Some(string_matches) => string_matches
+                .into_iter()
+                .map(|mat| entry(mat.candidate_id))
+                .collect(),
+            None => (0..candidates.len()).map(entry).collect(),
+        }
+    })
+}
+
+async fn complete_template_arguments(
+    server_id: &ContextServerId,
+    client: &InitializedContextServerProtocol,
+    template: &ResourceTemplate,
+    arguments: &str,
+) -> Vec<McpResourceMatch> {
+    let variables = template.variables();
+    let mut values = arguments.split_whitespace().collect::<Vec<_>>();
+    let partial_value = if arguments.is_empty() || arguments.ends_with(char::is_whitespace) {
+        ""
+    } else {
+        values.pop().unwrap_or_default()
+    };
+    let Some(variable) = variables.get(values.len()) else {
+        return Vec::new();
+    };
+
+    let mut suggestions = client
+        .request::<requests::CompletionComplete>(CompletionCompleteParams {
+            reference: CompletionReference::Resource(ResourceReference {
+                ty: PromptReferenceType::Resource,
+                uri: template.uri_template.clone(),
+            }),
+            argument: CompletionArgument {
+                name: variable.to_string(),
+                value: partial_value.to_string(),
+            },
+            meta: None,
+        })
+        .await
+        .log_err()
+        .map(|response| response.completion.values)
+        .unwrap_or_default();
+    if suggestions.is_empty() && !partial_value.is_empty() {
+        suggestions.push(partial_value.to_string());
+    }
+
+    let is_last_variable = values.len() + 1 == variables.len();
+    suggestions
+        .into_iter()
+        .map(|suggestion| {
+            let mut filled_values = values
+                .iter()
+                .map(|value| value.to_string())
+                .collect::<Vec<_>>();
+            filled_values.push(suggestion.clone());
+            let query = format!("{} {}", template.name, filled_values.join(" "));
+            if is_last_variable {
+                let values = variables
+                    .iter()
+                    .map(|variable| variable.to_string())
+                    .zip(filled_values)
+                    .collect::<HashMap<_, _>>();
+                McpResourceMatch::Resource(McpResourceEntry {
+                    server_id: server_id.clone(),
+                    uri: template.expand(&values).into(),
+                    name: query.into(),
+                })
+            } else {
+                McpResourceMatch::Template(McpResourceTemplateEntry {
+                    server_id: server_id.clone(),
+                    label: suggestion.into(),
+                    description: Some(format!("{{{variable}}}").into()),
+                    query: format!("{query} "),
+                })
+            }
+        })
+        .collect()
+}
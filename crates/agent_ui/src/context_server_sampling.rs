@@ -0,0 +1,254 @@
+use std::sync::Arc;
+
+use agent_settings::AgentSettings;
+use anyhow::{Context as _, Result, anyhow, bail};
+use base64::Engine as _;
+use context_server::ContextServerId;
+use context_server::types::{
+    CreateMessageRequest, CreateMessageResult, MessageContent, ModelPreferences, Role,
+};
+use fs::Fs;
+use futures::StreamExt as _;
+use gpui::{App, AsyncApp, Image, ImageFormat, PromptLevel, Task};
+use language_model::{
+    LanguageModel, LanguageModelImage, LanguageModelRegistry, LanguageModelRequest,
+    LanguageModelRequestMessage,
+};
+use project::context_server_store::{ContextServerSampler, ContextServerStore};
+use settings::{Settings as _, update_settings_file};
+
+/// How many characters of the request to show when asking for approval.
+const PREVIEW_LEN: usize = 500;
+
+pub(crate) fn init(fs: Arc<dyn Fs>, cx: &mut App) {
+    ContextServerStore::set_global_sampler(Arc::new(AgentContextServerSampler { fs }), cx);
+}
+
+/// Answers sampling requests from context servers with the user's language models, after the
+/// user approves them.
+struct AgentContextServerSampler {
+    fs: Arc<dyn Fs>,
+}
+
+impl ContextServerSampler for AgentContextServerSampler {
+    fn create_message(
+        &self,
+        server_id: ContextServerId,
+        request: CreateMessageRequest,
+        cx: &mut App,
+    ) -> Task<Result<CreateMessageResult>> {
+        let settings = &AgentSettings::get_global(cx).context_server_sampling;
+        let max_tokens = u64::from(request.max_tokens).min(settings.max_tokens);
+        let approval = if settings.is_allowed(&server_id.0) {
+            Task::ready(Ok(()))
+        } else {
+            request_approval(server_id, &request, max_tokens, self.fs.clone(), cx)
+        };
+        let Some(model) = select_model(request.model_preferences.as_ref(), cx) else {
+            return Task::ready(Err(anyhow!("no language model is configured")));
+        };
+
+        cx.spawn(async move |cx| {
+            approval.await?;
+            sample(model, request, max_tokens, cx).await
+        })
+    }
+}
+
+fn request_approval(
+    server_id: ContextServerId,
+    request: &CreateMessageRequest,
+    max_tokens: u64,
+    fs: Arc<dyn Fs>,
+    cx: &mut App,
+) -> Task<Result<()>> {
+    let Some(window) = cx.active_window() else {
+        return Task::ready(Err(anyhow!("no window to ask for approval in")));
+    };
+
+    let message = format!("The {server_id} MCP server wants to use your language model");
+    let detail = request_preview(request, max_tokens);
+    let answer = window.update(cx, |_, window, cx| {
+        window.prompt(
+            PromptLevel::Warning,
+            &message,
+            Some(&detail),
+            &["Allow", "Always Allow", "Deny"],
+            cx,
+        )
+    });
+
+    cx.spawn(async move |cx| match answer?.await? {
+        0 => Ok(()),
+        1 => {
+            cx.update(|cx| {
+                update_settings_file::<AgentSettings>(fs, cx, move |settings, _| {
+                    settings.allow_context_server_sampling(server_id.0)
+                })
+            })?;
+            Ok(())
+        }
+        _ => bail!("the user denied the sampling request"),
+    })
+}
+
+fn request_preview(request: &CreateMessageRequest, max_tokens: u64) -> String {
+    let mut preview = String::new();
+    if let Some(system_prompt) = &request.system_prompt {
+        preview.push_str(system_prompt);
+        preview.push_str("\n\n");
+    }
+    if let Some(MessageContent::Text { text, .. }) =
+        request.messages.last().map(|message| &message.content)
+    {
+        preview.push_str(text);
+    }
+    if preview.len() > PREVIEW_LEN {
+        let mut end = PREVIEW_LEN;
+        while !preview.is_char_boundary(end) {
+            end -= 1;
+        }
+        preview.truncate(end);
+        preview.push('…');
+    }
+    preview.push_str(&format!("\n\nUp to {max_tokens} tokens will be generated."));
+    preview
+}
+
+/// Picks the first authenticated model matching the server's hints, falling back to the default
+/// model.
+fn select_model(
+    preferences: Option<&ModelPreferences>,
+    cx: &App,
+) -> Option<Arc<dyn LanguageModel>> {
+    let registry = LanguageModelRegistry::read_global(cx);
+    let hints = preferences
+        .and_then(|preferences| preferences.hints.as_ref())
+        .into_iter()
+        .flatten()
+        .filter_map(|hint| hint.name.as_deref());
+    for hint in hints {
+        let model = registry.available_models(cx).find(|model| {
+            registry
+                .provider(&model.provider_id())
+                .is_some_and(|provider| provider.is_authenticated(cx))
+                && model_matches_hint(&model.id().0, &model.name().0, hint)
+        });
+        if model.is_some() {
+            return model;
+        }
+    }
+    registry.default_model().map(|configured| configured.model)
+}
+
+/// Hints are treated as substrings of model names, as the MCP specification suggests.
+fn model_matches_hint(model_id: &str, model_name: &str, hint: &str) -> bool {
+    let hint = hint.to_lowercase();
+    model_id.to_lowercase().contains(&hint) || model_name.to_lowercase().contains(&hint)
+}
+
+async fn sample(
+    model: Arc<dyn LanguageModel>,
+    request: CreateMessageRequest,
+    max_tokens: u64,
+    cx: &mut AsyncApp,
+) -> Result<CreateMessageResult> {
+    let mut messages = Vec::new();
+    if let Some(system_prompt) = request.system_prompt {
+        messages.push(LanguageModelRequestMessage {
+            role: language_model::Role::System,
+            content: vec![system_prompt.into()],
+            cache: false,
+        });
+    }
+    for message in request.messages {
+        let content = match message.content {
+            MessageContent::Text { text, .. } => text.into(),
+            MessageContent::Image {
+                data, mime_type, ..
+            } => {
+                let format = ImageFormat::from_mime_type(&mime_type)
+                    .with_context(|| format!("unsupported image type {mime_type}"))?;
+                let bytes = base64::engine::general_purpose::STANDARD.decode(data)?;
+                let image = Arc::new(Image::from_bytes(format, bytes));
+                let image = cx
+                    .update(|cx| LanguageModelImage::from_image(image, cx))?
+                    .await
+                    .context("failed to process image")?;
+                language_model::MessageContent::Image(image)
+            }
+            MessageContent::Audio { .. } | MessageContent::Resource { .. } => {
+                bail!("only text and image content is supported")
+            }
+        };
+        messages.push(LanguageModelRequestMessage {
+            role: match message.role {
+                Role::User => language_model::Role::User,
+                Role::Assistant => language_model::Role::Assistant,
+            },
+            content: vec![content],
+            cache: false,
+        });
+    }
+
+    let request = LanguageModelRequest {
+        messages,
+        stop: request.stop_sequences.unwrap_or_default(),
+        temperature: request.temperature.map(|temperature| temperature as f32),
+        ..Default::default()
+    };
+    let input_tokens = cx
+        .update(|cx| model.count_tokens(request.clone(), cx))?
+        .await?;
+    if input_tokens + max_tokens > model.max_token_count() {
+        bail!(
+            "the request needs {} tokens, but {} supports at most {}",
+            input_tokens + max_tokens,
+            model.name().0,
+            model.max_token_count()
+        );
+    }
+
+    let mut response = model.stream_completion_text(request, cx).await?;
+    let mut text = String::new();
+    let mut stop_reason = "endTurn";
+    while let Some(chunk) = response.stream.next().await {
+        text.push_str(&chunk?);
+        // Not every provider reports usage while streaming, so also estimate it from the text.
+        let output_tokens = response
+            .last_token_usage
+            .lock()
+            .output_tokens
+            .max(text.len() as u64 / 4);
+        if output_tokens >= max_tokens {
+            stop_reason = "maxTokens";
+            break;
+        }
+    }
+
+    Ok(CreateMessageResult {
+        role: Role::Assistant,
+        content: MessageContent::Text {
+            text,
+            annotations: None,
+        },
+        model: model.id().0.to_string(),
+        stop_reason: Some(stop_reason.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_matches_hint() {
+        assert!(model_matches_hint(
+            "claude-3-5-sonnet-latest",
+            "Claude 3.5 Sonnet",
+            "sonnet"
+        ));
+        assert!(model_matches_hint("gpt-4o", "GPT-4o", "GPT-4"));
+        assert!(!model_matches_hint("gpt-4o", "GPT-4o", "claude"));
+    }
+}
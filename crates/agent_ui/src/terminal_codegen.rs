@@ -3,7 +3,8 @@ use client::telemetry::Telemetry;
 use futures::{SinkExt, StreamExt, channel::mpsc};
 use gpui::{App, AppContext as _, Context, Entity, EventEmitter, Task};
 use language_model::{
-    ConfiguredModel, LanguageModelRegistry, LanguageModelRequest, report_assistant_event,
+    ConfiguredModel, LanguageModelRegistry, LanguageModelRequest, UsageLedger, UsageRecord,
+    UsageSource, report_assistant_event,
 };
 use std::{sync::Arc, time::Instant};
 use telemetry_events::{AssistantEventData, AssistantKind, AssistantPhase};
@@ -38,6 +39,12 @@ impl TerminalCodegen {
         else {
             return;
         };
+        if let Err(error) = UsageLedger::check_global_budget(cx) {
+            self.status = CodegenStatus::Error(error.into());
+            cx.emit(CodegenEvent::Finished);
+            cx.notify();
+            return;
+        }
 
         let model_api_key = model.api_key(cx);
         let http_client = cx.http_client();
@@ -49,6 +56,10 @@ impl TerminalCodegen {
             let model_telemetry_id = model.telemetry_id();
             let model_provider_id = model.provider_id();
             let response = model.stream_completion_text(prompt, &cx).await;
+            let token_usage = response
+                .as_ref()
+                .ok()
+                .map(|response| response.last_token_usage.clone());
             let generate = async {
                 let message_id = response
                     .as_ref()
@@ -127,6 +138,18 @@ impl TerminalCodegen {
                 } else {
                     this.status = CodegenStatus::Done;
                 }
+                if let Some(usage) = token_usage {
+                    UsageLedger::record_global(
+                        UsageRecord {
+                            source: UsageSource::InlineAssist,
+                            thread_id: None,
+                            provider: model.provider_id(),
+                            model: model.id(),
+                            usage: *usage.lock(),
+                        },
+                        cx,
+                    );
+                }
                 cx.emit(CodegenEvent::Finished);
                 cx.notify();
             })
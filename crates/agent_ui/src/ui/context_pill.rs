@@ -15,9 +15,9 @@ use ui::{IconButtonShape, Tooltip, prelude::*, tooltip_container};
 use agent::context::{
     AgentContext, AgentContextHandle, ContextId, ContextKind, DirectoryContext,
     DirectoryContextHandle, FetchedUrlContext, FileContext, FileContextHandle, ImageContext,
-    ImageStatus, RulesContext, RulesContextHandle, SelectionContext, SelectionContextHandle,
-    SymbolContext, SymbolContextHandle, TextThreadContext, TextThreadContextHandle, ThreadContext,
-    ThreadContextHandle,
+    ImageStatus, McpResourceContextHandle, RulesContext, RulesContextHandle, SelectionContext,
+    SelectionContextHandle, SymbolContext, SymbolContextHandle, TextThreadContext,
+    TextThreadContextHandle, ThreadContext, ThreadContextHandle,
 };
 
 #[derive(IntoElement)]
@@ -314,6 +314,7 @@ impl AddedContext {
             AgentContextHandle::TextThread(handle) => Some(Self::pending_text_thread(handle, cx)),
             AgentContextHandle::Rules(handle) => Self::pending_rules(handle, prompt_store, cx),
             AgentContextHandle::Image(handle) => Some(Self::image(handle, model, cx)),
+            AgentContextHandle::McpResource(handle) => Some(Self::mcp_resource(handle)),
         }
     }
 
@@ -332,6 +333,7 @@ impl AddedContext {
             AgentContext::TextThread(context) => Self::attached_text_thread(context),
             AgentContext::Rules(context) => Self::attached_rules(context),
             AgentContext::Image(context) => Self::image(context.clone(), model, cx),
+            AgentContext::McpResource(context) => Self::mcp_resource(context.handle.clone()),
         }
     }
 
@@ -481,6 +483,19 @@ impl AddedContext {
         }
     }
 
+    fn mcp_resource(handle: McpResourceContextHandle) -> AddedContext {
+        AddedContext {
+            kind: ContextKind::McpResource,
+            name: handle.name.clone(),
+            parent: Some(handle.server_id.0.as_ref().into()),
+            tooltip: Some(handle.uri.clone()),
+            icon_path: None,
+            status: ContextStatus::Ready,
+            render_hover: None,
+            handle: AgentContextHandle::McpResource(handle),
+        }
+    }
+
     fn pending_thread(handle: ThreadContextHandle, cx: &App) -> AddedContext {
         AddedContext {
             kind: ContextKind::Thread,
@@ -876,6 +876,7 @@ impl ContextStore {
                     _ => {}
                 }
             }
+            _ => {}
         }
     }
 
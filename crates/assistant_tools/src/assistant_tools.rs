@@ -15,6 +15,7 @@ mod project_notifications_tool;
 mod read_file_tool;
 mod schema;
 pub mod templates;
+pub mod terminal_sandbox;
 mod terminal_tool;
 mod thinking_tool;
 mod ui;
@@ -16,7 +16,8 @@ use http_client::StatusCode;
 use indoc::{formatdoc, indoc};
 use language_model::{
     LanguageModelRegistry, LanguageModelRequestTool, LanguageModelToolResult,
-    LanguageModelToolResultContent, LanguageModelToolUse, LanguageModelToolUseId, SelectedModel,
+    LanguageModelToolResultContent, LanguageModelToolUse, LanguageModelToolUseId, RequestCacheMode,
+    SelectedModel,
 };
 use project::Project;
 use prompt_store::{ModelContext, ProjectContext, PromptBuilder, WorktreeContext};
@@ -1521,7 +1522,7 @@ impl EditAgentTest {
         selected_model: &SelectedModel,
         cx: &mut AsyncApp,
     ) -> Result<Arc<dyn LanguageModel>> {
-        let (provider, model) = cx.update(|cx| {
+        let (provider, model, replaying) = cx.update(|cx| {
             let models = LanguageModelRegistry::read_global(cx);
             let model = models
                 .available_models(cx)
@@ -1531,9 +1532,15 @@ impl EditAgentTest {
                 })
                 .expect("Model not found");
             let provider = models.provider(&model.provider_id()).unwrap();
-            (provider, model)
+            let replaying = models
+                .request_cache()
+                .is_some_and(|cache| cache.mode() == RequestCacheMode::Replay);
+            (provider, model, replaying)
         })?;
-        cx.update(|cx| provider.authenticate(cx))?.await?;
+        // Completions recorded with `ZED_LLM_CACHE_DIR` can be replayed without credentials.
+        if !replaying {
+            cx.update(|cx| provider.authenticate(cx))?.await?;
+        }
         Ok(model)
     }
 
@@ -0,0 +1,330 @@
+//! Runs the agent's terminal commands in a sandbox on Linux, using bubblewrap.
+
+use agent_settings::TerminalSandboxSettings;
+use anyhow::{Context as _, Result, bail};
+use gpui::{App, Entity};
+use project::Project;
+use std::path::{Path, PathBuf};
+
+/// Paths within the worktrees that hold commands Zed or Git run outside of the sandbox later on,
+/// such as tasks, language server settings, Git hooks and the commands configured in
+/// `.git/config`, so commands in the sandbox must not change them.
+const PROTECTED_PATHS: &[&str] = &[".zed", ".git/hooks", ".git/config"];
+
+/// The exit code of a shell whose command was killed by `SIGXCPU` for exceeding its CPU time.
+const CPU_LIMIT_EXIT_CODE: u32 = 128 + 24;
+
+/// A sandbox which only lets commands write to the project's worktrees and a private `/tmp`.
+#[derive(Debug, Clone)]
+pub struct TerminalSandbox {
+    settings: TerminalSandboxSettings,
+    writable_roots: Vec<PathBuf>,
+    /// Whether commands run on another machine, where we can't tell whether bubblewrap is
+    /// available.
+    remote: bool,
+}
+
+impl TerminalSandbox {
+    /// Returns the sandbox described by `settings`, or `None` if sandboxing is disabled.
+    pub fn new(settings: &TerminalSandboxSettings, writable_roots: Vec<PathBuf>) -> Option<Self> {
+        settings.enabled.then(|| Self {
+            settings: settings.clone(),
+            writable_roots,
+            remote: false,
+        })
+    }
+
+    /// Returns the sandbox described by `settings` for commands run in `project`, which may
+    /// write to its visible worktrees.
+    pub fn for_project(
+        settings: &TerminalSandboxSettings,
+        project: &Entity<Project>,
+        cx: &App,
+    ) -> Option<Self> {
+        let project = project.read(cx);
+        let writable_roots = project
+            .visible_worktrees(cx)
+            .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
+            .collect();
+        let mut sandbox = Self::new(settings, writable_roots)?;
+        sandbox.remote = !project.is_local();
+        Some(sandbox)
+    }
+
+    /// Wraps the invocation of `program` so it runs inside the sandbox.
+    ///
+    /// Fails rather than running the command unsandboxed when the sandbox isn't available.
+    pub fn wrap(&self, program: String, args: Vec<String>) -> Result<(String, Vec<String>)> {
+        if self.remote {
+            bail!("Sandboxed terminal commands are only supported in local projects");
+        }
+        if !cfg!(target_os = "linux") {
+            bail!("Sandboxed terminal commands are only supported on Linux");
+        }
+        let bwrap = which::which("bwrap")
+            .context("Sandboxed terminal commands require bubblewrap (`bwrap`) to be installed")?;
+        for root in &self.writable_roots {
+            create_protected_dirs(root)?;
+        }
+
+        Ok((
+            bwrap.to_string_lossy().into_owned(),
+            self.bwrap_args(program, args),
+        ))
+    }
+
+    fn bwrap_args(&self, program: String, args: Vec<String>) -> Vec<String> {
+        let mut bwrap_args = [
+            "--ro-bind",
+            "/",
+            "/",
+            "--dev",
+            "/dev",
+            "--proc",
+            "/proc",
+            "--tmpfs",
+            "/tmp",
+            "--setenv",
+            "TMPDIR",
+            "/tmp",
+            "--unshare-pid",
+            "--unshare-ipc",
+            "--die-with-parent",
+            "--new-session",
+        ]
+        .map(String::from)
+        .to_vec();
+        for root in &self.writable_roots {
+            let path = root.to_string_lossy().into_owned();
+            bwrap_args.extend(["--bind".into(), path.clone(), path]);
+            for protected in PROTECTED_PATHS {
+                let path = root.join(protected).to_string_lossy().into_owned();
+                bwrap_args.extend(["--ro-bind-try".into(), path.clone(), path]);
+            }
+        }
+        if !self.settings.allow_network {
+            bwrap_args.push("--unshare-net".into());
+        }
+        bwrap_args.push("--".into());
+
+        let mut limits = Vec::new();
+        if let Some(max_cpu_seconds) = self.settings.max_cpu_seconds {
+            limits.push(format!("ulimit -t {max_cpu_seconds}"));
+        }
+        if let Some(max_memory_mb) = self.settings.max_memory_mb {
+            // This limits virtual rather than resident memory, so it also stops runtimes that
+            // reserve a lot of address space up front, like the JVM and Go, from starting.
+            limits.push(format!("ulimit -v {}", max_memory_mb.saturating_mul(1024)));
+        }
+        if !limits.is_empty() {
+            // Set the limits in a separate shell, so the command itself can't raise them again.
+            limits.push("exec \"$0\" \"$@\"".into());
+            bwrap_args.extend(["sh".into(), "-c".into(), limits.join(" && ")]);
+        }
+
+        bwrap_args.push(program);
+        bwrap_args.extend(args);
+        bwrap_args
+    }
+
+    /// Explains how the sandbox stopped a failed command, judging by its output and exit code.
+    pub fn violation(&self, output: &str, exit_code: Option<u32>) -> Option<String> {
+        if exit_code == Some(0) {
+            return None;
+        }
+
+        if output.contains("Read-only file system") {
+            return Some(
+                "The command was stopped by the sandbox: it can only write to the project's \
+                worktrees and /tmp."
+                    .into(),
+            );
+        }
+        if !self.settings.allow_network
+            && [
+                "Network is unreachable",
+                "Temporary failure in name resolution",
+                "Could not resolve host",
+            ]
+            .iter()
+            .any(|error| output.contains(error))
+        {
+            return Some(
+                "The command was stopped by the sandbox: it isn't allowed to access the network."
+                    .into(),
+            );
+        }
+        if let Some(max_cpu_seconds) = self.settings.max_cpu_seconds {
+            if exit_code == Some(CPU_LIMIT_EXIT_CODE) || output.contains("CPU time limit exceeded")
+            {
+                return Some(format!(
+                    "The command was stopped by the sandbox: it exceeded the CPU time limit of \
+                    {max_cpu_seconds} seconds."
+                ));
+            }
+        }
+        if let Some(max_memory_mb) = self.settings.max_memory_mb {
+            if [
+                "Cannot allocate memory",
+                "memory allocation of",
+                "out of memory",
+            ]
+            .iter()
+            .any(|error| output.contains(error))
+            {
+                return Some(format!(
+                    "The command was stopped by the sandbox: it exceeded the memory limit of \
+                    {max_memory_mb} MB."
+                ));
+            }
+        }
+
+        None
+    }
+}
+
+/// Creates the protected directories that don't exist yet in a worktree, so that they can be
+/// bound read-only rather than being created by a command in the sandbox.
+///
+/// A missing `.git` is left alone, since creating it would turn the worktree into a repository.
+fn create_protected_dirs(root: &Path) -> Result<()> {
+    let mut dirs = vec![root.join(".zed")];
+    if root.join(".git").is_dir() {
+        dirs.push(root.join(".git/hooks"));
+    }
+    for dir in dirs {
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("creating {} for the sandbox", dir.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_bwrap_args() {
+        let sandbox = TerminalSandbox::new(
+            &TerminalSandboxSettings {
+                enabled: true,
+                allow_network: false,
+                max_cpu_seconds: Some(60),
+                max_memory_mb: Some(512),
+            },
+            vec![PathBuf::from("/home/user/project")],
+        )
+        .unwrap();
+        let args = sandbox.bwrap_args("bash".into(), vec!["-c".into(), "make".into()]);
+        let separator = args.iter().position(|arg| arg == "--").unwrap();
+
+        assert!(
+            args[..separator]
+                .windows(3)
+                .any(|args| args == ["--bind", "/home/user/project", "/home/user/project"])
+        );
+        for protected in [
+            "/home/user/project/.zed",
+            "/home/user/project/.git/hooks",
+            "/home/user/project/.git/config",
+        ] {
+            assert!(
+                args[..separator]
+                    .windows(3)
+                    .any(|args| args == ["--ro-bind-try", protected, protected])
+            );
+        }
+        assert!(args[..separator].contains(&"--unshare-net".to_string()));
+        assert_eq!(
+            args[separator + 1..].to_vec(),
+            vec![
+                "sh",
+                "-c",
+                "ulimit -t 60 && ulimit -v 524288 && exec \"$0\" \"$@\"",
+                "bash",
+                "-c",
+                "make"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_create_protected_dirs() {
+        let repository = tempfile::tempdir().unwrap();
+        std::fs::create_dir(repository.path().join(".git")).unwrap();
+        std::fs::write(repository.path().join(".git/config"), "").unwrap();
+        create_protected_dirs(repository.path()).unwrap();
+        assert!(repository.path().join(".zed").is_dir());
+        assert!(repository.path().join(".git/hooks").is_dir());
+
+        // Existing settings and tasks are kept, and end up in the read-only `.zed` directory.
+        std::fs::write(repository.path().join(".zed/settings.json"), "{}").unwrap();
+        std::fs::write(repository.path().join(".zed/tasks.json"), "[]").unwrap();
+        create_protected_dirs(repository.path()).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(repository.path().join(".zed/tasks.json")).unwrap(),
+            "[]"
+        );
+
+        let directory = tempfile::tempdir().unwrap();
+        create_protected_dirs(directory.path()).unwrap();
+        assert!(directory.path().join(".zed").is_dir());
+        assert!(!directory.path().join(".git").exists());
+    }
+
+    #[test]
+    fn test_violation() {
+        let settings = TerminalSandboxSettings {
+            enabled: true,
+            allow_network: false,
+            max_cpu_seconds: Some(1),
+            max_memory_mb: None,
+        };
+        let sandbox = TerminalSandbox::new(&settings, Vec::new()).unwrap();
+
+        assert!(
+            sandbox
+                .violation(
+                    "touch: cannot touch '/etc/x': Read-only file system",
+                    Some(1)
+                )
+                .is_some()
+        );
+        assert!(
+            sandbox
+                .violation("curl: (6) Could not resolve host: zed.dev", Some(6))
+                .is_some()
+        );
+        assert!(sandbox.violation("", Some(CPU_LIMIT_EXIT_CODE)).is_some());
+        assert!(
+            sandbox
+                .violation("Cannot allocate memory", Some(1))
+                .is_none()
+        );
+        assert!(
+            sandbox
+                .violation("Read-only file system", Some(0))
+                .is_none()
+        );
+        assert!(TerminalSandbox::new(&TerminalSandboxSettings::default(), Vec::new()).is_none());
+    }
+
+    #[test]
+    fn test_remote_sandbox_refuses_to_wrap() {
+        let mut sandbox = TerminalSandbox::new(
+            &TerminalSandboxSettings {
+                enabled: true,
+                ..Default::default()
+            },
+            Vec::new(),
+        )
+        .unwrap();
+        sandbox.remote = true;
+        assert!(
+            sandbox
+                .wrap("bash".into(), vec!["-c".into(), "make".into()])
+                .is_err()
+        );
+    }
+}
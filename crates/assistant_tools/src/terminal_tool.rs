@@ -1,9 +1,10 @@
 use crate::{
     schema::json_schema_for,
+    terminal_sandbox::TerminalSandbox,
     ui::{COLLAPSED_LINES, ToolOutputPreview},
 };
 use action_log::ActionLog;
-use agent_settings;
+use agent_settings::{self, ThreadProfiles};
 use anyhow::{Context as _, Result, anyhow};
 use assistant_tool::{Tool, ToolCard, ToolResult, ToolUseStatus};
 use futures::{FutureExt as _, future::Shared};
@@ -122,7 +123,7 @@ impl Tool for TerminalTool {
     fn run(
         self: Arc<Self>,
         input: serde_json::Value,
-        _request: Arc<LanguageModelRequest>,
+        request: Arc<LanguageModelRequest>,
         project: Entity<Project>,
         _action_log: Entity<ActionLog>,
         _model: Arc<dyn LanguageModel>,
@@ -151,6 +152,7 @@ impl Tool for TerminalTool {
             format!("({}) </dev/null", input.command)
         };
         let args = vec!["-c".into(), command];
+        let sandbox = sandbox(&request, &project, cx);
 
         let cwd = working_dir.clone();
         let env = match &working_dir {
@@ -175,6 +177,10 @@ impl Tool for TerminalTool {
                 let env = env.await;
                 let pty_system = native_pty_system();
                 let program = program.await;
+                let (program, args) = match &sandbox {
+                    Some(sandbox) => sandbox.wrap(program, args)?,
+                    None => (program, args),
+                };
                 let mut cmd = CommandBuilder::new(program);
                 cmd.args(args);
                 for (k, v) in env {
@@ -201,8 +207,15 @@ impl Tool for TerminalTool {
                     .collect();
                 let content = content.trim_start().trim_start_matches("^D");
                 let exit_status = child.wait()?;
+                let exit_code = exit_status.exit_code();
                 let (processed_content, _) =
                     process_content(content, &input.command, Some(exit_status));
+                if let Some(violation) = sandbox
+                    .as_ref()
+                    .and_then(|sandbox| sandbox.violation(content, Some(exit_code)))
+                {
+                    return Err(anyhow!("{violation}\n\n{processed_content}"));
+                }
                 Ok(processed_content.into())
             });
             return ToolResult {
@@ -213,8 +226,13 @@ impl Tool for TerminalTool {
 
         let terminal = cx.spawn({
             let project = project.downgrade();
+            let sandbox = sandbox.clone();
             async move |cx| {
                 let program = program.await;
+                let (program, args) = match &sandbox {
+                    Some(sandbox) => sandbox.wrap(program, args)?,
+                    None => (program, args),
+                };
                 let env = env.await;
                 let terminal = project
                     .update(cx, |project, cx| {
@@ -296,6 +314,14 @@ impl Tool for TerminalTool {
                     exit_status.map(portable_pty::ExitStatus::from),
                 );
 
+                let sandbox_violation = sandbox.as_ref().and_then(|sandbox| {
+                    sandbox.violation(
+                        &content,
+                        exit_status
+                            .map(|status| portable_pty::ExitStatus::from(status).exit_code()),
+                    )
+                });
+
                 card.update(cx, |card, _| {
                     card.command_finished = true;
                     card.exit_status = exit_status;
@@ -307,6 +333,9 @@ impl Tool for TerminalTool {
                 })
                 .log_err();
 
+                if let Some(violation) = sandbox_violation {
+                    return Err(anyhow!("{violation}\n\n{processed_content}"));
+                }
                 Ok(processed_content.into())
             }
         });
@@ -379,6 +408,22 @@ fn process_content(
     (content, is_empty)
 }
 
+/// Returns the sandbox to run commands in, if the profile of the requesting thread enables one.
+fn sandbox(
+    request: &LanguageModelRequest,
+    project: &Entity<Project>,
+    cx: &App,
+) -> Option<TerminalSandbox> {
+    let settings = agent_settings::AgentSettings::get_global(cx);
+    let profile_id = request
+        .thread_id
+        .as_deref()
+        .and_then(|thread_id| ThreadProfiles::get(thread_id, cx))
+        .unwrap_or(&settings.default_profile);
+    let profile = settings.profiles.get(profile_id)?;
+    TerminalSandbox::for_project(&profile.terminal_sandbox, project, cx)
+}
+
 fn working_dir(
     input: &TerminalToolInput,
     project: &Entity<Project>,
@@ -742,6 +787,57 @@ mod tests {
             ThemeSettings::register(cx);
             TerminalSettings::register(cx);
             EditorSettings::register(cx);
+            agent_settings::AgentSettings::register(cx);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_sandbox_uses_thread_profile(
+        executor: BackgroundExecutor,
+        cx: &mut TestAppContext,
+    ) {
+        init_test(&executor, cx);
+
+        let fs = Arc::new(RealFs::new(None, executor));
+        let tree = TempTree::new(json!({
+            "project": {},
+        }));
+        let project: Entity<Project> =
+            Project::test(fs, [tree.path().join("project").as_path()], cx).await;
+
+        cx.update(|cx| {
+            let mut settings = agent_settings::AgentSettings::get_global(cx).clone();
+            settings.default_profile = agent_settings::AgentProfileId("default".into());
+            for (id, enabled) in [("default", false), ("sandboxed", true)] {
+                settings.profiles.insert(
+                    agent_settings::AgentProfileId(id.into()),
+                    agent_settings::AgentProfileSettings {
+                        name: id.to_string().into(),
+                        tools: Default::default(),
+                        enable_all_context_servers: false,
+                        context_servers: Default::default(),
+                        tool_permissions: Default::default(),
+                        terminal_sandbox: agent_settings::TerminalSandboxSettings {
+                            enabled,
+                            ..Default::default()
+                        },
+                    },
+                );
+            }
+            agent_settings::AgentSettings::override_global(settings, cx);
+            ThreadProfiles::set(
+                "sandboxed-thread".into(),
+                agent_settings::AgentProfileId("sandboxed".into()),
+                cx,
+            );
+
+            let request = |thread_id: &str| LanguageModelRequest {
+                thread_id: Some(thread_id.into()),
+                ..Default::default()
+            };
+            assert!(sandbox(&request("sandboxed-thread"), &project, cx).is_some());
+            assert!(sandbox(&request("other-thread"), &project, cx).is_none());
+            assert!(sandbox(&LanguageModelRequest::default(), &project, cx).is_none());
         });
     }
 
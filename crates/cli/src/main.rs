@@ -49,7 +49,9 @@ Examples:
     `zed path-to-your-project`
           Open your project in Zed
     `zed -n path-to-file `
-          Open file/folder in a new window",
+          Open file/folder in a new window
+    `zed --agent-run prompt.md path-to-your-project`
+          Run the agent on your project without opening a window",
     after_help = "To read from stdin, append '-', e.g. 'ps axf | zed -'"
 )]
 struct Args {
@@ -99,6 +101,19 @@ struct Args {
     ))]
     #[arg(long)]
     uninstall: bool,
+    /// Run the agent on the given paths without opening any windows, using the prompt in this
+    /// file (or stdin, if `-`), and print a JSON report of what it did.
+    #[arg(long, value_name = "PROMPT_FILE")]
+    agent_run: Option<String>,
+    /// The model for `--agent-run`, as `provider_id/model_id`
+    #[arg(long, value_name = "MODEL", requires = "agent_run")]
+    agent_model: Option<String>,
+    /// The agent profile for `--agent-run`
+    #[arg(long, value_name = "PROFILE", requires = "agent_run")]
+    agent_profile: Option<String>,
+    /// Allow the tool calls in `--agent-run` that would otherwise ask for permission
+    #[arg(long, requires = "agent_run")]
+    agent_allow_tools: bool,
 }
 
 fn parse_path_with_position(argument_str: &str) -> anyhow::Result<String> {
@@ -208,6 +223,30 @@ fn main() -> Result<()> {
         std::process::exit(status.code().unwrap_or(1));
     }
 
+    // The agent runs in the Zed binary itself, which exits with the run's status.
+    if let Some(prompt) = &args.agent_run {
+        let mut command = std::process::Command::new(app.path());
+        command.arg("--agent-run").arg(prompt);
+        if let Some(model) = &args.agent_model {
+            command.arg("--agent-model").arg(model);
+        }
+        if let Some(profile) = &args.agent_profile {
+            command.arg("--agent-profile").arg(profile);
+        }
+        if args.agent_allow_tools {
+            command.arg("--agent-allow-tools");
+        }
+        if let Some(dir) = &user_data_dir {
+            command.arg("--user-data-dir").arg(dir);
+        }
+        command.arg("--").args(&args.paths_with_position);
+
+        let status = command
+            .status()
+            .with_context(|| format!("Spawning {command:?}"))?;
+        std::process::exit(status.code().unwrap_or(1));
+    }
+
     let (server, server_name) =
         IpcOneShotServer::<IpcHandshake>::new().context("Handshake before Zed spawn")?;
     let url = format!("zed-cli://{server_name}");
@@ -3,6 +3,8 @@ use crate::{
     tests::{TestServer, rust_lang},
 };
 use call::ActiveCall;
+use dap::{FakeAdapter, adapters::DebugTaskDefinition};
+use debugger_ui::debugger_panel::DebugPanel;
 use editor::{
     DocumentColorsRenderMode, Editor, EditorSettings, RowInfo, SelectionEffects,
     actions::{
@@ -28,7 +30,7 @@ use project::{
     project_settings::{InlineBlameSettings, ProjectSettings},
 };
 use recent_projects::disconnected_overlay::DisconnectedOverlay;
-use rpc::RECEIVE_TIMEOUT;
+use rpc::{RECEIVE_TIMEOUT, proto};
 use serde_json::json;
 use settings::SettingsStore;
 use std::{
@@ -40,6 +42,7 @@ use std::{
         atomic::{self, AtomicBool, AtomicUsize},
     },
 };
+use task::TaskContext;
 use text::Point;
 use util::{path, uri};
 use workspace::{CloseIntent, Workspace};
@@ -3766,6 +3769,134 @@ async fn test_add_breakpoints(cx_a: &mut TestAppContext, cx_b: &mut TestAppConte
     assert_eq!(breakpoints_a, breakpoints_b);
 }
 
+#[gpui::test]
+async fn test_guest_sets_next_statement(cx_a: &mut TestAppContext, cx_b: &mut TestAppContext) {
+    let executor = cx_a.executor();
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+    cx_a.update(|cx| {
+        editor::init(cx);
+        debugger_ui::init(cx);
+        dap_adapters::init(cx);
+    });
+    client_a
+        .fs()
+        .insert_tree(
+            path!("/a"),
+            json!({
+                "main.rs": "one\ntwo\nthree",
+            }),
+        )
+        .await;
+    let (project_a, _) = client_a.build_local_project(path!("/a"), cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let _project_b = client_b.join_remote_project(project_id, cx_b).await;
+
+    let goto_target = Arc::new(Mutex::new(None));
+    let _subscription = project::debugger::test::intercept_debug_sessions(cx_a, {
+        let goto_target = goto_target.clone();
+        move |client| {
+            client.on_request::<dap::requests::Initialize, _>(move |_, _| {
+                Ok(dap::Capabilities {
+                    supports_goto_targets_request: Some(true),
+                    ..Default::default()
+                })
+            });
+            client.on_request::<dap::requests::GotoTargets, _>(move |_, args| {
+                assert_eq!(args.source.path, Some(path!("/a/main.rs").to_string()));
+                Ok(dap::GotoTargetsResponse {
+                    targets: vec![dap::GotoTarget {
+                        id: args.line * 10,
+                        label: "main.rs".into(),
+                        line: args.line,
+                        column: None,
+                        end_line: None,
+                        end_column: None,
+                        instruction_pointer_reference: None,
+                    }],
+                })
+            });
+            let goto_target = goto_target.clone();
+            client.on_request::<dap::requests::Goto, _>(move |_, args| {
+                *goto_target.try_lock().unwrap() = Some((args.thread_id, args.target_id));
+                Ok(())
+            });
+        }
+    });
+
+    let (workspace_a, cx_a) = client_a.build_workspace(&project_a, cx_a);
+    let debug_panel = workspace_a
+        .update_in(cx_a, |_, window, cx| cx.spawn_in(window, DebugPanel::load))
+        .await
+        .unwrap();
+    workspace_a.update_in(cx_a, |workspace, window, cx| {
+        workspace.add_panel(debug_panel, window, cx);
+        workspace.start_debug_session(
+            DebugTaskDefinition {
+                adapter: FakeAdapter::ADAPTER_NAME.into(),
+                label: "test".into(),
+                config: json!({
+                    "request": "launch"
+                }),
+                tcp_connection: None,
+            }
+            .to_scenario(),
+            TaskContext::default(),
+            None,
+            None,
+            window,
+            cx,
+        );
+    });
+    cx_a.run_until_parked();
+    let session_id = project_a.read_with(cx_a, |project, cx| {
+        let dap_store = project.dap_store().read(cx);
+        let session = dap_store.sessions().next().unwrap();
+        session.read(cx).session_id()
+    });
+
+    // Client B asks the host's adapter where execution can move to, then moves it.
+    let targets = client_b
+        .client()
+        .request(proto::DapGotoTargetsRequest {
+            project_id,
+            client_id: session_id.to_proto(),
+            path: path!("/a/main.rs").to_string(),
+            line: 2,
+            column: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        targets
+            .targets
+            .iter()
+            .map(|target| target.id)
+            .collect::<Vec<_>>(),
+        vec![20]
+    );
+
+    client_b
+        .client()
+        .request(proto::DapGotoRequest {
+            project_id,
+            client_id: session_id.to_proto(),
+            thread_id: 1,
+            target_id: 20,
+        })
+        .await
+        .unwrap();
+    assert_eq!(*goto_target.lock().await, Some((1, 20)));
+}
+
 #[gpui::test]
 async fn test_client_can_query_lsp_ext(cx_a: &mut TestAppContext, cx_b: &mut TestAppContext) {
     let mut server = TestServer::start(cx_a.executor()).await;
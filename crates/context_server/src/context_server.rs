@@ -10,10 +10,11 @@ use std::path::Path;
 use std::sync::Arc;
 use std::{fmt::Display, path::PathBuf};
 
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 use client::Client;
 use collections::HashMap;
-use gpui::AsyncApp;
+use gpui::{AsyncApp, Task};
+use http_client::{HttpClient, Url};
 use parking_lot::RwLock;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -52,8 +53,106 @@ impl std::fmt::Debug for ContextServerCommand {
     }
 }
 
+/// The protocol used to connect to a remote context server.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextServerHttpTransport {
+    /// The Streamable HTTP transport.
+    #[default]
+    StreamableHttp,
+    /// The legacy HTTP with SSE transport, for servers that do not support Streamable HTTP yet.
+    Sse,
+}
+
+/// OAuth bearer-token authentication for a remote context server.
+#[derive(Deserialize, Serialize, Clone, Default, PartialEq, Eq, JsonSchema)]
+pub struct ContextServerOAuth {
+    /// The access token sent to the server.
+    pub access_token: Option<String>,
+    /// The environment variable to read the access token from.
+    pub access_token_env: Option<String>,
+}
+
+impl ContextServerOAuth {
+    fn access_token(&self) -> Result<Option<String>> {
+        if let Some(access_token) = &self.access_token {
+            return Ok(Some(access_token.clone()));
+        }
+        self.access_token_env
+            .as_ref()
+            .map(|name| {
+                std::env::var(name)
+                    .with_context(|| format!("environment variable {name} is not set"))
+            })
+            .transpose()
+    }
+}
+
+impl std::fmt::Debug for ContextServerOAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContextServerOAuth")
+            .field(
+                "access_token",
+                &self.access_token.as_ref().map(|_| "[REDACTED]"),
+            )
+            .field("access_token_env", &self.access_token_env)
+            .finish()
+    }
+}
+
+/// The endpoint of a context server that is reached over HTTP.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, JsonSchema)]
+pub struct ContextServerEndpoint {
+    /// The URL of the server's MCP endpoint, or of its SSE endpoint for the legacy SSE transport.
+    pub url: String,
+    #[serde(default)]
+    pub transport: ContextServerHttpTransport,
+    /// Additional headers to send with every request.
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// OAuth bearer-token authentication.
+    #[serde(default)]
+    pub oauth: Option<ContextServerOAuth>,
+}
+
+impl ContextServerEndpoint {
+    /// Returns the headers to send with every request, including the `Authorization` header.
+    pub fn request_headers(&self) -> Result<HashMap<String, String>> {
+        let mut headers = self.headers.clone();
+        if let Some(oauth) = &self.oauth {
+            let access_token = oauth
+                .access_token()?
+                .context("no OAuth access token configured")?;
+            headers.insert("Authorization".into(), format!("Bearer {access_token}"));
+        }
+        Ok(headers)
+    }
+}
+
+impl std::fmt::Debug for ContextServerEndpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let filtered_headers = self
+            .headers
+            .iter()
+            .map(|(k, v)| {
+                let redact =
+                    k.eq_ignore_ascii_case("authorization") || should_redact(&k.to_uppercase());
+                (k, if redact { "[REDACTED]" } else { v })
+            })
+            .collect::<Vec<_>>();
+
+        f.debug_struct("ContextServerEndpoint")
+            .field("url", &self.url)
+            .field("transport", &self.transport)
+            .field("headers", &filtered_headers)
+            .field("oauth", &self.oauth)
+            .finish()
+    }
+}
+
 enum ContextServerTransport {
     Stdio(ContextServerCommand, Option<PathBuf>),
+    Http(ContextServerEndpoint, Arc<dyn HttpClient>),
     Custom(Arc<dyn crate::transport::Transport>),
 }
 
@@ -79,6 +178,18 @@ impl ContextServer {
         }
     }
 
+    pub fn http(
+        id: ContextServerId,
+        endpoint: ContextServerEndpoint,
+        http_client: Arc<dyn HttpClient>,
+    ) -> Self {
+        Self {
+            id,
+            client: RwLock::new(None),
+            configuration: ContextServerTransport::Http(endpoint, http_client),
+        }
+    }
+
     pub fn new(id: ContextServerId, transport: Arc<dyn crate::transport::Transport>) -> Self {
         Self {
             id,
@@ -100,18 +211,32 @@ impl ContextServer {
     }
 
     /// Starts the context server, making sure handlers are registered before initialization happens
+    ///
+    /// Request handlers answer requests sent by the server, and determine which client
+    /// capabilities are advertised during initialization.
     pub async fn start_with_handlers(
         &self,
         notification_handlers: Vec<(
             &'static str,
             Box<dyn 'static + Send + FnMut(serde_json::Value, AsyncApp)>,
         )>,
+        request_handlers: Vec<(
+            &'static str,
+            Box<
+                dyn 'static
+                    + Send
+                    + FnMut(serde_json::Value, AsyncApp) -> Task<Result<serde_json::Value>>,
+            >,
+        )>,
         cx: &AsyncApp,
     ) -> Result<()> {
         let client = self.new_client(cx)?;
         for (method, handler) in notification_handlers {
             client.on_notification(method, handler);
         }
+        for (method, handler) in request_handlers {
+            client.on_request(method, handler);
+        }
         self.initialize(client).await
     }
 
@@ -127,6 +252,35 @@ impl ContextServer {
                 working_directory,
                 cx.clone(),
             )?,
+            ContextServerTransport::Http(endpoint, http_client) => {
+                let url = Url::parse(&endpoint.url)
+                    .with_context(|| format!("invalid context server URL {:?}", endpoint.url))?;
+                let headers = endpoint.request_headers()?;
+                let transport: Arc<dyn crate::transport::Transport> = match endpoint.transport {
+                    ContextServerHttpTransport::StreamableHttp => {
+                        Arc::new(crate::transport::StreamableHttpTransport::new(
+                            url,
+                            headers,
+                            http_client.clone(),
+                            cx.background_executor().clone(),
+                        ))
+                    }
+                    ContextServerHttpTransport::Sse => {
+                        Arc::new(crate::transport::SseTransport::new(
+                            url,
+                            headers,
+                            http_client.clone(),
+                            cx.background_executor().clone(),
+                        ))
+                    }
+                };
+                Client::new(
+                    client::ContextServerId(self.id.0.clone()),
+                    self.id().0,
+                    transport,
+                    cx.clone(),
+                )?
+            }
             ContextServerTransport::Custom(transport) => Client::new(
                 client::ContextServerId(self.id.0.clone()),
                 self.id().0,
@@ -9,7 +9,7 @@ use std::time::Duration;
 
 use anyhow::Result;
 use futures::channel::oneshot;
-use gpui::AsyncApp;
+use gpui::{AsyncApp, Task};
 use serde_json::Value;
 
 use crate::client::Client;
@@ -39,8 +39,16 @@ impl ModelContextProtocol {
             protocol_version: types::ProtocolVersion(types::LATEST_PROTOCOL_VERSION.to_string()),
             capabilities: types::ClientCapabilities {
                 experimental: None,
-                sampling: None,
-                roots: None,
+                sampling: self
+                    .inner
+                    .has_request_handler(types::requests::CreateMessage::METHOD)
+                    .then(|| serde_json::json!({})),
+                roots: self
+                    .inner
+                    .has_request_handler(types::requests::ListRoots::METHOD)
+                    .then_some(types::RootsCapabilities {
+                        list_changed: Some(true),
+                    }),
             },
             meta: None,
             client_info,
@@ -122,4 +130,22 @@ impl InitializedContextServerProtocol {
     ) {
         self.inner.on_notification(method, f);
     }
+
+    pub fn on_request(
+        &self,
+        method: &'static str,
+        f: Box<dyn 'static + Send + FnMut(Value, AsyncApp) -> Task<Result<Value>>>,
+    ) {
+        self.inner.on_request(method, f);
+    }
+
+    /// Whether the server sends `notifications/resources/updated` for subscribed resources.
+    pub fn supports_resource_subscriptions(&self) -> bool {
+        self.initialize
+            .capabilities
+            .resources
+            .as_ref()
+            .and_then(|resources| resources.subscribe)
+            .unwrap_or(false)
+    }
 }
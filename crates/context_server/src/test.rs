@@ -1,12 +1,18 @@
 use anyhow::Context as _;
 use collections::HashMap;
-use futures::{Stream, StreamExt as _, lock::Mutex};
+use futures::{AsyncReadExt as _, Stream, StreamExt as _, TryStreamExt as _, lock::Mutex};
 use gpui::BackgroundExecutor;
+use http_client::{AsyncBody, FakeHttpClient, HttpClientWithUrl, Method, Request, Response};
+use serde_json::{Value, json};
 use std::{pin::Pin, sync::Arc};
 
 use crate::{
+    ContextServerEndpoint, ContextServerHttpTransport,
     transport::Transport,
-    types::{Implementation, InitializeResponse, ProtocolVersion, ServerCapabilities},
+    types::{
+        Implementation, InitializeResponse, ListToolsResponse, ProtocolVersion, ServerCapabilities,
+        Tool,
+    },
 };
 
 pub fn create_fake_transport(
@@ -36,6 +42,7 @@ pub struct FakeTransport {
         HashMap<&'static str, Arc<dyn Fn(serde_json::Value) -> serde_json::Value + Send + Sync>>,
     tx: futures::channel::mpsc::UnboundedSender<String>,
     rx: Arc<Mutex<futures::channel::mpsc::UnboundedReceiver<String>>>,
+    client_responses: Arc<parking_lot::Mutex<Vec<Value>>>,
     executor: BackgroundExecutor,
 }
 
@@ -46,6 +53,7 @@ impl FakeTransport {
             request_handlers: Default::default(),
             tx,
             rx: Arc::new(Mutex::new(rx)),
+            client_responses: Default::default(),
             executor,
         }
     }
@@ -66,6 +74,32 @@ impl FakeTransport {
         );
         self
     }
+
+    /// Sends a request to the client, as if it was sent by the server.
+    pub fn send_request(&self, id: i32, method: &str, params: Value) {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        });
+        self.tx.unbounded_send(request.to_string()).ok();
+    }
+
+    /// Sends a notification to the client, as if it was sent by the server.
+    pub fn send_notification(&self, method: &str, params: Value) {
+        let notification = json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+        });
+        self.tx.unbounded_send(notification.to_string()).ok();
+    }
+
+    /// Returns the responses that the client sent to requests from the server.
+    pub fn client_responses(&self) -> Vec<Value> {
+        self.client_responses.lock().clone()
+    }
 }
 
 #[async_trait::async_trait]
@@ -89,6 +123,8 @@ impl Transport for FakeTransport {
                 } else {
                     log::debug!("No handler registered for MCP request '{method}'");
                 }
+            } else if msg.get("id").is_some() {
+                self.client_responses.lock().push(msg);
             }
         }
         Ok(())
@@ -116,3 +152,272 @@ impl Transport for FakeTransport {
         Box::pin(futures::stream::empty())
     }
 }
+
+type EventStreamSender = futures::channel::mpsc::UnboundedSender<std::io::Result<Vec<u8>>>;
+
+/// A request received by a [`FakeHttpContextServer`].
+#[derive(Debug, Clone)]
+pub struct FakeHttpRequest {
+    pub method: Method,
+    pub path: String,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+impl FakeHttpRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .get(&name.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+}
+
+#[derive(Default)]
+struct FakeHttpContextServerState {
+    requests: Vec<FakeHttpRequest>,
+    event_streams: Vec<EventStreamSender>,
+    sse_streams: Vec<EventStreamSender>,
+    next_event_id: usize,
+}
+
+impl FakeHttpContextServerState {
+    fn event(&mut self, event: Option<&str>, data: &str) -> (String, Vec<u8>) {
+        self.next_event_id += 1;
+        let id = self.next_event_id.to_string();
+        let mut bytes = format!("id: {id}\n");
+        if let Some(event) = event {
+            bytes.push_str(&format!("event: {event}\n"));
+        }
+        bytes.push_str(&format!("data: {data}\n\n"));
+        (id, bytes.into_bytes())
+    }
+}
+
+/// A stand-in for a remote context server, which implements the Streamable HTTP transport at
+/// `/mcp` and the legacy SSE transport at `/sse`.
+pub struct FakeHttpContextServer {
+    request_handlers: HashMap<&'static str, Arc<dyn Fn(Value) -> Value + Send + Sync>>,
+    respond_with_event_streams: bool,
+    state: Arc<parking_lot::Mutex<FakeHttpContextServerState>>,
+}
+
+impl FakeHttpContextServer {
+    pub const SESSION_ID: &str = "fake-session";
+
+    const BASE_URL: &str = "http://mcp.example.com";
+
+    pub fn new(name: impl Into<String>) -> Self {
+        let name = name.into();
+        Self {
+            request_handlers: HashMap::default(),
+            respond_with_event_streams: false,
+            state: Arc::default(),
+        }
+        .on_request::<crate::types::requests::Initialize>(move |_| {
+            create_initialize_response(name.clone())
+        })
+        .on_request::<crate::types::requests::ListTools>(|_| ListToolsResponse {
+            tools: vec![Tool {
+                name: "echo".into(),
+                description: None,
+                input_schema: json!({ "type": "object" }),
+                output_schema: None,
+                annotations: None,
+            }],
+            next_cursor: None,
+            meta: None,
+        })
+    }
+
+    pub fn on_request<T: crate::types::Request>(
+        mut self,
+        handler: impl Fn(T::Params) -> T::Response + Send + Sync + 'static,
+    ) -> Self {
+        self.request_handlers.insert(
+            T::METHOD,
+            Arc::new(move |params| {
+                let params: T::Params =
+                    serde_json::from_value(params).expect("Invalid parameters received");
+                serde_json::to_value(handler(params)).unwrap()
+            }),
+        );
+        self
+    }
+
+    /// Responds to requests over the Streamable HTTP transport with event streams, rather than
+    /// with JSON.
+    pub fn respond_with_event_streams(mut self) -> Self {
+        self.respond_with_event_streams = true;
+        self
+    }
+
+    pub fn endpoint(&self, transport: ContextServerHttpTransport) -> ContextServerEndpoint {
+        let path = match transport {
+            ContextServerHttpTransport::StreamableHttp => "/mcp",
+            ContextServerHttpTransport::Sse => "/sse",
+        };
+        ContextServerEndpoint {
+            url: format!("{}{path}", Self::BASE_URL),
+            transport,
+            headers: HashMap::default(),
+            oauth: None,
+        }
+    }
+
+    /// Returns the requests received so far.
+    pub fn requests(&self) -> Vec<FakeHttpRequest> {
+        self.state.lock().requests.clone()
+    }
+
+    /// Sends a notification over the open event streams, returning the ID of its event.
+    pub fn send_notification(&self, method: &str) -> String {
+        let message = json!({ "jsonrpc": "2.0", "method": method }).to_string();
+        let mut state = self.state.lock();
+        let (id, event) = state.event(None, &message);
+        for stream in state.event_streams.iter().chain(&state.sse_streams) {
+            stream.unbounded_send(Ok(event.clone())).ok();
+        }
+        id
+    }
+
+    /// Closes the open event streams, as a server does when a connection drops.
+    pub fn close_event_streams(&self) {
+        let mut state = self.state.lock();
+        state.event_streams.clear();
+        state.sse_streams.clear();
+    }
+
+    pub fn http_client(&self) -> Arc<HttpClientWithUrl> {
+        let request_handlers = Arc::new(self.request_handlers.clone());
+        let respond_with_event_streams = self.respond_with_event_streams;
+        let state = self.state.clone();
+        FakeHttpClient::create(move |request| {
+            let request_handlers = request_handlers.clone();
+            let state = state.clone();
+            async move {
+                Self::handle_request(
+                    request,
+                    &request_handlers,
+                    respond_with_event_streams,
+                    &state,
+                )
+                .await
+            }
+        })
+    }
+
+    async fn handle_request(
+        request: Request<AsyncBody>,
+        request_handlers: &HashMap<&'static str, Arc<dyn Fn(Value) -> Value + Send + Sync>>,
+        respond_with_event_streams: bool,
+        state: &parking_lot::Mutex<FakeHttpContextServerState>,
+    ) -> anyhow::Result<Response<AsyncBody>> {
+        let (parts, mut body) = request.into_parts();
+        let mut text = String::new();
+        body.read_to_string(&mut text).await?;
+        let request = FakeHttpRequest {
+            method: parts.method,
+            path: parts.uri.path().to_string(),
+            headers: parts
+                .headers
+                .iter()
+                .map(|(name, value)| {
+                    (
+                        name.as_str().to_string(),
+                        value.to_str().unwrap_or_default().to_string(),
+                    )
+                })
+                .collect(),
+            body: text,
+        };
+        state.lock().requests.push(request.clone());
+
+        let response = Response::builder();
+        match (&request.method, request.path.as_str()) {
+            (&Method::POST, "/mcp") => {
+                let message = serde_json::from_str::<Value>(&request.body)?;
+                let is_initialize = message["method"] == "initialize";
+                if !is_initialize && request.header("Mcp-Session-Id") != Some(Self::SESSION_ID) {
+                    return Ok(response.status(404).body(AsyncBody::empty())?);
+                }
+                let Some(reply) = Self::reply(&message, request_handlers) else {
+                    return Ok(response.status(202).body(AsyncBody::empty())?);
+                };
+                let response = if is_initialize {
+                    response.header("Mcp-Session-Id", Self::SESSION_ID)
+                } else {
+                    response
+                };
+                if respond_with_event_streams {
+                    let (_, event) = state.lock().event(None, &reply);
+                    let (tx, body) = event_stream();
+                    tx.unbounded_send(Ok(event)).ok();
+                    Ok(response
+                        .header("Content-Type", "text/event-stream")
+                        .body(body)?)
+                } else {
+                    Ok(response
+                        .header("Content-Type", "application/json")
+                        .body(reply.into())?)
+                }
+            }
+            (&Method::GET, "/mcp") => {
+                let (tx, body) = event_stream();
+                state.lock().event_streams.push(tx);
+                Ok(response
+                    .header("Content-Type", "text/event-stream")
+                    .body(body)?)
+            }
+            (&Method::DELETE, "/mcp") => Ok(response.status(200).body(AsyncBody::empty())?),
+            (&Method::GET, "/sse") => {
+                let (tx, body) = event_stream();
+                let mut state = state.lock();
+                let (_, event) = state.event(Some("endpoint"), "/messages");
+                tx.unbounded_send(Ok(event)).ok();
+                state.sse_streams.push(tx);
+                Ok(response
+                    .header("Content-Type", "text/event-stream")
+                    .body(body)?)
+            }
+            (&Method::POST, "/messages") => {
+                let message = serde_json::from_str::<Value>(&request.body)?;
+                if let Some(reply) = Self::reply(&message, request_handlers) {
+                    let mut state = state.lock();
+                    let (_, event) = state.event(Some("message"), &reply);
+                    for stream in &state.sse_streams {
+                        stream.unbounded_send(Ok(event.clone())).ok();
+                    }
+                }
+                Ok(response.status(202).body(AsyncBody::empty())?)
+            }
+            _ => Ok(response.status(404).body(AsyncBody::empty())?),
+        }
+    }
+
+    fn reply(
+        message: &Value,
+        request_handlers: &HashMap<&'static str, Arc<dyn Fn(Value) -> Value + Send + Sync>>,
+    ) -> Option<String> {
+        let id = message.get("id")?;
+        let method = message.get("method")?.as_str()?;
+        let reply = match request_handlers.get(method) {
+            Some(handler) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "result": handler(message.get("params").cloned().unwrap_or(Value::Null)),
+            }),
+            None => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": crate::client::METHOD_NOT_FOUND, "message": "method not found" },
+            }),
+        };
+        Some(reply.to_string())
+    }
+}
+
+/// Returns a response body that streams the events sent over the returned channel.
+fn event_stream() -> (EventStreamSender, AsyncBody) {
+    let (tx, rx) = futures::channel::mpsc::unbounded();
+    (tx, AsyncBody::from_reader(rx.into_async_read()))
+}
@@ -1,12 +1,18 @@
+mod event_stream;
+mod sse_transport;
 mod stdio_transport;
+mod streamable_http_transport;
 
 use std::pin::Pin;
 
 use anyhow::Result;
 use async_trait::async_trait;
 use futures::Stream;
+use serde_json::Value;
 
+pub use sse_transport::*;
 pub use stdio_transport::*;
+pub use streamable_http_transport::*;
 
 #[async_trait]
 pub trait Transport: Send + Sync {
@@ -14,3 +20,24 @@ pub trait Transport: Send + Sync {
     fn receive(&self) -> Pin<Box<dyn Stream<Item = String> + Send>>;
     fn receive_err(&self) -> Pin<Box<dyn Stream<Item = String> + Send>>;
 }
+
+/// Returns the JSON-RPC error response to the given message, if it is a request.
+///
+/// HTTP transports use this to fail requests that the server could not receive, rather than
+/// leaving them to time out.
+fn error_response(message: &str, error: &str) -> Option<String> {
+    let message = serde_json::from_str::<Value>(message).ok()?;
+    let id = message.get("id")?;
+    message.get("method")?;
+    Some(
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": {
+                "code": crate::client::INTERNAL_ERROR,
+                "message": error,
+            },
+        })
+        .to_string(),
+    )
+}
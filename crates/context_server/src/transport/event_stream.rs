@@ -0,0 +1,151 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use futures::io::BufReader;
+use futures::{AsyncBufReadExt as _, AsyncRead, Stream, StreamExt as _, stream};
+
+/// How long to wait before reconnecting to an event stream, unless the server specifies otherwise.
+const DEFAULT_RECONNECTION_DELAY: Duration = Duration::from_secs(1);
+
+/// An event of a server-sent event stream.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct ServerSentEvent {
+    pub id: Option<String>,
+    pub event: Option<String>,
+    pub data: String,
+    pub retry: Option<Duration>,
+}
+
+/// Parses a stream of server-sent events, as specified in
+/// <https://html.spec.whatwg.org/multipage/server-sent-events.html#event-stream-interpretation>.
+pub(crate) fn server_sent_events(
+    reader: impl AsyncRead + Unpin + Send + 'static,
+) -> impl Stream<Item = Result<ServerSentEvent>> + Send {
+    let lines = BufReader::new(reader).lines();
+    stream::unfold(lines, |mut lines| async move {
+        let mut event = ServerSentEvent::default();
+        let mut has_fields = false;
+        loop {
+            let line = match lines.next().await? {
+                Ok(line) => line,
+                Err(error) => return Some((Err(error.into()), lines)),
+            };
+            if line.is_empty() {
+                if has_fields {
+                    return Some((Ok(event), lines));
+                }
+                continue;
+            }
+            if line.starts_with(':') {
+                continue;
+            }
+
+            let (field, value) = match line.split_once(':') {
+                Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+                None => (line.as_str(), ""),
+            };
+            match field {
+                "id" => event.id = Some(value.to_string()),
+                "event" => event.event = Some(value.to_string()),
+                "data" => {
+                    if !event.data.is_empty() {
+                        event.data.push('\n');
+                    }
+                    event.data.push_str(value);
+                }
+                "retry" => match value.parse() {
+                    Ok(millis) => event.retry = Some(Duration::from_millis(millis)),
+                    Err(_) => continue,
+                },
+                _ => continue,
+            }
+            has_fields = true;
+        }
+    })
+}
+
+/// The state needed to resume an event stream after it disconnects.
+#[derive(Debug)]
+pub(crate) struct EventStreamState {
+    /// The ID of the last event received, which is sent as the `Last-Event-ID` header when
+    /// reconnecting so that the server can replay the events that were missed.
+    pub last_event_id: Option<String>,
+    /// How long to wait before reconnecting.
+    pub reconnection_delay: Duration,
+}
+
+impl Default for EventStreamState {
+    fn default() -> Self {
+        Self {
+            last_event_id: None,
+            reconnection_delay: DEFAULT_RECONNECTION_DELAY,
+        }
+    }
+}
+
+impl EventStreamState {
+    pub fn update(&mut self, event: &ServerSentEvent) {
+        if let Some(id) = &event.id {
+            self.last_event_id = Some(id.clone());
+        }
+        if let Some(retry) = event.retry {
+            self.reconnection_delay = retry;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    async fn test_server_sent_events() {
+        let body = concat!(
+            ": keep-alive\n",
+            "\n",
+            "id: 1\n",
+            "data: {\"a\":1}\n",
+            "\n",
+            "event: endpoint\r\n",
+            "data: /messages?session=2\r\n",
+            "\r\n",
+            "retry: 500\n",
+            "data:first\n",
+            "data: second\n",
+            "\n",
+            "data: incomplete",
+        );
+        let events = server_sent_events(body.as_bytes())
+            .map(|event| event.unwrap())
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(
+            events,
+            [
+                ServerSentEvent {
+                    id: Some("1".into()),
+                    data: "{\"a\":1}".into(),
+                    ..Default::default()
+                },
+                ServerSentEvent {
+                    event: Some("endpoint".into()),
+                    data: "/messages?session=2".into(),
+                    ..Default::default()
+                },
+                ServerSentEvent {
+                    data: "first\nsecond".into(),
+                    retry: Some(Duration::from_millis(500)),
+                    ..Default::default()
+                },
+            ]
+        );
+
+        let mut state = EventStreamState::default();
+        for event in &events {
+            state.update(event);
+        }
+        assert_eq!(state.last_event_id.as_deref(), Some("1"));
+        assert_eq!(state.reconnection_delay, Duration::from_millis(500));
+    }
+}
@@ -0,0 +1,300 @@
+use std::pin::{Pin, pin};
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result};
+use async_trait::async_trait;
+use collections::HashMap;
+use futures::{AsyncReadExt as _, Stream, StreamExt as _, future};
+use gpui::{BackgroundExecutor, Task};
+use http_client::{AsyncBody, HttpClient, Method, Request, Url, http};
+use postage::watch;
+use smol::channel;
+
+use crate::transport::Transport;
+use crate::transport::error_response;
+use crate::transport::event_stream::{EventStreamState, server_sent_events};
+
+const LAST_EVENT_ID_HEADER: &str = "Last-Event-ID";
+const EVENT_STREAM_CONTENT_TYPE: &str = "text/event-stream";
+/// How many times to reconnect to an event stream that keeps failing before giving up.
+const MAX_RECONNECTION_ATTEMPTS: usize = 5;
+
+/// A transport for context servers that implement the legacy HTTP with SSE transport of the
+/// Model Context Protocol.
+///
+/// A GET request to the server's SSE endpoint opens an event stream, whose first event names
+/// the endpoint that messages are sent to in POST requests. The server sends all of its
+/// messages over the event stream, which is resumed from the last event received when it
+/// disconnects.
+pub struct SseTransport {
+    connection: Arc<Connection>,
+    endpoint_rx: watch::Receiver<Option<Url>>,
+    inbound_rx: channel::Receiver<String>,
+    stderr_rx: channel::Receiver<String>,
+    _listen_task: Task<()>,
+}
+
+struct Connection {
+    http_client: Arc<dyn HttpClient>,
+    url: Url,
+    headers: HashMap<String, String>,
+    executor: BackgroundExecutor,
+    inbound_tx: channel::Sender<String>,
+    stderr_tx: channel::Sender<String>,
+}
+
+impl SseTransport {
+    pub fn new(
+        url: Url,
+        headers: HashMap<String, String>,
+        http_client: Arc<dyn HttpClient>,
+        executor: BackgroundExecutor,
+    ) -> Self {
+        let (inbound_tx, inbound_rx) = channel::unbounded();
+        let (stderr_tx, stderr_rx) = channel::unbounded();
+        let (endpoint_tx, endpoint_rx) = watch::channel();
+        let connection = Arc::new(Connection {
+            http_client,
+            url,
+            headers,
+            executor: executor.clone(),
+            inbound_tx,
+            stderr_tx,
+        });
+        let listen_task = executor.spawn({
+            let connection = connection.clone();
+            async move { connection.listen(endpoint_tx).await }
+        });
+        Self {
+            connection,
+            endpoint_rx,
+            inbound_rx,
+            stderr_rx,
+            _listen_task: listen_task,
+        }
+    }
+}
+
+impl Connection {
+    fn request(&self, method: Method, url: &Url) -> http::request::Builder {
+        let mut request = Request::builder().method(method).uri(url.as_str());
+        for (name, value) in &self.headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        request
+    }
+
+    /// Receives messages over the event stream, reopening it whenever it disconnects.
+    async fn listen(&self, mut endpoint_tx: watch::Sender<Option<Url>>) {
+        let mut state = EventStreamState::default();
+        let mut failed_attempts = 0;
+        loop {
+            let result = match self.open_event_stream(state.last_event_id.as_deref()).await {
+                Ok(body) => {
+                    self.forward_events(body, &mut state, &mut endpoint_tx)
+                        .await
+                }
+                Err(error) => Err(error),
+            };
+            match result {
+                Ok(received) if received > 0 => failed_attempts = 0,
+                Ok(_) => failed_attempts += 1,
+                Err(error) => {
+                    log::warn!("context server event stream disconnected: {error:#}");
+                    failed_attempts += 1;
+                }
+            }
+            if self.inbound_tx.is_closed() {
+                return;
+            }
+            if failed_attempts > MAX_RECONNECTION_ATTEMPTS {
+                self.stderr_tx
+                    .send(format!("failed to reconnect to {}", self.url))
+                    .await
+                    .ok();
+                return;
+            }
+            self.executor.timer(state.reconnection_delay).await;
+        }
+    }
+
+    /// Opens the event stream, resuming it after the given event.
+    async fn open_event_stream(&self, last_event_id: Option<&str>) -> Result<AsyncBody> {
+        let mut request = self
+            .request(Method::GET, &self.url)
+            .header("Accept", EVENT_STREAM_CONTENT_TYPE);
+        if let Some(last_event_id) = last_event_id {
+            request = request.header(LAST_EVENT_ID_HEADER, last_event_id);
+        }
+        let response = self
+            .http_client
+            .send(request.body(AsyncBody::empty())?)
+            .await
+            .with_context(|| format!("connecting to {}", self.url))?;
+        anyhow::ensure!(
+            response.status().is_success(),
+            "failed to open event stream: HTTP {}",
+            response.status()
+        );
+        Ok(response.into_body())
+    }
+
+    /// Forwards the messages of the event stream, returning how many were received.
+    async fn forward_events(
+        &self,
+        body: AsyncBody,
+        state: &mut EventStreamState,
+        endpoint_tx: &mut watch::Sender<Option<Url>>,
+    ) -> Result<usize> {
+        let mut events = pin!(server_sent_events(body));
+        let mut received = 0;
+        while let Some(event) = events.next().await {
+            let event = event?;
+            state.update(&event);
+            match event.event.as_deref() {
+                Some("endpoint") => {
+                    let endpoint = resolve_endpoint(&self.url, &event.data)?;
+                    *endpoint_tx.borrow_mut() = Some(endpoint);
+                }
+                None | Some("message") if !event.data.is_empty() => {
+                    received += 1;
+                    if self.inbound_tx.send(event.data).await.is_err() {
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(received)
+    }
+
+    async fn post(&self, endpoint: &Url, message: String) -> Result<()> {
+        let request = self
+            .request(Method::POST, endpoint)
+            .header("Content-Type", "application/json")
+            .body(AsyncBody::from(message.clone()))?;
+        let mut response = self
+            .http_client
+            .send(request)
+            .await
+            .with_context(|| format!("sending message to {endpoint}"))?;
+        if !response.status().is_success() {
+            let mut body = String::new();
+            response.body_mut().read_to_string(&mut body).await.ok();
+            let error = format!("HTTP {}: {body}", response.status());
+            self.stderr_tx.send(error.clone()).await.ok();
+            if let Some(response) = error_response(&message, &error) {
+                self.inbound_tx.send(response).await.ok();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Resolves the endpoint announced by the server against the server's URL.
+///
+/// The endpoint must have the same origin as the server, so that a server can't have messages and
+/// the configured headers sent to another host.
+fn resolve_endpoint(server_url: &Url, endpoint: &str) -> Result<Url> {
+    let resolved = server_url
+        .join(endpoint.trim())
+        .with_context(|| format!("invalid endpoint {endpoint:?}"))?;
+    anyhow::ensure!(
+        resolved.origin() == server_url.origin(),
+        "endpoint {resolved} is not on the same origin as {server_url}"
+    );
+    Ok(resolved)
+}
+
+#[async_trait]
+impl Transport for SseTransport {
+    async fn send(&self, message: String) -> Result<()> {
+        let endpoint = self
+            .endpoint_rx
+            .clone()
+            .filter_map(future::ready)
+            .next()
+            .await
+            .context("context server event stream closed")?;
+        self.connection.post(&endpoint, message).await
+    }
+
+    fn receive(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+        Box::pin(self.inbound_rx.clone())
+    }
+
+    fn receive_err(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+        Box::pin(self.stderr_rx.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::FakeHttpContextServer;
+    use crate::types::requests::ListTools;
+    use crate::{ContextServer, ContextServerHttpTransport, ContextServerId};
+    use gpui::TestAppContext;
+
+    #[gpui::test]
+    async fn test_sse_transport(cx: &mut TestAppContext) {
+        let server = FakeHttpContextServer::new("test-server");
+        let context_server = ContextServer::http(
+            ContextServerId("remote".into()),
+            server.endpoint(ContextServerHttpTransport::Sse),
+            server.http_client(),
+        );
+        context_server.start(&cx.to_async()).await.unwrap();
+        cx.run_until_parked();
+
+        let client = context_server.client().unwrap();
+        let response = client.request::<ListTools>(()).await.unwrap();
+        assert_eq!(response.tools.len(), 1);
+
+        // Messages are posted to the endpoint announced over the event stream.
+        let requests = server.requests();
+        assert_eq!(requests[0].method, Method::GET);
+        assert_eq!(requests[0].path, "/sse");
+        assert!(
+            requests[1..]
+                .iter()
+                .all(|request| request.method == Method::POST && request.path == "/messages")
+        );
+    }
+
+    #[test]
+    fn test_resolve_endpoint() {
+        let server_url = Url::parse("https://example.com:8080/mcp/sse").unwrap();
+        assert_eq!(
+            resolve_endpoint(&server_url, "/messages?session=1")
+                .unwrap()
+                .as_str(),
+            "https://example.com:8080/messages?session=1"
+        );
+        assert_eq!(
+            resolve_endpoint(&server_url, "messages\n")
+                .unwrap()
+                .as_str(),
+            "https://example.com:8080/mcp/messages"
+        );
+        assert_eq!(
+            resolve_endpoint(&server_url, "https://example.com:8080/other")
+                .unwrap()
+                .as_str(),
+            "https://example.com:8080/other"
+        );
+
+        // Endpoints on another host, port or scheme are rejected.
+        for endpoint in [
+            "https://attacker.example/messages",
+            "//attacker.example/messages",
+            "https://example.com:9090/messages",
+            "http://example.com:8080/messages",
+        ] {
+            assert!(
+                resolve_endpoint(&server_url, endpoint).is_err(),
+                "{endpoint} should be rejected"
+            );
+        }
+    }
+}
@@ -0,0 +1,403 @@
+use std::pin::{Pin, pin};
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result};
+use async_trait::async_trait;
+use collections::HashMap;
+use futures::{AsyncReadExt as _, Stream, StreamExt as _};
+use gpui::{BackgroundExecutor, Task};
+use http_client::{AsyncBody, HttpClient, Method, Request, Response, StatusCode, Url, http};
+use parking_lot::Mutex;
+use serde_json::Value;
+use smol::channel;
+use util::ResultExt as _;
+
+use crate::transport::Transport;
+use crate::transport::error_response;
+use crate::transport::event_stream::{EventStreamState, server_sent_events};
+
+const SESSION_ID_HEADER: &str = "Mcp-Session-Id";
+const LAST_EVENT_ID_HEADER: &str = "Last-Event-ID";
+const EVENT_STREAM_CONTENT_TYPE: &str = "text/event-stream";
+const JSON_CONTENT_TYPE: &str = "application/json";
+const INITIALIZED_NOTIFICATION: &str = "notifications/initialized";
+/// How many times to reconnect to an event stream that keeps failing before giving up.
+const MAX_RECONNECTION_ATTEMPTS: usize = 5;
+
+/// A transport for context servers that implement the Streamable HTTP transport of the
+/// Model Context Protocol.
+///
+/// Every message is sent in a POST request to the server's MCP endpoint, which responds with
+/// either a JSON message or a stream of server-sent events. Once the connection is initialized,
+/// a GET request to the same endpoint opens a stream for the messages that the server sends on
+/// its own. Event streams that disconnect are resumed from the last event received.
+pub struct StreamableHttpTransport {
+    connection: Arc<Connection>,
+    inbound_rx: channel::Receiver<String>,
+    stderr_rx: channel::Receiver<String>,
+    listen_task: Mutex<Option<Task<()>>>,
+}
+
+struct Connection {
+    http_client: Arc<dyn HttpClient>,
+    endpoint: Url,
+    headers: HashMap<String, String>,
+    executor: BackgroundExecutor,
+    session_id: Mutex<Option<String>>,
+    inbound_tx: channel::Sender<String>,
+    stderr_tx: channel::Sender<String>,
+}
+
+impl StreamableHttpTransport {
+    pub fn new(
+        endpoint: Url,
+        headers: HashMap<String, String>,
+        http_client: Arc<dyn HttpClient>,
+        executor: BackgroundExecutor,
+    ) -> Self {
+        let (inbound_tx, inbound_rx) = channel::unbounded();
+        let (stderr_tx, stderr_rx) = channel::unbounded();
+        Self {
+            connection: Arc::new(Connection {
+                http_client,
+                endpoint,
+                headers,
+                executor,
+                session_id: Mutex::new(None),
+                inbound_tx,
+                stderr_tx,
+            }),
+            inbound_rx,
+            stderr_rx,
+            listen_task: Mutex::new(None),
+        }
+    }
+}
+
+impl Connection {
+    fn request(&self, method: Method) -> http::request::Builder {
+        let mut request = Request::builder()
+            .method(method)
+            .uri(self.endpoint.as_str());
+        for (name, value) in &self.headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        if let Some(session_id) = self.session_id.lock().as_ref() {
+            request = request.header(SESSION_ID_HEADER, session_id.as_str());
+        }
+        request
+    }
+
+    async fn post(self: &Arc<Self>, message: String) -> Result<()> {
+        let request = self
+            .request(Method::POST)
+            .header("Content-Type", JSON_CONTENT_TYPE)
+            .header(
+                "Accept",
+                format!("{JSON_CONTENT_TYPE}, {EVENT_STREAM_CONTENT_TYPE}"),
+            )
+            .body(AsyncBody::from(message.clone()))?;
+        let mut response = self
+            .http_client
+            .send(request)
+            .await
+            .with_context(|| format!("sending message to {}", self.endpoint))?;
+
+        if let Some(session_id) = header_value(&response, SESSION_ID_HEADER) {
+            *self.session_id.lock() = Some(session_id);
+        }
+
+        let status = response.status();
+        if status == StatusCode::NOT_FOUND && self.session_id.lock().take().is_some() {
+            self.fail_request(&message, "the context server session expired")
+                .await;
+            anyhow::bail!("context server session expired, restart the server to reconnect");
+        }
+        if !status.is_success() {
+            let mut body = String::new();
+            response.body_mut().read_to_string(&mut body).await.ok();
+            self.fail_request(&message, &format!("HTTP {status}: {body}"))
+                .await;
+            return Ok(());
+        }
+
+        let content_type = header_value(&response, "Content-Type").unwrap_or_default();
+        if content_type.starts_with(EVENT_STREAM_CONTENT_TYPE) {
+            let this = self.clone();
+            self.executor
+                .spawn(async move {
+                    let mut state = EventStreamState::default();
+                    let result = this.forward_events(response.into_body(), &mut state).await;
+                    if let Err(error) = result {
+                        log::warn!("context server response stream disconnected: {error:#}");
+                        if state.last_event_id.is_some() {
+                            this.listen(state, false).await;
+                        }
+                    }
+                })
+                .detach();
+        } else if content_type.starts_with(JSON_CONTENT_TYPE) {
+            let mut body = String::new();
+            response.body_mut().read_to_string(&mut body).await?;
+            self.forward_json(&body).await;
+        }
+        Ok(())
+    }
+
+    /// Forwards the messages of a JSON response, which may contain a batch of messages.
+    async fn forward_json(&self, body: &str) {
+        let messages = match serde_json::from_str::<Value>(body) {
+            Ok(Value::Array(messages)) => messages.iter().map(Value::to_string).collect(),
+            _ => vec![body.to_string()],
+        };
+        for message in messages {
+            self.inbound_tx.send(message).await.ok();
+        }
+    }
+
+    /// Forwards the messages of an event stream, returning how many were received.
+    async fn forward_events(&self, body: AsyncBody, state: &mut EventStreamState) -> Result<usize> {
+        let mut events = pin!(server_sent_events(body));
+        let mut received = 0;
+        while let Some(event) = events.next().await {
+            let event = event?;
+            state.update(&event);
+            if event.data.is_empty() || event.event.as_ref().is_some_and(|e| e != "message") {
+                continue;
+            }
+            received += 1;
+            if self.inbound_tx.send(event.data).await.is_err() {
+                break;
+            }
+        }
+        Ok(received)
+    }
+
+    /// Opens an event stream for the messages that the server sends on its own, resuming the
+    /// stream after the given event.
+    ///
+    /// Returns `None` if the server does not offer such a stream.
+    async fn open_event_stream(&self, last_event_id: Option<&str>) -> Result<Option<AsyncBody>> {
+        let mut request = self
+            .request(Method::GET)
+            .header("Accept", EVENT_STREAM_CONTENT_TYPE);
+        if let Some(last_event_id) = last_event_id {
+            request = request.header(LAST_EVENT_ID_HEADER, last_event_id);
+        }
+        let response = self
+            .http_client
+            .send(request.body(AsyncBody::empty())?)
+            .await?;
+        if response.status() == StatusCode::METHOD_NOT_ALLOWED {
+            return Ok(None);
+        }
+        anyhow::ensure!(
+            response.status().is_success(),
+            "failed to open event stream: HTTP {}",
+            response.status()
+        );
+        Ok(Some(response.into_body()))
+    }
+
+    /// Receives messages over a GET event stream, resuming it from the given state.
+    ///
+    /// When `reconnect` is true, the stream is reopened whenever the server closes it. Otherwise,
+    /// it is only reopened until it completes once, which resumes a disconnected response stream.
+    async fn listen(&self, mut state: EventStreamState, reconnect: bool) {
+        let mut failed_attempts = 0;
+        loop {
+            let result = match self.open_event_stream(state.last_event_id.as_deref()).await {
+                Ok(Some(body)) => self.forward_events(body, &mut state).await,
+                Ok(None) => return,
+                Err(error) => Err(error),
+            };
+            match result {
+                Ok(_) if !reconnect => return,
+                Ok(received) if received > 0 => failed_attempts = 0,
+                Ok(_) => failed_attempts += 1,
+                Err(error) => {
+                    log::warn!("context server event stream disconnected: {error:#}");
+                    failed_attempts += 1;
+                }
+            }
+            if self.inbound_tx.is_closed() {
+                return;
+            }
+            if failed_attempts > MAX_RECONNECTION_ATTEMPTS {
+                self.stderr_tx
+                    .send(format!("failed to reconnect to {}", self.endpoint))
+                    .await
+                    .ok();
+                return;
+            }
+            self.executor.timer(state.reconnection_delay).await;
+        }
+    }
+
+    async fn fail_request(&self, message: &str, error: &str) {
+        self.stderr_tx.send(error.to_string()).await.ok();
+        if let Some(response) = error_response(message, error) {
+            self.inbound_tx.send(response).await.ok();
+        }
+    }
+
+    /// Asks the server to terminate the session.
+    async fn terminate_session(&self) -> Result<()> {
+        let request = self.request(Method::DELETE).body(AsyncBody::empty())?;
+        self.http_client.send(request).await?;
+        Ok(())
+    }
+}
+
+fn header_value(response: &Response<AsyncBody>, name: &str) -> Option<String> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(ToString::to_string)
+}
+
+#[async_trait]
+impl Transport for StreamableHttpTransport {
+    async fn send(&self, message: String) -> Result<()> {
+        let is_initialized_notification = serde_json::from_str::<Value>(&message)
+            .ok()
+            .and_then(|message| {
+                message
+                    .get("method")
+                    .and_then(Value::as_str)
+                    .map(|method| method == INITIALIZED_NOTIFICATION)
+            })
+            .unwrap_or(false);
+
+        self.connection.post(message).await?;
+
+        if is_initialized_notification {
+            let connection = self.connection.clone();
+            *self.listen_task.lock() =
+                Some(self.connection.executor.spawn(async move {
+                    connection.listen(EventStreamState::default(), true).await
+                }));
+        }
+        Ok(())
+    }
+
+    fn receive(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+        Box::pin(self.inbound_rx.clone())
+    }
+
+    fn receive_err(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+        Box::pin(self.stderr_rx.clone())
+    }
+}
+
+impl Drop for StreamableHttpTransport {
+    fn drop(&mut self) {
+        self.listen_task.lock().take();
+        if self.connection.session_id.lock().is_some() {
+            let connection = self.connection.clone();
+            self.connection
+                .executor
+                .spawn(async move { connection.terminate_session().await.log_err() })
+                .detach();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::FakeHttpContextServer;
+    use crate::types::requests::ListTools;
+    use crate::{
+        ContextServer, ContextServerEndpoint, ContextServerHttpTransport, ContextServerId,
+        ContextServerOAuth,
+    };
+    use gpui::TestAppContext;
+    use std::time::Duration;
+
+    #[gpui::test]
+    async fn test_streamable_http_transport(cx: &mut TestAppContext) {
+        let server = FakeHttpContextServer::new("test-server");
+        let context_server = ContextServer::http(
+            ContextServerId("remote".into()),
+            ContextServerEndpoint {
+                headers: HashMap::from_iter([("X-Team".to_string(), "platform".to_string())]),
+                oauth: Some(ContextServerOAuth {
+                    access_token: Some("secret".into()),
+                    access_token_env: None,
+                }),
+                ..server.endpoint(ContextServerHttpTransport::StreamableHttp)
+            },
+            server.http_client(),
+        );
+        context_server.start(&cx.to_async()).await.unwrap();
+        cx.run_until_parked();
+
+        let client = context_server.client().unwrap();
+        let response = client.request::<ListTools>(()).await.unwrap();
+        assert_eq!(response.tools.len(), 1);
+
+        let requests = server.requests();
+        assert!(requests.iter().all(|request| {
+            request.header("Authorization") == Some("Bearer secret")
+                && request.header("X-Team") == Some("platform")
+        }));
+        // The session ID assigned during initialization is sent with every later request.
+        assert_eq!(requests[0].header(SESSION_ID_HEADER), None);
+        assert!(requests[1..].iter().all(|request| {
+            request.header(SESSION_ID_HEADER) == Some(FakeHttpContextServer::SESSION_ID)
+        }));
+        // Once initialized, the client opens an event stream for messages sent by the server.
+        assert!(
+            requests
+                .iter()
+                .any(|request| request.method == Method::GET && request.path == "/mcp")
+        );
+
+        drop(client);
+        context_server.stop().unwrap();
+        cx.run_until_parked();
+        assert!(
+            server
+                .requests()
+                .iter()
+                .any(|request| request.method == Method::DELETE)
+        );
+    }
+
+    #[gpui::test]
+    async fn test_streamable_http_event_stream_resumption(cx: &mut TestAppContext) {
+        let server = FakeHttpContextServer::new("test-server").respond_with_event_streams();
+        let context_server = ContextServer::http(
+            ContextServerId("remote".into()),
+            server.endpoint(ContextServerHttpTransport::StreamableHttp),
+            server.http_client(),
+        );
+        context_server.start(&cx.to_async()).await.unwrap();
+        cx.run_until_parked();
+
+        let client = context_server.client().unwrap();
+        let response = client.request::<ListTools>(()).await.unwrap();
+        assert_eq!(response.tools.len(), 1);
+
+        let notification_id = server.send_notification("notifications/tools/list_changed");
+        cx.run_until_parked();
+
+        // When the server closes its event stream, the client resumes it after the last event.
+        server.close_event_streams();
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
+
+        let reconnection = server
+            .requests()
+            .into_iter()
+            .filter(|request| request.method == Method::GET)
+            .last()
+            .unwrap();
+        assert_eq!(
+            reconnection.header(LAST_EVENT_ID_HEADER),
+            Some(notification_id.as_str())
+        );
+    }
+}
@@ -34,13 +34,13 @@ pub mod requests {
         "resources/unsubscribe",
         ResourcesUnsubscribe,
         ResourcesUnsubscribeParams,
-        ()
+        EmptyResponse
     );
     request!(
         "resources/subscribe",
         ResourcesSubscribe,
         ResourcesSubscribeParams,
-        ()
+        EmptyResponse
     );
     request!(
         "resources/read",
@@ -53,7 +53,7 @@ pub mod requests {
         "logging/setLevel",
         LoggingSetLevel,
         LoggingSetLevelParams,
-        ()
+        EmptyResponse
     );
     request!(
         "prompts/get",
@@ -68,7 +68,7 @@ pub mod requests {
         CompletionCompleteParams,
         CompletionCompleteResponse
     );
-    request!("ping", Ping, (), ());
+    request!("ping", Ping, (), EmptyResponse);
     request!("tools/list", ListTools, (), ListToolsResponse);
     request!(
         "resources/templates/list",
@@ -77,6 +77,12 @@ pub mod requests {
         ListResourceTemplatesResponse
     );
     request!("roots/list", ListRoots, (), ListRootsResponse);
+    request!(
+        "sampling/createMessage",
+        CreateMessage,
+        CreateMessageRequest,
+        CreateMessageResult
+    );
 }
 
 pub trait Request {
@@ -233,7 +239,8 @@ pub struct PromptReference {
 pub struct ResourceReference {
     #[serde(rename = "type")]
     pub ty: PromptReferenceType,
-    pub uri: Url,
+    /// The URI of a resource, or the URI template of a resource template.
+    pub uri: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -313,7 +320,7 @@ pub struct CreateMessageRequest {
     pub metadata: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateMessageResult {
     pub role: Role,
@@ -535,7 +542,7 @@ pub struct Implementation {
     pub version: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Resource {
     pub uri: Url,
@@ -572,7 +579,7 @@ pub struct BlobResourceContents {
     pub blob: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ResourceTemplate {
     pub uri_template: String,
@@ -583,6 +590,100 @@ pub struct ResourceTemplate {
     pub mime_type: Option<String>,
 }
 
+impl ResourceTemplate {
+    /// Returns the names of the variables in the URI template, in order of appearance.
+    pub fn variables(&self) -> Vec<&str> {
+        let mut variables = Vec::new();
+        for expression in template_expressions(&self.uri_template) {
+            let (_, names) = split_template_operator(expression);
+            for name in names.split(',').map(template_variable_name) {
+                if !name.is_empty() && !variables.contains(&name) {
+                    variables.push(name);
+                }
+            }
+        }
+        variables
+    }
+
+    /// Expands the URI template with the given values, as specified by RFC 6570.
+    ///
+    /// Variables without a value are left out of the expansion.
+    pub fn expand(&self, values: &HashMap<String, String>) -> String {
+        let mut expanded = String::new();
+        let mut rest = self.uri_template.as_str();
+        while let Some(start) = rest.find('{') {
+            let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+                break;
+            };
+            expanded.push_str(&rest[..start]);
+
+            let (operator, names) = split_template_operator(&rest[start + 1..end]);
+            let (prefix, separator, named, allow_reserved) = match operator {
+                Some('+') => ("", ",", false, true),
+                Some('#') => ("#", ",", false, true),
+                Some('.') => (".", ".", false, false),
+                Some('/') => ("/", "/", false, false),
+                Some(';') => (";", ";", true, false),
+                Some('?') => ("?", "&", true, false),
+                Some('&') => ("&", "&", true, false),
+                _ => ("", ",", false, false),
+            };
+            let mut first = true;
+            for name in names.split(',').map(template_variable_name) {
+                let Some(value) = values.get(name) else {
+                    continue;
+                };
+                expanded.push_str(if first { prefix } else { separator });
+                first = false;
+                if named {
+                    expanded.push_str(name);
+                    expanded.push('=');
+                }
+                encode_template_value(value, allow_reserved, &mut expanded);
+            }
+
+            rest = &rest[end + 1..];
+        }
+        expanded.push_str(rest);
+        expanded
+    }
+}
+
+fn template_expressions(template: &str) -> impl Iterator<Item = &str> {
+    template.split('{').skip(1).filter_map(|part| {
+        let (expression, _) = part.split_once('}')?;
+        Some(expression)
+    })
+}
+
+fn split_template_operator(expression: &str) -> (Option<char>, &str) {
+    match expression.chars().next() {
+        Some(operator @ ('+' | '#' | '.' | '/' | ';' | '?' | '&')) => {
+            (Some(operator), &expression[1..])
+        }
+        _ => (None, expression),
+    }
+}
+
+/// Strips the prefix (`:3`) and explode (`*`) modifiers from a variable.
+fn template_variable_name(variable: &str) -> &str {
+    let variable = variable.trim();
+    let variable = variable.split(':').next().unwrap_or(variable);
+    variable.trim_end_matches('*')
+}
+
+fn encode_template_value(value: &str, allow_reserved: bool, output: &mut String) {
+    for byte in value.bytes() {
+        let is_unreserved = byte.is_ascii_alphanumeric() || b"-._~".contains(&byte);
+        let is_reserved = b":/?#[]@!$&'()*+,;=".contains(&byte);
+        if is_unreserved || (allow_reserved && is_reserved) {
+            output.push(byte as char);
+        } else {
+            output.push_str(&format!("%{byte:02X}"));
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum LoggingLevel {
@@ -731,6 +832,13 @@ pub struct ListResourceTemplatesResponse {
     pub meta: Option<HashMap<String, serde_json::Value>>,
 }
 
+/// The result of requests that only acknowledge that they succeeded.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct EmptyResponse {
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<HashMap<String, serde_json::Value>>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ListRootsResponse {
@@ -746,3 +854,40 @@ pub struct Root {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resource_template_expansion() {
+        let template = ResourceTemplate {
+            uri_template: "repo://{owner}/{repo}/issues{/number}{?state,labels}".into(),
+            name: "Issues".into(),
+            description: None,
+            mime_type: None,
+        };
+        assert_eq!(
+            template.variables(),
+            ["owner", "repo", "number", "state", "labels"]
+        );
+
+        let values = HashMap::from_iter([
+            ("owner".to_string(), "zed-industries".to_string()),
+            ("repo".to_string(), "zed app".to_string()),
+            ("number".to_string(), "42".to_string()),
+            ("labels".to_string(), "a/b".to_string()),
+        ]);
+        assert_eq!(
+            template.expand(&values),
+            "repo://zed-industries/zed%20app/issues/42?labels=a%2Fb"
+        );
+
+        let template = ResourceTemplate {
+            uri_template: "file:///{+path}".into(),
+            ..template
+        };
+        let values = HashMap::from_iter([("path".to_string(), "src/main.rs".to_string())]);
+        assert_eq!(template.expand(&values), "file:///src/main.rs");
+    }
+}
@@ -36,6 +36,23 @@ impl CodeLldbDebugAdapter {
         obj.entry("cwd")
             .or_insert(delegate.worktree_root_path().to_string_lossy().into());
 
+        // Core dumps are loaded with a custom launch: the target is created from the core file,
+        // and no process is started.
+        if let Some(core_file) = obj.remove("coreFile") {
+            let core_file = core_file
+                .as_str()
+                .context("`coreFile` must be a path to a core dump")?
+                .to_owned();
+            let program = obj
+                .remove("program")
+                .and_then(|program| program.as_str().map(ToOwned::to_owned));
+            obj.entry("targetCreateCommands").or_insert_with(|| {
+                json!([core_dump_target_command(program.as_deref(), &core_file)])
+            });
+            obj.entry("processCreateCommands")
+                .or_insert_with(|| json!([]));
+        }
+
         let request = self.request_kind(&configuration).await?;
 
         Ok(dap::StartDebuggingRequestArguments {
@@ -144,6 +161,10 @@ impl DebugAdapter for CodeLldbDebugAdapter {
                     "type": "string",
                     "description": "Path to the program to debug or attach to"
                 },
+                "coreFile": {
+                    "type": "string",
+                    "description": "Path to a core dump to inspect post-mortem instead of starting `program`"
+                },
                 "args": {
                     "type": ["array", "string"],
                     "description": "Program arguments"
@@ -290,12 +311,15 @@ impl DebugAdapter for CodeLldbDebugAdapter {
                         }
                     },
                     "then": {
-                        "oneOf": [
+                        "anyOf": [
                             {
                                 "required": ["program"]
                             },
                             {
                                 "required": ["targetCreateCommands"]
+                            },
+                            {
+                                "required": ["coreFile"]
                             }
                         ]
                     }
@@ -406,3 +430,28 @@ impl DebugAdapter for CodeLldbDebugAdapter {
         })
     }
 }
+
+fn core_dump_target_command(program: Option<&str>, core_file: &str) -> String {
+    let quote = |text: &str| format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+    match program {
+        Some(program) => format!("target create -c {} {}", quote(core_file), quote(program)),
+        None => format!("target create -c {}", quote(core_file)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::core_dump_target_command;
+
+    #[test]
+    fn test_core_dump_target_command() {
+        assert_eq!(
+            core_dump_target_command(Some("/usr/bin/server"), "/tmp/core.1234"),
+            r#"target create -c "/tmp/core.1234" "/usr/bin/server""#
+        );
+        assert_eq!(
+            core_dump_target_command(None, r#"/tmp/my "core""#),
+            r#"target create -c "/tmp/my \"core\"""#
+        );
+    }
+}
@@ -111,6 +111,10 @@ impl DebugAdapter for GdbDebugAdapter {
                                     "type": "boolean",
                                     "description": "When true, GDB will set a temporary breakpoint at the program's first instruction, like the 'starti' command.",
                                     "default": false
+                                },
+                                "coreFile": {
+                                    "type": "string",
+                                    "description": "A core dump of the program to debug instead of running it. This corresponds to the GDB 'target core' command."
                                 }
                             },
                             "required": ["program"]
@@ -178,16 +182,19 @@ impl DebugAdapter for GdbDebugAdapter {
 
         let gdb_path = user_setting_path.unwrap_or(gdb_path?);
 
+        let mut arguments = user_args.unwrap_or_else(|| vec!["-i=dap".into()]);
         let mut configuration = config.config.clone();
         if let Some(configuration) = configuration.as_object_mut() {
             configuration
                 .entry("cwd")
                 .or_insert_with(|| delegate.worktree_root_path().to_string_lossy().into());
+
+            arguments.extend(core_dump_arguments(configuration)?);
         }
 
         Ok(DebugAdapterBinary {
             command: Some(gdb_path),
-            arguments: user_args.unwrap_or_else(|| vec!["-i=dap".into()]),
+            arguments,
             envs: HashMap::default(),
             cwd: Some(delegate.worktree_root_path().to_path_buf()),
             connection: None,
@@ -198,3 +205,81 @@ impl DebugAdapter for GdbDebugAdapter {
         })
     }
 }
+
+/// Replaces GDB's commands that start the program with ones that do nothing. GDB's DAP `launch`
+/// request runs the program once the configuration is done, which would replace the core dump
+/// being examined with a live process.
+const KEEP_CORE_DUMP_LOADED: &str = concat!(
+    "python exec(\"",
+    "class ZedKeepCoreDumpLoaded(gdb.Command):\\n",
+    "  def __init__(self, name):\\n",
+    "    super().__init__(name, gdb.COMMAND_RUNNING)\\n",
+    "  def invoke(self, argument, from_tty):\\n",
+    "    pass\\n",
+    "ZedKeepCoreDumpLoaded('run')\\n",
+    "ZedKeepCoreDumpLoaded('starti')\\n",
+    "\")",
+);
+
+/// Takes the core dump out of a launch configuration and returns the GDB arguments that load it.
+///
+/// GDB's DAP interpreter can only launch or attach to live processes, so core dumps are loaded on
+/// the command line, like `target core`, and launching is kept from running the program.
+fn core_dump_arguments(
+    configuration: &mut serde_json::Map<String, serde_json::Value>,
+) -> Result<Vec<String>> {
+    let Some(core_file) = configuration.remove("coreFile") else {
+        return Ok(Vec::new());
+    };
+    let core_file = core_file
+        .as_str()
+        .context("`coreFile` must be a path to a core dump")?;
+
+    let mut arguments = Vec::new();
+    if let Some(program) = configuration.remove("program") {
+        let program = program
+            .as_str()
+            .context("`program` must be a path to an executable")?;
+        arguments.push(format!("--exec={program}"));
+    }
+    arguments.push(format!("--core={core_file}"));
+    arguments.push("-ex".into());
+    arguments.push(KEEP_CORE_DUMP_LOADED.into());
+    Ok(arguments)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{KEEP_CORE_DUMP_LOADED, core_dump_arguments};
+
+    #[test]
+    fn test_core_dump_arguments() {
+        let mut configuration = json!({
+            "request": "launch",
+            "program": "/usr/bin/server",
+            "coreFile": "/tmp/core.1234",
+        });
+        let configuration = configuration.as_object_mut().unwrap();
+        assert_eq!(
+            core_dump_arguments(configuration).unwrap(),
+            [
+                "--exec=/usr/bin/server",
+                "--core=/tmp/core.1234",
+                "-ex",
+                KEEP_CORE_DUMP_LOADED,
+            ]
+        );
+        assert_eq!(
+            serde_json::Value::Object(configuration.clone()),
+            json!({"request": "launch"})
+        );
+        assert!(KEEP_CORE_DUMP_LOADED.contains("ZedKeepCoreDumpLoaded('run')\\n"));
+
+        let mut configuration = json!({"request": "launch", "program": "/usr/bin/server"});
+        let configuration = configuration.as_object_mut().unwrap();
+        assert!(core_dump_arguments(configuration).unwrap().is_empty());
+        assert!(configuration.contains_key("program"));
+    }
+}
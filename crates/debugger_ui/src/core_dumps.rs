@@ -0,0 +1,174 @@
+//! Discovery of recent crashes for post-mortem debugging.
+//!
+//! On Linux, systemd-coredump keeps the core dumps of crashed processes, which `coredumpctl` can list and extract.
+
+use std::{cmp::Reverse, path::PathBuf, sync::Arc};
+
+use anyhow::{Context as _, Result};
+use project::Fs;
+use serde::Deserialize;
+use util::command::new_smol_command;
+
+const MAX_RECENT_CRASHES: usize = 20;
+
+/// A crash recorded by systemd-coredump, whose core dump is still available.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct RecordedCrash {
+    pub(crate) pid: u32,
+    pub(crate) executable: PathBuf,
+    pub(crate) signal: Option<i32>,
+    /// Microseconds since the Unix epoch.
+    pub(crate) timestamp: u64,
+}
+
+impl RecordedCrash {
+    pub(crate) fn signal_name(&self) -> Option<&'static str> {
+        Some(match self.signal? {
+            3 => "SIGQUIT",
+            4 => "SIGILL",
+            5 => "SIGTRAP",
+            6 => "SIGABRT",
+            7 => "SIGBUS",
+            8 => "SIGFPE",
+            11 => "SIGSEGV",
+            31 => "SIGSYS",
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct CoredumpctlEntry {
+    time: u64,
+    pid: u32,
+    #[serde(default)]
+    sig: Option<i32>,
+    #[serde(default)]
+    corefile: Option<String>,
+    exe: PathBuf,
+}
+
+/// Parses the output of `coredumpctl list --json=short`, newest crashes first.
+pub(crate) fn parse_coredumpctl_list(output: &str) -> Result<Vec<RecordedCrash>> {
+    if output.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let entries: Vec<CoredumpctlEntry> =
+        serde_json::from_str(output).context("parsing coredumpctl output")?;
+    let mut crashes = entries
+        .into_iter()
+        .filter(|entry| entry.corefile.as_deref() == Some("present"))
+        .map(|entry| RecordedCrash {
+            pid: entry.pid,
+            executable: entry.exe,
+            signal: entry.sig,
+            timestamp: entry.time,
+        })
+        .collect::<Vec<_>>();
+    crashes.sort_by_key(|crash| Reverse(crash.timestamp));
+    crashes.truncate(MAX_RECENT_CRASHES);
+    Ok(crashes)
+}
+
+pub(crate) async fn recent_crashes() -> Result<Vec<RecordedCrash>> {
+    if !cfg!(target_os = "linux") {
+        return Ok(Vec::new());
+    }
+    let output = new_smol_command("coredumpctl")
+        .args(["list", "--json=short", "--no-pager", "--quiet"])
+        .output()
+        .await
+        .context("running coredumpctl")?;
+    // coredumpctl exits with an error when no crashes were recorded.
+    if !output.status.success() {
+        return Ok(Vec::new());
+    }
+    parse_coredumpctl_list(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Extracts the core dump of a recorded crash into a temporary file.
+pub(crate) async fn extract_core_dump(crash: RecordedCrash, fs: Arc<dyn Fs>) -> Result<PathBuf> {
+    let directory = paths::temp_dir().join("core-dumps");
+    fs.create_dir(&directory)
+        .await
+        .with_context(|| format!("creating {}", directory.display()))?;
+    // Process ids are reused, so the crash time tells apart the dumps of different crashes.
+    let path = directory.join(core_dump_file_name(&crash));
+    let output = new_smol_command("coredumpctl")
+        .arg("dump")
+        .args(crash_match_args(&crash))
+        .arg("--output")
+        .arg(&path)
+        .arg("--no-pager")
+        .output()
+        .await
+        .context("running coredumpctl")?;
+    anyhow::ensure!(
+        output.status.success(),
+        "coredumpctl failed to extract the core dump of pid {}: {}",
+        crash.pid,
+        String::from_utf8_lossy(&output.stderr).trim()
+    );
+    Ok(path)
+}
+
+/// The arguments that make `coredumpctl` select the dump of the given crash. A bare pid would
+/// select the newest dump of any process with that id, so the dump is also matched by the time
+/// it was recorded at, which `coredumpctl list` reports.
+fn crash_match_args(crash: &RecordedCrash) -> Vec<String> {
+    let timestamp = format!(
+        "@{}.{:06}",
+        crash.timestamp / 1_000_000,
+        crash.timestamp % 1_000_000
+    );
+    vec![
+        format!("--since={timestamp}"),
+        format!("--until={timestamp}"),
+        format!("COREDUMP_PID={}", crash.pid),
+    ]
+}
+
+fn core_dump_file_name(crash: &RecordedCrash) -> String {
+    format!("core.{}.{}", crash.pid, crash.timestamp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_coredumpctl_list() {
+        let output = r#"[
+            {"time":1700000000000000,"pid":101,"uid":1000,"gid":1000,"sig":11,"corefile":"present","exe":"/usr/bin/server","size":1024},
+            {"time":1700000100000000,"pid":202,"uid":1000,"gid":1000,"sig":6,"corefile":"missing","exe":"/usr/bin/worker","size":null},
+            {"time":1700000200000000,"pid":303,"uid":1000,"gid":1000,"sig":6,"corefile":"present","exe":"/usr/bin/client","size":2048}
+        ]"#;
+
+        let crashes = parse_coredumpctl_list(output).unwrap();
+        assert_eq!(
+            crashes
+                .iter()
+                .map(|crash| (crash.pid, crash.signal_name()))
+                .collect::<Vec<_>>(),
+            vec![(303, Some("SIGABRT")), (101, Some("SIGSEGV"))]
+        );
+        assert_eq!(crashes[0].executable, PathBuf::from("/usr/bin/client"));
+        assert_eq!(
+            core_dump_file_name(&crashes[0]),
+            "core.303.1700000200000000"
+        );
+        assert_eq!(
+            crash_match_args(&RecordedCrash {
+                timestamp: 1700000200000042,
+                ..crashes[0].clone()
+            }),
+            [
+                "--since=@1700000200.000042",
+                "--until=@1700000200.000042",
+                "COREDUMP_PID=303",
+            ]
+        );
+
+        assert!(parse_coredumpctl_list("").unwrap().is_empty());
+    }
+}
@@ -5,12 +5,13 @@ use crate::session::running::breakpoint_list::BreakpointList;
 
 use crate::{
     ClearAllBreakpoints, Continue, CopyDebugAdapterArguments, Detach, FocusBreakpointList,
-    FocusConsole, FocusFrames, FocusLoadedSources, FocusModules, FocusTerminal, FocusVariables,
-    NewProcessModal, NewProcessMode, Pause, RerunSession, StepInto, StepOut, StepOver, Stop,
-    ToggleExpandItem, ToggleSessionPicker, ToggleThreadPicker, persistence, spawn_task_or_modal,
+    FocusConsole, FocusDisassembly, FocusFrames, FocusLoadedSources, FocusModules, FocusTerminal,
+    FocusVariables, NewProcessModal, NewProcessMode, Pause, RerunSession, StepInto, StepOut,
+    StepOver, Stop, ToggleExpandItem, ToggleSessionPicker, ToggleThreadPicker, persistence,
+    spawn_task_or_modal,
 };
 use anyhow::{Context as _, Result, anyhow};
-use collections::IndexMap;
+use collections::{HashSet, IndexMap};
 use dap::adapters::DebugAdapterName;
 use dap::debugger_settings::DebugPanelDockPosition;
 use dap::{
@@ -19,6 +20,7 @@ use dap::{
 };
 use dap::{DapRegistry, StartDebuggingRequestArguments};
 use editor::Editor;
+use futures::channel::oneshot;
 use gpui::{
     Action, App, AsyncWindowContext, ClipboardItem, Context, DismissEvent, Entity, EntityId,
     EventEmitter, FocusHandle, Focusable, MouseButton, MouseDownEvent, Point, Subscription, Task,
@@ -34,10 +36,10 @@ use project::{Project, debugger::session::ThreadStatus};
 use rpc::proto::{self};
 use settings::Settings;
 use std::sync::{Arc, LazyLock};
-use task::{DebugScenario, TaskContext};
+use task::{DebugCompound, DebugScenario, TaskContext};
 use tree_sitter::{Query, StreamingIterator as _};
 use ui::{ContextMenu, Divider, PopoverMenuHandle, Tab, Tooltip, prelude::*};
-use util::{ResultExt, debug_panic, maybe};
+use util::{ResultExt, debug_panic, maybe, post_inc};
 use workspace::SplitDirection;
 use workspace::item::SaveOptions;
 use workspace::{
@@ -63,6 +65,19 @@ pub enum DebugPanelEvent {
     CapabilitiesChanged(SessionId),
 }
 
+/// Sessions started together from a [`DebugCompound`].
+pub(crate) struct CompoundSessionGroup {
+    id: usize,
+    pub(crate) label: SharedString,
+    stop_all: bool,
+    /// Whether some of the compound's scenarios are still to be launched.
+    launching: bool,
+    /// Whether the group's sessions are being stopped because one of them stopped, so that their
+    /// own shutdowns don't stop the group again.
+    stopping: bool,
+    pub(crate) sessions: Vec<SessionId>,
+}
+
 pub struct DebugPanel {
     size: Pixels,
     active_session: Option<Entity<DebugSession>>,
@@ -73,6 +88,8 @@ pub struct DebugPanel {
     debug_scenario_scheduled_last: bool,
     pub(crate) sessions_with_children:
         IndexMap<Entity<DebugSession>, Vec<WeakEntity<DebugSession>>>,
+    pub(crate) compound_groups: Vec<CompoundSessionGroup>,
+    next_compound_id: usize,
     pub(crate) thread_picker_menu_handle: PopoverMenuHandle<ContextMenu>,
     pub(crate) session_picker_menu_handle: PopoverMenuHandle<ContextMenu>,
     fs: Arc<dyn Fs>,
@@ -104,6 +121,8 @@ impl DebugPanel {
             Self {
                 size: px(300.),
                 sessions_with_children: Default::default(),
+                compound_groups: Vec::new(),
+                next_compound_id: 0,
                 active_session: None,
                 focus_handle,
                 breakpoint_list: BreakpointList::new(
@@ -188,10 +207,177 @@ impl DebugPanel {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let dap_store = self.project.read(cx).dap_store();
-        let Some(adapter) = DapRegistry::global(cx).adapter(&scenario.adapter) else {
+        self.launch_session(
+            scenario,
+            task_context,
+            active_buffer,
+            worktree_id,
+            window,
+            cx,
+        );
+    }
+
+    /// Starts every scenario of a compound, grouping the resulting sessions together in the panel.
+    pub fn start_compound(
+        &mut self,
+        compound: DebugCompound,
+        scenarios: Vec<DebugScenario>,
+        task_context: TaskContext,
+        worktree_id: Option<WorktreeId>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let group_id = post_inc(&mut self.next_compound_id);
+        self.compound_groups.push(CompoundSessionGroup {
+            id: group_id,
+            label: compound.label.clone(),
+            stop_all: compound.stop_all,
+            launching: true,
+            stopping: false,
+            sessions: Vec::new(),
+        });
+
+        cx.spawn_in(window, async move |this, cx| {
+            for scenario in scenarios {
+                let session = this.update_in(cx, |this, window, cx| {
+                    // Don't launch the rest of the compound once its sessions are being stopped.
+                    let group = this
+                        .compound_groups
+                        .iter()
+                        .find(|group| group.id == group_id)?;
+                    if group.stopping {
+                        return None;
+                    }
+                    let session = this.launch_session(
+                        scenario,
+                        task_context.clone(),
+                        None,
+                        worktree_id,
+                        window,
+                        cx,
+                    )?;
+                    this.add_session_to_compound(group_id, &session, cx);
+                    Some(session)
+                })?;
+
+                if compound.sequential
+                    && let Some(session) = session
+                {
+                    let (started_tx, started_rx) = oneshot::channel();
+                    let mut started_tx = Some(started_tx);
+                    let _subscription = cx.update(|_, cx| {
+                        cx.subscribe(&session, move |_, event: &SessionStateEvent, _| {
+                            if matches!(
+                                event,
+                                SessionStateEvent::Running | SessionStateEvent::Shutdown
+                            ) && let Some(started_tx) = started_tx.take()
+                            {
+                                started_tx.send(()).ok();
+                            }
+                        })
+                    })?;
+                    started_rx.await.ok();
+                }
+            }
+            this.update(cx, |this, cx| {
+                if let Some(group) = this
+                    .compound_groups
+                    .iter_mut()
+                    .find(|group| group.id == group_id)
+                {
+                    group.launching = false;
+                }
+                this.prune_compound_groups(cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Forgets the sessions of compounds that have stopped and are no longer shown in the panel,
+    /// and the groups that no longer have any sessions.
+    fn prune_compound_groups(&mut self, cx: &mut Context<Self>) {
+        let shown_sessions = self
+            .sessions_with_children
+            .keys()
+            .map(|session| session.read(cx).session_id(cx))
+            .collect::<HashSet<_>>();
+        let dap_store = self.project.read(cx).dap_store().read(cx);
+        for group in &mut self.compound_groups {
+            group.sessions.retain(|session_id| {
+                shown_sessions.contains(session_id)
+                    || dap_store
+                        .session_by_id(session_id)
+                        .is_some_and(|session| !session.read(cx).is_terminated())
+            });
+        }
+        self.compound_groups
+            .retain(|group| group.launching || !group.sessions.is_empty());
+    }
+
+    fn add_session_to_compound(
+        &mut self,
+        group_id: usize,
+        session: &Entity<Session>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(group) = self
+            .compound_groups
+            .iter_mut()
+            .find(|group| group.id == group_id)
+        else {
             return;
         };
+        group.sessions.push(session.read(cx).session_id());
+
+        cx.subscribe(
+            session,
+            move |this, session, event: &SessionStateEvent, cx| {
+                if !matches!(event, SessionStateEvent::Shutdown) {
+                    return;
+                }
+                let Some(group) = this
+                    .compound_groups
+                    .iter_mut()
+                    .find(|group| group.id == group_id)
+                else {
+                    return;
+                };
+                // Stopping the siblings emits their own shutdown events, which must not stop
+                // the group again.
+                if !group.stop_all || group.stopping {
+                    return;
+                }
+                group.stopping = true;
+                let stopped_id = session.read(cx).session_id();
+                let dap_store = this.project.read(cx).dap_store().read(cx);
+                let siblings = group
+                    .sessions
+                    .iter()
+                    .filter(|session_id| **session_id != stopped_id)
+                    .filter_map(|session_id| dap_store.session_by_id(session_id))
+                    .filter(|sibling| !sibling.read(cx).is_terminated())
+                    .collect::<Vec<_>>();
+                for sibling in siblings {
+                    sibling
+                        .update(cx, |sibling, cx| sibling.shutdown(cx))
+                        .detach();
+                }
+            },
+        )
+        .detach();
+    }
+
+    fn launch_session(
+        &mut self,
+        scenario: DebugScenario,
+        task_context: TaskContext,
+        active_buffer: Option<Entity<Buffer>>,
+        worktree_id: Option<WorktreeId>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Option<Entity<Session>> {
+        let dap_store = self.project.read(cx).dap_store();
+        let adapter = DapRegistry::global(cx).adapter(&scenario.adapter)?;
         let quirks = SessionQuirks {
             compact: adapter.compact_child_session(),
             prefer_thread_name: adapter.prefer_thread_name(),
@@ -206,6 +392,9 @@ impl DebugPanel {
                 cx,
             )
         });
+        if scenario.config.get("coreFile").is_some() {
+            session.update(cx, |session, _| session.set_post_mortem(true));
+        }
         let worktree = worktree_id.or_else(|| {
             active_buffer
                 .as_ref()
@@ -218,7 +407,7 @@ impl DebugPanel {
             .or_else(|| self.project.read(cx).visible_worktrees(cx).next())
         else {
             log::debug!("Could not find a worktree to spawn the debug session in");
-            return;
+            return None;
         };
 
         self.debug_scenario_scheduled_last = true;
@@ -307,6 +496,8 @@ impl DebugPanel {
                 debug_panic!("Session state should be in building because we are just starting it");
             }
         });
+
+        Some(session)
     }
 
     pub(crate) fn rerun_last_session(
@@ -392,6 +583,7 @@ impl DebugPanel {
         let adapter = curr_session.read(cx).adapter().clone();
         let binary = curr_session.read(cx).binary().cloned().unwrap();
         let task_context = curr_session.read(cx).task_context().clone();
+        let post_mortem = curr_session.read(cx).is_post_mortem();
 
         let curr_session_id = curr_session.read(cx).session_id();
         self.sessions_with_children
@@ -407,6 +599,7 @@ impl DebugPanel {
                 let session = dap_store.new_session(label, adapter, task_context, None, quirks, cx);
 
                 let task = session.update(cx, |session, cx| {
+                    session.set_post_mortem(post_mortem);
                     session.boot(binary, worktree, dap_store_handle.downgrade(), cx)
                 });
                 (session, task)
@@ -526,6 +719,7 @@ impl DebugPanel {
             session.update(cx, |session, cx| session.shutdown(cx)).ok();
             this.update(cx, |this, cx| {
                 this.retain_sessions(|other| entity_id != other.entity_id());
+                this.prune_compound_groups(cx);
                 if let Some(active_session_id) = this
                     .active_session
                     .as_ref()
@@ -683,6 +877,10 @@ impl DebugPanel {
                                     let capabilities = running_state.read(cx).capabilities(cx);
                                     let supports_detach =
                                         running_state.read(cx).session().read(cx).is_attached();
+                                    let is_post_mortem =
+                                        running_state.read(cx).session().read(cx).is_post_mortem();
+                                    let can_resume =
+                                        thread_status == ThreadStatus::Stopped && !is_post_mortem;
 
                                     this.map(|this| {
                                         if thread_status == ThreadStatus::Running {
@@ -722,7 +920,7 @@ impl DebugPanel {
                                                     &running_state,
                                                     |this, _, _window, cx| this.continue_thread(cx),
                                                 ))
-                                                .disabled(thread_status != ThreadStatus::Stopped)
+                                                .disabled(!can_resume)
                                                 .tooltip({
                                                     let focus_handle = focus_handle.clone();
                                                     move |window, cx| {
@@ -747,7 +945,7 @@ impl DebugPanel {
                                                     this.step_over(cx);
                                                 },
                                             ))
-                                            .disabled(thread_status != ThreadStatus::Stopped)
+                                            .disabled(!can_resume)
                                             .tooltip({
                                                 let focus_handle = focus_handle.clone();
                                                 move |window, cx| {
@@ -773,7 +971,7 @@ impl DebugPanel {
                                                 this.step_in(cx);
                                             },
                                         ))
-                                        .disabled(thread_status != ThreadStatus::Stopped)
+                                        .disabled(!can_resume)
                                         .tooltip({
                                             let focus_handle = focus_handle.clone();
                                             move |window, cx| {
@@ -796,7 +994,7 @@ impl DebugPanel {
                                                     this.step_out(cx);
                                                 },
                                             ))
-                                            .disabled(thread_status != ThreadStatus::Stopped)
+                                            .disabled(!can_resume)
                                             .tooltip({
                                                 let focus_handle = focus_handle.clone();
                                                 move |window, cx| {
@@ -1363,6 +1561,7 @@ async fn register_session_inner(
                 .read(cx)
                 .is_terminated()
         });
+        this.prune_compound_groups(cx);
 
         let debug_session = DebugSession::running(
             this.project.clone(),
@@ -1627,6 +1826,15 @@ impl Render for DebugPanel {
                     .ok();
                 }
             })
+            .on_action({
+                let this = this.clone();
+                move |_: &FocusDisassembly, window, cx| {
+                    this.update(cx, |this, cx| {
+                        this.activate_item(DebuggerPaneItem::Disassembly, window, cx);
+                    })
+                    .ok();
+                }
+            })
             .on_action({
                 let this = this.clone();
                 move |_: &FocusTerminal, window, cx| {
@@ -15,11 +15,12 @@ use stack_trace_view::StackTraceView;
 use tasks_ui::{Spawn, TaskOverrides};
 use ui::{FluentBuilder, InteractiveElement};
 use util::maybe;
-use workspace::{ItemHandle, ShutdownDebugAdapters, Workspace};
+use workspace::{ItemHandle, ShutdownDebugAdapters, Workspace, notifications::DetachAndPromptErr};
 use zed_actions::ToggleFocus;
 use zed_actions::debugger::OpenOnboardingModal;
 
 pub mod attach_modal;
+mod core_dumps;
 pub mod debugger_panel;
 mod dropdown_menus;
 mod new_process_modal;
@@ -54,6 +55,10 @@ actions!(
         StepOut,
         /// Steps back to the previous statement.
         StepBack,
+        /// Steps over the current machine instruction.
+        StepOverInstruction,
+        /// Steps into the current machine instruction.
+        StepIntoInstruction,
         /// Stops the debugging session.
         Stop,
         /// Toggles whether to ignore all breakpoints.
@@ -74,6 +79,8 @@ actions!(
         FocusLoadedSources,
         /// Focuses on the terminal panel.
         FocusTerminal,
+        /// Focuses on the disassembly panel.
+        FocusDisassembly,
         /// Shows the stack trace for the current thread.
         ShowStackTrace,
         /// Toggles the thread picker dropdown.
@@ -193,6 +200,22 @@ pub fn init(cx: &mut App) {
                             active_item.update(cx, |item, cx| item.step_out(cx)).ok();
                         }
                     })
+                    .on_action({
+                        let active_item = active_item.clone();
+                        move |_: &StepOverInstruction, _, cx| {
+                            active_item
+                                .update(cx, |item, cx| item.step_over_instruction(cx))
+                                .ok();
+                        }
+                    })
+                    .on_action({
+                        let active_item = active_item.clone();
+                        move |_: &StepIntoInstruction, _, cx| {
+                            active_item
+                                .update(cx, |item, cx| item.step_in_instruction(cx))
+                                .ok();
+                        }
+                    })
                     .when(supports_step_back, |div| {
                         let active_item = active_item.clone();
                         div.on_action(move |_: &StepBack, _, cx| {
@@ -370,6 +393,60 @@ pub fn init(cx: &mut App) {
                         },
                     );
 
+                    window.on_action_when(
+                        session.any_stopped_thread() && session.supports_set_next_statement(),
+                        TypeId::of::<editor::actions::SetNextStatement>(),
+                        {
+                            let editor = editor.clone();
+                            let active_session = active_session.clone();
+                            move |_, phase, window, cx| {
+                                if phase != DispatchPhase::Bubble {
+                                    return;
+                                }
+                                maybe!({
+                                    let (buffer, position, _) = editor
+                                        .update(cx, |editor, cx| {
+                                            let cursor_point: language::Point =
+                                                editor.selections.newest(cx).head();
+
+                                            editor
+                                                .buffer()
+                                                .read(cx)
+                                                .point_to_buffer_point(cursor_point, cx)
+                                        })
+                                        .ok()??;
+
+                                    let path =
+                                debugger::breakpoint_store::BreakpointStore::abs_path_from_buffer(
+                                    &buffer, cx,
+                                )?;
+
+                                    let task = active_session.update(cx, |session, cx| {
+                                        session.running_state().update(cx, |state, cx| {
+                                            let thread_id = state.selected_thread_id()?;
+                                            Some(state.session().update(cx, |session, cx| {
+                                                session.set_next_statement(
+                                                    thread_id,
+                                                    path,
+                                                    position.row,
+                                                    cx,
+                                                )
+                                            }))
+                                        })
+                                    })?;
+                                    task.detach_and_prompt_err(
+                                        "Failed to set next statement",
+                                        window,
+                                        cx,
+                                        |_, _, _| None,
+                                    );
+
+                                    Some(())
+                                });
+                            }
+                        },
+                    );
+
                     window.on_action(
                         TypeId::of::<editor::actions::EvaluateSelectedText>(),
                         move |_, _, window, cx| {
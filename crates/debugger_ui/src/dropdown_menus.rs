@@ -132,6 +132,34 @@ impl DebugPanel {
             );
         }
 
+        // Sessions started from the same compound are listed together, under the compound's label.
+        let mut current_group = None;
+        let mut session_entries = session_entries
+            .into_iter()
+            .map(|entry| {
+                let root = entry
+                    .ancestors
+                    .first()
+                    .unwrap_or(&entry.leaf)
+                    .read(cx)
+                    .session(cx);
+                let root = root.read(cx);
+                if root.parent_id(cx).is_none() {
+                    current_group = self
+                        .compound_groups
+                        .iter()
+                        .position(|group| group.sessions.contains(&root.session_id()));
+                }
+                (current_group, entry)
+            })
+            .collect::<Vec<_>>();
+        session_entries.sort_by_key(|(group, _)| *group);
+        let group_labels = self
+            .compound_groups
+            .iter()
+            .map(|group| group.label.clone())
+            .collect::<Vec<_>>();
+
         let weak = cx.weak_entity();
         let trigger_label = if let Some(active_session) = active_session.clone() {
             active_session.update(cx, |active_session, cx| {
@@ -181,7 +209,16 @@ impl DebugPanel {
             ContextMenu::build(window, cx, move |mut this, _, cx| {
                 let context_menu = cx.weak_entity();
                 let mut session_depths = HashMap::default();
-                for session_entry in session_entries {
+                let mut last_group = None;
+                for (group, session_entry) in session_entries {
+                    if group != last_group {
+                        if let Some(label) = group.and_then(|group| group_labels.get(group)) {
+                            this = this.header(label.clone());
+                        } else {
+                            this = this.separator();
+                        }
+                        last_group = group;
+                    }
                     let session_id = session_entry.leaf.read(cx).session_id(cx);
                     let parent_depth = session_entry
                         .ancestors
@@ -20,9 +20,9 @@ use gpui::{
 };
 use itertools::Itertools as _;
 use picker::{Picker, PickerDelegate, highlighted_match_with_paths::HighlightedMatch};
-use project::{DebugScenarioContext, TaskContexts, TaskSourceKind, task_store::TaskStore};
+use project::{DebugScenarioContext, Fs, TaskContexts, TaskSourceKind, task_store::TaskStore};
 use settings::Settings;
-use task::{DebugScenario, RevealTarget, ZedDebugConfig};
+use task::{DebugCompound, DebugScenario, RevealTarget, ZedDebugConfig};
 use theme::ThemeSettings;
 use ui::{
     ActiveTheme, Button, ButtonCommon, ButtonSize, CheckboxWithLabel, Clickable, Color, Context,
@@ -35,7 +35,11 @@ use ui::{
 use util::ResultExt;
 use workspace::{ModalView, Workspace, notifications::DetachAndPromptErr, pane};
 
-use crate::{attach_modal::AttachModal, debugger_panel::DebugPanel};
+use crate::{
+    attach_modal::AttachModal,
+    core_dumps::{self, RecordedCrash},
+    debugger_panel::DebugPanel,
+};
 
 pub(super) struct NewProcessModal {
     workspace: WeakEntity<Workspace>,
@@ -44,6 +48,7 @@ pub(super) struct NewProcessModal {
     debug_picker: Entity<Picker<DebugDelegate>>,
     attach_mode: Entity<AttachMode>,
     configure_mode: Entity<ConfigureMode>,
+    core_dump_mode: Entity<CoreDumpMode>,
     task_mode: TaskMode,
     debugger: Option<DebugAdapterName>,
     _subscriptions: [Subscription; 3],
@@ -80,6 +85,7 @@ impl NewProcessModal {
         };
         let task_store = workspace.project().read(cx).task_store().clone();
         let languages = workspace.app_state().languages.clone();
+        let fs = workspace.app_state().fs.clone();
 
         cx.spawn_in(window, async move |workspace, cx| {
             let task_contexts = workspace.update_in(cx, |workspace, window, cx| {
@@ -98,6 +104,7 @@ impl NewProcessModal {
                     });
 
                     let configure_mode = ConfigureMode::new(window, cx);
+                    let core_dump_mode = CoreDumpMode::new(fs.clone(), window, cx);
 
                     let task_overrides = Some(TaskOverrides { reveal_target });
 
@@ -252,6 +259,7 @@ impl NewProcessModal {
                         debug_picker,
                         attach_mode,
                         configure_mode,
+                        core_dump_mode,
                         task_mode,
                         debugger: None,
                         mode,
@@ -283,6 +291,9 @@ impl NewProcessModal {
             NewProcessMode::Launch => self.configure_mode.update(cx, |this, cx| {
                 this.clone().render(dap_menu, window, cx).into_any_element()
             }),
+            NewProcessMode::CoreDump => self.core_dump_mode.update(cx, |this, cx| {
+                this.render(dap_menu, window, cx).into_any_element()
+            }),
             NewProcessMode::Debug => v_flex()
                 .w(rems(34.))
                 .child(self.debug_picker.clone())
@@ -295,6 +306,7 @@ impl NewProcessModal {
             NewProcessMode::Task => self.task_mode.task_modal.focus_handle(cx),
             NewProcessMode::Attach => self.attach_mode.read(cx).attach_picker.focus_handle(cx),
             NewProcessMode::Launch => self.configure_mode.read(cx).program.focus_handle(cx),
+            NewProcessMode::CoreDump => self.core_dump_mode.read(cx).program.focus_handle(cx),
             NewProcessMode::Debug => self.debug_picker.focus_handle(cx),
         }
     }
@@ -307,6 +319,11 @@ impl NewProcessModal {
             NewProcessMode::Attach => {
                 DebugRequest::Attach(self.attach_mode.read(cx).debug_request())
             }
+            NewProcessMode::CoreDump => {
+                return Task::ready(Some(
+                    self.core_dump_mode.read(cx).debug_scenario(debugger, cx),
+                ));
+            }
             _ => return Task::ready(None),
         };
         let label = suggested_label(&request, debugger);
@@ -502,12 +519,16 @@ impl NewProcessModal {
 
 static SELECT_DEBUGGER_LABEL: SharedString = SharedString::new_static("Select Debugger");
 
+/// The debugger preselected for core dumps, as it's installed automatically, unlike GDB.
+const CORE_DUMP_DEBUGGER: &str = "CodeLLDB";
+
 #[derive(Clone, Copy)]
 pub(crate) enum NewProcessMode {
     Task,
     Launch,
     Attach,
     Debug,
+    CoreDump,
 }
 
 impl std::fmt::Display for NewProcessMode {
@@ -517,6 +538,7 @@ impl std::fmt::Display for NewProcessMode {
             NewProcessMode::Debug => "Debug",
             NewProcessMode::Attach => "Attach",
             NewProcessMode::Launch => "Launch",
+            NewProcessMode::CoreDump => "Core Dump",
         };
 
         write!(f, "{}", mode)
@@ -592,7 +614,8 @@ impl Render for NewProcessModal {
                     NewProcessMode::Task => NewProcessMode::Debug,
                     NewProcessMode::Debug => NewProcessMode::Attach,
                     NewProcessMode::Attach => NewProcessMode::Launch,
-                    NewProcessMode::Launch => NewProcessMode::Task,
+                    NewProcessMode::Launch => NewProcessMode::CoreDump,
+                    NewProcessMode::CoreDump => NewProcessMode::Task,
                 };
 
                 this.mode_focus_handle(cx).focus(window);
@@ -600,10 +623,11 @@ impl Render for NewProcessModal {
             .on_action(
                 cx.listener(|this, _: &pane::ActivatePreviousItem, window, cx| {
                     this.mode = match this.mode {
-                        NewProcessMode::Task => NewProcessMode::Launch,
+                        NewProcessMode::Task => NewProcessMode::CoreDump,
                         NewProcessMode::Debug => NewProcessMode::Task,
                         NewProcessMode::Attach => NewProcessMode::Debug,
                         NewProcessMode::Launch => NewProcessMode::Attach,
+                        NewProcessMode::CoreDump => NewProcessMode::Launch,
                     };
 
                     this.mode_focus_handle(cx).focus(window);
@@ -686,6 +710,27 @@ impl Render for NewProcessModal {
                             cx.notify();
                         }))
                         .tooltip(Tooltip::text("Launch a new process with a debugger"))
+                        .middle(),
+                    )
+                    .child(
+                        ToggleButton::new(
+                            "debugger-session-ui-core-dump-button",
+                            NewProcessMode::CoreDump.to_string(),
+                        )
+                        .size(ButtonSize::Default)
+                        .toggle_state(matches!(self.mode, NewProcessMode::CoreDump))
+                        .style(ui::ButtonStyle::Subtle)
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.mode = NewProcessMode::CoreDump;
+                            if this.debugger.is_none() {
+                                this.debugger = Some(DebugAdapterName(CORE_DUMP_DEBUGGER.into()));
+                            }
+                            this.mode_focus_handle(cx).focus(window);
+                            cx.notify();
+                        }))
+                        .tooltip(Tooltip::text(
+                            "Inspect a crashed process from its core dump",
+                        ))
                         .last(),
                     ),
             )
@@ -769,6 +814,18 @@ impl Render for NewProcessModal {
                                     .child(div().child(self.adapter_drop_down_menu(window, cx))),
                             )
                     }),
+                    NewProcessMode::CoreDump => el.child(
+                        container.child(div()).child(
+                            Button::new("debugger-spawn-core-dump", "Start")
+                                .on_click(cx.listener(|this, _, window, cx| {
+                                    this.start_new_session(window, cx)
+                                }))
+                                .disabled(
+                                    self.debugger.is_none()
+                                        || !self.core_dump_mode.read(cx).is_complete(cx),
+                                ),
+                        ),
+                    ),
                     NewProcessMode::Debug => el,
                     NewProcessMode::Task => el,
                 }
@@ -935,6 +992,186 @@ impl ConfigureMode {
     }
 }
 
+pub(super) struct CoreDumpMode {
+    fs: Arc<dyn Fs>,
+    program: Entity<Editor>,
+    core_file: Entity<Editor>,
+    recent_crashes: Vec<RecordedCrash>,
+    extracting: Option<u32>,
+    _load_crashes: Task<()>,
+}
+
+impl CoreDumpMode {
+    pub(super) fn new(fs: Arc<dyn Fs>, window: &mut Window, cx: &mut App) -> Entity<Self> {
+        let program = cx.new(|cx| Editor::single_line(window, cx));
+        program.update(cx, |this, cx| {
+            this.set_placeholder_text("Ex: ~/bin/program", cx);
+        });
+
+        let core_file = cx.new(|cx| Editor::single_line(window, cx));
+        core_file.update(cx, |this, cx| {
+            this.set_placeholder_text("Ex: ~/core.1234", cx);
+        });
+
+        cx.new(|cx| Self {
+            fs,
+            program,
+            core_file,
+            recent_crashes: Vec::new(),
+            extracting: None,
+            _load_crashes: cx.spawn(async move |this, cx| {
+                let Some(crashes) = core_dumps::recent_crashes().await.log_err() else {
+                    return;
+                };
+                this.update(cx, |this, cx| {
+                    this.recent_crashes = crashes;
+                    cx.notify();
+                })
+                .ok();
+            }),
+        })
+    }
+
+    fn is_complete(&self, cx: &App) -> bool {
+        !self.program.read(cx).is_empty(cx) && !self.core_file.read(cx).is_empty(cx)
+    }
+
+    pub(super) fn debug_scenario(&self, debugger: &str, cx: &App) -> DebugScenario {
+        let program = self.program.read(cx).text(cx);
+        let core_file = self.core_file.read(cx).text(cx);
+        let core_file_name = Path::new(&core_file)
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_else(|| Cow::Borrowed(&core_file));
+
+        DebugScenario {
+            adapter: debugger.to_owned().into(),
+            label: format!("{core_file_name} ({debugger})").into(),
+            build: None,
+            config: serde_json::json!({
+                "request": "launch",
+                "program": program,
+                "coreFile": core_file,
+            }),
+            tcp_connection: None,
+        }
+    }
+
+    fn select_crash(&mut self, crash: RecordedCrash, window: &mut Window, cx: &mut Context<Self>) {
+        self.program.update(cx, |editor, cx| {
+            editor.set_text(crash.executable.to_string_lossy(), window, cx);
+        });
+        self.extracting = Some(crash.pid);
+        cx.notify();
+
+        let extract = cx
+            .background_executor()
+            .spawn(core_dumps::extract_core_dump(crash, self.fs.clone()));
+        cx.spawn_in(window, async move |this, cx| {
+            let path = extract.await;
+            this.update_in(cx, |this, window, cx| {
+                this.extracting = None;
+                cx.notify();
+                let path = path?;
+                this.core_file.update(cx, |editor, cx| {
+                    editor.set_text(path.to_string_lossy(), window, cx);
+                });
+                anyhow::Ok(())
+            })?
+        })
+        .detach_and_prompt_err("Failed to extract core dump", window, cx, |_, _, _| None);
+    }
+
+    fn render(
+        &mut self,
+        adapter_menu: DropdownMenu,
+        window: &mut Window,
+        cx: &mut ui::Context<Self>,
+    ) -> impl IntoElement {
+        v_flex()
+            .p_2()
+            .w_full()
+            .gap_2()
+            .track_focus(&self.program.focus_handle(cx))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Label::new("Debugger")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(adapter_menu),
+            )
+            .child(
+                v_flex()
+                    .gap_0p5()
+                    .child(
+                        Label::new("Executable")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(render_editor(&self.program, window, cx)),
+            )
+            .child(
+                v_flex()
+                    .gap_0p5()
+                    .child(
+                        Label::new("Core File")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(render_editor(&self.core_file, window, cx)),
+            )
+            .when(!self.recent_crashes.is_empty(), |this| {
+                this.child(
+                    v_flex()
+                        .gap_0p5()
+                        .child(
+                            Label::new("Recent Crashes")
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                        .children(self.recent_crashes.iter().map(|crash| {
+                            let executable = crash
+                                .executable
+                                .file_name()
+                                .unwrap_or(crash.executable.as_os_str())
+                                .to_string_lossy()
+                                .into_owned();
+                            let details = match crash.signal_name() {
+                                Some(signal) => format!("pid {}, {signal}", crash.pid),
+                                None => format!("pid {}", crash.pid),
+                            };
+                            let extracting = self.extracting == Some(crash.pid);
+                            ListItem::new(SharedString::from(format!("recent-crash-{}", crash.pid)))
+                                .inset(true)
+                                .spacing(ListItemSpacing::Sparse)
+                                .disabled(self.extracting.is_some())
+                                .toggle_state(extracting)
+                                .on_click(cx.listener({
+                                    let crash = crash.clone();
+                                    move |this, _, window, cx| {
+                                        this.select_crash(crash.clone(), window, cx);
+                                    }
+                                }))
+                                .child(
+                                    h_flex().gap_2().child(Label::new(executable)).child(
+                                        Label::new(if extracting {
+                                            "Extracting…".to_string()
+                                        } else {
+                                            details
+                                        })
+                                        .size(LabelSize::Small)
+                                        .color(Color::Muted),
+                                    ),
+                                )
+                        })),
+                )
+            })
+    }
+}
+
 #[derive(Clone)]
 pub(super) struct AttachMode {
     pub(super) definition: ZedDebugConfig,
@@ -983,6 +1220,8 @@ pub(super) struct DebugDelegate {
         DebugScenario,
         Option<DebugScenarioContext>,
     )>,
+    /// Compound scenarios, matched after all [`Self::candidates`].
+    compounds: Vec<(TaskSourceKind, DebugCompound)>,
     selected_index: usize,
     matches: Vec<StringMatch>,
     prompt: String,
@@ -997,6 +1236,7 @@ impl DebugDelegate {
         Self {
             task_store,
             candidates: Vec::default(),
+            compounds: Vec::default(),
             selected_index: 0,
             matches: Vec::new(),
             prompt: String::new(),
@@ -1055,6 +1295,13 @@ impl DebugDelegate {
             })
         });
 
+        let compounds = self
+            .task_store
+            .read(cx)
+            .task_inventory()
+            .map(|inventory| inventory.read(cx).list_debug_compounds(&task_contexts))
+            .unwrap_or_default();
+
         let valid_adapters: HashSet<_> = cx.global::<DapRegistry>().enumerate_adapters();
 
         cx.spawn(async move |this, cx| {
@@ -1079,6 +1326,18 @@ impl DebugDelegate {
                     _ => false,
                 });
 
+                this.delegate.compounds = compounds
+                    .into_iter()
+                    .filter(|(kind, _)| match kind {
+                        TaskSourceKind::Worktree {
+                            id: _,
+                            directory_in_worktree: dir,
+                            id_base: _,
+                        } => !(hide_vscode && dir.ends_with(".vscode")),
+                        _ => true,
+                    })
+                    .collect();
+
                 this.delegate.candidates = recent
                     .into_iter()
                     .map(|(scenario, context)| {
@@ -1111,6 +1370,60 @@ impl DebugDelegate {
     }
 }
 
+impl DebugDelegate {
+    fn confirm_compound(
+        &mut self,
+        kind: TaskSourceKind,
+        compound: DebugCompound,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) {
+        let scenarios = match self.task_store.read(cx).task_inventory() {
+            Some(inventory) => inventory
+                .read(cx)
+                .debug_compound_scenarios(&kind, &compound),
+            None => Err(anyhow::anyhow!(
+                "No task inventory to look up debug scenarios in"
+            )),
+        };
+        let scenarios = match scenarios {
+            Ok(scenarios) => scenarios,
+            Err(error) => {
+                Task::ready(Err::<(), _>(error)).detach_and_prompt_err(
+                    "Failed to start compound debug session",
+                    window,
+                    cx,
+                    |error, _, _| Some(error.to_string()),
+                );
+                return;
+            }
+        };
+
+        let (task_context, worktree_id) = self
+            .task_contexts
+            .as_ref()
+            .and_then(|task_contexts| {
+                Some((
+                    task_contexts.active_context().cloned()?,
+                    task_contexts.worktree(),
+                ))
+            })
+            .unwrap_or_default();
+        let worktree_id = match &kind {
+            TaskSourceKind::Worktree { id, .. } => Some(*id),
+            _ => worktree_id,
+        };
+        for scenario in &scenarios {
+            send_telemetry(scenario, TelemetrySpawnLocation::ScenarioList, cx);
+        }
+        self.debug_panel
+            .update(cx, |panel, cx| {
+                panel.start_compound(compound, scenarios, task_context, worktree_id, window, cx);
+            })
+            .ok();
+    }
+}
+
 impl PickerDelegate for DebugDelegate {
     type ListItem = ui::ListItem;
 
@@ -1142,14 +1455,15 @@ impl PickerDelegate for DebugDelegate {
         cx: &mut Context<picker::Picker<Self>>,
     ) -> gpui::Task<()> {
         let candidates = self.candidates.clone();
+        let compounds = self.compounds.clone();
 
         cx.spawn_in(window, async move |picker, cx| {
             let candidates: Vec<_> = candidates
                 .into_iter()
+                .map(|(_, candidate, _)| candidate.label)
+                .chain(compounds.into_iter().map(|(_, compound)| compound.label))
                 .enumerate()
-                .map(|(index, (_, candidate, _))| {
-                    StringMatchCandidate::new(index, candidate.label.as_ref())
-                })
+                .map(|(index, label)| StringMatchCandidate::new(index, label.as_ref()))
                 .collect();
 
             let matches = fuzzy::match_strings(
@@ -1311,12 +1625,26 @@ impl PickerDelegate for DebugDelegate {
         window: &mut Window,
         cx: &mut Context<picker::Picker<Self>>,
     ) {
-        let debug_scenario = self
+        let Some(candidate_id) = self
             .matches
             .get(self.selected_index())
-            .and_then(|match_candidate| self.candidates.get(match_candidate.candidate_id).cloned());
+            .map(|match_candidate| match_candidate.candidate_id)
+        else {
+            return;
+        };
 
-        let Some((kind, debug_scenario, context)) = debug_scenario else {
+        if let Some((kind, compound)) = candidate_id
+            .checked_sub(self.candidates.len())
+            .and_then(|index| self.compounds.get(index))
+            .cloned()
+        {
+            self.confirm_compound(kind, compound, window, cx);
+            cx.emit(DismissEvent);
+            return;
+        }
+
+        let Some((kind, debug_scenario, context)) = self.candidates.get(candidate_id).cloned()
+        else {
             return;
         };
 
@@ -1442,7 +1770,23 @@ impl PickerDelegate for DebugDelegate {
             char_count: hit.string.chars().count(),
             color: Color::Default,
         };
-        let task_kind = &self.candidates[hit.candidate_id].0;
+        let task_kind = match self.candidates.get(hit.candidate_id) {
+            Some((kind, _, _)) => kind.as_ref(),
+            None => {
+                return Some(
+                    ListItem::new(SharedString::from(format!("debug-scenario-selection-{ix}")))
+                        .inset(true)
+                        .start_slot(
+                            Icon::new(IconName::ListTree)
+                                .color(Color::Muted)
+                                .size(IconSize::Small),
+                        )
+                        .spacing(ListItemSpacing::Sparse)
+                        .toggle_state(selected)
+                        .child(highlighted_location.render(window, cx)),
+                );
+            }
+        };
 
         let icon = match task_kind {
             Some(TaskSourceKind::UserInput) => Some(Icon::new(IconName::Terminal)),
@@ -0,0 +1,697 @@
+use std::{ops::Range, path::Path, sync::Arc};
+
+use collections::HashMap;
+use dap::DisassembledInstruction;
+use gpui::{
+    AnyElement, Entity, FocusHandle, Focusable, MouseButton, ScrollStrategy, Stateful,
+    Subscription, Task, UniformListScrollHandle, WeakEntity, uniform_list,
+};
+use project::{
+    Project,
+    debugger::session::{Session, SessionEvent},
+};
+use ui::{Scrollbar, ScrollbarState, Tooltip, VisibleOnHover, prelude::*};
+use util::ResultExt as _;
+use workspace::Workspace;
+
+use crate::{
+    StepIntoInstruction, StepOverInstruction,
+    session::running::{
+        RunningState,
+        stack_frame_list::{StackFrameList, StackFrameListEvent},
+    },
+};
+
+/// How many instructions to disassemble before the instruction pointer.
+const INSTRUCTIONS_BEFORE: u64 = 64;
+/// How many instructions to disassemble from the instruction pointer onwards.
+const INSTRUCTIONS_AFTER: u64 = 128;
+
+#[derive(Debug, PartialEq, Eq)]
+enum DisassemblyRow {
+    /// A line of the source code the following instructions were compiled from.
+    Source {
+        path: Arc<Path>,
+        line: u64,
+        text: SharedString,
+    },
+    /// An index into `DisassemblyView::instructions`.
+    Instruction(usize),
+}
+
+pub(crate) struct DisassemblyView {
+    session: Entity<Session>,
+    stack_frame_list: Entity<StackFrameList>,
+    running_state: WeakEntity<RunningState>,
+    workspace: WeakEntity<Workspace>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    scrollbar_state: ScrollbarState,
+    instructions: Vec<DisassembledInstruction>,
+    source_text: HashMap<Arc<Path>, String>,
+    rows: Vec<DisassemblyRow>,
+    instruction_pointer: Option<u64>,
+    show_source: bool,
+    error: Option<SharedString>,
+    _refresh_task: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl DisassemblyView {
+    pub(crate) fn new(
+        session: Entity<Session>,
+        stack_frame_list: Entity<StackFrameList>,
+        running_state: WeakEntity<RunningState>,
+        workspace: WeakEntity<Workspace>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let scroll_handle = UniformListScrollHandle::new();
+
+        let _subscriptions = vec![
+            cx.subscribe(&stack_frame_list, |this, _, event, cx| match event {
+                StackFrameListEvent::SelectedStackFrameChanged(_)
+                | StackFrameListEvent::BuiltEntries => this.refresh(cx),
+            }),
+            cx.subscribe(&session, |this, _, event, cx| {
+                if let SessionEvent::Stopped(_) = event {
+                    // Keep the previous disassembly around, but stop highlighting an instruction
+                    // the program may no longer be at.
+                    this.instruction_pointer = None;
+                    cx.notify();
+                }
+            }),
+            cx.observe(&session, |_, _, cx| cx.notify()),
+        ];
+
+        let mut this = Self {
+            scrollbar_state: ScrollbarState::new(scroll_handle.clone()),
+            scroll_handle,
+            session,
+            stack_frame_list,
+            running_state,
+            workspace,
+            focus_handle: cx.focus_handle(),
+            instructions: Vec::new(),
+            source_text: HashMap::default(),
+            rows: Vec::new(),
+            instruction_pointer: None,
+            show_source: true,
+            error: None,
+            _refresh_task: Task::ready(()),
+            _subscriptions,
+        };
+        this.refresh(cx);
+        this
+    }
+
+    fn refresh(&mut self, cx: &mut Context<Self>) {
+        let Some(memory_reference) = self
+            .stack_frame_list
+            .read(cx)
+            .opened_stack_frame()
+            .and_then(|stack_frame| stack_frame.instruction_pointer_reference.clone())
+        else {
+            self.instruction_pointer = None;
+            cx.notify();
+            return;
+        };
+
+        let instructions = self.session.update(cx, |session, cx| {
+            session.disassemble(
+                memory_reference.clone(),
+                -(INSTRUCTIONS_BEFORE as i64),
+                INSTRUCTIONS_BEFORE + INSTRUCTIONS_AFTER,
+                cx,
+            )
+        });
+        let project = self
+            .workspace
+            .read_with(cx, |workspace, _| workspace.project().clone())
+            .ok();
+
+        self._refresh_task = cx.spawn(async move |this, cx| {
+            let Some(instructions) = instructions.await else {
+                this.update(cx, |this, cx| {
+                    this.error = Some("Failed to disassemble the current stack frame".into());
+                    this.instructions.clear();
+                    this.rebuild_rows(cx);
+                })
+                .ok();
+                return;
+            };
+
+            let source_text = match project {
+                Some(project) => load_source_text(&instructions, project, cx).await,
+                None => HashMap::default(),
+            };
+
+            this.update(cx, |this, cx| {
+                this.error = None;
+                this.instructions = instructions;
+                this.source_text = source_text;
+                this.instruction_pointer = parse_address(&memory_reference);
+                this.rebuild_rows(cx);
+                if let Some(ix) = this.current_row() {
+                    this.scroll_handle
+                        .scroll_to_item(ix, ScrollStrategy::Center);
+                }
+            })
+            .ok();
+        });
+    }
+
+    fn rebuild_rows(&mut self, cx: &mut Context<Self>) {
+        self.rows = build_rows(&self.instructions, &self.source_text, self.show_source);
+        cx.notify();
+    }
+
+    fn current_row(&self) -> Option<usize> {
+        let instruction_pointer = self.instruction_pointer?;
+        self.rows.iter().position(|row| match row {
+            DisassemblyRow::Instruction(ix) => {
+                parse_address(&self.instructions[*ix].address) == Some(instruction_pointer)
+            }
+            DisassemblyRow::Source { .. } => false,
+        })
+    }
+
+    fn toggle_show_source(&mut self, cx: &mut Context<Self>) {
+        self.show_source = !self.show_source;
+        self.rebuild_rows(cx);
+    }
+
+    fn toggle_breakpoint(&mut self, instruction_ix: usize, cx: &mut Context<Self>) {
+        let Some(instruction) = self.instructions.get(instruction_ix) else {
+            return;
+        };
+        let instruction_reference = instruction.address.clone();
+        self.session.update(cx, |session, cx| {
+            session.toggle_instruction_breakpoint(instruction_reference, cx);
+        });
+    }
+
+    fn open_source(
+        &mut self,
+        path: Arc<Path>,
+        line: u64,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let row = line.saturating_sub(1) as u32;
+        let Some(open) = self
+            .workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_abs_path(
+                    path.to_path_buf(),
+                    workspace::OpenOptions {
+                        visible: Some(workspace::OpenVisible::None),
+                        ..Default::default()
+                    },
+                    window,
+                    cx,
+                )
+            })
+            .ok()
+        else {
+            return;
+        };
+
+        cx.spawn_in(window, async move |_, cx| {
+            let item = open.await?;
+            if let Some(editor) = item.downcast::<editor::Editor>() {
+                editor.update_in(cx, |editor, window, cx| {
+                    editor.go_to_singleton_buffer_point(language::Point::new(row, 0), window, cx);
+                })?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn render_entry(&mut self, ix: usize, cx: &mut Context<Self>) -> AnyElement {
+        match &self.rows[ix] {
+            DisassemblyRow::Source { path, line, text } => {
+                let path = path.clone();
+                let line = *line;
+                let file_name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+
+                h_flex()
+                    .id(("disassembly-source", ix))
+                    .w_full()
+                    .gap_2()
+                    .pl_6()
+                    .pt_1()
+                    .buffer_font(cx)
+                    .text_ui_xs(cx)
+                    .text_color(cx.theme().colors().text_muted)
+                    .cursor_pointer()
+                    .hover(|style| style.bg(cx.theme().colors().element_hover))
+                    .on_click(cx.listener(move |this, _, window, cx| {
+                        this.open_source(path.clone(), line, window, cx);
+                    }))
+                    .child(div().flex_none().child(format!("{file_name}:{line}")))
+                    .child(div().overflow_hidden().text_ellipsis().child(text.clone()))
+                    .into_any()
+            }
+            DisassemblyRow::Instruction(instruction_ix) => {
+                let instruction_ix = *instruction_ix;
+                let instruction = &self.instructions[instruction_ix];
+                let is_current = self.instruction_pointer.is_some()
+                    && parse_address(&instruction.address) == self.instruction_pointer;
+                let breakpoint = self
+                    .session
+                    .read(cx)
+                    .instruction_breakpoints()
+                    .get(&instruction.address)
+                    .map(|breakpoint| breakpoint.is_enabled);
+                let group = SharedString::from(format!("disassembly-row-{ix}"));
+
+                let gutter = div()
+                    .id(("disassembly-breakpoint", ix))
+                    .flex_none()
+                    .w_4()
+                    .cursor_pointer()
+                    .child(
+                        Icon::new(match breakpoint {
+                            Some(false) => IconName::DebugDisabledBreakpoint,
+                            _ => IconName::DebugBreakpoint,
+                        })
+                        .color(if breakpoint.is_some() {
+                            Color::Debugger
+                        } else {
+                            Color::Hint
+                        })
+                        .size(IconSize::XSmall),
+                    )
+                    .when(breakpoint.is_none(), |this| {
+                        this.visible_on_hover(group.clone())
+                    })
+                    .tooltip(Tooltip::text(if breakpoint.is_some() {
+                        "Remove Instruction Breakpoint"
+                    } else {
+                        "Set Instruction Breakpoint"
+                    }))
+                    .on_click(cx.listener(move |this, _, _, cx| {
+                        cx.stop_propagation();
+                        this.toggle_breakpoint(instruction_ix, cx);
+                    }));
+
+                h_flex()
+                    .id(("disassembly-instruction", ix))
+                    .group(group)
+                    .w_full()
+                    .gap_2()
+                    .px_1()
+                    .buffer_font(cx)
+                    .text_ui_sm(cx)
+                    .when(is_current, |this| {
+                        this.bg(cx.theme().colors().editor_debugger_active_line_background)
+                    })
+                    .hover(|style| style.bg(cx.theme().colors().element_hover))
+                    .child(gutter)
+                    .child(div().flex_none().w_4().when(is_current, |this| {
+                        this.child(
+                            Icon::new(IconName::ArrowRight)
+                                .color(Color::Warning)
+                                .size(IconSize::XSmall),
+                        )
+                    }))
+                    .child(
+                        div()
+                            .flex_none()
+                            .text_color(cx.theme().colors().text_muted)
+                            .child(instruction.address.clone()),
+                    )
+                    .when_some(instruction.instruction_bytes.clone(), |this, bytes| {
+                        this.child(
+                            div()
+                                .flex_none()
+                                .w_32()
+                                .overflow_hidden()
+                                .text_ellipsis()
+                                .text_color(cx.theme().colors().text_placeholder)
+                                .child(bytes),
+                        )
+                    })
+                    .child(
+                        div()
+                            .flex_1()
+                            .overflow_hidden()
+                            .text_ellipsis()
+                            .child(instruction.instruction.clone()),
+                    )
+                    .when_some(instruction.symbol.clone(), |this, symbol| {
+                        this.child(
+                            div()
+                                .flex_none()
+                                .text_color(cx.theme().colors().text_muted)
+                                .child(format!("<{symbol}>")),
+                        )
+                    })
+                    .into_any()
+            }
+        }
+    }
+
+    fn render_vertical_scrollbar(&self, cx: &mut Context<Self>) -> Stateful<Div> {
+        div()
+            .occlude()
+            .id("disassembly-view-vertical-scrollbar")
+            .on_mouse_move(cx.listener(|_, _, _, cx| {
+                cx.notify();
+                cx.stop_propagation()
+            }))
+            .on_hover(|_, _, cx| {
+                cx.stop_propagation();
+            })
+            .on_any_mouse_down(|_, _, cx| {
+                cx.stop_propagation();
+            })
+            .on_mouse_up(
+                MouseButton::Left,
+                cx.listener(|_, _, _, cx| {
+                    cx.stop_propagation();
+                }),
+            )
+            .on_scroll_wheel(cx.listener(|_, _, _, cx| {
+                cx.notify();
+            }))
+            .h_full()
+            .absolute()
+            .right_1()
+            .top_1()
+            .bottom_0()
+            .w(px(12.))
+            .cursor_default()
+            .children(Scrollbar::vertical(self.scrollbar_state.clone()))
+    }
+
+    fn render_toolbar(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let is_stopped = self
+            .running_state
+            .read_with(cx, |state, cx| state.thread_status(cx))
+            .ok()
+            .flatten()
+            == Some(project::debugger::session::ThreadStatus::Stopped);
+        let focus_handle = self.focus_handle.clone();
+
+        h_flex()
+            .w_full()
+            .gap_1()
+            .pb_1()
+            .child(
+                IconButton::new("disassembly-step-over-instruction", IconName::DebugStepOver)
+                    .icon_size(IconSize::XSmall)
+                    .disabled(!is_stopped)
+                    .tooltip({
+                        let focus_handle = focus_handle.clone();
+                        move |window, cx| {
+                            Tooltip::for_action_in(
+                                "Step Over Instruction",
+                                &StepOverInstruction,
+                                &focus_handle,
+                                window,
+                                cx,
+                            )
+                        }
+                    })
+                    .on_click(cx.listener(|this, _, _, cx| {
+                        this.running_state
+                            .update(cx, |state, cx| state.step_over_instruction(cx))
+                            .ok();
+                    })),
+            )
+            .child(
+                IconButton::new("disassembly-step-into-instruction", IconName::DebugStepInto)
+                    .icon_size(IconSize::XSmall)
+                    .disabled(!is_stopped)
+                    .tooltip({
+                        let focus_handle = focus_handle.clone();
+                        move |window, cx| {
+                            Tooltip::for_action_in(
+                                "Step Into Instruction",
+                                &StepIntoInstruction,
+                                &focus_handle,
+                                window,
+                                cx,
+                            )
+                        }
+                    })
+                    .on_click(cx.listener(|this, _, _, cx| {
+                        this.running_state
+                            .update(cx, |state, cx| state.step_in_instruction(cx))
+                            .ok();
+                    })),
+            )
+            .child(div().flex_1())
+            .child(
+                Button::new("disassembly-toggle-source", "Show Source")
+                    .label_size(LabelSize::Small)
+                    .toggle_state(self.show_source)
+                    .on_click(cx.listener(|this, _, _, cx| this.toggle_show_source(cx))),
+            )
+    }
+
+    fn render_list(&mut self, cx: &mut Context<Self>) -> impl IntoElement {
+        uniform_list(
+            "disassembly-view",
+            self.rows.len(),
+            cx.processor(|this, range: Range<usize>, _window, cx| {
+                range.map(|ix| this.render_entry(ix, cx)).collect()
+            }),
+        )
+        .track_scroll(self.scroll_handle.clone())
+        .size_full()
+    }
+}
+
+/// Opens the source files the instructions were compiled from, so their lines can be shown
+/// alongside the assembly.
+async fn load_source_text(
+    instructions: &[DisassembledInstruction],
+    project: Entity<Project>,
+    cx: &mut gpui::AsyncApp,
+) -> HashMap<Arc<Path>, String> {
+    let mut paths = instructions
+        .iter()
+        .filter_map(|instruction| instruction.location.as_ref()?.path.as_deref())
+        .map(|path| Arc::<Path>::from(Path::new(path)))
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths.dedup();
+
+    let mut source_text = HashMap::default();
+    for path in paths {
+        let Some(buffer) = project
+            .update(cx, |project, cx| project.open_local_buffer(&path, cx))
+            .log_err()
+        else {
+            continue;
+        };
+        let Some(buffer) = buffer.await.log_err() else {
+            continue;
+        };
+        if let Some(text) = buffer.read_with(cx, |buffer, _| buffer.text()).log_err() {
+            source_text.insert(path, text);
+        }
+    }
+    source_text
+}
+
+/// Interleaves instructions with the source lines they were compiled from.
+///
+/// Adapters only send an instruction's location when it differs from the previous instruction's,
+/// so the last location seen applies until a new one arrives.
+fn build_rows(
+    instructions: &[DisassembledInstruction],
+    source_text: &HashMap<Arc<Path>, String>,
+    show_source: bool,
+) -> Vec<DisassemblyRow> {
+    let mut rows = Vec::with_capacity(instructions.len());
+    let mut current_path: Option<Arc<Path>> = None;
+    let mut current_line = None;
+
+    for (ix, instruction) in instructions.iter().enumerate() {
+        if !show_source {
+            rows.push(DisassemblyRow::Instruction(ix));
+            continue;
+        }
+
+        if let Some(path) = instruction
+            .location
+            .as_ref()
+            .and_then(|source| source.path.as_deref())
+        {
+            if current_path.as_deref() != Some(Path::new(path)) {
+                current_path = Some(Arc::from(Path::new(path)));
+                current_line = None;
+            }
+        }
+
+        if let (Some(path), Some(line)) = (&current_path, instruction.line) {
+            if current_line != Some(line) {
+                current_line = Some(line);
+                let text = source_text
+                    .get(path)
+                    .and_then(|text| text.lines().nth(line.saturating_sub(1) as usize))
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string();
+                rows.push(DisassemblyRow::Source {
+                    path: path.clone(),
+                    line,
+                    text: text.into(),
+                });
+            }
+        }
+
+        rows.push(DisassemblyRow::Instruction(ix));
+    }
+
+    rows
+}
+
+/// Parses an address or memory reference, which adapters send either in hex (`0x...`) or decimal.
+fn parse_address(address: &str) -> Option<u64> {
+    let address = address.trim();
+    match address
+        .strip_prefix("0x")
+        .or_else(|| address.strip_prefix("0X"))
+    {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => address.parse().ok(),
+    }
+}
+
+impl Focusable for DisassemblyView {
+    fn focus_handle(&self, _: &gpui::App) -> gpui::FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for DisassemblyView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("DisassemblyView")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .p_1()
+            .child(self.render_toolbar(cx))
+            .map(|this| {
+                if let Some(error) = self.error.clone() {
+                    this.child(Label::new(error).color(Color::Error).size(LabelSize::Small))
+                } else if self.rows.is_empty() {
+                    this.child(
+                        Label::new("No disassembly available for the selected stack frame")
+                            .color(Color::Muted)
+                            .size(LabelSize::Small),
+                    )
+                } else {
+                    this.child(
+                        div()
+                            .relative()
+                            .size_full()
+                            .child(self.render_list(cx))
+                            .child(self.render_vertical_scrollbar(cx)),
+                    )
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instruction(
+        address: &str,
+        path: Option<&str>,
+        line: Option<u64>,
+    ) -> DisassembledInstruction {
+        DisassembledInstruction {
+            address: address.to_string(),
+            instruction_bytes: None,
+            instruction: "nop".to_string(),
+            symbol: None,
+            location: path.map(|path| dap::Source {
+                name: None,
+                path: Some(path.to_string()),
+                source_reference: None,
+                presentation_hint: None,
+                origin: None,
+                sources: None,
+                adapter_data: None,
+                checksums: None,
+            }),
+            line,
+            column: None,
+            end_line: None,
+            end_column: None,
+            presentation_hint: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_address() {
+        assert_eq!(parse_address("0x10"), Some(16));
+        assert_eq!(parse_address("0X00000001000003F0"), Some(0x1_0000_03F0));
+        assert_eq!(parse_address("42"), Some(42));
+        assert_eq!(parse_address("main+4"), None);
+    }
+
+    #[test]
+    fn test_build_rows_interleaves_source() {
+        let instructions = [
+            instruction("0x0", Some("/src/main.rs"), Some(2)),
+            instruction("0x4", None, Some(2)),
+            instruction("0x8", None, Some(3)),
+            instruction("0xc", Some("/src/lib.rs"), Some(3)),
+            instruction("0x10", None, None),
+        ];
+        let source_text = HashMap::from_iter([(
+            Arc::from(Path::new("/src/main.rs")),
+            "fn main() {\n    let x = 1;\n    foo(x);\n}\n".to_string(),
+        )]);
+
+        let rows = build_rows(&instructions, &source_text, true);
+        let main_rs: Arc<Path> = Arc::from(Path::new("/src/main.rs"));
+        let lib_rs: Arc<Path> = Arc::from(Path::new("/src/lib.rs"));
+        assert_eq!(
+            rows,
+            vec![
+                DisassemblyRow::Source {
+                    path: main_rs.clone(),
+                    line: 2,
+                    text: "let x = 1;".into(),
+                },
+                DisassemblyRow::Instruction(0),
+                DisassemblyRow::Instruction(1),
+                DisassemblyRow::Source {
+                    path: main_rs,
+                    line: 3,
+                    text: "foo(x);".into(),
+                },
+                DisassemblyRow::Instruction(2),
+                // Same line number, but a different file.
+                DisassemblyRow::Source {
+                    path: lib_rs,
+                    line: 3,
+                    text: "".into(),
+                },
+                DisassemblyRow::Instruction(3),
+                DisassemblyRow::Instruction(4),
+            ]
+        );
+
+        let rows = build_rows(&instructions, &source_text, false);
+        assert_eq!(
+            rows,
+            (0..instructions.len())
+                .map(DisassemblyRow::Instruction)
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -488,24 +488,32 @@ impl MemoryView {
             // Go into memory writing mode.
             if !self.is_writing_memory {
                 let should_return = self.session.update(cx, |session, cx| {
-                    if !session
+                    let error = if session.is_post_mortem() {
+                        Some("Memory of a core dump is read-only".to_string())
+                    } else if !session
                         .capabilities()
                         .supports_write_memory_request
                         .unwrap_or_default()
                     {
                         let adapter_name = session.adapter();
-                        // We cannot write memory with this adapter.
+                        Some(format!(
+                            "Debug Adapter `{adapter_name}` does not support writing to memory"
+                        ))
+                    } else {
+                        None
+                    };
+                    if let Some(error) = error {
+                        // We cannot write memory in this session.
                         _ = self.workspace.update(cx, |this, cx| {
                             this.toggle_status_toast(
-                                StatusToast::new(format!(
-                                    "Debug Adapter `{adapter_name}` does not support writing to memory"
-                                ), cx, |this, cx| {
+                                StatusToast::new(error, cx, |this, cx| {
                                     cx.spawn(async move |this, cx| {
-                                        cx.background_executor().timer(Duration::from_secs(2)).await;
-                                        _ = this.update(cx, |_, cx| {
-                                            cx.emit(DismissEvent)
-                                        });
-                                    }).detach();
+                                        cx.background_executor()
+                                            .timer(Duration::from_secs(2))
+                                            .await;
+                                        _ = this.update(cx, |_, cx| cx.emit(DismissEvent));
+                                    })
+                                    .detach();
                                     this.icon(ToastIcon::new(IconName::XCircle).color(Color::Error))
                                 }),
                                 cx,
@@ -635,7 +635,8 @@ impl VariableList {
                     session
                         .capabilities()
                         .supports_set_variable
-                        .unwrap_or_default(),
+                        .unwrap_or_default()
+                        && !session.is_post_mortem(),
                     session
                         .capabilities()
                         .supports_data_breakpoints
@@ -1096,6 +1097,7 @@ impl VariableList {
                         this.text_color(cx.theme().colors().text_muted)
                             .when(
                                 !self.disabled
+                                    && !self.session.read(cx).is_post_mortem()
                                     && self
                                         .session
                                         .read(cx)
@@ -20,14 +20,14 @@ use editor::{
 use gpui::{BackgroundExecutor, TestAppContext, VisualTestContext};
 use project::{
     FakeFs, Project,
-    debugger::session::{ThreadId, ThreadStatus},
+    debugger::session::{SessionStateEvent, ThreadId, ThreadStatus},
 };
 use serde_json::json;
 use std::{
     path::Path,
     sync::{
         Arc,
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicUsize, Ordering},
     },
 };
 use terminal_view::terminal_panel::TerminalPanel;
@@ -1756,6 +1756,114 @@ async fn test_active_debug_line_setting(executor: BackgroundExecutor, cx: &mut T
     });
 }
 
+#[gpui::test]
+async fn test_set_next_statement(executor: BackgroundExecutor, cx: &mut TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(executor.clone());
+
+    fs.insert_tree(
+        path!("/project"),
+        json!({
+            "main.rs": "First line\nSecond line\nThird line\nFourth line",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, [path!("/project").as_ref()], cx).await;
+    let workspace = init_test_workspace(&project, cx).await;
+    let cx = &mut VisualTestContext::from_window(*workspace, cx);
+
+    let session = start_debug_session(&workspace, cx, |client| {
+        client.on_request::<dap::requests::Initialize, _>(move |_, _| {
+            Ok(dap::Capabilities {
+                supports_goto_targets_request: Some(true),
+                ..Default::default()
+            })
+        });
+    })
+    .unwrap();
+    let client = session.update(cx, |session, _| session.adapter_client().unwrap());
+
+    client.on_request::<Threads, _>(move |_, _| {
+        Ok(dap::ThreadsResponse {
+            threads: vec![dap::Thread {
+                id: 1,
+                name: "Thread 1".into(),
+            }],
+        })
+    });
+
+    client.on_request::<StackTrace, _>(move |_, _| {
+        Ok(dap::StackTraceResponse {
+            stack_frames: Vec::default(),
+            total_frames: None,
+        })
+    });
+
+    client.on_request::<dap::requests::GotoTargets, _>(move |_, args| {
+        assert_eq!(
+            args.source.path,
+            Some(path!("/project/main.rs").to_string())
+        );
+
+        Ok(dap::GotoTargetsResponse {
+            targets: vec![dap::GotoTarget {
+                id: args.line * 10,
+                label: "main.rs".into(),
+                line: args.line,
+                column: None,
+                end_line: None,
+                end_column: None,
+                instruction_pointer_reference: None,
+            }],
+        })
+    });
+
+    let goto_target = Arc::new(std::sync::Mutex::new(None));
+    client.on_request::<dap::requests::Goto, _>({
+        let goto_target = goto_target.clone();
+        move |_, args| {
+            assert_eq!(args.thread_id, 1);
+            *goto_target.lock().unwrap() = Some(args.target_id);
+            Ok(())
+        }
+    });
+
+    client
+        .fake_event(dap::messages::Events::Stopped(dap::StoppedEvent {
+            reason: dap::StoppedEventReason::Pause,
+            description: None,
+            thread_id: Some(1),
+            preserve_focus_hint: None,
+            text: None,
+            all_threads_stopped: None,
+            hit_breakpoint_ids: None,
+        }))
+        .await;
+
+    cx.run_until_parked();
+
+    session
+        .update(cx, |session, cx| {
+            assert!(session.supports_set_next_statement());
+            session.set_next_statement(
+                ThreadId(1),
+                Path::new(path!("/project/main.rs")).into(),
+                2,
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(
+        *goto_target.lock().unwrap(),
+        Some(30),
+        "Goto should be sent with the target of the third line"
+    );
+}
+
 #[gpui::test]
 async fn test_debug_adapters_shutdown_on_app_quit(
     executor: BackgroundExecutor,
@@ -1947,3 +2055,111 @@ async fn test_adapter_shutdown_with_child_sessions_on_app_quit(
         "Child session should have received disconnect request"
     );
 }
+
+#[gpui::test]
+async fn test_stop_all_compound_sessions(executor: BackgroundExecutor, cx: &mut TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(executor.clone());
+    fs.insert_tree(
+        path!("/project"),
+        json!({
+            "main.rs": "First line\nSecond line\nThird line\nFourth line",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, [path!("/project").as_ref()], cx).await;
+    let dap_store = project.update(cx, |project, _| project.dap_store());
+    let workspace = init_test_workspace(&project, cx).await;
+    let cx = &mut VisualTestContext::from_window(*workspace, cx);
+    let debug_panel = workspace
+        .update(cx, |workspace, _, cx| {
+            workspace.panel::<DebugPanel>(cx).unwrap()
+        })
+        .unwrap();
+
+    let _subscription = project::debugger::test::intercept_debug_sessions(cx, |client| {
+        client.on_request::<Disconnect, _>(move |_, _| Ok(()));
+    });
+    let scenario = |label: &str| {
+        DebugTaskDefinition {
+            adapter: "fake-adapter".into(),
+            label: label.to_string().into(),
+            config: json!({
+                "request": "launch"
+            }),
+            tcp_connection: None,
+        }
+        .to_scenario()
+    };
+    debug_panel.update_in(cx, |debug_panel, window, cx| {
+        debug_panel.start_compound(
+            task::DebugCompound {
+                label: "Client and Server".into(),
+                configurations: vec!["client".into(), "server".into()],
+                stop_all: true,
+                sequential: false,
+            },
+            vec![scenario("client"), scenario("server")],
+            task::TaskContext::default(),
+            None,
+            window,
+            cx,
+        );
+    });
+    cx.run_until_parked();
+
+    let sessions = debug_panel.read_with(cx, |debug_panel, cx| {
+        assert_eq!(debug_panel.compound_groups.len(), 1);
+        debug_panel.compound_groups[0]
+            .sessions
+            .iter()
+            .map(|session_id| dap_store.read(cx).session_by_id(session_id).unwrap())
+            .collect::<Vec<_>>()
+    });
+    assert_eq!(sessions.len(), 2);
+
+    let shutdown_count = Arc::new(AtomicUsize::new(0));
+    let _subscriptions = sessions
+        .iter()
+        .map(|session| {
+            let shutdown_count = shutdown_count.clone();
+            cx.update(|_, cx| {
+                cx.subscribe(session, move |_, event: &SessionStateEvent, _| {
+                    if let SessionStateEvent::Shutdown = event {
+                        shutdown_count.fetch_add(1, Ordering::SeqCst);
+                    }
+                })
+            })
+        })
+        .collect::<Vec<_>>();
+
+    // Stopping one session of the compound stops the other one, once.
+    sessions[0]
+        .update(cx, |session, cx| session.shutdown(cx))
+        .detach();
+    cx.run_until_parked();
+    for session in &sessions {
+        assert!(session.read_with(cx, |session, _| session.is_terminated()));
+    }
+    assert_eq!(shutdown_count.load(Ordering::SeqCst), 2);
+
+    // The stopped sessions stay grouped while they are shown, and the group is forgotten once
+    // they are closed.
+    debug_panel.read_with(cx, |debug_panel, _| {
+        assert_eq!(debug_panel.compound_groups[0].sessions.len(), 2);
+    });
+    let debug_sessions = debug_panel.read_with(cx, |debug_panel, _| {
+        debug_panel.sessions().collect::<Vec<_>>()
+    });
+    for debug_session in debug_sessions {
+        debug_panel.update_in(cx, |debug_panel, window, cx| {
+            debug_panel.close_session(debug_session.entity_id(), window, cx);
+        });
+        cx.run_until_parked();
+    }
+    debug_panel.read_with(cx, |debug_panel, _| {
+        assert!(debug_panel.compound_groups.is_empty());
+    });
+}
@@ -123,8 +123,8 @@ use language::{
     AutoindentMode, BlockCommentConfig, BracketMatch, BracketPair, Buffer, BufferRow,
     BufferSnapshot, Capability, CharClassifier, CharKind, CodeLabel, CursorShape, DiagnosticEntry,
     DiffOptions, EditPredictionsMode, EditPreview, HighlightedText, IndentKind, IndentSize,
-    Language, OffsetRangeExt, Point, Runnable, RunnableRange, Selection, SelectionGoal, TextObject,
-    TransactionId, TreeSitterOptions, WordsQuery,
+    Language, LanguageName, OffsetRangeExt, Point, Runnable, RunnableRange, Selection,
+    SelectionGoal, TextObject, TransactionId, TreeSitterOptions, WordsQuery,
     language_settings::{
         self, InlayHintSettings, LspInsertMode, RewrapBehavior, WordsCompletionMode,
         all_language_settings, language_settings,
@@ -306,6 +306,7 @@ impl InlayId {
 
 pub enum ActiveDebugLine {}
 pub enum DebugStackFrameLine {}
+enum NextStatementTarget {}
 enum DocumentHighlightRead {}
 enum DocumentHighlightWrite {}
 enum InputComposition {}
@@ -847,6 +848,13 @@ impl RunnableTasks {
     }
 }
 
+/// The runnables that extensions detected in a buffer, kept until the buffer changes.
+struct ExtensionRunnables {
+    version: clock::Global,
+    language: Option<LanguageName>,
+    runnables: Vec<RunnableRange>,
+}
+
 #[derive(Clone)]
 pub struct ResolvedTasks {
     templates: SmallVec<[(TaskSourceKind, ResolvedTask); 1]>,
@@ -1154,9 +1162,12 @@ pub struct Editor {
     last_position_map: Option<Rc<PositionMap>>,
     expect_bounds_change: Option<Bounds<Pixels>>,
     tasks: BTreeMap<(BufferId, BufferRow), RunnableTasks>,
+    extension_runnables: HashMap<BufferId, ExtensionRunnables>,
     tasks_update_task: Option<Task<()>>,
     breakpoint_store: Option<Entity<BreakpointStore>>,
     gutter_breakpoint_indicator: (Option<PhantomBreakpointIndicator>, Option<Task<()>>),
+    /// The row the current execution position is being dragged to from the gutter.
+    execution_position_drag: Option<DisplayRow>,
     hovered_diff_hunk_row: Option<DisplayRow>,
     pull_diagnostics_task: Task<()>,
     in_project_search: bool,
@@ -2157,9 +2168,11 @@ impl Editor {
             blame: None,
             blame_subscription: None,
             tasks: BTreeMap::default(),
+            extension_runnables: HashMap::default(),
 
             breakpoint_store,
             gutter_breakpoint_indicator: (None, None),
+            execution_position_drag: None,
             hovered_diff_hunk_row: None,
             _subscriptions: (!is_minimap)
                 .then(|| {
@@ -8030,6 +8043,7 @@ impl Editor {
         };
 
         let run_to_cursor = window.is_action_available(&RunToCursor, cx);
+        let set_next_statement = window.is_action_available(&SetNextStatement, cx);
 
         let toggle_state_msg = breakpoint.as_ref().map_or(None, |bp| match bp.1.state {
             BreakpointState::Enabled => Some("Disable"),
@@ -8058,6 +8072,24 @@ impl Editor {
 
                         window.dispatch_action(Box::new(RunToCursor), cx);
                     })
+                    .when(!set_next_statement, |this| this.separator())
+                })
+                .when(set_next_statement, |this| {
+                    let weak_editor = weak_editor.clone();
+                    this.entry("Set next statement", None, move |window, cx| {
+                        weak_editor
+                            .update(cx, |editor, cx| {
+                                editor.change_selections(
+                                    SelectionEffects::no_scroll(),
+                                    window,
+                                    cx,
+                                    |s| s.select_ranges([Point::new(row, 0)..Point::new(row, 0)]),
+                                );
+                            })
+                            .ok();
+
+                        window.dispatch_action(Box::new(SetNextStatement), cx);
+                    })
                     .separator()
                 })
                 .when_some(toggle_state_msg, |this, msg| {
@@ -14928,7 +14960,7 @@ impl Editor {
             if hide_runnables {
                 return;
             }
-            let new_rows =
+            let mut new_rows =
                 cx.background_spawn({
                     let snapshot = display_snapshot.clone();
                     async move {
@@ -14936,6 +14968,58 @@ impl Editor {
                     }
                 })
                     .await;
+            let Ok(extension_runnables) = editor.update(cx, |editor, cx| {
+                let buffers = multi_buffer
+                    .upgrade()
+                    .map(|multi_buffer| multi_buffer.read(cx).all_buffers())
+                    .unwrap_or_default();
+                editor.extension_runnables.retain(|buffer_id, _| {
+                    buffers
+                        .iter()
+                        .any(|buffer| buffer.read(cx).remote_id() == *buffer_id)
+                });
+                let project = project.read(cx);
+                future::join_all(
+                    buffers
+                        .iter()
+                        .map(|buffer| {
+                            let snapshot = buffer.read(cx);
+                            let buffer_id = snapshot.remote_id();
+                            let version = snapshot.version();
+                            let language = snapshot.language().map(|language| language.name());
+                            let runnables = match editor.extension_runnables.get(&buffer_id) {
+                                Some(cached)
+                                    if cached.version == version && cached.language == language =>
+                                {
+                                    Task::ready(cached.runnables.clone())
+                                }
+                                _ => project.extension_runnables(buffer, cx),
+                            };
+                            async move { (buffer_id, version, language, runnables.await) }
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            }) else {
+                return;
+            };
+            let extension_runnables = extension_runnables.await;
+            let Ok(()) = editor.update(cx, |editor, _| {
+                for (buffer_id, version, language, runnables) in extension_runnables {
+                    new_rows.extend(runnables.iter().cloned().filter_map(|runnable| {
+                        Self::extension_runnable_range(&display_snapshot.buffer_snapshot, runnable)
+                    }));
+                    editor.extension_runnables.insert(
+                        buffer_id,
+                        ExtensionRunnables {
+                            version,
+                            language,
+                            runnables,
+                        },
+                    );
+                }
+            }) else {
+                return;
+            };
             let Ok(lsp_tasks) =
                 cx.update(|_, cx| crate::lsp_tasks(project.clone(), &task_sources, None, cx))
             else {
@@ -15029,6 +15113,35 @@ impl Editor {
         snapshot.buffer_snapshot.runnable_ranges(range).collect()
     }
 
+    /// Converts the buffer offsets of a runnable detected by an extension into offsets of the multi-buffer,
+    /// as produced by [`Self::fetch_runnable_ranges`].
+    fn extension_runnable_range(
+        snapshot: &MultiBufferSnapshot,
+        mut runnable: RunnableRange,
+    ) -> Option<RunnableRange> {
+        let (excerpt_id, buffer_snapshot, excerpt_range) =
+            snapshot
+                .excerpts()
+                .find(|(_, buffer_snapshot, excerpt_range)| {
+                    buffer_snapshot.remote_id() == runnable.buffer_id
+                        && excerpt_range
+                            .context
+                            .to_offset(buffer_snapshot)
+                            .contains(&runnable.run_range.start)
+                })?;
+        let excerpt_end = excerpt_range.context.end.to_offset(buffer_snapshot);
+        let start = snapshot.anchor_in_excerpt(
+            excerpt_id,
+            buffer_snapshot.anchor_before(runnable.run_range.start),
+        )?;
+        let end = snapshot.anchor_in_excerpt(
+            excerpt_id,
+            buffer_snapshot.anchor_after(runnable.run_range.end.min(excerpt_end)),
+        )?;
+        runnable.run_range = start.to_offset(snapshot)..end.to_offset(snapshot);
+        Some(runnable)
+    }
+
     fn runnable_rows(
         project: Entity<Project>,
         snapshot: DisplaySnapshot,
@@ -18834,6 +18947,77 @@ impl Editor {
         .is_some()
     }
 
+    fn active_debug_display_row(&self, snapshot: &EditorSnapshot) -> Option<DisplayRow> {
+        let (range, _) = self.highlighted_rows::<ActiveDebugLine>().last()?;
+        Some(range.start.to_display_point(snapshot).row())
+    }
+
+    /// Starts dragging the current execution position, if it's on the given row.
+    fn begin_execution_position_drag(
+        &mut self,
+        row: DisplayRow,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        if !window.is_action_available(&SetNextStatement, cx) {
+            return false;
+        }
+        let snapshot = self.snapshot(window, cx);
+        if self.active_debug_display_row(&snapshot) != Some(row) {
+            return false;
+        }
+        window.focus(&self.focus_handle);
+        self.execution_position_drag = Some(row);
+        true
+    }
+
+    fn update_execution_position_drag(
+        &mut self,
+        row: DisplayRow,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.execution_position_drag == Some(row) {
+            return;
+        }
+        self.execution_position_drag = Some(row);
+        self.clear_row_highlights::<NextStatementTarget>();
+
+        let snapshot = self.snapshot(window, cx);
+        if self.active_debug_display_row(&snapshot) != Some(row) {
+            let position = snapshot.display_point_to_anchor(DisplayPoint::new(row, 0), Bias::Left);
+            self.highlight_rows::<NextStatementTarget>(
+                position..position,
+                cx.theme()
+                    .colors()
+                    .editor_debugger_active_line_background
+                    .opacity(0.5),
+                RowHighlightOptions::default(),
+                cx,
+            );
+        }
+        cx.notify();
+    }
+
+    /// Moves the execution to the row the current execution position was dropped on.
+    fn end_execution_position_drag(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(row) = self.execution_position_drag.take() else {
+            return;
+        };
+        self.clear_row_highlights::<NextStatementTarget>();
+        cx.notify();
+
+        let snapshot = self.snapshot(window, cx);
+        if self.active_debug_display_row(&snapshot) == Some(row) {
+            return;
+        }
+        let point = DisplayPoint::new(row, 0).to_point(&snapshot);
+        self.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
+            s.select_ranges([point..point])
+        });
+        window.dispatch_action(Box::new(SetNextStatement), cx);
+    }
+
     pub fn copy_file_name_without_extension(
         &mut self,
         _: &CopyFileNameWithoutExtension,
@@ -20261,6 +20445,8 @@ impl Editor {
                 .unwrap_or(DiagnosticSeverity::Hint);
             self.set_max_diagnostics_severity(new_severity, cx);
         }
+        // Whether extensions detect runnables depends on the task settings.
+        self.extension_runnables.clear();
         self.tasks_update_task = Some(self.refresh_runnables(window, cx));
         self.update_edit_prediction_settings(cx);
         self.refresh_edit_prediction(true, false, window, cx);
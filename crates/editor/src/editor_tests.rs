@@ -13,6 +13,7 @@ use crate::{
     },
 };
 use buffer_diff::{BufferDiff, DiffHunkSecondaryStatus, DiffHunkStatus, DiffHunkStatusKind};
+use extension::{ExtensionHostProxy, ExtensionTaskProviderProxy as _, FakeExtension};
 use futures::StreamExt;
 use gpui::{
     BackgroundExecutor, DismissEvent, Rgba, SemanticVersion, TestAppContext, UpdateGlobal,
@@ -20052,6 +20053,74 @@ async fn test_find_enclosing_node_with_task(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_extension_runnables_cached_per_buffer_version(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let extension = Arc::new(
+        FakeExtension::new(FakeExtension::empty_manifest("fake-runner")).with_runnables(|buffer| {
+            buffer
+                .text
+                .match_indices("fn ")
+                .map(|(start, _)| extension::Runnable {
+                    range: start..start + 2,
+                    tags: vec!["fake-test".to_string()],
+                    captures: Default::default(),
+                })
+                .collect()
+        }),
+    );
+    cx.update(|cx| {
+        project::task_store::extension_tasks::init(cx);
+        ExtensionHostProxy::default_global(cx).register_task_provider(
+            extension.clone(),
+            "fake-runner".into(),
+            vec!["Rust".into()],
+        );
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_file(path!("/file.rs"), "fn one() {}\n".into())
+        .await;
+    let project = Project::test(fs, [path!("/file.rs").as_ref()], cx).await;
+    project.read_with(cx, |project, _| project.languages().add(rust_lang()));
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(path!("/file.rs"), cx)
+        })
+        .await
+        .unwrap();
+    let multi_buffer = cx.new(|cx| MultiBuffer::singleton(buffer, cx));
+    let (editor, cx) = cx.add_window_view(|window, cx| {
+        build_editor_with_project(project.clone(), multi_buffer, window, cx)
+    });
+    cx.executor().advance_clock(UPDATE_DEBOUNCE);
+    cx.run_until_parked();
+    assert_eq!(extension.detect_runnables_count(), 1);
+
+    editor.update_in(cx, |editor, window, cx| {
+        editor.tasks_update_task = Some(editor.refresh_runnables(window, cx));
+    });
+    cx.executor().advance_clock(UPDATE_DEBOUNCE);
+    cx.run_until_parked();
+    assert_eq!(
+        extension.detect_runnables_count(),
+        1,
+        "refreshing an unchanged buffer should reuse its runnables"
+    );
+
+    editor.update_in(cx, |editor, window, cx| {
+        editor.set_text("fn one() {}\nfn two() {}\n", window, cx);
+    });
+    cx.executor().advance_clock(UPDATE_DEBOUNCE);
+    cx.run_until_parked();
+    assert_eq!(
+        extension.detect_runnables_count(),
+        2,
+        "editing the buffer should detect its runnables again"
+    );
+}
+
 #[gpui::test]
 async fn test_folding_buffers(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
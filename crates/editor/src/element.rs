@@ -636,6 +636,17 @@ impl EditorElement {
         let mut click_count = event.click_count;
         let mut modifiers = event.modifiers;
 
+        if gutter_hitbox.is_hovered(window)
+            && editor.begin_execution_position_drag(
+                point_for_position.previous_valid.row(),
+                window,
+                cx,
+            )
+        {
+            cx.stop_propagation();
+            return;
+        }
+
         if let Some(hovered_hunk) = hovered_hunk {
             editor.toggle_single_diff_hunk(hovered_hunk, cx);
             cx.notify();
@@ -849,6 +860,12 @@ impl EditorElement {
         window: &mut Window,
         cx: &mut Context<Editor>,
     ) {
+        if editor.execution_position_drag.is_some() {
+            editor.end_execution_position_drag(window, cx);
+            cx.stop_propagation();
+            return;
+        }
+
         let text_hitbox = &position_map.text_hitbox;
         let end_selection = editor.has_pending_selection();
         let pending_nonempty_selections = editor.has_pending_nonempty_selection();
@@ -968,6 +985,15 @@ impl EditorElement {
         window: &mut Window,
         cx: &mut Context<Editor>,
     ) {
+        if editor.execution_position_drag.is_some() {
+            let row = position_map
+                .point_for_position(event.position)
+                .previous_valid
+                .row();
+            editor.update_execution_position_drag(row, window, cx);
+            return;
+        }
+
         if !editor.has_pending_selection()
             && matches!(editor.selection_drag_state, SelectionDragState::None)
         {
@@ -5421,6 +5447,9 @@ impl EditorElement {
 
         let line_height = layout.position_map.line_height;
         window.set_cursor_style(CursorStyle::Arrow, &layout.gutter_hitbox);
+        if self.editor.read(cx).execution_position_drag.is_some() {
+            window.set_window_cursor_style(CursorStyle::ClosedHand);
+        }
 
         for LineNumberLayout {
             shaped_line,
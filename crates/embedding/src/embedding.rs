@@ -1,17 +1,31 @@
 mod lmstudio;
+#[cfg(feature = "local-embeddings")]
+mod local;
 mod ollama;
 mod open_ai;
+mod remote;
+mod semantic_index_settings;
 
 pub use lmstudio::*;
+#[cfg(feature = "local-embeddings")]
+pub use local::*;
 pub use ollama::*;
 pub use open_ai::*;
+pub use remote::*;
+pub use semantic_index_settings::{LocalEmbeddingSettings, SemanticIndexSettings};
 use sha2::{Digest, Sha256};
 
 use anyhow::Result;
 use futures::{FutureExt, future::BoxFuture};
+use gpui::App;
 use serde::{Deserialize, Serialize};
+use settings::Settings as _;
 use std::{fmt, future};
 
+pub fn init(cx: &mut App) {
+    SemanticIndexSettings::register(cx);
+}
+
 /// Trait for embedding providers. Texts in, vectors out.
 pub trait EmbeddingProvider: Sync + Send {
     fn embed<'a>(&'a self, texts: &'a [TextToEmbed<'a>]) -> BoxFuture<'a, Result<Vec<Embedding>>>;
@@ -42,6 +56,10 @@ impl Embedding {
         self.0.len()
     }
 
+    pub fn values(&self) -> &[f32] {
+        &self.0
+    }
+
     pub fn similarity(&self, others: &[Embedding]) -> (f32, usize) {
         debug_assert!(others.iter().all(|other| self.0.len() == other.0.len()));
         others
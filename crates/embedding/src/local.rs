@@ -0,0 +1,297 @@
+use crate::{Embedding, EmbeddingProvider, LocalEmbeddingSettings, TextToEmbed};
+use anyhow::{Context as _, Result, anyhow};
+use futures::{FutureExt, future::BoxFuture};
+use ort::{
+    session::{Session, builder::GraphOptimizationLevel},
+    value::Tensor,
+};
+use parking_lot::Mutex;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokenizers::{PaddingParams, PaddingStrategy, Tokenizer, TruncationParams};
+
+/// Where to find a local sentence-embedding model, and how to run it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LocalEmbeddingOptions {
+    /// An ONNX export of a sentence-embedding model, such as `all-MiniLM-L6-v2` or
+    /// `bge-small-en-v1.5`.
+    pub model_path: PathBuf,
+    /// The model's `tokenizer.json`. Defaults to the one next to the model.
+    pub tokenizer_path: Option<PathBuf>,
+    /// How many CPU threads to run the model on. Defaults to all of them.
+    pub threads: Option<usize>,
+    /// The ONNX Runtime library to load. Defaults to the one in `ORT_DYLIB_PATH`, or else the one
+    /// on the library search path.
+    pub onnx_runtime_path: Option<PathBuf>,
+    /// How many texts to embed at once.
+    pub batch_size: usize,
+    /// Longer texts are truncated to this many tokens.
+    pub max_tokens: usize,
+}
+
+impl LocalEmbeddingOptions {
+    pub fn new(model_path: impl Into<PathBuf>) -> Self {
+        Self {
+            model_path: model_path.into(),
+            tokenizer_path: None,
+            threads: None,
+            onnx_runtime_path: None,
+            batch_size: 32,
+            max_tokens: 512,
+        }
+    }
+
+    pub fn from_settings(settings: &LocalEmbeddingSettings) -> Self {
+        Self {
+            tokenizer_path: settings.tokenizer_path.clone(),
+            threads: settings.threads,
+            onnx_runtime_path: settings.onnx_runtime_path.clone(),
+            ..Self::new(settings.model_path.clone())
+        }
+    }
+}
+
+/// Embeds text in-process, on the CPU, without any network access.
+pub struct LocalEmbeddingProvider {
+    model: Arc<LocalEmbeddingModel>,
+    batch_size: usize,
+}
+
+struct LocalEmbeddingModel {
+    session: Session,
+    tokenizer: Tokenizer,
+    /// BERT-style models take the segment of each token as an extra input.
+    takes_token_type_ids: bool,
+}
+
+impl LocalEmbeddingProvider {
+    /// Loads the model, which can take a moment, so it's best done on a background thread.
+    pub fn new(options: LocalEmbeddingOptions) -> Result<Self> {
+        let threads = match options.threads {
+            Some(threads) => threads,
+            None => std::thread::available_parallelism().map_or(1, |threads| threads.get()),
+        };
+        load_onnx_runtime(options.onnx_runtime_path.as_deref())?;
+        let session = Session::builder()?
+            .with_optimization_level(GraphOptimizationLevel::Level3)?
+            .with_intra_threads(threads)?
+            .commit_from_file(&options.model_path)
+            .with_context(|| format!("loading embedding model {}", options.model_path.display()))?;
+        let takes_token_type_ids = session
+            .inputs
+            .iter()
+            .any(|input| input.name == "token_type_ids");
+
+        let tokenizer_path = options
+            .tokenizer_path
+            .unwrap_or_else(|| default_tokenizer_path(&options.model_path));
+        let mut tokenizer = Tokenizer::from_file(&tokenizer_path)
+            .map_err(|error| anyhow!(error))
+            .with_context(|| format!("loading tokenizer {}", tokenizer_path.display()))?;
+        tokenizer.with_padding(Some(PaddingParams {
+            strategy: PaddingStrategy::BatchLongest,
+            ..Default::default()
+        }));
+        tokenizer
+            .with_truncation(Some(TruncationParams {
+                max_length: options.max_tokens,
+                ..Default::default()
+            }))
+            .map_err(|error| anyhow!(error))?;
+
+        Ok(Self {
+            model: Arc::new(LocalEmbeddingModel {
+                session,
+                tokenizer,
+                takes_token_type_ids,
+            }),
+            batch_size: options.batch_size.max(1),
+        })
+    }
+}
+
+/// The ONNX Runtime library that was loaded, or `Some(None)` for the default one. ONNX Runtime
+/// can only be loaded once per process, so later models have to use the same library.
+static LOADED_ONNX_RUNTIME: Mutex<Option<Option<PathBuf>>> = Mutex::new(None);
+
+fn load_onnx_runtime(path: Option<&Path>) -> Result<()> {
+    let mut loaded = LOADED_ONNX_RUNTIME.lock();
+    match loaded.as_ref() {
+        Some(loaded) => {
+            if let Some(path) = path.filter(|path| loaded.as_deref() != Some(*path)) {
+                let loaded = match loaded {
+                    Some(loaded) => loaded.display().to_string(),
+                    None => "the default library".to_string(),
+                };
+                anyhow::bail!(
+                    "can't load ONNX Runtime {} because {loaded} is already loaded; restart to switch",
+                    path.display(),
+                );
+            }
+        }
+        None => {
+            if let Some(path) = path {
+                ort::init_from(path.to_string_lossy())
+                    .commit()
+                    .with_context(|| format!("loading ONNX Runtime {}", path.display()))?;
+            }
+            *loaded = Some(path.map(Path::to_path_buf));
+        }
+    }
+    Ok(())
+}
+
+fn default_tokenizer_path(model_path: &Path) -> PathBuf {
+    model_path.with_file_name("tokenizer.json")
+}
+
+impl EmbeddingProvider for LocalEmbeddingProvider {
+    fn embed<'a>(&'a self, texts: &'a [TextToEmbed<'a>]) -> BoxFuture<'a, Result<Vec<Embedding>>> {
+        let model = self.model.clone();
+        let texts = texts
+            .iter()
+            .map(|to_embed| to_embed.text.to_string())
+            .collect::<Vec<_>>();
+        smol::unblock(move || model.embed(texts)).boxed()
+    }
+
+    fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+}
+
+impl LocalEmbeddingModel {
+    fn embed(&self, texts: Vec<String>) -> Result<Vec<Embedding>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let encodings = self
+            .tokenizer
+            .encode_batch(texts, true)
+            .map_err(|error| anyhow!(error))?;
+        let batch_size = encodings.len();
+        let sequence_length = encodings
+            .first()
+            .map_or(0, |encoding| encoding.get_ids().len());
+
+        let mut input_ids = Vec::with_capacity(batch_size * sequence_length);
+        let mut attention_mask = Vec::with_capacity(batch_size * sequence_length);
+        let mut token_type_ids = Vec::with_capacity(batch_size * sequence_length);
+        for encoding in &encodings {
+            input_ids.extend(encoding.get_ids().iter().map(|&id| id as i64));
+            attention_mask.extend(
+                encoding
+                    .get_attention_mask()
+                    .iter()
+                    .map(|&mask| mask as i64),
+            );
+            token_type_ids.extend(encoding.get_type_ids().iter().map(|&id| id as i64));
+        }
+
+        let shape = [batch_size, sequence_length];
+        let mut inputs = ort::inputs![
+            "input_ids" => Tensor::from_array((shape, input_ids))?,
+            "attention_mask" => Tensor::from_array((shape, attention_mask.clone()))?,
+        ]?;
+        if self.takes_token_type_ids {
+            inputs.push((
+                "token_type_ids".into(),
+                Tensor::from_array((shape, token_type_ids))?.into(),
+            ));
+        }
+
+        let outputs = self.session.run(inputs)?;
+        let (output_shape, output) = outputs[0].try_extract_raw_tensor::<f32>()?;
+        match output_shape.as_slice() {
+            // Already pooled, as in `sentence_embedding` outputs.
+            [rows, dimensions] if *rows as usize == batch_size => Ok(output
+                .chunks(*dimensions as usize)
+                .map(|embedding| Embedding::new(embedding.to_vec()))
+                .collect()),
+            // Per-token states, as in `last_hidden_state` outputs.
+            [rows, tokens, dimensions]
+                if *rows as usize == batch_size && *tokens as usize == sequence_length =>
+            {
+                Ok(mean_pool(
+                    output,
+                    &attention_mask,
+                    sequence_length,
+                    *dimensions as usize,
+                ))
+            }
+            _ => Err(anyhow!(
+                "unexpected embedding model output shape {output_shape:?}"
+            )),
+        }
+    }
+}
+
+/// Averages the token states of each text, leaving out padding.
+fn mean_pool(
+    states: &[f32],
+    attention_mask: &[i64],
+    sequence_length: usize,
+    dimensions: usize,
+) -> Vec<Embedding> {
+    states
+        .chunks(sequence_length * dimensions)
+        .zip(attention_mask.chunks(sequence_length))
+        .map(|(tokens, mask)| {
+            let mut sum = vec![0f32; dimensions];
+            let mut count = 0f32;
+            for (token, &mask) in tokens.chunks(dimensions).zip(mask) {
+                if mask == 0 {
+                    continue;
+                }
+                for (sum, value) in sum.iter_mut().zip(token) {
+                    *sum += value;
+                }
+                count += 1.;
+            }
+            for sum in &mut sum {
+                *sum /= count.max(1.);
+            }
+            Embedding::new(sum)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_onnx_runtime_is_loaded_once() {
+        // Using the default library doesn't load it until a model is.
+        load_onnx_runtime(None).unwrap();
+        load_onnx_runtime(None).unwrap();
+
+        let error = load_onnx_runtime(Some(Path::new("/opt/onnxruntime/libonnxruntime.so")))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.contains("the default library is already loaded"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn test_mean_pool_skips_padding() {
+        let states = [
+            // The first text has two tokens.
+            1., 0., //
+            3., 0., //
+            // The second text has one token, and padding.
+            0., 2., //
+            9., 9., //
+        ];
+        let attention_mask = [1, 1, 1, 0];
+        assert_eq!(
+            mean_pool(&states, &attention_mask, 2, 2),
+            vec![Embedding::new(vec![2., 0.]), Embedding::new(vec![0., 2.])]
+        );
+    }
+}
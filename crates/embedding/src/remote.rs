@@ -0,0 +1,46 @@
+use crate::{Embedding, EmbeddingProvider, TextToEmbed};
+use anyhow::Result;
+use futures::{FutureExt, future::BoxFuture};
+use rpc::{AnyProtoClient, proto};
+
+/// Embeds text on the host of a remote project, with the model configured there in
+/// `semantic_index.local_embeddings`.
+pub struct RemoteEmbeddingProvider {
+    client: AnyProtoClient,
+}
+
+impl RemoteEmbeddingProvider {
+    pub fn new(client: AnyProtoClient) -> Self {
+        Self { client }
+    }
+}
+
+impl EmbeddingProvider for RemoteEmbeddingProvider {
+    fn embed<'a>(&'a self, texts: &'a [TextToEmbed<'a>]) -> BoxFuture<'a, Result<Vec<Embedding>>> {
+        let request = self.client.request(proto::EmbedTexts {
+            texts: texts
+                .iter()
+                .map(|to_embed| to_embed.text.to_string())
+                .collect(),
+        });
+        async move {
+            let response = request.await?;
+            anyhow::ensure!(
+                response.embeddings.len() == texts.len(),
+                "the remote host returned {} embeddings for {} texts",
+                response.embeddings.len(),
+                texts.len()
+            );
+            Ok(response
+                .embeddings
+                .into_iter()
+                .map(|embedding| Embedding::new(embedding.values))
+                .collect())
+        }
+        .boxed()
+    }
+
+    fn batch_size(&self) -> usize {
+        32
+    }
+}
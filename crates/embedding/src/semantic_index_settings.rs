@@ -0,0 +1,52 @@
+use gpui::App;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+use std::path::PathBuf;
+
+/// The settings for semantic indexing.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SemanticIndexSettings {
+    /// A sentence-embedding model to run in-process, on the CPU, instead of calling an embedding
+    /// service. Only used by builds with local embeddings, such as the remote server, which then
+    /// embeds text for the projects opened on it.
+    ///
+    /// Example: { "model_path": "/opt/models/bge-small-en-v1.5/model.onnx", "threads": 4 }
+    ///
+    /// Default: null
+    #[serde(default)]
+    pub local_embeddings: Option<LocalEmbeddingSettings>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct LocalEmbeddingSettings {
+    /// An ONNX export of a sentence-embedding model, such as `all-MiniLM-L6-v2`.
+    pub model_path: PathBuf,
+    /// The model's `tokenizer.json`. Defaults to the one next to the model.
+    #[serde(default)]
+    pub tokenizer_path: Option<PathBuf>,
+    /// How many CPU threads to run the model on. Defaults to all of them.
+    #[serde(default)]
+    pub threads: Option<usize>,
+    /// The ONNX Runtime library to run the model with, such as `/usr/lib/libonnxruntime.so`.
+    /// Defaults to the one in `ORT_DYLIB_PATH`, or else the one on the library search path.
+    #[serde(default)]
+    pub onnx_runtime_path: Option<PathBuf>,
+}
+
+impl Settings for SemanticIndexSettings {
+    const KEY: Option<&'static str> = Some("semantic_index");
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut App) -> anyhow::Result<Self> {
+        SettingsSources::<Self::FileContent>::json_merge_with(
+            [sources.default]
+                .into_iter()
+                .chain(sources.user)
+                .chain(sources.server),
+        )
+    }
+
+    fn import_from_vscode(_vscode: &settings::VsCodeSettings, _current: &mut Self::FileContent) {}
+}
@@ -21,7 +21,10 @@ use gpui::http_client::read_proxy_from_env;
 use gpui::{App, AppContext, Application, AsyncApp, Entity, UpdateGlobal};
 use gpui_tokio::Tokio;
 use language::LanguageRegistry;
-use language_model::{ConfiguredModel, LanguageModel, LanguageModelRegistry, SelectedModel};
+use language_model::{
+    ConfiguredModel, LanguageModel, LanguageModelRegistry, RequestCache, RequestCacheMode,
+    SelectedModel,
+};
 use node_runtime::{NodeBinaryOptions, NodeRuntime};
 use project::Project;
 use project::project_settings::ProjectSettings;
@@ -61,6 +64,14 @@ struct Args {
     /// Maximum number of examples to run concurrently.
     #[arg(long, default_value = "4")]
     concurrency: usize,
+    /// Directory to record language model completions to, or replay them from.
+    #[arg(long)]
+    llm_cache: Option<PathBuf>,
+    /// How to use `--llm-cache`: `read_through` replays recorded completions and records missing
+    /// ones, `record` re-records every completion, and `replay` runs offline, failing on any
+    /// completion that wasn't recorded.
+    #[arg(long, default_value = "read_through")]
+    llm_cache_mode: RequestCacheMode,
 }
 
 fn main() {
@@ -126,6 +137,14 @@ fn main() {
 
         let mut cumulative_tool_metrics = ToolMetrics::default();
 
+        let replaying = args.llm_cache.is_some() && args.llm_cache_mode == RequestCacheMode::Replay;
+        if let Some(dir) = args.llm_cache.clone() {
+            println!("Using recorded completions in {}", dir.display());
+            LanguageModelRegistry::global(cx).update(cx, |registry, _| {
+                registry.set_request_cache(Some(RequestCache::new(dir, args.llm_cache_mode)));
+            });
+        }
+
         let agent_model = load_model(&args.model, cx).unwrap();
         let judge_model = load_model(&args.judge_model, cx).unwrap();
 
@@ -137,8 +156,11 @@ fn main() {
         let auth2 = judge_model.provider.authenticate(cx);
 
         cx.spawn(async move |cx| {
-            auth1.await?;
-            auth2.await?;
+            // Replaying doesn't need credentials, as it doesn't reach the providers.
+            if !replaying {
+                auth1.await?;
+                auth2.await?;
+            }
 
             let mut examples = Vec::new();
 
@@ -6,10 +6,13 @@ pub use download_file_capability::*;
 pub use npm_install_package_capability::*;
 pub use process_exec_capability::*;
 
+use std::fmt;
+
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// A capability for an extension.
-#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "kind", rename_all = "snake_case")]
 pub enum ExtensionCapability {
     #[serde(rename = "process:exec")]
@@ -18,3 +21,26 @@ pub enum ExtensionCapability {
     #[serde(rename = "npm:install")]
     NpmInstallPackage(NpmInstallPackageCapability),
 }
+
+impl fmt::Display for ExtensionCapability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ProcessExec(capability) => {
+                write!(f, "process:exec {}", capability.command)?;
+                for arg in &capability.args {
+                    write!(f, " {arg}")?;
+                }
+                Ok(())
+            }
+            Self::DownloadFile(capability) => {
+                write!(
+                    f,
+                    "download_file {}/{}",
+                    capability.host,
+                    capability.path.join("/")
+                )
+            }
+            Self::NpmInstallPackage(capability) => write!(f, "npm:install {}", capability.package),
+        }
+    }
+}
@@ -3,8 +3,11 @@ pub mod extension_builder;
 mod extension_events;
 mod extension_host_proxy;
 mod extension_manifest;
+#[cfg(any(test, feature = "test-support"))]
+mod fake_extension;
 mod types;
 
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
@@ -21,6 +24,8 @@ pub use crate::capabilities::*;
 pub use crate::extension_events::*;
 pub use crate::extension_host_proxy::*;
 pub use crate::extension_manifest::*;
+#[cfg(any(test, feature = "test-support"))]
+pub use crate::fake_extension::*;
 pub use crate::types::*;
 
 /// Initializes the `extension` crate.
@@ -167,6 +172,71 @@ pub trait Extension: Send + Sync + 'static {
         locator_name: String,
         config: SpawnInTerminal,
     ) -> Result<DebugRequest>;
+
+    async fn format_buffer(
+        &self,
+        formatter_id: Arc<str>,
+        buffer: FormattingBuffer,
+        range: Option<Range<usize>>,
+        worktree: Arc<dyn WorktreeDelegate>,
+    ) -> Result<Vec<TextEdit>>;
+
+    async fn code_actions(
+        &self,
+        provider_id: Arc<str>,
+        buffer: FormattingBuffer,
+        range: Range<usize>,
+        diagnostics: Vec<Diagnostic>,
+        worktree: Arc<dyn WorktreeDelegate>,
+    ) -> Result<Vec<CodeAction>>;
+
+    async fn task_templates(
+        &self,
+        provider_id: Arc<str>,
+        worktree: Arc<dyn WorktreeDelegate>,
+    ) -> Result<Vec<TaskDefinition>>;
+
+    async fn task_variables(
+        &self,
+        provider_id: Arc<str>,
+        location: TaskLocation,
+        worktree: Arc<dyn WorktreeDelegate>,
+    ) -> Result<EnvVars>;
+
+    async fn detect_runnables(
+        &self,
+        provider_id: Arc<str>,
+        buffer: FormattingBuffer,
+        worktree: Arc<dyn WorktreeDelegate>,
+    ) -> Result<Vec<Runnable>>;
+
+    async fn llm_provider_models(
+        &self,
+        provider_id: Arc<str>,
+        credential: Option<String>,
+    ) -> Result<Vec<LlmModelInfo>>;
+
+    async fn llm_stream_completion_start(
+        &self,
+        provider_id: Arc<str>,
+        model_id: Arc<str>,
+        request: LlmCompletionRequest,
+        credential: Option<String>,
+    ) -> Result<String>;
+
+    async fn llm_stream_completion_next(
+        &self,
+        stream_id: String,
+    ) -> Result<Option<LlmCompletionEvent>>;
+
+    async fn llm_stream_completion_close(&self, stream_id: String) -> Result<()>;
+
+    async fn llm_count_tokens(
+        &self,
+        provider_id: Arc<str>,
+        model_id: Arc<str>,
+        request: LlmCompletionRequest,
+    ) -> Result<u64>;
 }
 
 pub fn parse_wasm_extension_version(
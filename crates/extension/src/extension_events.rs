@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use gpui::{App, AppContext as _, Context, Entity, EventEmitter, Global};
 
-use crate::ExtensionManifest;
+use crate::{ExtensionCapability, ExtensionManifest};
 
 pub fn init(cx: &mut App) {
     let extension_events = cx.new(ExtensionEvents::new);
@@ -36,9 +36,15 @@ impl ExtensionEvents {
 #[derive(Clone)]
 pub enum Event {
     ExtensionInstalled(Arc<ExtensionManifest>),
+    ExtensionUpgraded(Arc<ExtensionManifest>),
     ExtensionUninstalled(Arc<ExtensionManifest>),
     ExtensionsInstalledChanged,
     ConfigureExtensionRequested(Arc<ExtensionManifest>),
+    /// An extension attempted to use a capability that was not granted to it.
+    CapabilityDenied {
+        manifest: Arc<ExtensionManifest>,
+        capability: ExtensionCapability,
+    },
 }
 
 impl EventEmitter<Event> for ExtensionEvents {}
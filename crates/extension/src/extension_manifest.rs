@@ -91,6 +91,14 @@ pub struct ExtensionManifest {
     pub debug_adapters: BTreeMap<Arc<str>, DebugAdapterManifestEntry>,
     #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     pub debug_locators: BTreeMap<Arc<str>, DebugLocatorManifestEntry>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub formatters: BTreeMap<Arc<str>, FormatterManifestEntry>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub code_action_providers: BTreeMap<Arc<str>, CodeActionProviderManifestEntry>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub task_providers: BTreeMap<Arc<str>, TaskProviderManifestEntry>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub language_model_providers: BTreeMap<Arc<str>, LanguageModelProviderManifestEntry>,
 }
 
 impl ExtensionManifest {
@@ -201,6 +209,38 @@ pub struct DebugAdapterManifestEntry {
 #[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
 pub struct DebugLocatorManifestEntry {}
 
+#[derive(Clone, Default, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct FormatterManifestEntry {
+    /// The list of languages this formatter can format.
+    #[serde(default)]
+    pub languages: Vec<LanguageName>,
+}
+
+#[derive(Clone, Default, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct CodeActionProviderManifestEntry {
+    /// The list of languages this provider offers code actions for.
+    #[serde(default)]
+    pub languages: Vec<LanguageName>,
+}
+
+#[derive(Clone, Default, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct TaskProviderManifestEntry {
+    /// The list of languages this provider contributes tasks for.
+    #[serde(default)]
+    pub languages: Vec<LanguageName>,
+}
+
+#[derive(Clone, Default, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct LanguageModelProviderManifestEntry {
+    /// The name of the provider, as shown in the UI.
+    pub name: String,
+    /// Whether the user needs to enter an API key to use the provider.
+    ///
+    /// The API key is stored in the system keychain and passed to the extension with each request.
+    #[serde(default)]
+    pub requires_api_key: bool,
+}
+
 impl ExtensionManifest {
     pub async fn load(fs: Arc<dyn Fs>, extension_dir: &Path) -> Result<Self> {
         let extension_name = extension_dir
@@ -270,6 +310,10 @@ fn manifest_from_old_manifest(
         capabilities: Vec::new(),
         debug_adapters: Default::default(),
         debug_locators: Default::default(),
+        formatters: Default::default(),
+        code_action_providers: Default::default(),
+        task_providers: Default::default(),
+        language_model_providers: Default::default(),
     }
 }
 
@@ -302,6 +346,10 @@ mod tests {
             capabilities: vec![],
             debug_adapters: Default::default(),
             debug_locators: Default::default(),
+            formatters: Default::default(),
+            code_action_providers: Default::default(),
+            task_providers: Default::default(),
+            language_model_providers: Default::default(),
         }
     }
 
@@ -0,0 +1,353 @@
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use ::lsp::LanguageServerName;
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use language::LanguageName;
+use task::{SpawnInTerminal, ZedDebugConfig};
+
+use crate::*;
+
+type FormatFn = dyn Fn(&FormattingBuffer) -> Vec<TextEdit> + Send + Sync;
+type CodeActionsFn = dyn Fn(&FormattingBuffer) -> Vec<CodeAction> + Send + Sync;
+type RunnablesFn = dyn Fn(&FormattingBuffer) -> Vec<Runnable> + Send + Sync;
+
+/// An [`Extension`] for tests, which formats buffers, offers code actions and detects runnables
+/// with the given functions.
+///
+/// The other methods return an error.
+pub struct FakeExtension {
+    manifest: Arc<ExtensionManifest>,
+    format: Option<Box<FormatFn>>,
+    code_actions: Option<Box<CodeActionsFn>>,
+    runnables: Option<Box<RunnablesFn>>,
+    format_count: AtomicUsize,
+    detect_runnables_count: AtomicUsize,
+}
+
+impl FakeExtension {
+    pub fn new(manifest: ExtensionManifest) -> Self {
+        Self {
+            manifest: Arc::new(manifest),
+            format: None,
+            code_actions: None,
+            runnables: None,
+            format_count: AtomicUsize::new(0),
+            detect_runnables_count: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns a manifest with the given id and nothing else in it.
+    pub fn empty_manifest(id: &str) -> ExtensionManifest {
+        ExtensionManifest {
+            id: id.into(),
+            name: id.to_string(),
+            version: "0.1.0".into(),
+            schema_version: SchemaVersion::ZERO,
+            description: None,
+            repository: None,
+            authors: Vec::new(),
+            lib: Default::default(),
+            themes: Vec::new(),
+            icon_themes: Vec::new(),
+            languages: Vec::new(),
+            grammars: Default::default(),
+            language_servers: Default::default(),
+            context_servers: Default::default(),
+            slash_commands: Default::default(),
+            snippets: None,
+            capabilities: Vec::new(),
+            debug_adapters: Default::default(),
+            debug_locators: Default::default(),
+            formatters: Default::default(),
+            code_action_providers: Default::default(),
+            task_providers: Default::default(),
+            language_model_providers: Default::default(),
+        }
+    }
+
+    pub fn with_formatter(
+        mut self,
+        format: impl Fn(&FormattingBuffer) -> Vec<TextEdit> + Send + Sync + 'static,
+    ) -> Self {
+        self.format = Some(Box::new(format));
+        self
+    }
+
+    pub fn with_code_actions(
+        mut self,
+        code_actions: impl Fn(&FormattingBuffer) -> Vec<CodeAction> + Send + Sync + 'static,
+    ) -> Self {
+        self.code_actions = Some(Box::new(code_actions));
+        self
+    }
+
+    pub fn with_runnables(
+        mut self,
+        runnables: impl Fn(&FormattingBuffer) -> Vec<Runnable> + Send + Sync + 'static,
+    ) -> Self {
+        self.runnables = Some(Box::new(runnables));
+        self
+    }
+
+    /// How many times [`Extension::format_buffer`] was called.
+    pub fn format_count(&self) -> usize {
+        self.format_count.load(Ordering::SeqCst)
+    }
+
+    /// How many times [`Extension::detect_runnables`] was called.
+    pub fn detect_runnables_count(&self) -> usize {
+        self.detect_runnables_count.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl Extension for FakeExtension {
+    fn manifest(&self) -> Arc<ExtensionManifest> {
+        self.manifest.clone()
+    }
+
+    fn work_dir(&self) -> Arc<Path> {
+        Path::new("/extensions/work")
+            .join(self.manifest.id.as_ref())
+            .into()
+    }
+
+    async fn language_server_command(
+        &self,
+        _: LanguageServerName,
+        _: LanguageName,
+        _: Arc<dyn WorktreeDelegate>,
+    ) -> Result<Command> {
+        bail!("FakeExtension does not support `language_server_command`")
+    }
+
+    async fn language_server_initialization_options(
+        &self,
+        _: LanguageServerName,
+        _: LanguageName,
+        _: Arc<dyn WorktreeDelegate>,
+    ) -> Result<Option<String>> {
+        bail!("FakeExtension does not support `language_server_initialization_options`")
+    }
+
+    async fn language_server_workspace_configuration(
+        &self,
+        _: LanguageServerName,
+        _: Arc<dyn WorktreeDelegate>,
+    ) -> Result<Option<String>> {
+        bail!("FakeExtension does not support `language_server_workspace_configuration`")
+    }
+
+    async fn language_server_additional_initialization_options(
+        &self,
+        _: LanguageServerName,
+        _: LanguageServerName,
+        _: Arc<dyn WorktreeDelegate>,
+    ) -> Result<Option<String>> {
+        bail!("FakeExtension does not support `language_server_additional_initialization_options`")
+    }
+
+    async fn language_server_additional_workspace_configuration(
+        &self,
+        _: LanguageServerName,
+        _: LanguageServerName,
+        _: Arc<dyn WorktreeDelegate>,
+    ) -> Result<Option<String>> {
+        bail!("FakeExtension does not support `language_server_additional_workspace_configuration`")
+    }
+
+    async fn labels_for_completions(
+        &self,
+        _: LanguageServerName,
+        _: Vec<Completion>,
+    ) -> Result<Vec<Option<CodeLabel>>> {
+        bail!("FakeExtension does not support `labels_for_completions`")
+    }
+
+    async fn labels_for_symbols(
+        &self,
+        _: LanguageServerName,
+        _: Vec<Symbol>,
+    ) -> Result<Vec<Option<CodeLabel>>> {
+        bail!("FakeExtension does not support `labels_for_symbols`")
+    }
+
+    async fn complete_slash_command_argument(
+        &self,
+        _: SlashCommand,
+        _: Vec<String>,
+    ) -> Result<Vec<SlashCommandArgumentCompletion>> {
+        bail!("FakeExtension does not support `complete_slash_command_argument`")
+    }
+
+    async fn run_slash_command(
+        &self,
+        _: SlashCommand,
+        _: Vec<String>,
+        _: Option<Arc<dyn WorktreeDelegate>>,
+    ) -> Result<SlashCommandOutput> {
+        bail!("FakeExtension does not support `run_slash_command`")
+    }
+
+    async fn context_server_command(
+        &self,
+        _: Arc<str>,
+        _: Arc<dyn ProjectDelegate>,
+    ) -> Result<Command> {
+        bail!("FakeExtension does not support `context_server_command`")
+    }
+
+    async fn context_server_configuration(
+        &self,
+        _: Arc<str>,
+        _: Arc<dyn ProjectDelegate>,
+    ) -> Result<Option<ContextServerConfiguration>> {
+        bail!("FakeExtension does not support `context_server_configuration`")
+    }
+
+    async fn suggest_docs_packages(&self, _: Arc<str>) -> Result<Vec<String>> {
+        bail!("FakeExtension does not support `suggest_docs_packages`")
+    }
+
+    async fn index_docs(
+        &self,
+        _: Arc<str>,
+        _: Arc<str>,
+        _: Arc<dyn KeyValueStoreDelegate>,
+    ) -> Result<()> {
+        bail!("FakeExtension does not support `index_docs`")
+    }
+
+    async fn get_dap_binary(
+        &self,
+        _: Arc<str>,
+        _: DebugTaskDefinition,
+        _: Option<PathBuf>,
+        _: Arc<dyn WorktreeDelegate>,
+    ) -> Result<DebugAdapterBinary> {
+        bail!("FakeExtension does not support `get_dap_binary`")
+    }
+
+    async fn dap_request_kind(
+        &self,
+        _: Arc<str>,
+        _: serde_json::Value,
+    ) -> Result<StartDebuggingRequestArgumentsRequest> {
+        bail!("FakeExtension does not support `dap_request_kind`")
+    }
+
+    async fn dap_config_to_scenario(&self, _: ZedDebugConfig) -> Result<DebugScenario> {
+        bail!("FakeExtension does not support `dap_config_to_scenario`")
+    }
+
+    async fn dap_locator_create_scenario(
+        &self,
+        _: String,
+        _: BuildTaskTemplate,
+        _: String,
+        _: String,
+    ) -> Result<Option<DebugScenario>> {
+        bail!("FakeExtension does not support `dap_locator_create_scenario`")
+    }
+
+    async fn run_dap_locator(&self, _: String, _: SpawnInTerminal) -> Result<DebugRequest> {
+        bail!("FakeExtension does not support `run_dap_locator`")
+    }
+
+    async fn format_buffer(
+        &self,
+        _: Arc<str>,
+        buffer: FormattingBuffer,
+        _: Option<Range<usize>>,
+        _: Arc<dyn WorktreeDelegate>,
+    ) -> Result<Vec<TextEdit>> {
+        self.format_count.fetch_add(1, Ordering::SeqCst);
+        Ok(self
+            .format
+            .as_ref()
+            .map_or_else(Vec::new, |format| format(&buffer)))
+    }
+
+    async fn code_actions(
+        &self,
+        _: Arc<str>,
+        buffer: FormattingBuffer,
+        _: Range<usize>,
+        _: Vec<Diagnostic>,
+        _: Arc<dyn WorktreeDelegate>,
+    ) -> Result<Vec<CodeAction>> {
+        Ok(self
+            .code_actions
+            .as_ref()
+            .map_or_else(Vec::new, |code_actions| code_actions(&buffer)))
+    }
+
+    async fn task_templates(
+        &self,
+        _: Arc<str>,
+        _: Arc<dyn WorktreeDelegate>,
+    ) -> Result<Vec<TaskDefinition>> {
+        Ok(Vec::new())
+    }
+
+    async fn task_variables(
+        &self,
+        _: Arc<str>,
+        _: TaskLocation,
+        _: Arc<dyn WorktreeDelegate>,
+    ) -> Result<EnvVars> {
+        Ok(Default::default())
+    }
+
+    async fn detect_runnables(
+        &self,
+        _: Arc<str>,
+        buffer: FormattingBuffer,
+        _: Arc<dyn WorktreeDelegate>,
+    ) -> Result<Vec<Runnable>> {
+        self.detect_runnables_count.fetch_add(1, Ordering::SeqCst);
+        Ok(self
+            .runnables
+            .as_ref()
+            .map_or_else(Vec::new, |runnables| runnables(&buffer)))
+    }
+
+    async fn llm_provider_models(
+        &self,
+        _: Arc<str>,
+        _: Option<String>,
+    ) -> Result<Vec<LlmModelInfo>> {
+        bail!("FakeExtension does not support `llm_provider_models`")
+    }
+
+    async fn llm_stream_completion_start(
+        &self,
+        _: Arc<str>,
+        _: Arc<str>,
+        _: LlmCompletionRequest,
+        _: Option<String>,
+    ) -> Result<String> {
+        bail!("FakeExtension does not support `llm_stream_completion_start`")
+    }
+
+    async fn llm_stream_completion_next(&self, _: String) -> Result<Option<LlmCompletionEvent>> {
+        bail!("FakeExtension does not support `llm_stream_completion_next`")
+    }
+
+    async fn llm_stream_completion_close(&self, _: String) -> Result<()> {
+        bail!("FakeExtension does not support `llm_stream_completion_close`")
+    }
+
+    async fn llm_count_tokens(
+        &self,
+        _: Arc<str>,
+        _: Arc<str>,
+        _: LlmCompletionRequest,
+    ) -> Result<u64> {
+        bail!("FakeExtension does not support `llm_count_tokens`")
+    }
+}
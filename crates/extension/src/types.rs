@@ -1,7 +1,10 @@
 mod context_server;
 mod dap;
+mod formatting;
+mod llm;
 mod lsp;
 mod slash_command;
+mod tasks;
 
 use std::{ops::Range, path::PathBuf};
 
@@ -9,8 +12,11 @@ use util::redact::should_redact;
 
 pub use context_server::*;
 pub use dap::*;
+pub use formatting::*;
+pub use llm::*;
 pub use lsp::*;
 pub use slash_command::*;
+pub use tasks::*;
 
 /// A list of environment variables.
 pub type EnvVars = Vec<(String, String)>;
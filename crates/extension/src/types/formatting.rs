@@ -0,0 +1,55 @@
+use std::ops::Range;
+
+/// A buffer that is being formatted or that code actions are requested for.
+#[derive(Debug, Clone)]
+pub struct FormattingBuffer {
+    /// The path to the buffer's file, relative to the worktree root.
+    pub path: String,
+    /// The name of the buffer's language.
+    pub language_name: String,
+    /// The full text of the buffer.
+    pub text: String,
+    /// The number of columns that make up an indentation level.
+    pub tab_size: u32,
+    /// Whether indentation uses spaces rather than tabs.
+    pub insert_spaces: bool,
+}
+
+/// A replacement of a range of a buffer's text, in byte offsets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    /// The range of text to replace.
+    pub range: Range<usize>,
+    /// The text to insert in place of the range.
+    pub new_text: String,
+}
+
+/// The severity of a diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+/// A diagnostic reported for a buffer.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    /// The range of the buffer the diagnostic applies to, in byte offsets.
+    pub range: Range<usize>,
+    pub severity: DiagnosticSeverity,
+    pub message: String,
+    pub source: Option<String>,
+    pub code: Option<String>,
+}
+
+/// A code action offered by an extension.
+#[derive(Debug, Clone)]
+pub struct CodeAction {
+    pub title: String,
+    /// The kind of the code action, such as `quickfix` or `refactor.extract`.
+    pub kind: Option<String>,
+    /// The edits to apply to the buffer when the code action is chosen.
+    pub edits: Vec<TextEdit>,
+}
@@ -0,0 +1,141 @@
+/// A language model offered by a provider.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LlmModelInfo {
+    /// The ID of the model, as sent to the provider.
+    pub id: String,
+    /// The name of the model, as shown in the UI.
+    pub name: String,
+    pub max_token_count: u64,
+    pub max_output_tokens: Option<u64>,
+    pub supports_tools: bool,
+    pub supports_images: bool,
+}
+
+/// The role of a message author.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LlmMessageRole {
+    User,
+    Assistant,
+    System,
+}
+
+/// An image in a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LlmImage {
+    /// The base64-encoded PNG image.
+    pub source: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// The reasoning of a model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LlmThinking {
+    pub text: String,
+    pub signature: Option<String>,
+}
+
+/// A tool call made by a model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LlmToolUse {
+    pub id: String,
+    pub name: String,
+    /// The JSON-encoded input to the tool.
+    pub input: String,
+}
+
+/// The content of a tool result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LlmToolResultContent {
+    Text(String),
+    Image(LlmImage),
+}
+
+/// The result of a tool call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LlmToolResult {
+    pub tool_use_id: String,
+    pub tool_name: String,
+    pub is_error: bool,
+    pub content: LlmToolResultContent,
+}
+
+/// A part of a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LlmMessageContent {
+    Text(String),
+    Thinking(LlmThinking),
+    RedactedThinking(String),
+    Image(LlmImage),
+    ToolUse(LlmToolUse),
+    ToolResult(LlmToolResult),
+}
+
+/// A message in a completion request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LlmRequestMessage {
+    pub role: LlmMessageRole,
+    pub content: Vec<LlmMessageContent>,
+    /// Whether the provider should cache the conversation up to and including this message.
+    pub cache: bool,
+}
+
+/// A tool that the model may call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LlmToolDefinition {
+    pub name: String,
+    pub description: String,
+    /// The JSON schema of the tool's input.
+    pub input_schema: String,
+}
+
+/// How the model should choose the tools to call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LlmToolChoice {
+    Auto,
+    Any,
+    None,
+}
+
+/// A request for a completion.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LlmCompletionRequest {
+    pub thread_id: Option<String>,
+    pub prompt_id: Option<String>,
+    pub messages: Vec<LlmRequestMessage>,
+    pub tools: Vec<LlmToolDefinition>,
+    pub tool_choice: Option<LlmToolChoice>,
+    pub stop: Vec<String>,
+    pub temperature: Option<f32>,
+    pub thinking_allowed: bool,
+}
+
+/// The reason a model stopped generating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LlmStopReason {
+    EndTurn,
+    MaxTokens,
+    ToolUse,
+    Refusal,
+}
+
+/// The number of tokens used by a completion.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LlmTokenUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_input_tokens: u64,
+    pub cache_read_input_tokens: u64,
+}
+
+/// An event in a streamed completion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LlmCompletionEvent {
+    StartMessage(String),
+    Text(String),
+    Thinking(LlmThinking),
+    RedactedThinking(String),
+    ToolUse(LlmToolUse),
+    Stop(LlmStopReason),
+    Usage(LlmTokenUsage),
+}
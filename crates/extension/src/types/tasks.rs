@@ -0,0 +1,41 @@
+use std::ops::Range;
+
+use crate::EnvVars;
+
+/// A task that an extension contributes to the task inventory.
+#[derive(Debug, Clone)]
+pub struct TaskDefinition {
+    /// Human readable name of the task to display in the UI.
+    pub label: String,
+    /// Executable command to spawn.
+    pub command: String,
+    pub args: Vec<String>,
+    pub env: EnvVars,
+    pub cwd: Option<String>,
+    /// The tags of the runnables that this task applies to.
+    pub tags: Vec<String>,
+}
+
+/// A location in a buffer for which task variables are requested.
+#[derive(Debug, Clone)]
+pub struct TaskLocation {
+    /// The path to the buffer's file, relative to the worktree root.
+    pub path: String,
+    /// The name of the buffer's language.
+    pub language_name: String,
+    pub row: u32,
+    pub column: u32,
+    /// The name of the symbol that contains the location, if any.
+    pub symbol: Option<String>,
+}
+
+/// A range of a buffer that can be run with the tasks matching its tags.
+#[derive(Debug, Clone)]
+pub struct Runnable {
+    /// The range of the buffer's text that the runnable applies to, in byte offsets.
+    pub range: Range<usize>,
+    /// The tags used to select the tasks for this runnable.
+    pub tags: Vec<String>,
+    /// Additional task variables for this runnable.
+    pub captures: EnvVars,
+}
@@ -25,10 +25,18 @@ pub use wit::{
         LaunchRequest, StartDebuggingRequestArguments, StartDebuggingRequestArgumentsRequest,
         TaskTemplate, TcpArguments, TcpArgumentsTemplate, resolve_tcp_template,
     },
+    zed::extension::formatting::{
+        CodeAction, Diagnostic, DiagnosticSeverity, FormattingBuffer, TextEdit,
+    },
     zed::extension::github::{
         GithubRelease, GithubReleaseAsset, GithubReleaseOptions, github_release_by_tag_name,
         latest_github_release,
     },
+    zed::extension::llm_provider::{
+        LlmCompletionEvent, LlmCompletionRequest, LlmImage, LlmMessageContent, LlmMessageRole,
+        LlmModelInfo, LlmRequestMessage, LlmStopReason, LlmThinking, LlmTokenUsage, LlmToolChoice,
+        LlmToolDefinition, LlmToolResult, LlmToolResultContent, LlmToolUse,
+    },
     zed::extension::nodejs::{
         node_binary_path, npm_install_package, npm_package_installed_version,
         npm_package_latest_version,
@@ -37,6 +45,7 @@ pub use wit::{
     zed::extension::slash_command::{
         SlashCommand, SlashCommandArgumentCompletion, SlashCommandOutput, SlashCommandOutputSection,
     },
+    zed::extension::tasks::{Runnable, TaskDefinition, TaskLocation},
 };
 
 // Undocumented WIT re-exports.
@@ -259,6 +268,108 @@ pub trait Extension: Send + Sync {
     ) -> Result<DebugRequest, String> {
         Err("`run_dap_locator` not implemented".to_string())
     }
+
+    /// Returns the edits that format the given buffer with the specified formatter.
+    ///
+    /// When a range is provided, only that range of the buffer should be formatted.
+    fn format_buffer(
+        &mut self,
+        _formatter_id: String,
+        _buffer: FormattingBuffer,
+        _range: Option<Range>,
+        _worktree: &Worktree,
+    ) -> Result<Vec<TextEdit>, String> {
+        Err("`format_buffer` not implemented".to_string())
+    }
+
+    /// Returns the code actions that the specified provider offers for the given range of a buffer.
+    fn code_actions(
+        &mut self,
+        _provider_id: String,
+        _buffer: FormattingBuffer,
+        _range: Range,
+        _diagnostics: Vec<Diagnostic>,
+        _worktree: &Worktree,
+    ) -> Result<Vec<CodeAction>, String> {
+        Ok(Vec::new())
+    }
+
+    /// Returns the task templates that the specified task provider contributes.
+    fn task_templates(
+        &mut self,
+        _provider_id: String,
+        _worktree: &Worktree,
+    ) -> Result<Vec<TaskDefinition>, String> {
+        Ok(Vec::new())
+    }
+
+    /// Returns the task variables that the specified task provider supplies for the given location.
+    ///
+    /// Each variable is available to tasks as `$ZED_CUSTOM_<name>`.
+    fn task_variables(
+        &mut self,
+        _provider_id: String,
+        _location: TaskLocation,
+        _worktree: &Worktree,
+    ) -> Result<EnvVars, String> {
+        Ok(Vec::new())
+    }
+
+    /// Returns the runnables that the specified task provider detects in the given buffer.
+    fn detect_runnables(
+        &mut self,
+        _provider_id: String,
+        _buffer: FormattingBuffer,
+        _worktree: &Worktree,
+    ) -> Result<Vec<Runnable>, String> {
+        Ok(Vec::new())
+    }
+
+    /// Returns the models offered by the specified language model provider.
+    ///
+    /// The credential is the API key the user entered for the provider, if any.
+    fn llm_provider_models(
+        &mut self,
+        _provider_id: String,
+        _credential: Option<String>,
+    ) -> Result<Vec<LlmModelInfo>, String> {
+        Ok(Vec::new())
+    }
+
+    /// Starts streaming a completion from the specified model.
+    ///
+    /// Returns an ID for the stream, which Zed passes to [`Extension::llm_stream_completion_next`]
+    /// to receive the events of the completion.
+    fn llm_stream_completion_start(
+        &mut self,
+        _provider_id: String,
+        _model_id: String,
+        _request: LlmCompletionRequest,
+        _credential: Option<String>,
+    ) -> Result<String, String> {
+        Err("`llm_stream_completion_start` not implemented".to_string())
+    }
+
+    /// Returns the next event of the specified completion stream, or `None` when the stream has ended.
+    fn llm_stream_completion_next(
+        &mut self,
+        _stream_id: String,
+    ) -> Result<Option<LlmCompletionEvent>, String> {
+        Ok(None)
+    }
+
+    /// Closes the specified completion stream, releasing any resources associated with it.
+    fn llm_stream_completion_close(&mut self, _stream_id: String) {}
+
+    /// Returns the number of tokens the request uses with the specified model.
+    fn llm_count_tokens(
+        &mut self,
+        _provider_id: String,
+        _model_id: String,
+        _request: LlmCompletionRequest,
+    ) -> Result<u64, String> {
+        Err("`llm_count_tokens` not implemented".to_string())
+    }
 }
 
 /// Registers the provided type as a Zed extension.
@@ -300,7 +411,7 @@ mod wit {
 
     wit_bindgen::generate!({
         skip: ["init-extension"],
-        path: "./wit/since_v0.6.0",
+        path: "./wit/since_v0.7.0",
     });
 }
 
@@ -484,6 +595,80 @@ impl wit::Guest for Component {
     ) -> Result<DebugRequest, String> {
         extension().run_dap_locator(locator_name, build_task)
     }
+
+    fn format_buffer(
+        formatter_id: String,
+        buffer: FormattingBuffer,
+        range: Option<Range>,
+        worktree: &Worktree,
+    ) -> Result<Vec<TextEdit>, String> {
+        extension().format_buffer(formatter_id, buffer, range, worktree)
+    }
+
+    fn code_actions(
+        provider_id: String,
+        buffer: FormattingBuffer,
+        range: Range,
+        diagnostics: Vec<Diagnostic>,
+        worktree: &Worktree,
+    ) -> Result<Vec<CodeAction>, String> {
+        extension().code_actions(provider_id, buffer, range, diagnostics, worktree)
+    }
+
+    fn task_templates(
+        provider_id: String,
+        worktree: &Worktree,
+    ) -> Result<Vec<TaskDefinition>, String> {
+        extension().task_templates(provider_id, worktree)
+    }
+
+    fn task_variables(
+        provider_id: String,
+        location: TaskLocation,
+        worktree: &Worktree,
+    ) -> Result<EnvVars, String> {
+        extension().task_variables(provider_id, location, worktree)
+    }
+
+    fn detect_runnables(
+        provider_id: String,
+        buffer: FormattingBuffer,
+        worktree: &Worktree,
+    ) -> Result<Vec<Runnable>, String> {
+        extension().detect_runnables(provider_id, buffer, worktree)
+    }
+
+    fn llm_provider_models(
+        provider_id: String,
+        credential: Option<String>,
+    ) -> Result<Vec<LlmModelInfo>, String> {
+        extension().llm_provider_models(provider_id, credential)
+    }
+
+    fn llm_stream_completion_start(
+        provider_id: String,
+        model_id: String,
+        request: LlmCompletionRequest,
+        credential: Option<String>,
+    ) -> Result<String, String> {
+        extension().llm_stream_completion_start(provider_id, model_id, request, credential)
+    }
+
+    fn llm_stream_completion_next(stream_id: String) -> Result<Option<LlmCompletionEvent>, String> {
+        extension().llm_stream_completion_next(stream_id)
+    }
+
+    fn llm_stream_completion_close(stream_id: String) {
+        extension().llm_stream_completion_close(stream_id)
+    }
+
+    fn llm_count_tokens(
+        provider_id: String,
+        model_id: String,
+        request: LlmCompletionRequest,
+    ) -> Result<u64, String> {
+        extension().llm_count_tokens(provider_id, model_id, request)
+    }
 }
 
 /// The ID of a language server.
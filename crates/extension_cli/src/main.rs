@@ -7,12 +7,14 @@ use std::sync::Arc;
 
 use ::fs::{CopyOptions, Fs, RealFs, copy_recursive};
 use anyhow::{Context as _, Result, bail};
+use chrono::Utc;
 use clap::Parser;
 use extension::ExtensionManifest;
 use extension::extension_builder::{CompileExtensionOptions, ExtensionBuilder};
 use language::LanguageConfig;
 use reqwest_client::ReqwestClient;
 use rpc::ExtensionProvides;
+use sha2::{Digest as _, Sha256};
 use tree_sitter::{Language, Query, WasmStore};
 
 #[derive(Parser, Debug)]
@@ -27,6 +29,12 @@ struct Args {
     /// The path to a directory where build dependencies are downloaded
     #[arg(long)]
     scratch_dir: PathBuf,
+    /// The path to a static extension registry to publish the packaged extension to.
+    ///
+    /// The registry can be used as a `directory` extension registry, or served over HTTP
+    /// as an `http` extension registry.
+    #[arg(long)]
+    registry_dir: Option<PathBuf>,
 }
 
 #[tokio::main]
@@ -99,7 +107,8 @@ async fn main() -> Result<()> {
 
     let extension_provides = extension_provides(&manifest);
 
-    let manifest_json = serde_json::to_string(&rpc::ExtensionApiManifest {
+    let extension_id = manifest.id.clone();
+    let api_manifest = rpc::ExtensionApiManifest {
         name: manifest.name,
         version: manifest.version,
         description: manifest.description,
@@ -110,10 +119,68 @@ async fn main() -> Result<()> {
             .context("missing repository in extension manifest")?,
         wasm_api_version: manifest.lib.version.map(|version| version.to_string()),
         provides: extension_provides,
-    })?;
+    };
+    let manifest_json = serde_json::to_string(&api_manifest)?;
     fs::remove_dir_all(&archive_dir)?;
     fs::write(output_dir.join("manifest.json"), manifest_json.as_bytes())?;
 
+    if let Some(registry_dir) = args.registry_dir {
+        publish_to_registry(
+            &registry_dir,
+            extension_id,
+            api_manifest,
+            &output_dir.join("archive.tar.gz"),
+        )
+        .context("failed to publish extension to registry")?;
+    }
+
+    Ok(())
+}
+
+/// Adds the packaged extension to a static extension registry, replacing any previously
+/// published archive of the same version.
+fn publish_to_registry(
+    registry_dir: &Path,
+    extension_id: Arc<str>,
+    manifest: rpc::ExtensionApiManifest,
+    archive_path: &Path,
+) -> Result<()> {
+    let archive = format!("{extension_id}/{}.tar.gz", manifest.version);
+    let archive_bytes = fs::read(archive_path)?;
+    let sha256 = hex::encode(Sha256::digest(&archive_bytes));
+
+    fs::create_dir_all(registry_dir.join(extension_id.as_ref()))?;
+    fs::write(registry_dir.join(&archive), &archive_bytes)?;
+
+    let index_path = registry_dir.join("index.json");
+    let mut index = if index_path.exists() {
+        serde_json::from_slice::<rpc::ExtensionRegistryIndex>(&fs::read(&index_path)?)
+            .with_context(|| format!("invalid registry index {}", index_path.display()))?
+    } else {
+        rpc::ExtensionRegistryIndex::default()
+    };
+    index.extensions.retain(|entry| {
+        entry.metadata.id != extension_id || entry.metadata.manifest.version != manifest.version
+    });
+    index.extensions.push(rpc::ExtensionRegistryEntry {
+        metadata: rpc::ExtensionMetadata {
+            id: extension_id,
+            manifest,
+            published_at: Utc::now(),
+            download_count: 0,
+        },
+        archive,
+        sha256,
+    });
+    index.extensions.sort_by(|a, b| {
+        a.metadata
+            .id
+            .cmp(&b.metadata.id)
+            .then_with(|| a.metadata.published_at.cmp(&b.metadata.published_at))
+    });
+    fs::write(&index_path, serde_json::to_vec_pretty(&index)?)?;
+
+    log::info!("published extension to registry {}", registry_dir.display());
     Ok(())
 }
 
@@ -1,52 +1,195 @@
+use std::collections::VecDeque;
 use std::sync::Arc;
+use std::time::SystemTime;
 
-use anyhow::{Result, bail};
-use extension::{ExtensionCapability, ExtensionManifest};
+use anyhow::{Result, anyhow, bail};
+use extension::{
+    DownloadFileCapability, ExtensionCapability, ExtensionManifest, NpmInstallPackageCapability,
+    ProcessExecCapability,
+};
+use futures::channel::mpsc::{UnboundedReceiver, UnboundedSender, unbounded};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
 use url::Url;
 
+/// The maximum number of entries kept in the [`CapabilityAuditLog`].
+const MAX_AUDIT_LOG_ENTRIES: usize = 1000;
+
+/// A use of a capability by an extension, or an attempt to use one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CapabilityAuditEntry {
+    pub timestamp: SystemTime,
+    pub extension_id: Arc<str>,
+    /// The capability that the extension used, narrowed to the exact request.
+    pub capability: ExtensionCapability,
+    pub granted: bool,
+}
+
+/// An entry that was just recorded in the [`CapabilityAuditLog`], along with the manifest of the
+/// extension that it belongs to.
+pub struct RecordedCapabilityUse {
+    pub manifest: Arc<ExtensionManifest>,
+    pub entry: CapabilityAuditEntry,
+}
+
+/// A log of the capabilities used by extensions, keeping the most recent entries.
+pub struct CapabilityAuditLog {
+    entries: Mutex<VecDeque<CapabilityAuditEntry>>,
+    recorded_tx: UnboundedSender<RecordedCapabilityUse>,
+}
+
+impl CapabilityAuditLog {
+    /// Returns a new [`CapabilityAuditLog`], along with a receiver of the entries as they are
+    /// recorded.
+    pub fn new() -> (Arc<Self>, UnboundedReceiver<RecordedCapabilityUse>) {
+        let (recorded_tx, recorded_rx) = unbounded();
+        let this = Arc::new(Self {
+            entries: Mutex::default(),
+            recorded_tx,
+        });
+        (this, recorded_rx)
+    }
+
+    /// Returns the recorded entries, oldest first.
+    pub fn entries(&self) -> Vec<CapabilityAuditEntry> {
+        self.entries.lock().iter().cloned().collect()
+    }
+
+    /// Serializes the entries as JSON lines, oldest first.
+    pub fn to_json_lines(&self) -> String {
+        let mut json_lines = String::new();
+        for entry in self.entries.lock().iter() {
+            if let Ok(line) = serde_json::to_string(entry) {
+                json_lines.push_str(&line);
+                json_lines.push('\n');
+            }
+        }
+        json_lines
+    }
+
+    /// Restores the entries persisted by [`Self::to_json_lines`] in an earlier session, placing
+    /// them before the entries recorded since. Lines that cannot be parsed are skipped.
+    pub fn restore(&self, json_lines: &str) {
+        let persisted = json_lines
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| serde_json::from_str::<CapabilityAuditEntry>(line).ok());
+
+        let mut entries = self.entries.lock();
+        let recorded = std::mem::take(&mut *entries);
+        entries.extend(persisted);
+        entries.extend(recorded);
+        let excess = entries.len().saturating_sub(MAX_AUDIT_LOG_ENTRIES);
+        entries.drain(..excess);
+    }
+
+    fn record(
+        &self,
+        manifest: &Arc<ExtensionManifest>,
+        capability: ExtensionCapability,
+        granted: bool,
+    ) {
+        if granted {
+            log::info!("extension {} used capability {capability}", manifest.id);
+        } else {
+            log::warn!(
+                "extension {} was denied capability {capability}",
+                manifest.id
+            );
+        }
+
+        let entry = CapabilityAuditEntry {
+            timestamp: SystemTime::now(),
+            extension_id: manifest.id.clone(),
+            capability,
+            granted,
+        };
+        self.recorded_tx
+            .unbounded_send(RecordedCapabilityUse {
+                manifest: manifest.clone(),
+                entry: entry.clone(),
+            })
+            .ok();
+
+        let mut entries = self.entries.lock();
+        if entries.len() == MAX_AUDIT_LOG_ENTRIES {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+}
+
 pub struct CapabilityGranter {
     granted_capabilities: Vec<ExtensionCapability>,
     manifest: Arc<ExtensionManifest>,
+    audit_log: Arc<CapabilityAuditLog>,
 }
 
 impl CapabilityGranter {
     pub fn new(
         granted_capabilities: Vec<ExtensionCapability>,
         manifest: Arc<ExtensionManifest>,
+        audit_log: Arc<CapabilityAuditLog>,
     ) -> Self {
         Self {
             granted_capabilities,
             manifest,
+            audit_log,
         }
     }
 
+    /// Records the use of the given capability in the audit log.
+    fn audit(&self, capability: ExtensionCapability, result: Result<()>) -> Result<()> {
+        self.audit_log
+            .record(&self.manifest, capability, result.is_ok());
+        result
+    }
+
     pub fn grant_exec(
         &self,
         desired_command: &str,
         desired_args: &[impl AsRef<str> + std::fmt::Debug],
     ) -> Result<()> {
-        self.manifest.allow_exec(desired_command, desired_args)?;
+        let capability = ExtensionCapability::ProcessExec(ProcessExecCapability {
+            command: desired_command.to_string(),
+            args: desired_args
+                .iter()
+                .map(|arg| arg.as_ref().to_string())
+                .collect(),
+        });
+        let result = self
+            .manifest
+            .allow_exec(desired_command, desired_args)
+            .and_then(|()| {
+                let is_allowed =
+                    self.granted_capabilities
+                        .iter()
+                        .any(|capability| match capability {
+                            ExtensionCapability::ProcessExec(capability) => {
+                                capability.allows(desired_command, desired_args)
+                            }
+                            _ => false,
+                        });
 
-        let is_allowed = self
-            .granted_capabilities
-            .iter()
-            .any(|capability| match capability {
-                ExtensionCapability::ProcessExec(capability) => {
-                    capability.allows(desired_command, desired_args)
+                if !is_allowed {
+                    bail!(
+                        "capability for process:exec {desired_command} {desired_args:?} is not granted by the extension host",
+                    );
                 }
-                _ => false,
-            });
 
-        if !is_allowed {
-            bail!(
-                "capability for process:exec {desired_command} {desired_args:?} is not granted by the extension host",
-            );
-        }
-
-        Ok(())
+                Ok(())
+            });
+        self.audit(capability, result)
     }
 
     pub fn grant_download_file(&self, desired_url: &Url) -> Result<()> {
+        let capability = ExtensionCapability::DownloadFile(DownloadFileCapability {
+            host: desired_url.host_str().unwrap_or_default().to_string(),
+            path: desired_url
+                .path_segments()
+                .map(|segments| segments.map(ToOwned::to_owned).collect())
+                .unwrap_or_default(),
+        });
         let is_allowed = self
             .granted_capabilities
             .iter()
@@ -55,16 +198,20 @@ impl CapabilityGranter {
                 _ => false,
             });
 
-        if !is_allowed {
-            bail!(
+        let result = if is_allowed {
+            Ok(())
+        } else {
+            Err(anyhow!(
                 "capability for download_file {desired_url} is not granted by the extension host",
-            );
-        }
-
-        Ok(())
+            ))
+        };
+        self.audit(capability, result)
     }
 
     pub fn grant_npm_install_package(&self, package_name: &str) -> Result<()> {
+        let capability = ExtensionCapability::NpmInstallPackage(NpmInstallPackageCapability {
+            package: package_name.to_string(),
+        });
         let is_allowed = self
             .granted_capabilities
             .iter()
@@ -75,11 +222,14 @@ impl CapabilityGranter {
                 _ => false,
             });
 
-        if !is_allowed {
-            bail!("capability for npm:install {package_name} is not granted by the extension host",);
-        }
-
-        Ok(())
+        let result = if is_allowed {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "capability for npm:install {package_name} is not granted by the extension host",
+            ))
+        };
+        self.audit(capability, result)
     }
 }
 
@@ -112,6 +262,10 @@ mod tests {
             capabilities: vec![],
             debug_adapters: Default::default(),
             debug_locators: Default::default(),
+            formatters: Default::default(),
+            code_action_providers: Default::default(),
+            task_providers: Default::default(),
+            language_model_providers: Default::default(),
         }
     }
 
@@ -125,8 +279,10 @@ mod tests {
             ..extension_manifest()
         });
 
+        let (audit_log, _recorded) = CapabilityAuditLog::new();
+
         // It returns an error when the extension host has no granted capabilities.
-        let granter = CapabilityGranter::new(Vec::new(), manifest.clone());
+        let granter = CapabilityGranter::new(Vec::new(), manifest.clone(), audit_log.clone());
         assert!(granter.grant_exec("ls", &["-la"]).is_err());
 
         // It succeeds when the extension host has the exact capability.
@@ -136,6 +292,7 @@ mod tests {
                 args: vec!["-la".to_string()],
             })],
             manifest.clone(),
+            audit_log.clone(),
         );
         assert!(granter.grant_exec("ls", &["-la"]).is_ok());
 
@@ -146,7 +303,71 @@ mod tests {
                 args: vec!["**".to_string()],
             })],
             manifest.clone(),
+            audit_log.clone(),
         );
         assert!(granter.grant_exec("ls", &["-la"]).is_ok());
     }
+
+    #[test]
+    fn test_audit_log() {
+        let manifest = Arc::new(ExtensionManifest {
+            capabilities: vec![ExtensionCapability::ProcessExec(ProcessExecCapability {
+                command: "clang-format".to_string(),
+                args: vec!["**".to_string()],
+            })],
+            ..extension_manifest()
+        });
+        let (audit_log, mut recorded) = CapabilityAuditLog::new();
+        let granter = CapabilityGranter::new(
+            vec![ExtensionCapability::ProcessExec(ProcessExecCapability {
+                command: "clang-format".to_string(),
+                args: vec!["**".to_string()],
+            })],
+            manifest.clone(),
+            audit_log.clone(),
+        );
+
+        assert!(granter.grant_exec("clang-format", &["--version"]).is_ok());
+        assert!(granter.grant_npm_install_package("prettier").is_err());
+
+        let entries = audit_log.entries();
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (entry.capability.to_string(), entry.granted))
+                .collect::<Vec<_>>(),
+            [
+                ("process:exec clang-format --version".to_string(), true),
+                ("npm:install prettier".to_string(), false),
+            ]
+        );
+
+        let recorded = std::iter::from_fn(|| recorded.try_next().ok().flatten())
+            .map(|recorded| recorded.entry)
+            .collect::<Vec<_>>();
+        assert_eq!(recorded, entries);
+        assert!(
+            entries
+                .iter()
+                .all(|entry| entry.extension_id.as_ref() == "test")
+        );
+
+        // The persisted entries are restored before the ones recorded in this session.
+        let (restored_log, _recorded) = CapabilityAuditLog::new();
+        let granter = CapabilityGranter::new(Vec::new(), manifest, restored_log.clone());
+        assert!(granter.grant_npm_install_package("eslint").is_err());
+        restored_log.restore(&format!("not json\n{}", audit_log.to_json_lines()));
+        assert_eq!(
+            restored_log
+                .entries()
+                .iter()
+                .map(|entry| entry.capability.to_string())
+                .collect::<Vec<_>>(),
+            [
+                "process:exec clang-format --version",
+                "npm:install prettier",
+                "npm:install eslint",
+            ]
+        );
+    }
 }
@@ -1,4 +1,5 @@
 mod capability_granter;
+pub mod extension_registry;
 pub mod extension_settings;
 pub mod headless_host;
 pub mod wasm_host;
@@ -10,16 +11,21 @@ use anyhow::{Context as _, Result, anyhow, bail};
 use async_compression::futures::bufread::GzipDecoder;
 use async_tar::Archive;
 use client::ExtensionProvides;
-use client::{Client, ExtensionMetadata, GetExtensionsResponse, proto, telemetry::Telemetry};
+use client::{
+    Client, ExtensionMetadata, ExtensionRegistryIndex, GetExtensionsResponse, proto,
+    telemetry::Telemetry,
+};
 use collections::{BTreeMap, BTreeSet, HashMap, HashSet, btree_map};
 pub use extension::ExtensionManifest;
 use extension::extension_builder::{CompileExtensionOptions, ExtensionBuilder};
 use extension::{
-    ExtensionContextServerProxy, ExtensionDebugAdapterProviderProxy, ExtensionEvents,
-    ExtensionGrammarProxy, ExtensionHostProxy, ExtensionLanguageProxy,
-    ExtensionLanguageServerProxy, ExtensionSlashCommandProxy, ExtensionSnippetProxy,
+    ExtensionCapability, ExtensionContextServerProxy, ExtensionDebugAdapterProviderProxy,
+    ExtensionEvents, ExtensionFormattingProxy, ExtensionGrammarProxy, ExtensionHostProxy,
+    ExtensionLanguageModelProviderProxy, ExtensionLanguageProxy, ExtensionLanguageServerProxy,
+    ExtensionSlashCommandProxy, ExtensionSnippetProxy, ExtensionTaskProviderProxy,
     ExtensionThemeProxy,
 };
+use extension_registry::{ExtensionRegistry, merge_extensions, read_url, verify_checksum};
 use fs::{Fs, RemoveOptions};
 use futures::future::join_all;
 use futures::{
@@ -46,7 +52,7 @@ use release_channel::ReleaseChannel;
 use remote::SshRemoteClient;
 use semantic_version::SemanticVersion;
 use serde::{Deserialize, Serialize};
-use settings::Settings;
+use settings::{Settings, SettingsStore};
 use std::ops::RangeInclusive;
 use std::str::FromStr;
 use std::{
@@ -62,6 +68,7 @@ use wasm_host::{
     wit::{is_supported_wasm_api_version, wasm_api_version_range},
 };
 
+pub use capability_granter::{CapabilityAuditEntry, CapabilityAuditLog};
 pub use extension::{
     ExtensionLibraryKind, GrammarManifestEntry, OldExtensionManifest, SchemaVersion,
 };
@@ -120,6 +127,19 @@ pub struct ExtensionStore {
     pub tasks: Vec<Task<()>>,
     pub ssh_clients: HashMap<String, WeakEntity<SshRemoteClient>>,
     pub ssh_registered_tx: UnboundedSender<()>,
+    capability_approver: Option<CapabilityApprover>,
+}
+
+/// Asks the user whether to install an extension that requests the given capabilities, resolving
+/// to `true` if they agree.
+pub type CapabilityApprover =
+    Arc<dyn Fn(Arc<ExtensionManifest>, Vec<ExtensionCapability>, &mut App) -> Task<bool>>;
+
+/// Where to download an extension archive from.
+struct ExtensionDownload {
+    url: Url,
+    /// The expected SHA-256 checksum of the archive, if the registry publishes one.
+    sha256: Option<String>,
 }
 
 #[derive(Clone, Copy)]
@@ -273,8 +293,26 @@ impl ExtensionStore {
 
             ssh_clients: HashMap::default(),
             ssh_registered_tx: connection_registered_tx,
+            capability_approver: None,
         };
 
+        this.wasm_host.set_capability_overrides(
+            ExtensionSettings::get_global(cx)
+                .extension_capabilities
+                .clone(),
+        );
+        cx.observe_global::<SettingsStore>(|this, cx| {
+            let overrides = ExtensionSettings::get_global(cx)
+                .extension_capabilities
+                .clone();
+            for extension_id in this.wasm_host.set_capability_overrides(overrides) {
+                if this.extension_index.extensions.contains_key(&extension_id) {
+                    drop(this.reload(Some(extension_id), cx));
+                }
+            }
+        })
+        .detach();
+
         // The extensions store maintains an index file, which contains a complete
         // list of the installed extensions and the resources that they provide.
         // This index is loaded synchronously on startup.
@@ -426,6 +464,39 @@ impl ExtensionStore {
         self.installed_dir.clone()
     }
 
+    /// Returns the log of the capabilities used by extensions.
+    pub fn capability_audit_log(&self) -> &Arc<CapabilityAuditLog> {
+        &self.wasm_host.capability_audit_log
+    }
+
+    /// Sets the approver asked before installing or upgrading an extension that requests
+    /// capabilities. Without one, such extensions are installed without asking.
+    pub fn set_capability_approver(&mut self, approver: CapabilityApprover) {
+        self.capability_approver = Some(approver);
+    }
+
+    /// Asks the capability approver whether to install the given extension, if it requests
+    /// capabilities that the installed version of the extension did not.
+    fn approve_capabilities(&self, manifest: Arc<ExtensionManifest>, cx: &mut App) -> Task<bool> {
+        let Some(approver) = self.capability_approver.clone() else {
+            return Task::ready(true);
+        };
+        let installed_capabilities = self
+            .extension_manifest_for_id(&manifest.id)
+            .map(|installed| installed.capabilities.clone())
+            .unwrap_or_default();
+        let new_capabilities = manifest
+            .capabilities
+            .iter()
+            .filter(|capability| !installed_capabilities.contains(capability))
+            .cloned()
+            .collect::<Vec<_>>();
+        if new_capabilities.is_empty() {
+            return Task::ready(true);
+        }
+        approver(manifest, new_capabilities, cx)
+    }
+
     pub fn outstanding_operations(&self) -> &BTreeMap<Arc<str>, ExtensionOperation> {
         &self.outstanding_operations
     }
@@ -517,6 +588,7 @@ impl ExtensionStore {
             query.push(("filter", search));
         }
 
+        let provides = provides_filter.cloned();
         let provides_filter = provides_filter.map(|provides_filter| {
             provides_filter
                 .iter()
@@ -528,7 +600,24 @@ impl ExtensionStore {
             query.push(("provides", provides_filter));
         }
 
-        self.fetch_extensions_from_api("/extensions", &query, cx)
+        let search = search.map(ToOwned::to_owned);
+        let task = self.fetch_extensions_from_registries(
+            |this, cx| this.fetch_extensions_from_api("/extensions", &query, cx),
+            move |index, release_channel| {
+                extension_registry::search(
+                    index,
+                    search.as_deref(),
+                    provides.as_ref(),
+                    release_channel,
+                )
+            },
+            cx,
+        );
+        cx.background_spawn(async move {
+            Ok(merge_extensions(task.await?, |extension| {
+                extension.id.clone()
+            }))
+        })
     }
 
     pub fn fetch_extensions_with_update_available(
@@ -543,25 +632,37 @@ impl ExtensionStore {
             .extensions
             .iter()
             .filter(|(id, entry)| !entry.dev && extension_settings.should_auto_update(id))
-            .map(|(id, _)| id.as_ref())
+            .map(|(id, _)| id.clone())
+            .collect::<HashSet<_>>();
+        let extension_ids_query = extension_ids
+            .iter()
+            .map(|id| id.as_ref())
             .collect::<Vec<_>>()
             .join(",");
-        let task = self.fetch_extensions_from_api(
-            "/extensions/updates",
-            &[
-                ("min_schema_version", &schema_versions.start().to_string()),
-                ("max_schema_version", &schema_versions.end().to_string()),
-                (
-                    "min_wasm_api_version",
-                    &wasm_api_versions.start().to_string(),
-                ),
-                ("max_wasm_api_version", &wasm_api_versions.end().to_string()),
-                ("ids", &extension_ids),
-            ],
+        let task = self.fetch_extensions_from_registries(
+            |this, cx| {
+                this.fetch_extensions_from_api(
+                    "/extensions/updates",
+                    &[
+                        ("min_schema_version", &schema_versions.start().to_string()),
+                        ("max_schema_version", &schema_versions.end().to_string()),
+                        (
+                            "min_wasm_api_version",
+                            &wasm_api_versions.start().to_string(),
+                        ),
+                        ("max_wasm_api_version", &wasm_api_versions.end().to_string()),
+                        ("ids", &extension_ids_query),
+                    ],
+                    cx,
+                )
+            },
+            move |index, release_channel| {
+                extension_registry::latest_versions(index, &extension_ids, release_channel)
+            },
             cx,
         );
         cx.spawn(async move |this, cx| {
-            let extensions = task.await?;
+            let extensions = merge_extensions(task.await?, |extension| extension.id.clone());
             this.update(cx, |this, _cx| {
                 extensions
                     .into_iter()
@@ -583,7 +684,18 @@ impl ExtensionStore {
         extension_id: &str,
         cx: &mut Context<Self>,
     ) -> Task<Result<Vec<ExtensionMetadata>>> {
-        self.fetch_extensions_from_api(&format!("/extensions/{extension_id}"), &[], cx)
+        let path = format!("/extensions/{extension_id}");
+        let extension_id = extension_id.to_string();
+        let task = self.fetch_extensions_from_registries(
+            |this, cx| this.fetch_extensions_from_api(&path, &[], cx),
+            move |index, _| extension_registry::versions(index, &extension_id),
+            cx,
+        );
+        cx.background_spawn(async move {
+            Ok(merge_extensions(task.await?, |extension| {
+                extension.manifest.version.clone()
+            }))
+        })
     }
 
     /// Installs any extensions that should be included with Zed by default.
@@ -654,6 +766,131 @@ impl ExtensionStore {
         anyhow::Ok(())
     }
 
+    fn registries(cx: &App) -> Vec<ExtensionRegistry> {
+        ExtensionSettings::get_global(cx)
+            .registries()
+            .iter()
+            .filter_map(|settings| ExtensionRegistry::from_settings(settings).log_err())
+            .collect()
+    }
+
+    /// Fetches extensions from each of the configured registries, in order of precedence.
+    ///
+    /// Registries that fail to respond are skipped, unless all of them do.
+    fn fetch_extensions_from_registries(
+        &self,
+        fetch_from_api: impl Fn(&Self, &mut Context<Self>) -> Task<Result<Vec<ExtensionMetadata>>>,
+        query_index: impl Fn(&ExtensionRegistryIndex, ReleaseChannel) -> Vec<ExtensionMetadata>
+        + Clone
+        + Send
+        + 'static,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<Vec<ExtensionMetadata>>>> {
+        let release_channel = ReleaseChannel::global(cx);
+        let tasks = Self::registries(cx)
+            .into_iter()
+            .map(|registry| match registry {
+                ExtensionRegistry::Zed => fetch_from_api(self, cx),
+                ExtensionRegistry::Static(registry) => {
+                    let fs = self.fs.clone();
+                    let http_client = self.http_client.clone();
+                    let query_index = query_index.clone();
+                    cx.background_spawn(async move {
+                        let index = registry
+                            .load_index(fs.as_ref(), http_client.as_ref())
+                            .await?;
+                        Ok(query_index(&index, release_channel))
+                    })
+                }
+            })
+            .collect::<Vec<_>>();
+
+        cx.background_spawn(async move {
+            let mut extensions = Vec::new();
+            let mut last_error = None;
+            for result in join_all(tasks).await {
+                match result {
+                    Ok(registry_extensions) => extensions.push(registry_extensions),
+                    Err(error) => {
+                        log::error!("failed to fetch extensions from registry: {error:#}");
+                        last_error = Some(error);
+                    }
+                }
+            }
+            match last_error {
+                Some(error) if extensions.is_empty() => Err(error),
+                _ => Ok(extensions),
+            }
+        })
+    }
+
+    /// Resolves where to download the given version of an extension from, or its latest version
+    /// if no version is given, from the first registry that has it.
+    fn resolve_extension_download(
+        &self,
+        extension_id: Arc<str>,
+        version: Option<Arc<str>>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<ExtensionDownload>> {
+        let release_channel = ReleaseChannel::global(cx);
+        let schema_versions = schema_version_range();
+        let wasm_api_versions = wasm_api_version_range(release_channel);
+        let zed_api_url = match &version {
+            Some(version) => self.http_client.build_zed_api_url(
+                &format!("/extensions/{extension_id}/{version}/download"),
+                &[],
+            ),
+            None => self.http_client.build_zed_api_url(
+                &format!("/extensions/{extension_id}/download"),
+                &[
+                    ("min_schema_version", &schema_versions.start().to_string()),
+                    ("max_schema_version", &schema_versions.end().to_string()),
+                    (
+                        "min_wasm_api_version",
+                        &wasm_api_versions.start().to_string(),
+                    ),
+                    ("max_wasm_api_version", &wasm_api_versions.end().to_string()),
+                ],
+            ),
+        };
+        let registries = Self::registries(cx);
+        let fs = self.fs.clone();
+        let http_client = self.http_client.clone();
+        cx.background_spawn(async move {
+            for registry in registries {
+                match registry {
+                    ExtensionRegistry::Zed => {
+                        return Ok(ExtensionDownload {
+                            url: zed_api_url?,
+                            sha256: None,
+                        });
+                    }
+                    ExtensionRegistry::Static(registry) => {
+                        let Some(index) = registry
+                            .load_index(fs.as_ref(), http_client.as_ref())
+                            .await
+                            .log_err()
+                        else {
+                            continue;
+                        };
+                        if let Some(entry) = extension_registry::find_entry(
+                            &index,
+                            &extension_id,
+                            version.as_deref(),
+                            release_channel,
+                        ) {
+                            return Ok(ExtensionDownload {
+                                url: registry.archive_url(entry)?,
+                                sha256: Some(entry.sha256.clone()),
+                            });
+                        }
+                    }
+                }
+            }
+            bail!("extension {extension_id} was not found in any extension registry")
+        })
+    }
+
     fn fetch_extensions_from_api(
         &self,
         path: &str,
@@ -700,11 +937,17 @@ impl ExtensionStore {
     fn install_or_upgrade_extension_at_endpoint(
         &mut self,
         extension_id: Arc<str>,
-        url: Url,
+        download: ExtensionDownload,
         operation: ExtensionOperation,
         cx: &mut Context<Self>,
     ) -> Task<Result<()>> {
         let extension_dir = self.installed_dir.join(extension_id.as_ref());
+        // The archive is unpacked outside of the installed directory, so that the extension is
+        // not loaded before the user approves the capabilities it requests.
+        let staging_dir = self
+            .installed_dir
+            .with_file_name("staging")
+            .join(extension_id.as_ref());
         let http_client = self.http_client.clone();
         let fs = self.fs.clone();
 
@@ -723,38 +966,73 @@ impl ExtensionStore {
                 }
             });
 
-            let mut response = http_client
-                .get(url.as_ref(), Default::default(), true)
-                .await
-                .context("downloading extension")?;
-
-            fs.remove_dir(
-                &extension_dir,
-                RemoveOptions {
-                    recursive: true,
-                    ignore_if_not_exists: true,
-                },
-            )
-            .await?;
+            let tar_gz_bytes = if download.url.scheme() == "file" {
+                read_url(&download.url, fs.as_ref(), http_client.as_ref())
+                    .await
+                    .context("reading extension archive")?
+            } else {
+                let mut response = http_client
+                    .get(download.url.as_ref(), Default::default(), true)
+                    .await
+                    .context("downloading extension")?;
 
-            let content_length = response
-                .headers()
-                .get(http_client::http::header::CONTENT_LENGTH)
-                .and_then(|value| value.to_str().ok()?.parse::<usize>().ok());
+                let content_length = response
+                    .headers()
+                    .get(http_client::http::header::CONTENT_LENGTH)
+                    .and_then(|value| value.to_str().ok()?.parse::<usize>().ok());
 
-            let mut body = BufReader::new(response.body_mut());
-            let mut tar_gz_bytes = Vec::new();
-            body.read_to_end(&mut tar_gz_bytes).await?;
+                let mut body = BufReader::new(response.body_mut());
+                let mut tar_gz_bytes = Vec::new();
+                body.read_to_end(&mut tar_gz_bytes).await?;
 
-            if let Some(content_length) = content_length {
-                let actual_len = tar_gz_bytes.len();
-                if content_length != actual_len {
-                    bail!("downloaded extension size {actual_len} does not match content length {content_length}");
+                if let Some(content_length) = content_length {
+                    let actual_len = tar_gz_bytes.len();
+                    if content_length != actual_len {
+                        bail!("downloaded extension size {actual_len} does not match content length {content_length}");
+                    }
                 }
+                tar_gz_bytes
+            };
+
+            if let Some(sha256) = &download.sha256 {
+                verify_checksum(&tar_gz_bytes, sha256)
+                    .with_context(|| format!("verifying archive of extension {extension_id}"))?;
             }
+
+            let remove_options = RemoveOptions {
+                recursive: true,
+                ignore_if_not_exists: true,
+            };
+            fs.remove_dir(&staging_dir, remove_options).await?;
             let decompressed_bytes = GzipDecoder::new(BufReader::new(tar_gz_bytes.as_slice()));
             let archive = Archive::new(decompressed_bytes);
-            archive.unpack(extension_dir).await?;
+            archive.unpack(&staging_dir).await?;
+
+            let manifest = match ExtensionManifest::load(fs.clone(), &staging_dir).await {
+                Ok(manifest) => Arc::new(manifest),
+                Err(error) => {
+                    fs.remove_dir(&staging_dir, remove_options).await.log_err();
+                    return Err(
+                        error.context(format!("loading manifest of extension {extension_id}"))
+                    );
+                }
+            };
+            let approved = this
+                .update(cx, |this, cx| this.approve_capabilities(manifest, cx))?
+                .await;
+            if !approved {
+                fs.remove_dir(&staging_dir, remove_options).await.log_err();
+                log::info!("installation of extension {extension_id} was declined");
+                return Ok(());
+            }
+
+            fs.remove_dir(&extension_dir, remove_options).await?;
+            if let Some(installed_dir) = extension_dir.parent() {
+                fs.create_dir(installed_dir).await?;
+            }
+            fs.rename(&staging_dir, &extension_dir, Default::default())
+                .await
+                .with_context(|| format!("moving extension {extension_id} into place"))?;
             this.update( cx, |this, cx| {
                 this.reload(Some(extension_id.clone()), cx)
             })?
@@ -775,6 +1053,20 @@ impl ExtensionStore {
                     }
                 })
                 .ok();
+            } else if let ExtensionOperation::Upgrade = operation {
+                this.update( cx, |this, cx| {
+                    if let Some(events) = ExtensionEvents::try_global(cx) {
+                        if let Some(manifest) = this.extension_manifest_for_id(&extension_id) {
+                            events.update(cx, |this, cx| {
+                                this.emit(
+                                    extension::Event::ExtensionUpgraded(manifest.clone()),
+                                    cx,
+                                )
+                            });
+                        }
+                    }
+                })
+                .ok();
             }
 
             anyhow::Ok(())
@@ -783,32 +1075,9 @@ impl ExtensionStore {
 
     pub fn install_latest_extension(&mut self, extension_id: Arc<str>, cx: &mut Context<Self>) {
         log::info!("installing extension {extension_id} latest version");
-
-        let schema_versions = schema_version_range();
-        let wasm_api_versions = wasm_api_version_range(ReleaseChannel::global(cx));
-
-        let Some(url) = self
-            .http_client
-            .build_zed_api_url(
-                &format!("/extensions/{extension_id}/download"),
-                &[
-                    ("min_schema_version", &schema_versions.start().to_string()),
-                    ("max_schema_version", &schema_versions.end().to_string()),
-                    (
-                        "min_wasm_api_version",
-                        &wasm_api_versions.start().to_string(),
-                    ),
-                    ("max_wasm_api_version", &wasm_api_versions.end().to_string()),
-                ],
-            )
-            .log_err()
-        else {
-            return;
-        };
-
-        self.install_or_upgrade_extension_at_endpoint(
+        self.install_or_upgrade_extension_from_registries(
             extension_id,
-            url,
+            None,
             ExtensionOperation::Install,
             cx,
         )
@@ -832,18 +1101,29 @@ impl ExtensionStore {
         cx: &mut Context<Self>,
     ) -> Task<Result<()>> {
         log::info!("installing extension {extension_id} {version}");
-        let Some(url) = self
-            .http_client
-            .build_zed_api_url(
-                &format!("/extensions/{extension_id}/{version}/download"),
-                &[],
-            )
-            .log_err()
-        else {
-            return Task::ready(Ok(()));
-        };
+        self.install_or_upgrade_extension_from_registries(
+            extension_id,
+            Some(version),
+            operation,
+            cx,
+        )
+    }
 
-        self.install_or_upgrade_extension_at_endpoint(extension_id, url, operation, cx)
+    fn install_or_upgrade_extension_from_registries(
+        &mut self,
+        extension_id: Arc<str>,
+        version: Option<Arc<str>>,
+        operation: ExtensionOperation,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let download = self.resolve_extension_download(extension_id.clone(), version, cx);
+        cx.spawn(async move |this, cx| {
+            let download = download.await?;
+            this.update(cx, |this, cx| {
+                this.install_or_upgrade_extension_at_endpoint(extension_id, download, operation, cx)
+            })?
+            .await
+        })
     }
 
     pub fn uninstall_extension(
@@ -1189,6 +1469,23 @@ impl ExtensionStore {
             for (locator, _) in &extension.manifest.debug_locators {
                 self.proxy.unregister_debug_locator(locator.clone());
             }
+            for (formatter_id, _) in &extension.manifest.formatters {
+                self.proxy.unregister_formatter(formatter_id.clone());
+            }
+            for (provider_id, _) in &extension.manifest.code_action_providers {
+                self.proxy
+                    .unregister_code_action_provider(provider_id.clone());
+            }
+            for (provider_id, _) in &extension.manifest.task_providers {
+                self.proxy.unregister_task_provider(provider_id.clone());
+            }
+            for (provider_id, _) in &extension.manifest.language_model_providers {
+                self.proxy.unregister_language_model_provider(
+                    extension_id.clone(),
+                    provider_id.clone(),
+                    cx,
+                );
+            }
             for (command_name, _) in &extension.manifest.slash_commands {
                 self.proxy.unregister_slash_command(command_name.clone());
             }
@@ -1416,6 +1713,39 @@ impl ExtensionStore {
                         this.proxy
                             .register_debug_locator(extension.clone(), debug_adapter.clone());
                     }
+
+                    for (formatter_id, formatter) in &manifest.formatters {
+                        this.proxy.register_formatter(
+                            extension.clone(),
+                            formatter_id.clone(),
+                            formatter.languages.clone(),
+                        );
+                    }
+
+                    for (provider_id, provider) in &manifest.code_action_providers {
+                        this.proxy.register_code_action_provider(
+                            extension.clone(),
+                            provider_id.clone(),
+                            provider.languages.clone(),
+                        );
+                    }
+
+                    for (provider_id, provider) in &manifest.task_providers {
+                        this.proxy.register_task_provider(
+                            extension.clone(),
+                            provider_id.clone(),
+                            provider.languages.clone(),
+                        );
+                    }
+
+                    for (provider_id, provider) in &manifest.language_model_providers {
+                        this.proxy.register_language_model_provider(
+                            extension.clone(),
+                            provider_id.clone(),
+                            provider.clone(),
+                            cx,
+                        );
+                    }
                 }
 
                 this.wasm_extensions.extend(wasm_extensions);
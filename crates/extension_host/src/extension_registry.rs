@@ -0,0 +1,388 @@
+//! Extension registries that extensions are fetched and installed from.
+//!
+//! Besides the Zed extension registry, extensions can be installed from static registries,
+//! which are plain directories or static HTTP servers with an `index.json` at their root.
+//! The index lists every published version of every extension in the registry, along with
+//! the path and SHA-256 checksum of its archive.
+
+use std::collections::BTreeSet;
+use std::hash::Hash;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result, bail};
+use client::{
+    ExtensionMetadata, ExtensionProvides, ExtensionRegistryEntry, ExtensionRegistryIndex,
+};
+use collections::HashSet;
+use fs::Fs;
+use futures::AsyncReadExt as _;
+use http_client::{AsyncBody, HttpClient};
+use release_channel::ReleaseChannel;
+use semantic_version::SemanticVersion;
+use sha2::{Digest as _, Sha256};
+use url::Url;
+
+use crate::extension_settings::ExtensionRegistrySettings;
+use crate::is_version_compatible;
+
+/// The name of the index file at the root of a static registry.
+pub const REGISTRY_INDEX_FILE_NAME: &str = "index.json";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExtensionRegistry {
+    /// The Zed extension registry.
+    Zed,
+    /// A static registry in a local directory or on a static HTTP server.
+    Static(StaticExtensionRegistry),
+}
+
+impl ExtensionRegistry {
+    pub fn from_settings(settings: &ExtensionRegistrySettings) -> Result<Self> {
+        match settings {
+            ExtensionRegistrySettings::Zed => Ok(Self::Zed),
+            ExtensionRegistrySettings::Directory { path } => {
+                let root = Url::from_directory_path(path).ok().with_context(|| {
+                    format!("extension registry path {path:?} is not an absolute path")
+                })?;
+                Ok(Self::Static(StaticExtensionRegistry::new(root)))
+            }
+            ExtensionRegistrySettings::Http { url } => {
+                let mut root = Url::parse(url)
+                    .with_context(|| format!("invalid extension registry URL {url:?}"))?;
+                if !root.path().ends_with('/') {
+                    root.set_path(&format!("{}/", root.path()));
+                }
+                Ok(Self::Static(StaticExtensionRegistry::new(root)))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StaticExtensionRegistry {
+    root: Url,
+}
+
+impl StaticExtensionRegistry {
+    pub fn new(root: Url) -> Self {
+        Self { root }
+    }
+
+    pub async fn load_index(
+        &self,
+        fs: &dyn Fs,
+        http_client: &dyn HttpClient,
+    ) -> Result<ExtensionRegistryIndex> {
+        let url = self.root.join(REGISTRY_INDEX_FILE_NAME)?;
+        let bytes = read_url(&url, fs, http_client).await?;
+        serde_json::from_slice(&bytes)
+            .with_context(|| format!("parsing extension registry index {url}"))
+    }
+
+    pub fn archive_url(&self, entry: &ExtensionRegistryEntry) -> Result<Url> {
+        self.root.join(&entry.archive).with_context(|| {
+            format!(
+                "invalid archive path {:?} for extension {}",
+                entry.archive, entry.metadata.id
+            )
+        })
+    }
+}
+
+/// Reads the contents of a `file` or `http(s)` URL.
+pub async fn read_url(url: &Url, fs: &dyn Fs, http_client: &dyn HttpClient) -> Result<Vec<u8>> {
+    if url.scheme() == "file" {
+        let path = url
+            .to_file_path()
+            .ok()
+            .with_context(|| format!("invalid file URL {url}"))?;
+        return fs.load_bytes(&path).await;
+    }
+
+    let mut response = http_client
+        .get(url.as_str(), AsyncBody::empty(), true)
+        .await
+        .with_context(|| format!("fetching {url}"))?;
+    let mut body = Vec::new();
+    response
+        .body_mut()
+        .read_to_end(&mut body)
+        .await
+        .with_context(|| format!("reading {url}"))?;
+    if !response.status().is_success() {
+        bail!(
+            "status error {} fetching {url}, response: {:?}",
+            response.status().as_u16(),
+            String::from_utf8_lossy(&body)
+        );
+    }
+    Ok(body)
+}
+
+/// Returns an error if the SHA-256 checksum of the given bytes does not match the expected one.
+pub fn verify_checksum(bytes: &[u8], expected_sha256: &str) -> Result<()> {
+    let actual_sha256 = hex::encode(Sha256::digest(bytes));
+    if !actual_sha256.eq_ignore_ascii_case(expected_sha256.trim()) {
+        bail!("checksum mismatch: expected {expected_sha256}, got {actual_sha256}");
+    }
+    Ok(())
+}
+
+fn version(metadata: &ExtensionMetadata) -> Option<SemanticVersion> {
+    SemanticVersion::from_str(&metadata.manifest.version).ok()
+}
+
+/// Returns the compatible entries of the index, with the latest version of each extension first.
+fn compatible_entries(
+    index: &ExtensionRegistryIndex,
+    release_channel: ReleaseChannel,
+) -> Vec<&ExtensionRegistryEntry> {
+    let mut entries = index
+        .extensions
+        .iter()
+        .filter(|entry| is_version_compatible(release_channel, &entry.metadata))
+        .collect::<Vec<_>>();
+    entries.sort_by(|a, b| {
+        a.metadata
+            .id
+            .cmp(&b.metadata.id)
+            .then_with(|| version(&b.metadata).cmp(&version(&a.metadata)))
+    });
+    entries
+}
+
+fn latest_entries(
+    index: &ExtensionRegistryIndex,
+    release_channel: ReleaseChannel,
+) -> Vec<&ExtensionRegistryEntry> {
+    let mut seen = HashSet::default();
+    compatible_entries(index, release_channel)
+        .into_iter()
+        .filter(|entry| seen.insert(entry.metadata.id.clone()))
+        .collect()
+}
+
+/// Returns the latest compatible version of every extension in the index that matches the given
+/// search query and provides at least one of the given features.
+pub fn search(
+    index: &ExtensionRegistryIndex,
+    search: Option<&str>,
+    provides_filter: Option<&BTreeSet<ExtensionProvides>>,
+    release_channel: ReleaseChannel,
+) -> Vec<ExtensionMetadata> {
+    let search = search.map(|search| search.to_lowercase());
+    latest_entries(index, release_channel)
+        .into_iter()
+        .map(|entry| &entry.metadata)
+        .filter(|metadata| {
+            search.as_ref().is_none_or(|search| {
+                metadata.id.to_lowercase().contains(search)
+                    || metadata.manifest.name.to_lowercase().contains(search)
+                    || metadata
+                        .manifest
+                        .description
+                        .as_ref()
+                        .is_some_and(|description| description.to_lowercase().contains(search))
+            })
+        })
+        .filter(|metadata| {
+            provides_filter.is_none_or(|provides_filter| {
+                provides_filter.is_empty()
+                    || !metadata.manifest.provides.is_disjoint(provides_filter)
+            })
+        })
+        .cloned()
+        .collect()
+}
+
+/// Returns the latest compatible version of each of the given extensions that is in the index.
+pub fn latest_versions(
+    index: &ExtensionRegistryIndex,
+    extension_ids: &HashSet<Arc<str>>,
+    release_channel: ReleaseChannel,
+) -> Vec<ExtensionMetadata> {
+    latest_entries(index, release_channel)
+        .into_iter()
+        .filter(|entry| extension_ids.contains(&entry.metadata.id))
+        .map(|entry| entry.metadata.clone())
+        .collect()
+}
+
+/// Returns all versions of the given extension in the index, latest first.
+pub fn versions(index: &ExtensionRegistryIndex, extension_id: &str) -> Vec<ExtensionMetadata> {
+    let mut versions = index
+        .extensions
+        .iter()
+        .filter(|entry| entry.metadata.id.as_ref() == extension_id)
+        .map(|entry| entry.metadata.clone())
+        .collect::<Vec<_>>();
+    versions.sort_by(|a, b| version(b).cmp(&version(a)));
+    versions
+}
+
+/// Returns the entry for the given version of an extension, or for its latest compatible
+/// version if no version is given.
+pub fn find_entry<'a>(
+    index: &'a ExtensionRegistryIndex,
+    extension_id: &str,
+    version: Option<&str>,
+    release_channel: ReleaseChannel,
+) -> Option<&'a ExtensionRegistryEntry> {
+    match version {
+        Some(version) => index.extensions.iter().find(|entry| {
+            entry.metadata.id.as_ref() == extension_id
+                && entry.metadata.manifest.version.as_ref() == version
+        }),
+        None => compatible_entries(index, release_channel)
+            .into_iter()
+            .find(|entry| entry.metadata.id.as_ref() == extension_id),
+    }
+}
+
+/// Merges the extensions fetched from several registries, in order of precedence, keeping only
+/// the first extension with each key.
+pub fn merge_extensions<K: Eq + Hash>(
+    results: Vec<Vec<ExtensionMetadata>>,
+    key: impl Fn(&ExtensionMetadata) -> K,
+) -> Vec<ExtensionMetadata> {
+    let mut seen = HashSet::default();
+    results
+        .into_iter()
+        .flatten()
+        .filter(|metadata| seen.insert(key(metadata)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use client::ExtensionApiManifest;
+
+    fn entry(id: &str, version: &str, provides: &[ExtensionProvides]) -> ExtensionRegistryEntry {
+        ExtensionRegistryEntry {
+            metadata: ExtensionMetadata {
+                id: id.into(),
+                manifest: ExtensionApiManifest {
+                    name: format!("{id} extension"),
+                    version: version.into(),
+                    description: None,
+                    authors: Vec::new(),
+                    repository: format!("https://example.com/{id}"),
+                    schema_version: Some(1),
+                    wasm_api_version: None,
+                    provides: provides.iter().copied().collect(),
+                },
+                published_at: Default::default(),
+                download_count: 0,
+            },
+            archive: format!("{id}/{version}.tar.gz"),
+            sha256: String::new(),
+        }
+    }
+
+    fn ids_and_versions(extensions: &[ExtensionMetadata]) -> Vec<(&str, &str)> {
+        extensions
+            .iter()
+            .map(|metadata| (metadata.id.as_ref(), metadata.manifest.version.as_ref()))
+            .collect()
+    }
+
+    #[test]
+    fn test_static_registry_queries() {
+        let mut too_new = entry("ruby", "0.3.0", &[]);
+        too_new.metadata.manifest.schema_version = Some(i32::MAX);
+        let index = ExtensionRegistryIndex {
+            extensions: vec![
+                entry("ruby", "0.1.0", &[ExtensionProvides::Languages]),
+                entry("ruby", "0.2.0", &[ExtensionProvides::Languages]),
+                too_new,
+                entry("gruvbox", "1.0.0", &[ExtensionProvides::Themes]),
+            ],
+        };
+        let channel = ReleaseChannel::Stable;
+
+        assert_eq!(
+            ids_and_versions(&search(&index, None, None, channel)),
+            [("gruvbox", "1.0.0"), ("ruby", "0.2.0")]
+        );
+        assert_eq!(
+            ids_and_versions(&search(&index, Some("RUB"), None, channel)),
+            [("ruby", "0.2.0")]
+        );
+        let themes = BTreeSet::from_iter([ExtensionProvides::Themes]);
+        assert_eq!(
+            ids_and_versions(&search(&index, None, Some(&themes), channel)),
+            [("gruvbox", "1.0.0")]
+        );
+
+        let ids = HashSet::from_iter(["ruby".into()]);
+        assert_eq!(
+            ids_and_versions(&latest_versions(&index, &ids, channel)),
+            [("ruby", "0.2.0")]
+        );
+        assert_eq!(
+            ids_and_versions(&versions(&index, "ruby")),
+            [("ruby", "0.3.0"), ("ruby", "0.2.0"), ("ruby", "0.1.0")]
+        );
+
+        let latest = find_entry(&index, "ruby", None, channel).unwrap();
+        assert_eq!(latest.archive, "ruby/0.2.0.tar.gz");
+        let pinned = find_entry(&index, "ruby", Some("0.1.0"), channel).unwrap();
+        assert_eq!(pinned.archive, "ruby/0.1.0.tar.gz");
+        assert!(find_entry(&index, "ruby", Some("9.9.9"), channel).is_none());
+    }
+
+    #[test]
+    fn test_registry_from_settings() {
+        let registry = ExtensionRegistry::from_settings(&ExtensionRegistrySettings::Http {
+            url: "https://example.com/zed-extensions".into(),
+        })
+        .unwrap();
+        let ExtensionRegistry::Static(registry) = registry else {
+            panic!("expected a static registry");
+        };
+        let entry = entry("ruby", "0.1.0", &[]);
+        assert_eq!(
+            registry.archive_url(&entry).unwrap().as_str(),
+            "https://example.com/zed-extensions/ruby/0.1.0.tar.gz"
+        );
+
+        assert!(
+            ExtensionRegistry::from_settings(&ExtensionRegistrySettings::Directory {
+                path: "relative/path".into(),
+            })
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_verify_checksum() {
+        let sha256 = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+        verify_checksum(b"hello", sha256).unwrap();
+        verify_checksum(b"hello", &sha256.to_uppercase()).unwrap();
+        assert!(verify_checksum(b"hello!", sha256).is_err());
+    }
+
+    #[test]
+    fn test_merge_extensions() {
+        let mirror = vec![entry("ruby", "0.2.0", &[]).metadata];
+        let upstream = vec![
+            entry("ruby", "0.2.0", &[]).metadata,
+            entry("ruby", "0.3.0", &[]).metadata,
+        ];
+        assert_eq!(
+            ids_and_versions(&merge_extensions(
+                vec![mirror.clone(), upstream.clone()],
+                |metadata| metadata.id.clone()
+            )),
+            [("ruby", "0.2.0")]
+        );
+        assert_eq!(
+            ids_and_versions(&merge_extensions(vec![mirror, upstream], |metadata| {
+                (metadata.id.clone(), metadata.manifest.version.clone())
+            })),
+            [("ruby", "0.2.0"), ("ruby", "0.3.0")]
+        );
+    }
+}
@@ -1,10 +1,11 @@
 use anyhow::Result;
 use collections::HashMap;
+use extension::ExtensionCapability;
 use gpui::App;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsSources};
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
 #[derive(Deserialize, Serialize, Debug, Default, Clone, JsonSchema)]
 pub struct ExtensionSettings {
@@ -18,6 +19,32 @@ pub struct ExtensionSettings {
     pub auto_install_extensions: HashMap<Arc<str>, bool>,
     #[serde(default)]
     pub auto_update_extensions: HashMap<Arc<str>, bool>,
+    /// The registries that extensions are fetched from and installed from, in order of precedence.
+    ///
+    /// Default: [{ "type": "zed" }]
+    #[serde(default)]
+    pub extension_registries: Vec<ExtensionRegistrySettings>,
+    /// The capabilities granted to specific extensions, replacing the capabilities that
+    /// extensions are granted by default.
+    ///
+    /// Extensions can only use the capabilities that are both requested in their manifest
+    /// and granted to them.
+    ///
+    /// Example: { "clang-format": [{ "kind": "process:exec", "command": "/usr/bin/clang-format", "args": ["**"] }] }
+    #[serde(default)]
+    pub extension_capabilities: HashMap<Arc<str>, Vec<ExtensionCapability>>,
+}
+
+/// A registry that extensions are installed from.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExtensionRegistrySettings {
+    /// The Zed extension registry.
+    Zed,
+    /// A static registry in a local directory, as generated by `zed-extension --registry-dir`.
+    Directory { path: PathBuf },
+    /// A static registry served over HTTP, with the same layout as a directory registry.
+    Http { url: String },
 }
 
 impl ExtensionSettings {
@@ -35,6 +62,15 @@ impl ExtensionSettings {
             .copied()
             .unwrap_or(true)
     }
+
+    /// Returns the configured extension registries, falling back to the Zed extension registry.
+    pub fn registries(&self) -> Vec<ExtensionRegistrySettings> {
+        if self.extension_registries.is_empty() {
+            vec![ExtensionRegistrySettings::Zed]
+        } else {
+            self.extension_registries.clone()
+        }
+    }
 }
 
 impl Settings for ExtensionSettings {
@@ -1,14 +1,18 @@
+use crate::extension_settings::ExtensionRegistrySettings;
 use crate::{
     Event, ExtensionIndex, ExtensionIndexEntry, ExtensionIndexLanguageEntry,
-    ExtensionIndexThemeEntry, ExtensionManifest, ExtensionSettings, ExtensionStore,
-    GrammarManifestEntry, RELOAD_DEBOUNCE_DURATION, SchemaVersion,
+    ExtensionIndexThemeEntry, ExtensionManifest, ExtensionOperation, ExtensionSettings,
+    ExtensionStore, GrammarManifestEntry, RELOAD_DEBOUNCE_DURATION, SchemaVersion,
 };
 use async_compression::futures::bufread::GzipEncoder;
+use client::{
+    ExtensionApiManifest, ExtensionMetadata, ExtensionRegistryEntry, ExtensionRegistryIndex,
+};
 use collections::{BTreeMap, HashSet};
 use extension::ExtensionHostProxy;
 use fs::{FakeFs, Fs, RealFs};
 use futures::{AsyncReadExt, StreamExt, io::BufReader};
-use gpui::{AppContext as _, SemanticVersion, TestAppContext};
+use gpui::{AppContext as _, SemanticVersion, Task, TestAppContext};
 use http_client::{FakeHttpClient, Response};
 use language::{BinaryStatus, LanguageMatcher, LanguageName, LanguageRegistry};
 use language_extension::LspAccess;
@@ -20,10 +24,14 @@ use release_channel::AppVersion;
 use reqwest_client::ReqwestClient;
 use serde_json::json;
 use settings::{Settings as _, SettingsStore};
+use sha2::{Digest as _, Sha256};
 use std::{
     ffi::OsString,
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
 };
 use theme::ThemeRegistry;
 use util::test::TempTree;
@@ -164,6 +172,10 @@ async fn test_extension_store(cx: &mut TestAppContext) {
                         capabilities: Vec::new(),
                         debug_adapters: Default::default(),
                         debug_locators: Default::default(),
+                        formatters: Default::default(),
+                        code_action_providers: Default::default(),
+                        task_providers: Default::default(),
+                        language_model_providers: Default::default(),
                     }),
                     dev: false,
                 },
@@ -194,6 +206,10 @@ async fn test_extension_store(cx: &mut TestAppContext) {
                         capabilities: Vec::new(),
                         debug_adapters: Default::default(),
                         debug_locators: Default::default(),
+                        formatters: Default::default(),
+                        code_action_providers: Default::default(),
+                        task_providers: Default::default(),
+                        language_model_providers: Default::default(),
                     }),
                     dev: false,
                 },
@@ -373,6 +389,10 @@ async fn test_extension_store(cx: &mut TestAppContext) {
                 capabilities: Vec::new(),
                 debug_adapters: Default::default(),
                 debug_locators: Default::default(),
+                formatters: Default::default(),
+                code_action_providers: Default::default(),
+                task_providers: Default::default(),
+                language_model_providers: Default::default(),
             }),
             dev: false,
         },
@@ -849,6 +869,213 @@ async fn test_extension_store_with_test_extension(cx: &mut TestAppContext) {
     assert!(fs.metadata(&expected_server_path).await.unwrap().is_none());
 }
 
+#[gpui::test]
+async fn test_install_extension_from_directory_registry(cx: &mut TestAppContext) {
+    init_test(cx);
+    cx.executor().allow_parking();
+
+    let fs = Arc::new(RealFs::new(None, cx.executor()));
+    let extensions_dir = TempTree::new(json!({
+        "installed": {},
+        "work": {}
+    }));
+    let registry_dir = TempTree::new(json!({
+        "zed-mirrored": {}
+    }));
+    let extensions_dir = extensions_dir.path().canonicalize().unwrap();
+    let registry_dir = registry_dir.path().canonicalize().unwrap();
+
+    let manifest = concat!(
+        "id = \"zed-mirrored\"\nname = \"Mirrored\"\nversion = \"0.1.0\"\nschema_version = 1\n",
+        "[[capabilities]]\nkind = \"process:exec\"\ncommand = \"echo\"\nargs = [\"hello\"]\n",
+    );
+    let mut tar_bytes = Vec::<u8>::new();
+    let mut archive = async_tar::Builder::new(&mut tar_bytes);
+    let mut header = async_tar::Header::new_gnu();
+    header.set_size(manifest.len() as u64);
+    header.set_mode(0o644);
+    archive
+        .append_data(&mut header, "extension.toml", manifest.as_bytes())
+        .await
+        .unwrap();
+    archive.into_inner().await.unwrap();
+    let mut archive_bytes = Vec::new();
+    GzipEncoder::new(BufReader::new(tar_bytes.as_slice()))
+        .read_to_end(&mut archive_bytes)
+        .await
+        .unwrap();
+    std::fs::write(
+        registry_dir.join("zed-mirrored/0.1.0.tar.gz"),
+        &archive_bytes,
+    )
+    .unwrap();
+
+    let registry_entry = |version: &str, sha256: String| ExtensionRegistryEntry {
+        metadata: ExtensionMetadata {
+            id: "zed-mirrored".into(),
+            manifest: ExtensionApiManifest {
+                name: "Mirrored".into(),
+                version: version.into(),
+                description: None,
+                authors: Vec::new(),
+                repository: "https://example.com/zed-mirrored".into(),
+                schema_version: Some(1),
+                wasm_api_version: None,
+                provides: Default::default(),
+            },
+            published_at: Default::default(),
+            download_count: 0,
+        },
+        archive: "zed-mirrored/0.1.0.tar.gz".into(),
+        sha256,
+    };
+    let index = ExtensionRegistryIndex {
+        extensions: vec![
+            registry_entry("0.1.0", hex::encode(Sha256::digest(&archive_bytes))),
+            // A version whose published checksum does not match its archive.
+            registry_entry("0.2.0", hex::encode(Sha256::digest(b"something else"))),
+        ],
+    };
+    std::fs::write(
+        registry_dir.join("index.json"),
+        serde_json::to_vec(&index).unwrap(),
+    )
+    .unwrap();
+
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |store, cx| {
+            store.update_user_settings::<ExtensionSettings>(cx, |settings| {
+                settings.extension_registries = vec![ExtensionRegistrySettings::Directory {
+                    path: registry_dir.clone(),
+                }];
+            });
+        });
+    });
+
+    let extension_store = cx.new(|cx| {
+        ExtensionStore::new(
+            extensions_dir.clone(),
+            None,
+            Arc::new(ExtensionHostProxy::new()),
+            fs.clone(),
+            FakeHttpClient::with_404_response(),
+            FakeHttpClient::with_404_response(),
+            None,
+            NodeRuntime::unavailable(),
+            cx,
+        )
+    });
+
+    // Ensure that debounces fire.
+    let mut events = cx.events(&extension_store);
+    let executor = cx.executor();
+    let _task = cx.executor().spawn(async move {
+        while let Some(event) = events.next().await {
+            if let Event::StartedReloading = event {
+                executor.advance_clock(RELOAD_DEBOUNCE_DURATION);
+            }
+        }
+    });
+
+    let extensions = extension_store
+        .update(cx, |store, cx| {
+            store.fetch_extensions(Some("mirror"), None, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        extensions
+            .iter()
+            .map(|extension| (extension.id.as_ref(), extension.manifest.version.as_ref()))
+            .collect::<Vec<_>>(),
+        [("zed-mirrored", "0.2.0")]
+    );
+
+    // The user is asked to approve the capabilities requested by the extension before it is
+    // installed.
+    let requested_capabilities = Arc::new(Mutex::new(Vec::new()));
+    let approve = Arc::new(AtomicBool::new(false));
+    extension_store.update(cx, |store, _| {
+        store.set_capability_approver(Arc::new({
+            let requested_capabilities = requested_capabilities.clone();
+            let approve = approve.clone();
+            move |manifest, capabilities, _| {
+                requested_capabilities.lock().push((
+                    manifest.id.to_string(),
+                    capabilities
+                        .iter()
+                        .map(|capability| capability.to_string())
+                        .collect::<Vec<_>>(),
+                ));
+                Task::ready(approve.load(Ordering::SeqCst))
+            }
+        }));
+    });
+
+    extension_store
+        .update(cx, |store, cx| {
+            store.install_or_upgrade_extension(
+                "zed-mirrored".into(),
+                "0.1.0".into(),
+                ExtensionOperation::Install,
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        requested_capabilities.lock().drain(..).collect::<Vec<_>>(),
+        [(
+            "zed-mirrored".to_string(),
+            vec!["process:exec echo hello".to_string()]
+        )]
+    );
+    extension_store.read_with(cx, |store, _| {
+        assert!(
+            !store
+                .extension_index
+                .extensions
+                .contains_key("zed-mirrored")
+        );
+    });
+    assert!(!extensions_dir.join("installed/zed-mirrored").exists());
+    assert!(!extensions_dir.join("staging/zed-mirrored").exists());
+
+    approve.store(true, Ordering::SeqCst);
+    extension_store
+        .update(cx, |store, cx| {
+            store.install_or_upgrade_extension(
+                "zed-mirrored".into(),
+                "0.1.0".into(),
+                ExtensionOperation::Install,
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    extension_store.read_with(cx, |store, _| {
+        let installed = &store.extension_index.extensions["zed-mirrored"];
+        assert_eq!(installed.manifest.version.as_ref(), "0.1.0");
+    });
+    assert_eq!(requested_capabilities.lock().len(), 1);
+    assert!(!extensions_dir.join("staging/zed-mirrored").exists());
+
+    let error = extension_store
+        .update(cx, |store, cx| {
+            store.upgrade_extension("zed-mirrored".into(), "0.2.0".into(), cx)
+        })
+        .await
+        .unwrap_err();
+    assert!(
+        format!("{error:#}").contains("checksum mismatch"),
+        "unexpected error: {error:#}"
+    );
+    extension_store.read_with(cx, |store, _| {
+        let installed = &store.extension_index.extensions["zed-mirrored"];
+        assert_eq!(installed.manifest.version.as_ref(), "0.1.0");
+    });
+}
+
 fn init_test(cx: &mut TestAppContext) {
     cx.update(|cx| {
         let store = SettingsStore::test(cx);
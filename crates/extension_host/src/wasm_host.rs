@@ -1,15 +1,18 @@
 pub mod wit;
 
 use crate::ExtensionManifest;
-use crate::capability_granter::CapabilityGranter;
+use crate::capability_granter::{CapabilityAuditLog, CapabilityGranter};
 use anyhow::{Context as _, Result, anyhow, bail};
 use async_trait::async_trait;
+use collections::{HashMap, HashSet};
 use dap::{DebugRequest, StartDebuggingRequestArgumentsRequest};
 use extension::{
-    CodeLabel, Command, Completion, ContextServerConfiguration, DebugAdapterBinary,
-    DebugTaskDefinition, DownloadFileCapability, ExtensionCapability, ExtensionHostProxy,
-    KeyValueStoreDelegate, NpmInstallPackageCapability, ProcessExecCapability, ProjectDelegate,
-    SlashCommand, SlashCommandArgumentCompletion, SlashCommandOutput, Symbol, WorktreeDelegate,
+    CodeAction, CodeLabel, Command, Completion, ContextServerConfiguration, DebugAdapterBinary,
+    DebugTaskDefinition, Diagnostic, DownloadFileCapability, EnvVars, ExtensionCapability,
+    ExtensionEvents, ExtensionHostProxy, FormattingBuffer, KeyValueStoreDelegate,
+    LlmCompletionEvent, LlmCompletionRequest, LlmModelInfo, NpmInstallPackageCapability,
+    ProcessExecCapability, ProjectDelegate, Runnable, SlashCommand, SlashCommandArgumentCompletion,
+    SlashCommandOutput, Symbol, TaskDefinition, TaskLocation, TextEdit, WorktreeDelegate,
 };
 use fs::{Fs, normalize_path};
 use futures::future::LocalBoxFuture;
@@ -27,9 +30,11 @@ use language::LanguageName;
 use lsp::LanguageServerName;
 use moka::sync::Cache;
 use node_runtime::NodeRuntime;
+use parking_lot::RwLock;
 use release_channel::ReleaseChannel;
 use semantic_version::SemanticVersion;
 use std::borrow::Cow;
+use std::ops::Range;
 use std::sync::{LazyLock, OnceLock};
 use std::time::Duration;
 use std::{
@@ -37,6 +42,7 @@ use std::{
     sync::Arc,
 };
 use task::{DebugScenario, SpawnInTerminal, TaskTemplate, ZedDebugConfig};
+use util::ResultExt as _;
 use wasmtime::{
     CacheStore, Engine, Store,
     component::{Component, ResourceTable},
@@ -44,6 +50,9 @@ use wasmtime::{
 use wasmtime_wasi::{self as wasi, WasiView};
 use wit::Extension;
 
+/// The name of the file in the work directory that the [`CapabilityAuditLog`] is persisted to.
+pub const CAPABILITY_AUDIT_LOG_FILE_NAME: &str = "capability_audit_log.jsonl";
+
 pub struct WasmHost {
     engine: Engine,
     release_channel: ReleaseChannel,
@@ -54,6 +63,11 @@ pub struct WasmHost {
     pub work_dir: PathBuf,
     /// The capabilities granted to extensions running on the host.
     pub(crate) granted_capabilities: Vec<ExtensionCapability>,
+    /// The capabilities granted to specific extensions in the user's settings, which replace
+    /// the capabilities granted by the host.
+    capability_overrides: RwLock<HashMap<Arc<str>, Vec<ExtensionCapability>>>,
+    pub capability_audit_log: Arc<CapabilityAuditLog>,
+    _capability_audit_task: Task<()>,
     _main_thread_message_task: Task<()>,
     main_thread_message_tx: mpsc::UnboundedSender<MainThreadCall>,
 }
@@ -483,6 +497,191 @@ impl extension::Extension for WasmExtension {
         })
         .await?
     }
+
+    async fn format_buffer(
+        &self,
+        formatter_id: Arc<str>,
+        buffer: FormattingBuffer,
+        range: Option<Range<usize>>,
+        worktree: Arc<dyn WorktreeDelegate>,
+    ) -> Result<Vec<TextEdit>> {
+        self.call(|extension, store| {
+            async move {
+                let resource = store.data_mut().table().push(worktree)?;
+                extension
+                    .call_format_buffer(store, &formatter_id, buffer, range, resource)
+                    .await?
+                    .map_err(|err| store.data().extension_error(err))
+            }
+            .boxed()
+        })
+        .await?
+    }
+
+    async fn code_actions(
+        &self,
+        provider_id: Arc<str>,
+        buffer: FormattingBuffer,
+        range: Range<usize>,
+        diagnostics: Vec<Diagnostic>,
+        worktree: Arc<dyn WorktreeDelegate>,
+    ) -> Result<Vec<CodeAction>> {
+        self.call(|extension, store| {
+            async move {
+                let resource = store.data_mut().table().push(worktree)?;
+                extension
+                    .call_code_actions(store, &provider_id, buffer, range, diagnostics, resource)
+                    .await?
+                    .map_err(|err| store.data().extension_error(err))
+            }
+            .boxed()
+        })
+        .await?
+    }
+
+    async fn task_templates(
+        &self,
+        provider_id: Arc<str>,
+        worktree: Arc<dyn WorktreeDelegate>,
+    ) -> Result<Vec<TaskDefinition>> {
+        self.call(|extension, store| {
+            async move {
+                let resource = store.data_mut().table().push(worktree)?;
+                extension
+                    .call_task_templates(store, &provider_id, resource)
+                    .await?
+                    .map_err(|err| store.data().extension_error(err))
+            }
+            .boxed()
+        })
+        .await?
+    }
+
+    async fn task_variables(
+        &self,
+        provider_id: Arc<str>,
+        location: TaskLocation,
+        worktree: Arc<dyn WorktreeDelegate>,
+    ) -> Result<EnvVars> {
+        self.call(|extension, store| {
+            async move {
+                let resource = store.data_mut().table().push(worktree)?;
+                extension
+                    .call_task_variables(store, &provider_id, location, resource)
+                    .await?
+                    .map_err(|err| store.data().extension_error(err))
+            }
+            .boxed()
+        })
+        .await?
+    }
+
+    async fn detect_runnables(
+        &self,
+        provider_id: Arc<str>,
+        buffer: FormattingBuffer,
+        worktree: Arc<dyn WorktreeDelegate>,
+    ) -> Result<Vec<Runnable>> {
+        self.call(|extension, store| {
+            async move {
+                let resource = store.data_mut().table().push(worktree)?;
+                extension
+                    .call_detect_runnables(store, &provider_id, buffer, resource)
+                    .await?
+                    .map_err(|err| store.data().extension_error(err))
+            }
+            .boxed()
+        })
+        .await?
+    }
+
+    async fn llm_provider_models(
+        &self,
+        provider_id: Arc<str>,
+        credential: Option<String>,
+    ) -> Result<Vec<LlmModelInfo>> {
+        self.call(|extension, store| {
+            async move {
+                extension
+                    .call_llm_provider_models(store, &provider_id, credential)
+                    .await?
+                    .map_err(|err| store.data().extension_error(err))
+            }
+            .boxed()
+        })
+        .await?
+    }
+
+    async fn llm_stream_completion_start(
+        &self,
+        provider_id: Arc<str>,
+        model_id: Arc<str>,
+        request: LlmCompletionRequest,
+        credential: Option<String>,
+    ) -> Result<String> {
+        self.call(|extension, store| {
+            async move {
+                extension
+                    .call_llm_stream_completion_start(
+                        store,
+                        &provider_id,
+                        &model_id,
+                        request,
+                        credential,
+                    )
+                    .await?
+                    .map_err(|err| store.data().extension_error(err))
+            }
+            .boxed()
+        })
+        .await?
+    }
+
+    async fn llm_stream_completion_next(
+        &self,
+        stream_id: String,
+    ) -> Result<Option<LlmCompletionEvent>> {
+        self.call(|extension, store| {
+            async move {
+                extension
+                    .call_llm_stream_completion_next(store, &stream_id)
+                    .await?
+                    .map_err(|err| store.data().extension_error(err))
+            }
+            .boxed()
+        })
+        .await?
+    }
+
+    async fn llm_stream_completion_close(&self, stream_id: String) -> Result<()> {
+        self.call(|extension, store| {
+            async move {
+                extension
+                    .call_llm_stream_completion_close(store, &stream_id)
+                    .await
+            }
+            .boxed()
+        })
+        .await?
+    }
+
+    async fn llm_count_tokens(
+        &self,
+        provider_id: Arc<str>,
+        model_id: Arc<str>,
+        request: LlmCompletionRequest,
+    ) -> Result<u64> {
+        self.call(|extension, store| {
+            async move {
+                extension
+                    .call_llm_count_tokens(store, &provider_id, &model_id, request)
+                    .await?
+                    .map_err(|err| store.data().extension_error(err))
+            }
+            .boxed()
+        })
+        .await?
+    }
 }
 
 pub struct WasmState {
@@ -568,6 +767,53 @@ impl WasmHost {
                 message(cx).await;
             }
         });
+        let (capability_audit_log, mut recorded_capability_uses) = CapabilityAuditLog::new();
+        let capability_audit_task = cx.spawn({
+            let fs = fs.clone();
+            let capability_audit_log = capability_audit_log.clone();
+            let path = work_dir.join(CAPABILITY_AUDIT_LOG_FILE_NAME);
+            async move |cx| {
+                if let Ok(json_lines) = fs.load(&path).await {
+                    capability_audit_log.restore(&json_lines);
+                }
+
+                while let Some(recorded) = recorded_capability_uses.next().await {
+                    // Write the log once per batch of recorded uses, rather than once per use.
+                    let mut batch = vec![recorded];
+                    while let Ok(Some(recorded)) = recorded_capability_uses.try_next() {
+                        batch.push(recorded);
+                    }
+
+                    cx.update(|cx| {
+                        let Some(events) = ExtensionEvents::try_global(cx) else {
+                            return;
+                        };
+                        for recorded in &batch {
+                            if recorded.entry.granted {
+                                continue;
+                            }
+                            events.update(cx, |events, cx| {
+                                events.emit(
+                                    extension::Event::CapabilityDenied {
+                                        manifest: recorded.manifest.clone(),
+                                        capability: recorded.entry.capability.clone(),
+                                    },
+                                    cx,
+                                )
+                            });
+                        }
+                    })
+                    .ok();
+
+                    if let Some(dir) = path.parent() {
+                        fs.create_dir(dir).await.log_err();
+                    }
+                    fs.atomic_write(path.clone(), capability_audit_log.to_json_lines())
+                        .await
+                        .log_err();
+                }
+            }
+        });
         Arc::new(Self {
             engine: wasm_engine(cx.background_executor()),
             fs,
@@ -589,11 +835,44 @@ impl WasmHost {
                     package: "*".to_string(),
                 }),
             ],
+            capability_overrides: RwLock::default(),
+            capability_audit_log,
+            _capability_audit_task: capability_audit_task,
             _main_thread_message_task: task,
             main_thread_message_tx: tx,
         })
     }
 
+    /// Returns the capabilities granted to the given extension.
+    fn granted_capabilities_for(&self, extension_id: &str) -> Vec<ExtensionCapability> {
+        self.capability_overrides
+            .read()
+            .get(extension_id)
+            .cloned()
+            .unwrap_or_else(|| self.granted_capabilities.clone())
+    }
+
+    /// Sets the capabilities granted to specific extensions, returning the IDs of the extensions
+    /// whose grants changed.
+    ///
+    /// The new grants apply to extensions when they are next loaded.
+    pub(crate) fn set_capability_overrides(
+        &self,
+        overrides: HashMap<Arc<str>, Vec<ExtensionCapability>>,
+    ) -> HashSet<Arc<str>> {
+        let mut capability_overrides = self.capability_overrides.write();
+        let changed_extensions = capability_overrides
+            .keys()
+            .chain(overrides.keys())
+            .filter(|extension_id| {
+                capability_overrides.get(*extension_id) != overrides.get(*extension_id)
+            })
+            .cloned()
+            .collect();
+        *capability_overrides = overrides;
+        changed_extensions
+    }
+
     pub fn load_extension(
         self: &Arc<Self>,
         wasm_bytes: Vec<u8>,
@@ -616,8 +895,9 @@ impl WasmHost {
                     table: ResourceTable::new(),
                     host: this.clone(),
                     capability_granter: CapabilityGranter::new(
-                        this.granted_capabilities.clone(),
+                        this.granted_capabilities_for(&manifest.id),
                         manifest.clone(),
+                        this.capability_audit_log.clone(),
                     ),
                 },
             );
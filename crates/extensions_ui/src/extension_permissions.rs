@@ -0,0 +1,164 @@
+use std::sync::Arc;
+
+use editor::{Editor, MultiBuffer};
+use extension::{ExtensionCapability, ExtensionManifest};
+use extension_host::ExtensionStore;
+use gpui::{App, AppContext as _, Context, PromptLevel, SharedString, Task, Window};
+use time::{OffsetDateTime, UtcOffset};
+use time_format::TimestampFormat;
+use ui::prelude::*;
+use workspace::notifications::simple_message_notification::MessageNotification;
+use workspace::{Workspace, notifications::NotificationId};
+
+use crate::OpenCapabilityAuditLog;
+
+struct ExtensionCapabilityDeniedNotification;
+
+/// Asks the user, in the active window, whether to install an extension that requests the given
+/// capabilities. The extension is not installed when there is no window to ask in.
+pub(crate) fn approve_capabilities(
+    manifest: Arc<ExtensionManifest>,
+    capabilities: Vec<ExtensionCapability>,
+    cx: &mut App,
+) -> Task<bool> {
+    let Some(window) = cx
+        .active_window()
+        .or_else(|| cx.windows().into_iter().next())
+    else {
+        log::warn!(
+            "not installing extension {}, since there is no window to approve its capabilities in",
+            manifest.id
+        );
+        return Task::ready(false);
+    };
+
+    let message = format!(
+        "The '{}' extension requests the following capabilities",
+        manifest.name
+    );
+    let detail = format!(
+        "{}\n\nYou can restrict them later with the `extension_capabilities` setting.",
+        capabilities
+            .iter()
+            .map(|capability| format!("• {capability}"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    );
+    let Ok(answer) = window.update(cx, |_, window, cx| {
+        window.prompt(
+            PromptLevel::Warning,
+            &message,
+            Some(&detail),
+            &["Allow and Install", "Cancel"],
+            cx,
+        )
+    }) else {
+        return Task::ready(false);
+    };
+    cx.background_spawn(async move { answer.await.ok() == Some(0) })
+}
+
+/// Shows the capabilities an extension was denied in response to extension events.
+pub(crate) fn handle_extension_event(
+    workspace: &mut Workspace,
+    event: &extension::Event,
+    cx: &mut Context<Workspace>,
+) {
+    if let extension::Event::CapabilityDenied {
+        manifest,
+        capability,
+    } = event
+    {
+        show_capability_denied(workspace, manifest, capability, cx);
+    }
+}
+
+fn show_capability_denied(
+    workspace: &mut Workspace,
+    manifest: &Arc<ExtensionManifest>,
+    capability: &ExtensionCapability,
+    cx: &mut Context<Workspace>,
+) {
+    let message = format!(
+        "The '{}' extension was denied permission to use `{capability}`.",
+        manifest.name
+    );
+
+    let notification_id = NotificationId::composite::<ExtensionCapabilityDeniedNotification>(
+        SharedString::from(manifest.id.clone()),
+    );
+    workspace.show_notification(notification_id, cx, |cx| {
+        cx.new(move |cx| {
+            MessageNotification::new(message, cx)
+                .primary_message("Grant in Settings")
+                .primary_icon(IconName::Settings)
+                .primary_on_click(|window, cx| {
+                    window.dispatch_action(zed_actions::OpenSettings.boxed_clone(), cx);
+                })
+                .secondary_message("View Capability Log")
+                .secondary_icon(IconName::FileTextOutlined)
+                .secondary_on_click(|window, cx| {
+                    window.dispatch_action(OpenCapabilityAuditLog.boxed_clone(), cx);
+                })
+        })
+    });
+}
+
+/// Opens the log of the capabilities used by extensions in a read-only editor, newest first.
+pub(crate) fn open_capability_audit_log(
+    workspace: &mut Workspace,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let text = capability_audit_log_text(ExtensionStore::global(cx).read(cx));
+
+    workspace
+        .with_local_workspace(window, cx, move |workspace, window, cx| {
+            let project = workspace.project().clone();
+            let buffer = project.update(cx, |project, cx| {
+                project.create_local_buffer(&text, None, cx)
+            });
+            let buffer = cx.new(|cx| {
+                MultiBuffer::singleton(buffer, cx).with_title("Extension Capability Log".into())
+            });
+            let editor = cx.new(|cx| {
+                let mut editor = Editor::for_multibuffer(buffer, Some(project), window, cx);
+                editor.set_read_only(true);
+                editor.set_breadcrumb_header("Extension Capability Log".into());
+                editor
+            });
+            workspace.add_item_to_active_pane(Box::new(editor), None, true, window, cx);
+        })
+        .detach();
+}
+
+fn capability_audit_log_text(store: &ExtensionStore) -> String {
+    let installed_extensions = store.installed_extensions();
+    let entries = store.capability_audit_log().entries();
+    if entries.is_empty() {
+        return "No extension has used a capability yet.\n".to_string();
+    }
+
+    let now = OffsetDateTime::now_utc();
+    let timezone = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+    let mut text = String::new();
+    for entry in entries.iter().rev() {
+        let timestamp = time_format::format_localized_timestamp(
+            OffsetDateTime::from(entry.timestamp),
+            now,
+            timezone,
+            TimestampFormat::Absolute,
+        );
+        let extension_name = installed_extensions
+            .get(&entry.extension_id)
+            .map_or(entry.extension_id.as_ref(), |installed| {
+                installed.manifest.name.as_str()
+            });
+        let status = if entry.granted { "granted" } else { "denied" };
+        text.push_str(&format!(
+            "{timestamp}  {status:<7}  {extension_name}: {}\n",
+            entry.capability
+        ));
+    }
+    text
+}
@@ -1,4 +1,5 @@
 mod components;
+mod extension_permissions;
 mod extension_suggest;
 mod extension_version_selector;
 
@@ -43,11 +44,19 @@ actions!(
     zed,
     [
         /// Installs an extension from a local directory for development.
-        InstallDevExtension
+        InstallDevExtension,
+        /// Opens the log of the capabilities used by extensions.
+        OpenCapabilityAuditLog
     ]
 );
 
 pub fn init(cx: &mut App) {
+    if let Some(store) = ExtensionStore::try_global(cx) {
+        store.update(cx, |store, _| {
+            store.set_capability_approver(Arc::new(extension_permissions::approve_capabilities));
+        });
+    }
+
     cx.observe_new(move |workspace: &mut Workspace, window, cx| {
         let Some(window) = window else {
             return;
@@ -109,6 +118,9 @@ pub fn init(cx: &mut App) {
                     }
                 },
             )
+            .register_action(|workspace, _: &OpenCapabilityAuditLog, window, cx| {
+                extension_permissions::open_capability_audit_log(workspace, window, cx);
+            })
             .register_action(move |workspace, _: &InstallDevExtension, window, cx| {
                 let store = ExtensionStore::global(cx);
                 let prompt = workspace.prompt_for_open_path(
@@ -176,6 +188,13 @@ pub fn init(cx: &mut App) {
             }
         })
         .detach();
+
+        if let Some(extension_events) = extension::ExtensionEvents::try_global(cx) {
+            cx.subscribe_in(&extension_events, window, |workspace, _, event, _, cx| {
+                extension_permissions::handle_extension_event(workspace, event, cx);
+            })
+            .detach();
+        }
     })
     .detach();
 }
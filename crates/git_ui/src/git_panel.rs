@@ -41,7 +41,7 @@ use itertools::Itertools;
 use language::{Buffer, File};
 use language_model::{
     ConfiguredModel, LanguageModel, LanguageModelRegistry, LanguageModelRequest,
-    LanguageModelRequestMessage, Role,
+    LanguageModelRequestMessage, Role, UsageLedger, UsageRecord, UsageSource,
 };
 use menu::{Confirm, SecondaryConfirm, SelectFirst, SelectLast, SelectNext, SelectPrevious};
 use multi_buffer::ExcerptInfo;
@@ -1950,6 +1950,11 @@ impl GitPanel {
                     thinking_allowed: false,
                 };
 
+                if let Err(e) = cx.update(|cx| UsageLedger::check_global_budget(cx))? {
+                    Self::show_commit_message_error(&this, &e, cx);
+                    return anyhow::Ok(());
+                }
+
                 let stream = model.stream_completion_text(request, &cx);
                 match stream.await {
                     Ok(mut messages) => {
@@ -1978,6 +1983,20 @@ impl GitPanel {
                                 }
                             }
                         }
+
+                        let usage = *messages.last_token_usage.lock();
+                        cx.update(|cx| {
+                            UsageLedger::record_global(
+                                UsageRecord {
+                                    source: UsageSource::CommitMessage,
+                                    thread_id: None,
+                                    provider: model.provider_id(),
+                                    model: model.id(),
+                                    usage,
+                                },
+                                cx,
+                            )
+                        })?;
                     }
                     Err(e) => {
                         Self::show_commit_message_error(&this, &e, cx);
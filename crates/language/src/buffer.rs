@@ -529,6 +529,7 @@ pub enum CharKind {
 }
 
 /// A runnable is a set of data about a region that could be resolved into a task
+#[derive(Clone)]
 pub struct Runnable {
     pub tags: SmallVec<[RunnableTag; 1]>,
     pub language: Arc<Language>,
@@ -915,6 +915,8 @@ pub enum Formatter {
     },
     /// Files should be formatted using code actions executed by language servers.
     CodeActions(HashMap<String, bool>),
+    /// Format code using a formatter provided by an extension.
+    Extension(Arc<str>),
 }
 
 /// The settings for indent guides.
@@ -1649,6 +1651,15 @@ mod tests {
                 Formatter::Prettier
             ])))
         );
+        let raw = "{\"formatter\": [{\"extension\": \"ruff\"}, \"prettier\"]}";
+        let settings: LanguageSettingsContent = serde_json::from_str(raw).unwrap();
+        assert_eq!(
+            settings.formatter,
+            Some(SelectedFormatter::List(FormatterList::Vec(vec![
+                Formatter::Extension("ruff".into()),
+                Formatter::Prettier
+            ])))
+        );
     }
 
     #[test]
@@ -10,6 +10,7 @@ use lsp::LanguageServerName;
 use task::{TaskTemplates, TaskVariables};
 use text::BufferId;
 
+#[derive(Clone)]
 pub struct RunnableRange {
     pub buffer_id: BufferId,
     pub run_range: Range<usize>,
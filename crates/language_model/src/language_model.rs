@@ -2,8 +2,10 @@ mod model;
 mod rate_limiter;
 mod registry;
 mod request;
+mod request_cache;
 mod role;
 mod telemetry;
+mod usage_ledger;
 
 #[cfg(any(test, feature = "test-support"))]
 pub mod fake_provider;
@@ -32,8 +34,10 @@ pub use crate::model::*;
 pub use crate::rate_limiter::*;
 pub use crate::registry::*;
 pub use crate::request::*;
+pub use crate::request_cache::*;
 pub use crate::role::*;
 pub use crate::telemetry::*;
+pub use crate::usage_ledger::*;
 
 pub const ANTHROPIC_PROVIDER_ID: LanguageModelProviderId =
     LanguageModelProviderId::new("anthropic");
@@ -54,6 +58,7 @@ pub const ZED_CLOUD_PROVIDER_NAME: LanguageModelProviderName =
 
 pub fn init(client: Arc<Client>, cx: &mut App) {
     init_settings(cx);
+    usage_ledger::init(cx);
     RefreshLlmTokenListener::register(client.clone(), cx);
 }
 
@@ -1,15 +1,18 @@
 use crate::{
-    LanguageModel, LanguageModelId, LanguageModelProvider, LanguageModelProviderId,
-    LanguageModelProviderState,
+    CachingLanguageModel, LanguageModel, LanguageModelId, LanguageModelProvider,
+    LanguageModelProviderId, LanguageModelProviderState, RequestCache,
 };
 use collections::BTreeMap;
 use gpui::{App, Context, Entity, EventEmitter, Global, prelude::*};
 use std::{str::FromStr, sync::Arc};
 use thiserror::Error;
-use util::maybe;
+use util::{ResultExt as _, maybe};
 
 pub fn init(cx: &mut App) {
-    let registry = cx.new(|_cx| LanguageModelRegistry::default());
+    let registry = cx.new(|_cx| LanguageModelRegistry {
+        request_cache: RequestCache::from_env().log_err().flatten().map(Arc::new),
+        ..Default::default()
+    });
     cx.set_global(GlobalLanguageModelRegistry(registry));
 }
 
@@ -54,6 +57,9 @@ pub struct LanguageModelRegistry {
     thread_summary_model: Option<ConfiguredModel>,
     providers: BTreeMap<LanguageModelProviderId, Arc<dyn LanguageModelProvider>>,
     inline_alternatives: Vec<Arc<dyn LanguageModel>>,
+    /// When set, the completions of the models handed out by the registry are recorded to, or
+    /// replayed from, this cache.
+    request_cache: Option<Arc<RequestCache>>,
 }
 
 #[derive(Debug)]
@@ -218,6 +224,7 @@ impl LanguageModelRegistry {
         self.providers
             .values()
             .flat_map(|provider| provider.provided_models(cx))
+            .map(|model| self.with_request_cache(model))
     }
 
     pub fn provider(&self, id: &LanguageModelProviderId) -> Option<Arc<dyn LanguageModelProvider>> {
@@ -283,9 +290,27 @@ impl LanguageModelRegistry {
             .iter()
             .find(|model| model.id() == selected_model.model)?
             .clone();
+        let model = self.with_request_cache(model);
         Some(ConfiguredModel { provider, model })
     }
 
+    pub fn request_cache(&self) -> Option<&Arc<RequestCache>> {
+        self.request_cache.as_ref()
+    }
+
+    /// Records or replays the completions of the models handed out from now on through `cache`.
+    pub fn set_request_cache(&mut self, cache: Option<RequestCache>) {
+        self.request_cache = cache.map(Arc::new);
+    }
+
+    /// Wraps `model` so that its completions go through the request cache, if there is one.
+    pub fn with_request_cache(&self, model: Arc<dyn LanguageModel>) -> Arc<dyn LanguageModel> {
+        match &self.request_cache {
+            Some(cache) => Arc::new(CachingLanguageModel::new(model, cache.clone())),
+            None => model,
+        }
+    }
+
     pub fn set_default_model(&mut self, model: Option<ConfiguredModel>, cx: &mut Context<Self>) {
         match (self.default_model.as_ref(), model.as_ref()) {
             (Some(old), Some(new)) if old.is_same_as(new) => {}
@@ -297,7 +322,7 @@ impl LanguageModelRegistry {
             let fast_model = provider.default_fast_model(cx)?;
             Some(ConfiguredModel {
                 provider: provider.clone(),
-                model: fast_model,
+                model: self.with_request_cache(fast_model),
             })
         });
         self.default_model = model;
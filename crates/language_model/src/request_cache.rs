@@ -0,0 +1,441 @@
+use crate::{
+    LanguageModel, LanguageModelCacheConfiguration, LanguageModelCompletionError,
+    LanguageModelCompletionEvent, LanguageModelId, LanguageModelName, LanguageModelProviderId,
+    LanguageModelProviderName, LanguageModelRequest, LanguageModelToolChoice,
+    LanguageModelToolSchemaFormat,
+};
+use anyhow::{Context as _, Result, anyhow};
+use futures::{FutureExt, StreamExt, future::BoxFuture, stream::BoxStream};
+use gpui::{App, AsyncApp};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+use util::ResultExt as _;
+
+/// A directory to record completions to, or replay them from.
+pub const CACHE_DIR_ENV_VAR: &str = "ZED_LLM_CACHE_DIR";
+/// One of `read_through` (the default), `record` or `replay`.
+pub const CACHE_MODE_ENV_VAR: &str = "ZED_LLM_CACHE_MODE";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RequestCacheMode {
+    /// Replay recorded responses, and record the ones that are missing.
+    #[default]
+    ReadThrough,
+    /// Always send requests, and record their responses, replacing earlier recordings.
+    Record,
+    /// Only replay recorded responses. Requests that weren't recorded fail, without using the
+    /// network.
+    Replay,
+}
+
+impl FromStr for RequestCacheMode {
+    type Err = anyhow::Error;
+
+    fn from_str(mode: &str) -> Result<Self> {
+        match mode {
+            "read_through" => Ok(Self::ReadThrough),
+            "record" => Ok(Self::Record),
+            "replay" => Ok(Self::Replay),
+            _ => Err(anyhow!(
+                "unknown request cache mode `{mode}`, expected `read_through`, `record` or `replay`"
+            )),
+        }
+    }
+}
+
+/// Completions recorded on disk, keyed by the request that produced them.
+///
+/// Used to run evals and tests against real models offline and deterministically.
+#[derive(Debug)]
+pub struct RequestCache {
+    dir: PathBuf,
+    mode: RequestCacheMode,
+}
+
+/// A completion, as stored in the cache directory.
+#[derive(Serialize, Deserialize)]
+struct RecordedCompletion {
+    provider: String,
+    model: String,
+    request: LanguageModelRequest,
+    events: Vec<LanguageModelCompletionEvent>,
+}
+
+impl RequestCache {
+    pub fn new(dir: impl Into<PathBuf>, mode: RequestCacheMode) -> Self {
+        Self {
+            dir: dir.into(),
+            mode,
+        }
+    }
+
+    /// The cache configured by [`CACHE_DIR_ENV_VAR`] and [`CACHE_MODE_ENV_VAR`], if any.
+    pub fn from_env() -> Result<Option<Self>> {
+        let Some(dir) = std::env::var_os(CACHE_DIR_ENV_VAR) else {
+            return Ok(None);
+        };
+        let mode = match std::env::var(CACHE_MODE_ENV_VAR) {
+            Ok(mode) => mode.parse()?,
+            Err(_) => RequestCacheMode::default(),
+        };
+        Ok(Some(Self::new(dir, mode)))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn mode(&self) -> RequestCacheMode {
+        self.mode
+    }
+
+    fn path_for(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.json"))
+    }
+}
+
+/// Strips the parts of a request that change between otherwise identical runs.
+fn normalize_request(request: &LanguageModelRequest) -> LanguageModelRequest {
+    LanguageModelRequest {
+        thread_id: None,
+        prompt_id: None,
+        ..request.clone()
+    }
+}
+
+/// The key a request's completion is stored under, which covers the model and the normalized
+/// request.
+pub fn request_cache_key(
+    provider: &LanguageModelProviderId,
+    model: &LanguageModelId,
+    request: &LanguageModelRequest,
+) -> Result<String> {
+    let mut hasher = Sha256::new();
+    hasher.update(provider.0.as_bytes());
+    hasher.update([0]);
+    hasher.update(model.0.as_bytes());
+    hasher.update([0]);
+    hasher.update(serde_json::to_vec(&normalize_request(request))?);
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// A model whose completions go through a [`RequestCache`].
+pub struct CachingLanguageModel {
+    model: Arc<dyn LanguageModel>,
+    cache: Arc<RequestCache>,
+}
+
+impl CachingLanguageModel {
+    pub fn new(model: Arc<dyn LanguageModel>, cache: Arc<RequestCache>) -> Self {
+        Self { model, cache }
+    }
+
+    fn record(
+        &self,
+        request: LanguageModelRequest,
+        path: PathBuf,
+        cx: &AsyncApp,
+    ) -> BoxFuture<
+        'static,
+        Result<
+            BoxStream<'static, Result<LanguageModelCompletionEvent, LanguageModelCompletionError>>,
+            LanguageModelCompletionError,
+        >,
+    > {
+        let recorded = Arc::new(Mutex::new(Some(RecordedCompletion {
+            provider: self.model.provider_id().0.to_string(),
+            model: self.model.id().0.to_string(),
+            request: normalize_request(&request),
+            events: Vec::new(),
+        })));
+        let executor = cx.background_executor().clone();
+        let response = self.model.stream_completion(request, cx);
+        async move {
+            let events = response.await?;
+            let events = events
+                .map({
+                    let recorded = recorded.clone();
+                    move |event| {
+                        let mut recorded = recorded.lock();
+                        match &event {
+                            Ok(event) => {
+                                if let Some(recorded) = recorded.as_mut() {
+                                    recorded.events.push(event.clone());
+                                }
+                            }
+                            // Don't record failed completions, so they're retried next time.
+                            Err(_) => *recorded = None,
+                        }
+                        event
+                    }
+                })
+                .chain(
+                    futures::stream::once(async move {
+                        if let Some(recorded) = recorded.lock().take() {
+                            executor
+                                .spawn(async move {
+                                    write_recorded_completion(&path, &recorded).log_err()
+                                })
+                                .detach();
+                        }
+                    })
+                    .filter_map(|()| futures::future::ready(None)),
+                );
+            Ok(events.boxed())
+        }
+        .boxed()
+    }
+}
+
+fn write_recorded_completion(path: &Path, recorded: &RecordedCompletion) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(recorded)?)
+        .with_context(|| format!("recording completion to {}", path.display()))
+}
+
+fn read_recorded_completion(path: &Path) -> Result<Option<RecordedCompletion>> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(serde_json::from_str(&contents).with_context(
+            || format!("parsing recorded completion {}", path.display()),
+        )?)),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error.into()),
+    }
+}
+
+impl LanguageModel for CachingLanguageModel {
+    fn id(&self) -> LanguageModelId {
+        self.model.id()
+    }
+
+    fn name(&self) -> LanguageModelName {
+        self.model.name()
+    }
+
+    fn provider_id(&self) -> LanguageModelProviderId {
+        self.model.provider_id()
+    }
+
+    fn provider_name(&self) -> LanguageModelProviderName {
+        self.model.provider_name()
+    }
+
+    fn upstream_provider_id(&self) -> LanguageModelProviderId {
+        self.model.upstream_provider_id()
+    }
+
+    fn upstream_provider_name(&self) -> LanguageModelProviderName {
+        self.model.upstream_provider_name()
+    }
+
+    fn telemetry_id(&self) -> String {
+        self.model.telemetry_id()
+    }
+
+    fn api_key(&self, cx: &App) -> Option<String> {
+        self.model.api_key(cx)
+    }
+
+    fn supports_images(&self) -> bool {
+        self.model.supports_images()
+    }
+
+    fn supports_tools(&self) -> bool {
+        self.model.supports_tools()
+    }
+
+    fn supports_tool_choice(&self, choice: LanguageModelToolChoice) -> bool {
+        self.model.supports_tool_choice(choice)
+    }
+
+    fn supports_burn_mode(&self) -> bool {
+        self.model.supports_burn_mode()
+    }
+
+    fn tool_input_format(&self) -> LanguageModelToolSchemaFormat {
+        self.model.tool_input_format()
+    }
+
+    fn max_token_count(&self) -> u64 {
+        self.model.max_token_count()
+    }
+
+    fn max_token_count_in_burn_mode(&self) -> Option<u64> {
+        self.model.max_token_count_in_burn_mode()
+    }
+
+    fn max_output_tokens(&self) -> Option<u64> {
+        self.model.max_output_tokens()
+    }
+
+    fn count_tokens(
+        &self,
+        request: LanguageModelRequest,
+        cx: &App,
+    ) -> BoxFuture<'static, Result<u64>> {
+        self.model.count_tokens(request, cx)
+    }
+
+    fn stream_completion(
+        &self,
+        request: LanguageModelRequest,
+        cx: &AsyncApp,
+    ) -> BoxFuture<
+        'static,
+        Result<
+            BoxStream<'static, Result<LanguageModelCompletionEvent, LanguageModelCompletionError>>,
+            LanguageModelCompletionError,
+        >,
+    > {
+        let key = match request_cache_key(&self.model.provider_id(), &self.model.id(), &request) {
+            Ok(key) => key,
+            Err(error) => return futures::future::ready(Err(error.into())).boxed(),
+        };
+        let path = self.cache.path_for(&key);
+        if self.cache.mode == RequestCacheMode::Record {
+            return self.record(request, path, cx);
+        }
+
+        let this = Self::new(self.model.clone(), self.cache.clone());
+        let mode = self.cache.mode;
+        let cx = cx.clone();
+        async move {
+            let recorded = cx
+                .background_spawn({
+                    let path = path.clone();
+                    async move { read_recorded_completion(&path) }
+                })
+                .await?;
+            match recorded {
+                Some(recorded) => {
+                    Ok(futures::stream::iter(recorded.events.into_iter().map(Ok)).boxed())
+                }
+                None if mode == RequestCacheMode::Replay => Err(anyhow!(
+                    "no recorded completion for {}/{} at {}",
+                    this.provider_id(),
+                    this.id().0,
+                    path.display()
+                )
+                .into()),
+                None => this.record(request, path, &cx).await,
+            }
+        }
+        .boxed()
+    }
+
+    fn cache_configuration(&self) -> Option<LanguageModelCacheConfiguration> {
+        self.model.cache_configuration()
+    }
+
+    #[cfg(any(test, feature = "test-support"))]
+    fn as_fake(&self) -> &crate::fake_provider::FakeLanguageModel {
+        self.model.as_fake()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LanguageModelRequestMessage, MessageContent, Role, StopReason};
+    use futures::StreamExt as _;
+    use gpui::TestAppContext;
+
+    fn request(text: &str) -> LanguageModelRequest {
+        LanguageModelRequest {
+            thread_id: Some("thread".into()),
+            messages: vec![LanguageModelRequestMessage {
+                role: Role::User,
+                content: vec![MessageContent::Text(text.into())],
+                cache: false,
+            }],
+            ..Default::default()
+        }
+    }
+
+    async fn collect(
+        model: &CachingLanguageModel,
+        request: LanguageModelRequest,
+        cx: &mut TestAppContext,
+    ) -> Result<Vec<LanguageModelCompletionEvent>, LanguageModelCompletionError> {
+        let events = cx.update(|cx| model.stream_completion(request, &cx.to_async()));
+        let events = events.await?;
+        Ok(events.map(|event| event.unwrap()).collect().await)
+    }
+
+    #[gpui::test]
+    async fn test_record_and_replay(cx: &mut TestAppContext) {
+        let dir = tempfile::tempdir().unwrap();
+        let fake = Arc::new(crate::fake_provider::FakeLanguageModel::default());
+        let recording = CachingLanguageModel::new(
+            fake.clone(),
+            Arc::new(RequestCache::new(dir.path(), RequestCacheMode::Record)),
+        );
+
+        let events = cx.update(|cx| recording.stream_completion(request("hi"), &cx.to_async()));
+        cx.run_until_parked();
+        fake.send_last_completion_stream_text_chunk("Hello");
+        fake.send_last_completion_stream_event(LanguageModelCompletionEvent::Stop(
+            StopReason::EndTurn,
+        ));
+        fake.end_last_completion_stream();
+        let events = events.await.unwrap().collect::<Vec<_>>().await;
+        assert_eq!(events.len(), 2);
+        cx.run_until_parked();
+
+        // Replaying doesn't reach the model, and ignores the thread id.
+        let replaying = CachingLanguageModel::new(
+            fake.clone(),
+            Arc::new(RequestCache::new(dir.path(), RequestCacheMode::Replay)),
+        );
+        let mut other_thread = request("hi");
+        other_thread.thread_id = Some("another thread".into());
+        assert_eq!(
+            collect(&replaying, other_thread, cx).await.unwrap(),
+            vec![
+                LanguageModelCompletionEvent::Text("Hello".into()),
+                LanguageModelCompletionEvent::Stop(StopReason::EndTurn),
+            ]
+        );
+        assert_eq!(fake.completion_count(), 0);
+
+        let error = collect(&replaying, request("bye"), cx).await.unwrap_err();
+        assert!(
+            error.to_string().contains("no recorded completion"),
+            "unexpected error: {error}"
+        );
+        assert_eq!(fake.completion_count(), 0);
+    }
+
+    #[gpui::test]
+    async fn test_read_through(cx: &mut TestAppContext) {
+        let dir = tempfile::tempdir().unwrap();
+        let fake = Arc::new(crate::fake_provider::FakeLanguageModel::default());
+        let model = CachingLanguageModel::new(
+            fake.clone(),
+            Arc::new(RequestCache::new(dir.path(), RequestCacheMode::ReadThrough)),
+        );
+
+        let events = cx.update(|cx| model.stream_completion(request("hi"), &cx.to_async()));
+        let events = cx.executor().spawn(events);
+        cx.run_until_parked();
+        assert_eq!(fake.completion_count(), 1);
+        fake.send_last_completion_stream_text_chunk("Hello");
+        fake.end_last_completion_stream();
+        events.await.unwrap().collect::<Vec<_>>().await;
+        cx.run_until_parked();
+
+        assert_eq!(
+            collect(&model, request("hi"), cx).await.unwrap(),
+            vec![LanguageModelCompletionEvent::Text("Hello".into())]
+        );
+        assert_eq!(fake.completion_count(), 0);
+    }
+}
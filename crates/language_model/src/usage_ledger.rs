@@ -0,0 +1,788 @@
+use crate::{LanguageModelId, LanguageModelProviderId, TokenUsage};
+use anyhow::{Result, anyhow};
+use chrono::{Datelike as _, Local, NaiveDate};
+use collections::HashMap;
+use futures::{FutureExt as _, future::Shared};
+use gpui::{App, AppContext as _, Context, Entity, EventEmitter, Global, Task};
+use indoc::indoc;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlez::{
+    bindable::{Bind, Column, StaticColumnCount},
+    connection::Connection,
+    statement::Statement,
+};
+use std::{
+    fmt,
+    path::PathBuf,
+    sync::{Arc, LazyLock, Mutex},
+};
+use thiserror::Error;
+use util::ResultExt as _;
+
+static ZED_STATELESS: LazyLock<bool> =
+    LazyLock::new(|| std::env::var("ZED_STATELESS").map_or(false, |v| !v.is_empty()));
+
+/// The model id that sets the price of every model of a provider without a price of its own.
+pub const ANY_MODEL: &str = "*";
+
+pub fn init(cx: &mut App) {
+    let path = if *ZED_STATELESS || cfg!(any(test, feature = "test-support")) {
+        None
+    } else {
+        Some(paths::data_dir().join("usage").join("usage.db"))
+    };
+    let ledger = cx.new(|cx| UsageLedger::new(path, cx));
+    cx.set_global(GlobalUsageLedger(ledger));
+}
+
+struct GlobalUsageLedger(Entity<UsageLedger>);
+
+impl Global for GlobalUsageLedger {}
+
+/// What a language model request was made for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageSource {
+    AgentThread,
+    InlineAssist,
+    CommitMessage,
+    EditPrediction,
+}
+
+impl UsageSource {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::AgentThread => "agent_thread",
+            Self::InlineAssist => "inline_assist",
+            Self::CommitMessage => "commit_message",
+            Self::EditPrediction => "edit_prediction",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::AgentThread => "Agent Threads",
+            Self::InlineAssist => "Inline Assist",
+            Self::CommitMessage => "Commit Messages",
+            Self::EditPrediction => "Edit Predictions",
+        }
+    }
+}
+
+impl std::str::FromStr for UsageSource {
+    type Err = anyhow::Error;
+
+    fn from_str(source: &str) -> Result<Self> {
+        match source {
+            "agent_thread" => Ok(Self::AgentThread),
+            "inline_assist" => Ok(Self::InlineAssist),
+            "commit_message" => Ok(Self::CommitMessage),
+            "edit_prediction" => Ok(Self::EditPrediction),
+            _ => Err(anyhow!("unknown usage source: {source}")),
+        }
+    }
+}
+
+/// A model's price, in US dollars per million tokens.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ModelPrice {
+    pub input: f64,
+    pub output: f64,
+    /// The price of writing input tokens to the prompt cache. Defaults to the input price.
+    #[serde(default)]
+    pub cache_write: Option<f64>,
+    /// The price of reading input tokens from the prompt cache. Defaults to the input price.
+    #[serde(default)]
+    pub cache_read: Option<f64>,
+}
+
+impl ModelPrice {
+    pub const FREE: Self = Self::new(0., 0.);
+
+    pub const fn new(input: f64, output: f64) -> Self {
+        Self {
+            input,
+            output,
+            cache_write: None,
+            cache_read: None,
+        }
+    }
+
+    pub const fn with_cache(self, cache_write: f64, cache_read: f64) -> Self {
+        Self {
+            cache_write: Some(cache_write),
+            cache_read: Some(cache_read),
+            ..self
+        }
+    }
+
+    /// The cost of `usage`, in US dollars.
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        let per_token = |price: f64, tokens: u64| price * tokens as f64 / 1_000_000.;
+        per_token(self.input, usage.input_tokens)
+            + per_token(self.output, usage.output_tokens)
+            + per_token(
+                self.cache_write.unwrap_or(self.input),
+                usage.cache_creation_input_tokens,
+            )
+            + per_token(
+                self.cache_read.unwrap_or(self.input),
+                usage.cache_read_input_tokens,
+            )
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetAction {
+    /// Let requests through, but warn that the budget was exceeded.
+    #[default]
+    Warn,
+    /// Fail requests until the budget's period is over.
+    Block,
+}
+
+/// Limits on how much to spend on language models.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct UsageBudget {
+    /// The most to spend per day, in US dollars.
+    #[serde(default)]
+    pub daily: Option<f64>,
+    /// The most to spend per calendar month, in US dollars.
+    #[serde(default)]
+    pub monthly: Option<f64>,
+    #[serde(default)]
+    pub when_exceeded: BudgetAction,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BudgetPeriod {
+    Day,
+    Month,
+}
+
+impl fmt::Display for BudgetPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Day => write!(f, "daily"),
+            Self::Month => write!(f, "monthly"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Error)]
+#[error("Exceeded the {period} language model budget of ${limit:.2} (spent ${spent:.2}).")]
+pub struct BudgetExceeded {
+    pub period: BudgetPeriod,
+    pub limit: f64,
+    pub spent: f64,
+}
+
+/// The tokens used by a single language model request.
+#[derive(Clone, Debug)]
+pub struct UsageRecord {
+    pub source: UsageSource,
+    /// The agent thread the request was made for.
+    pub thread_id: Option<String>,
+    pub provider: LanguageModelProviderId,
+    pub model: LanguageModelId,
+    pub usage: TokenUsage,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct UsageTotals {
+    pub requests: u64,
+    pub usage: TokenUsage,
+    /// The cost of the requests to models with a price, in US dollars.
+    pub cost: f64,
+    /// How many of the requests were to models without a price.
+    pub unpriced_requests: u64,
+}
+
+/// How to break down usage totals.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UsageGrouping {
+    Day,
+    /// By `provider_id/model_id`.
+    Model,
+    Source,
+    Thread,
+}
+
+impl UsageGrouping {
+    fn column(&self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Model => "provider || '/' || model",
+            Self::Source => "source",
+            Self::Thread => "thread_id",
+        }
+    }
+}
+
+pub enum UsageLedgerEvent {
+    BudgetExceeded(BudgetExceeded),
+}
+
+/// Keeps track of the tokens used by language model requests and what they cost, in a local
+/// database.
+pub struct UsageLedger {
+    database: Shared<Task<Result<Arc<UsageDatabase>, Arc<anyhow::Error>>>>,
+    prices: HashMap<LanguageModelProviderId, HashMap<String, ModelPrice>>,
+    budget: UsageBudget,
+    today: NaiveDate,
+    spent_today: f64,
+    spent_this_month: f64,
+    exceeded: Option<BudgetExceeded>,
+}
+
+impl EventEmitter<UsageLedgerEvent> for UsageLedger {}
+
+impl UsageLedger {
+    pub fn global(cx: &App) -> Option<Entity<Self>> {
+        cx.try_global::<GlobalUsageLedger>()
+            .map(|ledger| ledger.0.clone())
+    }
+
+    /// Records `record` in the global ledger, if there is one.
+    pub fn record_global(record: UsageRecord, cx: &mut App) {
+        if let Some(ledger) = Self::global(cx) {
+            ledger.update(cx, |ledger, cx| ledger.record(record, cx));
+        }
+    }
+
+    /// Fails if a budget that blocks requests was exceeded.
+    pub fn check_global_budget(cx: &App) -> Result<(), BudgetExceeded> {
+        match Self::global(cx) {
+            Some(ledger) => ledger.read(cx).check_budget(),
+            None => Ok(()),
+        }
+    }
+
+    /// Creates a ledger stored at `path`, or in memory if there's no path.
+    pub fn new(path: Option<PathBuf>, cx: &mut Context<Self>) -> Self {
+        let today = Local::now().date_naive();
+        let database = cx
+            .background_spawn(async move {
+                UsageDatabase::new(path, start_of_month(today))
+                    .map(Arc::new)
+                    .map_err(Arc::new)
+            })
+            .shared();
+
+        cx.spawn({
+            let database = database.clone();
+            async move |this, cx| {
+                let Some(database) = database.await.log_err() else {
+                    return;
+                };
+                this.update(cx, |this, cx| {
+                    for (day, totals) in &database.spent_at_open {
+                        if *day == day_key(this.today) {
+                            this.spent_today += totals.cost;
+                        }
+                        if start_of_month(this.today) == start_of_month(today) {
+                            this.spent_this_month += totals.cost;
+                        }
+                    }
+                    this.check_exceeded(cx);
+                    cx.notify();
+                })
+                .ok();
+            }
+        })
+        .detach();
+
+        Self {
+            database,
+            prices: HashMap::default(),
+            budget: UsageBudget::default(),
+            today,
+            spent_today: 0.,
+            spent_this_month: 0.,
+            exceeded: None,
+        }
+    }
+
+    /// Sets the price of each provider's models, where [`ANY_MODEL`] applies to the models
+    /// without a price of their own.
+    pub fn set_prices(
+        &mut self,
+        prices: HashMap<LanguageModelProviderId, HashMap<String, ModelPrice>>,
+        cx: &mut Context<Self>,
+    ) {
+        self.prices = prices;
+        cx.notify();
+    }
+
+    pub fn set_budget(&mut self, budget: UsageBudget, cx: &mut Context<Self>) {
+        self.budget = budget;
+        self.exceeded = None;
+        self.check_exceeded(cx);
+        cx.notify();
+    }
+
+    pub fn budget(&self) -> UsageBudget {
+        self.budget
+    }
+
+    pub fn price(
+        &self,
+        provider: &LanguageModelProviderId,
+        model: &LanguageModelId,
+    ) -> Option<ModelPrice> {
+        let prices = self.prices.get(provider)?;
+        prices
+            .get(model.0.as_ref())
+            .or_else(|| prices.get(ANY_MODEL))
+            .copied()
+    }
+
+    pub fn record(&mut self, record: UsageRecord, cx: &mut Context<Self>) {
+        let now = Local::now();
+        self.roll_over(now.date_naive());
+        let cost = self
+            .price(&record.provider, &record.model)
+            .map(|price| price.cost(&record.usage));
+        if let Some(cost) = cost {
+            self.spent_today += cost;
+            self.spent_this_month += cost;
+        }
+
+        let database = self.database.clone();
+        cx.background_spawn(async move {
+            let database = database.await.map_err(|err| anyhow!(err))?;
+            database.insert(now.timestamp(), day_key(now.date_naive()), &record, cost)
+        })
+        .detach_and_log_err(cx);
+
+        self.check_exceeded(cx);
+        cx.notify();
+    }
+
+    /// The amounts spent today and this month, in US dollars.
+    pub fn spent(&self) -> (f64, f64) {
+        let today = Local::now().date_naive();
+        if self.today == today {
+            (self.spent_today, self.spent_this_month)
+        } else if start_of_month(self.today) == start_of_month(today) {
+            (0., self.spent_this_month)
+        } else {
+            (0., 0.)
+        }
+    }
+
+    /// The budget that was exceeded, if any.
+    pub fn exceeded_budget(&self) -> Option<BudgetExceeded> {
+        let (spent_today, spent_this_month) = self.spent();
+        if let Some(limit) = self.budget.daily
+            && spent_today >= limit
+        {
+            return Some(BudgetExceeded {
+                period: BudgetPeriod::Day,
+                limit,
+                spent: spent_today,
+            });
+        }
+        if let Some(limit) = self.budget.monthly
+            && spent_this_month >= limit
+        {
+            return Some(BudgetExceeded {
+                period: BudgetPeriod::Month,
+                limit,
+                spent: spent_this_month,
+            });
+        }
+        None
+    }
+
+    /// Fails if a budget that blocks requests was exceeded.
+    pub fn check_budget(&self) -> Result<(), BudgetExceeded> {
+        match self.exceeded_budget() {
+            Some(exceeded) if self.budget.when_exceeded == BudgetAction::Block => Err(exceeded),
+            _ => Ok(()),
+        }
+    }
+
+    /// Adds up the usage since `since` (or ever), broken down by `group_by`.
+    pub fn totals(
+        &self,
+        group_by: UsageGrouping,
+        since: Option<NaiveDate>,
+        cx: &App,
+    ) -> Task<Result<Vec<(String, UsageTotals)>>> {
+        let database = self.database.clone();
+        cx.background_spawn(async move {
+            let database = database.await.map_err(|err| anyhow!(err))?;
+            database.totals(group_by, since, None)
+        })
+    }
+
+    /// Adds up the usage of an agent thread.
+    pub fn thread_totals(&self, thread_id: String, cx: &App) -> Task<Result<UsageTotals>> {
+        let database = self.database.clone();
+        cx.background_spawn(async move {
+            let database = database.await.map_err(|err| anyhow!(err))?;
+            let totals = database.totals(UsageGrouping::Thread, None, Some(thread_id))?;
+            Ok(totals
+                .into_iter()
+                .next()
+                .map(|(_, totals)| totals)
+                .unwrap_or_default())
+        })
+    }
+
+    fn roll_over(&mut self, today: NaiveDate) {
+        if today == self.today {
+            return;
+        }
+        if start_of_month(today) != start_of_month(self.today) {
+            self.spent_this_month = 0.;
+        }
+        self.spent_today = 0.;
+        self.today = today;
+        self.exceeded = None;
+    }
+
+    fn check_exceeded(&mut self, cx: &mut Context<Self>) {
+        let exceeded = self.exceeded_budget();
+        let newly_exceeded = exceeded.filter(|exceeded| {
+            self.exceeded
+                .is_none_or(|previous| previous.period != exceeded.period)
+        });
+        self.exceeded = exceeded;
+        if let Some(exceeded) = newly_exceeded {
+            cx.emit(UsageLedgerEvent::BudgetExceeded(exceeded));
+        }
+    }
+}
+
+pub fn start_of_month(day: NaiveDate) -> NaiveDate {
+    day.with_day(1).unwrap_or(day)
+}
+
+fn day_key(day: NaiveDate) -> String {
+    day.format("%Y-%m-%d").to_string()
+}
+
+impl StaticColumnCount for TokenUsage {
+    fn column_count() -> usize {
+        4
+    }
+}
+
+impl Bind for TokenUsage {
+    fn bind(&self, statement: &Statement, start_index: i32) -> Result<i32> {
+        (
+            self.input_tokens,
+            self.output_tokens,
+            self.cache_creation_input_tokens,
+            self.cache_read_input_tokens,
+        )
+            .bind(statement, start_index)
+    }
+}
+
+impl Column for TokenUsage {
+    fn column(statement: &mut Statement, start_index: i32) -> Result<(Self, i32)> {
+        let (
+            (input_tokens, output_tokens, cache_creation_input_tokens, cache_read_input_tokens),
+            next_index,
+        ) = <(u64, u64, u64, u64)>::column(statement, start_index)?;
+        Ok((
+            TokenUsage {
+                input_tokens,
+                output_tokens,
+                cache_creation_input_tokens,
+                cache_read_input_tokens,
+            },
+            next_index,
+        ))
+    }
+}
+
+struct UsageDatabase {
+    connection: Mutex<Connection>,
+    /// The usage per day since the start of the month, when the database was opened.
+    spent_at_open: Vec<(String, UsageTotals)>,
+}
+
+impl UsageDatabase {
+    fn new(path: Option<PathBuf>, month_start: NaiveDate) -> Result<Self> {
+        let connection = if let Some(path) = path {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            Connection::open_file(&path.to_string_lossy())
+        } else {
+            Connection::open_memory(None)
+        };
+
+        connection.exec(indoc! {"
+            CREATE TABLE IF NOT EXISTS usage (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                recorded_at INTEGER NOT NULL,
+                day TEXT NOT NULL,
+                source TEXT NOT NULL,
+                thread_id TEXT,
+                provider TEXT NOT NULL,
+                model TEXT NOT NULL,
+                input_tokens INTEGER NOT NULL,
+                output_tokens INTEGER NOT NULL,
+                cache_creation_input_tokens INTEGER NOT NULL,
+                cache_read_input_tokens INTEGER NOT NULL,
+                cost REAL
+            )
+        "})?()
+        .map_err(|e| anyhow!("Failed to create usage table: {}", e))?;
+        connection.exec(indoc! {"
+            CREATE INDEX IF NOT EXISTS usage_by_day ON usage (day)
+        "})?()?;
+        connection.exec(indoc! {"
+            CREATE INDEX IF NOT EXISTS usage_by_thread ON usage (thread_id)
+        "})?()?;
+
+        let mut database = Self {
+            connection: Mutex::new(connection),
+            spent_at_open: Vec::new(),
+        };
+        database.spent_at_open = database.totals(UsageGrouping::Day, Some(month_start), None)?;
+        Ok(database)
+    }
+
+    fn insert(
+        &self,
+        recorded_at: i64,
+        day: String,
+        record: &UsageRecord,
+        cost: Option<f64>,
+    ) -> Result<()> {
+        let connection = self.connection.lock().unwrap();
+        let mut insert = connection.exec_bound::<(
+            (i64, String, &str, Option<&str>, &str, &str),
+            TokenUsage,
+            Option<f64>,
+        )>(indoc! {"
+            INSERT INTO usage (
+                recorded_at, day, source, thread_id, provider, model,
+                input_tokens, output_tokens, cache_creation_input_tokens, cache_read_input_tokens,
+                cost
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "})?;
+        insert((
+            (
+                recorded_at,
+                day,
+                record.source.as_str(),
+                record.thread_id.as_deref(),
+                record.provider.0.as_ref(),
+                record.model.0.as_ref(),
+            ),
+            record.usage,
+            cost,
+        ))
+    }
+
+    fn totals(
+        &self,
+        group_by: UsageGrouping,
+        since: Option<NaiveDate>,
+        thread_id: Option<String>,
+    ) -> Result<Vec<(String, UsageTotals)>> {
+        let connection = self.connection.lock().unwrap();
+        let mut select = connection
+            .select_bound::<(Option<String>, Option<String>), (String, u64, TokenUsage, f64, u64)>(
+                &format!(
+                    indoc! {"
+                    SELECT
+                        {column}, COUNT(*),
+                        SUM(input_tokens), SUM(output_tokens),
+                        SUM(cache_creation_input_tokens), SUM(cache_read_input_tokens),
+                        TOTAL(cost), SUM(cost IS NULL)
+                    FROM usage
+                    WHERE {column} IS NOT NULL
+                    AND (?1 IS NULL OR day >= ?1)
+                    AND (?2 IS NULL OR thread_id = ?2)
+                    GROUP BY 1
+                    ORDER BY 1
+                "},
+                    column = group_by.column()
+                ),
+            )?;
+        let rows = select((since.map(day_key), thread_id))?;
+        Ok(rows
+            .into_iter()
+            .map(|(key, requests, usage, cost, unpriced_requests)| {
+                (
+                    key,
+                    UsageTotals {
+                        requests,
+                        usage,
+                        cost,
+                        unpriced_requests,
+                    },
+                )
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+
+    fn record(model: &str, thread_id: Option<&str>, input_tokens: u64) -> UsageRecord {
+        UsageRecord {
+            source: UsageSource::AgentThread,
+            thread_id: thread_id.map(ToString::to_string),
+            provider: LanguageModelProviderId::new("anthropic"),
+            model: LanguageModelId::from(model.to_string()),
+            usage: TokenUsage {
+                input_tokens,
+                output_tokens: 500_000,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[gpui::test]
+    async fn test_totals(cx: &mut TestAppContext) {
+        let ledger = cx.new(|cx| UsageLedger::new(None, cx));
+        ledger.update(cx, |ledger, cx| {
+            ledger.set_prices(
+                HashMap::from_iter([(
+                    LanguageModelProviderId::new("anthropic"),
+                    HashMap::from_iter([("priced".to_string(), ModelPrice::new(3., 2.))]),
+                )]),
+                cx,
+            );
+            ledger.record(record("priced", Some("thread-1"), 1_000_000), cx);
+            ledger.record(record("priced", Some("thread-2"), 2_000_000), cx);
+            ledger.record(record("unpriced", Some("thread-1"), 1_000_000), cx);
+        });
+        cx.run_until_parked();
+
+        let by_model = ledger
+            .read_with(cx, |ledger, cx| {
+                ledger.totals(UsageGrouping::Model, None, cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            by_model,
+            vec![
+                (
+                    "anthropic/priced".to_string(),
+                    UsageTotals {
+                        requests: 2,
+                        usage: TokenUsage {
+                            input_tokens: 3_000_000,
+                            output_tokens: 1_000_000,
+                            ..Default::default()
+                        },
+                        cost: 11.,
+                        unpriced_requests: 0,
+                    }
+                ),
+                (
+                    "anthropic/unpriced".to_string(),
+                    UsageTotals {
+                        requests: 1,
+                        usage: TokenUsage {
+                            input_tokens: 1_000_000,
+                            output_tokens: 500_000,
+                            ..Default::default()
+                        },
+                        cost: 0.,
+                        unpriced_requests: 1,
+                    }
+                )
+            ]
+        );
+
+        let thread_totals = ledger
+            .read_with(cx, |ledger, cx| {
+                ledger.thread_totals("thread-1".to_string(), cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(thread_totals.requests, 2);
+        assert_eq!(thread_totals.usage.input_tokens, 2_000_000);
+        assert_eq!(thread_totals.cost, 4.);
+
+        let by_day = ledger
+            .read_with(cx, |ledger, cx| {
+                ledger.totals(UsageGrouping::Day, Some(Local::now().date_naive()), cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(by_day.len(), 1);
+        assert_eq!(by_day[0].1.requests, 3);
+    }
+
+    #[gpui::test]
+    async fn test_budget(cx: &mut TestAppContext) {
+        let ledger = cx.new(|cx| UsageLedger::new(None, cx));
+        let exceeded = Arc::new(Mutex::new(Vec::new()));
+        cx.update(|cx| {
+            let exceeded = exceeded.clone();
+            cx.subscribe(&ledger, move |_, event, _| match event {
+                UsageLedgerEvent::BudgetExceeded(budget) => {
+                    exceeded.lock().unwrap().push(budget.period)
+                }
+            })
+            .detach();
+        });
+        cx.run_until_parked();
+
+        ledger.update(cx, |ledger, cx| {
+            ledger.set_prices(
+                HashMap::from_iter([(
+                    LanguageModelProviderId::new("anthropic"),
+                    HashMap::from_iter([(ANY_MODEL.to_string(), ModelPrice::new(1., 0.))]),
+                )]),
+                cx,
+            );
+            ledger.set_budget(
+                UsageBudget {
+                    daily: Some(2.),
+                    monthly: None,
+                    when_exceeded: BudgetAction::Block,
+                },
+                cx,
+            );
+            ledger.record(record("any", None, 1_000_000), cx);
+            assert_eq!(ledger.check_budget(), Ok(()));
+            ledger.record(record("any", None, 1_000_000), cx);
+            assert_eq!(
+                ledger.check_budget(),
+                Err(BudgetExceeded {
+                    period: BudgetPeriod::Day,
+                    limit: 2.,
+                    spent: 2.,
+                })
+            );
+            ledger.record(record("any", None, 1_000_000), cx);
+
+            // Warning about the budget lets requests through.
+            ledger.set_budget(
+                UsageBudget {
+                    daily: Some(2.),
+                    monthly: None,
+                    when_exceeded: BudgetAction::Warn,
+                },
+                cx,
+            );
+            assert_eq!(ledger.check_budget(), Ok(()));
+            assert!(ledger.exceeded_budget().is_some());
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            *exceeded.lock().unwrap(),
+            vec![BudgetPeriod::Day, BudgetPeriod::Day]
+        );
+    }
+}
@@ -2,9 +2,9 @@ use std::sync::Arc;
 
 use ::settings::{Settings, SettingsStore};
 use client::{Client, UserStore};
-use collections::HashSet;
+use collections::{HashMap, HashSet};
 use gpui::{App, Context, Entity};
-use language_model::{LanguageModelProviderId, LanguageModelRegistry};
+use language_model::{LanguageModelProviderId, LanguageModelRegistry, UsageLedger};
 use provider::deepseek::DeepSeekLanguageModelProvider;
 
 pub mod provider;
@@ -48,7 +48,9 @@ pub fn init(user_store: Entity<UserStore>, client: Arc<Client>, cx: &mut App) {
             cx,
         );
     });
+    update_usage_ledger(cx);
     cx.observe_global::<SettingsStore>(move |cx| {
+        update_usage_ledger(cx);
         let openai_compatible_providers_new = AllLanguageModelSettings::get_global(cx)
             .openai_compatible
             .keys()
@@ -68,6 +70,40 @@ pub fn init(user_store: Entity<UserStore>, client: Arc<Client>, cx: &mut App) {
         }
     })
     .detach();
+
+    provider::extension::init(cx);
+}
+
+/// Gives the usage ledger the built-in prices of each provider's models, overridden by the ones
+/// in the settings, and the budget.
+fn update_usage_ledger(cx: &mut App) {
+    let Some(ledger) = UsageLedger::global(cx) else {
+        return;
+    };
+    let settings = AllLanguageModelSettings::get_global(cx);
+    let mut prices = HashMap::from_iter([
+        provider::anthropic::model_prices(),
+        provider::lmstudio::model_prices(),
+        provider::ollama::model_prices(),
+        provider::open_ai::model_prices(),
+    ]);
+    for (provider, provider_prices) in &settings.pricing {
+        prices
+            .entry(LanguageModelProviderId::from(provider.clone()))
+            .or_default()
+            .extend(
+                provider_prices
+                    .iter()
+                    .map(|(model, price)| (model.clone(), *price)),
+            );
+    }
+    let budget = settings.budget;
+    ledger.update(cx, |ledger, cx| {
+        if ledger.budget() != budget {
+            ledger.set_budget(budget, cx);
+        }
+        ledger.set_prices(prices, cx);
+    });
 }
 
 fn register_openai_compatible_providers(
@@ -3,6 +3,7 @@ pub mod bedrock;
 pub mod cloud;
 pub mod copilot_chat;
 pub mod deepseek;
+pub mod extension;
 pub mod google;
 pub mod lmstudio;
 pub mod mistral;
@@ -19,7 +19,7 @@ use language_model::{
     LanguageModelCompletionError, LanguageModelId, LanguageModelName, LanguageModelProvider,
     LanguageModelProviderId, LanguageModelProviderName, LanguageModelProviderState,
     LanguageModelRequest, LanguageModelToolChoice, LanguageModelToolResultContent, MessageContent,
-    RateLimiter, Role,
+    ModelPrice, RateLimiter, Role,
 };
 use language_model::{LanguageModelCompletionEvent, LanguageModelToolUse, StopReason};
 use schemars::JsonSchema;
@@ -317,6 +317,32 @@ pub struct AnthropicModel {
     request_limiter: RateLimiter,
 }
 
+/// Anthropic's list prices for its models, for the usage ledger.
+pub fn model_prices() -> (LanguageModelProviderId, HashMap<String, ModelPrice>) {
+    let prices = anthropic::Model::iter()
+        .filter_map(|model| {
+            let price = match model {
+                anthropic::Model::ClaudeOpus4
+                | anthropic::Model::ClaudeOpus4Thinking
+                | anthropic::Model::ClaudeOpus4_1
+                | anthropic::Model::ClaudeOpus4_1Thinking
+                | anthropic::Model::Claude3Opus => ModelPrice::new(15., 75.).with_cache(18.75, 1.5),
+                anthropic::Model::ClaudeSonnet4
+                | anthropic::Model::ClaudeSonnet4Thinking
+                | anthropic::Model::Claude3_7Sonnet
+                | anthropic::Model::Claude3_7SonnetThinking
+                | anthropic::Model::Claude3_5Sonnet
+                | anthropic::Model::Claude3Sonnet => ModelPrice::new(3., 15.).with_cache(3.75, 0.3),
+                anthropic::Model::Claude3_5Haiku => ModelPrice::new(0.8, 4.).with_cache(1., 0.08),
+                anthropic::Model::Claude3Haiku => ModelPrice::new(0.25, 1.25).with_cache(0.3, 0.03),
+                anthropic::Model::Custom { .. } => return None,
+            };
+            Some((model.id().to_string(), price))
+        })
+        .collect();
+    (PROVIDER_ID, prices)
+}
+
 pub fn count_anthropic_tokens(
     request: LanguageModelRequest,
     cx: &App,
@@ -0,0 +1,780 @@
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result, anyhow};
+use credentials_provider::CredentialsProvider;
+use extension::{
+    Extension, ExtensionHostProxy, ExtensionLanguageModelProviderProxy,
+    LanguageModelProviderManifestEntry, LlmCompletionEvent, LlmCompletionRequest, LlmImage,
+    LlmMessageContent, LlmMessageRole, LlmModelInfo, LlmRequestMessage, LlmStopReason, LlmThinking,
+    LlmToolChoice, LlmToolDefinition, LlmToolResult, LlmToolResultContent, LlmToolUse,
+};
+use futures::{FutureExt, StreamExt, future::BoxFuture, stream::BoxStream};
+use gpui::{AnyView, App, AsyncApp, BackgroundExecutor, Context, Entity, Task, Window};
+use language_model::{
+    AuthenticateError, LanguageModel, LanguageModelCompletionError, LanguageModelCompletionEvent,
+    LanguageModelId, LanguageModelImage, LanguageModelName, LanguageModelProvider,
+    LanguageModelProviderId, LanguageModelProviderName, LanguageModelProviderState,
+    LanguageModelRegistry, LanguageModelRequest, LanguageModelToolChoice,
+    LanguageModelToolResultContent, LanguageModelToolUse, MessageContent, RateLimiter, Role,
+    StopReason, TokenUsage,
+};
+use ui::{ElevationIndex, prelude::*};
+use ui_input::SingleLineInput;
+use util::ResultExt;
+
+pub fn init(cx: &mut App) {
+    let proxy = ExtensionHostProxy::default_global(cx);
+    proxy.register_language_model_provider_proxy(LanguageModelProviderRegistryProxy);
+}
+
+struct LanguageModelProviderRegistryProxy;
+
+impl ExtensionLanguageModelProviderProxy for LanguageModelProviderRegistryProxy {
+    fn register_language_model_provider(
+        &self,
+        extension: Arc<dyn Extension>,
+        provider_id: Arc<str>,
+        provider: LanguageModelProviderManifestEntry,
+        cx: &mut App,
+    ) {
+        let provider = ExtensionLanguageModelProvider::new(extension, provider_id, provider, cx);
+        // We don't log an error, because "not signed in" is also an error.
+        provider.authenticate(cx).detach();
+        LanguageModelRegistry::global(cx).update(cx, |registry, cx| {
+            registry.register_provider(provider, cx);
+        });
+    }
+
+    fn unregister_language_model_provider(
+        &self,
+        extension_id: Arc<str>,
+        provider_id: Arc<str>,
+        cx: &mut App,
+    ) {
+        LanguageModelRegistry::global(cx).update(cx, |registry, cx| {
+            registry.unregister_provider(extension_provider_id(&extension_id, &provider_id), cx);
+        });
+    }
+}
+
+/// Returns the id under which a provider of the given extension is registered.
+///
+/// The id is namespaced by the extension, so that an extension can't replace a built-in provider
+/// or a provider of another extension.
+pub fn extension_provider_id(extension_id: &str, provider_id: &str) -> LanguageModelProviderId {
+    LanguageModelProviderId::from(format!("{extension_id}/{provider_id}"))
+}
+
+pub struct ExtensionLanguageModelProvider {
+    id: LanguageModelProviderId,
+    name: LanguageModelProviderName,
+    state: Entity<State>,
+}
+
+pub struct State {
+    extension: Arc<dyn Extension>,
+    provider_id: Arc<str>,
+    credentials_url: String,
+    requires_api_key: bool,
+    api_key: Option<String>,
+    models: Vec<LlmModelInfo>,
+}
+
+impl State {
+    fn is_authenticated(&self) -> bool {
+        !self.requires_api_key || self.api_key.is_some()
+    }
+
+    fn reset_api_key(&self, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let credentials_provider = <dyn CredentialsProvider>::global(cx);
+        let credentials_url = self.credentials_url.clone();
+        cx.spawn(async move |this, cx| {
+            credentials_provider
+                .delete_credentials(&credentials_url, &cx)
+                .await
+                .log_err();
+            this.update(cx, |this, cx| {
+                this.api_key = None;
+                this.models.clear();
+                cx.notify();
+            })
+        })
+    }
+
+    fn set_api_key(&mut self, api_key: String, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let credentials_provider = <dyn CredentialsProvider>::global(cx);
+        let credentials_url = self.credentials_url.clone();
+        cx.spawn(async move |this, cx| {
+            credentials_provider
+                .write_credentials(&credentials_url, "Bearer", api_key.as_bytes(), &cx)
+                .await
+                .log_err();
+            this.update(cx, |this, cx| {
+                this.api_key = Some(api_key);
+                cx.notify();
+                this.fetch_models(cx)
+            })?
+            .await
+        })
+    }
+
+    fn authenticate(&self, cx: &mut Context<Self>) -> Task<Result<(), AuthenticateError>> {
+        if self.is_authenticated() && !self.models.is_empty() {
+            return Task::ready(Ok(()));
+        }
+
+        let credentials_provider = <dyn CredentialsProvider>::global(cx);
+        let credentials_url = self.credentials_url.clone();
+        let requires_api_key = self.requires_api_key;
+        let api_key = self.api_key.clone();
+        cx.spawn(async move |this, cx| {
+            let api_key = match api_key {
+                Some(api_key) => Some(api_key),
+                None if requires_api_key => {
+                    let (_, api_key) = credentials_provider
+                        .read_credentials(&credentials_url, &cx)
+                        .await?
+                        .ok_or(AuthenticateError::CredentialsNotFound)?;
+                    Some(String::from_utf8(api_key).context("invalid API key")?)
+                }
+                None => None,
+            };
+            this.update(cx, |this, cx| {
+                this.api_key = api_key;
+                cx.notify();
+                this.fetch_models(cx)
+            })?
+            .await?;
+
+            Ok(())
+        })
+    }
+
+    fn fetch_models(&self, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let extension = self.extension.clone();
+        let provider_id = self.provider_id.clone();
+        let api_key = self.api_key.clone();
+        cx.spawn(async move |this, cx| {
+            let models = extension
+                .llm_provider_models(provider_id.clone(), api_key)
+                .await
+                .with_context(|| format!("fetching models of {provider_id}"))?;
+            this.update(cx, |this, cx| {
+                this.models = models;
+                cx.notify();
+            })
+        })
+    }
+}
+
+impl ExtensionLanguageModelProvider {
+    pub fn new(
+        extension: Arc<dyn Extension>,
+        provider_id: Arc<str>,
+        provider: LanguageModelProviderManifestEntry,
+        cx: &mut App,
+    ) -> Self {
+        let extension_id = extension.manifest().id.clone();
+        let credentials_url =
+            format!("zed-extension://{extension_id}/language-model-providers/{provider_id}");
+        let state = cx.new(|_| State {
+            extension,
+            provider_id: provider_id.clone(),
+            credentials_url,
+            requires_api_key: provider.requires_api_key,
+            api_key: None,
+            models: Vec::new(),
+        });
+
+        Self {
+            id: extension_provider_id(&extension_id, &provider_id),
+            name: provider.name.into(),
+            state,
+        }
+    }
+
+    fn create_language_model(&self, model: LlmModelInfo) -> Arc<dyn LanguageModel> {
+        Arc::new(ExtensionLanguageModel {
+            id: LanguageModelId::from(model.id.clone()),
+            provider_id: self.id.clone(),
+            provider_name: self.name.clone(),
+            model,
+            state: self.state.clone(),
+            request_limiter: RateLimiter::new(4),
+        })
+    }
+}
+
+impl LanguageModelProviderState for ExtensionLanguageModelProvider {
+    type ObservableEntity = State;
+
+    fn observable_entity(&self) -> Option<Entity<Self::ObservableEntity>> {
+        Some(self.state.clone())
+    }
+}
+
+impl LanguageModelProvider for ExtensionLanguageModelProvider {
+    fn id(&self) -> LanguageModelProviderId {
+        self.id.clone()
+    }
+
+    fn name(&self) -> LanguageModelProviderName {
+        self.name.clone()
+    }
+
+    fn default_model(&self, cx: &App) -> Option<Arc<dyn LanguageModel>> {
+        self.state
+            .read(cx)
+            .models
+            .first()
+            .map(|model| self.create_language_model(model.clone()))
+    }
+
+    fn default_fast_model(&self, _cx: &App) -> Option<Arc<dyn LanguageModel>> {
+        None
+    }
+
+    fn provided_models(&self, cx: &App) -> Vec<Arc<dyn LanguageModel>> {
+        self.state
+            .read(cx)
+            .models
+            .iter()
+            .map(|model| self.create_language_model(model.clone()))
+            .collect()
+    }
+
+    fn is_authenticated(&self, cx: &App) -> bool {
+        self.state.read(cx).is_authenticated()
+    }
+
+    fn authenticate(&self, cx: &mut App) -> Task<Result<(), AuthenticateError>> {
+        self.state.update(cx, |state, cx| state.authenticate(cx))
+    }
+
+    fn configuration_view(&self, window: &mut Window, cx: &mut App) -> AnyView {
+        cx.new(|cx| ConfigurationView::new(self.state.clone(), window, cx))
+            .into()
+    }
+
+    fn reset_credentials(&self, cx: &mut App) -> Task<Result<()>> {
+        self.state.update(cx, |state, cx| state.reset_api_key(cx))
+    }
+}
+
+pub struct ExtensionLanguageModel {
+    id: LanguageModelId,
+    provider_id: LanguageModelProviderId,
+    provider_name: LanguageModelProviderName,
+    model: LlmModelInfo,
+    state: Entity<State>,
+    request_limiter: RateLimiter,
+}
+
+impl LanguageModel for ExtensionLanguageModel {
+    fn id(&self) -> LanguageModelId {
+        self.id.clone()
+    }
+
+    fn name(&self) -> LanguageModelName {
+        LanguageModelName::from(self.model.name.clone())
+    }
+
+    fn provider_id(&self) -> LanguageModelProviderId {
+        self.provider_id.clone()
+    }
+
+    fn provider_name(&self) -> LanguageModelProviderName {
+        self.provider_name.clone()
+    }
+
+    fn supports_tools(&self) -> bool {
+        self.model.supports_tools
+    }
+
+    fn supports_images(&self) -> bool {
+        self.model.supports_images
+    }
+
+    fn supports_tool_choice(&self, choice: LanguageModelToolChoice) -> bool {
+        match choice {
+            LanguageModelToolChoice::Auto
+            | LanguageModelToolChoice::Any
+            | LanguageModelToolChoice::None => self.model.supports_tools,
+        }
+    }
+
+    fn telemetry_id(&self) -> String {
+        format!("extension/{}/{}", self.provider_id, self.model.id)
+    }
+
+    fn max_token_count(&self) -> u64 {
+        self.model.max_token_count
+    }
+
+    fn max_output_tokens(&self) -> Option<u64> {
+        self.model.max_output_tokens
+    }
+
+    fn count_tokens(
+        &self,
+        request: LanguageModelRequest,
+        cx: &App,
+    ) -> BoxFuture<'static, Result<u64>> {
+        let state = self.state.read(cx);
+        let extension = state.extension.clone();
+        let provider_id = state.provider_id.clone();
+        let model_id: Arc<str> = self.model.id.as_str().into();
+        cx.background_spawn(async move {
+            match extension
+                .llm_count_tokens(provider_id, model_id, into_extension(request.clone()))
+                .await
+            {
+                Ok(token_count) => Ok(token_count),
+                Err(error) => {
+                    // Extensions are not required to count tokens, so estimate them instead.
+                    log::debug!("estimating token count: {error:#}");
+                    let messages = super::open_ai::collect_tiktoken_messages(request);
+                    tiktoken_rs::num_tokens_from_messages("gpt-4", &messages)
+                        .map(|tokens| tokens as u64)
+                }
+            }
+        })
+        .boxed()
+    }
+
+    fn stream_completion(
+        &self,
+        request: LanguageModelRequest,
+        cx: &AsyncApp,
+    ) -> BoxFuture<
+        'static,
+        Result<
+            BoxStream<'static, Result<LanguageModelCompletionEvent, LanguageModelCompletionError>>,
+            LanguageModelCompletionError,
+        >,
+    > {
+        let Ok((extension, provider_id, requires_api_key, api_key)) =
+            cx.read_entity(&self.state, |state, _| {
+                (
+                    state.extension.clone(),
+                    state.provider_id.clone(),
+                    state.requires_api_key,
+                    state.api_key.clone(),
+                )
+            })
+        else {
+            return futures::future::ready(Err(anyhow!("App state dropped").into())).boxed();
+        };
+
+        let provider = self.provider_name.clone();
+        let model_id: Arc<str> = self.model.id.as_str().into();
+        let request = into_extension(request);
+        let executor = cx.background_executor().clone();
+        let future = self.request_limiter.stream(async move {
+            if requires_api_key && api_key.is_none() {
+                return Err(LanguageModelCompletionError::NoApiKey { provider });
+            }
+            let stream_id = extension
+                .llm_stream_completion_start(provider_id, model_id, request, api_key)
+                .await?;
+            Ok(completion_events(extension, stream_id, executor))
+        });
+
+        async move { Ok(future.await?.boxed()) }.boxed()
+    }
+}
+
+/// An open completion stream of an extension, which is closed when dropped.
+struct CompletionStream {
+    extension: Arc<dyn Extension>,
+    stream_id: String,
+    executor: BackgroundExecutor,
+}
+
+impl Drop for CompletionStream {
+    fn drop(&mut self) {
+        let extension = self.extension.clone();
+        let stream_id = std::mem::take(&mut self.stream_id);
+        self.executor
+            .spawn(async move {
+                extension
+                    .llm_stream_completion_close(stream_id)
+                    .await
+                    .log_err();
+            })
+            .detach();
+    }
+}
+
+fn completion_events(
+    extension: Arc<dyn Extension>,
+    stream_id: String,
+    executor: BackgroundExecutor,
+) -> BoxStream<'static, Result<LanguageModelCompletionEvent, LanguageModelCompletionError>> {
+    let stream = CompletionStream {
+        extension,
+        stream_id,
+        executor,
+    };
+    futures::stream::unfold(Some(stream), |stream| async move {
+        let stream = stream?;
+        match stream
+            .extension
+            .llm_stream_completion_next(stream.stream_id.clone())
+            .await
+        {
+            Ok(Some(event)) => Some((Ok(into_completion_event(event)), Some(stream))),
+            Ok(None) => None,
+            Err(error) => Some((Err(error.into()), None)),
+        }
+    })
+    .boxed()
+}
+
+fn into_extension_image(image: LanguageModelImage) -> LlmImage {
+    LlmImage {
+        source: image.source.to_string(),
+        width: image.size.width.0 as u32,
+        height: image.size.height.0 as u32,
+    }
+}
+
+fn into_extension_content(content: MessageContent) -> LlmMessageContent {
+    match content {
+        MessageContent::Text(text) => LlmMessageContent::Text(text),
+        MessageContent::Thinking { text, signature } => {
+            LlmMessageContent::Thinking(LlmThinking { text, signature })
+        }
+        MessageContent::RedactedThinking(data) => LlmMessageContent::RedactedThinking(data),
+        MessageContent::Image(image) => LlmMessageContent::Image(into_extension_image(image)),
+        MessageContent::ToolUse(tool_use) => LlmMessageContent::ToolUse(LlmToolUse {
+            id: tool_use.id.to_string(),
+            name: tool_use.name.to_string(),
+            input: tool_use.input.to_string(),
+        }),
+        MessageContent::ToolResult(tool_result) => LlmMessageContent::ToolResult(LlmToolResult {
+            tool_use_id: tool_result.tool_use_id.to_string(),
+            tool_name: tool_result.tool_name.to_string(),
+            is_error: tool_result.is_error,
+            content: match tool_result.content {
+                LanguageModelToolResultContent::Text(text) => {
+                    LlmToolResultContent::Text(text.to_string())
+                }
+                LanguageModelToolResultContent::Image(image) => {
+                    LlmToolResultContent::Image(into_extension_image(image))
+                }
+            },
+        }),
+    }
+}
+
+fn into_extension(request: LanguageModelRequest) -> LlmCompletionRequest {
+    LlmCompletionRequest {
+        thread_id: request.thread_id,
+        prompt_id: request.prompt_id,
+        messages: request
+            .messages
+            .into_iter()
+            .map(|message| LlmRequestMessage {
+                role: match message.role {
+                    Role::User => LlmMessageRole::User,
+                    Role::Assistant => LlmMessageRole::Assistant,
+                    Role::System => LlmMessageRole::System,
+                },
+                content: message
+                    .content
+                    .into_iter()
+                    .map(into_extension_content)
+                    .collect(),
+                cache: message.cache,
+            })
+            .collect(),
+        tools: request
+            .tools
+            .into_iter()
+            .map(|tool| LlmToolDefinition {
+                name: tool.name,
+                description: tool.description,
+                input_schema: tool.input_schema.to_string(),
+            })
+            .collect(),
+        tool_choice: request.tool_choice.map(|choice| match choice {
+            LanguageModelToolChoice::Auto => LlmToolChoice::Auto,
+            LanguageModelToolChoice::Any => LlmToolChoice::Any,
+            LanguageModelToolChoice::None => LlmToolChoice::None,
+        }),
+        stop: request.stop,
+        temperature: request.temperature,
+        thinking_allowed: request.thinking_allowed,
+    }
+}
+
+fn into_completion_event(event: LlmCompletionEvent) -> LanguageModelCompletionEvent {
+    match event {
+        LlmCompletionEvent::StartMessage(message_id) => {
+            LanguageModelCompletionEvent::StartMessage { message_id }
+        }
+        LlmCompletionEvent::Text(text) => LanguageModelCompletionEvent::Text(text),
+        LlmCompletionEvent::Thinking(thinking) => LanguageModelCompletionEvent::Thinking {
+            text: thinking.text,
+            signature: thinking.signature,
+        },
+        LlmCompletionEvent::RedactedThinking(data) => {
+            LanguageModelCompletionEvent::RedactedThinking { data }
+        }
+        LlmCompletionEvent::ToolUse(tool_use) => match serde_json::from_str(&tool_use.input) {
+            Ok(input) => LanguageModelCompletionEvent::ToolUse(LanguageModelToolUse {
+                id: tool_use.id.into(),
+                name: tool_use.name.into(),
+                is_input_complete: true,
+                input,
+                raw_input: tool_use.input,
+            }),
+            Err(error) => LanguageModelCompletionEvent::ToolUseJsonParseError {
+                id: tool_use.id.into(),
+                tool_name: tool_use.name.into(),
+                raw_input: tool_use.input.into(),
+                json_parse_error: error.to_string(),
+            },
+        },
+        LlmCompletionEvent::Stop(reason) => LanguageModelCompletionEvent::Stop(match reason {
+            LlmStopReason::EndTurn => StopReason::EndTurn,
+            LlmStopReason::MaxTokens => StopReason::MaxTokens,
+            LlmStopReason::ToolUse => StopReason::ToolUse,
+            LlmStopReason::Refusal => StopReason::Refusal,
+        }),
+        LlmCompletionEvent::Usage(usage) => LanguageModelCompletionEvent::UsageUpdate(TokenUsage {
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            cache_creation_input_tokens: usage.cache_creation_input_tokens,
+            cache_read_input_tokens: usage.cache_read_input_tokens,
+        }),
+    }
+}
+
+struct ConfigurationView {
+    api_key_editor: Entity<SingleLineInput>,
+    state: Entity<State>,
+    load_credentials_task: Option<Task<()>>,
+}
+
+impl ConfigurationView {
+    fn new(state: Entity<State>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let api_key_editor = cx.new(|cx| {
+            SingleLineInput::new(
+                window,
+                cx,
+                "000000000000000000000000000000000000000000000000000",
+            )
+        });
+
+        cx.observe(&state, |_, _, cx| {
+            cx.notify();
+        })
+        .detach();
+
+        let load_credentials_task = Some(cx.spawn_in(window, {
+            let state = state.clone();
+            async move |this, cx| {
+                if let Some(task) = state
+                    .update(cx, |state, cx| state.authenticate(cx))
+                    .log_err()
+                {
+                    // We don't log an error, because "not signed in" is also an error.
+                    let _ = task.await;
+                }
+                this.update(cx, |this, cx| {
+                    this.load_credentials_task = None;
+                    cx.notify();
+                })
+                .log_err();
+            }
+        }));
+
+        Self {
+            api_key_editor,
+            state,
+            load_credentials_task,
+        }
+    }
+
+    fn save_api_key(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        let api_key = self
+            .api_key_editor
+            .read(cx)
+            .editor()
+            .read(cx)
+            .text(cx)
+            .trim()
+            .to_string();
+        if api_key.is_empty() {
+            return;
+        }
+
+        let state = self.state.clone();
+        cx.spawn_in(window, async move |_, cx| {
+            state
+                .update(cx, |state, cx| state.set_api_key(api_key, cx))?
+                .await
+        })
+        .detach_and_log_err(cx);
+
+        cx.notify();
+    }
+
+    fn reset_api_key(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.api_key_editor.update(cx, |input, cx| {
+            input.editor.update(cx, |editor, cx| {
+                editor.set_text("", window, cx);
+            });
+        });
+
+        let state = self.state.clone();
+        cx.spawn_in(window, async move |_, cx| {
+            state.update(cx, |state, cx| state.reset_api_key(cx))?.await
+        })
+        .detach_and_log_err(cx);
+
+        cx.notify();
+    }
+}
+
+impl Render for ConfigurationView {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let state = self.state.read(cx);
+
+        if self.load_credentials_task.is_some() {
+            return div().child(Label::new("Loading credentials…")).into_any();
+        }
+
+        if !state.requires_api_key {
+            return v_flex()
+                .size_full()
+                .child(Label::new(
+                    "This provider is configured by its extension and needs no API key.",
+                ))
+                .into_any();
+        }
+
+        if !state.is_authenticated() {
+            return v_flex()
+                .size_full()
+                .on_action(cx.listener(Self::save_api_key))
+                .child(Label::new(
+                    "To use Zed's agent with this provider, you need to add an API key.",
+                ))
+                .child(
+                    div()
+                        .pt(DynamicSpacing::Base04.rems(cx))
+                        .child(self.api_key_editor.clone()),
+                )
+                .into_any();
+        }
+
+        h_flex()
+            .mt_1()
+            .p_1()
+            .justify_between()
+            .rounded_md()
+            .border_1()
+            .border_color(cx.theme().colors().border)
+            .bg(cx.theme().colors().background)
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(Icon::new(IconName::Check).color(Color::Success))
+                    .child(Label::new("API key configured.")),
+            )
+            .child(
+                Button::new("reset-api-key", "Reset API Key")
+                    .label_size(LabelSize::Small)
+                    .icon(IconName::Undo)
+                    .icon_size(IconSize::Small)
+                    .icon_position(IconPosition::Start)
+                    .layer(ElevationIndex::ModalSurface)
+                    .on_click(cx.listener(|this, _, window, cx| this.reset_api_key(window, cx))),
+            )
+            .into_any()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use extension::FakeExtension;
+    use gpui::TestAppContext;
+    use language_model::fake_provider::FakeLanguageModelProvider;
+
+    #[gpui::test]
+    fn test_extension_provider_cannot_replace_built_in_provider(cx: &mut TestAppContext) {
+        let built_in_id = LanguageModelProviderId::from("anthropic".to_string());
+        let registry = cx.new(|cx| {
+            let mut registry = LanguageModelRegistry::default();
+            registry.register_provider(
+                FakeLanguageModelProvider::new(
+                    built_in_id.clone(),
+                    LanguageModelProviderName::from("Anthropic".to_string()),
+                ),
+                cx,
+            );
+            registry
+        });
+
+        let extension: Arc<dyn Extension> =
+            Arc::new(FakeExtension::new(FakeExtension::empty_manifest("my-llm")));
+        let provider = cx.update(|cx| {
+            ExtensionLanguageModelProvider::new(
+                extension,
+                "anthropic".into(),
+                LanguageModelProviderManifestEntry {
+                    name: "Not Anthropic".into(),
+                    requires_api_key: false,
+                },
+                cx,
+            )
+        });
+        assert_eq!(provider.id(), extension_provider_id("my-llm", "anthropic"));
+        registry.update(cx, |registry, cx| registry.register_provider(provider, cx));
+
+        registry.read_with(cx, |registry, _| {
+            assert_eq!(
+                registry.provider(&built_in_id).unwrap().name().0.as_ref(),
+                "Anthropic"
+            );
+            assert_eq!(
+                registry
+                    .provider(&extension_provider_id("my-llm", "anthropic"))
+                    .unwrap()
+                    .name()
+                    .0
+                    .as_ref(),
+                "Not Anthropic"
+            );
+        });
+    }
+
+    #[test]
+    fn test_into_completion_event_parses_tool_input() {
+        let event = into_completion_event(LlmCompletionEvent::ToolUse(LlmToolUse {
+            id: "call-1".into(),
+            name: "grep".into(),
+            input: r#"{"regex":"fn main"}"#.into(),
+        }));
+        let LanguageModelCompletionEvent::ToolUse(tool_use) = event else {
+            panic!("expected a tool use, got {event:?}");
+        };
+        assert_eq!(tool_use.name.as_ref(), "grep");
+        assert_eq!(tool_use.input, serde_json::json!({"regex": "fn main"}));
+        assert!(tool_use.is_input_complete);
+
+        let event = into_completion_event(LlmCompletionEvent::ToolUse(LlmToolUse {
+            id: "call-2".into(),
+            name: "grep".into(),
+            input: "{".into(),
+        }));
+        assert!(matches!(
+            event,
+            LanguageModelCompletionEvent::ToolUseJsonParseError { .. }
+        ));
+    }
+}
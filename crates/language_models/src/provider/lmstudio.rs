@@ -5,9 +5,9 @@ use futures::{FutureExt, StreamExt, future::BoxFuture, stream::BoxStream};
 use gpui::{AnyView, App, AsyncApp, Context, Subscription, Task};
 use http_client::HttpClient;
 use language_model::{
-    AuthenticateError, LanguageModelCompletionError, LanguageModelCompletionEvent,
+    ANY_MODEL, AuthenticateError, LanguageModelCompletionError, LanguageModelCompletionEvent,
     LanguageModelToolChoice, LanguageModelToolResultContent, LanguageModelToolUse, MessageContent,
-    StopReason, TokenUsage,
+    ModelPrice, StopReason, TokenUsage,
 };
 use language_model::{
     LanguageModel, LanguageModelId, LanguageModelName, LanguageModelProvider,
@@ -154,6 +154,14 @@ impl LanguageModelProviderState for LmStudioLanguageModelProvider {
     }
 }
 
+/// Local models are free to use, so their usage shows up in the usage ledger without a cost.
+pub fn model_prices() -> (LanguageModelProviderId, HashMap<String, ModelPrice>) {
+    (
+        PROVIDER_ID,
+        HashMap::from_iter([(ANY_MODEL.to_string(), ModelPrice::FREE)]),
+    )
+}
+
 impl LanguageModelProvider for LmStudioLanguageModelProvider {
     fn id(&self) -> LanguageModelProviderId {
         PROVIDER_ID
@@ -11,7 +11,7 @@ use language_model::{
     LanguageModelId, LanguageModelName, LanguageModelProvider, LanguageModelProviderId,
     LanguageModelProviderName, LanguageModelProviderState, LanguageModelRequest,
     LanguageModelToolChoice, LanguageModelToolResultContent, LanguageModelToolUse, MessageContent,
-    RateLimiter, Role, StopReason, TokenUsage,
+    ModelPrice, RateLimiter, Role, StopReason, TokenUsage,
 };
 use menu;
 use open_ai::{ImageUrl, Model, ReasoningEffort, ResponseStreamEvent, stream_completion};
@@ -668,6 +668,38 @@ pub(crate) fn collect_tiktoken_messages(
         .collect::<Vec<_>>()
 }
 
+/// OpenAI's list prices for its models, for the usage ledger.
+pub fn model_prices() -> (LanguageModelProviderId, HashMap<String, ModelPrice>) {
+    // OpenAI doesn't charge extra for writing to the prompt cache.
+    let price = |input: f64, output: f64, cache_read: f64| {
+        ModelPrice::new(input, output).with_cache(input, cache_read)
+    };
+    let prices = Model::iter()
+        .filter_map(|model| {
+            let price = match model {
+                Model::ThreePointFiveTurbo => ModelPrice::new(0.5, 1.5),
+                Model::Four => ModelPrice::new(30., 60.),
+                Model::FourTurbo => ModelPrice::new(10., 30.),
+                Model::FourOmni => price(2.5, 10., 1.25),
+                Model::FourOmniMini => price(0.15, 0.6, 0.075),
+                Model::FourPointOne => price(2., 8., 0.5),
+                Model::FourPointOneMini => price(0.4, 1.6, 0.1),
+                Model::FourPointOneNano => price(0.1, 0.4, 0.025),
+                Model::O1 => price(15., 60., 7.5),
+                Model::O3Mini => price(1.1, 4.4, 0.55),
+                Model::O3 => price(2., 8., 0.5),
+                Model::O4Mini => price(1.1, 4.4, 0.275),
+                Model::Five => price(1.25, 10., 0.125),
+                Model::FiveMini => price(0.25, 2., 0.025),
+                Model::FiveNano => price(0.05, 0.4, 0.005),
+                Model::Custom { .. } => return None,
+            };
+            Some((model.id().to_string(), price))
+        })
+        .collect();
+    (PROVIDER_ID, prices)
+}
+
 pub fn count_open_ai_tokens(
     request: LanguageModelRequest,
     model: Model,
@@ -3,6 +3,7 @@ use std::sync::Arc;
 use anyhow::Result;
 use collections::HashMap;
 use gpui::App;
+use language_model::{ModelPrice, UsageBudget};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsSources};
@@ -44,6 +45,9 @@ pub struct AllLanguageModelSettings {
     pub vercel: VercelSettings,
     pub x_ai: XAiSettings,
     pub zed_dot_dev: ZedDotDevSettings,
+    /// Prices that override the built-in ones, by provider id and model id.
+    pub pricing: HashMap<String, HashMap<String, ModelPrice>>,
+    pub budget: UsageBudget,
 }
 
 #[derive(Default, Clone, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
@@ -62,6 +66,12 @@ pub struct AllLanguageModelSettingsContent {
     pub x_ai: Option<XAiSettingsContent>,
     #[serde(rename = "zed.dev")]
     pub zed_dot_dev: Option<ZedDotDevSettingsContent>,
+    /// The prices of language models in US dollars per million tokens, by provider id and model
+    /// id, used to work out the cost of their usage. The model id `*` applies to all of a
+    /// provider's models without a price of their own.
+    pub pricing: Option<HashMap<String, HashMap<String, ModelPrice>>>,
+    /// Limits on how much to spend on language models, based on their prices.
+    pub budget: Option<UsageBudget>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
@@ -316,6 +326,12 @@ impl settings::Settings for AllLanguageModelSettings {
                     .as_ref()
                     .and_then(|s| s.available_models.clone()),
             );
+
+            // Usage
+            for (provider, prices) in value.pricing.clone().unwrap_or_default() {
+                settings.pricing.entry(provider).or_default().extend(prices);
+            }
+            merge(&mut settings.budget, value.budget);
         }
 
         Ok(settings)
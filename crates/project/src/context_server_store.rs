@@ -1,27 +1,50 @@
 pub mod extension;
 pub mod registry;
 
-use std::{path::Path, sync::Arc};
+use std::{fmt::Write as _, path::Path, sync::Arc};
 
-use anyhow::{Context as _, Result};
+use anyhow::{Context as _, Result, anyhow};
 use collections::{HashMap, HashSet};
-use context_server::{ContextServer, ContextServerCommand, ContextServerId};
+use context_server::types::{
+    CreateMessageRequest, CreateMessageResult, ListRootsResponse, Notification as _, Request as _,
+    ResourceContentsType, ResourcesReadParams, ResourcesSubscribeParams,
+    ResourcesUnsubscribeParams, ResourcesUpdatedParams, Root, notifications, requests,
+};
+use context_server::{ContextServer, ContextServerCommand, ContextServerEndpoint, ContextServerId};
 use futures::{FutureExt as _, future::join_all};
-use gpui::{App, AsyncApp, Context, Entity, EventEmitter, Subscription, Task, WeakEntity, actions};
+use gpui::{
+    App, AsyncApp, Context, Entity, EventEmitter, Global, Subscription, Task, WeakEntity, actions,
+};
 use registry::ContextServerDescriptorRegistry;
 use settings::{Settings as _, SettingsStore};
+use url::Url;
 use util::ResultExt as _;
 
 use crate::{
     Project,
     project_settings::{ContextServerSettings, ProjectSettings},
-    worktree_store::WorktreeStore,
+    worktree_store::{WorktreeStore, WorktreeStoreEvent},
 };
 
 pub fn init(cx: &mut App) {
     extension::init(cx);
 }
 
+/// Answers `sampling/createMessage` requests, which let context servers request completions
+/// from the user's language model.
+pub trait ContextServerSampler: 'static {
+    fn create_message(
+        &self,
+        server_id: ContextServerId,
+        request: CreateMessageRequest,
+        cx: &mut App,
+    ) -> Task<Result<CreateMessageResult>>;
+}
+
+struct GlobalContextServerSampler(Arc<dyn ContextServerSampler>);
+
+impl Global for GlobalContextServerSampler {}
+
 actions!(
     context_server,
     [
@@ -99,13 +122,18 @@ pub enum ContextServerConfiguration {
         command: ContextServerCommand,
         settings: serde_json::Value,
     },
+    Remote {
+        endpoint: ContextServerEndpoint,
+    },
 }
 
 impl ContextServerConfiguration {
-    pub fn command(&self) -> &ContextServerCommand {
+    /// Returns the command that starts the context server, unless it is a remote server.
+    pub fn command(&self) -> Option<&ContextServerCommand> {
         match self {
-            ContextServerConfiguration::Custom { command } => command,
-            ContextServerConfiguration::Extension { command, .. } => command,
+            ContextServerConfiguration::Custom { command } => Some(command),
+            ContextServerConfiguration::Extension { command, .. } => Some(command),
+            ContextServerConfiguration::Remote { .. } => None,
         }
     }
 
@@ -134,6 +162,10 @@ impl ContextServerConfiguration {
 
                 Some(ContextServerConfiguration::Extension { command, settings })
             }
+            ContextServerSettings::Remote {
+                enabled: _,
+                endpoint,
+            } => Some(ContextServerConfiguration::Remote { endpoint }),
         }
     }
 }
@@ -150,6 +182,8 @@ pub struct ContextServerStore {
     update_servers_task: Option<Task<Result<()>>>,
     context_server_factory: Option<ContextServerFactory>,
     needs_server_update: bool,
+    /// The number of subscribers to each resource, keyed by server and resource URI.
+    resource_subscriptions: HashMap<(ContextServerId, String), usize>,
     _subscriptions: Vec<Subscription>,
 }
 
@@ -158,6 +192,13 @@ pub enum Event {
         server_id: ContextServerId,
         status: ContextServerStatus,
     },
+    /// A resource that was subscribed to with `resources/subscribe` has changed.
+    ResourceUpdated {
+        server_id: ContextServerId,
+        uri: String,
+    },
+    /// The list of resources that a context server provides has changed.
+    ResourcesListChanged { server_id: ContextServerId },
 }
 
 impl EventEmitter<Event> for ContextServerStore {}
@@ -223,7 +264,7 @@ impl ContextServerStore {
         weak_project: WeakEntity<Project>,
         cx: &mut Context<Self>,
     ) -> Self {
-        let subscriptions = if maintain_server_loop {
+        let mut subscriptions = if maintain_server_loop {
             vec![
                 cx.observe(&registry, |this, _registry, cx| {
                     this.available_context_servers_changed(cx);
@@ -240,6 +281,14 @@ impl ContextServerStore {
         } else {
             Vec::new()
         };
+        subscriptions.push(
+            cx.subscribe(&worktree_store, |this, _, event, _| match event {
+                WorktreeStoreEvent::WorktreeAdded(_) | WorktreeStoreEvent::WorktreeRemoved(..) => {
+                    this.roots_changed();
+                }
+                _ => {}
+            }),
+        );
 
         let mut this = Self {
             _subscriptions: subscriptions,
@@ -252,6 +301,7 @@ impl ContextServerStore {
             servers: HashMap::default(),
             update_servers_task: None,
             context_server_factory,
+            resource_subscriptions: HashMap::default(),
         };
         if maintain_server_loop {
             this.available_context_servers_changed(cx);
@@ -392,12 +442,18 @@ impl ContextServerStore {
             self.stop_server(&id, cx).log_err();
         }
 
+        let notification_handlers = Self::notification_handlers(id.clone(), cx);
+        let request_handlers = Self::request_handlers(id.clone(), cx);
         let task = cx.spawn({
             let id = server.id();
             let server = server.clone();
             let configuration = configuration.clone();
             async move |this, cx| {
-                match server.clone().start(&cx).await {
+                match server
+                    .clone()
+                    .start_with_handlers(notification_handlers, request_handlers, &cx)
+                    .await
+                {
                     Ok(_) => {
                         log::info!("Started {} context server", id);
                         debug_assert!(server.client().is_some());
@@ -444,6 +500,271 @@ impl ContextServerStore {
         );
     }
 
+    fn notification_handlers(
+        server_id: ContextServerId,
+        cx: &Context<Self>,
+    ) -> Vec<(
+        &'static str,
+        Box<dyn 'static + Send + FnMut(serde_json::Value, AsyncApp)>,
+    )> {
+        let this = cx.weak_entity();
+        vec![
+            (
+                notifications::ResourcesUpdated::METHOD,
+                Box::new({
+                    let this = this.clone();
+                    let server_id = server_id.clone();
+                    move |params, mut cx| {
+                        let Some(params) =
+                            serde_json::from_value::<ResourcesUpdatedParams>(params).log_err()
+                        else {
+                            return;
+                        };
+                        this.update(&mut cx, |_, cx| {
+                            cx.emit(Event::ResourceUpdated {
+                                server_id: server_id.clone(),
+                                uri: params.uri,
+                            })
+                        })
+                        .ok();
+                    }
+                }),
+            ),
+            (
+                notifications::ResourcesListChanged::METHOD,
+                Box::new(move |_, mut cx| {
+                    this.update(&mut cx, |_, cx| {
+                        cx.emit(Event::ResourcesListChanged {
+                            server_id: server_id.clone(),
+                        })
+                    })
+                    .ok();
+                }),
+            ),
+        ]
+    }
+
+    fn request_handlers(
+        server_id: ContextServerId,
+        cx: &Context<Self>,
+    ) -> Vec<(
+        &'static str,
+        Box<
+            dyn 'static
+                + Send
+                + FnMut(serde_json::Value, AsyncApp) -> Task<Result<serde_json::Value>>,
+        >,
+    )> {
+        let mut handlers: Vec<(
+            &'static str,
+            Box<
+                dyn 'static
+                    + Send
+                    + FnMut(serde_json::Value, AsyncApp) -> Task<Result<serde_json::Value>>,
+            >,
+        )> = Vec::new();
+
+        let this = cx.weak_entity();
+        handlers.push((
+            requests::ListRoots::METHOD,
+            Box::new(move |_, cx| {
+                Task::ready(
+                    this.read_with(&cx, |this, cx| this.roots(cx))
+                        .and_then(|roots| Ok(serde_json::to_value(roots)?)),
+                )
+            }),
+        ));
+
+        // Only advertise sampling when something can answer it.
+        if cx.has_global::<GlobalContextServerSampler>() {
+            let this = cx.weak_entity();
+            handlers.push((
+                requests::CreateMessage::METHOD,
+                Box::new(move |params, cx| {
+                    let this = this.clone();
+                    let server_id = server_id.clone();
+                    cx.spawn(async move |cx| {
+                        let request = serde_json::from_value::<CreateMessageRequest>(params)?;
+                        let result = this
+                            .update(cx, |this, cx| this.create_message(server_id, request, cx))?
+                            .await?;
+                        Ok(serde_json::to_value(result)?)
+                    })
+                }),
+            ));
+        }
+
+        handlers
+    }
+
+    /// Sets the sampler that answers sampling requests from context servers started after this call.
+    pub fn set_global_sampler(sampler: Arc<dyn ContextServerSampler>, cx: &mut App) {
+        cx.set_global(GlobalContextServerSampler(sampler));
+    }
+
+    /// Requests a completion on behalf of a context server.
+    pub fn create_message(
+        &mut self,
+        server_id: ContextServerId,
+        request: CreateMessageRequest,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<CreateMessageResult>> {
+        if self.get_running_server(&server_id).is_none() {
+            return Task::ready(Err(anyhow!("context server {server_id} is not running")));
+        }
+        let Some(sampler) = cx
+            .try_global::<GlobalContextServerSampler>()
+            .map(|sampler| sampler.0.clone())
+        else {
+            return Task::ready(Err(anyhow!("sampling is not supported")));
+        };
+        sampler.create_message(server_id, request, cx)
+    }
+
+    /// Returns the roots that context servers are allowed to operate on, which are the
+    /// project's visible worktrees.
+    pub fn roots(&self, cx: &App) -> ListRootsResponse {
+        let roots = self
+            .worktree_store
+            .read(cx)
+            .visible_worktrees(cx)
+            .filter_map(|worktree| {
+                let worktree = worktree.read(cx);
+                let uri = Url::from_file_path(worktree.abs_path()).ok()?;
+                Some(Root {
+                    uri,
+                    name: Some(worktree.root_name().to_string()),
+                })
+            })
+            .collect();
+        ListRootsResponse { roots, meta: None }
+    }
+
+    fn roots_changed(&self) {
+        for server in self.running_servers() {
+            if let Some(client) = server.client() {
+                client
+                    .notify::<notifications::RootsListChanged>(())
+                    .log_err();
+            }
+        }
+    }
+
+    /// Reads the text contents of a resource provided by a running context server.
+    pub fn read_resource(
+        &self,
+        server_id: &ContextServerId,
+        uri: &str,
+        cx: &App,
+    ) -> Task<Result<String>> {
+        let Some(server) = self.get_running_server(server_id) else {
+            return Task::ready(Err(anyhow!("context server {server_id} is not running")));
+        };
+        let uri = match Url::parse(uri) {
+            Ok(uri) => uri,
+            Err(error) => return Task::ready(Err(error.into())),
+        };
+        cx.background_spawn(async move {
+            let client = server
+                .client()
+                .context("context server is not initialized")?;
+            let response = client
+                .request::<requests::ResourcesRead>(ResourcesReadParams { uri, meta: None })
+                .await?;
+            let mut text = String::new();
+            for contents in response.contents {
+                if !text.is_empty() {
+                    text.push('\n');
+                }
+                match contents {
+                    ResourceContentsType::Text(contents) => text.push_str(&contents.text),
+                    ResourceContentsType::Blob(contents) => {
+                        let mime_type = contents.mime_type.as_deref().unwrap_or("binary");
+                        write!(text, "[{mime_type} contents of {}]", contents.uri).ok();
+                    }
+                }
+            }
+            Ok(text)
+        })
+    }
+
+    /// Subscribes to updates of a resource, which are reported as [`Event::ResourceUpdated`].
+    ///
+    /// Subscriptions are counted, and must be balanced by calls to
+    /// [`Self::unsubscribe_from_resource`].
+    pub fn subscribe_to_resource(
+        &mut self,
+        server_id: ContextServerId,
+        uri: String,
+        cx: &mut Context<Self>,
+    ) {
+        let server = self.get_running_server(&server_id);
+        let count = self
+            .resource_subscriptions
+            .entry((server_id, uri.clone()))
+            .or_default();
+        *count += 1;
+        if *count == 1 {
+            if let Some(server) = server {
+                Self::send_resource_subscription(&server, &uri, true, cx);
+            }
+        }
+    }
+
+    pub fn unsubscribe_from_resource(
+        &mut self,
+        server_id: ContextServerId,
+        uri: String,
+        cx: &mut Context<Self>,
+    ) {
+        let key = (server_id, uri);
+        let Some(count) = self.resource_subscriptions.get_mut(&key) else {
+            return;
+        };
+        *count -= 1;
+        if *count == 0 {
+            self.resource_subscriptions.remove(&key);
+            if let Some(server) = self.get_running_server(&key.0) {
+                Self::send_resource_subscription(&server, &key.1, false, cx);
+            }
+        }
+    }
+
+    fn send_resource_subscription(
+        server: &Arc<ContextServer>,
+        uri: &str,
+        subscribe: bool,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(client) = server.client() else {
+            return;
+        };
+        if !client.supports_resource_subscriptions() {
+            return;
+        }
+        let Some(uri) = Url::parse(uri).log_err() else {
+            return;
+        };
+        cx.background_spawn(async move {
+            if subscribe {
+                client
+                    .request::<requests::ResourcesSubscribe>(ResourcesSubscribeParams {
+                        uri,
+                        meta: None,
+                    })
+                    .await
+            } else {
+                client
+                    .request::<requests::ResourcesUnsubscribe>(ResourcesUnsubscribeParams {
+                        uri,
+                        meta: None,
+                    })
+                    .await
+            }
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn remove_server(&mut self, id: &ContextServerId, cx: &mut Context<Self>) -> Result<()> {
         let state = self
             .servers
@@ -481,13 +802,16 @@ impl ContextServerStore {
             });
 
         if let Some(factory) = self.context_server_factory.as_ref() {
-            factory(id, configuration)
-        } else {
-            Arc::new(ContextServer::stdio(
-                id,
-                configuration.command().clone(),
-                root_path,
-            ))
+            return factory(id, configuration);
+        }
+        match configuration.as_ref() {
+            ContextServerConfiguration::Custom { command }
+            | ContextServerConfiguration::Extension { command, .. } => {
+                Arc::new(ContextServer::stdio(id, command.clone(), root_path))
+            }
+            ContextServerConfiguration::Remote { endpoint } => {
+                Arc::new(ContextServer::http(id, endpoint.clone(), cx.http_client()))
+            }
         }
     }
 
@@ -513,6 +837,14 @@ impl ContextServerStore {
         cx: &mut Context<Self>,
     ) {
         let status = ContextServerStatus::from_state(&state);
+        if let ContextServerState::Running { server, .. } = &state {
+            // Subscriptions don't survive a restart of the server.
+            for (server_id, uri) in self.resource_subscriptions.keys() {
+                if server_id == &id {
+                    Self::send_resource_subscription(server, uri, true, cx);
+                }
+            }
+        }
         self.servers.insert(id.clone(), state);
         cx.emit(Event::ServerStatusChanged {
             server_id: id,
@@ -634,7 +966,9 @@ mod tests {
         FakeFs, Project, context_server_store::registry::ContextServerDescriptor,
         project_settings::ProjectSettings,
     };
-    use context_server::test::create_fake_transport;
+    use context_server::{
+        ContextServerHttpTransport, ContextServerOAuth, test::create_fake_transport, types,
+    };
     use gpui::{AppContext, TestAppContext, UpdateGlobal as _};
     use serde_json::json;
     use std::{cell::RefCell, path::PathBuf, rc::Rc};
@@ -1189,6 +1523,221 @@ mod tests {
         }
     }
 
+    #[gpui::test]
+    async fn test_remote_context_server_configuration(cx: &mut TestAppContext) {
+        const SERVER_ID: &'static str = "remote-mcp";
+
+        let settings = serde_json::from_value::<ContextServerSettings>(json!({
+            "source": "remote",
+            "url": "https://mcp.example.com/mcp",
+            "headers": { "X-Team": "platform" },
+            "oauth": { "access_token_env": "MCP_TOKEN" }
+        }))
+        .unwrap();
+        let (_fs, project) = setup_context_server_test(
+            cx,
+            json!({"code.rs": ""}),
+            vec![(SERVER_ID.into(), settings)],
+        )
+        .await;
+
+        let executor = cx.executor();
+        let registry = cx.new(|_| ContextServerDescriptorRegistry::new());
+        let store = cx.new(|cx| {
+            ContextServerStore::test_maintain_server_loop(
+                Box::new(move |id, _| {
+                    Arc::new(ContextServer::new(
+                        id.clone(),
+                        Arc::new(create_fake_transport(id.0.to_string(), executor.clone())),
+                    ))
+                }),
+                registry.clone(),
+                project.read(cx).worktree_store(),
+                project.downgrade(),
+                cx,
+            )
+        });
+        cx.run_until_parked();
+
+        let configuration = store
+            .read_with(cx, |store, _| {
+                store.configuration_for_server(&ContextServerId(SERVER_ID.into()))
+            })
+            .unwrap();
+        assert_eq!(
+            *configuration,
+            ContextServerConfiguration::Remote {
+                endpoint: ContextServerEndpoint {
+                    url: "https://mcp.example.com/mcp".into(),
+                    transport: ContextServerHttpTransport::StreamableHttp,
+                    headers: HashMap::from_iter([("X-Team".into(), "platform".into())]),
+                    oauth: Some(ContextServerOAuth {
+                        access_token: None,
+                        access_token_env: Some("MCP_TOKEN".into()),
+                    }),
+                },
+            }
+        );
+        assert_eq!(configuration.command(), None);
+        assert_eq!(
+            store.read_with(cx, |store, _| {
+                store.status_for_server(&ContextServerId(SERVER_ID.into()))
+            }),
+            Some(ContextServerStatus::Running)
+        );
+    }
+
+    #[gpui::test]
+    async fn test_context_server_roots_and_resource_updates(cx: &mut TestAppContext) {
+        const SERVER_ID: &'static str = "mcp-1";
+
+        let (_fs, project) = setup_context_server_test(
+            cx,
+            json!({"code.rs": ""}),
+            vec![(SERVER_ID.into(), dummy_server_settings())],
+        )
+        .await;
+
+        let transport = Arc::new(create_fake_transport(SERVER_ID, cx.executor()));
+        let registry = cx.new(|_| ContextServerDescriptorRegistry::new());
+        let store = cx.new(|cx| {
+            ContextServerStore::test_maintain_server_loop(
+                Box::new({
+                    let transport = transport.clone();
+                    move |id, _| Arc::new(ContextServer::new(id.clone(), transport.clone()))
+                }),
+                registry.clone(),
+                project.read(cx).worktree_store(),
+                project.downgrade(),
+                cx,
+            )
+        });
+        cx.run_until_parked();
+
+        // The server can list the project's worktrees as roots.
+        transport.send_request(1, "roots/list", json!({}));
+        cx.run_until_parked();
+        assert_eq!(
+            transport.client_responses(),
+            [json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": {
+                    "roots": [{
+                        "uri": Url::from_file_path(path!("/test")).unwrap(),
+                        "name": "test",
+                    }],
+                },
+            })]
+        );
+
+        let updated_resources = Rc::new(RefCell::new(Vec::new()));
+        let _subscription = cx.update(|cx| {
+            cx.subscribe(&store, {
+                let updated_resources = updated_resources.clone();
+                move |_, event, _| {
+                    if let Event::ResourceUpdated { server_id, uri } = event {
+                        updated_resources
+                            .borrow_mut()
+                            .push((server_id.clone(), uri.clone()));
+                    }
+                }
+            })
+        });
+        transport.send_notification(
+            "notifications/resources/updated",
+            json!({ "uri": "file:///test/code.rs" }),
+        );
+        cx.run_until_parked();
+        assert_eq!(
+            *updated_resources.borrow(),
+            [(
+                ContextServerId(SERVER_ID.into()),
+                "file:///test/code.rs".to_string()
+            )]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_context_server_sampling(cx: &mut TestAppContext) {
+        const SERVER_ID: &'static str = "mcp-1";
+
+        struct FakeSampler;
+
+        impl ContextServerSampler for FakeSampler {
+            fn create_message(
+                &self,
+                server_id: ContextServerId,
+                request: CreateMessageRequest,
+                _cx: &mut App,
+            ) -> Task<Result<CreateMessageResult>> {
+                let prompt = match &request.messages[0].content {
+                    types::MessageContent::Text { text, .. } => text.clone(),
+                    _ => String::new(),
+                };
+                Task::ready(Ok(CreateMessageResult {
+                    role: types::Role::Assistant,
+                    content: types::MessageContent::Text {
+                        text: format!("{server_id} asked: {prompt}"),
+                        annotations: None,
+                    },
+                    model: "fake".into(),
+                    stop_reason: Some("endTurn".into()),
+                }))
+            }
+        }
+
+        let (_fs, project) = setup_context_server_test(
+            cx,
+            json!({"code.rs": ""}),
+            vec![(SERVER_ID.into(), dummy_server_settings())],
+        )
+        .await;
+        cx.update(|cx| ContextServerStore::set_global_sampler(Arc::new(FakeSampler), cx));
+
+        let transport = Arc::new(create_fake_transport(SERVER_ID, cx.executor()));
+        let registry = cx.new(|_| ContextServerDescriptorRegistry::new());
+        let _store = cx.new(|cx| {
+            ContextServerStore::test_maintain_server_loop(
+                Box::new({
+                    let transport = transport.clone();
+                    move |id, _| Arc::new(ContextServer::new(id.clone(), transport.clone()))
+                }),
+                registry.clone(),
+                project.read(cx).worktree_store(),
+                project.downgrade(),
+                cx,
+            )
+        });
+        cx.run_until_parked();
+
+        transport.send_request(
+            1,
+            "sampling/createMessage",
+            json!({
+                "messages": [{
+                    "role": "user",
+                    "content": { "type": "text", "text": "summarize" },
+                }],
+                "maxTokens": 100,
+            }),
+        );
+        cx.run_until_parked();
+        assert_eq!(
+            transport.client_responses(),
+            [json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": {
+                    "role": "assistant",
+                    "content": { "type": "text", "text": "mcp-1 asked: summarize" },
+                    "model": "fake",
+                    "stopReason": "endTurn",
+                },
+            })]
+        );
+    }
+
     fn set_context_server_configuration(
         context_servers: Vec<(Arc<str>, ContextServerSettings)>,
         cx: &mut TestAppContext,
@@ -1266,6 +1815,7 @@ mod tests {
                         ix += 1;
                         *received_event_count.borrow_mut() += 1;
                     }
+                    _ => {}
                 }
             });
             ServerEvents {
@@ -1,6 +1,6 @@
 use super::{
     breakpoint_store::BreakpointStore,
-    dap_command::EvaluateCommand,
+    dap_command::{DapCommand, EvaluateCommand, GotoCommand, GotoTargetsCommand},
     locators,
     session::{self, Session, SessionStateEvent},
 };
@@ -123,6 +123,8 @@ impl DapStore {
         client.add_entity_request_handler(Self::handle_run_debug_locator);
         client.add_entity_request_handler(Self::handle_get_debug_adapter_binary);
         client.add_entity_message_handler(Self::handle_log_to_debug_console);
+        client.add_entity_request_handler(Self::handle_dap_command::<GotoTargetsCommand>);
+        client.add_entity_request_handler(Self::handle_dap_command::<GotoCommand>);
     }
 
     #[expect(clippy::too_many_arguments)]
@@ -751,6 +753,27 @@ impl DapStore {
         Ok(request.to_proto())
     }
 
+    async fn handle_dap_command<T: DapCommand>(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<T::ProtoRequest>,
+        mut cx: AsyncApp,
+    ) -> Result<T::ProtoResponse>
+    where
+        T::ProtoRequest: proto::RequestMessage<Response = T::ProtoResponse>,
+        <T::DapRequest as dap::requests::Request>::Response: 'static,
+        <T::DapRequest as dap::requests::Request>::Arguments: 'static + Send,
+    {
+        let session_id = T::client_id_from_proto(&envelope.payload);
+        let request = T::from_proto(&envelope.payload);
+        let session = this
+            .read_with(&cx, |this, _| this.session_by_id(session_id))?
+            .with_context(|| format!("no debug session with id {}", session_id.0))?;
+        let response = session
+            .update(&mut cx, |session, _| session.request_from_proto(request))?
+            .await?;
+        Ok(T::response_to_proto(session_id, response))
+    }
+
     async fn handle_get_debug_adapter_binary(
         this: Entity<Self>,
         envelope: TypedEnvelope<proto::GetDebugAdapterBinary>,
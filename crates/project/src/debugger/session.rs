@@ -1,5 +1,7 @@
 use crate::debugger::breakpoint_store::BreakpointSessionState;
-use crate::debugger::dap_command::{DataBreakpointContext, ReadMemory};
+use crate::debugger::dap_command::{
+    DataBreakpointContext, DisassembleCommand, ReadMemory, SetInstructionBreakpointsCommand,
+};
 use crate::debugger::memory::{self, Memory, MemoryIterator, MemoryPageBuilder, PageAddress};
 
 use super::breakpoint_store::{
@@ -7,11 +9,11 @@ use super::breakpoint_store::{
 };
 use super::dap_command::{
     self, Attach, ConfigurationDone, ContinueCommand, DataBreakpointInfoCommand, DisconnectCommand,
-    EvaluateCommand, Initialize, Launch, LoadedSourcesCommand, LocalDapCommand, LocationsCommand,
-    ModulesCommand, NextCommand, PauseCommand, RestartCommand, RestartStackFrameCommand,
-    ScopesCommand, SetDataBreakpointsCommand, SetExceptionBreakpoints, SetVariableValueCommand,
-    StackTraceCommand, StepBackCommand, StepCommand, StepInCommand, StepOutCommand,
-    TerminateCommand, TerminateThreadsCommand, ThreadsCommand, VariablesCommand,
+    EvaluateCommand, GotoCommand, GotoTargetsCommand, Initialize, Launch, LoadedSourcesCommand,
+    LocalDapCommand, LocationsCommand, ModulesCommand, NextCommand, PauseCommand, RestartCommand,
+    RestartStackFrameCommand, ScopesCommand, SetDataBreakpointsCommand, SetExceptionBreakpoints,
+    SetVariableValueCommand, StackTraceCommand, StepBackCommand, StepCommand, StepInCommand,
+    StepOutCommand, TerminateCommand, TerminateThreadsCommand, ThreadsCommand, VariablesCommand,
 };
 use super::dap_store::DapStore;
 use anyhow::{Context as _, Result, anyhow};
@@ -140,6 +142,12 @@ pub struct DataBreakpointState {
     pub context: Arc<DataBreakpointContext>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstructionBreakpointState {
+    pub dap: dap::InstructionBreakpoint,
+    pub is_enabled: bool,
+}
+
 pub enum SessionState {
     /// Represents a session that is building/initializing
     /// even if a session doesn't have a pre build task this state
@@ -166,7 +174,7 @@ pub struct SessionQuirks {
     pub prefer_thread_name: bool,
 }
 
-fn client_source(abs_path: &Path) -> dap::Source {
+pub(super) fn client_source(abs_path: &Path) -> dap::Source {
     dap::Source {
         name: abs_path
             .file_name()
@@ -692,11 +700,13 @@ pub struct Session {
     ignore_breakpoints: bool,
     exception_breakpoints: BTreeMap<String, (ExceptionBreakpointsFilter, IsEnabled)>,
     data_breakpoints: BTreeMap<String, DataBreakpointState>,
+    instruction_breakpoints: BTreeMap<String, InstructionBreakpointState>,
     background_tasks: Vec<Task<()>>,
     restart_task: Option<Task<()>>,
     task_context: TaskContext,
     memory: memory::Memory,
     quirks: SessionQuirks,
+    post_mortem: bool,
 }
 
 trait CacheableCommand: Any + Send + Sync {
@@ -864,12 +874,14 @@ impl Session {
                 ignore_breakpoints: false,
                 breakpoint_store,
                 data_breakpoints: Default::default(),
+                instruction_breakpoints: Default::default(),
                 exception_breakpoints: Default::default(),
                 label,
                 adapter,
                 task_context,
                 memory: memory::Memory::new(),
                 quirks,
+                post_mortem: false,
             };
 
             this
@@ -1061,6 +1073,16 @@ impl Session {
         self.is_session_terminated
     }
 
+    /// Whether this session inspects a core dump rather than a live process.
+    /// Post-mortem sessions can't resume, step or modify the debuggee's state.
+    pub fn is_post_mortem(&self) -> bool {
+        self.post_mortem
+    }
+
+    pub fn set_post_mortem(&mut self, post_mortem: bool) {
+        self.post_mortem = post_mortem;
+    }
+
     pub fn console_output(&mut self, cx: &mut Context<Self>) -> mpsc::UnboundedSender<String> {
         let (tx, mut rx) = mpsc::unbounded();
 
@@ -1804,6 +1826,9 @@ impl Session {
     }
 
     pub fn write_memory(&mut self, address: u64, data: &[u8], cx: &mut Context<Self>) {
+        if self.post_mortem {
+            return;
+        }
         let data = base64::engine::general_purpose::STANDARD.encode(data);
         self.request(
             WriteMemoryArguments {
@@ -1991,6 +2016,83 @@ impl Session {
         self.send_data_breakpoints(cx);
     }
 
+    /// Instruction breakpoints, keyed by the address of the instruction they're set on.
+    pub fn instruction_breakpoints(&self) -> &BTreeMap<String, InstructionBreakpointState> {
+        &self.instruction_breakpoints
+    }
+
+    /// Adds an instruction breakpoint at `instruction_reference`, or removes the one that's already there.
+    pub fn toggle_instruction_breakpoint(
+        &mut self,
+        instruction_reference: String,
+        cx: &mut Context<Self>,
+    ) {
+        if self
+            .instruction_breakpoints
+            .remove(&instruction_reference)
+            .is_none()
+        {
+            self.instruction_breakpoints.insert(
+                instruction_reference.clone(),
+                InstructionBreakpointState {
+                    dap: dap::InstructionBreakpoint {
+                        instruction_reference,
+                        offset: None,
+                        condition: None,
+                        hit_condition: None,
+                        mode: None,
+                    },
+                    is_enabled: true,
+                },
+            );
+        }
+        self.send_instruction_breakpoints(cx);
+        cx.notify();
+    }
+
+    pub fn toggle_instruction_breakpoint_enabled(
+        &mut self,
+        instruction_reference: &str,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(state) = self.instruction_breakpoints.get_mut(instruction_reference) {
+            state.is_enabled = !state.is_enabled;
+            self.send_instruction_breakpoints(cx);
+            cx.notify();
+        }
+    }
+
+    fn send_instruction_breakpoints(&mut self, cx: &mut Context<Self>) {
+        if let Some(mode) = self.as_running() {
+            let breakpoints = self
+                .instruction_breakpoints
+                .values()
+                .filter_map(|state| state.is_enabled.then(|| state.dap.clone()))
+                .collect();
+            let command = SetInstructionBreakpointsCommand { breakpoints };
+            mode.request(command).detach_and_log_err(cx);
+        }
+    }
+
+    /// Disassembles `instruction_count` instructions, starting `instruction_offset` instructions away from `memory_reference`.
+    pub fn disassemble(
+        &mut self,
+        memory_reference: String,
+        instruction_offset: i64,
+        instruction_count: u64,
+        cx: &mut Context<Self>,
+    ) -> Task<Option<Vec<dap::DisassembledInstruction>>> {
+        self.request(
+            DisassembleCommand {
+                memory_reference,
+                instruction_offset,
+                instruction_count,
+            },
+            |_, response, _| response.log_err(),
+            cx,
+        )
+    }
+
     pub fn breakpoints_enabled(&self) -> bool {
         self.ignore_breakpoints
     }
@@ -2065,6 +2167,9 @@ impl Session {
     }
 
     pub fn pause_thread(&mut self, thread_id: ThreadId, cx: &mut Context<Self>) {
+        if self.post_mortem {
+            return;
+        }
         self.request(
             PauseCommand {
                 thread_id: thread_id.0,
@@ -2076,6 +2181,9 @@ impl Session {
     }
 
     pub fn restart_stack_frame(&mut self, stack_frame_id: u64, cx: &mut Context<Self>) {
+        if self.post_mortem {
+            return;
+        }
         self.request(
             RestartStackFrameCommand { stack_frame_id },
             Self::empty_response,
@@ -2084,6 +2192,80 @@ impl Session {
         .detach();
     }
 
+    pub fn supports_set_next_statement(&self) -> bool {
+        self.capabilities
+            .supports_goto_targets_request
+            .unwrap_or_default()
+            && !self.post_mortem
+    }
+
+    /// Moves the execution of a stopped thread to the given row, without running the code in between.
+    pub fn set_next_statement(
+        &mut self,
+        thread_id: ThreadId,
+        path: Arc<Path>,
+        row: u32,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        if !self.supports_set_next_statement() {
+            return Task::ready(Err(anyhow!(
+                "The debug adapter doesn't support setting the next statement"
+            )));
+        }
+        if !matches!(
+            self.thread_states.thread_state(thread_id),
+            Some(ThreadStatus::Stopped)
+        ) {
+            return Task::ready(Err(anyhow!("The thread must be stopped")));
+        }
+
+        let targets = self.request(
+            GotoTargetsCommand {
+                path,
+                line: row as u64 + 1,
+                column: None,
+            },
+            |_, response, _| response.log_err(),
+            cx,
+        );
+        cx.spawn(async move |this, cx| {
+            let target = targets
+                .await
+                .and_then(|targets| targets.into_iter().next())
+                .context("Execution can't be moved to this line")?;
+            this.update(cx, |this, cx| {
+                this.thread_states.process_step(thread_id);
+                this.request(
+                    GotoCommand {
+                        thread_id: thread_id.0,
+                        target_id: target.id,
+                    },
+                    Self::on_step_response::<GotoCommand>(thread_id),
+                    cx,
+                )
+            })?
+            .await
+            .context("Failed to move execution")
+        })
+    }
+
+    /// Sends a request received from a collaborator or remote client to this session's adapter.
+    pub(super) fn request_from_proto<T: LocalDapCommand>(
+        &self,
+        request: T,
+    ) -> Task<Result<T::Response>>
+    where
+        <T::DapRequest as dap::requests::Request>::Response: 'static,
+        <T::DapRequest as dap::requests::Request>::Arguments: 'static + Send,
+    {
+        if !T::is_supported(&self.capabilities) {
+            return Task::ready(Err(anyhow!(
+                "The debug adapter doesn't support {request:?}"
+            )));
+        }
+        self.mode.request_dap(request)
+    }
+
     pub fn restart(&mut self, args: Option<Value>, cx: &mut Context<Self>) {
         if self.restart_task.is_some() || self.as_running().is_none() {
             return;
@@ -2181,6 +2363,9 @@ impl Session {
     }
 
     pub fn continue_thread(&mut self, thread_id: ThreadId, cx: &mut Context<Self>) {
+        if self.post_mortem {
+            return;
+        }
         let supports_single_thread_execution_requests =
             self.capabilities.supports_single_thread_execution_requests;
         self.thread_states.continue_thread(thread_id);
@@ -2213,6 +2398,9 @@ impl Session {
         granularity: SteppingGranularity,
         cx: &mut Context<Self>,
     ) {
+        if self.post_mortem {
+            return;
+        }
         let supports_single_thread_execution_requests =
             self.capabilities.supports_single_thread_execution_requests;
         let supports_stepping_granularity = self
@@ -2243,6 +2431,9 @@ impl Session {
         granularity: SteppingGranularity,
         cx: &mut Context<Self>,
     ) {
+        if self.post_mortem {
+            return;
+        }
         let supports_single_thread_execution_requests =
             self.capabilities.supports_single_thread_execution_requests;
         let supports_stepping_granularity = self
@@ -2273,6 +2464,9 @@ impl Session {
         granularity: SteppingGranularity,
         cx: &mut Context<Self>,
     ) {
+        if self.post_mortem {
+            return;
+        }
         let supports_single_thread_execution_requests =
             self.capabilities.supports_single_thread_execution_requests;
         let supports_stepping_granularity = self
@@ -2303,6 +2497,9 @@ impl Session {
         granularity: SteppingGranularity,
         cx: &mut Context<Self>,
     ) {
+        if self.post_mortem {
+            return;
+        }
         let supports_single_thread_execution_requests =
             self.capabilities.supports_single_thread_execution_requests;
         let supports_stepping_granularity = self
@@ -2574,7 +2771,7 @@ impl Session {
         value: String,
         cx: &mut Context<Self>,
     ) {
-        if self.capabilities.supports_set_variable.unwrap_or_default() {
+        if self.capabilities.supports_set_variable.unwrap_or_default() && !self.post_mortem {
             self.request(
                 SetVariableValueCommand {
                     name,
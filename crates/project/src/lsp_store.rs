@@ -1,4 +1,5 @@
 pub mod clangd_ext;
+pub mod extension_formatting;
 pub mod json_language_server_ext;
 pub mod lsp_ext_command;
 pub mod rust_analyzer_ext;
@@ -1439,6 +1440,36 @@ impl LocalLspStore {
                         },
                     )?;
                 }
+                Formatter::Extension(formatter_id) => {
+                    let logger = zlog::scoped!(logger => "extension");
+                    zlog::trace!(logger => "formatting");
+                    let _timer = zlog::time!(logger => "Formatting buffer via extension");
+
+                    let edits = extension_formatting::format_buffer(
+                        &lsp_store,
+                        buffer,
+                        formatter_id,
+                        &settings,
+                        cx,
+                    )
+                    .await
+                    .with_context(|| {
+                        format!("Failed to format buffer via extension formatter: {formatter_id}")
+                    })?;
+                    if edits.is_empty() {
+                        zlog::trace!(logger => "No changes");
+                        continue;
+                    }
+
+                    extend_formatting_transaction(
+                        buffer,
+                        formatting_transaction_id,
+                        cx,
+                        |buffer, cx| {
+                            buffer.edit(edits, None, cx);
+                        },
+                    )?;
+                }
                 Formatter::CodeActions(code_actions) => {
                     let logger = zlog::scoped!(logger => "code-actions");
                     zlog::trace!(logger => "formatting");
@@ -3915,8 +3946,10 @@ impl LspStore {
     ) {
         match evt {
             extension::Event::ExtensionInstalled(_)
+            | extension::Event::ExtensionUpgraded(_)
             | extension::Event::ExtensionUninstalled(_)
-            | extension::Event::ConfigureExtensionRequested(_) => return,
+            | extension::Event::ConfigureExtensionRequested(_)
+            | extension::Event::CapabilityDenied { .. } => return,
             extension::Event::ExtensionsInstalledChanged => {}
         }
         if self.as_local().is_none() {
@@ -4729,6 +4762,14 @@ impl LspStore {
                     .await
             })
         } else if self.mode.is_local() {
+            if extension_formatting::is_extension_code_action(&action) {
+                return extension_formatting::apply_code_action(
+                    buffer_handle,
+                    &action,
+                    push_to_history,
+                    cx,
+                );
+            }
             let Some((lsp_adapter, lang_server)) = buffer_handle.update(cx, |buffer, cx| {
                 self.language_server_for_local_buffer(buffer, action.server_id, cx)
                     .map(|(adapter, server)| (adapter.clone(), server.clone()))
@@ -5652,11 +5693,15 @@ impl LspStore {
                 },
                 cx,
             );
+            let extension_actions_task =
+                extension_formatting::code_actions(self, buffer, range, kinds, cx);
             cx.background_spawn(async move {
-                Ok(all_actions_task
-                    .await
+                let (all_actions, extension_actions) =
+                    futures::join!(all_actions_task, extension_actions_task);
+                Ok(all_actions
                     .into_iter()
                     .flat_map(|(_, actions)| actions)
+                    .chain(extension_actions)
                     .collect())
             })
         }
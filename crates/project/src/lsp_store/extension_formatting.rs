@@ -0,0 +1,520 @@
+//! Formatters and code action providers contributed by extensions.
+
+use std::{ops::Range, path::PathBuf, sync::Arc};
+
+use anyhow::{Context as _, Result};
+use async_trait::async_trait;
+use collections::HashMap;
+use extension::{Extension, ExtensionFormattingProxy, ExtensionHostProxy, WorktreeDelegate};
+use gpui::{App, AsyncApp, Context, Entity, Global, Task, WeakEntity};
+use language::{
+    Bias, Buffer, BufferSnapshot, LanguageName, LocalFile as _, LspAdapterDelegate,
+    language_settings::{LanguageSettings, language_settings},
+    point_to_lsp, range_from_lsp,
+};
+use lsp::{CodeActionKind, LanguageServerId};
+use parking_lot::RwLock;
+use text::{Anchor, OffsetRangeExt as _};
+use worktree::Worktree;
+
+use crate::{CodeAction, LspAction, ProjectTransaction};
+
+use super::{FormattableBuffer, LocalLspAdapterDelegate, LspStore};
+
+/// The key under which code actions provided by extensions record their provider in the
+/// action's `data`.
+const EXTENSION_CODE_ACTION_PROVIDER_KEY: &str = "extension_code_action_provider";
+
+/// The server id of code actions provided by extensions, which is never given to a language
+/// server, so that they are told apart from language server code actions.
+pub(crate) const EXTENSION_CODE_ACTION_SERVER_ID: LanguageServerId = LanguageServerId(usize::MAX);
+
+pub fn init(cx: &mut App) {
+    let proxy = ExtensionHostProxy::default_global(cx);
+    proxy.register_formatting_proxy(ExtensionFormattingRegistryProxy {
+        registry: ExtensionFormattingRegistry::default_global(cx),
+    });
+}
+
+#[derive(Clone)]
+struct ExtensionProvider {
+    extension: Arc<dyn Extension>,
+    languages: Vec<LanguageName>,
+}
+
+struct GlobalExtensionFormattingRegistry(Arc<ExtensionFormattingRegistry>);
+
+impl Global for GlobalExtensionFormattingRegistry {}
+
+/// The formatters and code action providers registered by extensions.
+#[derive(Default)]
+pub struct ExtensionFormattingRegistry {
+    formatters: RwLock<HashMap<Arc<str>, ExtensionProvider>>,
+    code_action_providers: RwLock<HashMap<Arc<str>, ExtensionProvider>>,
+}
+
+impl ExtensionFormattingRegistry {
+    /// Returns the global [`ExtensionFormattingRegistry`].
+    ///
+    /// Inserts a default [`ExtensionFormattingRegistry`] if one does not yet exist.
+    pub fn default_global(cx: &mut App) -> Arc<Self> {
+        cx.default_global::<GlobalExtensionFormattingRegistry>()
+            .0
+            .clone()
+    }
+
+    /// Returns the global [`ExtensionFormattingRegistry`], if one was initialized.
+    pub fn try_global(cx: &App) -> Option<Arc<Self>> {
+        cx.try_global::<GlobalExtensionFormattingRegistry>()
+            .map(|registry| registry.0.clone())
+    }
+
+    fn formatter(&self, formatter_id: &str, language: &LanguageName) -> Option<Arc<dyn Extension>> {
+        self.formatters
+            .read()
+            .get(formatter_id)
+            .filter(|provider| provider.languages.contains(language))
+            .map(|provider| provider.extension.clone())
+    }
+
+    fn code_action_providers(
+        &self,
+        language: &LanguageName,
+    ) -> Vec<(Arc<str>, Arc<dyn Extension>)> {
+        let mut providers = self
+            .code_action_providers
+            .read()
+            .iter()
+            .filter(|(_, provider)| provider.languages.contains(language))
+            .map(|(id, provider)| (id.clone(), provider.extension.clone()))
+            .collect::<Vec<_>>();
+        providers.sort_by(|(a, _), (b, _)| a.cmp(b));
+        providers
+    }
+}
+
+impl Default for GlobalExtensionFormattingRegistry {
+    fn default() -> Self {
+        Self(Arc::new(ExtensionFormattingRegistry::default()))
+    }
+}
+
+struct ExtensionFormattingRegistryProxy {
+    registry: Arc<ExtensionFormattingRegistry>,
+}
+
+impl ExtensionFormattingProxy for ExtensionFormattingRegistryProxy {
+    fn register_formatter(
+        &self,
+        extension: Arc<dyn Extension>,
+        formatter_id: Arc<str>,
+        languages: Vec<LanguageName>,
+    ) {
+        self.registry.formatters.write().insert(
+            formatter_id,
+            ExtensionProvider {
+                extension,
+                languages,
+            },
+        );
+    }
+
+    fn unregister_formatter(&self, formatter_id: Arc<str>) {
+        self.registry.formatters.write().remove(&formatter_id);
+    }
+
+    fn register_code_action_provider(
+        &self,
+        extension: Arc<dyn Extension>,
+        provider_id: Arc<str>,
+        languages: Vec<LanguageName>,
+    ) {
+        self.registry.code_action_providers.write().insert(
+            provider_id,
+            ExtensionProvider {
+                extension,
+                languages,
+            },
+        );
+    }
+
+    fn unregister_code_action_provider(&self, provider_id: Arc<str>) {
+        self.registry
+            .code_action_providers
+            .write()
+            .remove(&provider_id);
+    }
+}
+
+/// An adapter that allows an [`LspAdapterDelegate`] to be used as a [`WorktreeDelegate`].
+struct WorktreeDelegateAdapter(Arc<dyn LspAdapterDelegate>);
+
+#[async_trait]
+impl WorktreeDelegate for WorktreeDelegateAdapter {
+    fn id(&self) -> u64 {
+        self.0.worktree_id().to_proto()
+    }
+
+    fn root_path(&self) -> String {
+        self.0.worktree_root_path().to_string_lossy().to_string()
+    }
+
+    async fn read_text_file(&self, path: PathBuf) -> Result<String> {
+        self.0.read_text_file(path).await
+    }
+
+    async fn which(&self, binary_name: String) -> Option<String> {
+        self.0
+            .which(binary_name.as_ref())
+            .await
+            .map(|path| path.to_string_lossy().to_string())
+    }
+
+    async fn shell_env(&self) -> Vec<(String, String)> {
+        self.0.shell_env().await.into_iter().collect()
+    }
+}
+
+pub(crate) fn formatting_buffer(
+    buffer: &Buffer,
+    settings: &LanguageSettings,
+) -> extension::FormattingBuffer {
+    extension::FormattingBuffer {
+        path: buffer
+            .file()
+            .map(|file| file.path().to_string_lossy().to_string())
+            .unwrap_or_default(),
+        language_name: buffer
+            .language()
+            .map(|language| language.name().to_string())
+            .unwrap_or_default(),
+        text: buffer.text(),
+        tab_size: settings.tab_size.get(),
+        insert_spaces: !settings.hard_tabs,
+    }
+}
+
+pub(crate) fn clip_range(snapshot: &BufferSnapshot, range: Range<usize>) -> Range<usize> {
+    let start = snapshot.clip_offset(range.start.min(snapshot.len()), Bias::Left);
+    let end = snapshot.clip_offset(range.end.min(snapshot.len()), Bias::Right);
+    start..end.max(start)
+}
+
+fn worktree_delegate(
+    lsp_store: &WeakEntity<LspStore>,
+    worktree: &Entity<Worktree>,
+    cx: &mut AsyncApp,
+) -> Result<Arc<dyn WorktreeDelegate>> {
+    lsp_store.update(cx, |lsp_store, cx| {
+        let local = lsp_store
+            .as_local()
+            .context("extension formatters are only available in local projects")?;
+        let delegate = LocalLspAdapterDelegate::from_local_lsp(local, worktree, cx);
+        Ok(Arc::new(WorktreeDelegateAdapter(delegate)) as Arc<dyn WorktreeDelegate>)
+    })?
+}
+
+/// Returns the edits produced by formatting the buffer with the given extension formatter.
+pub(super) async fn format_buffer(
+    lsp_store: &WeakEntity<LspStore>,
+    buffer: &FormattableBuffer,
+    formatter_id: &Arc<str>,
+    settings: &LanguageSettings,
+    cx: &mut AsyncApp,
+) -> Result<Vec<(Range<Anchor>, String)>> {
+    let (formatting_buffer, snapshot, worktree, language) =
+        buffer.handle.read_with(cx, |buffer, _| {
+            let worktree =
+                worktree::File::from_dyn(buffer.file()).map(|file| file.worktree.clone());
+            (
+                formatting_buffer(buffer, settings),
+                buffer.snapshot(),
+                worktree,
+                buffer.language().map(|language| language.name()),
+            )
+        })?;
+    let language = language.context("buffer has no language")?;
+    let extension = cx
+        .update(|cx| {
+            ExtensionFormattingRegistry::try_global(cx)
+                .and_then(|registry| registry.formatter(formatter_id, &language))
+        })?
+        .with_context(|| {
+            format!("no extension provides a formatter named {formatter_id:?} for {language}")
+        })?;
+    let worktree = worktree.context("buffer is not part of a worktree")?;
+    let delegate = worktree_delegate(lsp_store, &worktree, cx)?;
+
+    let ranges = match buffer.ranges.as_ref() {
+        Some(ranges) => ranges
+            .iter()
+            .map(|range| Some(range.to_offset(&snapshot)))
+            .collect(),
+        None => vec![None],
+    };
+
+    let mut edits = Vec::new();
+    for range in ranges {
+        let range_edits = extension
+            .format_buffer(
+                formatter_id.clone(),
+                formatting_buffer.clone(),
+                range,
+                delegate.clone(),
+            )
+            .await?;
+        edits.extend(range_edits.into_iter().map(|edit| {
+            let range = clip_range(&snapshot, edit.range);
+            (
+                snapshot.anchor_before(range.start)..snapshot.anchor_after(range.end),
+                edit.new_text,
+            )
+        }));
+    }
+    Ok(edits)
+}
+
+fn diagnostic_severity(severity: lsp::DiagnosticSeverity) -> extension::DiagnosticSeverity {
+    match severity {
+        lsp::DiagnosticSeverity::ERROR => extension::DiagnosticSeverity::Error,
+        lsp::DiagnosticSeverity::WARNING => extension::DiagnosticSeverity::Warning,
+        lsp::DiagnosticSeverity::HINT => extension::DiagnosticSeverity::Hint,
+        _ => extension::DiagnosticSeverity::Information,
+    }
+}
+
+fn code_action_matches_kinds(action: &extension::CodeAction, kinds: &[CodeActionKind]) -> bool {
+    let Some(action_kind) = action.kind.as_deref() else {
+        return false;
+    };
+    kinds.iter().any(|kind| {
+        action_kind == kind.as_str()
+            || action_kind
+                .strip_prefix(kind.as_str())
+                .is_some_and(|rest| rest.starts_with('.'))
+    })
+}
+
+fn code_action(
+    provider_id: &str,
+    action: extension::CodeAction,
+    range: Range<Anchor>,
+    snapshot: &BufferSnapshot,
+    uri: &lsp::Url,
+) -> CodeAction {
+    let edits = action
+        .edits
+        .into_iter()
+        .map(|edit| {
+            let range = clip_range(snapshot, edit.range);
+            lsp::TextEdit::new(
+                lsp::Range::new(
+                    point_to_lsp(snapshot.offset_to_point_utf16(range.start)),
+                    point_to_lsp(snapshot.offset_to_point_utf16(range.end)),
+                ),
+                edit.new_text,
+            )
+        })
+        .collect();
+
+    CodeAction {
+        server_id: EXTENSION_CODE_ACTION_SERVER_ID,
+        range,
+        lsp_action: LspAction::Action(Box::new(lsp::CodeAction {
+            title: action.title,
+            kind: action.kind.map(CodeActionKind::from),
+            edit: Some(lsp::WorkspaceEdit {
+                changes: Some([(uri.clone(), edits)].into_iter().collect()),
+                ..Default::default()
+            }),
+            data: Some(serde_json::json!({
+                EXTENSION_CODE_ACTION_PROVIDER_KEY: provider_id,
+            })),
+            ..Default::default()
+        })),
+        resolved: true,
+    }
+}
+
+/// Requests code actions for the given range of a buffer from every extension that provides
+/// code actions for the buffer's language.
+pub(super) fn code_actions(
+    lsp_store: &LspStore,
+    buffer: &Entity<Buffer>,
+    range: Range<Anchor>,
+    kinds: Option<Vec<CodeActionKind>>,
+    cx: &mut Context<LspStore>,
+) -> Task<Vec<CodeAction>> {
+    let Some(local) = lsp_store.as_local() else {
+        return Task::ready(Vec::new());
+    };
+    let Some(registry) = ExtensionFormattingRegistry::try_global(cx) else {
+        return Task::ready(Vec::new());
+    };
+
+    let buffer = buffer.read(cx);
+    let Some(language) = buffer.language() else {
+        return Task::ready(Vec::new());
+    };
+    let providers = registry.code_action_providers(&language.name());
+    if providers.is_empty() {
+        return Task::ready(Vec::new());
+    }
+    let Some(file) = worktree::File::from_dyn(buffer.file()) else {
+        return Task::ready(Vec::new());
+    };
+    let Some(uri) = file
+        .as_local()
+        .and_then(|file| lsp::Url::from_file_path(file.abs_path(cx)).ok())
+    else {
+        return Task::ready(Vec::new());
+    };
+
+    let settings = language_settings(Some(language.name()), buffer.file(), cx);
+    let formatting_buffer = formatting_buffer(buffer, &settings);
+    let snapshot = buffer.snapshot();
+    let offset_range = range.to_offset(&snapshot);
+    let diagnostics = snapshot
+        .diagnostics_in_range::<_, usize>(range.clone(), false)
+        .map(|entry| extension::Diagnostic {
+            range: entry.range,
+            severity: diagnostic_severity(entry.diagnostic.severity),
+            message: entry.diagnostic.message,
+            source: entry.diagnostic.source,
+            code: entry.diagnostic.code.map(|code| match code {
+                lsp::NumberOrString::Number(code) => code.to_string(),
+                lsp::NumberOrString::String(code) => code,
+            }),
+        })
+        .collect::<Vec<_>>();
+    let worktree = file.worktree.clone();
+    let delegate: Arc<dyn WorktreeDelegate> = Arc::new(WorktreeDelegateAdapter(
+        LocalLspAdapterDelegate::from_local_lsp(local, &worktree, cx),
+    ));
+
+    cx.background_spawn(async move {
+        let mut actions = Vec::new();
+        for (provider_id, extension) in providers {
+            let provider_actions = extension
+                .code_actions(
+                    provider_id.clone(),
+                    formatting_buffer.clone(),
+                    offset_range.clone(),
+                    diagnostics.clone(),
+                    delegate.clone(),
+                )
+                .await;
+            match provider_actions {
+                Ok(provider_actions) => {
+                    actions.extend(
+                        provider_actions
+                            .into_iter()
+                            .filter(|action| {
+                                kinds
+                                    .as_deref()
+                                    .is_none_or(|kinds| code_action_matches_kinds(action, kinds))
+                            })
+                            .map(|action| {
+                                code_action(&provider_id, action, range.clone(), &snapshot, &uri)
+                            }),
+                    );
+                }
+                Err(error) => {
+                    log::error!(
+                        "failed to get code actions from extension provider {provider_id}: {error:#}"
+                    );
+                }
+            }
+        }
+        actions
+    })
+}
+
+/// Returns whether the code action was provided by an extension rather than a language server.
+pub(super) fn is_extension_code_action(action: &CodeAction) -> bool {
+    action.server_id == EXTENSION_CODE_ACTION_SERVER_ID
+}
+
+/// Applies the edits of a code action that was provided by an extension.
+pub(super) fn apply_code_action(
+    buffer_handle: Entity<Buffer>,
+    action: &CodeAction,
+    push_to_history: bool,
+    cx: &mut Context<LspStore>,
+) -> Task<Result<ProjectTransaction>> {
+    let edits = action
+        .lsp_action
+        .edit()
+        .and_then(|edit| edit.changes.as_ref())
+        .into_iter()
+        .flat_map(|changes| changes.values().flatten())
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let transaction = buffer_handle.update(cx, |buffer, cx| {
+        let snapshot = buffer.snapshot();
+        let edits = edits
+            .into_iter()
+            .map(|edit| {
+                let range = range_from_lsp(edit.range);
+                let start = snapshot.clip_point_utf16(range.start, Bias::Left);
+                let end = snapshot.clip_point_utf16(range.end, Bias::Left);
+                (start..end, edit.new_text)
+            })
+            .collect::<Vec<_>>();
+
+        buffer.finalize_last_transaction();
+        buffer.start_transaction();
+        buffer.edit(edits, None, cx);
+        buffer.end_transaction(cx).and_then(|transaction_id| {
+            if push_to_history {
+                buffer.finalize_last_transaction();
+                buffer.get_transaction(transaction_id).cloned()
+            } else {
+                buffer.forget_transaction(transaction_id)
+            }
+        })
+    });
+
+    let mut project_transaction = ProjectTransaction::default();
+    if let Some(transaction) = transaction {
+        project_transaction.0.insert(buffer_handle, transaction);
+    }
+    Task::ready(Ok(project_transaction))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(kind: Option<&str>) -> extension::CodeAction {
+        extension::CodeAction {
+            title: "Fix".to_string(),
+            kind: kind.map(ToString::to_string),
+            edits: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_code_action_matches_kinds() {
+        let kinds = [CodeActionKind::QUICKFIX, CodeActionKind::REFACTOR_EXTRACT];
+        assert!(code_action_matches_kinds(&action(Some("quickfix")), &kinds));
+        assert!(code_action_matches_kinds(
+            &action(Some("quickfix.unused")),
+            &kinds
+        ));
+        assert!(code_action_matches_kinds(
+            &action(Some("refactor.extract.function")),
+            &kinds
+        ));
+        assert!(!code_action_matches_kinds(
+            &action(Some("quickfixes")),
+            &kinds
+        ));
+        assert!(!code_action_matches_kinds(
+            &action(Some("refactor")),
+            &kinds
+        ));
+        assert!(!code_action_matches_kinds(&action(None), &kinds));
+    }
+}
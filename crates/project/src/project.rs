@@ -1017,6 +1017,8 @@ impl Project {
         DapStore::init(&client, cx);
         BreakpointStore::init(&client);
         context_server_store::init(cx);
+        lsp_store::extension_formatting::init(cx);
+        task_store::extension_tasks::init(cx);
     }
 
     pub fn local(
@@ -1166,6 +1168,7 @@ impl Project {
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    remote_tmux: None,
                 },
                 node: Some(node),
                 search_history: Self::new_search_history(),
@@ -1289,7 +1292,7 @@ impl Project {
 
             cx.subscribe(&ssh, Self::on_ssh_event).detach();
 
-            let this = Self {
+            let mut this = Self {
                 buffer_ordered_messages_tx: tx,
                 collaborators: Default::default(),
                 worktree_store,
@@ -1333,6 +1336,7 @@ impl Project {
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    remote_tmux: None,
                 },
                 node: Some(node),
                 search_history: Self::new_search_history(),
@@ -1371,6 +1375,7 @@ impl Project {
             DapStore::init(&ssh_proto, cx);
             GitStore::init(&ssh_proto);
 
+            this.detect_remote_tmux(cx);
             this
         })
     }
@@ -1594,6 +1599,7 @@ impl Project {
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    remote_tmux: None,
                 },
                 node: None,
                 search_history: Self::new_search_history(),
@@ -1941,6 +1947,17 @@ impl Project {
         &self.task_store
     }
 
+    /// Returns the runnables that extensions detect in the given buffer.
+    ///
+    /// The ranges of the returned runnables are offsets into the buffer.
+    pub fn extension_runnables(
+        &self,
+        buffer: &Entity<Buffer>,
+        cx: &App,
+    ) -> Task<Vec<language::RunnableRange>> {
+        task_store::extension_tasks::detect_runnables(buffer, self.fs.clone(), cx)
+    }
+
     pub fn snippets(&self) -> &Entity<SnippetProvider> {
         &self.snippets
     }
@@ -3024,6 +3041,10 @@ impl Project {
                 });
                 cx.emit(Event::DisconnectedFromSshRemote);
             }
+            remote::SshRemoteEvent::Reconnected => {
+                self.detect_remote_tmux(cx);
+                self.reattach_ssh_terminals(cx);
+            }
         }
     }
 
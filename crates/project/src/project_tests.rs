@@ -8,6 +8,7 @@ use buffer_diff::{
     BufferDiffEvent, CALCULATE_DIFF_TASK, DiffHunkSecondaryStatus, DiffHunkStatus,
     DiffHunkStatusKind, assert_hunks,
 };
+use extension::{ExtensionFormattingProxy as _, ExtensionHostProxy, FakeExtension};
 use fs::FakeFs;
 use futures::{StreamExt, future};
 use git::{
@@ -22,7 +23,10 @@ use itertools::Itertools;
 use language::{
     Diagnostic, DiagnosticEntry, DiagnosticSet, DiagnosticSourceKind, DiskState, FakeLspAdapter,
     LanguageConfig, LanguageMatcher, LanguageName, LineEnding, OffsetRangeExt, Point, ToPoint,
-    language_settings::{AllLanguageSettings, LanguageSettingsContent, language_settings},
+    language_settings::{
+        AllLanguageSettings, Formatter, FormatterList, LanguageSettingsContent, SelectedFormatter,
+        language_settings,
+    },
     tree_sitter_rust, tree_sitter_typescript,
 };
 use lsp::{
@@ -8833,6 +8837,94 @@ async fn test_git_worktrees_and_submodules(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_extension_formatter(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let extension = Arc::new(
+        FakeExtension::new(FakeExtension::empty_manifest("shout")).with_formatter(|buffer| {
+            vec![extension::TextEdit {
+                range: 0..buffer.text.len(),
+                new_text: buffer.text.to_uppercase(),
+            }]
+        }),
+    );
+    cx.update(|cx| {
+        lsp_store::extension_formatting::init(cx);
+        ExtensionHostProxy::default_global(cx).register_formatter(
+            extension.clone(),
+            "shout".into(),
+            vec!["Rust".into()],
+        );
+        SettingsStore::update_global(cx, |settings, cx| {
+            settings.update_user_settings::<AllLanguageSettings>(cx, |settings| {
+                settings.defaults.formatter = Some(SelectedFormatter::List(FormatterList::Single(
+                    Formatter::Extension("shout".into()),
+                )));
+            });
+        });
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "main.rs": "fn main() {}\n",
+            "data.json": "{}\n",
+        }),
+    )
+    .await;
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+    let languages = project.read_with(cx, |project, _| project.languages().clone());
+    languages.add(rust_lang());
+    languages.add(json_lang());
+
+    let rust_buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(path!("/dir/main.rs"), cx)
+        })
+        .await
+        .unwrap();
+    project
+        .update(cx, |project, cx| {
+            project.format(
+                HashSet::from_iter([rust_buffer.clone()]),
+                LspFormatTarget::Buffers,
+                true,
+                lsp_store::FormatTrigger::Manual,
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    rust_buffer.read_with(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "FN MAIN() {}\n");
+    });
+    assert_eq!(extension.format_count(), 1);
+
+    // The formatter isn't used for languages it doesn't support.
+    let json_buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(path!("/dir/data.json"), cx)
+        })
+        .await
+        .unwrap();
+    let result = project
+        .update(cx, |project, cx| {
+            project.format(
+                HashSet::from_iter([json_buffer.clone()]),
+                LspFormatTarget::Buffers,
+                true,
+                lsp_store::FormatTrigger::Manual,
+                cx,
+            )
+        })
+        .await;
+    assert!(result.is_err());
+    json_buffer.read_with(cx, |buffer, _| assert_eq!(buffer.text(), "{}\n"));
+    assert_eq!(extension.format_count(), 1);
+}
+
 #[gpui::test]
 async fn test_repository_deduplication(cx: &mut gpui::TestAppContext) {
     init_test(cx);
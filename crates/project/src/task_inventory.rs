@@ -8,10 +8,11 @@ use std::{
     sync::Arc,
 };
 
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 use collections::{HashMap, HashSet, VecDeque};
 use dap::DapRegistry;
 use fs::Fs;
+use futures::future::join_all;
 use gpui::{App, AppContext as _, Context, Entity, SharedString, Task, WeakEntity};
 use itertools::Itertools;
 use language::{
@@ -22,14 +23,17 @@ use lsp::{LanguageServerId, LanguageServerName};
 use paths::{debug_task_file_name, task_file_name};
 use settings::{InvalidSettingsError, parse_json_with_comments};
 use task::{
-    DebugScenario, ResolvedTask, TaskContext, TaskId, TaskTemplate, TaskTemplates, TaskVariables,
-    VariableName,
+    DebugCompound, DebugScenario, DebugTaskEntry, ResolvedTask, TaskContext, TaskId, TaskTemplate,
+    TaskTemplates, TaskVariables, VariableName,
 };
 use text::{BufferId, Point, ToPoint};
 use util::{NumericPrefixWithSuffix, ResultExt as _, paths::PathExt as _, post_inc};
 use worktree::WorktreeId;
 
-use crate::{task_store::TaskSettingsLocation, worktree_store::WorktreeStore};
+use crate::{
+    task_store::{TaskSettingsLocation, extension_tasks},
+    worktree_store::WorktreeStore,
+};
 
 #[derive(Clone, Debug, Default)]
 pub struct DebugScenarioContext {
@@ -45,6 +49,7 @@ pub struct Inventory {
     last_scheduled_scenarios: VecDeque<(DebugScenario, DebugScenarioContext)>,
     templates_from_settings: InventoryFor<TaskTemplate>,
     scenarios_from_settings: InventoryFor<DebugScenario>,
+    compounds_from_settings: InventoryFor<DebugCompound>,
 }
 
 impl std::fmt::Debug for Inventory {
@@ -54,6 +59,7 @@ impl std::fmt::Debug for Inventory {
             .field("last_scheduled_scenarios", &self.last_scheduled_scenarios)
             .field("templates_from_settings", &self.templates_from_settings)
             .field("scenarios_from_settings", &self.scenarios_from_settings)
+            .field("compounds_from_settings", &self.compounds_from_settings)
             .finish()
     }
 }
@@ -75,6 +81,12 @@ impl InventoryContents for DebugScenario {
     const LABEL: &'static str = "debug scenarios";
 }
 
+impl InventoryContents for DebugCompound {
+    const GLOBAL_SOURCE_FILE: &'static str = "debug.json";
+
+    const LABEL: &'static str = "compound debug scenarios";
+}
+
 #[derive(Debug)]
 struct InventoryFor<T> {
     global: HashMap<PathBuf, Vec<T>>,
@@ -249,6 +261,7 @@ impl Inventory {
             last_scheduled_scenarios: VecDeque::default(),
             templates_from_settings: InventoryFor::default(),
             scenarios_from_settings: InventoryFor::default(),
+            compounds_from_settings: InventoryFor::default(),
         })
     }
 
@@ -346,6 +359,68 @@ impl Inventory {
         })
     }
 
+    /// Lists compound debug scenarios from the settings files, worktree ones first.
+    pub fn list_debug_compounds(
+        &self,
+        task_contexts: &TaskContexts,
+    ) -> Vec<(TaskSourceKind, DebugCompound)> {
+        let mut compounds = Vec::new();
+        if let Some(worktree_id) = task_contexts
+            .active_worktree_context
+            .iter()
+            .chain(task_contexts.other_worktree_contexts.iter())
+            .map(|context| context.0)
+            .next()
+        {
+            compounds.extend(self.compounds_from_settings.worktree_scenarios(worktree_id));
+        }
+        compounds.extend(self.compounds_from_settings.global_scenarios());
+        compounds
+    }
+
+    /// Resolves the scenarios a compound refers to, in the compound's order.
+    /// Scenarios defined in the same file as the compound take precedence, then ones from the same worktree, then global ones.
+    pub fn debug_compound_scenarios(
+        &self,
+        source_kind: &TaskSourceKind,
+        compound: &DebugCompound,
+    ) -> Result<Vec<DebugScenario>> {
+        let worktree_scenarios = match source_kind {
+            TaskSourceKind::Worktree { id, .. } => self
+                .worktree_scenarios_from_settings(*id)
+                .collect::<Vec<_>>(),
+            _ => Vec::new(),
+        };
+        let global_scenarios = self
+            .global_debug_scenarios_from_settings()
+            .collect::<Vec<_>>();
+        let candidates = worktree_scenarios
+            .iter()
+            .chain(global_scenarios.iter())
+            .filter(|(kind, _)| same_source_file(kind, source_kind))
+            .chain(worktree_scenarios.iter())
+            .chain(global_scenarios.iter())
+            .map(|(_, scenario)| scenario)
+            .collect::<Vec<_>>();
+
+        compound
+            .configurations
+            .iter()
+            .map(|label| {
+                candidates
+                    .iter()
+                    .find(|scenario| &scenario.label == label)
+                    .map(|scenario| (*scenario).clone())
+                    .with_context(|| {
+                        format!(
+                            "compound {:?} refers to unknown debug scenario {label:?}",
+                            compound.label
+                        )
+                    })
+            })
+            .collect()
+    }
+
     pub fn task_template_by_label(
         &self,
         buffer: Option<Entity<Buffer>>,
@@ -395,26 +470,14 @@ impl Inventory {
         let task_source_kind = language.as_ref().map(|language| TaskSourceKind::Language {
             name: language.name().into(),
         });
-        let language_tasks = language
-            .filter(|language| {
-                language_settings(Some(language.name()), file.as_ref(), cx)
-                    .tasks
-                    .enabled
-            })
-            .and_then(|language| {
-                language
-                    .context_provider()
-                    .map(|provider| provider.associated_tasks(fs, file, cx))
-            });
+        let language_tasks = language_associated_tasks(language, fs, file, cx);
         cx.background_spawn(async move {
-            if let Some(t) = language_tasks {
-                worktree_tasks.extend(t.await.into_iter().flat_map(|tasks| {
-                    tasks
-                        .0
-                        .into_iter()
-                        .filter_map(|task| Some((task_source_kind.clone()?, task)))
-                }));
-            }
+            worktree_tasks.extend(
+                language_tasks
+                    .await
+                    .into_iter()
+                    .filter_map(|task| Some((task_source_kind.clone()?, task))),
+            );
             worktree_tasks.extend(global_tasks);
             worktree_tasks
         })
@@ -480,37 +543,21 @@ impl Inventory {
 
         let not_used_score = post_inc(&mut lru_score);
         let global_tasks = self.global_templates_from_settings().collect::<Vec<_>>();
-        let associated_tasks = language
-            .filter(|language| {
-                language_settings(Some(language.name()), file.as_ref(), cx)
-                    .tasks
-                    .enabled
-            })
-            .and_then(|language| {
-                language
-                    .context_provider()
-                    .map(|provider| provider.associated_tasks(fs, file, cx))
-            });
+        let associated_tasks = language_associated_tasks(language, fs, file, cx);
         let worktree_tasks = worktree
             .into_iter()
             .flat_map(|worktree| self.worktree_templates_from_settings(worktree))
             .collect::<Vec<_>>();
         let task_contexts = task_contexts.clone();
         cx.background_spawn(async move {
-            let language_tasks = if let Some(task) = associated_tasks {
-                task.await.map(|templates| {
-                    templates
-                        .0
-                        .into_iter()
-                        .flat_map(|task| Some((task_source_kind.clone()?, task)))
-                })
-            } else {
-                None
-            };
+            let language_tasks = associated_tasks
+                .await
+                .into_iter()
+                .flat_map(|task| Some((task_source_kind.clone()?, task)));
 
             let worktree_tasks = worktree_tasks
                 .into_iter()
-                .chain(language_tasks.into_iter().flatten())
+                .chain(language_tasks)
                 .chain(global_tasks);
 
             let new_resolved_tasks = worktree_tasks
@@ -742,12 +789,13 @@ impl Inventory {
             }
         };
 
-        let new_templates = raw_tasks
+        let (new_templates, new_compounds): (Vec<_>, Vec<_>) = raw_tasks
             .into_iter()
-            .filter_map(|raw_template| {
-                serde_json::from_value::<DebugScenario>(raw_template).log_err()
-            })
-            .collect::<Vec<_>>();
+            .filter_map(|raw_template| parse_debug_task_entry(raw_template).log_err())
+            .partition_map(|entry| match entry {
+                DebugTaskEntry::Scenario(scenario) => itertools::Either::Left(scenario),
+                DebugTaskEntry::Compound(compound) => itertools::Either::Right(compound),
+            });
 
         let parsed_scenarios = &mut self.scenarios_from_settings;
         let mut new_definitions: HashMap<_, _> = new_templates
@@ -766,6 +814,10 @@ impl Inventory {
                     .global
                     .entry(path.to_owned())
                     .insert_entry(new_templates);
+                self.compounds_from_settings
+                    .global
+                    .entry(path.to_owned())
+                    .insert_entry(new_compounds);
             }
             TaskSettingsLocation::Worktree(location) => {
                 previously_existing_scenarios = parsed_scenarios
@@ -786,6 +838,22 @@ impl Inventory {
                         .or_default()
                         .insert(Arc::from(location.path), new_templates);
                 }
+
+                if new_compounds.is_empty() {
+                    if let Some(worktree_compounds) = self
+                        .compounds_from_settings
+                        .worktree
+                        .get_mut(&location.worktree_id)
+                    {
+                        worktree_compounds.remove(location.path);
+                    }
+                } else {
+                    self.compounds_from_settings
+                        .worktree
+                        .entry(location.worktree_id)
+                        .or_default()
+                        .insert(Arc::from(location.path), new_compounds);
+                }
             }
         }
         self.last_scheduled_scenarios.retain_mut(|(scenario, _)| {
@@ -804,6 +872,43 @@ impl Inventory {
     }
 }
 
+/// Entries with `configurations` and without an `adapter` are compounds, everything else is a scenario.
+/// Deciding upfront (instead of an untagged deserialization) keeps the parse errors specific.
+fn parse_debug_task_entry(raw: serde_json::Value) -> serde_json::Result<DebugTaskEntry> {
+    if raw.get("configurations").is_some() && raw.get("adapter").is_none() {
+        serde_json::from_value::<DebugCompound>(raw).map(DebugTaskEntry::Compound)
+    } else {
+        serde_json::from_value::<DebugScenario>(raw).map(DebugTaskEntry::Scenario)
+    }
+}
+
+/// Whether both kinds point to the same settings file, ignoring the kind of contents it was parsed into.
+fn same_source_file(a: &TaskSourceKind, b: &TaskSourceKind) -> bool {
+    match (a, b) {
+        (
+            TaskSourceKind::Worktree {
+                id: a_id,
+                directory_in_worktree: a_directory,
+                ..
+            },
+            TaskSourceKind::Worktree {
+                id: b_id,
+                directory_in_worktree: b_directory,
+                ..
+            },
+        ) => a_id == b_id && a_directory == b_directory,
+        (
+            TaskSourceKind::AbsPath {
+                abs_path: a_path, ..
+            },
+            TaskSourceKind::AbsPath {
+                abs_path: b_path, ..
+            },
+        ) => a_path == b_path,
+        _ => false,
+    }
+}
+
 fn task_lru_comparator(
     (kind_a, task_a, lru_score_a): &(TaskSourceKind, ResolvedTask, u32),
     (kind_b, task_b, lru_score_b): &(TaskSourceKind, ResolvedTask, u32),
@@ -1074,6 +1179,37 @@ impl ContextProvider for BasicContextProvider {
     }
 }
 
+/// Gathers the tasks associated with the language, both from its own context provider and from
+/// the task providers that extensions register for it.
+fn language_associated_tasks(
+    language: Option<Arc<Language>>,
+    fs: Arc<dyn Fs>,
+    file: Option<Arc<dyn File>>,
+    cx: &App,
+) -> Task<Vec<TaskTemplate>> {
+    let Some(language) = language.filter(|language| {
+        language_settings(Some(language.name()), file.as_ref(), cx)
+            .tasks
+            .enabled
+    }) else {
+        return Task::ready(Vec::new());
+    };
+    let tasks = language
+        .context_provider()
+        .into_iter()
+        .chain(extension_tasks::context_provider(&language.name(), cx))
+        .map(|provider| provider.associated_tasks(fs.clone(), file.clone(), cx))
+        .collect::<Vec<_>>();
+    cx.background_spawn(async move {
+        join_all(tasks)
+            .await
+            .into_iter()
+            .flatten()
+            .flat_map(|templates| templates.0)
+            .collect()
+    })
+}
+
 /// A ContextProvider that doesn't provide any task variables on it's own, though it has some associated tasks.
 pub struct ContextProviderWithTasks {
     templates: TaskTemplates,
@@ -1403,6 +1539,97 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_debug_compounds(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        let inventory = cx.update(|cx| Inventory::new(fs, cx));
+        let worktree_id = WorktreeId::from_usize(0);
+        inventory.update(cx, |inventory, _| {
+            inventory
+                .update_file_based_scenarios(
+                    TaskSettingsLocation::Global(Path::new("/config/debug.json")),
+                    Some(
+                        r#"
+                        [{
+                            "label": "worker",
+                            "adapter": "CodeLLDB",
+                            "request": "launch",
+                            "program": "global-worker",
+                        }, {
+                            "label": "server",
+                            "adapter": "CodeLLDB",
+                            "request": "launch",
+                            "program": "global-server",
+                        }]
+                        "#,
+                    ),
+                )
+                .unwrap();
+            inventory
+                .update_file_based_scenarios(
+                    TaskSettingsLocation::Worktree(SettingsLocation {
+                        worktree_id,
+                        path: Path::new(".zed"),
+                    }),
+                    Some(
+                        r#"
+                        [{
+                            "label": "server",
+                            "adapter": "Delve",
+                            "request": "launch",
+                            "program": "server",
+                        }, {
+                            "label": "everything",
+                            "configurations": ["server", "worker"],
+                            "stop_all": true,
+                        }, {
+                            "label": "broken",
+                            "configurations": ["server", "missing"],
+                        }]
+                        "#,
+                    ),
+                )
+                .unwrap();
+        });
+
+        let task_contexts = TaskContexts {
+            active_worktree_context: Some((worktree_id, TaskContext::default())),
+            ..TaskContexts::default()
+        };
+        let (scenarios, compounds) = inventory.update(cx, |inventory, cx| {
+            let compounds = inventory.list_debug_compounds(&task_contexts);
+            let scenarios =
+                inventory.list_debug_scenarios(&task_contexts, vec![], vec![], false, cx);
+            (scenarios, compounds)
+        });
+        assert_eq!(scenarios.await.1.len(), 3);
+        assert_eq!(
+            compounds
+                .iter()
+                .map(|(_, compound)| compound.label.as_ref())
+                .collect::<Vec<_>>(),
+            vec!["everything", "broken"]
+        );
+
+        inventory.update(cx, |inventory, _| {
+            let (kind, everything) = &compounds[0];
+            let resolved = inventory
+                .debug_compound_scenarios(kind, everything)
+                .unwrap();
+            assert_eq!(
+                resolved
+                    .iter()
+                    .map(|scenario| (scenario.label.as_ref(), scenario.adapter.as_ref()))
+                    .collect::<Vec<_>>(),
+                vec![("server", "Delve"), ("worker", "CodeLLDB")]
+            );
+
+            let (kind, broken) = &compounds[1];
+            assert!(inventory.debug_compound_scenarios(kind, broken).is_err());
+        });
+    }
+
     #[gpui::test]
     async fn test_inventory_static_task_filters(cx: &mut TestAppContext) {
         init_test(cx);
@@ -22,6 +22,8 @@ use crate::{
     worktree_store::WorktreeStore,
 };
 
+pub mod extension_tasks;
+
 // platform-dependent warning
 pub enum TaskStore {
     Functional(StoreState),
@@ -454,11 +456,10 @@ fn combine_task_variables(
     toolchain_store: Arc<dyn LanguageToolchainStore>,
     cx: &mut App,
 ) -> Task<anyhow::Result<TaskVariables>> {
-    let language_context_provider = location
-        .buffer
-        .read(cx)
-        .language()
-        .and_then(|language| language.context_provider());
+    let language = location.buffer.read(cx).language();
+    let language_context_provider = language.and_then(|language| language.context_provider());
+    let extension_context_provider =
+        language.and_then(|language| extension_tasks::context_provider(&language.name(), cx));
     cx.spawn(async move |cx| {
         let baseline = cx
             .update(|cx| {
@@ -479,6 +480,26 @@ fn combine_task_variables(
             .context("building basic default context")?;
         captured_variables.extend(baseline);
         if let Some(provider) = language_context_provider {
+            captured_variables.extend(
+                cx.update(|cx| {
+                    let worktree_root = worktree_root(&worktree_store, &location, cx);
+                    provider.build_context(
+                        &captured_variables,
+                        ContextLocation {
+                            fs: fs.clone(),
+                            worktree_root,
+                            file_location: &location,
+                        },
+                        project_env.clone(),
+                        toolchain_store.clone(),
+                        cx,
+                    )
+                })?
+                .await
+                .context("building provider context")?,
+            );
+        }
+        if let Some(provider) = extension_context_provider {
             captured_variables.extend(
                 cx.update(|cx| {
                     let worktree_root = worktree_root(&worktree_store, &location, cx);
@@ -495,7 +516,7 @@ fn combine_task_variables(
                     )
                 })?
                 .await
-                .context("building provider context")?,
+                .context("building extension provider context")?,
             );
         }
         Ok(captured_variables)
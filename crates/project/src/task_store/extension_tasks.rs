@@ -0,0 +1,370 @@
+//! Task templates, task variables and runnables contributed by extensions.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Context as _, Result};
+use async_trait::async_trait;
+use collections::HashMap;
+use extension::{Extension, ExtensionHostProxy, ExtensionTaskProviderProxy, WorktreeDelegate};
+use fs::Fs;
+use gpui::{App, Entity, Global, Task};
+use language::{
+    Buffer, ContextLocation, ContextProvider, File, LanguageName, LanguageToolchainStore, Runnable,
+    RunnableRange, language_settings::language_settings,
+};
+use parking_lot::RwLock;
+use task::{RunnableTag, TaskTemplate, TaskTemplates, TaskVariables, VariableName};
+use text::ToPoint as _;
+use util::ResultExt as _;
+use worktree::WorktreeId;
+
+use crate::lsp_store::extension_formatting::{clip_range, formatting_buffer};
+
+pub fn init(cx: &mut App) {
+    let proxy = ExtensionHostProxy::default_global(cx);
+    proxy.register_task_provider_proxy(ExtensionTaskRegistryProxy {
+        registry: ExtensionTaskRegistry::default_global(cx),
+    });
+}
+
+#[derive(Clone)]
+struct ExtensionTaskProvider {
+    extension: Arc<dyn Extension>,
+    languages: Vec<LanguageName>,
+}
+
+struct GlobalExtensionTaskRegistry(Arc<ExtensionTaskRegistry>);
+
+impl Global for GlobalExtensionTaskRegistry {}
+
+/// The task providers registered by extensions.
+#[derive(Default)]
+pub struct ExtensionTaskRegistry {
+    providers: RwLock<HashMap<Arc<str>, ExtensionTaskProvider>>,
+}
+
+impl ExtensionTaskRegistry {
+    /// Returns the global [`ExtensionTaskRegistry`].
+    ///
+    /// Inserts a default [`ExtensionTaskRegistry`] if one does not yet exist.
+    pub fn default_global(cx: &mut App) -> Arc<Self> {
+        cx.default_global::<GlobalExtensionTaskRegistry>().0.clone()
+    }
+
+    /// Returns the global [`ExtensionTaskRegistry`], if one was initialized.
+    pub fn try_global(cx: &App) -> Option<Arc<Self>> {
+        cx.try_global::<GlobalExtensionTaskRegistry>()
+            .map(|registry| registry.0.clone())
+    }
+
+    fn providers(&self, language: &LanguageName) -> Vec<(Arc<str>, Arc<dyn Extension>)> {
+        let mut providers = self
+            .providers
+            .read()
+            .iter()
+            .filter(|(_, provider)| provider.languages.contains(language))
+            .map(|(id, provider)| (id.clone(), provider.extension.clone()))
+            .collect::<Vec<_>>();
+        providers.sort_by(|(a, _), (b, _)| a.cmp(b));
+        providers
+    }
+}
+
+impl Default for GlobalExtensionTaskRegistry {
+    fn default() -> Self {
+        Self(Arc::new(ExtensionTaskRegistry::default()))
+    }
+}
+
+struct ExtensionTaskRegistryProxy {
+    registry: Arc<ExtensionTaskRegistry>,
+}
+
+impl ExtensionTaskProviderProxy for ExtensionTaskRegistryProxy {
+    fn register_task_provider(
+        &self,
+        extension: Arc<dyn Extension>,
+        provider_id: Arc<str>,
+        languages: Vec<LanguageName>,
+    ) {
+        self.registry.providers.write().insert(
+            provider_id,
+            ExtensionTaskProvider {
+                extension,
+                languages,
+            },
+        );
+    }
+
+    fn unregister_task_provider(&self, provider_id: Arc<str>) {
+        self.registry.providers.write().remove(&provider_id);
+    }
+}
+
+/// The worktree that extension task providers are queried for.
+struct TaskWorktreeDelegate {
+    id: WorktreeId,
+    root_path: Arc<Path>,
+    fs: Arc<dyn Fs>,
+    shell_env: HashMap<String, String>,
+}
+
+#[async_trait]
+impl WorktreeDelegate for TaskWorktreeDelegate {
+    fn id(&self) -> u64 {
+        self.id.to_proto()
+    }
+
+    fn root_path(&self) -> String {
+        self.root_path.to_string_lossy().to_string()
+    }
+
+    async fn read_text_file(&self, path: PathBuf) -> Result<String> {
+        self.fs.load(&self.root_path.join(path)).await
+    }
+
+    async fn which(&self, binary_name: String) -> Option<String> {
+        let shell_path = self.shell_env.get("PATH");
+        let path = if shell_path.is_some() {
+            which::which_in(binary_name, shell_path, &self.root_path).ok()?
+        } else {
+            which::which(binary_name).ok()?
+        };
+        Some(path.to_string_lossy().to_string())
+    }
+
+    async fn shell_env(&self) -> Vec<(String, String)> {
+        self.shell_env.clone().into_iter().collect()
+    }
+}
+
+/// Returns a delegate for the local worktree containing the given file.
+///
+/// Extensions run on the local machine, so files of remote worktrees are not supported.
+fn worktree_delegate(
+    file: &Arc<dyn File>,
+    fs: Arc<dyn Fs>,
+    shell_env: HashMap<String, String>,
+    cx: &App,
+) -> Option<Arc<dyn WorktreeDelegate>> {
+    let file = worktree::File::from_dyn(Some(file))?;
+    let worktree = file.worktree.read(cx);
+    if !worktree.is_local() {
+        return None;
+    }
+    Some(Arc::new(TaskWorktreeDelegate {
+        id: worktree.id(),
+        root_path: worktree.abs_path(),
+        fs,
+        shell_env,
+    }))
+}
+
+fn task_template(definition: extension::TaskDefinition) -> TaskTemplate {
+    TaskTemplate {
+        label: definition.label,
+        command: definition.command,
+        args: definition.args,
+        env: definition.env.into_iter().collect(),
+        cwd: definition.cwd,
+        tags: definition.tags,
+        ..TaskTemplate::default()
+    }
+}
+
+/// Returns a [`ContextProvider`] for the extension task providers registered for the given language.
+pub fn context_provider(language: &LanguageName, cx: &App) -> Option<Arc<dyn ContextProvider>> {
+    let providers = ExtensionTaskRegistry::try_global(cx)?.providers(language);
+    if providers.is_empty() {
+        return None;
+    }
+    Some(Arc::new(ExtensionTaskContextProvider { providers }))
+}
+
+struct ExtensionTaskContextProvider {
+    providers: Vec<(Arc<str>, Arc<dyn Extension>)>,
+}
+
+impl ContextProvider for ExtensionTaskContextProvider {
+    fn build_context(
+        &self,
+        variables: &TaskVariables,
+        location: ContextLocation<'_>,
+        project_env: Option<HashMap<String, String>>,
+        _: Arc<dyn LanguageToolchainStore>,
+        cx: &mut App,
+    ) -> Task<Result<TaskVariables>> {
+        let buffer = location.file_location.buffer.read(cx);
+        let delegate = location.fs.zip(buffer.file()).and_then(|(fs, file)| {
+            worktree_delegate(file, fs, project_env.unwrap_or_default(), cx)
+        });
+        let Some(delegate) = delegate else {
+            return Task::ready(Ok(TaskVariables::default()));
+        };
+
+        let position = location.file_location.range.start.to_point(buffer);
+        let task_location = extension::TaskLocation {
+            path: buffer
+                .file()
+                .map(|file| file.path().to_string_lossy().to_string())
+                .unwrap_or_default(),
+            language_name: buffer
+                .language()
+                .map(|language| language.name().to_string())
+                .unwrap_or_default(),
+            row: position.row,
+            column: position.column,
+            symbol: variables.get(&VariableName::Symbol).map(ToOwned::to_owned),
+        };
+        let providers = self.providers.clone();
+        cx.background_spawn(async move {
+            let mut task_variables = TaskVariables::default();
+            for (provider_id, extension) in providers {
+                let Some(variables) = extension
+                    .task_variables(provider_id.clone(), task_location.clone(), delegate.clone())
+                    .await
+                    .with_context(|| format!("getting task variables from {provider_id:?}"))
+                    .log_err()
+                else {
+                    continue;
+                };
+                for (name, value) in variables {
+                    task_variables.insert(VariableName::Custom(name.into()), value);
+                }
+            }
+            Ok(task_variables)
+        })
+    }
+
+    fn associated_tasks(
+        &self,
+        fs: Arc<dyn Fs>,
+        file: Option<Arc<dyn File>>,
+        cx: &App,
+    ) -> Task<Option<TaskTemplates>> {
+        let Some(delegate) = file
+            .as_ref()
+            .and_then(|file| worktree_delegate(file, fs, HashMap::default(), cx))
+        else {
+            return Task::ready(None);
+        };
+
+        let providers = self.providers.clone();
+        cx.background_spawn(async move {
+            let mut templates = Vec::new();
+            for (provider_id, extension) in providers {
+                let Some(definitions) = extension
+                    .task_templates(provider_id.clone(), delegate.clone())
+                    .await
+                    .with_context(|| format!("getting task templates from {provider_id:?}"))
+                    .log_err()
+                else {
+                    continue;
+                };
+                templates.extend(definitions.into_iter().map(task_template));
+            }
+            Some(TaskTemplates(templates))
+        })
+    }
+}
+
+/// Returns the runnables that extension task providers detect in the given buffer.
+///
+/// The ranges of the returned runnables are offsets into the buffer.
+pub fn detect_runnables(
+    buffer: &Entity<Buffer>,
+    fs: Arc<dyn Fs>,
+    cx: &App,
+) -> Task<Vec<RunnableRange>> {
+    let buffer = buffer.read(cx);
+    let Some(language) = buffer.language().cloned() else {
+        return Task::ready(Vec::new());
+    };
+    let settings = language_settings(Some(language.name()), buffer.file(), cx);
+    if !settings.tasks.enabled {
+        return Task::ready(Vec::new());
+    }
+    let providers = ExtensionTaskRegistry::try_global(cx)
+        .map(|registry| registry.providers(&language.name()))
+        .unwrap_or_default();
+    if providers.is_empty() {
+        return Task::ready(Vec::new());
+    }
+    let Some(delegate) = buffer
+        .file()
+        .and_then(|file| worktree_delegate(file, fs, HashMap::default(), cx))
+    else {
+        return Task::ready(Vec::new());
+    };
+
+    let formatting_buffer = formatting_buffer(buffer, &settings);
+    let snapshot = buffer.snapshot();
+    let buffer_id = buffer.remote_id();
+    cx.background_spawn(async move {
+        let mut runnables = Vec::new();
+        for (provider_id, extension) in providers {
+            let Some(detected) = extension
+                .detect_runnables(
+                    provider_id.clone(),
+                    formatting_buffer.clone(),
+                    delegate.clone(),
+                )
+                .await
+                .with_context(|| format!("detecting runnables with {provider_id:?}"))
+                .log_err()
+            else {
+                continue;
+            };
+            runnables.extend(detected.into_iter().map(|runnable| {
+                let range = clip_range(&snapshot, runnable.range);
+                RunnableRange {
+                    buffer_id,
+                    run_range: range.clone(),
+                    full_range: range,
+                    runnable: Runnable {
+                        tags: runnable
+                            .tags
+                            .into_iter()
+                            .map(|tag| RunnableTag(tag.into()))
+                            .collect(),
+                        language: language.clone(),
+                        buffer: buffer_id,
+                    },
+                    extra_captures: runnable.captures.into_iter().collect(),
+                }
+            }));
+        }
+        runnables
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_task_template_from_definition() {
+        let template = task_template(extension::TaskDefinition {
+            label: "build $ZED_CUSTOM_TARGET".to_string(),
+            command: "mybuild".to_string(),
+            args: vec!["--target".to_string(), "$ZED_CUSTOM_TARGET".to_string()],
+            env: vec![("MYBUILD_COLOR".to_string(), "1".to_string())],
+            cwd: Some("$ZED_WORKTREE_ROOT".to_string()),
+            tags: vec!["mybuild-target".to_string()],
+        });
+
+        assert_eq!(template.label, "build $ZED_CUSTOM_TARGET");
+        assert_eq!(template.command, "mybuild");
+        assert_eq!(template.args, ["--target", "$ZED_CUSTOM_TARGET"]);
+        assert_eq!(
+            template.env.get("MYBUILD_COLOR").map(String::as_str),
+            Some("1")
+        );
+        assert_eq!(template.cwd.as_deref(), Some("$ZED_WORKTREE_ROOT"));
+        assert_eq!(template.tags, ["mybuild-target"]);
+        assert!(!template.use_new_terminal);
+    }
+}
@@ -4,18 +4,22 @@ use collections::HashMap;
 use gpui::{App, AppContext as _, Context, Entity, Task, WeakEntity};
 use itertools::Itertools;
 use language::LanguageName;
-use remote::ssh_session::SshArgs;
+use remote::{ConnectionState, ssh_session::SshArgs};
 use settings::{Settings, SettingsLocation};
 use smol::channel::bounded;
 use std::{
     borrow::Cow,
     env::{self},
     path::{Path, PathBuf},
-    sync::Arc,
+    rc::Rc,
+    sync::{
+        Arc,
+        atomic::{self, AtomicUsize},
+    },
 };
 use task::{DEFAULT_REMOTE_SHELL, Shell, ShellBuilder, SpawnInTerminal};
 use terminal::{
-    TaskState, TaskStatus, Terminal, TerminalBuilder,
+    PersistentSshSession, TaskState, TaskStatus, Terminal, TerminalBuilder,
     terminal_settings::{self, ActivateScript, TerminalSettings, VenvSettings},
 };
 use util::{
@@ -25,6 +29,9 @@ use util::{
 
 pub struct Terminals {
     pub(crate) local_handles: Vec<WeakEntity<terminal::Terminal>>,
+    /// Whether tmux is installed on the ssh host, once known. Persistent terminals run in tmux
+    /// sessions, so they're only started when it is.
+    pub(crate) remote_tmux: Option<bool>,
 }
 
 /// Terminals are opened either for the users shell, or to run a task.
@@ -56,6 +63,22 @@ pub struct SshDetails {
     pub ssh_command: SshCommand,
     pub envs: Option<HashMap<String, String>>,
     pub path_style: PathStyle,
+    /// Whether terminals are started in tmux sessions that survive reconnects, which requires
+    /// both the connection to ask for it and tmux to be installed on the host.
+    pub persistent_terminals: bool,
+}
+
+/// The exit code `ssh` reports when it couldn't reach the host.
+const SSH_CONNECTION_LOST_EXIT_CODE: i32 = 255;
+
+static NEXT_PERSISTENT_SESSION_ID: AtomicUsize = AtomicUsize::new(0);
+
+fn next_persistent_session_name() -> String {
+    format!(
+        "zed-{}-{}",
+        std::process::id(),
+        NEXT_PERSISTENT_SESSION_ID.fetch_add(1, atomic::Ordering::SeqCst)
+    )
 }
 
 impl Project {
@@ -83,11 +106,14 @@ impl Project {
         if let Some(ssh_client) = &self.ssh_client {
             let ssh_client = ssh_client.read(cx);
             if let Some((SshArgs { arguments, envs }, path_style)) = ssh_client.ssh_info() {
+                let connection_options = ssh_client.connection_options();
                 return Some(SshDetails {
-                    host: ssh_client.connection_options().host.clone(),
+                    host: connection_options.host,
                     ssh_command: SshCommand { arguments },
                     envs,
                     path_style,
+                    persistent_terminals: connection_options.persistent_terminals
+                        && self.terminals.remote_tmux == Some(true),
                 });
             }
         }
@@ -255,6 +281,7 @@ impl Project {
         env.extend(settings.env);
 
         let local_path = if is_ssh_terminal { None } else { path.clone() };
+        let mut persistent_session = None;
 
         let mut python_venv_activate_command = Task::ready(None);
 
@@ -275,6 +302,7 @@ impl Project {
                         ssh_command,
                         envs,
                         path_style,
+                        persistent_terminals,
                     }) => {
                         log::debug!("Connecting to a remote server: {ssh_command:?}");
 
@@ -285,13 +313,17 @@ impl Project {
                         env.entry("TERM".to_string())
                             .or_insert_with(|| "xterm-256color".to_string());
 
-                        let (program, args) = wrap_for_ssh(
+                        if persistent_terminals {
+                            persistent_session = Some(next_persistent_session_name());
+                        }
+                        let (program, args) = wrap_for_ssh_session(
                             &ssh_command,
                             None,
                             path.as_deref(),
                             env,
                             None,
                             path_style,
+                            persistent_session.as_deref(),
                         );
                         env = HashMap::default();
                         if let Some(envs) = envs {
@@ -338,11 +370,15 @@ impl Project {
                         ssh_command,
                         envs,
                         path_style,
+                        persistent_terminals,
                     }) => {
                         log::debug!("Connecting to a remote server: {ssh_command:?}");
                         env.entry("TERM".to_string())
                             .or_insert_with(|| "xterm-256color".to_string());
-                        let (program, args) = wrap_for_ssh(
+                        if persistent_terminals {
+                            persistent_session = Some(next_persistent_session_name());
+                        }
+                        let (program, args) = wrap_for_ssh_session(
                             &ssh_command,
                             spawn_task
                                 .command
@@ -352,6 +388,7 @@ impl Project {
                             env,
                             python_venv_directory.as_deref(),
                             path_style,
+                            persistent_session.as_deref(),
                         );
                         env = HashMap::default();
                         if let Some(envs) = envs {
@@ -400,14 +437,30 @@ impl Project {
             cx,
         )
         .map(|builder| {
-            let terminal_handle = cx.new(|cx| builder.subscribe(cx));
+            let connection_lost = this.ssh_connection_lost();
+            let terminal_handle = cx.new(|cx| {
+                let mut terminal = builder.subscribe(cx);
+                if let Some(name) = persistent_session {
+                    terminal.set_persistent_ssh_session(PersistentSshSession {
+                        name,
+                        connection_lost,
+                    });
+                }
+                terminal
+            });
 
             this.terminals
                 .local_handles
                 .push(terminal_handle.downgrade());
 
             let id = terminal_handle.entity_id();
-            cx.observe_release(&terminal_handle, move |project, _terminal, cx| {
+            cx.observe_release(&terminal_handle, move |project, terminal, cx| {
+                // Closing a persistent terminal ends its session, which would otherwise keep
+                // running on the host.
+                if let Some(session_name) = terminal.persistent_ssh_session() {
+                    project.kill_ssh_session(session_name, cx);
+                }
+
                 let handles = &mut project.terminals.local_handles;
 
                 if let Some(index) = handles
@@ -636,6 +689,142 @@ impl Project {
         });
     }
 
+    /// Checks whether tmux is installed on the ssh host, if the connection asks for persistent
+    /// terminals and that isn't known yet. Until it is, terminals aren't persistent.
+    pub(crate) fn detect_remote_tmux(&mut self, cx: &mut Context<Self>) {
+        if self.terminals.remote_tmux.is_some() {
+            return;
+        }
+        let Some(ssh_client) = &self.ssh_client else {
+            return;
+        };
+        let ssh_client = ssh_client.read(cx);
+        if !ssh_client.connection_options().persistent_terminals {
+            return;
+        }
+        let Some((SshArgs { arguments, .. }, _)) = ssh_client.ssh_info() else {
+            return;
+        };
+
+        cx.spawn(async move |this, cx| {
+            let output = util::command::new_smol_command("ssh")
+                .args(arguments)
+                .arg("command -v tmux")
+                .output()
+                .await?;
+            let remote_tmux = match output.status.code() {
+                Some(0) => true,
+                // The connection failed, so check again once it's re-established.
+                Some(SSH_CONNECTION_LOST_EXIT_CODE) | None => return Ok(()),
+                Some(_) => {
+                    log::info!("tmux isn't installed on the ssh host, terminals won't persist");
+                    false
+                }
+            };
+            this.update(cx, |this, _| this.terminals.remote_tmux = Some(remote_tmux))
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Reconnects every terminal that lost its ssh connection to the persistent
+    /// session it was running in on the remote host.
+    pub(crate) fn reattach_ssh_terminals(&mut self, cx: &mut Context<Self>) {
+        let Some(SshDetails {
+            ssh_command, envs, ..
+        }) = self.ssh_details(cx)
+        else {
+            return;
+        };
+
+        for terminal in self.terminals.local_handles.clone() {
+            let Some(terminal) = terminal.upgrade() else {
+                continue;
+            };
+            let Some(session_name) = terminal
+                .read(cx)
+                .persistent_ssh_session()
+                .filter(|_| terminal.read(cx).is_detached())
+                .map(str::to_owned)
+            else {
+                continue;
+            };
+
+            let settings = TerminalSettings::get_global(cx).clone();
+            let (program, args) = reattach_ssh_session(&ssh_command, &session_name);
+            let (completion_tx, _) = bounded(1);
+            let builder = TerminalBuilder::new(
+                None,
+                None,
+                None,
+                Shell::WithArguments {
+                    program,
+                    args,
+                    title_override: None,
+                },
+                envs.clone().unwrap_or_default(),
+                settings.cursor_shape.unwrap_or_default(),
+                settings.alternate_scroll,
+                settings.max_scroll_history_lines,
+                true,
+                cx.entity_id().as_u64(),
+                completion_tx,
+                cx,
+            );
+            match builder {
+                Ok(builder) => {
+                    log::info!("Reattaching to remote terminal session {session_name}");
+                    terminal.update(cx, |terminal, cx| terminal.reattach(builder, cx));
+                }
+                Err(error) => {
+                    log::error!(
+                        "Failed to reattach remote terminal session {session_name}: {error:#}"
+                    );
+                }
+            }
+        }
+    }
+
+    /// Ends the persistent session of a closed terminal on the ssh host.
+    fn kill_ssh_session(&self, session_name: &str, cx: &mut Context<Self>) {
+        let Some(SshDetails { ssh_command, .. }) = self.ssh_details(cx) else {
+            return;
+        };
+        let kill_command = kill_ssh_session(session_name);
+        let session_name = session_name.to_string();
+        cx.background_spawn(async move {
+            let output = util::command::new_smol_command("ssh")
+                .args(ssh_command.arguments)
+                .arg(kill_command)
+                .output()
+                .await?;
+            if !output.status.success() {
+                log::warn!(
+                    "Failed to end remote terminal session {session_name}: {}",
+                    String::from_utf8_lossy(&output.stderr)
+                );
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Builds the check that tells a persistent remote terminal whether its `ssh` process exited
+    /// because the connection to the host was lost, rather than because its shell did.
+    fn ssh_connection_lost(&self) -> Rc<dyn Fn(&mut App) -> Task<bool>> {
+        let ssh_client = self.ssh_client.as_ref().map(Entity::downgrade);
+        Rc::new(move |cx| {
+            let Some(ssh_client) = ssh_client.as_ref().and_then(WeakEntity::upgrade) else {
+                return Task::ready(true);
+            };
+            let ssh_client = ssh_client.read(cx);
+            if ssh_client.connection_state() != ConnectionState::Connected {
+                return Task::ready(true);
+            }
+            let ping = ssh_client.ping();
+            cx.background_spawn(async move { ping.await.is_err() })
+        })
+    }
+
     pub fn local_terminal_handles(&self) -> &Vec<WeakEntity<terminal::Terminal>> {
         &self.terminals.local_handles
     }
@@ -648,6 +837,33 @@ pub fn wrap_for_ssh(
     env: HashMap<String, String>,
     venv_directory: Option<&Path>,
     path_style: PathStyle,
+) -> (String, Vec<String>) {
+    wrap_for_ssh_session(
+        ssh_command,
+        command,
+        path,
+        env,
+        venv_directory,
+        path_style,
+        None,
+    )
+}
+
+/// Like [`wrap_for_ssh`], but when a `session_name` is given, the command is started
+/// inside a tmux session of that name on the remote host, which must have tmux installed.
+/// Such a session keeps running when the ssh connection drops and can be reattached
+/// later with [`reattach_ssh_session`].
+///
+/// The tmux client always exits successfully, so the command's exit status is written to
+/// [`exit_status_file`] and reported by the shell that runs the client once it exits.
+fn wrap_for_ssh_session(
+    ssh_command: &SshCommand,
+    command: Option<(&String, &Vec<String>)>,
+    path: Option<&Path>,
+    env: HashMap<String, String>,
+    venv_directory: Option<&Path>,
+    path_style: PathStyle,
+    session_name: Option<&str>,
 ) -> (String, Vec<String>) {
     let to_run = if let Some((command, args)) = command {
         // DEFAULT_REMOTE_SHELL is '"${SHELL:-sh}"' so must not be escaped
@@ -694,7 +910,65 @@ pub fn wrap_for_ssh(
         format!("cd; {env_changes} {to_run}")
     };
     let shell_invocation = format!("sh -c {}", shlex::try_quote(&commands).unwrap());
+    let shell_invocation = match session_name {
+        Some(session_name) => {
+            let status_file = exit_status_file(session_name);
+            let session_command = format!(
+                "sh -c {}",
+                shlex::try_quote(&format!("{shell_invocation}; echo $? > {status_file}")).unwrap()
+            );
+            let session_name = shlex::try_quote(session_name).unwrap();
+            let session_command = shlex::try_quote(&session_command).unwrap();
+            report_exit_status(
+                &format!(
+                    "tmux new-session -A -s {session_name} {session_command} \\; set-option status off"
+                ),
+                &status_file,
+            )
+        }
+        None => shell_invocation,
+    };
+
+    ssh_invocation(ssh_command, shell_invocation)
+}
 
+pub fn reattach_ssh_session(ssh_command: &SshCommand, session_name: &str) -> (String, Vec<String>) {
+    let status_file = exit_status_file(session_name);
+    let session_name = shlex::try_quote(session_name).unwrap();
+    ssh_invocation(
+        ssh_command,
+        report_exit_status(
+            &format!("tmux attach-session -t {session_name}"),
+            &status_file,
+        ),
+    )
+}
+
+/// The shell command that ends the persistent session with the given name on the host.
+fn kill_ssh_session(session_name: &str) -> String {
+    let status_file = exit_status_file(session_name);
+    let session_name = shlex::try_quote(session_name).unwrap();
+    format!("tmux kill-session -t {session_name}; rm -f {status_file}")
+}
+
+/// The file on the host that the exit status of the command run in the persistent session with
+/// the given name is written to, already quoted for the shell.
+fn exit_status_file(session_name: &str) -> String {
+    shlex::try_quote(&format!("/tmp/{session_name}.exit-status"))
+        .unwrap()
+        .into_owned()
+}
+
+/// Runs the given tmux client command, then exits with the status that the session's command
+/// wrote to `status_file`, or successfully if it is still running.
+fn report_exit_status(tmux_command: &str, status_file: &str) -> String {
+    let script = format!(
+        "{tmux_command}; status=$(cat {status_file} 2>/dev/null) && rm -f {status_file}; exit \"${{status:-0}}\""
+    );
+    format!("exec sh -c {}", shlex::try_quote(&script).unwrap())
+}
+
+fn ssh_invocation(ssh_command: &SshCommand, shell_invocation: String) -> (String, Vec<String>) {
     let program = "ssh".to_string();
     let mut args = ssh_command.arguments.clone();
 
@@ -719,6 +993,75 @@ fn add_environment_path(env: &mut HashMap<String, String>, new_path: &Path) -> R
 #[cfg(test)]
 mod tests {
     use collections::HashMap;
+    use util::paths::PathStyle;
+
+    use super::SshCommand;
+
+    #[test]
+    fn test_wrap_for_ssh_session() {
+        let ssh_command = SshCommand {
+            arguments: vec!["-p".to_string(), "2222".to_string(), "example".to_string()],
+        };
+
+        let (program, plain_args) = super::wrap_for_ssh_session(
+            &ssh_command,
+            None,
+            None,
+            HashMap::default(),
+            None,
+            PathStyle::Posix,
+            None,
+        );
+        assert_eq!(program, "ssh");
+        assert_eq!(&plain_args[..4], ["-p", "2222", "example", "-t"]);
+        let plain_invocation = plain_args.last().unwrap();
+        assert!(plain_invocation.starts_with("sh -c "));
+
+        let (program, persistent_args) = super::wrap_for_ssh_session(
+            &ssh_command,
+            None,
+            None,
+            HashMap::default(),
+            None,
+            PathStyle::Posix,
+            Some("zed-1-0"),
+        );
+        assert_eq!(program, "ssh");
+        assert_eq!(&persistent_args[..4], ["-p", "2222", "example", "-t"]);
+        let persistent_invocation = unquote_sh_command(persistent_args.last().unwrap());
+        assert!(persistent_invocation.starts_with("tmux new-session -A -s zed-1-0 "));
+        let session_command = format!("{plain_invocation}; echo $? > /tmp/zed-1-0.exit-status");
+        assert!(
+            persistent_invocation.contains(
+                &shlex::try_quote(&format!(
+                    "sh -c {}",
+                    shlex::try_quote(&session_command).unwrap()
+                ))
+                .unwrap()[..]
+            )
+        );
+        // The tmux client exits successfully, so the command's exit status is read back from the
+        // file it was written to.
+        assert!(persistent_invocation.ends_with(
+            "; status=$(cat /tmp/zed-1-0.exit-status 2>/dev/null) && rm -f /tmp/zed-1-0.exit-status; exit \"${status:-0}\""
+        ));
+
+        let (_, reattach_args) = super::reattach_ssh_session(&ssh_command, "zed-1-0");
+        let reattach_invocation = unquote_sh_command(reattach_args.last().unwrap());
+        assert!(reattach_invocation.starts_with("tmux attach-session -t zed-1-0; "));
+        assert!(reattach_invocation.contains("cat /tmp/zed-1-0.exit-status"));
+
+        assert_eq!(
+            super::kill_ssh_session("zed-1-0"),
+            "tmux kill-session -t zed-1-0; rm -f /tmp/zed-1-0.exit-status"
+        );
+    }
+
+    fn unquote_sh_command(invocation: &str) -> String {
+        let args = shlex::split(invocation).unwrap();
+        assert_eq!(&args[..3], ["exec", "sh", "-c"]);
+        args[3].clone()
+    }
 
     #[test]
     fn test_add_environment_path_with_existing_path() {
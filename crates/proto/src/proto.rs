@@ -60,6 +60,8 @@ messages!(
     (DeleteChannel, Foreground),
     (DeleteNotification, Foreground),
     (DeleteProjectEntry, Foreground),
+    (EmbedTexts, Background),
+    (EmbedTextsResponse, Background),
     (EndStream, Foreground),
     (Error, Foreground),
     (ExpandProjectEntry, Foreground),
@@ -99,6 +101,8 @@ messages!(
     (GetNotificationsResponse, Foreground),
     (GetCrashFiles, Background),
     (GetCrashFilesResponse, Background),
+    (GetListeningPorts, Background),
+    (GetListeningPortsResponse, Background),
     (GetPathMetadata, Background),
     (GetPathMetadataResponse, Background),
     (GetPermalinkToLine, Foreground),
@@ -303,6 +307,9 @@ messages!(
     (RunDebugLocators, Background),
     (DebugRequest, Background),
     (LogToDebugConsole, Background),
+    (DapGotoTargetsRequest, Background),
+    (DapGotoTargetsResponse, Background),
+    (DapGotoRequest, Background),
     (GetDocumentDiagnostics, Background),
     (GetDocumentDiagnosticsResponse, Background),
     (PullWorkspaceDiagnostics, Background),
@@ -451,6 +458,8 @@ request_messages!(
     (ActiveToolchain, ActiveToolchainResponse),
     (GetPathMetadata, GetPathMetadataResponse),
     (GetCrashFiles, GetCrashFilesResponse),
+    (GetListeningPorts, GetListeningPortsResponse),
+    (EmbedTexts, EmbedTextsResponse),
     (CancelLanguageServerWork, Ack),
     (SyncExtensions, SyncExtensionsResponse),
     (InstallExtension, Ack),
@@ -472,6 +481,8 @@ request_messages!(
     (ToggleBreakpoint, Ack),
     (GetDebugAdapterBinary, DebugAdapterBinary),
     (RunDebugLocators, DebugRequest),
+    (DapGotoTargetsRequest, DapGotoTargetsResponse),
+    (DapGotoRequest, Ack),
     (GetDocumentDiagnostics, GetDocumentDiagnosticsResponse),
     (PullWorkspaceDiagnostics, Ack),
     (GetDefaultBranch, GetDefaultBranchResponse),
@@ -605,6 +616,8 @@ entity_messages!(
     RunDebugLocators,
     GetDebugAdapterBinary,
     LogToDebugConsole,
+    DapGotoTargetsRequest,
+    DapGotoRequest,
     GetDocumentDiagnostics,
     PullWorkspaceDiagnostics,
     GetDefaultBranch,
@@ -0,0 +1,462 @@
+use std::{net::TcpListener, time::Duration};
+
+use anyhow::{Context as _, Result};
+use editor::Editor;
+use gpui::{
+    Action, App, Context, Entity, EventEmitter, FocusHandle, Focusable, Pixels, SharedString,
+    Subscription, Task, WeakEntity, Window, actions, px,
+};
+use project::Project;
+use remote::{ListeningPort, SshPortForwardOption, SshRemoteClient};
+use ui::{IconButtonShape, ListItem, ListItemSpacing, Tooltip, prelude::*};
+use util::ResultExt;
+use workspace::{
+    Workspace,
+    dock::{DockPosition, Panel, PanelEvent},
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+actions!(
+    ports_panel,
+    [
+        /// Toggles focus on the ports panel.
+        ToggleFocus
+    ]
+);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &ToggleFocus, window, cx| {
+            workspace.toggle_panel_focus::<PortsPanel>(window, cx);
+        });
+    })
+    .detach();
+}
+
+/// Lists the ports forwarded over the SSH connection of a remote project,
+/// together with the ports that processes on the remote host listen on.
+pub struct PortsPanel {
+    project: Entity<Project>,
+    focus_handle: FocusHandle,
+    port_editor: Entity<Editor>,
+    position: DockPosition,
+    size: Option<Pixels>,
+    detected_ports: Vec<ListeningPort>,
+    error: Option<SharedString>,
+    _poll_ports: Option<Task<()>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl PortsPanel {
+    pub fn new(workspace: &Workspace, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let project = workspace.project().clone();
+        let port_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Forward a port, e.g. 8080 or 3000:localhost:8080", cx);
+            editor
+        });
+
+        let mut subscriptions = vec![cx.observe(&project, |_, _, cx| cx.notify())];
+        if let Some(ssh_client) = project.read(cx).ssh_client() {
+            subscriptions.push(cx.observe(&ssh_client, |_, _, cx| cx.notify()));
+        }
+
+        Self {
+            project,
+            focus_handle: cx.focus_handle(),
+            port_editor,
+            position: DockPosition::Bottom,
+            size: None,
+            detected_ports: Vec::new(),
+            error: None,
+            _poll_ports: None,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn ssh_client(&self, cx: &App) -> Option<Entity<SshRemoteClient>> {
+        self.project.read(cx).ssh_client()
+    }
+
+    fn poll_ports(&mut self, cx: &mut Context<Self>) -> Task<()> {
+        cx.spawn(async move |this: WeakEntity<Self>, cx| {
+            loop {
+                let Ok(Some(request)) = this.update(cx, |this, cx| {
+                    this.ssh_client(cx)
+                        .map(|ssh_client| ssh_client.read(cx).listening_ports())
+                }) else {
+                    return;
+                };
+                let result = request.await;
+                if this
+                    .update(cx, |this, cx| {
+                        match result {
+                            Ok(ports) => this.detected_ports = ports,
+                            Err(error) => {
+                                log::debug!("failed to list remote ports: {error:#}");
+                                this.detected_ports.clear();
+                            }
+                        }
+                        cx.notify();
+                    })
+                    .is_err()
+                {
+                    return;
+                }
+                cx.background_executor().timer(POLL_INTERVAL).await;
+            }
+        })
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        let text = self.port_editor.read(cx).text(cx);
+        match parse_port_forward(&text) {
+            Ok(forward) => {
+                self.port_editor
+                    .update(cx, |editor, cx| editor.clear(window, cx));
+                self.add_forward(forward, cx);
+            }
+            Err(error) => {
+                self.error = Some(format!("{error:#}").into());
+                cx.notify();
+            }
+        }
+    }
+
+    fn forward_detected_port(&mut self, port: &ListeningPort, cx: &mut Context<Self>) {
+        let Some(local_port) = available_local_port(port.port) else {
+            self.error = Some("Could not find a free local port".into());
+            cx.notify();
+            return;
+        };
+        let remote_host = match port.address.as_str() {
+            "0.0.0.0" | "::" | "127.0.0.1" => None,
+            "::1" => Some("[::1]".to_string()),
+            address => Some(address.to_string()),
+        };
+        self.add_forward(
+            SshPortForwardOption {
+                local_host: None,
+                local_port,
+                remote_host,
+                remote_port: port.port,
+            },
+            cx,
+        );
+    }
+
+    fn add_forward(&mut self, forward: SshPortForwardOption, cx: &mut Context<Self>) {
+        let Some(ssh_client) = self.ssh_client(cx) else {
+            return;
+        };
+        self.error = None;
+        let task = ssh_client.update(cx, |ssh_client, cx| {
+            ssh_client.add_port_forward(forward, cx)
+        });
+        self.report_errors(task, cx);
+    }
+
+    fn remove_forward(&mut self, forward: SshPortForwardOption, cx: &mut Context<Self>) {
+        let Some(ssh_client) = self.ssh_client(cx) else {
+            return;
+        };
+        self.error = None;
+        let task = ssh_client.update(cx, |ssh_client, cx| {
+            ssh_client.remove_port_forward(forward, cx)
+        });
+        self.report_errors(task, cx);
+    }
+
+    fn report_errors(&mut self, task: Task<Result<()>>, cx: &mut Context<Self>) {
+        cx.spawn(async move |this, cx| {
+            if let Err(error) = task.await {
+                this.update(cx, |this, cx| {
+                    this.error = Some(format!("{error:#}").into());
+                    cx.notify();
+                })
+                .log_err();
+            }
+        })
+        .detach();
+        cx.notify();
+    }
+
+    fn render_forward(
+        &self,
+        ix: usize,
+        forward: SshPortForwardOption,
+        is_dynamic: bool,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let local_host = forward.local_host.as_deref().unwrap_or("localhost");
+        let url = format!("http://{}:{}", local_host, forward.local_port);
+        let description = format!(
+            "{}:{} → {}:{}",
+            local_host,
+            forward.local_port,
+            forward.remote_host.as_deref().unwrap_or("localhost"),
+            forward.remote_port
+        );
+
+        ListItem::new(("port-forward", ix))
+            .inset(true)
+            .spacing(ListItemSpacing::Sparse)
+            .start_slot(
+                Icon::new(IconName::ArrowRightLeft)
+                    .color(Color::Muted)
+                    .size(IconSize::Small),
+            )
+            .child(Label::new(description))
+            .when(!is_dynamic, |item| {
+                item.child(
+                    Label::new("from settings")
+                        .color(Color::Muted)
+                        .size(LabelSize::Small),
+                )
+            })
+            .end_slot(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        IconButton::new(("open-port-forward", ix), IconName::ArrowUpRight)
+                            .icon_size(IconSize::Small)
+                            .shape(IconButtonShape::Square)
+                            .tooltip(Tooltip::text("Open in Browser"))
+                            .on_click(move |_, _, cx| cx.open_url(&url)),
+                    )
+                    .when(is_dynamic, |this| {
+                        this.child(
+                            IconButton::new(("remove-port-forward", ix), IconName::Close)
+                                .icon_size(IconSize::Small)
+                                .shape(IconButtonShape::Square)
+                                .tooltip(Tooltip::text("Stop Forwarding"))
+                                .on_click(cx.listener(move |this, _, _, cx| {
+                                    this.remove_forward(forward.clone(), cx)
+                                })),
+                        )
+                    }),
+            )
+    }
+
+    fn render_detected_port(
+        &self,
+        ix: usize,
+        port: ListeningPort,
+        is_forwarded: bool,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let mut description = format!("{}:{}", port.address, port.port);
+        if let Some(process_name) = &port.process_name {
+            description.push_str(&format!(" ({process_name})"));
+        }
+
+        ListItem::new(("detected-port", ix))
+            .inset(true)
+            .spacing(ListItemSpacing::Sparse)
+            .start_slot(
+                Icon::new(IconName::Server)
+                    .color(Color::Muted)
+                    .size(IconSize::Small),
+            )
+            .child(Label::new(description))
+            .end_slot(
+                Button::new(("forward-detected-port", ix), "Forward")
+                    .label_size(LabelSize::Small)
+                    .disabled(is_forwarded)
+                    .on_click(
+                        cx.listener(move |this, _, _, cx| this.forward_detected_port(&port, cx)),
+                    ),
+            )
+    }
+}
+
+/// Parses a port forward entered by the user. Accepts `remote_port`,
+/// `local_port:remote_port` and `local_port:remote_host:remote_port`.
+fn parse_port_forward(text: &str) -> Result<SshPortForwardOption> {
+    let parts = text.trim().split(':').collect::<Vec<_>>();
+    let parse_port = |port: &str| {
+        port.trim()
+            .parse::<u16>()
+            .with_context(|| format!("invalid port: {port:?}"))
+    };
+    let (local_port, remote_host, remote_port) = match parts.as_slice() {
+        [port] => {
+            let port = parse_port(port)?;
+            (port, None, port)
+        }
+        [local_port, remote_port] => (parse_port(local_port)?, None, parse_port(remote_port)?),
+        [local_port, remote_host, remote_port] => (
+            parse_port(local_port)?,
+            Some(remote_host.trim().to_string()).filter(|host| !host.is_empty()),
+            parse_port(remote_port)?,
+        ),
+        _ => anyhow::bail!("expected PORT, LOCAL_PORT:REMOTE_PORT or LOCAL_PORT:HOST:REMOTE_PORT"),
+    };
+    Ok(SshPortForwardOption {
+        local_host: None,
+        local_port,
+        remote_host,
+        remote_port,
+    })
+}
+
+/// Prefers forwarding to the same port locally, falling back to any free port.
+fn available_local_port(preferred: u16) -> Option<u16> {
+    if TcpListener::bind(("127.0.0.1", preferred)).is_ok() {
+        return Some(preferred);
+    }
+    let listener = TcpListener::bind(("127.0.0.1", 0)).ok()?;
+    Some(listener.local_addr().ok()?.port())
+}
+
+impl Focusable for PortsPanel {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for PortsPanel {}
+
+impl Render for PortsPanel {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let (forwards, dynamic) = self
+            .ssh_client(cx)
+            .map(|ssh_client| {
+                let ssh_client = ssh_client.read(cx);
+                let forwards = ssh_client.port_forwards();
+                let dynamic = forwards
+                    .iter()
+                    .map(|forward| ssh_client.is_dynamic_port_forward(forward))
+                    .collect::<Vec<_>>();
+                (forwards, dynamic)
+            })
+            .unwrap_or_default();
+        let detected_ports = self.detected_ports.clone();
+
+        v_flex()
+            .key_context("PortsPanel")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::confirm))
+            .size_full()
+            .p_2()
+            .gap_2()
+            .child(
+                div()
+                    .px_2()
+                    .py_1()
+                    .border_1()
+                    .rounded_md()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(self.port_editor.clone()),
+            )
+            .when_some(self.error.clone(), |this, error| {
+                this.child(Label::new(error).color(Color::Error).size(LabelSize::Small))
+            })
+            .child(Headline::new("Forwarded Ports").size(HeadlineSize::XSmall))
+            .child(if forwards.is_empty() {
+                Label::new("No ports are forwarded")
+                    .color(Color::Muted)
+                    .into_any_element()
+            } else {
+                v_flex()
+                    .children(forwards.iter().cloned().zip(dynamic).enumerate().map(
+                        |(ix, (forward, is_dynamic))| {
+                            self.render_forward(ix, forward, is_dynamic, cx)
+                        },
+                    ))
+                    .into_any_element()
+            })
+            .child(Headline::new("Detected Ports").size(HeadlineSize::XSmall))
+            .child(if detected_ports.is_empty() {
+                Label::new("No listening ports detected on the remote host")
+                    .color(Color::Muted)
+                    .into_any_element()
+            } else {
+                v_flex()
+                    .children(detected_ports.into_iter().enumerate().map(|(ix, port)| {
+                        let is_forwarded = forwards
+                            .iter()
+                            .any(|forward| forward.remote_port == port.port);
+                        self.render_detected_port(ix, port, is_forwarded, cx)
+                    }))
+                    .into_any_element()
+            })
+    }
+}
+
+impl Panel for PortsPanel {
+    fn persistent_name() -> &'static str {
+        "PortsPanel"
+    }
+
+    fn position(&self, _: &Window, _: &App) -> DockPosition {
+        self.position
+    }
+
+    fn position_is_valid(&self, _: DockPosition) -> bool {
+        true
+    }
+
+    fn set_position(&mut self, position: DockPosition, _: &mut Window, cx: &mut Context<Self>) {
+        self.position = position;
+        cx.notify();
+    }
+
+    fn size(&self, _: &Window, _: &App) -> Pixels {
+        self.size.unwrap_or(px(240.))
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, _: &mut Window, cx: &mut Context<Self>) {
+        self.size = size;
+        cx.notify();
+    }
+
+    fn set_active(&mut self, active: bool, _: &mut Window, cx: &mut Context<Self>) {
+        self._poll_ports = active.then(|| self.poll_ports(cx));
+    }
+
+    fn icon(&self, _: &Window, _: &App) -> Option<IconName> {
+        Some(IconName::ArrowRightLeft)
+    }
+
+    fn icon_tooltip(&self, _: &Window, _: &App) -> Option<&'static str> {
+        Some("Ports Panel")
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+
+    fn enabled(&self, cx: &App) -> bool {
+        self.project.read(cx).is_via_ssh()
+    }
+
+    fn activation_priority(&self) -> u32 {
+        10
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_port_forward() {
+        let forward = parse_port_forward("8080").unwrap();
+        assert_eq!((forward.local_port, forward.remote_port), (8080, 8080));
+        assert_eq!(forward.remote_host, None);
+
+        let forward = parse_port_forward(" 3000:8080 ").unwrap();
+        assert_eq!((forward.local_port, forward.remote_port), (3000, 8080));
+
+        let forward = parse_port_forward("3000:db.internal:5432").unwrap();
+        assert_eq!(forward.remote_host.as_deref(), Some("db.internal"));
+        assert_eq!(forward.ssh_spec(), "localhost:3000:db.internal:5432");
+
+        assert!(parse_port_forward("").is_err());
+        assert!(parse_port_forward("http").is_err());
+        assert!(parse_port_forward("70000").is_err());
+        assert!(parse_port_forward("1:2:3:4").is_err());
+    }
+}
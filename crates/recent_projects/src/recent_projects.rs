@@ -1,4 +1,5 @@
 pub mod disconnected_overlay;
+pub mod ports_panel;
 mod remote_servers;
 mod ssh_config;
 mod ssh_connections;
@@ -33,6 +34,7 @@ use zed_actions::{OpenRecent, OpenRemote};
 
 pub fn init(cx: &mut App) {
     SshSettings::register(cx);
+    ports_panel::init(cx);
     cx.on_action(|open_recent: &OpenRecent, cx| {
         let create_new_window = open_recent.create_new_window;
         with_active_or_new_workspace(cx, move |workspace, window, cx| {
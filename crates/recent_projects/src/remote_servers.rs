@@ -1,6 +1,6 @@
 use std::any::Any;
 use std::borrow::Cow;
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::Arc;
@@ -28,7 +28,7 @@ use paths::user_ssh_config_file;
 use picker::Picker;
 use project::Fs;
 use project::Project;
-use remote::ssh_session::ConnectionIdentifier;
+use remote::ssh_session::{ConnectionIdentifier, SshJumpHost};
 use remote::{SshConnectionOptions, SshRemoteClient};
 use settings::Settings;
 use settings::SettingsStore;
@@ -54,6 +54,7 @@ use workspace::{
 };
 
 use crate::ssh_config::parse_ssh_config_hosts;
+use crate::ssh_config::parse_ssh_config_jump_hosts;
 use crate::ssh_connections::RemoteSettingsContent;
 use crate::ssh_connections::SshConnection;
 use crate::ssh_connections::SshConnectionHeader;
@@ -72,6 +73,7 @@ pub struct RemoteServerProjects {
     retained_connections: Vec<Entity<SshRemoteClient>>,
     ssh_config_updates: Task<()>,
     ssh_config_servers: BTreeSet<SharedString>,
+    ssh_config_jump_hosts: BTreeMap<SharedString, Vec<SshJumpHost>>,
     create_new_window: bool,
     _subscription: Subscription,
 }
@@ -403,6 +405,7 @@ impl RemoteServerProjects {
                         recent_projects.ssh_config_updates = spawn_ssh_config_watch(fs.clone(), cx);
                     } else {
                         recent_projects.ssh_config_servers.clear();
+                        recent_projects.ssh_config_jump_hosts.clear();
                         recent_projects.ssh_config_updates = Task::ready(());
                     }
                 }
@@ -415,6 +418,7 @@ impl RemoteServerProjects {
             retained_connections: Vec::new(),
             ssh_config_updates,
             ssh_config_servers: BTreeSet::new(),
+            ssh_config_jump_hosts: BTreeMap::new(),
             create_new_window,
             _subscription,
         }
@@ -1032,6 +1036,8 @@ impl RemoteServerProjects {
                     args: connection_options.args.unwrap_or_default(),
                     upload_binary_over_ssh: None,
                     port_forwards: connection_options.port_forwards,
+                    jump_hosts: connection_options.jump_hosts,
+                    persistent_terminals: connection_options.persistent_terminals.then_some(true),
                 })
         });
     }
@@ -1623,6 +1629,7 @@ impl RemoteServerProjects {
         self.add_ssh_server(
             SshConnectionOptions {
                 host: ssh_config_host.to_string(),
+                jump_hosts: self.ssh_config_jump_hosts.get(ssh_config_host).cloned(),
                 ..SshConnectionOptions::default()
             },
             cx,
@@ -1644,6 +1651,8 @@ fn spawn_ssh_config_watch(fs: Arc<dyn Fs>, cx: &Context<RemoteServerProjects>) -
     cx.spawn(async move |remote_server_projects, cx| {
         let mut global_hosts = BTreeSet::default();
         let mut user_hosts = BTreeSet::default();
+        let mut global_jump_hosts = BTreeMap::default();
+        let mut user_jump_hosts = BTreeMap::default();
         let mut running_receivers = 2;
 
         loop {
@@ -1652,8 +1661,10 @@ fn spawn_ssh_config_watch(fs: Arc<dyn Fs>, cx: &Context<RemoteServerProjects>) -
                     match new_global_file_contents {
                         Some(new_global_file_contents) => {
                             global_hosts = parse_ssh_config_hosts(&new_global_file_contents);
+                            global_jump_hosts = parse_ssh_config_jump_hosts(&new_global_file_contents);
                             if remote_server_projects.update(cx, |remote_server_projects, cx| {
                                 remote_server_projects.ssh_config_servers = global_hosts.iter().chain(user_hosts.iter()).map(SharedString::from).collect();
+                                remote_server_projects.ssh_config_jump_hosts = merge_jump_hosts(&global_jump_hosts, &user_jump_hosts);
                                 cx.notify();
                             }).is_err() {
                                 return;
@@ -1671,8 +1682,10 @@ fn spawn_ssh_config_watch(fs: Arc<dyn Fs>, cx: &Context<RemoteServerProjects>) -
                     match new_user_file_contents {
                         Some(new_user_file_contents) => {
                             user_hosts = parse_ssh_config_hosts(&new_user_file_contents);
+                            user_jump_hosts = parse_ssh_config_jump_hosts(&new_user_file_contents);
                             if remote_server_projects.update(cx, |remote_server_projects, cx| {
                                 remote_server_projects.ssh_config_servers = global_hosts.iter().chain(user_hosts.iter()).map(SharedString::from).collect();
+                                remote_server_projects.ssh_config_jump_hosts = merge_jump_hosts(&global_jump_hosts, &user_jump_hosts);
                                 cx.notify();
                             }).is_err() {
                                 return;
@@ -1691,6 +1704,33 @@ fn spawn_ssh_config_watch(fs: Arc<dyn Fs>, cx: &Context<RemoteServerProjects>) -
     })
 }
 
+/// Combines the jump chains from the global and the user ssh config.
+/// Entries from the user config take precedence, as ssh reads that one first.
+fn merge_jump_hosts(
+    global_jump_hosts: &BTreeMap<String, anyhow::Result<Vec<String>>>,
+    user_jump_hosts: &BTreeMap<String, anyhow::Result<Vec<String>>>,
+) -> BTreeMap<SharedString, Vec<SshJumpHost>> {
+    global_jump_hosts
+        .iter()
+        .chain(user_jump_hosts.iter())
+        .filter_map(|(host, chain)| {
+            let chain = match chain {
+                Ok(chain) => chain,
+                Err(error) => {
+                    log::error!("{error:#}");
+                    return None;
+                }
+            };
+            let chain = chain
+                .iter()
+                .map(|hop| SshJumpHost::parse(hop))
+                .collect::<anyhow::Result<Vec<_>>>()
+                .log_err()?;
+            Some((SharedString::from(host.clone()), chain))
+        })
+        .collect()
+}
+
 fn get_text(element: &Entity<Editor>, cx: &mut App) -> String {
     element.read(cx).text(cx).trim().to_string()
 }
@@ -1,4 +1,5 @@
-use std::collections::BTreeSet;
+use anyhow::Result;
+use std::collections::{BTreeMap, BTreeSet};
 
 pub fn parse_ssh_config_hosts(config: &str) -> BTreeSet<String> {
     let mut hosts = BTreeSet::new();
@@ -38,6 +39,84 @@ pub fn parse_ssh_config_hosts(config: &str) -> BTreeSet<String> {
     hosts
 }
 
+/// Returns the `ProxyJump` chain configured for each concrete host in the config.
+///
+/// Jump hosts that themselves have a `ProxyJump` configured are expanded, so that
+/// every chain starts from the first host that is reachable directly. Hosts whose
+/// chain leads back to a host already in it get an error instead.
+pub fn parse_ssh_config_jump_hosts(config: &str) -> BTreeMap<String, Result<Vec<String>>> {
+    let mut jumps = BTreeMap::<String, Vec<String>>::new();
+    let mut current_hosts = BTreeSet::new();
+    let mut continues_host_line = false;
+    for line in config.lines() {
+        let line = line.trim();
+        if continues_host_line {
+            parse_hosts_from(line.trim_end_matches('\\'), &mut current_hosts);
+            continues_host_line = line.ends_with('\\');
+            continue;
+        }
+
+        let Some((keyword, value)) = split_keyword(line) else {
+            continue;
+        };
+        if keyword.eq_ignore_ascii_case("Host") {
+            current_hosts.clear();
+            parse_hosts_from(value.trim_end_matches('\\'), &mut current_hosts);
+            continues_host_line = value.ends_with('\\');
+        } else if keyword.eq_ignore_ascii_case("Match") {
+            current_hosts.clear();
+        } else if keyword.eq_ignore_ascii_case("ProxyJump") {
+            let chain = if value.eq_ignore_ascii_case("none") {
+                Vec::new()
+            } else {
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|hop| !hop.is_empty())
+                    .map(str::to_owned)
+                    .collect()
+            };
+            for host in &current_hosts {
+                // As with ssh itself, the first value obtained for a host wins.
+                jumps.entry(host.clone()).or_insert_with(|| chain.clone());
+            }
+        }
+    }
+
+    jumps
+        .keys()
+        .map(|host| (host.clone(), resolve_jump_chain(&jumps, host)))
+        .filter(|(_, chain)| chain.as_ref().map_or(true, |chain| !chain.is_empty()))
+        .collect()
+}
+
+fn resolve_jump_chain(jumps: &BTreeMap<String, Vec<String>>, host: &str) -> Result<Vec<String>> {
+    let mut chain = jumps.get(host).cloned().unwrap_or_default();
+    let mut visited = BTreeSet::from_iter([host.to_owned()]);
+    while let Some(first_hop) = chain.first() {
+        if !visited.insert(first_hop.clone()) {
+            anyhow::bail!("the ProxyJump chain of {host} loops back to {first_hop}");
+        }
+        match jumps.get(first_hop) {
+            Some(prefix) if !prefix.is_empty() => {
+                chain = prefix.iter().cloned().chain(chain).collect();
+            }
+            _ => break,
+        }
+    }
+    Ok(chain)
+}
+
+fn split_keyword(line: &str) -> Option<(&str, &str)> {
+    if line.starts_with('#') {
+        return None;
+    }
+    let split_at = line.find(|c: char| c.is_whitespace() || c == '=')?;
+    let (keyword, value) = line.split_at(split_at);
+    let value = value.trim_start().strip_prefix('=').unwrap_or(value).trim();
+    Some((keyword, value))
+}
+
 fn parse_hosts_from(line: &str, hosts: &mut BTreeSet<String>) {
     hosts.extend(
         line.split_whitespace()
@@ -93,4 +172,54 @@ mod tests {
 
         assert_eq!(expected_hosts, parse_ssh_config_hosts(hosts));
     }
+
+    #[test]
+    fn test_parse_jump_hosts() {
+        let config = "
+            Host *
+              ServerAliveInterval 30
+
+            Host bastion
+              HostName bastion.example.com
+              User jumper
+
+            Host inner
+              ProxyJump bastion
+
+            Host deep deeper
+              ProxyJump=inner,admin@gateway:2222
+
+            Host direct
+              ProxyJump none
+
+            Host direct
+              ProxyJump bastion
+
+            Host loop-a
+              ProxyJump loop-b
+            Host loop-b
+              ProxyJump loop-a
+        ";
+
+        let jumps = parse_ssh_config_jump_hosts(config);
+        let get = |host: &str| jumps.get(host).map(|chain| chain.as_ref().unwrap());
+        let chain = |hops: &[&str]| hops.iter().map(|hop| hop.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(get("bastion"), None);
+        assert_eq!(get("direct"), None);
+        assert_eq!(get("inner"), Some(&chain(&["bastion"])));
+        assert_eq!(
+            get("deep"),
+            Some(&chain(&["bastion", "inner", "admin@gateway:2222"]))
+        );
+        assert_eq!(get("deeper"), get("deep"));
+        assert_eq!(
+            jumps["loop-a"].as_ref().unwrap_err().to_string(),
+            "the ProxyJump chain of loop-a loops back to loop-a"
+        );
+        assert_eq!(
+            jumps["loop-b"].as_ref().unwrap_err().to_string(),
+            "the ProxyJump chain of loop-b loops back to loop-b"
+        );
+    }
 }
@@ -15,7 +15,7 @@ use gpui::{
 use language::CursorShape;
 use markdown::{Markdown, MarkdownElement, MarkdownStyle};
 use release_channel::ReleaseChannel;
-use remote::ssh_session::{ConnectionIdentifier, SshPortForwardOption};
+use remote::ssh_session::{ConnectionIdentifier, SshJumpHost, SshPortForwardOption};
 use remote::{SshConnectionOptions, SshPlatform, SshRemoteClient};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -57,6 +57,8 @@ impl SshSettings {
                     port,
                     username,
                     port_forwards: conn.port_forwards,
+                    jump_hosts: conn.jump_hosts,
+                    persistent_terminals: conn.persistent_terminals.unwrap_or_default(),
                     password: None,
                 };
             }
@@ -94,6 +96,17 @@ pub struct SshConnection {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub port_forwards: Option<Vec<SshPortForwardOption>>,
+
+    /// Hosts to jump through before reaching this server, in order.
+    /// Equivalent to ssh's `ProxyJump` / `-J` option.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jump_hosts: Option<Vec<SshJumpHost>>,
+
+    /// If set to true and tmux is installed on this server, terminals and tasks
+    /// on it are started inside a tmux session, so that they keep running while
+    /// Zed is disconnected and are reattached when the connection is re-established.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub persistent_terminals: Option<bool>,
 }
 
 impl From<SshConnection> for SshConnectionOptions {
@@ -107,6 +120,8 @@ impl From<SshConnection> for SshConnectionOptions {
             nickname: val.nickname,
             upload_binary_over_ssh: val.upload_binary_over_ssh.unwrap_or_default(),
             port_forwards: val.port_forwards,
+            jump_hosts: val.jump_hosts,
+            persistent_terminals: val.persistent_terminals.unwrap_or_default(),
         }
     }
 }
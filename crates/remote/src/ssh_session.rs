@@ -78,6 +78,105 @@ pub struct SshPortForwardOption {
     pub remote_port: u16,
 }
 
+impl SshPortForwardOption {
+    /// The forward in the `local_host:local_port:remote_host:remote_port` form that `ssh -L` expects.
+    pub fn ssh_spec(&self) -> String {
+        format!(
+            "{}:{}:{}:{}",
+            self.local_host.as_deref().unwrap_or("localhost"),
+            self.local_port,
+            self.remote_host.as_deref().unwrap_or("localhost"),
+            self.remote_port
+        )
+    }
+}
+
+/// A TCP port that a process on the remote host is listening on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListeningPort {
+    pub port: u16,
+    pub address: String,
+    pub pid: Option<u32>,
+    pub process_name: Option<String>,
+}
+
+/// A single hop in a `ProxyJump` chain, e.g. `user@bastion:2222`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize, JsonSchema)]
+pub struct SshJumpHost {
+    pub host: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+}
+
+impl SshJumpHost {
+    /// Parses a single `[user@]host[:port]` jump destination, where IPv6 hosts are either given
+    /// bare or in brackets, as in `[::1]:22`.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim().trim_start_matches("ssh://");
+        anyhow::ensure!(!spec.is_empty(), "empty jump host");
+        let (username, rest) = match spec.rsplit_once('@') {
+            Some((username, rest)) => (Some(username.to_string()), rest),
+            None => (None, spec),
+        };
+        let (host, port) = if let Some(bracketed) = rest.strip_prefix('[') {
+            let (host, port) = bracketed
+                .split_once(']')
+                .with_context(|| format!("missing ']' in jump host {spec:?}"))?;
+            let port = match port {
+                "" => None,
+                port => {
+                    let port = port
+                        .strip_prefix(':')
+                        .with_context(|| format!("invalid jump host {spec:?}"))?;
+                    Some(parse_port_number(port)?)
+                }
+            };
+            (host, port)
+        } else if rest.matches(':').count() > 1 {
+            (rest, None)
+        } else {
+            match rest.split_once(':') {
+                Some((host, port)) => (host, Some(parse_port_number(port)?)),
+                None => (rest, None),
+            }
+        };
+        anyhow::ensure!(!host.is_empty(), "missing host in jump host {spec:?}");
+        Ok(Self {
+            host: host.to_string(),
+            username,
+            port,
+        })
+    }
+
+    /// Parses a comma-separated `ProxyJump` chain, as accepted by `ssh -J`.
+    pub fn parse_chain(chain: &str) -> Result<Vec<Self>> {
+        chain
+            .split(',')
+            .filter(|hop| !hop.trim().is_empty())
+            .map(Self::parse)
+            .collect()
+    }
+}
+
+impl fmt::Display for SshJumpHost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(username) = &self.username {
+            write!(f, "{username}@")?;
+        }
+        if self.host.contains(':') {
+            write!(f, "[{}]", self.host)?;
+        } else {
+            write!(f, "{}", self.host)?;
+        }
+        if let Some(port) = self.port {
+            write!(f, ":{port}")?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
 pub struct SshConnectionOptions {
     pub host: String,
@@ -86,9 +185,13 @@ pub struct SshConnectionOptions {
     pub password: Option<String>,
     pub args: Option<Vec<String>>,
     pub port_forwards: Option<Vec<SshPortForwardOption>>,
+    pub jump_hosts: Option<Vec<SshJumpHost>>,
 
     pub nickname: Option<String>,
     pub upload_binary_over_ssh: bool,
+    /// Run remote terminals inside a detachable session on the server, so
+    /// that they survive reconnects and can be reattached afterwards.
+    pub persistent_terminals: bool,
 }
 
 pub struct SshArgs {
@@ -152,14 +255,14 @@ impl SshConnectionOptions {
         let mut port: Option<u16> = None;
         let mut args = Vec::new();
         let mut port_forwards: Vec<SshPortForwardOption> = Vec::new();
+        let mut jump_hosts: Vec<SshJumpHost> = Vec::new();
 
         // disallowed: -E, -e, -F, -f, -G, -g, -M, -N, -n, -O, -q, -S, -s, -T, -t, -V, -v, -W
         const ALLOWED_OPTS: &[&str] = &[
             "-4", "-6", "-A", "-a", "-C", "-K", "-k", "-X", "-x", "-Y", "-y",
         ];
         const ALLOWED_ARGS: &[&str] = &[
-            "-B", "-b", "-c", "-D", "-F", "-I", "-i", "-J", "-l", "-m", "-o", "-P", "-p", "-R",
-            "-w",
+            "-B", "-b", "-c", "-D", "-F", "-I", "-i", "-l", "-m", "-o", "-P", "-p", "-R", "-w",
         ];
 
         let mut tokens = shlex::split(input).context("invalid input")?.into_iter();
@@ -196,6 +299,20 @@ impl SshConnectionOptions {
                     anyhow::bail!("Missing port forward format");
                 }
             }
+            if arg == "-J" || arg.starts_with("-J") {
+                let chain = if arg == "-J" {
+                    tokens.next()
+                } else {
+                    Some(arg.strip_prefix("-J").unwrap().to_string())
+                };
+
+                if let Some(chain) = chain {
+                    jump_hosts.extend(SshJumpHost::parse_chain(&chain)?);
+                    continue;
+                } else {
+                    anyhow::bail!("Missing jump host");
+                }
+            }
 
             for a in ALLOWED_ARGS {
                 if arg == *a {
@@ -234,15 +351,22 @@ impl SshConnectionOptions {
             _ => Some(port_forwards),
         };
 
+        let jump_hosts = match jump_hosts.len() {
+            0 => None,
+            _ => Some(jump_hosts),
+        };
+
         Ok(Self {
             host: hostname.to_string(),
             username: username.clone(),
             port,
             port_forwards,
+            jump_hosts,
             args: Some(args),
             password: None,
             nickname: None,
             upload_binary_over_ssh: false,
+            persistent_terminals: false,
         })
     }
 
@@ -265,22 +389,13 @@ impl SshConnectionOptions {
     pub fn additional_args(&self) -> Vec<String> {
         let mut args = self.args.iter().flatten().cloned().collect::<Vec<String>>();
 
-        if let Some(forwards) = &self.port_forwards {
-            args.extend(forwards.iter().map(|pf| {
-                let local_host = match &pf.local_host {
-                    Some(host) => host,
-                    None => "localhost",
-                };
-                let remote_host = match &pf.remote_host {
-                    Some(host) => host,
-                    None => "localhost",
-                };
+        if let Some(jump_hosts) = self.jump_hosts.as_ref().filter(|hosts| !hosts.is_empty()) {
+            args.push("-J".to_string());
+            args.push(jump_hosts.iter().join(","));
+        }
 
-                format!(
-                    "-L{}:{}:{}:{}",
-                    local_host, pf.local_port, remote_host, pf.remote_port
-                )
-            }));
+        if let Some(forwards) = &self.port_forwards {
+            args.extend(forwards.iter().map(|pf| format!("-L{}", pf.ssh_spec())));
         }
 
         args
@@ -394,6 +509,43 @@ impl SshSocket {
         Ok(String::from_utf8_lossy(&output.stdout).to_string())
     }
 
+    // Asks the running master process to add or cancel a `-L` forward,
+    // so that forwards can change without reconnecting.
+    #[cfg(not(target_os = "windows"))]
+    async fn control_port_forward(
+        &self,
+        operation: &str,
+        forward: &SshPortForwardOption,
+    ) -> Result<()> {
+        let output = util::command::new_smol_command("ssh")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .args(["-o", "ControlMaster=no", "-o"])
+            .arg(format!("ControlPath={}", self.socket_path.display()))
+            .args(["-O", operation, "-L"])
+            .arg(forward.ssh_spec())
+            .arg(self.connection_options.ssh_url())
+            .output()
+            .await?;
+        anyhow::ensure!(
+            output.status.success(),
+            "failed to {operation} port forward {}: {}",
+            forward.ssh_spec(),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        Ok(())
+    }
+
+    #[cfg(target_os = "windows")]
+    async fn control_port_forward(
+        &self,
+        _operation: &str,
+        _forward: &SshPortForwardOption,
+    ) -> Result<()> {
+        anyhow::bail!("changing port forwards of a live connection is not supported on Windows")
+    }
+
     #[cfg(not(target_os = "windows"))]
     fn ssh_options<'a>(&self, command: &'a mut process::Command) -> &'a mut process::Command {
         command
@@ -535,6 +687,15 @@ impl State {
         }
     }
 
+    fn remote_connection(&self) -> Option<Arc<dyn RemoteConnection>> {
+        match self {
+            Self::Connected { ssh_connection, .. }
+            | Self::HeartbeatMissed { ssh_connection, .. }
+            | Self::ReconnectFailed { ssh_connection, .. } => Some(ssh_connection.clone()),
+            _ => None,
+        }
+    }
+
     fn can_reconnect(&self) -> bool {
         match self {
             Self::Connected { .. }
@@ -563,6 +724,10 @@ impl State {
         matches!(self, Self::Reconnecting { .. })
     }
 
+    fn is_connected(&self) -> bool {
+        matches!(self, Self::Connected { .. })
+    }
+
     fn heartbeat_recovered(self) -> Self {
         match self {
             Self::HeartbeatMissed {
@@ -642,11 +807,16 @@ pub struct SshRemoteClient {
     connection_options: SshConnectionOptions,
     path_style: PathStyle,
     state: Arc<Mutex<Option<State>>>,
+    /// Forwards added while connected, on top of the ones from `connection_options`.
+    dynamic_port_forwards: Vec<SshPortForwardOption>,
 }
 
 #[derive(Debug)]
 pub enum SshRemoteEvent {
     Disconnected,
+    /// The connection was re-established after being lost.
+    /// Remote processes that outlived the old connection can be reattached.
+    Reconnected,
 }
 
 impl EventEmitter<SshRemoteEvent> for SshRemoteClient {}
@@ -718,6 +888,7 @@ impl SshRemoteClient {
                     connection_options,
                     path_style,
                     state: Arc::new(Mutex::new(Some(State::Connecting))),
+                    dynamic_port_forwards: Vec::new(),
                 })?;
 
                 let io_task = ssh_connection.start_proxy(
@@ -967,6 +1138,10 @@ impl SshRemoteClient {
 
                 if this.state_is(State::is_reconnect_failed) {
                     this.reconnect(cx)
+                } else if this.state_is(State::is_connected) {
+                    this.restore_port_forwards(cx);
+                    cx.emit(SshRemoteEvent::Reconnected);
+                    Ok(())
                 } else if this.state_is(State::is_reconnect_exhausted) {
                     Ok(())
                 } else {
@@ -1157,6 +1332,110 @@ impl SshRemoteClient {
             .map(|ssh_connection| (ssh_connection.ssh_args(), ssh_connection.path_style()))
     }
 
+    /// All port forwards of this connection: the configured ones, followed by
+    /// the ones added with [`Self::add_port_forward`].
+    pub fn port_forwards(&self) -> Vec<SshPortForwardOption> {
+        self.connection_options
+            .port_forwards
+            .iter()
+            .flatten()
+            .chain(self.dynamic_port_forwards.iter())
+            .cloned()
+            .collect()
+    }
+
+    pub fn is_dynamic_port_forward(&self, forward: &SshPortForwardOption) -> bool {
+        self.dynamic_port_forwards.contains(forward)
+    }
+
+    pub fn add_port_forward(
+        &mut self,
+        forward: SshPortForwardOption,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        if self.port_forwards().contains(&forward) {
+            return Task::ready(Ok(()));
+        }
+        let Some(connection) = self.remote_connection() else {
+            return Task::ready(Err(anyhow!("no ssh connection")));
+        };
+        cx.spawn(async move |this, cx| {
+            connection.add_port_forward(&forward).await?;
+            this.update(cx, |this, cx| {
+                this.dynamic_port_forwards.push(forward);
+                cx.notify();
+            })
+        })
+    }
+
+    pub fn remove_port_forward(
+        &mut self,
+        forward: SshPortForwardOption,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        if !self.is_dynamic_port_forward(&forward) {
+            return Task::ready(Err(anyhow!(
+                "port forward {} is configured in settings",
+                forward.ssh_spec()
+            )));
+        }
+        let Some(connection) = self.remote_connection() else {
+            return Task::ready(Err(anyhow!("no ssh connection")));
+        };
+        cx.spawn(async move |this, cx| {
+            connection.cancel_port_forward(&forward).await?;
+            this.update(cx, |this, cx| {
+                this.dynamic_port_forwards
+                    .retain(|existing| existing != &forward);
+                cx.notify();
+            })
+        })
+    }
+
+    // A new master process only knows about the forwards passed on its command line.
+    fn restore_port_forwards(&self, cx: &mut Context<Self>) {
+        let Some(connection) = self.remote_connection() else {
+            return;
+        };
+        let forwards = self.dynamic_port_forwards.clone();
+        if forwards.is_empty() {
+            return;
+        }
+        cx.spawn(async move |_, _| {
+            for forward in forwards {
+                connection.add_port_forward(&forward).await.log_err();
+            }
+        })
+        .detach();
+    }
+
+    /// Lists the TCP ports that processes on the remote host are listening on.
+    pub fn listening_ports(&self) -> impl Future<Output = Result<Vec<ListeningPort>>> + use<> {
+        let request = self.client.request(proto::GetListeningPorts {});
+        async move {
+            Ok(request
+                .await?
+                .ports
+                .into_iter()
+                .filter_map(|port| {
+                    Some(ListeningPort {
+                        port: u16::try_from(port.port).ok()?,
+                        address: port.address,
+                        pid: port.pid,
+                        process_name: port.process_name,
+                    })
+                })
+                .collect())
+        }
+    }
+
+    fn remote_connection(&self) -> Option<Arc<dyn RemoteConnection>> {
+        self.state
+            .lock()
+            .as_ref()
+            .and_then(|state| state.remote_connection())
+    }
+
     pub fn upload_directory(
         &self,
         src_path: PathBuf,
@@ -1194,6 +1473,13 @@ impl SshRemoteClient {
         self.connection_state() == ConnectionState::Disconnected
     }
 
+    /// Checks whether the host still responds. Unlike [`Self::connection_state`], which only
+    /// changes once heartbeats have been missed, this notices a dropped connection right away.
+    pub fn ping(&self) -> impl Future<Output = Result<()>> + use<> {
+        let client = self.client.clone();
+        async move { client.ping(HEARTBEAT_TIMEOUT).await }
+    }
+
     pub fn path_style(&self) -> PathStyle {
         self.path_style
     }
@@ -1384,6 +1670,12 @@ trait RemoteConnection: Send + Sync {
     ) -> Task<Result<()>>;
     async fn kill(&self) -> Result<()>;
     fn has_been_killed(&self) -> bool;
+    async fn add_port_forward(&self, _forward: &SshPortForwardOption) -> Result<()> {
+        anyhow::bail!("port forwarding is not supported by this connection")
+    }
+    async fn cancel_port_forward(&self, _forward: &SshPortForwardOption) -> Result<()> {
+        anyhow::bail!("port forwarding is not supported by this connection")
+    }
     /// On Windows, we need to use `SSH_ASKPASS` to provide the password to ssh.
     /// On Linux, we use the `ControlPath` option to create a socket file that ssh can use to
     fn ssh_args(&self) -> SshArgs;
@@ -1418,6 +1710,14 @@ impl RemoteConnection for SshRemoteConnection {
         self.master_process.lock().is_none()
     }
 
+    async fn add_port_forward(&self, forward: &SshPortForwardOption) -> Result<()> {
+        self.socket.control_port_forward("forward", forward).await
+    }
+
+    async fn cancel_port_forward(&self, forward: &SshPortForwardOption) -> Result<()> {
+        self.socket.control_port_forward("cancel", forward).await
+    }
+
     fn ssh_args(&self) -> SshArgs {
         self.socket.ssh_args()
     }
@@ -2732,3 +3032,51 @@ mod fake {
         fn set_status(&self, _: Option<&str>, _: &mut AsyncApp) {}
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_jump_host() {
+        let jump_host = |host: &str, username: Option<&str>, port| SshJumpHost {
+            host: host.to_string(),
+            username: username.map(str::to_string),
+            port,
+        };
+
+        assert_eq!(
+            SshJumpHost::parse("user@bastion:2222").unwrap(),
+            jump_host("bastion", Some("user"), Some(2222))
+        );
+        assert_eq!(
+            SshJumpHost::parse("ssh://bastion").unwrap(),
+            jump_host("bastion", None, None)
+        );
+        assert_eq!(
+            SshJumpHost::parse("[::1]:22").unwrap(),
+            jump_host("::1", None, Some(22))
+        );
+        assert_eq!(
+            SshJumpHost::parse("user@[fe80::1]").unwrap(),
+            jump_host("fe80::1", Some("user"), None)
+        );
+        assert_eq!(
+            SshJumpHost::parse("2001:db8::1").unwrap(),
+            jump_host("2001:db8::1", None, None)
+        );
+        assert!(SshJumpHost::parse("[::1").is_err());
+        assert!(SshJumpHost::parse("[::1]22").is_err());
+        assert!(SshJumpHost::parse("[]:22").is_err());
+        assert!(SshJumpHost::parse("bastion:ssh").is_err());
+
+        assert_eq!(
+            SshJumpHost::parse_chain("user@[::1]:22, bastion")
+                .unwrap()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["user@[::1]:22", "bastion"]
+        );
+    }
+}
@@ -1,6 +1,10 @@
 use ::proto::{FromProto, ToProto};
 use anyhow::{Context as _, Result, anyhow};
 
+use embedding::{
+    EmbeddingProvider as _, LocalEmbeddingOptions, LocalEmbeddingProvider, SemanticIndexSettings,
+    TextToEmbed,
+};
 use extension::ExtensionHostProxy;
 use extension_host::headless_host::HeadlessExtensionStore;
 use fs::Fs;
@@ -25,7 +29,7 @@ use rpc::{
     proto::{self, SSH_PEER_ID, SSH_PROJECT_ID},
 };
 
-use settings::initial_server_settings_content;
+use settings::{Settings as _, initial_server_settings_content};
 use smol::stream::StreamExt;
 use std::{
     path::{Path, PathBuf},
@@ -47,6 +51,9 @@ pub struct HeadlessProject {
     pub languages: Arc<LanguageRegistry>,
     pub extensions: Entity<HeadlessExtensionStore>,
     pub git_store: Entity<GitStore>,
+    /// The model from `semantic_index.local_embeddings`, once it's been loaded to embed texts for
+    /// the client.
+    local_embeddings: Option<(LocalEmbeddingOptions, Arc<LocalEmbeddingProvider>)>,
 }
 
 pub struct HeadlessAppState {
@@ -63,6 +70,7 @@ impl HeadlessProject {
         settings::init(cx);
         language::init(cx);
         project::Project::init_settings(cx);
+        embedding::init(cx);
     }
 
     pub fn new(
@@ -225,6 +233,8 @@ impl HeadlessProject {
 
         client.add_request_handler(cx.weak_entity(), Self::handle_list_remote_directory);
         client.add_request_handler(cx.weak_entity(), Self::handle_get_path_metadata);
+        client.add_request_handler(cx.weak_entity(), Self::handle_get_listening_ports);
+        client.add_request_handler(cx.weak_entity(), Self::handle_embed_texts);
         client.add_request_handler(cx.weak_entity(), Self::handle_shutdown_remote_server);
         client.add_request_handler(cx.weak_entity(), Self::handle_ping);
 
@@ -272,6 +282,7 @@ impl HeadlessProject {
             languages,
             extensions,
             git_store,
+            local_embeddings: None,
         }
     }
 
@@ -644,6 +655,69 @@ impl HeadlessProject {
         })
     }
 
+    pub async fn handle_get_listening_ports(
+        _this: Entity<Self>,
+        _envelope: TypedEnvelope<proto::GetListeningPorts>,
+        _cx: AsyncApp,
+    ) -> Result<proto::GetListeningPortsResponse> {
+        Ok(proto::GetListeningPortsResponse {
+            ports: crate::listening_ports::listening_ports().await?,
+        })
+    }
+
+    pub async fn handle_embed_texts(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::EmbedTexts>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::EmbedTextsResponse> {
+        let options = cx
+            .update(|cx| {
+                SemanticIndexSettings::get_global(cx)
+                    .local_embeddings
+                    .as_ref()
+                    .map(LocalEmbeddingOptions::from_settings)
+            })?
+            .context("no local embedding model is configured in the server settings")?;
+        let loaded = this.read_with(&cx, |this, _| {
+            this.local_embeddings
+                .as_ref()
+                .filter(|(loaded_options, _)| loaded_options == &options)
+                .map(|(_, provider)| provider.clone())
+        })?;
+        let provider = match loaded {
+            Some(provider) => provider,
+            None => {
+                let provider = cx
+                    .background_spawn({
+                        let options = options.clone();
+                        async move { LocalEmbeddingProvider::new(options) }
+                    })
+                    .await?;
+                let provider = Arc::new(provider);
+                this.update(&mut cx, |this, _| {
+                    this.local_embeddings = Some((options, provider.clone()));
+                })?;
+                provider
+            }
+        };
+
+        let texts = envelope
+            .payload
+            .texts
+            .iter()
+            .map(|text| TextToEmbed::new(text))
+            .collect::<Vec<_>>();
+        let embeddings = provider.embed(&texts).await?;
+        Ok(proto::EmbedTextsResponse {
+            embeddings: embeddings
+                .iter()
+                .map(|embedding| proto::TextEmbedding {
+                    values: embedding.values().to_vec(),
+                })
+                .collect(),
+        })
+    }
+
     pub async fn handle_shutdown_remote_server(
         _this: Entity<Self>,
         _envelope: TypedEnvelope<proto::ShutdownRemoteServer>,
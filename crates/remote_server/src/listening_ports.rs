@@ -0,0 +1,331 @@
+use anyhow::Result;
+use rpc::proto;
+use std::collections::HashSet;
+
+/// Lists the TCP ports that processes of the server's user are listening on.
+pub async fn listening_ports() -> Result<Vec<proto::ListeningPort>> {
+    let mut ports = platform_listening_ports().await?;
+    let own_pid = std::process::id();
+    ports.retain(|port| port.pid != Some(own_pid));
+
+    let mut seen = HashSet::new();
+    ports.retain(|port| seen.insert((port.address.clone(), port.port)));
+    ports.sort_by(|a, b| a.port.cmp(&b.port).then_with(|| a.address.cmp(&b.address)));
+    Ok(ports)
+}
+
+#[cfg(target_os = "linux")]
+async fn platform_listening_ports() -> Result<Vec<proto::ListeningPort>> {
+    smol::unblock(|| {
+        let uid = unsafe { libc::getuid() };
+        let mut sockets = Vec::new();
+        for (path, is_ipv6) in [("/proc/net/tcp", false), ("/proc/net/tcp6", true)] {
+            // IPv6 may be disabled, in which case the file does not exist.
+            if let Ok(contents) = std::fs::read_to_string(path) {
+                sockets.extend(
+                    parse_proc_net_tcp(&contents, is_ipv6)
+                        .into_iter()
+                        .filter(|socket| socket.uid == uid),
+                );
+            }
+        }
+        let owners = socket_owners(uid, &sockets);
+        Ok(sockets
+            .into_iter()
+            .map(|socket| {
+                let mut port = socket.port;
+                if let Some((pid, process_name)) = owners.get(&socket.inode) {
+                    port.pid = Some(*pid);
+                    port.process_name = process_name.clone();
+                }
+                port
+            })
+            .collect())
+    })
+    .await
+}
+
+#[cfg(target_os = "macos")]
+async fn platform_listening_ports() -> Result<Vec<proto::ListeningPort>> {
+    use anyhow::Context as _;
+
+    let uid = unsafe { libc::getuid() };
+    let output = util::command::new_smol_command("lsof")
+        .args(["-nP", "-iTCP", "-sTCP:LISTEN", "-a", "-u"])
+        .arg(uid.to_string())
+        .arg("-Fpcn")
+        .output()
+        .await
+        .context("failed to run lsof")?;
+    // lsof exits with 1 when nothing matches.
+    Ok(parse_lsof_output(&String::from_utf8_lossy(&output.stdout)))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+async fn platform_listening_ports() -> Result<Vec<proto::ListeningPort>> {
+    anyhow::bail!("listing listening ports is not supported on this platform")
+}
+
+#[cfg(target_os = "linux")]
+type SocketOwners = std::collections::HashMap<u64, (u32, Option<String>)>;
+
+/// How long the owners found by scanning `/proc/*/fd` are reused before a socket
+/// without a known owner causes another scan.
+#[cfg(target_os = "linux")]
+const SOCKET_OWNER_SCAN_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+#[cfg(target_os = "linux")]
+static LAST_SOCKET_OWNER_SCAN: std::sync::Mutex<Option<(std::time::Instant, SocketOwners)>> =
+    std::sync::Mutex::new(None);
+
+/// Maps the inodes of the given sockets to the process owning them.
+///
+/// Scanning every file descriptor of every process is slow, so the result of the
+/// last scan is reused unless it's missing some of the sockets and is old enough.
+#[cfg(target_os = "linux")]
+fn socket_owners(uid: u32, sockets: &[ProcNetSocket]) -> SocketOwners {
+    let mut last_scan = LAST_SOCKET_OWNER_SCAN
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let needs_scan = last_scan.as_ref().map_or(true, |(scanned_at, owners)| {
+        scanned_at.elapsed() >= SOCKET_OWNER_SCAN_INTERVAL
+            && sockets
+                .iter()
+                .any(|socket| !owners.contains_key(&socket.inode))
+    });
+    if needs_scan {
+        *last_scan = Some((std::time::Instant::now(), scan_socket_owners(uid)));
+    }
+    last_scan
+        .as_ref()
+        .map(|(_, owners)| owners.clone())
+        .unwrap_or_default()
+}
+
+/// Maps socket inodes to the process owning them, looking only at the processes
+/// of the given user.
+#[cfg(target_os = "linux")]
+fn scan_socket_owners(uid: u32) -> SocketOwners {
+    use std::os::unix::fs::MetadataExt as _;
+
+    let mut owners = SocketOwners::new();
+    let Ok(processes) = std::fs::read_dir("/proc") else {
+        return owners;
+    };
+    for process in processes.flatten() {
+        let Some(pid) = process
+            .file_name()
+            .to_str()
+            .and_then(|name| name.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        if process
+            .metadata()
+            .map_or(true, |metadata| metadata.uid() != uid)
+        {
+            continue;
+        }
+        let Ok(fds) = std::fs::read_dir(process.path().join("fd")) else {
+            continue;
+        };
+        let mut process_name = None;
+        for fd in fds.flatten() {
+            let Ok(target) = std::fs::read_link(fd.path()) else {
+                continue;
+            };
+            let Some(inode) = target
+                .to_str()
+                .and_then(|target| target.strip_prefix("socket:["))
+                .and_then(|target| target.strip_suffix(']'))
+                .and_then(|inode| inode.parse::<u64>().ok())
+            else {
+                continue;
+            };
+            let process_name = process_name.get_or_insert_with(|| {
+                std::fs::read_to_string(process.path().join("comm"))
+                    .ok()
+                    .map(|name| name.trim().to_string())
+            });
+            owners.insert(inode, (pid, process_name.clone()));
+        }
+    }
+    owners
+}
+
+#[cfg(any(target_os = "linux", test))]
+const TCP_LISTEN_STATE: &str = "0A";
+
+/// A listening socket from `/proc/net/tcp` or `/proc/net/tcp6`.
+#[cfg(any(target_os = "linux", test))]
+struct ProcNetSocket {
+    port: proto::ListeningPort,
+    inode: u64,
+    /// The user that opened the socket.
+    uid: u32,
+}
+
+/// Parses the contents of `/proc/net/tcp` or `/proc/net/tcp6`, returning the
+/// listening sockets.
+#[cfg(any(target_os = "linux", test))]
+fn parse_proc_net_tcp(contents: &str, is_ipv6: bool) -> Vec<ProcNetSocket> {
+    contents
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            let (local_address, state, uid, inode) = (
+                fields.get(1)?,
+                fields.get(3)?,
+                fields.get(7)?,
+                fields.get(9)?,
+            );
+            if *state != TCP_LISTEN_STATE {
+                return None;
+            }
+            let (address, port) = local_address.split_once(':')?;
+            let address = if is_ipv6 {
+                parse_ipv6_address(address)?
+            } else {
+                parse_ipv4_address(address)?
+            };
+            let port = u16::from_str_radix(port, 16).ok()?;
+            Some(ProcNetSocket {
+                port: proto::ListeningPort {
+                    port: port as u32,
+                    address,
+                    pid: None,
+                    process_name: None,
+                },
+                inode: inode.parse().ok()?,
+                uid: uid.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+// Addresses are written as the hexadecimal dump of the in-memory representation,
+// which is in host byte order for each 32-bit word.
+#[cfg(any(target_os = "linux", test))]
+fn parse_ipv4_address(hex: &str) -> Option<String> {
+    let word = u32::from_str_radix(hex, 16).ok()?;
+    Some(std::net::Ipv4Addr::from(word.to_ne_bytes()).to_string())
+}
+
+#[cfg(any(target_os = "linux", test))]
+fn parse_ipv6_address(hex: &str) -> Option<String> {
+    if hex.len() != 32 {
+        return None;
+    }
+    let mut bytes = [0u8; 16];
+    for (index, chunk) in bytes.chunks_mut(4).enumerate() {
+        let word = u32::from_str_radix(hex.get(index * 8..index * 8 + 8)?, 16).ok()?;
+        chunk.copy_from_slice(&word.to_ne_bytes());
+    }
+    Some(std::net::Ipv6Addr::from(bytes).to_string())
+}
+
+/// Parses the output of `lsof -nP -iTCP -sTCP:LISTEN -Fpcn`.
+#[cfg(any(target_os = "macos", test))]
+fn parse_lsof_output(output: &str) -> Vec<proto::ListeningPort> {
+    let mut ports = Vec::new();
+    let mut pid = None;
+    let mut process_name = None;
+    for line in output.lines() {
+        let Some(tag) = line.chars().next() else {
+            continue;
+        };
+        let value = &line[tag.len_utf8()..];
+        match tag {
+            'p' => {
+                pid = value.parse::<u32>().ok();
+                process_name = None;
+            }
+            'c' => process_name = Some(value.to_string()),
+            'n' => {
+                let Some((address, port)) = value.rsplit_once(':') else {
+                    continue;
+                };
+                let Ok(port) = port.parse::<u16>() else {
+                    continue;
+                };
+                let address = address.trim_start_matches('[').trim_end_matches(']');
+                ports.push(proto::ListeningPort {
+                    port: port as u32,
+                    address: if address == "*" {
+                        "0.0.0.0".to_string()
+                    } else {
+                        address.to_string()
+                    },
+                    pid,
+                    process_name: process_name.clone(),
+                });
+            }
+            _ => {}
+        }
+    }
+    ports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_net_tcp() {
+        let tcp = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 31337 1 0000000000000000 100 0 0 10 0
+   1: 00000000:0016 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 1234 1 0000000000000000 100 0 0 10 0
+   2: 0100007F:1F90 0100007F:D6A8 01 00000000:00000000 00:00000000 00000000  1000        0 31338 1 0000000000000000 20 4 30 10 -1";
+        let ports = parse_proc_net_tcp(tcp, false);
+        assert_eq!(ports.len(), 2);
+        if cfg!(target_endian = "little") {
+            assert_eq!(ports[0].port.address, "127.0.0.1");
+        }
+        assert_eq!(ports[0].port.port, 8080);
+        assert_eq!(ports[0].inode, 31337);
+        assert_eq!(ports[0].uid, 1000);
+        assert_eq!(ports[1].port.address, "0.0.0.0");
+        assert_eq!(ports[1].port.port, 22);
+        assert_eq!(ports[1].uid, 0);
+
+        let tcp6 = "  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000001000000:0BB8 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 4242 1 0000000000000000 100 0 0 10 0";
+        let ports = parse_proc_net_tcp(tcp6, true);
+        assert_eq!(ports.len(), 1);
+        if cfg!(target_endian = "little") {
+            assert_eq!(ports[0].port.address, "::1");
+        }
+        assert_eq!(ports[0].port.port, 3000);
+        assert_eq!(ports[0].inode, 4242);
+    }
+
+    #[test]
+    fn test_parse_lsof_output() {
+        let output = "p501\ncnode\nn*:3000\nn127.0.0.1:9229\np777\ncpython3.12\nn[::1]:8000\n";
+        let ports = parse_lsof_output(output);
+        assert_eq!(
+            ports,
+            vec![
+                proto::ListeningPort {
+                    port: 3000,
+                    address: "0.0.0.0".to_string(),
+                    pid: Some(501),
+                    process_name: Some("node".to_string()),
+                },
+                proto::ListeningPort {
+                    port: 9229,
+                    address: "127.0.0.1".to_string(),
+                    pid: Some(501),
+                    process_name: Some("node".to_string()),
+                },
+                proto::ListeningPort {
+                    port: 8000,
+                    address: "::1".to_string(),
+                    pid: Some(777),
+                    process_name: Some("python3.12".to_string()),
+                },
+            ]
+        );
+    }
+}
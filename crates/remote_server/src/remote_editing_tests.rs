@@ -8,6 +8,7 @@ use client::{Client, UserStore};
 use clock::FakeSystemClock;
 use language_model::{LanguageModelRequest, fake_provider::FakeLanguageModel};
 
+use embedding::{EmbeddingProvider as _, RemoteEmbeddingProvider, TextToEmbed};
 use extension::ExtensionHostProxy;
 use fs::{FakeFs, Fs};
 use gpui::{AppContext as _, Entity, SemanticVersion, TestAppContext};
@@ -1768,6 +1769,41 @@ async fn test_remote_agent_fs_tool_calls(cx: &mut TestAppContext, server_cx: &mu
     does_not_exist_result.output.await.unwrap_err();
 }
 
+#[gpui::test]
+async fn test_remote_embeddings_use_server_settings(
+    cx: &mut TestAppContext,
+    server_cx: &mut TestAppContext,
+) {
+    let fs = FakeFs::new(server_cx.executor());
+    let (project, _headless) = init_test(&fs, cx, server_cx).await;
+    let client = project.read_with(cx, |project, cx| {
+        project.ssh_client().unwrap().read(cx).proto_client()
+    });
+    let provider = RemoteEmbeddingProvider::new(client);
+    let texts = [TextToEmbed::new("fn main() {}")];
+
+    let error = provider.embed(&texts).await.unwrap_err();
+    assert!(
+        format!("{error:#}").contains("no local embedding model is configured"),
+        "{error:#}"
+    );
+
+    server_cx
+        .update_global(|settings_store: &mut SettingsStore, cx| {
+            settings_store.set_server_settings(
+                r#"{"semantic_index":{"local_embeddings":{"model_path":"/models/missing.onnx"}}}"#,
+                cx,
+            )
+        })
+        .unwrap();
+    // The server now tries to load the configured model, which doesn't exist.
+    let error = provider.embed(&texts).await.unwrap_err();
+    assert!(
+        !format!("{error:#}").contains("no local embedding model is configured"),
+        "{error:#}"
+    );
+}
+
 pub async fn init_test(
     server_fs: &Arc<FakeFs>,
     cx: &mut TestAppContext,
@@ -156,9 +156,13 @@ impl PickerDelegate for KernelPickerDelegate {
                 "Python Env",
                 Some(truncate_path(&kernelspec.path(), 42)),
             ),
-            KernelSpecification::Remote(_) => (
+            KernelSpecification::Remote(spec) => (
                 kernelspec.name(),
-                "Remote",
+                if spec.kernel_id.is_some() {
+                    "Remote (running)"
+                } else {
+                    "Remote"
+                },
                 Some(truncate_path(&kernelspec.path(), 42)),
             ),
         };
@@ -9,6 +9,7 @@ use settings::{Settings, SettingsSources};
 #[derive(Debug, Default)]
 pub struct JupyterSettings {
     pub kernel_selections: HashMap<String, String>,
+    pub servers: Vec<JupyterServerSettings>,
 }
 
 impl JupyterSettings {
@@ -26,12 +27,82 @@ pub struct JupyterSettingsContent {
     ///
     /// Default: `{}`
     pub kernel_selections: Option<HashMap<String, String>>,
+    /// Remote Jupyter servers to list and launch kernels from.
+    ///
+    /// Default: `[]`
+    pub servers: Option<Vec<JupyterServerSettings>>,
 }
 
 impl Default for JupyterSettingsContent {
     fn default() -> Self {
         JupyterSettingsContent {
             kernel_selections: Some(HashMap::new()),
+            servers: None,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, JsonSchema, Debug, PartialEq, Eq)]
+pub struct JupyterServerSettings {
+    /// The name shown for this server in the kernel picker.
+    pub name: String,
+    /// The base URL of the server, e.g. `http://localhost:8888`.
+    pub url: String,
+    /// How to authenticate with the server.
+    ///
+    /// Default: `{ "type": "none" }`
+    #[serde(default)]
+    pub auth: JupyterServerAuth,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JupyterServerAuth {
+    /// Connect without credentials.
+    #[default]
+    None,
+    /// Authenticate with a server token, given directly or read from an environment variable.
+    Token {
+        token: Option<String>,
+        token_env: Option<String>,
+    },
+    /// Log in with the server's password, given directly or read from an environment variable.
+    Password {
+        password: Option<String>,
+        password_env: Option<String>,
+    },
+}
+
+impl JupyterServerAuth {
+    /// Returns the token or password for this profile, if any.
+    pub fn secret(&self) -> Option<String> {
+        let (value, env_var) = match self {
+            JupyterServerAuth::None => return None,
+            JupyterServerAuth::Token { token, token_env } => (token, token_env),
+            JupyterServerAuth::Password {
+                password,
+                password_env,
+            } => (password, password_env),
+        };
+        value
+            .clone()
+            .or_else(|| std::env::var(env_var.as_ref()?).ok())
+    }
+}
+
+impl std::fmt::Debug for JupyterServerAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Keep secrets out of logs.
+        match self {
+            JupyterServerAuth::None => write!(f, "None"),
+            JupyterServerAuth::Token { token_env, .. } => f
+                .debug_struct("Token")
+                .field("token_env", token_env)
+                .finish_non_exhaustive(),
+            JupyterServerAuth::Password { password_env, .. } => f
+                .debug_struct("Password")
+                .field("password_env", password_env)
+                .finish_non_exhaustive(),
         }
     }
 }
@@ -56,6 +127,9 @@ impl Settings for JupyterSettings {
                     settings.kernel_selections.insert(k.clone(), v.clone());
                 }
             }
+            if let Some(servers) = &value.servers {
+                settings.servers = servers.clone();
+            }
         }
 
         Ok(settings)
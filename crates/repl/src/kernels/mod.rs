@@ -154,6 +154,18 @@ pub trait RunningKernel: Send + Debug {
     fn kernel_info(&self) -> Option<&KernelInfoReply>;
     fn set_kernel_info(&mut self, info: KernelInfoReply);
     fn force_shutdown(&mut self, window: &mut Window, cx: &mut App) -> Task<anyhow::Result<()>>;
+
+    /// Interrupts the kernel out of band. Returns `None` if the kernel should be
+    /// sent an `interrupt_request` instead.
+    fn interrupt(&mut self, _cx: &mut App) -> Option<Task<anyhow::Result<()>>> {
+        None
+    }
+
+    /// Restarts the kernel in place, keeping the connection to it. Returns `None`
+    /// if the kernel has to be shut down and launched again instead.
+    fn restart(&mut self, _cx: &mut App) -> Option<Task<anyhow::Result<()>>> {
+        None
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -1,34 +1,279 @@
-use futures::{SinkExt as _, channel::mpsc};
-use gpui::{App, AppContext as _, Entity, Task, Window};
-use http_client::{AsyncBody, HttpClient, Request};
+use collections::{BTreeMap, HashMap};
+use futures::{FutureExt as _, SinkExt as _, channel::mpsc};
+use gpui::{App, AppContext as _, AsyncWindowContext, Entity, Task, Window};
+use http_client::{AsyncBody, HttpClient, Method, Request, Response, StatusCode, Url, http};
 use jupyter_protocol::{ExecutionState, JupyterKernelspec, JupyterMessage, KernelInfoReply};
 
 use async_tungstenite::tokio::connect_async;
 use async_tungstenite::tungstenite::{client::IntoClientRequest, http::HeaderValue};
 
 use futures::StreamExt;
+use serde::Deserialize;
 use smol::io::AsyncReadExt as _;
 
-use crate::Session;
+use crate::{JupyterServerAuth, Session};
 
 use super::RunningKernel;
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 use jupyter_websocket_client::{
     JupyterWebSocket, JupyterWebSocketReader, JupyterWebSocketWriter, KernelLaunchRequest,
-    KernelSpecsResponse, RemoteServer,
+    KernelSpecsResponse,
 };
-use std::{fmt::Debug, sync::Arc};
+use std::{fmt::Debug, sync::Arc, time::Duration};
+
+const RECONNECT_ATTEMPTS: u32 = 6;
+const RECONNECT_INITIAL_DELAY: Duration = Duration::from_secs(1);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Credentials for a Jupyter server, resolved from a [`JupyterServerAuth`] profile.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub enum JupyterServerCredentials {
+    #[default]
+    None,
+    Token(String),
+    /// Cookies from a password login, along with the XSRF token the server
+    /// expects on requests that modify state.
+    Session {
+        cookie: String,
+        xsrf_token: Option<String>,
+    },
+}
+
+impl Debug for JupyterServerCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // custom debug that keeps secrets out of logs
+        match self {
+            Self::None => write!(f, "None"),
+            Self::Token(_) => write!(f, "Token"),
+            Self::Session { .. } => write!(f, "Session"),
+        }
+    }
+}
+
+/// An authenticated connection to a Jupyter server's REST API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JupyterServerConnection {
+    pub base_url: String,
+    pub credentials: JupyterServerCredentials,
+}
+
+impl JupyterServerConnection {
+    /// Resolves the credentials for `auth`, logging in to the server when it uses a password.
+    pub async fn connect(
+        base_url: &str,
+        auth: &JupyterServerAuth,
+        http_client: Arc<dyn HttpClient>,
+    ) -> Result<Self> {
+        let base_url = base_url.trim_end_matches('/').to_string();
+        let credentials = match auth {
+            JupyterServerAuth::None => JupyterServerCredentials::None,
+            JupyterServerAuth::Token { .. } => JupyterServerCredentials::Token(
+                auth.secret()
+                    .with_context(|| format!("no token configured for {base_url}"))?,
+            ),
+            JupyterServerAuth::Password { .. } => {
+                let password = auth
+                    .secret()
+                    .with_context(|| format!("no password configured for {base_url}"))?;
+                password_login(&base_url, &password, http_client).await?
+            }
+        };
+        Ok(Self {
+            base_url,
+            credentials,
+        })
+    }
+
+    pub fn api_url(&self, path: &str) -> String {
+        format!("{}/api{}", self.base_url, path)
+    }
+
+    fn request(&self, method: Method, path: &str) -> http::request::Builder {
+        let builder = Request::builder()
+            .method(method.clone())
+            .uri(self.api_url(path));
+        match &self.credentials {
+            JupyterServerCredentials::None => builder,
+            JupyterServerCredentials::Token(token) => {
+                builder.header("Authorization", format!("token {token}"))
+            }
+            JupyterServerCredentials::Session { cookie, xsrf_token } => {
+                let builder = builder.header("Cookie", cookie);
+                match xsrf_token {
+                    Some(xsrf_token) if method != Method::GET => {
+                        builder.header("X-XSRFToken", xsrf_token)
+                    }
+                    _ => builder,
+                }
+            }
+        }
+    }
+
+    async fn get_json<T: for<'de> Deserialize<'de>>(
+        &self,
+        path: &str,
+        http_client: &Arc<dyn HttpClient>,
+    ) -> Result<T> {
+        let request = self.request(Method::GET, path).body(AsyncBody::default())?;
+        let response = http_client.send(request).await?;
+        let status = response.status();
+        let body = read_body(response).await?;
+        anyhow::ensure!(
+            status.is_success(),
+            "GET {path} failed with {status}: {body}"
+        );
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    async fn post(&self, path: &str, http_client: &Arc<dyn HttpClient>) -> Result<()> {
+        let request = self
+            .request(Method::POST, path)
+            .body(AsyncBody::default())?;
+        let response = http_client.send(request).await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = read_body(response).await?;
+            anyhow::bail!("POST {path} failed with {status}: {body}");
+        }
+        Ok(())
+    }
+
+    fn kernel_socket_request(&self, kernel_id: &str) -> Result<Request<()>> {
+        let ws_base_url = if let Some(rest) = self.base_url.strip_prefix("https://") {
+            format!("wss://{rest}")
+        } else if let Some(rest) = self.base_url.strip_prefix("http://") {
+            format!("ws://{rest}")
+        } else {
+            self.base_url.clone()
+        };
+        let mut ws_url = format!("{ws_base_url}/api/kernels/{kernel_id}/channels");
+        if let JupyterServerCredentials::Token(token) = &self.credentials {
+            ws_url.push_str(&format!("?token={token}"));
+        }
+
+        let mut req: Request<()> = ws_url.into_client_request()?;
+        let headers = req.headers_mut();
+
+        headers.insert(
+            "User-Agent",
+            HeaderValue::from_str(&format!(
+                "Zed/{} ({}; {})",
+                "repl",
+                std::env::consts::OS,
+                std::env::consts::ARCH
+            ))?,
+        );
+        if let JupyterServerCredentials::Session { cookie, .. } = &self.credentials {
+            headers.insert("Cookie", HeaderValue::from_str(cookie)?);
+        }
+
+        Ok(req)
+    }
+}
+
+async fn read_body(response: Response<AsyncBody>) -> Result<String> {
+    let mut body = String::new();
+    response.into_body().read_to_string(&mut body).await?;
+    Ok(body)
+}
+
+fn response_cookies(response: &Response<AsyncBody>) -> Vec<(String, String)> {
+    response
+        .headers()
+        .get_all("set-cookie")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .filter_map(|cookie| {
+            let (name, value) = cookie.split(';').next()?.split_once('=')?;
+            Some((name.trim().to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+fn cookie_header(cookies: &BTreeMap<String, String>) -> String {
+    cookies
+        .iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Logs in through the server's login form, the same way the browser does.
+async fn password_login(
+    base_url: &str,
+    password: &str,
+    http_client: Arc<dyn HttpClient>,
+) -> Result<JupyterServerCredentials> {
+    let login_url = format!("{base_url}/login");
+
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(&login_url)
+        .body(AsyncBody::default())?;
+    let response = http_client.send(request).await?;
+    let mut cookies = response_cookies(&response)
+        .into_iter()
+        .collect::<BTreeMap<_, _>>();
+    let xsrf_token = cookies.get("_xsrf").cloned();
+
+    let mut form = Url::parse("http://localhost/")?;
+    {
+        let mut pairs = form.query_pairs_mut();
+        if let Some(xsrf_token) = &xsrf_token {
+            pairs.append_pair("_xsrf", xsrf_token);
+        }
+        pairs.append_pair("password", password);
+    }
+
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(&login_url)
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .header("Cookie", cookie_header(&cookies))
+        .body(AsyncBody::from(
+            form.query().unwrap_or_default().to_string(),
+        ))?;
+    let response = http_client.send(request).await?;
+
+    // The server redirects after a successful login and re-renders the form otherwise.
+    anyhow::ensure!(
+        response.status().is_redirection(),
+        "Failed to log in to {base_url}: {}",
+        response.status()
+    );
+    cookies.extend(response_cookies(&response));
+    anyhow::ensure!(
+        cookies.keys().any(|name| name != "_xsrf"),
+        "Failed to log in to {base_url}: no session cookie was returned"
+    );
+
+    Ok(JupyterServerCredentials::Session {
+        cookie: cookie_header(&cookies),
+        xsrf_token,
+    })
+}
 
 #[derive(Debug, Clone)]
 pub struct RemoteKernelSpecification {
     pub name: String,
     pub url: String,
-    pub token: String,
+    pub credentials: JupyterServerCredentials,
     pub kernelspec: JupyterKernelspec,
+    /// A kernel already running on the server to attach to, instead of launching a new one.
+    pub kernel_id: Option<String>,
+}
+
+impl RemoteKernelSpecification {
+    fn connection(&self) -> JupyterServerConnection {
+        JupyterServerConnection {
+            base_url: self.url.clone(),
+            credentials: self.credentials.clone(),
+        }
+    }
 }
 
 pub async fn launch_remote_kernel(
-    remote_server: &RemoteServer,
+    connection: &JupyterServerConnection,
     http_client: Arc<dyn HttpClient>,
     kernel_name: &str,
     _path: &str,
@@ -43,22 +288,18 @@ pub async fn launch_remote_kernel(
 
     let kernel_launch_request = serde_json::to_string(&kernel_launch_request)?;
 
-    let request = Request::builder()
-        .method("POST")
-        .uri(&remote_server.api_url("/kernels"))
-        .header("Authorization", format!("token {}", remote_server.token))
+    let request = connection
+        .request(Method::POST, "/kernels")
         .body(AsyncBody::from(kernel_launch_request))?;
 
     let response = http_client.send(request).await?;
 
     if !response.status().is_success() {
-        let mut body = String::new();
-        response.into_body().read_to_string(&mut body).await?;
+        let body = read_body(response).await?;
         anyhow::bail!("Failed to launch kernel: {body}");
     }
 
-    let mut body = String::new();
-    response.into_body().read_to_string(&mut body).await?;
+    let body = read_body(response).await?;
 
     let response: jupyter_websocket_client::Kernel = serde_json::from_str(&body)?;
 
@@ -66,58 +307,209 @@ pub async fn launch_remote_kernel(
 }
 
 pub async fn list_remote_kernelspecs(
-    remote_server: RemoteServer,
+    connection: &JupyterServerConnection,
     http_client: Arc<dyn HttpClient>,
 ) -> Result<Vec<RemoteKernelSpecification>> {
-    let url = remote_server.api_url("/kernelspecs");
-
-    let request = Request::builder()
-        .method("GET")
-        .uri(&url)
-        .header("Authorization", format!("token {}", remote_server.token))
-        .body(AsyncBody::default())?;
-
-    let response = http_client.send(request).await?;
-
-    anyhow::ensure!(
-        response.status().is_success(),
-        "Failed to fetch kernel specs: {}",
-        response.status()
-    );
-    let mut body = response.into_body();
-
-    let mut body_bytes = Vec::new();
-    body.read_to_end(&mut body_bytes).await?;
-
-    let kernel_specs: KernelSpecsResponse = serde_json::from_slice(&body_bytes)?;
+    let kernel_specs: KernelSpecsResponse = connection
+        .get_json("/kernelspecs", &http_client)
+        .await
+        .context("Failed to fetch kernel specs")?;
 
     let remote_kernelspecs = kernel_specs
         .kernelspecs
         .into_iter()
         .map(|(name, spec)| RemoteKernelSpecification {
             name: name.clone(),
-            url: remote_server.base_url.clone(),
-            token: remote_server.token.clone(),
+            url: connection.base_url.clone(),
+            credentials: connection.credentials.clone(),
             kernelspec: spec.spec,
+            kernel_id: None,
         })
         .collect::<Vec<RemoteKernelSpecification>>();
 
     anyhow::ensure!(!remote_kernelspecs.is_empty(), "No kernel specs found");
-    Ok(remote_kernelspecs.clone())
+    Ok(remote_kernelspecs)
+}
+
+#[derive(Debug, Deserialize)]
+struct RemoteKernel {
+    id: String,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RemoteSession {
+    path: String,
+    kernel: RemoteKernel,
+}
+
+/// Lists the kernels already running on the server as specifications that attach to them.
+///
+/// Kernels that belong to a session (e.g. a notebook open in JupyterLab) are named after the
+/// session's path. `kernelspecs` supplies the language of each kernel.
+pub async fn list_running_remote_kernels(
+    connection: &JupyterServerConnection,
+    http_client: Arc<dyn HttpClient>,
+    kernelspecs: &[RemoteKernelSpecification],
+) -> Result<Vec<RemoteKernelSpecification>> {
+    let sessions: Vec<RemoteSession> = connection
+        .get_json("/sessions", &http_client)
+        .await
+        .context("Failed to fetch sessions")?;
+    let kernels: Vec<RemoteKernel> = connection
+        .get_json("/kernels", &http_client)
+        .await
+        .context("Failed to fetch running kernels")?;
+
+    let session_paths = sessions
+        .into_iter()
+        .map(|session| (session.kernel.id, session.path))
+        .collect::<HashMap<_, _>>();
+
+    Ok(kernels
+        .into_iter()
+        .filter_map(|kernel| {
+            let spec = kernelspecs.iter().find(|spec| spec.name == kernel.name)?;
+            let name = match session_paths.get(&kernel.id) {
+                Some(path) => format!("{} ({path})", kernel.name),
+                None => format!(
+                    "{} (running {})",
+                    kernel.name,
+                    kernel.id.get(..8).unwrap_or(&kernel.id)
+                ),
+            };
+            Some(RemoteKernelSpecification {
+                name,
+                url: connection.base_url.clone(),
+                credentials: connection.credentials.clone(),
+                kernelspec: spec.kernelspec.clone(),
+                kernel_id: Some(kernel.id),
+            })
+        })
+        .collect())
+}
+
+async fn remote_kernel_exists(
+    connection: &JupyterServerConnection,
+    http_client: &Arc<dyn HttpClient>,
+    kernel_id: &str,
+) -> Result<bool> {
+    let request = connection
+        .request(Method::GET, &format!("/kernels/{kernel_id}"))
+        .body(AsyncBody::default())?;
+    let response = http_client.send(request).await?;
+    match response.status() {
+        StatusCode::NOT_FOUND => Ok(false),
+        status if status.is_success() => Ok(true),
+        status => anyhow::bail!("Failed to look up kernel {kernel_id}: {status}"),
+    }
+}
+
+async fn connect_kernel_socket(
+    connection: &JupyterServerConnection,
+    kernel_id: &str,
+) -> Result<JupyterWebSocket> {
+    let (ws_stream, _response) =
+        connect_async(connection.kernel_socket_request(kernel_id)?).await?;
+    Ok(JupyterWebSocket { inner: ws_stream })
+}
+
+async fn reconnect_kernel_socket(
+    connection: &JupyterServerConnection,
+    http_client: &Arc<dyn HttpClient>,
+    kernel_id: &str,
+    cx: &AsyncWindowContext,
+) -> Result<JupyterWebSocket> {
+    let mut delay = RECONNECT_INITIAL_DELAY;
+    for attempt in 1..=RECONNECT_ATTEMPTS {
+        cx.background_executor().timer(delay).await;
+        delay = (delay * 2).min(RECONNECT_MAX_DELAY);
+
+        match remote_kernel_exists(connection, http_client, kernel_id).await {
+            Ok(true) => match connect_kernel_socket(connection, kernel_id).await {
+                Ok(socket) => return Ok(socket),
+                Err(error) => {
+                    log::warn!("Reconnecting to kernel {kernel_id} (attempt {attempt}): {error:?}")
+                }
+            },
+            Ok(false) => anyhow::bail!("Kernel {kernel_id} no longer exists on the server"),
+            Err(error) => {
+                log::warn!("Reconnecting to kernel {kernel_id} (attempt {attempt}): {error:?}")
+            }
+        }
+    }
+    anyhow::bail!("Lost connection to kernel {kernel_id}")
+}
+
+/// Shuttles messages between the session and the kernel's websocket, reconnecting when the
+/// connection drops. Runs until the session drops its request sender.
+async fn run_kernel_connection(
+    mut socket: JupyterWebSocket,
+    mut request_rx: mpsc::Receiver<JupyterMessage>,
+    connection: JupyterServerConnection,
+    http_client: Arc<dyn HttpClient>,
+    kernel_id: String,
+    session: Entity<Session>,
+    cx: &mut AsyncWindowContext,
+) -> Result<()> {
+    loop {
+        let (mut w, mut r): (JupyterWebSocketWriter, JupyterWebSocketReader) = socket.split();
+
+        loop {
+            futures::select_biased! {
+                message = r.next().fuse() => match message {
+                    Some(Ok(message)) => {
+                        session
+                            .update_in(cx, |session, window, cx| {
+                                session.route(&message, window, cx);
+                            })
+                            .ok();
+                    }
+                    Some(Err(e)) => {
+                        log::error!("Error receiving message: {:?}", e);
+                    }
+                    None => break,
+                },
+                request = request_rx.next() => match request {
+                    Some(request) => {
+                        if let Err(e) = w.send(request).await {
+                            log::error!("Error sending message: {:?}", e);
+                        }
+                    }
+                    None => return Ok(()),
+                },
+            }
+        }
+
+        log::warn!("Connection to kernel {kernel_id} dropped, reconnecting");
+        socket = match reconnect_kernel_socket(&connection, &http_client, &kernel_id, cx).await {
+            Ok(socket) => socket,
+            Err(error) => {
+                session
+                    .update(cx, |session, cx| {
+                        session.kernel_errored(error.to_string(), cx);
+                    })
+                    .ok();
+                return Err(error);
+            }
+        };
+    }
 }
 
 impl PartialEq for RemoteKernelSpecification {
     fn eq(&self, other: &Self) -> bool {
-        self.name == other.name && self.url == other.url
+        self.name == other.name && self.url == other.url && self.kernel_id == other.kernel_id
     }
 }
 
 impl Eq for RemoteKernelSpecification {}
 
 pub struct RemoteRunningKernel {
-    remote_server: RemoteServer,
-    _receiving_task: Task<Result<()>>,
-    _routing_task: Task<Result<()>>,
+    connection: JupyterServerConnection,
+    connection_task: Task<Result<()>>,
+    /// Whether the kernel was already running when we attached to it, in which case shutting
+    /// down only disconnects from it, leaving it running for whoever started it.
+    attached: bool,
     http_client: Arc<dyn HttpClient>,
     pub working_directory: std::path::PathBuf,
     pub request_tx: mpsc::Sender<JupyterMessage>,
@@ -134,89 +526,51 @@ impl RemoteRunningKernel {
         window: &mut Window,
         cx: &mut App,
     ) -> Task<Result<Box<dyn RunningKernel>>> {
-        let remote_server = RemoteServer {
-            base_url: kernelspec.url,
-            token: kernelspec.token,
-        };
+        let connection = kernelspec.connection();
 
         let http_client = cx.http_client();
 
         window.spawn(cx, async move |cx| {
-            let kernel_id = launch_remote_kernel(
-                &remote_server,
-                http_client.clone(),
-                &kernelspec.name,
-                working_directory.to_str().unwrap_or_default(),
-            )
-            .await?;
-
-            let ws_url = format!(
-                "{}/api/kernels/{}/channels?token={}",
-                remote_server.base_url.replace("http", "ws"),
-                kernel_id,
-                remote_server.token
-            );
-
-            let mut req: Request<()> = ws_url.into_client_request()?;
-            let headers = req.headers_mut();
-
-            headers.insert(
-                "User-Agent",
-                HeaderValue::from_str(&format!(
-                    "Zed/{} ({}; {})",
-                    "repl",
-                    std::env::consts::OS,
-                    std::env::consts::ARCH
-                ))?,
-            );
-
-            let response = connect_async(req).await;
-
-            let (ws_stream, _response) = response?;
-
-            let kernel_socket = JupyterWebSocket { inner: ws_stream };
-
-            let (mut w, mut r): (JupyterWebSocketWriter, JupyterWebSocketReader) =
-                kernel_socket.split();
-
-            let (request_tx, mut request_rx) =
-                futures::channel::mpsc::channel::<JupyterMessage>(100);
-
-            let routing_task = cx.background_spawn({
-                async move {
-                    while let Some(message) = request_rx.next().await {
-                        w.send(message).await.ok();
-                    }
-                    Ok(())
+            let attached = kernelspec.kernel_id.is_some();
+            let kernel_id = match kernelspec.kernel_id {
+                Some(kernel_id) => kernel_id,
+                None => {
+                    launch_remote_kernel(
+                        &connection,
+                        http_client.clone(),
+                        &kernelspec.name,
+                        working_directory.to_str().unwrap_or_default(),
+                    )
+                    .await?
                 }
-            });
+            };
+
+            let socket = connect_kernel_socket(&connection, &kernel_id).await?;
 
-            let receiving_task = cx.spawn({
-                let session = session.clone();
+            let (request_tx, request_rx) = futures::channel::mpsc::channel::<JupyterMessage>(100);
 
+            let connection_task = cx.spawn({
+                let connection = connection.clone();
+                let http_client = http_client.clone();
+                let kernel_id = kernel_id.clone();
                 async move |cx| {
-                    while let Some(message) = r.next().await {
-                        match message {
-                            Ok(message) => {
-                                session
-                                    .update_in(cx, |session, window, cx| {
-                                        session.route(&message, window, cx);
-                                    })
-                                    .ok();
-                            }
-                            Err(e) => {
-                                log::error!("Error receiving message: {:?}", e);
-                            }
-                        }
-                    }
-                    Ok(())
+                    run_kernel_connection(
+                        socket,
+                        request_rx,
+                        connection,
+                        http_client,
+                        kernel_id,
+                        session,
+                        cx,
+                    )
+                    .await
                 }
             });
 
             anyhow::Ok(Box::new(Self {
-                _routing_task: routing_task,
-                _receiving_task: receiving_task,
-                remote_server,
+                connection_task,
+                attached,
+                connection,
                 working_directory,
                 request_tx,
                 // todo(kyle): pull this from the kernel API to start with
@@ -233,7 +587,9 @@ impl Debug for RemoteRunningKernel {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("RemoteRunningKernel")
             // custom debug that keeps tokens out of logs
-            .field("remote_server url", &self.remote_server.base_url)
+            .field("remote_server url", &self.connection.base_url)
+            .field("kernel_id", &self.kernel_id)
+            .field("attached", &self.attached)
             .field("working_directory", &self.working_directory)
             .field("request_tx", &self.request_tx)
             .field("execution_state", &self.execution_state)
@@ -268,17 +624,20 @@ impl RunningKernel for RemoteRunningKernel {
     }
 
     fn force_shutdown(&mut self, window: &mut Window, cx: &mut App) -> Task<anyhow::Result<()>> {
-        let url = self
-            .remote_server
-            .api_url(&format!("/kernels/{}", self.kernel_id));
-        let token = self.remote_server.token.clone();
+        if self.attached {
+            // Dropping the connection task closes the websocket.
+            self.request_tx.close_channel();
+            self.connection_task = Task::ready(Ok(()));
+            return Task::ready(Ok(()));
+        }
+
+        let path = format!("/kernels/{}", self.kernel_id);
+        let connection = self.connection.clone();
         let http_client = self.http_client.clone();
 
         window.spawn(cx, async move |_| {
-            let request = Request::builder()
-                .method("DELETE")
-                .uri(&url)
-                .header("Authorization", format!("token {}", token))
+            let request = connection
+                .request(Method::DELETE, &path)
                 .body(AsyncBody::default())?;
 
             let response = http_client.send(request).await?;
@@ -291,4 +650,208 @@ impl RunningKernel for RemoteRunningKernel {
             Ok(())
         })
     }
+
+    fn interrupt(&mut self, cx: &mut App) -> Option<Task<anyhow::Result<()>>> {
+        let path = format!("/kernels/{}/interrupt", self.kernel_id);
+        let connection = self.connection.clone();
+        let http_client = self.http_client.clone();
+
+        Some(cx.background_spawn(async move { connection.post(&path, &http_client).await }))
+    }
+
+    fn restart(&mut self, cx: &mut App) -> Option<Task<anyhow::Result<()>>> {
+        let path = format!("/kernels/{}/restart", self.kernel_id);
+        let connection = self.connection.clone();
+        let http_client = self.http_client.clone();
+
+        Some(cx.background_spawn(async move { connection.post(&path, &http_client).await }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_client::FakeHttpClient;
+    use smol::io::AsyncReadExt as _;
+
+    fn python_spec(connection: &JupyterServerConnection) -> RemoteKernelSpecification {
+        RemoteKernelSpecification {
+            name: "python3".to_string(),
+            url: connection.base_url.clone(),
+            credentials: connection.credentials.clone(),
+            kernelspec: JupyterKernelspec {
+                argv: Vec::new(),
+                display_name: "Python 3".to_string(),
+                language: "python".to_string(),
+                interrupt_mode: None,
+                metadata: None,
+                env: None,
+            },
+            kernel_id: None,
+        }
+    }
+
+    #[gpui::test]
+    async fn test_password_login() {
+        let http_client: Arc<dyn HttpClient> = FakeHttpClient::create(|request| async move {
+            assert_eq!(request.uri().path(), "/login");
+            let response = Response::builder();
+            let response = if request.method() == Method::GET {
+                response
+                    .status(200)
+                    .header("set-cookie", "_xsrf=2|abc; Path=/")
+            } else {
+                assert_eq!(request.headers().get("Cookie").unwrap(), "_xsrf=2|abc");
+                let mut body = String::new();
+                request.into_body().read_to_string(&mut body).await?;
+                if body == "_xsrf=2%7Cabc&password=hunter+2" {
+                    response
+                        .status(302)
+                        .header("set-cookie", "username-localhost-8888=\"2|xyz\"; Path=/")
+                } else {
+                    response.status(401)
+                }
+            };
+            Ok(response.body(AsyncBody::default())?)
+        });
+
+        let auth = JupyterServerAuth::Password {
+            password: Some("hunter 2".to_string()),
+            password_env: None,
+        };
+        let connection =
+            JupyterServerConnection::connect("http://localhost:8888/", &auth, http_client.clone())
+                .await
+                .unwrap();
+        assert_eq!(connection.base_url, "http://localhost:8888");
+        assert_eq!(
+            connection.credentials,
+            JupyterServerCredentials::Session {
+                cookie: "_xsrf=2|abc; username-localhost-8888=\"2|xyz\"".to_string(),
+                xsrf_token: Some("2|abc".to_string()),
+            }
+        );
+
+        let request = connection.request(Method::POST, "/kernels/k1/interrupt");
+        let headers = request.headers_ref().unwrap();
+        assert_eq!(headers.get("X-XSRFToken").unwrap(), "2|abc");
+        assert!(headers.get("Authorization").is_none());
+
+        let auth = JupyterServerAuth::Password {
+            password: Some("wrong".to_string()),
+            password_env: None,
+        };
+        assert!(
+            JupyterServerConnection::connect("http://localhost:8888", &auth, http_client)
+                .await
+                .is_err()
+        );
+    }
+
+    #[gpui::test]
+    async fn test_list_running_remote_kernels() {
+        let http_client: Arc<dyn HttpClient> = FakeHttpClient::create(|request| async move {
+            assert_eq!(
+                request.headers().get("Authorization").unwrap(),
+                "token secret"
+            );
+            let body = match request.uri().path() {
+                "/api/sessions" => serde_json::json!([
+                    {
+                        "id": "s1",
+                        "path": "analysis.ipynb",
+                        "kernel": { "id": "0123456789abcdef", "name": "python3" }
+                    }
+                ]),
+                "/api/kernels" => serde_json::json!([
+                    { "id": "0123456789abcdef", "name": "python3" },
+                    { "id": "fedcba9876543210", "name": "python3" },
+                    { "id": "aaaaaaaaaaaaaaaa", "name": "ir" }
+                ]),
+                path => panic!("unexpected request to {path}"),
+            };
+            Ok(Response::builder()
+                .status(200)
+                .body(AsyncBody::from(body.to_string()))?)
+        });
+
+        let connection = JupyterServerConnection {
+            base_url: "http://localhost:8888".to_string(),
+            credentials: JupyterServerCredentials::Token("secret".to_string()),
+        };
+        let specs = [python_spec(&connection)];
+        let running = list_running_remote_kernels(&connection, http_client, &specs)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            running
+                .iter()
+                .map(|spec| (spec.name.as_str(), spec.kernel_id.as_deref()))
+                .collect::<Vec<_>>(),
+            [
+                ("python3 (analysis.ipynb)", Some("0123456789abcdef")),
+                ("python3 (running fedcba98)", Some("fedcba9876543210")),
+            ]
+        );
+        assert!(
+            running
+                .iter()
+                .all(|spec| spec.kernelspec.language == "python")
+        );
+    }
+
+    fn running_kernel(attached: bool, http_client: Arc<dyn HttpClient>) -> RemoteRunningKernel {
+        RemoteRunningKernel {
+            connection: JupyterServerConnection {
+                base_url: "http://localhost:8888".to_string(),
+                credentials: JupyterServerCredentials::Token("secret".to_string()),
+            },
+            connection_task: Task::ready(Ok(())),
+            attached,
+            http_client,
+            working_directory: std::path::PathBuf::from("/project"),
+            request_tx: mpsc::channel(1).0,
+            execution_state: ExecutionState::Idle,
+            kernel_info: None,
+            kernel_id: "k1".to_string(),
+        }
+    }
+
+    #[gpui::test]
+    async fn test_force_shutdown_leaves_attached_kernels_running(cx: &mut gpui::TestAppContext) {
+        let cx = cx.add_empty_window();
+
+        let http_client: Arc<dyn HttpClient> = FakeHttpClient::create(|request| async move {
+            anyhow::bail!("unexpected request to {}", request.uri())
+        });
+        let mut kernel = running_kernel(true, http_client);
+        cx.update(|window, cx| kernel.force_shutdown(window, cx))
+            .await
+            .unwrap();
+        assert!(kernel.request_tx.is_closed());
+
+        let http_client: Arc<dyn HttpClient> = FakeHttpClient::create(|request| async move {
+            assert_eq!(request.method(), Method::DELETE);
+            assert!(request.uri().path().ends_with("/kernels/k1"));
+            Ok(Response::builder().status(204).body(AsyncBody::default())?)
+        });
+        let mut kernel = running_kernel(false, http_client);
+        cx.update(|window, cx| kernel.force_shutdown(window, cx))
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn test_kernel_socket_request() {
+        let connection = JupyterServerConnection {
+            base_url: "https://example.com/jupyter".to_string(),
+            credentials: JupyterServerCredentials::Token("secret".to_string()),
+        };
+        let request = connection.kernel_socket_request("k1").unwrap();
+        assert_eq!(
+            request.uri().to_string(),
+            "wss://example.com/jupyter/api/kernels/k1/channels?token=secret"
+        );
+    }
 }
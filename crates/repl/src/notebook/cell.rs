@@ -1,14 +1,17 @@
 #![allow(unused, dead_code)]
 use std::sync::Arc;
 
+use anyhow::Result;
 use editor::{Editor, EditorMode, MultiBuffer};
-use futures::future::Shared;
+use futures::{FutureExt as _, future::Shared};
 use gpui::{
-    App, Entity, Hsla, RetainAllImageCache, Task, TextStyleRefinement, image_cache, prelude::*,
+    App, ClickEvent, Entity, EntityId, EventEmitter, Focusable, Hsla, RetainAllImageCache,
+    Subscription, Task, TextStyleRefinement, image_cache, prelude::*,
 };
 use language::{Buffer, Language, LanguageRegistry};
 use markdown_preview::{markdown_parser::parse_markdown, markdown_renderer::render_markdown_block};
 use nbformat::v4::{CellId, CellMetadata, CellType};
+use runtimelib::{ExecutionState, JupyterMessageContent};
 use settings::Settings as _;
 use theme::ThemeSettings;
 use ui::{IconButtonShape, prelude::*};
@@ -112,6 +115,95 @@ fn convert_outputs(
         .collect()
 }
 
+/// Events emitted by a cell to the notebook containing it.
+pub enum CellEvent {
+    /// The user asked to run the cell.
+    Run,
+}
+
+/// Splits cell source into lines the way nbformat stores it, keeping the line endings.
+pub(crate) fn source_lines(source: &str) -> Vec<String> {
+    source.split_inclusive('\n').map(str::to_string).collect()
+}
+
+/// Converts an IOPub message into the output that nbformat would store for it.
+fn output_from_message(content: &JupyterMessageContent) -> Option<nbformat::v4::Output> {
+    let (output_type, value) = match content {
+        JupyterMessageContent::StreamContent(stream) => ("stream", serde_json::to_value(stream)),
+        JupyterMessageContent::DisplayData(data) => ("display_data", serde_json::to_value(data)),
+        JupyterMessageContent::ExecuteResult(result) => {
+            ("execute_result", serde_json::to_value(result))
+        }
+        JupyterMessageContent::ErrorOutput(error) => ("error", serde_json::to_value(error)),
+        _ => return None,
+    };
+    let mut value = value.log_err()?;
+    let object = value.as_object_mut()?;
+    // Transient data (such as display IDs) is never persisted.
+    object.remove("transient");
+    object.insert("output_type".into(), output_type.into());
+    serde_json::from_value(value).log_err()
+}
+
+fn cell_editor(
+    source: &str,
+    language: Option<Shared<Task<Option<Arc<Language>>>>>,
+    window: &mut Window,
+    cx: &mut App,
+) -> (Entity<Editor>, Task<()>) {
+    let buffer = cx.new(|cx| Buffer::local(source, cx));
+    let multi_buffer = cx.new(|cx| MultiBuffer::singleton(buffer.clone(), cx));
+
+    let editor = cx.new(|cx| {
+        let mut editor = Editor::new(
+            EditorMode::AutoHeight {
+                min_lines: 1,
+                max_lines: Some(1024),
+            },
+            multi_buffer,
+            None,
+            window,
+            cx,
+        );
+
+        let theme = ThemeSettings::get_global(cx);
+
+        let refinement = TextStyleRefinement {
+            font_family: Some(theme.buffer_font.family.clone()),
+            font_size: Some(theme.buffer_font_size(cx).into()),
+            color: Some(cx.theme().colors().editor_foreground),
+            background_color: Some(gpui::transparent_black()),
+            ..Default::default()
+        };
+
+        editor.set_show_gutter(false, cx);
+        editor.set_text_style_refinement(refinement);
+        editor
+    });
+
+    let language_task = match language {
+        Some(language) => window.spawn(cx, async move |cx| {
+            let language = language.await;
+            buffer
+                .update(cx, |buffer, cx| buffer.set_language(language, cx))
+                .ok();
+        }),
+        None => Task::ready(()),
+    };
+
+    (editor, language_task)
+}
+
+fn mark_saved(editor: &Entity<Editor>, cx: &mut App) {
+    let Some(buffer) = editor.read(cx).buffer().read(cx).as_singleton() else {
+        return;
+    };
+    buffer.update(cx, |buffer, cx| {
+        let version = buffer.version();
+        buffer.did_save(version, None, cx);
+    });
+}
+
 impl Cell {
     pub fn load(
         cell: &nbformat::v4::Cell,
@@ -128,37 +220,46 @@ impl Cell {
                 ..
             } => {
                 let source = source.join("");
+                // Attachments are not rendered yet, but have to survive a round trip.
+                let attachments = serde_json::to_value(cell)
+                    .ok()
+                    .and_then(|mut cell| cell.get_mut("attachments").map(serde_json::Value::take))
+                    .filter(|attachments| !attachments.is_null());
+
+                let markdown_language = {
+                    let languages = languages.clone();
+                    cx.background_spawn(async move {
+                        languages.language_for_name("Markdown").await.ok()
+                    })
+                    .shared()
+                };
 
                 let entity = cx.new(|cx| {
-                    let markdown_parsing_task = {
-                        let languages = languages.clone();
-                        let source = source.clone();
-
-                        cx.spawn_in(window, async move |this, cx| {
-                            let parsed_markdown = cx
-                                .background_spawn(async move {
-                                    parse_markdown(&source, None, Some(languages)).await
-                                })
-                                .await;
-
-                            this.update(cx, |cell: &mut MarkdownCell, _| {
-                                cell.parsed_markdown = Some(parsed_markdown);
-                            })
-                            .log_err();
-                        })
-                    };
+                    let (editor, language_task) =
+                        cell_editor(&source, Some(markdown_language), window, cx);
+                    let blur_subscription = cx.on_blur(
+                        &editor.focus_handle(cx),
+                        window,
+                        |cell: &mut MarkdownCell, window, cx| cell.finish_editing(window, cx),
+                    );
 
-                    MarkdownCell {
-                        markdown_parsing_task,
+                    let mut cell = MarkdownCell {
+                        markdown_parsing_task: Task::ready(()),
                         image_cache: RetainAllImageCache::new(cx),
                         languages: languages.clone(),
                         id: id.clone(),
                         metadata: metadata.clone(),
-                        source: source.clone(),
+                        attachments,
+                        editor,
+                        editing: source.is_empty(),
                         parsed_markdown: None,
                         selected: false,
                         cell_position: None,
-                    }
+                        _language_task: language_task,
+                        _blur_subscription: blur_subscription,
+                    };
+                    cell.parse_markdown(window, cx);
+                    cell
                 });
 
                 Cell::Markdown(entity)
@@ -170,57 +271,18 @@ impl Cell {
                 source,
                 outputs,
             } => Cell::Code(cx.new(|cx| {
-                let text = source.join("");
-
-                let buffer = cx.new(|cx| Buffer::local(text.clone(), cx));
-                let multi_buffer = cx.new(|cx| MultiBuffer::singleton(buffer.clone(), cx));
-
-                let editor_view = cx.new(|cx| {
-                    let mut editor = Editor::new(
-                        EditorMode::AutoHeight {
-                            min_lines: 1,
-                            max_lines: Some(1024),
-                        },
-                        multi_buffer,
-                        None,
-                        window,
-                        cx,
-                    );
-
-                    let theme = ThemeSettings::get_global(cx);
-
-                    let refinement = TextStyleRefinement {
-                        font_family: Some(theme.buffer_font.family.clone()),
-                        font_size: Some(theme.buffer_font_size(cx).into()),
-                        color: Some(cx.theme().colors().editor_foreground),
-                        background_color: Some(gpui::transparent_black()),
-                        ..Default::default()
-                    };
-
-                    editor.set_text(text, window, cx);
-                    editor.set_show_gutter(false, cx);
-                    editor.set_text_style_refinement(refinement);
-
-                    // editor.set_read_only(true);
-                    editor
-                });
-
-                let buffer = buffer.clone();
-                let language_task = cx.spawn_in(window, async move |this, cx| {
-                    let language = notebook_language.await;
-
-                    buffer.update(cx, |buffer, cx| {
-                        buffer.set_language(language.clone(), cx);
-                    });
-                });
+                let (editor, language_task) =
+                    cell_editor(&source.join(""), Some(notebook_language), window, cx);
 
                 CodeCell {
                     id: id.clone(),
                     metadata: metadata.clone(),
                     execution_count: *execution_count,
-                    source: source.join(""),
-                    editor: editor_view,
+                    editor,
                     outputs: convert_outputs(outputs, window, cx),
+                    raw_outputs: outputs.clone(),
+                    clear_on_next_output: false,
+                    executing: false,
                     selected: false,
                     language_task,
                     cell_position: None,
@@ -230,15 +292,102 @@ impl Cell {
                 id,
                 metadata,
                 source,
-            } => Cell::Raw(cx.new(|_| RawCell {
-                id: id.clone(),
-                metadata: metadata.clone(),
-                source: source.join(""),
-                selected: false,
-                cell_position: None,
+            } => Cell::Raw(cx.new(|cx| {
+                let (editor, _) = cell_editor(&source.join(""), None, window, cx);
+                RawCell {
+                    id: id.clone(),
+                    metadata: metadata.clone(),
+                    editor,
+                    selected: false,
+                    cell_position: None,
+                }
             })),
         }
     }
+
+    pub fn id(&self, cx: &App) -> CellId {
+        match self {
+            Cell::Code(cell) => cell.read(cx).id.clone(),
+            Cell::Markdown(cell) => cell.read(cx).id.clone(),
+            Cell::Raw(cell) => cell.read(cx).id.clone(),
+        }
+    }
+
+    pub fn entity_id(&self) -> EntityId {
+        match self {
+            Cell::Code(cell) => cell.entity_id(),
+            Cell::Markdown(cell) => cell.entity_id(),
+            Cell::Raw(cell) => cell.entity_id(),
+        }
+    }
+
+    pub fn cell_type(&self) -> CellType {
+        match self {
+            Cell::Code(_) => CellType::Code,
+            Cell::Markdown(_) => CellType::Markdown,
+            Cell::Raw(_) => CellType::Raw,
+        }
+    }
+
+    pub fn editor(&self, cx: &App) -> Entity<Editor> {
+        match self {
+            Cell::Code(cell) => cell.read(cx).editor.clone(),
+            Cell::Markdown(cell) => cell.read(cx).editor.clone(),
+            Cell::Raw(cell) => cell.read(cx).editor.clone(),
+        }
+    }
+
+    pub fn source(&self, cx: &App) -> String {
+        self.editor(cx).read(cx).text(cx)
+    }
+
+    pub fn is_dirty(&self, cx: &App) -> bool {
+        self.editor(cx).read(cx).buffer().read(cx).is_dirty(cx)
+    }
+
+    /// Marks the cell source as saved, so that it is no longer dirty.
+    pub fn did_save(&self, cx: &mut App) {
+        let editor = self.editor(cx);
+        mark_saved(&editor, cx);
+    }
+
+    /// The nbformat representation of the cell, as it should be written to disk.
+    pub fn to_nbformat(&self, cx: &App) -> Result<nbformat::v4::Cell> {
+        let source = source_lines(&self.source(cx));
+        Ok(match self {
+            Cell::Code(cell) => {
+                let cell = cell.read(cx);
+                nbformat::v4::Cell::Code {
+                    id: cell.id.clone(),
+                    metadata: cell.metadata.clone(),
+                    execution_count: cell.execution_count,
+                    source,
+                    outputs: cell.raw_outputs.clone(),
+                }
+            }
+            Cell::Markdown(cell) => {
+                let cell = cell.read(cx);
+                let mut value = serde_json::json!({
+                    "cell_type": "markdown",
+                    "id": cell.id,
+                    "metadata": cell.metadata,
+                    "source": source,
+                });
+                if let Some(attachments) = &cell.attachments {
+                    value["attachments"] = attachments.clone();
+                }
+                serde_json::from_value(value)?
+            }
+            Cell::Raw(cell) => {
+                let cell = cell.read(cx);
+                nbformat::v4::Cell::Raw {
+                    id: cell.id.clone(),
+                    metadata: cell.metadata.clone(),
+                    source,
+                }
+            }
+        })
+    }
 }
 
 pub trait RenderableCell: Render {
@@ -247,7 +396,10 @@ pub trait RenderableCell: Render {
     fn id(&self) -> &CellId;
     fn cell_type(&self) -> CellType;
     fn metadata(&self) -> &CellMetadata;
-    fn source(&self) -> &String;
+    fn editor(&self) -> &Entity<Editor>;
+    fn source(&self, cx: &App) -> String {
+        self.editor().read(cx).text(cx)
+    }
     fn selected(&self) -> bool;
     fn set_selected(&mut self, selected: bool) -> &mut Self;
     fn selected_bg_color(&self, window: &mut Window, cx: &mut Context<Self>) -> Hsla {
@@ -335,13 +487,57 @@ pub trait RunnableCell: RenderableCell {
 pub struct MarkdownCell {
     id: CellId,
     metadata: CellMetadata,
+    attachments: Option<serde_json::Value>,
     image_cache: Entity<RetainAllImageCache>,
-    source: String,
+    editor: Entity<Editor>,
+    editing: bool,
     parsed_markdown: Option<markdown_preview::markdown_elements::ParsedMarkdown>,
     markdown_parsing_task: Task<()>,
     selected: bool,
     cell_position: Option<CellPosition>,
     languages: Arc<LanguageRegistry>,
+    _language_task: Task<()>,
+    _blur_subscription: Subscription,
+}
+
+impl MarkdownCell {
+    pub fn is_editing(&self) -> bool {
+        self.editing
+    }
+
+    pub fn start_editing(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.editing = true;
+        self.editor.focus_handle(cx).focus(window);
+        cx.notify();
+    }
+
+    /// Leaves edit mode and renders the source.
+    pub fn finish_editing(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.editing || self.source(cx).is_empty() {
+            return;
+        }
+        self.editing = false;
+        self.parse_markdown(window, cx);
+        cx.notify();
+    }
+
+    fn parse_markdown(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let source = self.source(cx);
+        let languages = self.languages.clone();
+        self.markdown_parsing_task = cx.spawn_in(window, async move |this, cx| {
+            let parsed_markdown = cx
+                .background_spawn(
+                    async move { parse_markdown(&source, None, Some(languages)).await },
+                )
+                .await;
+
+            this.update(cx, |cell, cx| {
+                cell.parsed_markdown = Some(parsed_markdown);
+                cx.notify();
+            })
+            .log_err();
+        });
+    }
 }
 
 impl RenderableCell for MarkdownCell {
@@ -359,8 +555,8 @@ impl RenderableCell for MarkdownCell {
         &self.metadata
     }
 
-    fn source(&self) -> &String {
-        &self.source
+    fn editor(&self) -> &Entity<Editor> {
+        &self.editor
     }
 
     fn selected(&self) -> bool {
@@ -388,13 +584,52 @@ impl RenderableCell for MarkdownCell {
 
 impl Render for MarkdownCell {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        let Some(parsed) = self.parsed_markdown.as_ref() else {
-            return div();
+        let content = match self.parsed_markdown.as_ref() {
+            Some(parsed) if !self.editing => {
+                let mut markdown_render_context =
+                    markdown_preview::markdown_renderer::RenderContext::new(None, window, cx);
+
+                v_flex()
+                    .id("markdown-cell-preview")
+                    .image_cache(self.image_cache.clone())
+                    .size_full()
+                    .flex_1()
+                    .p_3()
+                    .font_ui(cx)
+                    .text_size(TextSize::Default.rems(cx))
+                    .children(parsed.children.iter().map(|child| {
+                        div().relative().child(
+                            div()
+                                .relative()
+                                .child(render_markdown_block(child, &mut markdown_render_context)),
+                        )
+                    }))
+                    .on_click(cx.listener(|this, event: &ClickEvent, window, cx| {
+                        if event.click_count() >= 2 {
+                            this.start_editing(window, cx);
+                        }
+                    }))
+                    .into_any_element()
+            }
+            _ => div()
+                .py_1p5()
+                .w_full()
+                .child(
+                    div()
+                        .flex()
+                        .size_full()
+                        .flex_1()
+                        .py_3()
+                        .px_5()
+                        .rounded_lg()
+                        .border_1()
+                        .border_color(cx.theme().colors().border)
+                        .bg(cx.theme().colors().editor_background)
+                        .child(div().w_full().child(self.editor.clone())),
+                )
+                .into_any_element(),
         };
 
-        let mut markdown_render_context =
-            markdown_preview::markdown_renderer::RenderContext::new(None, window, cx);
-
         v_flex()
             .size_full()
             // TODO: Move base cell render into trait impl so we don't have to repeat this
@@ -408,20 +643,7 @@ impl Render for MarkdownCell {
                     .gap(DynamicSpacing::Base08.rems(cx))
                     .bg(self.selected_bg_color(window, cx))
                     .child(self.gutter(window, cx))
-                    .child(
-                        v_flex()
-                            .image_cache(self.image_cache.clone())
-                            .size_full()
-                            .flex_1()
-                            .p_3()
-                            .font_ui(cx)
-                            .text_size(TextSize::Default.rems(cx))
-                            .children(parsed.children.iter().map(|child| {
-                                div().relative().child(div().relative().child(
-                                    render_markdown_block(child, &mut markdown_render_context),
-                                ))
-                            })),
-                    ),
+                    .child(content),
             )
             // TODO: Move base cell render into trait impl so we don't have to repeat this
             .children(self.cell_position_spacer(false, window, cx))
@@ -432,24 +654,121 @@ pub struct CodeCell {
     id: CellId,
     metadata: CellMetadata,
     execution_count: Option<i32>,
-    source: String,
     editor: Entity<editor::Editor>,
     outputs: Vec<Output>,
+    /// The outputs as they are stored in the notebook file.
+    raw_outputs: Vec<nbformat::v4::Output>,
+    clear_on_next_output: bool,
+    executing: bool,
     selected: bool,
     cell_position: Option<CellPosition>,
     language_task: Task<()>,
 }
 
+impl EventEmitter<CellEvent> for CodeCell {}
+
 impl CodeCell {
     pub fn is_dirty(&self, cx: &App) -> bool {
         self.editor.read(cx).buffer().read(cx).is_dirty(cx)
     }
+
     pub fn has_outputs(&self) -> bool {
         !self.outputs.is_empty()
     }
 
     pub fn clear_outputs(&mut self) {
         self.outputs.clear();
+        self.raw_outputs.clear();
+        self.clear_on_next_output = false;
+    }
+
+    pub fn is_executing(&self) -> bool {
+        self.executing
+    }
+
+    /// Resets the cell before its source is sent to the kernel.
+    pub fn start_execution(&mut self, cx: &mut Context<Self>) {
+        self.clear_outputs();
+        self.execution_count = None;
+        self.executing = true;
+        cx.notify();
+    }
+
+    /// Shows a message in place of the outputs, e.g. when no kernel could be started.
+    /// The message is not saved with the notebook.
+    pub fn set_message(&mut self, message: impl Into<String>, cx: &mut Context<Self>) {
+        self.clear_outputs();
+        self.executing = false;
+        self.outputs.push(Output::Message(message.into()));
+        cx.notify();
+    }
+
+    /// Applies a message the kernel sent in response to executing this cell.
+    pub fn handle_message(
+        &mut self,
+        content: &JupyterMessageContent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match content {
+            JupyterMessageContent::ExecuteInput(input) => {
+                self.execution_count = serde_json::to_value(input)
+                    .ok()
+                    .and_then(|input| input.get("execution_count")?.as_i64())
+                    .and_then(|count| i32::try_from(count).ok());
+            }
+            JupyterMessageContent::ClearOutput(options) => {
+                if options.wait {
+                    self.clear_on_next_output = true;
+                } else {
+                    self.clear_outputs();
+                }
+            }
+            JupyterMessageContent::Status(status) => {
+                self.executing = matches!(status.execution_state, ExecutionState::Busy);
+            }
+            content => {
+                let Some(output) = output_from_message(content) else {
+                    return;
+                };
+                if self.clear_on_next_output {
+                    self.clear_outputs();
+                }
+                self.push_output(output, window, cx);
+            }
+        }
+        cx.notify();
+    }
+
+    fn push_output(
+        &mut self,
+        output: nbformat::v4::Output,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        // Consecutive writes to the same stream are stored as a single output, as Jupyter does.
+        if let (
+            nbformat::v4::Output::Stream { name, text },
+            Some(nbformat::v4::Output::Stream {
+                name: last_name,
+                text: last_text,
+            }),
+        ) = (&output, self.raw_outputs.last_mut())
+        {
+            if name == last_name {
+                last_text.0.push_str(&text.0);
+                let text = last_text.0.clone();
+                self.outputs.pop();
+                self.outputs.push(Output::Stream {
+                    content: cx.new(|cx| TerminalOutput::from(&text, window, cx)),
+                });
+                return;
+            }
+        }
+
+        self.outputs
+            .extend(convert_outputs(&vec![output.clone()], window, cx));
+        self.raw_outputs.push(output);
     }
 
     fn output_control(&self) -> Option<CellControlType> {
@@ -517,8 +836,8 @@ impl RenderableCell for CodeCell {
         &self.metadata
     }
 
-    fn source(&self) -> &String {
-        &self.source
+    fn editor(&self) -> &Entity<Editor> {
+        &self.editor
     }
 
     fn control(&self, window: &mut Window, cx: &mut Context<Self>) -> Option<CellControl> {
@@ -552,8 +871,9 @@ impl RenderableCell for CodeCell {
 }
 
 impl RunnableCell for CodeCell {
-    fn run(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        println!("Running code cell: {}", self.id);
+    fn run(&mut self, _window: &mut Window, cx: &mut Context<Self>) {
+        // The notebook owns the kernel session, so it does the actual execution.
+        cx.emit(CellEvent::Run);
     }
 
     fn execution_count(&self) -> Option<i32> {
@@ -671,7 +991,7 @@ impl Render for CodeCell {
 pub struct RawCell {
     id: CellId,
     metadata: CellMetadata,
-    source: String,
+    editor: Entity<Editor>,
     selected: bool,
     cell_position: Option<CellPosition>,
 }
@@ -691,8 +1011,8 @@ impl RenderableCell for RawCell {
         &self.metadata
     }
 
-    fn source(&self) -> &String {
-        &self.source
+    fn editor(&self) -> &Entity<Editor> {
+        &self.editor
     }
 
     fn selected(&self) -> bool {
@@ -735,9 +1055,7 @@ impl Render for RawCell {
                             .size_full()
                             .flex_1()
                             .p_3()
-                            .font_ui(cx)
-                            .text_size(TextSize::Default.rems(cx))
-                            .child(self.source.clone()),
+                            .child(self.editor.clone()),
                     ),
             )
             // TODO: Move base cell render into trait impl so we don't have to repeat this
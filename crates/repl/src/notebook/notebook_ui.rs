@@ -1,6 +1,9 @@
 #![allow(unused, dead_code)]
 use std::future::Future;
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use anyhow::{Context as _, Result};
 use client::proto::ViewId;
@@ -9,20 +12,27 @@ use feature_flags::{FeatureFlagAppExt as _, NotebookFeatureFlag};
 use futures::FutureExt;
 use futures::future::Shared;
 use gpui::{
-    AnyElement, App, Entity, EventEmitter, FocusHandle, Focusable, ListScrollEvent, ListState,
-    Point, Task, actions, list, prelude::*,
+    AnyElement, App, Entity, EntityId, EventEmitter, FocusHandle, Focusable, KeyContext,
+    ListScrollEvent, ListState, Point, Subscription, Task, actions, list, prelude::*,
 };
 use language::{Language, LanguageRegistry};
-use project::{Project, ProjectEntryId, ProjectPath};
+use project::{Fs, Project, ProjectEntryId, ProjectPath};
+use runtimelib::JupyterMessageContent;
+use serde::Serialize as _;
 use ui::{Tooltip, prelude::*};
+use util::ResultExt as _;
 use workspace::item::{ItemEvent, SaveOptions, TabContentParams};
 use workspace::searchable::SearchableItemHandle;
 use workspace::{Item, ItemHandle, Pane, ProjectItem, ToolbarItemLocation};
 use workspace::{ToolbarItemEvent, ToolbarItemView};
 
-use super::{Cell, CellPosition, RenderableCell};
+use super::{Cell, CellEvent, CellPosition, RenderableCell, source_lines};
+use crate::kernels::{Kernel, KernelSpecification};
+use crate::repl_store::ReplStore;
+use crate::session::{Session, SessionEvent};
 
 use nbformat::v4::CellId;
+use nbformat::v4::CellType;
 use nbformat::v4::Metadata as NotebookMetadata;
 
 actions!(
@@ -42,9 +52,27 @@ actions!(
         AddMarkdownBlock,
         /// Adds a new code cell.
         AddCodeBlock,
+        /// Runs the current cell and selects the next one.
+        RunCell,
+        /// Deletes the current cell.
+        DeleteCell,
+        /// Changes the current cell into a code cell.
+        ChangeToCodeCell,
+        /// Changes the current cell into a markdown cell.
+        ChangeToMarkdownCell,
+        /// Changes the current cell into a raw cell.
+        ChangeToRawCell,
+        /// Undoes the last cell operation.
+        Undo,
+        /// Redoes the last undone cell operation.
+        Redo,
+        /// Stops editing the current cell, so that keystrokes act on cells.
+        StopEditingCell,
     ]
 );
 
+const EMPTY_NOTEBOOK: &str = r#"{"cells": [], "metadata": {}, "nbformat": 4, "nbformat_minor": 5}"#;
+
 pub(crate) const MAX_TEXT_BLOCK_WIDTH: f32 = 9999.0;
 pub(crate) const SMALL_SPACING_SIZE: f32 = 8.0;
 pub(crate) const MEDIUM_SPACING_SIZE: f32 = 12.0;
@@ -77,6 +105,7 @@ pub struct NotebookEditor {
 
     focus_handle: FocusHandle,
     notebook_item: Entity<NotebookItem>,
+    notebook_language: Shared<Task<Option<Arc<Language>>>>,
 
     remote_id: Option<ViewId>,
     cell_list: ListState,
@@ -84,6 +113,26 @@ pub struct NotebookEditor {
     selected_cell_index: usize,
     cell_order: Vec<CellId>,
     cell_map: HashMap<CellId, Cell>,
+    cell_subscriptions: HashMap<EntityId, Vec<Subscription>>,
+    /// Whether cells were added, removed, reordered or produced outputs since the last save.
+    cells_changed: bool,
+    undo_stack: Vec<CellsSnapshot>,
+    redo_stack: Vec<CellsSnapshot>,
+
+    session: Option<Entity<Session>>,
+    /// Maps the IDs of pending execute requests to the cell being executed.
+    executions: HashMap<String, CellId>,
+    _session_subscription: Option<Subscription>,
+}
+
+/// The cell layout of a notebook, as recorded for undo and redo. Cells are
+/// kept alive by the snapshot, so restoring one brings back their source,
+/// outputs and IDs.
+#[derive(Clone)]
+struct CellsSnapshot {
+    cell_order: Vec<CellId>,
+    cell_map: HashMap<CellId, Cell>,
+    selected_cell_index: usize,
 }
 
 impl NotebookEditor {
@@ -96,51 +145,145 @@ impl NotebookEditor {
         let focus_handle = cx.focus_handle();
 
         let languages = project.read(cx).languages().clone();
-        let language_name = notebook_item.read(cx).language_name();
 
         let notebook_language = notebook_item.read(cx).notebook_language();
         let notebook_language = cx
             .spawn_in(window, async move |_, _| notebook_language.await)
             .shared();
 
-        let mut cell_order = vec![]; // Vec<CellId>
-        let mut cell_map = HashMap::default(); // HashMap<CellId, Cell>
+        let cell_list = ListState::new(0, gpui::ListAlignment::Top, px(1000.));
 
-        for (index, cell) in notebook_item
-            .read(cx)
-            .notebook
-            .clone()
-            .cells
-            .iter()
-            .enumerate()
-        {
-            let cell_id = cell.id();
-            cell_order.push(cell_id.clone());
-            cell_map.insert(
-                cell_id.clone(),
-                Cell::load(cell, &languages, notebook_language.clone(), window, cx),
-            );
-        }
-
-        let notebook_handle = cx.entity().downgrade();
-        let cell_count = cell_order.len();
-
-        let this = cx.entity();
-        let cell_list = ListState::new(cell_count, gpui::ListAlignment::Top, px(1000.));
-
-        Self {
+        let mut this = Self {
             project,
             languages: languages.clone(),
             focus_handle,
             notebook_item,
+            notebook_language,
             remote_id: None,
             cell_list,
             selected_cell_index: 0,
-            cell_order: cell_order.clone(),
-            cell_map: cell_map.clone(),
+            cell_order: Vec::new(),
+            cell_map: HashMap::default(),
+            cell_subscriptions: HashMap::default(),
+            cells_changed: false,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            session: None,
+            executions: HashMap::default(),
+            _session_subscription: None,
+        };
+        this.load_cells(window, cx);
+        this
+    }
+
+    /// Replaces all cells with the ones stored in the notebook item.
+    fn load_cells(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let notebook = self.notebook_item.read(cx).notebook.clone();
+
+        self.cell_order.clear();
+        self.cell_map.clear();
+        for cell in &notebook.cells {
+            let cell_id = cell.id().clone();
+            self.cell_order.push(cell_id.clone());
+            self.cell_map.insert(
+                cell_id,
+                Cell::load(
+                    cell,
+                    &self.languages,
+                    self.notebook_language.clone(),
+                    window,
+                    cx,
+                ),
+            );
+        }
+
+        // A new notebook starts out with an empty code cell, like in Jupyter.
+        if self.cell_order.is_empty() {
+            if let Some(cell) = self.new_cell(CellType::Code, new_cell_id(), None, "", window, cx) {
+                let cell_id = cell.id(cx);
+                self.cell_order.push(cell_id.clone());
+                self.cell_map.insert(cell_id, cell);
+            }
+        }
+
+        self.selected_cell_index = 0;
+        self.cells_changed = false;
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.executions.clear();
+        self.cell_list.reset(self.cell_order.len());
+        self.sync_cell_subscriptions(window, cx);
+        cx.notify();
+    }
+
+    fn new_cell(
+        &self,
+        cell_type: CellType,
+        cell_id: CellId,
+        metadata: Option<serde_json::Value>,
+        source: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Option<Cell> {
+        let cell = new_nbformat_cell(cell_type, &cell_id, metadata, source).log_err()?;
+        Some(Cell::load(
+            &cell,
+            &self.languages,
+            self.notebook_language.clone(),
+            window,
+            cx,
+        ))
+    }
+
+    /// Subscribes to the cells that were added to the notebook and drops the
+    /// subscriptions of cells that are no longer part of it.
+    fn sync_cell_subscriptions(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let cells = self.cell_map.values().cloned().collect::<Vec<_>>();
+        self.cell_subscriptions
+            .retain(|entity_id, _| cells.iter().any(|cell| cell.entity_id() == *entity_id));
+
+        for cell in cells {
+            if self.cell_subscriptions.contains_key(&cell.entity_id()) {
+                continue;
+            }
+
+            let editor = cell.editor(cx);
+            let mut subscriptions = vec![cx.subscribe(&editor, |_, _, event, cx| {
+                if let editor::EditorEvent::BufferEdited = event {
+                    cx.emit(NotebookEditorEvent::Edited);
+                    cx.notify();
+                }
+            })];
+            if let Cell::Code(code_cell) = &cell {
+                let cell_id = cell.id(cx);
+                subscriptions.push(cx.subscribe_in(
+                    code_cell,
+                    window,
+                    move |this, _, event, window, cx| match event {
+                        CellEvent::Run => {
+                            if let Some(index) = this.index_of(&cell_id) {
+                                this.set_selected_index(index, false, window, cx);
+                                this.run_cell_at(index, window, cx);
+                            }
+                        }
+                    },
+                ));
+            }
+            self.cell_subscriptions
+                .insert(cell.entity_id(), subscriptions);
         }
     }
 
+    fn index_of(&self, cell_id: &CellId) -> Option<usize> {
+        self.cell_order.iter().position(|id| id == cell_id)
+    }
+
+    fn selected_cell(&self) -> Option<&Cell> {
+        self.cell_order
+            .get(self.selected_cell_index)
+            .and_then(|cell_id| self.cell_map.get(cell_id))
+    }
+
     fn has_outputs(&self, window: &mut Window, cx: &mut Context<Self>) -> bool {
         self.cell_map.values().any(|cell| {
             if let Cell::Code(code_cell) = cell {
@@ -154,39 +297,364 @@ impl NotebookEditor {
     fn clear_outputs(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         for cell in self.cell_map.values() {
             if let Cell::Code(code_cell) = cell {
-                code_cell.update(cx, |cell, _cx| {
+                code_cell.update(cx, |cell, cx| {
                     cell.clear_outputs();
+                    cx.notify();
                 });
             }
         }
+        self.mark_changed(cx);
     }
 
     fn run_cells(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        println!("Cells would all run here, if that was implemented!");
+        for index in 0..self.cell_order.len() {
+            self.run_cell_at(index, window, cx);
+        }
+    }
+
+    fn run_cell(&mut self, _: &RunCell, window: &mut Window, cx: &mut Context<Self>) {
+        let index = self.selected_cell_index;
+        self.run_cell_at(index, window, cx);
+        if index + 1 < self.cell_count() {
+            self.set_selected_index(index + 1, true, window, cx);
+            if let Some(cell) = self.selected_cell() {
+                cell.editor(cx).focus_handle(cx).focus(window);
+            }
+        }
+        cx.notify();
+    }
+
+    fn run_cell_at(&mut self, index: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(cell_id) = self.cell_order.get(index).cloned() else {
+            return;
+        };
+        let Some(cell) = self.cell_map.get(&cell_id).cloned() else {
+            return;
+        };
+
+        match cell {
+            Cell::Code(code_cell) => {
+                let code = code_cell.read(cx).source(cx);
+                if code.trim().is_empty() {
+                    return;
+                }
+                let Some(session) = self.session(window, cx) else {
+                    code_cell.update(cx, |cell, cx| {
+                        cell.set_message("No kernel is available for this notebook", cx)
+                    });
+                    return;
+                };
+                code_cell.update(cx, |cell, cx| cell.start_execution(cx));
+                let message_id =
+                    session.update(cx, |session, cx| session.execute_detached(code, cx));
+                self.executions.insert(message_id, cell_id);
+                self.mark_changed(cx);
+            }
+            Cell::Markdown(markdown_cell) => {
+                markdown_cell.update(cx, |cell, cx| cell.finish_editing(window, cx));
+            }
+            Cell::Raw(_) => {}
+        }
+    }
+
+    /// Returns the kernel session of this notebook, starting one if needed.
+    fn session(&mut self, window: &mut Window, cx: &mut Context<Self>) -> Option<Entity<Session>> {
+        if let Some(session) = &self.session {
+            if !matches!(session.read(cx).kernel, Kernel::Shutdown) {
+                return Some(session.clone());
+            }
+        }
+
+        let kernel_specification = self.kernel_specification(cx)?;
+        let fs = self.project.read(cx).fs().clone();
+        let working_directory = self
+            .notebook_item
+            .read(cx)
+            .path
+            .parent()
+            .map(|directory| directory.to_path_buf())
+            .unwrap_or_else(std::env::temp_dir);
+        let session =
+            cx.new(|cx| Session::detached(fs, kernel_specification, working_directory, window, cx));
+        self._session_subscription =
+            Some(cx.subscribe_in(&session, window, Self::on_session_event));
+        self.session = Some(session.clone());
+        Some(session)
+    }
+
+    /// Prefers the kernel named in the notebook metadata, falling back to the
+    /// kernel selected for the notebook's language.
+    fn kernel_specification(&self, cx: &App) -> Option<KernelSpecification> {
+        let store = ReplStore::global(cx);
+        let store = store.read(cx);
+        let notebook_item = self.notebook_item.read(cx);
+        let worktree_id = notebook_item.project_path.worktree_id;
+
+        notebook_item
+            .notebook
+            .metadata
+            .kernelspec
+            .as_ref()
+            .and_then(|kernelspec| {
+                store
+                    .kernel_specifications_for_worktree(worktree_id)
+                    .find(|specification| specification.name().as_ref() == kernelspec.name)
+                    .cloned()
+            })
+            .or_else(|| {
+                let language = self.notebook_language.clone().now_or_never().flatten();
+                store.active_kernelspec(worktree_id, language, cx)
+            })
+    }
+
+    fn on_session_event(
+        &mut self,
+        _: &Entity<Session>,
+        event: &SessionEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let SessionEvent::Message(message) = event else {
+            return;
+        };
+        let Some(parent_message_id) = message
+            .parent_header
+            .as_ref()
+            .map(|header| header.msg_id.clone())
+        else {
+            return;
+        };
+        let Some(cell_id) = self.executions.get(&parent_message_id).cloned() else {
+            return;
+        };
+
+        if let JupyterMessageContent::Status(status) = &message.content {
+            if matches!(status.execution_state, runtimelib::ExecutionState::Idle) {
+                self.executions.remove(&parent_message_id);
+            }
+        }
+
+        if let Some(Cell::Code(code_cell)) = self.cell_map.get(&cell_id) {
+            code_cell.update(cx, |cell, cx| {
+                cell.handle_message(&message.content, window, cx)
+            });
+            if !matches!(message.content, JupyterMessageContent::Status(_)) {
+                self.mark_changed(cx);
+            }
+        }
     }
 
     fn open_notebook(&mut self, _: &OpenNotebook, _window: &mut Window, _cx: &mut Context<Self>) {
         println!("Open notebook triggered");
     }
 
+    fn snapshot(&self) -> CellsSnapshot {
+        CellsSnapshot {
+            cell_order: self.cell_order.clone(),
+            cell_map: self.cell_map.clone(),
+            selected_cell_index: self.selected_cell_index,
+        }
+    }
+
+    /// Records the current cell layout before it is changed by a cell operation.
+    fn push_undo(&mut self) {
+        self.undo_stack.push(self.snapshot());
+        self.redo_stack.clear();
+    }
+
+    fn restore(&mut self, snapshot: CellsSnapshot, window: &mut Window, cx: &mut Context<Self>) {
+        self.cell_order = snapshot.cell_order;
+        self.cell_map = snapshot.cell_map;
+        self.cell_list.reset(self.cell_order.len());
+        self.sync_cell_subscriptions(window, cx);
+        let index = snapshot
+            .selected_cell_index
+            .min(self.cell_count().saturating_sub(1));
+        self.set_selected_index(index, true, window, cx);
+        self.mark_changed(cx);
+    }
+
+    fn undo(&mut self, _: &Undo, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(snapshot) = self.undo_stack.pop() {
+            self.redo_stack.push(self.snapshot());
+            self.restore(snapshot, window, cx);
+        }
+    }
+
+    fn redo(&mut self, _: &Redo, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(snapshot) = self.redo_stack.pop() {
+            self.undo_stack.push(self.snapshot());
+            self.restore(snapshot, window, cx);
+        }
+    }
+
+    fn mark_changed(&mut self, cx: &mut Context<Self>) {
+        self.cells_changed = true;
+        cx.emit(NotebookEditorEvent::Edited);
+        cx.notify();
+    }
+
     fn move_cell_up(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        println!("Move cell up triggered");
+        let index = self.selected_cell_index;
+        if index == 0 || index >= self.cell_count() {
+            return;
+        }
+        self.push_undo();
+        self.cell_order.swap(index - 1, index);
+        self.cell_list.splice(index - 1..index + 1, 2);
+        self.set_selected_index(index - 1, true, window, cx);
+        self.mark_changed(cx);
     }
 
     fn move_cell_down(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        println!("Move cell down triggered");
+        let index = self.selected_cell_index;
+        if index + 1 >= self.cell_count() {
+            return;
+        }
+        self.push_undo();
+        self.cell_order.swap(index, index + 1);
+        self.cell_list.splice(index..index + 2, 2);
+        self.set_selected_index(index + 1, true, window, cx);
+        self.mark_changed(cx);
     }
 
     fn add_markdown_block(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        println!("Add markdown block triggered");
+        self.add_cell(CellType::Markdown, window, cx);
     }
 
     fn add_code_block(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        println!("Add code block triggered");
+        self.add_cell(CellType::Code, window, cx);
+    }
+
+    /// Inserts an empty cell below the current one.
+    fn add_cell(&mut self, cell_type: CellType, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(cell) = self.new_cell(cell_type, new_cell_id(), None, "", window, cx) else {
+            return;
+        };
+        let index = if self.cell_order.is_empty() {
+            0
+        } else {
+            self.selected_cell_index + 1
+        };
+
+        self.push_undo();
+        let cell_id = cell.id(cx);
+        self.cell_order.insert(index, cell_id.clone());
+        self.cell_map.insert(cell_id, cell.clone());
+        self.cell_list.splice(index..index, 1);
+        self.sync_cell_subscriptions(window, cx);
+        self.set_selected_index(index, true, window, cx);
+        cell.editor(cx).focus_handle(cx).focus(window);
+        self.mark_changed(cx);
+    }
+
+    fn delete_cell(&mut self, _: &DeleteCell, window: &mut Window, cx: &mut Context<Self>) {
+        let index = self.selected_cell_index;
+        if index >= self.cell_count() {
+            return;
+        }
+
+        self.push_undo();
+        let cell_id = self.cell_order.remove(index);
+        self.cell_map.remove(&cell_id);
+        self.cell_list.splice(index..index + 1, 0);
+        self.sync_cell_subscriptions(window, cx);
+        let index = index.min(self.cell_count().saturating_sub(1));
+        self.set_selected_index(index, true, window, cx);
+        self.focus_handle.focus(window);
+        self.mark_changed(cx);
+    }
+
+    /// Changes the type of the current cell, keeping its ID, metadata and source.
+    fn change_cell_type(
+        &mut self,
+        cell_type: CellType,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let index = self.selected_cell_index;
+        let Some(cell) = self.selected_cell().cloned() else {
+            return;
+        };
+        let is_same_type = matches!(
+            (&cell, &cell_type),
+            (Cell::Code(_), CellType::Code)
+                | (Cell::Markdown(_), CellType::Markdown)
+                | (Cell::Raw(_), CellType::Raw)
+        );
+        if is_same_type {
+            return;
+        }
+
+        let cell_id = cell.id(cx);
+        let metadata = cell
+            .to_nbformat(cx)
+            .ok()
+            .and_then(|cell| serde_json::to_value(cell).ok())
+            .and_then(|mut cell| cell.get_mut("metadata").map(serde_json::Value::take));
+        let source = cell.source(cx);
+        let Some(new_cell) =
+            self.new_cell(cell_type, cell_id.clone(), metadata, &source, window, cx)
+        else {
+            return;
+        };
+
+        self.push_undo();
+        self.cell_map.insert(cell_id, new_cell);
+        self.cell_list.splice(index..index + 1, 1);
+        self.sync_cell_subscriptions(window, cx);
+        self.mark_changed(cx);
+    }
+
+    /// Builds the notebook that should be written to disk from the current cells.
+    fn to_nbformat(&self, cx: &App) -> Result<nbformat::v4::Notebook> {
+        let mut notebook = self.notebook_item.read(cx).notebook.clone();
+        notebook.cells = self
+            .cell_order
+            .iter()
+            .filter_map(|cell_id| self.cell_map.get(cell_id))
+            .map(|cell| cell.to_nbformat(cx))
+            .collect::<Result<Vec<_>>>()?;
+        notebook.nbformat = 4;
+        // Cell IDs were introduced in nbformat 4.5.
+        notebook.nbformat_minor = notebook.nbformat_minor.max(5);
+        Ok(notebook)
+    }
+
+    fn write_notebook(
+        &mut self,
+        abs_path: PathBuf,
+        fs: Arc<dyn Fs>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let notebook = match self.to_nbformat(cx) {
+            Ok(notebook) => notebook,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        let text = match serialize_notebook(&notebook) {
+            Ok(text) => text,
+            Err(error) => return Task::ready(Err(error)),
+        };
+
+        cx.spawn(async move |this, cx| {
+            fs.atomic_write(abs_path, text)
+                .await
+                .context("writing notebook")?;
+            this.update(cx, |this, cx| {
+                this.notebook_item
+                    .update(cx, |item, _| item.notebook = notebook);
+                for cell in this.cell_map.values() {
+                    cell.did_save(cx);
+                }
+                this.cells_changed = false;
+                cx.emit(NotebookEditorEvent::TabContentChanged);
+                cx.notify();
+            })
+        })
     }
 
     fn cell_count(&self) -> usize {
-        self.cell_map.len()
+        self.cell_order.len()
     }
 
     fn selected_index(&self) -> usize {
@@ -485,8 +953,16 @@ impl NotebookEditor {
 
 impl Render for NotebookEditor {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let mut key_context = KeyContext::new_with_defaults();
+        key_context.add("notebook");
+        key_context.add(if self.focus_handle.is_focused(window) {
+            "not_editing"
+        } else {
+            "editing"
+        });
+
         div()
-            .key_context("notebook")
+            .key_context(key_context)
             .track_focus(&self.focus_handle)
             .on_action(cx.listener(|this, &OpenNotebook, window, cx| {
                 this.open_notebook(&OpenNotebook, window, cx)
@@ -505,6 +981,22 @@ impl Render for NotebookEditor {
             .on_action(
                 cx.listener(|this, &AddCodeBlock, window, cx| this.add_code_block(window, cx)),
             )
+            .on_action(cx.listener(Self::run_cell))
+            .on_action(cx.listener(Self::delete_cell))
+            .on_action(cx.listener(|this, &ChangeToCodeCell, window, cx| {
+                this.change_cell_type(CellType::Code, window, cx)
+            }))
+            .on_action(cx.listener(|this, &ChangeToMarkdownCell, window, cx| {
+                this.change_cell_type(CellType::Markdown, window, cx)
+            }))
+            .on_action(cx.listener(|this, &ChangeToRawCell, window, cx| {
+                this.change_cell_type(CellType::Raw, window, cx)
+            }))
+            .on_action(cx.listener(Self::undo))
+            .on_action(cx.listener(Self::redo))
+            .on_action(
+                cx.listener(|this, &StopEditingCell, window, _| this.focus_handle.focus(window)),
+            )
             .on_action(cx.listener(Self::select_next))
             .on_action(cx.listener(Self::select_previous))
             .on_action(cx.listener(Self::select_first))
@@ -529,7 +1021,15 @@ impl Render for NotebookEditor {
                                 .get(ix)
                                 .and_then(|cell_id| this.cell_map.get(cell_id))
                                 .map(|cell| {
-                                    this.render_cell(ix, cell, window, cx).into_any_element()
+                                    div()
+                                        .capture_any_mouse_down(cx.listener(
+                                            move |this, _, window, cx| {
+                                                this.set_selected_index(ix, false, window, cx);
+                                                cx.notify();
+                                            },
+                                        ))
+                                        .child(this.render_cell(ix, cell, window, cx))
+                                        .into_any_element()
                                 })
                                 .unwrap_or_else(|| div().into_any())
                         }),
@@ -575,23 +1075,7 @@ impl project::ProjectItem for NotebookItem {
                     .with_context(|| format!("finding the absolute path of {path:?}"))?;
 
                 // todo: watch for changes to the file
-                let file_content = fs.load(&abs_path.as_path()).await?;
-                let notebook = nbformat::parse_notebook(&file_content);
-
-                let notebook = match notebook {
-                    Ok(nbformat::Notebook::V4(notebook)) => notebook,
-                    // 4.1 - 4.4 are converted to 4.5
-                    Ok(nbformat::Notebook::Legacy(legacy_notebook)) => {
-                        // TODO: Decide if we want to mutate the notebook by including Cell IDs
-                        // and any other conversions
-                        let notebook = nbformat::upgrade_legacy_notebook(legacy_notebook)?;
-                        notebook
-                    }
-                    // Bad notebooks and notebooks v4.0 and below are not supported
-                    Err(e) => {
-                        anyhow::bail!("Failed to parse notebook: {:?}", e);
-                    }
-                };
+                let notebook = NotebookItem::load_notebook(fs.as_ref(), &abs_path).await?;
 
                 let id = project
                     .update(cx, |project, cx| project.entry_for_path(&path, cx))?
@@ -624,7 +1108,109 @@ impl project::ProjectItem for NotebookItem {
     }
 }
 
+/// Parses the contents of an `.ipynb` file. Empty files are treated as a new,
+/// empty notebook.
+fn parse_notebook(file_content: &str) -> Result<nbformat::v4::Notebook> {
+    let file_content = if file_content.trim().is_empty() {
+        EMPTY_NOTEBOOK
+    } else {
+        file_content
+    };
+
+    let mut notebook = match nbformat::parse_notebook(file_content) {
+        Ok(nbformat::Notebook::V4(notebook)) => notebook,
+        // 4.1 - 4.4 are converted to 4.5, which assigns IDs to all cells
+        Ok(nbformat::Notebook::Legacy(legacy_notebook)) => {
+            nbformat::upgrade_legacy_notebook(legacy_notebook)?
+        }
+        // Bad notebooks and notebooks v4.0 and below are not supported
+        Err(e) => {
+            anyhow::bail!("Failed to parse notebook: {:?}", e);
+        }
+    };
+    ensure_unique_cell_ids(&mut notebook);
+    Ok(notebook)
+}
+
+/// Cells are tracked by ID, so notebooks whose cells share an ID (e.g. after
+/// copying cells between files by hand) get fresh IDs for the duplicates.
+fn ensure_unique_cell_ids(notebook: &mut nbformat::v4::Notebook) {
+    let mut seen = collections::HashSet::default();
+    for cell in &mut notebook.cells {
+        let (nbformat::v4::Cell::Markdown { id, .. }
+        | nbformat::v4::Cell::Code { id, .. }
+        | nbformat::v4::Cell::Raw { id, .. }) = cell;
+        if !seen.insert(id.clone()) {
+            *id = new_cell_id();
+            seen.insert(id.clone());
+        }
+    }
+}
+
+fn new_cell_id() -> CellId {
+    CellId::from(uuid::Uuid::new_v4())
+}
+
+fn new_nbformat_cell(
+    cell_type: CellType,
+    cell_id: &CellId,
+    metadata: Option<serde_json::Value>,
+    source: &str,
+) -> Result<nbformat::v4::Cell> {
+    let mut cell = serde_json::json!({
+        "id": cell_id,
+        "metadata": metadata.unwrap_or_else(|| serde_json::json!({})),
+        "source": source_lines(source),
+    });
+    match cell_type {
+        CellType::Code => {
+            cell["cell_type"] = "code".into();
+            cell["execution_count"] = serde_json::Value::Null;
+            cell["outputs"] = serde_json::json!([]);
+        }
+        CellType::Markdown => cell["cell_type"] = "markdown".into(),
+        CellType::Raw => cell["cell_type"] = "raw".into(),
+    }
+    Ok(serde_json::from_value(cell)?)
+}
+
+/// Serializes a notebook the way Jupyter writes it: sorted keys, one space of
+/// indentation and a trailing newline. This keeps diffs of saved notebooks small.
+fn serialize_notebook(notebook: &nbformat::v4::Notebook) -> Result<String> {
+    let mut value = serde_json::to_value(notebook)?;
+    sort_keys(&mut value);
+
+    let mut buffer = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b" ");
+    let mut serializer = serde_json::Serializer::with_formatter(&mut buffer, formatter);
+    value.serialize(&mut serializer)?;
+
+    let mut text = String::from_utf8(buffer)?;
+    text.push('\n');
+    Ok(text)
+}
+
+fn sort_keys(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(object) => {
+            let mut entries = std::mem::take(object).into_iter().collect::<Vec<_>>();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            for (key, mut value) in entries {
+                sort_keys(&mut value);
+                object.insert(key, value);
+            }
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(sort_keys),
+        _ => {}
+    }
+}
+
 impl NotebookItem {
+    async fn load_notebook(fs: &dyn Fs, abs_path: &Path) -> Result<nbformat::v4::Notebook> {
+        let file_content = fs.load(abs_path).await?;
+        parse_notebook(&file_content)
+    }
+
     pub fn language_name(&self) -> Option<String> {
         self.notebook
             .metadata
@@ -653,7 +1239,14 @@ impl NotebookItem {
     }
 }
 
-impl EventEmitter<()> for NotebookEditor {}
+pub enum NotebookEditorEvent {
+    /// Cells or their sources changed.
+    Edited,
+    /// The notebook was saved or reloaded, which changes its title or dirty state.
+    TabContentChanged,
+}
+
+impl EventEmitter<NotebookEditorEvent> for NotebookEditor {}
 
 // pub struct NotebookControls {
 //     pane_focused: bool,
@@ -701,7 +1294,14 @@ impl EventEmitter<()> for NotebookEditor {}
 // }
 
 impl Item for NotebookEditor {
-    type Event = ();
+    type Event = NotebookEditorEvent;
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(ItemEvent::UpdateTab);
+        if let NotebookEditorEvent::Edited = event {
+            f(ItemEvent::Edit);
+        }
+    }
 
     fn clone_on_split(
         &self,
@@ -772,49 +1372,73 @@ impl Item for NotebookEditor {
         // TODO
     }
 
-    // TODO
     fn can_save(&self, _cx: &App) -> bool {
-        false
+        true
     }
-    // TODO
+
+    fn can_save_as(&self, _cx: &App) -> bool {
+        true
+    }
+
     fn save(
         &mut self,
         _options: SaveOptions,
-        _project: Entity<Project>,
+        project: Entity<Project>,
         _window: &mut Window,
-        _cx: &mut Context<Self>,
+        cx: &mut Context<Self>,
     ) -> Task<Result<()>> {
-        unimplemented!("save() must be implemented if can_save() returns true")
+        let abs_path = self.notebook_item.read(cx).path.clone();
+        let fs = project.read(cx).fs().clone();
+        self.write_notebook(abs_path, fs, cx)
     }
 
-    // TODO
     fn save_as(
         &mut self,
-        _project: Entity<Project>,
-        _path: ProjectPath,
+        project: Entity<Project>,
+        path: ProjectPath,
         _window: &mut Window,
-        _cx: &mut Context<Self>,
+        cx: &mut Context<Self>,
     ) -> Task<Result<()>> {
-        unimplemented!("save_as() must be implemented if can_save() returns true")
+        let Some(abs_path) = project.read(cx).absolute_path(&path, cx) else {
+            return Task::ready(Err(anyhow::anyhow!("no absolute path for {path:?}")));
+        };
+        let fs = project.read(cx).fs().clone();
+        let write = self.write_notebook(abs_path.clone(), fs, cx);
+        let notebook_item = self.notebook_item.clone();
+        cx.spawn(async move |this, cx| {
+            write.await?;
+            notebook_item.update(cx, |item, cx| {
+                if let Some(entry) = project.read(cx).entry_for_path(&path, cx) {
+                    item.id = entry.id;
+                }
+                item.path = abs_path;
+                item.project_path = path;
+            })?;
+            this.update(cx, |_, cx| cx.emit(NotebookEditorEvent::TabContentChanged))
+        })
     }
-    // TODO
+
     fn reload(
         &mut self,
-        _project: Entity<Project>,
-        _window: &mut Window,
-        _cx: &mut Context<Self>,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
     ) -> Task<Result<()>> {
-        unimplemented!("reload() must be implemented if can_save() returns true")
+        let abs_path = self.notebook_item.read(cx).path.clone();
+        let fs = project.read(cx).fs().clone();
+        cx.spawn_in(window, async move |this, cx| {
+            let notebook = NotebookItem::load_notebook(fs.as_ref(), &abs_path).await?;
+            this.update_in(cx, |this, window, cx| {
+                this.notebook_item
+                    .update(cx, |item, _| item.notebook = notebook);
+                this.load_cells(window, cx);
+                cx.emit(NotebookEditorEvent::TabContentChanged);
+            })
+        })
     }
 
     fn is_dirty(&self, cx: &App) -> bool {
-        self.cell_map.values().any(|cell| {
-            if let Cell::Code(code_cell) = cell {
-                code_cell.read(cx).is_dirty(cx)
-            } else {
-                false
-            }
-        })
+        self.cells_changed || self.cell_map.values().any(|cell| cell.is_dirty(cx))
     }
 }
 
@@ -837,3 +1461,91 @@ impl ProjectItem for NotebookEditor {
         Self::new(project, item, window, cx)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTEBOOK: &str = r##"{
+ "cells": [
+  {
+   "cell_type": "markdown",
+   "id": "intro",
+   "metadata": {},
+   "source": ["# Title\n", "Some text"]
+  },
+  {
+   "cell_type": "code",
+   "execution_count": 1,
+   "id": "intro",
+   "metadata": {"tags": ["setup"]},
+   "outputs": [{"name": "stdout", "output_type": "stream", "text": ["hello\n"]}],
+   "source": ["print('hello')"]
+  }
+ ],
+ "metadata": {"kernelspec": {"display_name": "Python 3", "language": "python", "name": "python3"}},
+ "nbformat": 4,
+ "nbformat_minor": 5
+}"##;
+
+    fn cell_ids(notebook: &nbformat::v4::Notebook) -> Vec<String> {
+        notebook
+            .cells
+            .iter()
+            .map(|cell| {
+                let (nbformat::v4::Cell::Markdown { id, .. }
+                | nbformat::v4::Cell::Code { id, .. }
+                | nbformat::v4::Cell::Raw { id, .. }) = cell;
+                id.to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_notebook_round_trip() {
+        let notebook = parse_notebook(NOTEBOOK).unwrap();
+        let ids = cell_ids(&notebook);
+        assert_eq!(ids.len(), 2);
+        assert_eq!(ids[0], "intro");
+        assert_ne!(ids[1], "intro", "duplicate cell IDs are replaced");
+
+        let text = serialize_notebook(&notebook).unwrap();
+        assert!(text.starts_with("{\n \"cells\": [\n  {\n   \"cell_type\": \"markdown\""));
+        assert!(text.ends_with("}\n"));
+
+        let reparsed = parse_notebook(&text).unwrap();
+        assert_eq!(cell_ids(&reparsed), ids, "cell IDs are stable across saves");
+        assert_eq!(serialize_notebook(&reparsed).unwrap(), text);
+    }
+
+    #[test]
+    fn test_new_notebook_and_cells() {
+        let notebook = parse_notebook("").unwrap();
+        assert!(notebook.cells.is_empty());
+
+        let id = new_cell_id();
+        let cell = new_nbformat_cell(CellType::Code, &id, None, "a = 1\nb = 2\n").unwrap();
+        match &cell {
+            nbformat::v4::Cell::Code {
+                id: cell_id,
+                execution_count,
+                source,
+                outputs,
+                ..
+            } => {
+                assert_eq!(cell_id, &id);
+                assert_eq!(*execution_count, None);
+                assert_eq!(source, &vec!["a = 1\n".to_string(), "b = 2\n".to_string()]);
+                assert!(outputs.is_empty());
+            }
+            _ => panic!("expected a code cell"),
+        }
+
+        let metadata = serde_json::json!({"tags": ["keep"]});
+        let cell = new_nbformat_cell(CellType::Markdown, &id, Some(metadata), "# Hi").unwrap();
+        let value = serde_json::to_value(&cell).unwrap();
+        assert_eq!(value["cell_type"], "markdown");
+        assert_eq!(value["metadata"]["tags"][0], "keep");
+        assert_eq!(value["source"], serde_json::json!(["# Hi"]));
+    }
+}
@@ -16,7 +16,7 @@ use project::Fs;
 pub use runtimelib::ExecutionState;
 use settings::Settings as _;
 
-pub use crate::jupyter_settings::JupyterSettings;
+pub use crate::jupyter_settings::{JupyterServerAuth, JupyterServerSettings, JupyterSettings};
 pub use crate::kernels::{Kernel, KernelSpecification, KernelStatus};
 pub use crate::repl_editor::*;
 pub use crate::repl_sessions_ui::{
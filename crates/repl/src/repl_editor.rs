@@ -59,6 +59,7 @@ pub fn assign_kernelspec(
                             store.remove_session(shutdown_event.entity_id());
                         });
                     }
+                    SessionEvent::Message(_) => {}
                 }
             })
             .detach();
@@ -130,6 +131,7 @@ pub fn run(
                                 store.remove_session(shutdown_event.entity_id());
                             });
                         }
+                        SessionEvent::Message(_) => {}
                     }
                 })
                 .detach();
@@ -4,15 +4,18 @@ use anyhow::{Context as _, Result};
 use collections::HashMap;
 use command_palette_hooks::CommandPaletteFilter;
 use gpui::{App, Context, Entity, EntityId, Global, Subscription, Task, prelude::*};
-use jupyter_websocket_client::RemoteServer;
 use language::Language;
 use project::{Fs, Project, WorktreeId};
 use settings::{Settings, SettingsStore};
+use util::ResultExt as _;
 
 use crate::kernels::{
-    list_remote_kernelspecs, local_kernel_specifications, python_env_kernel_specifications,
+    JupyterServerConnection, list_remote_kernelspecs, list_running_remote_kernels,
+    local_kernel_specifications, python_env_kernel_specifications,
+};
+use crate::{
+    JupyterServerAuth, JupyterServerSettings, JupyterSettings, KernelSpecification, Session,
 };
-use crate::{JupyterSettings, KernelSpecification, Session};
 
 struct GlobalReplStore(Entity<ReplStore>);
 
@@ -25,6 +28,7 @@ pub struct ReplStore {
     kernel_specifications: Vec<KernelSpecification>,
     selected_kernel_for_worktree: HashMap<WorktreeId, KernelSpecification>,
     kernel_specifications_for_worktree: HashMap<WorktreeId, Vec<KernelSpecification>>,
+    jupyter_servers: Vec<JupyterServerSettings>,
     _subscriptions: Vec<Subscription>,
 }
 
@@ -48,6 +52,12 @@ impl ReplStore {
     pub fn new(fs: Arc<dyn Fs>, cx: &mut Context<Self>) -> Self {
         let subscriptions = vec![cx.observe_global::<SettingsStore>(move |this, cx| {
             this.set_enabled(JupyterSettings::enabled(cx), cx);
+
+            let jupyter_servers = &JupyterSettings::get_global(cx).servers;
+            if &this.jupyter_servers != jupyter_servers {
+                this.jupyter_servers = jupyter_servers.clone();
+                this.refresh_kernelspecs(cx).detach_and_log_err(cx);
+            }
         })];
 
         let this = Self {
@@ -58,6 +68,7 @@ impl ReplStore {
             _subscriptions: subscriptions,
             kernel_specifications_for_worktree: HashMap::default(),
             selected_kernel_for_worktree: HashMap::default(),
+            jupyter_servers: JupyterSettings::get_global(cx).servers.clone(),
         };
         this.on_enabled_changed(cx);
         this
@@ -139,24 +150,57 @@ impl ReplStore {
         &self,
         cx: &mut Context<Self>,
     ) -> Option<Task<Result<Vec<KernelSpecification>>>> {
-        match (
+        let mut servers = self.jupyter_servers.clone();
+        if let (Ok(server), Ok(token)) = (
             std::env::var("JUPYTER_SERVER"),
             std::env::var("JUPYTER_TOKEN"),
         ) {
-            (Ok(server), Ok(token)) => {
-                let remote_server = RemoteServer {
-                    base_url: server,
-                    token,
-                };
-                let http_client = cx.http_client();
-                Some(cx.spawn(async move |_, _| {
-                    list_remote_kernelspecs(remote_server, http_client)
-                        .await
-                        .map(|specs| specs.into_iter().map(KernelSpecification::Remote).collect())
-                }))
-            }
-            _ => None,
+            servers.push(JupyterServerSettings {
+                name: server.clone(),
+                url: server,
+                auth: JupyterServerAuth::Token {
+                    token: Some(token),
+                    token_env: None,
+                },
+            });
+        }
+
+        if servers.is_empty() {
+            return None;
         }
+
+        let http_client = cx.http_client();
+        Some(cx.background_spawn(async move {
+            let server_specs = servers.into_iter().map(|server| {
+                let http_client = http_client.clone();
+                async move {
+                    let connection = JupyterServerConnection::connect(
+                        &server.url,
+                        &server.auth,
+                        http_client.clone(),
+                    )
+                    .await
+                    .with_context(|| format!("connecting to Jupyter server {}", server.name))?;
+                    let mut specs = list_remote_kernelspecs(&connection, http_client.clone())
+                        .await
+                        .with_context(|| format!("listing kernels on {}", server.name))?;
+                    let running = list_running_remote_kernels(&connection, http_client, &specs)
+                        .await
+                        .log_err()
+                        .unwrap_or_default();
+                    specs.extend(running);
+                    anyhow::Ok(specs)
+                }
+            });
+
+            Ok(futures::future::join_all(server_specs)
+                .await
+                .into_iter()
+                .filter_map(|specs| specs.log_err())
+                .flatten()
+                .map(KernelSpecification::Remote)
+                .collect())
+        }))
     }
 
     pub fn refresh_kernelspecs(&mut self, cx: &mut Context<Self>) -> Task<Result<()>> {
@@ -27,16 +27,20 @@ use runtimelib::{
     ExecuteRequest, ExecutionState, InterruptRequest, JupyterMessage, JupyterMessageContent,
     ShutdownRequest,
 };
-use std::{env::temp_dir, ops::Range, sync::Arc, time::Duration};
+use std::{env::temp_dir, ops::Range, path::PathBuf, sync::Arc, time::Duration};
 use theme::ActiveTheme;
 use ui::{IconButtonShape, Tooltip, prelude::*};
 use util::ResultExt as _;
 
 pub struct Session {
     fs: Arc<dyn Fs>,
-    editor: WeakEntity<Editor>,
+    /// The editor whose code is executed, or `None` for notebook sessions.
+    editor: Option<WeakEntity<Editor>>,
+    working_directory: Option<PathBuf>,
     pub kernel: Kernel,
     blocks: HashMap<String, EditorBlock>,
+    /// Executions started with [`Session::execute_detached`], keyed by message ID.
+    detached_executions: HashSet<String>,
     pub kernel_specification: KernelSpecification,
     _buffer_subscription: Subscription,
 }
@@ -217,9 +221,11 @@ impl Session {
 
         let mut session = Self {
             fs,
-            editor,
+            editor: Some(editor),
+            working_directory: None,
             kernel: Kernel::StartingKernel(Task::ready(()).shared()),
             blocks: HashMap::default(),
+            detached_executions: HashSet::default(),
             kernel_specification,
             _buffer_subscription: subscription,
         };
@@ -228,13 +234,44 @@ impl Session {
         session
     }
 
+    /// Creates a session that isn't attached to an editor. Code is run with
+    /// [`Session::execute_detached`] and its output is reported through
+    /// [`SessionEvent::Message`].
+    pub fn detached(
+        fs: Arc<dyn Fs>,
+        kernel_specification: KernelSpecification,
+        working_directory: PathBuf,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let mut session = Self {
+            fs,
+            editor: None,
+            working_directory: Some(working_directory),
+            kernel: Kernel::StartingKernel(Task::ready(()).shared()),
+            blocks: HashMap::default(),
+            detached_executions: HashSet::default(),
+            kernel_specification,
+            _buffer_subscription: Subscription::new(|| {}),
+        };
+
+        session.start_kernel(window, cx);
+        session
+    }
+
     fn start_kernel(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         let kernel_language = self.kernel_specification.language();
-        let entity_id = self.editor.entity_id();
-        let working_directory = self
+        let entity_id = self
             .editor
-            .upgrade()
-            .and_then(|editor| editor.read(cx).working_directory(cx))
+            .as_ref()
+            .map_or(cx.entity_id(), |editor| editor.entity_id());
+        let working_directory = self
+            .working_directory
+            .clone()
+            .or_else(|| {
+                let editor = self.editor.as_ref()?.upgrade()?;
+                editor.read(cx).working_directory(cx)
+            })
             .unwrap_or_else(temp_dir);
 
         telemetry::event!(
@@ -332,16 +369,22 @@ impl Session {
             });
 
             if !blocks_to_remove.is_empty() {
-                self.editor
-                    .update(cx, |editor, cx| {
-                        editor.remove_blocks(blocks_to_remove, None, cx);
-                    })
-                    .ok();
+                self.remove_blocks(blocks_to_remove, cx);
                 cx.notify();
             }
         }
     }
 
+    fn remove_blocks(&self, block_ids: HashSet<CustomBlockId>, cx: &mut Context<Self>) {
+        if let Some(editor) = self.editor.as_ref() {
+            editor
+                .update(cx, |editor, cx| {
+                    editor.remove_blocks(block_ids, None, cx);
+                })
+                .ok();
+        }
+    }
+
     fn send(&mut self, message: JupyterMessage, _cx: &mut Context<Self>) -> anyhow::Result<()> {
         if let Kernel::RunningKernel(kernel) = &mut self.kernel {
             kernel.request_tx().try_send(message).ok();
@@ -354,11 +397,7 @@ impl Session {
         let blocks_to_remove: HashSet<CustomBlockId> =
             self.blocks.values().map(|block| block.block_id).collect();
 
-        self.editor
-            .update(cx, |editor, cx| {
-                editor.remove_blocks(blocks_to_remove, None, cx);
-            })
-            .ok();
+        self.remove_blocks(blocks_to_remove, cx);
 
         self.blocks.clear();
     }
@@ -372,7 +411,7 @@ impl Session {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let Some(editor) = self.editor.upgrade() else {
+        let Some(editor) = self.editor.as_ref().and_then(|editor| editor.upgrade()) else {
             return;
         };
 
@@ -400,11 +439,7 @@ impl Session {
             }
         });
 
-        self.editor
-            .update(cx, |editor, cx| {
-                editor.remove_blocks(blocks_to_remove, None, cx);
-            })
-            .ok();
+        self.remove_blocks(blocks_to_remove, cx);
 
         let status = match &self.kernel {
             Kernel::Restarting => ExecutionStatus::Restarting,
@@ -417,7 +452,7 @@ impl Session {
 
         let parent_message_id = message.header.msg_id.clone();
         let session_view = cx.entity().downgrade();
-        let weak_editor = self.editor.clone();
+        let weak_editor = editor.downgrade();
 
         let on_close: CloseBlockFn = Arc::new(
             move |block_id: CustomBlockId, _: &mut Window, cx: &mut App| {
@@ -439,7 +474,7 @@ impl Session {
         );
 
         let Ok(editor_block) =
-            EditorBlock::new(self.editor.clone(), anchor_range, status, on_close, cx)
+            EditorBlock::new(editor.downgrade(), anchor_range, status, on_close, cx)
         else {
             return;
         };
@@ -453,6 +488,38 @@ impl Session {
         self.blocks
             .insert(message.header.msg_id.clone(), editor_block);
 
+        self.send_when_started(message, cx);
+
+        if move_down {
+            editor.update(cx, move |editor, cx| {
+                editor.change_selections(
+                    SelectionEffects::scroll(Autoscroll::top_relative(8)),
+                    window,
+                    cx,
+                    |selections| {
+                        selections.select_ranges([new_cursor_pos..new_cursor_pos]);
+                    },
+                );
+            });
+        }
+    }
+
+    /// Executes code without rendering its output into an editor, returning
+    /// the ID of the execute request. Messages sent by the kernel in reply are
+    /// emitted as [`SessionEvent::Message`] until the execution finishes.
+    pub fn execute_detached(&mut self, code: String, cx: &mut Context<Self>) -> String {
+        let message: JupyterMessage = ExecuteRequest {
+            code,
+            ..ExecuteRequest::default()
+        }
+        .into();
+        let message_id = message.header.msg_id.clone();
+        self.detached_executions.insert(message_id.clone());
+        self.send_when_started(message, cx);
+        message_id
+    }
+
+    fn send_when_started(&mut self, message: JupyterMessage, cx: &mut Context<Self>) {
         match &self.kernel {
             Kernel::RunningKernel(_) => {
                 self.send(message, cx).ok();
@@ -460,7 +527,6 @@ impl Session {
             Kernel::StartingKernel(task) => {
                 // Queue up the execution as a task to run after the kernel starts
                 let task = task.clone();
-                let message = message.clone();
 
                 cx.spawn(async move |this, cx| {
                     task.await;
@@ -473,19 +539,6 @@ impl Session {
             }
             _ => {}
         }
-
-        if move_down {
-            editor.update(cx, move |editor, cx| {
-                editor.change_selections(
-                    SelectionEffects::scroll(Autoscroll::top_relative(8)),
-                    window,
-                    cx,
-                    |selections| {
-                        selections.select_ranges([new_cursor_pos..new_cursor_pos]);
-                    },
-                );
-            });
-        }
     }
 
     pub fn route(&mut self, message: &JupyterMessage, window: &mut Window, cx: &mut Context<Self>) {
@@ -528,6 +581,16 @@ impl Session {
             _ => {}
         }
 
+        if self.detached_executions.contains(parent_message_id) {
+            if let JupyterMessageContent::Status(status) = &message.content {
+                if matches!(status.execution_state, ExecutionState::Idle) {
+                    self.detached_executions.remove(parent_message_id);
+                }
+            }
+            cx.emit(SessionEvent::Message(message.clone()));
+            return;
+        }
+
         if let Some(block) = self.blocks.get_mut(parent_message_id) {
             block.handle_message(message, window, cx);
         }
@@ -535,9 +598,12 @@ impl Session {
 
     pub fn interrupt(&mut self, cx: &mut Context<Self>) {
         match &mut self.kernel {
-            Kernel::RunningKernel(_kernel) => {
-                self.send(InterruptRequest {}.into(), cx).ok();
-            }
+            Kernel::RunningKernel(kernel) => match kernel.interrupt(cx) {
+                Some(interrupt) => interrupt.detach_and_log_err(cx),
+                None => {
+                    self.send(InterruptRequest {}.into(), cx).ok();
+                }
+            },
             Kernel::StartingKernel(_task) => {
                 // NOTE: If we switch to a literal queue instead of chaining on to the task, clear all queued executions
             }
@@ -547,7 +613,9 @@ impl Session {
 
     pub fn kernel(&mut self, kernel: Kernel, cx: &mut Context<Self>) {
         if let Kernel::Shutdown = kernel {
-            cx.emit(SessionEvent::Shutdown(self.editor.clone()));
+            if let Some(editor) = self.editor.clone() {
+                cx.emit(SessionEvent::Shutdown(editor));
+            }
         }
 
         let kernel_status = KernelStatus::from(&kernel).to_string();
@@ -605,6 +673,23 @@ impl Session {
                 // Do nothing if already restarting
             }
             Kernel::RunningKernel(mut kernel) => {
+                if let Some(restart) = kernel.restart(cx) {
+                    // The kernel restarts in place, so keep talking to it over the same connection.
+                    self.kernel = Kernel::RunningKernel(kernel);
+                    self.clear_outputs(cx);
+                    cx.spawn(async move |this, cx| {
+                        if let Err(error) = restart.await {
+                            this.update(cx, |session, cx| {
+                                session.kernel_errored(error.to_string(), cx);
+                            })
+                            .ok();
+                        }
+                    })
+                    .detach();
+                    cx.notify();
+                    return;
+                }
+
                 let mut request_tx = kernel.request_tx().clone();
 
                 let forced = kernel.force_shutdown(window, cx);
@@ -642,6 +727,8 @@ impl Session {
 
 pub enum SessionEvent {
     Shutdown(WeakEntity<Editor>),
+    /// A message sent by the kernel in reply to [`Session::execute_detached`].
+    Message(JupyterMessage),
 }
 
 impl EventEmitter<SessionEvent> for Session {}
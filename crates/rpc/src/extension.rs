@@ -61,3 +61,20 @@ pub struct ExtensionMetadata {
 pub struct GetExtensionsResponse {
     pub data: Vec<ExtensionMetadata>,
 }
+
+/// The index of a static extension registry, stored as `index.json` at the root of the registry.
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct ExtensionRegistryIndex {
+    pub extensions: Vec<ExtensionRegistryEntry>,
+}
+
+/// A published version of an extension in a static extension registry.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct ExtensionRegistryEntry {
+    #[serde(flatten)]
+    pub metadata: ExtensionMetadata,
+    /// The path of the extension's `.tar.gz` archive, relative to the root of the registry.
+    pub archive: String,
+    /// The hex-encoded SHA-256 checksum of the archive.
+    pub sha256: String,
+}
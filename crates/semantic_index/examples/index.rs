@@ -4,7 +4,9 @@ use gpui::Application;
 use http_client::HttpClientWithUrl;
 use language::language_settings::AllLanguageSettings;
 use project::Project;
-use semantic_index::{OpenAiEmbeddingModel, OpenAiEmbeddingProvider, SemanticDb};
+use semantic_index::{
+    EmbeddingProvider, OpenAiEmbeddingModel, OpenAiEmbeddingProvider, SemanticDb,
+};
 use settings::SettingsStore;
 use std::{
     path::{Path, PathBuf},
@@ -46,14 +48,15 @@ fn main() {
 
         // let embedding_provider = semantic_index::FakeEmbeddingProvider;
 
-        let api_key = std::env::var("OPENAI_API_KEY").expect("OPENAI_API_KEY not set");
-
-        let embedding_provider = Arc::new(OpenAiEmbeddingProvider::new(
-            http.clone(),
-            OpenAiEmbeddingModel::TextEmbedding3Small,
-            open_ai::OPEN_AI_API_URL.to_string(),
-            api_key,
-        ));
+        let embedding_provider = local_embedding_provider().unwrap_or_else(|| {
+            let api_key = std::env::var("OPENAI_API_KEY").expect("OPENAI_API_KEY not set");
+            Arc::new(OpenAiEmbeddingProvider::new(
+                http.clone(),
+                OpenAiEmbeddingModel::TextEmbedding3Small,
+                open_ai::OPEN_AI_API_URL.to_string(),
+                api_key,
+            ))
+        });
 
         cx.spawn(async move |cx| {
             let semantic_index = SemanticDb::new(
@@ -138,3 +141,23 @@ fn main() {
         .detach();
     });
 }
+
+/// With `--features local-embeddings`, set `LOCAL_EMBEDDING_MODEL` to the path of an ONNX model
+/// (and optionally `LOCAL_EMBEDDING_THREADS`) to index without network access. ONNX Runtime is
+/// loaded from `ORT_DYLIB_PATH`, or else from the library search path.
+#[cfg(feature = "local-embeddings")]
+fn local_embedding_provider() -> Option<Arc<dyn EmbeddingProvider>> {
+    let model_path = std::env::var_os("LOCAL_EMBEDDING_MODEL")?;
+    let mut options = semantic_index::LocalEmbeddingOptions::new(model_path);
+    options.threads = std::env::var("LOCAL_EMBEDDING_THREADS")
+        .ok()
+        .and_then(|threads| threads.parse().ok());
+    Some(Arc::new(
+        semantic_index::LocalEmbeddingProvider::new(options).unwrap(),
+    ))
+}
+
+#[cfg(not(feature = "local-embeddings"))]
+fn local_embedding_provider() -> Option<Arc<dyn EmbeddingProvider>> {
+    None
+}
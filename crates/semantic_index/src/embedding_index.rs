@@ -1,10 +1,10 @@
 use crate::{
     chunking::{self, Chunk},
-    embedding::{Embedding, EmbeddingProvider, TextToEmbed},
     indexing::{IndexingEntryHandle, IndexingEntrySet},
 };
 use anyhow::{Context as _, Result};
 use collections::Bound;
+use embedding::{Embedding, EmbeddingProvider, TextToEmbed};
 use feature_flags::FeatureFlagAppExt;
 use fs::Fs;
 use fs::MTime;
@@ -1,10 +1,10 @@
 use crate::{
-    embedding::{EmbeddingProvider, TextToEmbed},
     summary_index::FileSummary,
     worktree_index::{WorktreeIndex, WorktreeIndexHandle},
 };
 use anyhow::{Context as _, Result, anyhow};
 use collections::HashMap;
+use embedding::{EmbeddingProvider, TextToEmbed};
 use fs::Fs;
 use futures::FutureExt;
 use gpui::{
@@ -1,5 +1,4 @@
 mod chunking;
-mod embedding;
 mod embedding_index;
 mod indexing;
 mod project_index;
@@ -27,6 +26,10 @@ pub use project_index::{LoadedSearchResult, ProjectIndex, SearchResult, Status};
 pub use project_index_debug_view::ProjectIndexDebugView;
 pub use summary_index::FileSummary;
 
+pub fn init(cx: &mut App) {
+    embedding::init(cx);
+}
+
 pub struct SemanticDb {
     embedding_provider: Arc<dyn EmbeddingProvider>,
     db_connection: Option<heed::Env>,
@@ -1,8 +1,8 @@
-use crate::embedding::EmbeddingProvider;
 use crate::embedding_index::EmbeddingIndex;
 use crate::indexing::IndexingEntrySet;
 use crate::summary_index::SummaryIndex;
 use anyhow::Result;
+use embedding::EmbeddingProvider;
 use fs::Fs;
 use futures::future::Shared;
 use gpui::{App, AppContext as _, AsyncApp, Context, Entity, Subscription, Task, WeakEntity};
@@ -280,10 +280,63 @@ pub struct DebugScenario {
     pub tcp_connection: Option<TcpArgumentsTemplate>,
 }
 
+/// A named group of debug scenarios that are started together.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct DebugCompound {
+    /// Name of the compound debug task
+    pub label: SharedString,
+    /// Labels of the debug scenarios to start, in the order they should be started.
+    /// Scenarios are looked up in the same debug.json file first, then globally.
+    pub configurations: Vec<SharedString>,
+    /// Whether stopping any session of the compound stops all of its other sessions
+    #[serde(default)]
+    pub stop_all: bool,
+    /// Whether to wait for each session to start running before starting the next one
+    #[serde(default)]
+    pub sequential: bool,
+}
+
+/// A single entry of a debug.json file: either a debug scenario, or a compound of them.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug, JsonSchema)]
+#[serde(untagged)]
+pub enum DebugTaskEntry {
+    Compound(DebugCompound),
+    Scenario(DebugScenario),
+}
+
+impl From<DebugScenario> for DebugTaskEntry {
+    fn from(scenario: DebugScenario) -> Self {
+        Self::Scenario(scenario)
+    }
+}
+
+impl From<DebugCompound> for DebugTaskEntry {
+    fn from(compound: DebugCompound) -> Self {
+        Self::Compound(compound)
+    }
+}
+
 /// A group of Debug Tasks defined in a JSON file.
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(transparent)]
-pub struct DebugTaskFile(pub Vec<DebugScenario>);
+pub struct DebugTaskFile(pub Vec<DebugTaskEntry>);
+
+impl DebugTaskFile {
+    pub fn scenarios(&self) -> impl Iterator<Item = &DebugScenario> {
+        self.0.iter().filter_map(|entry| match entry {
+            DebugTaskEntry::Scenario(scenario) => Some(scenario),
+            DebugTaskEntry::Compound(_) => None,
+        })
+    }
+
+    pub fn compounds(&self) -> impl Iterator<Item = &DebugCompound> {
+        self.0.iter().filter_map(|entry| match entry {
+            DebugTaskEntry::Compound(compound) => Some(compound),
+            DebugTaskEntry::Scenario(_) => None,
+        })
+    }
+}
 
 impl DebugTaskFile {
     pub fn generate_json_schema(schemas: &AdapterSchemas) -> serde_json::Value {
@@ -361,45 +414,73 @@ impl DebugTaskFile {
             "type": "array",
             "items": {
                 "type": "object",
-                "required": ["adapter", "label"],
-                // TODO: Uncommenting this will cause json-language-server to provide warnings for
-                // unrecognized properties. It should be enabled if/when there's an adapter JSON
-                // schema that's comprehensive. In order to not get warnings for the other schemas,
-                // `additionalProperties` or `unevaluatedProperties` (to handle "allOf" etc style
-                // schema combinations) could be set to `true` for that schema.
-                //
-                // "unevaluatedProperties": false,
-                "properties": {
-                    "adapter": {
-                        "type": "string",
-                        "description": "The name of the debug adapter"
-                    },
-                    "label": {
-                        "type": "string",
-                        "description": "The name of the debug configuration"
-                    },
-                    "build": build_task_definition_ref,
-                    "tcp_connection": {
-                        "type": "object",
-                        "description": "Optional TCP connection information for connecting to an already running debug adapter",
-                        "properties": {
-                            "port": {
-                                "type": "integer",
-                                "description": "The port that the debug adapter is listening on (default: auto-find open port)"
-                            },
-                            "host": {
-                                "type": "string",
-                                "pattern": "^((25[0-5]|(2[0-4]|1\\d|[1-9]|)\\d)\\.?\\b){4}$",
-                                "description": "The host that the debug adapter is listening to (default: 127.0.0.1)"
-                            },
-                            "timeout": {
-                                "type": "integer",
-                                "description": "The max amount of time in milliseconds to connect to a tcp DAP before returning an error (default: 2000ms)"
-                            }
+                "if": {
+                    "required": ["configurations"]
+                },
+                "then": {
+                    "required": ["label", "configurations"],
+                    "additionalProperties": false,
+                    "properties": {
+                        "label": {
+                            "type": "string",
+                            "description": "The name of the compound debug configuration"
+                        },
+                        "configurations": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "description": "Labels of the debug configurations to start together, in order"
+                        },
+                        "stop_all": {
+                            "type": "boolean",
+                            "description": "Whether stopping one session of the compound stops all of them (default: false)"
+                        },
+                        "sequential": {
+                            "type": "boolean",
+                            "description": "Whether to wait for each session to start running before starting the next one (default: false)"
                         }
                     }
                 },
-                "allOf": adapter_conditions
+                "else": {
+                    "required": ["adapter", "label"],
+                    // TODO: Uncommenting this will cause json-language-server to provide warnings for
+                    // unrecognized properties. It should be enabled if/when there's an adapter JSON
+                    // schema that's comprehensive. In order to not get warnings for the other schemas,
+                    // `additionalProperties` or `unevaluatedProperties` (to handle "allOf" etc style
+                    // schema combinations) could be set to `true` for that schema.
+                    //
+                    // "unevaluatedProperties": false,
+                    "properties": {
+                        "adapter": {
+                            "type": "string",
+                            "description": "The name of the debug adapter"
+                        },
+                        "label": {
+                            "type": "string",
+                            "description": "The name of the debug configuration"
+                        },
+                        "build": build_task_definition_ref,
+                        "tcp_connection": {
+                            "type": "object",
+                            "description": "Optional TCP connection information for connecting to an already running debug adapter",
+                            "properties": {
+                                "port": {
+                                    "type": "integer",
+                                    "description": "The port that the debug adapter is listening on (default: auto-find open port)"
+                                },
+                                "host": {
+                                    "type": "string",
+                                    "pattern": "^((25[0-5]|(2[0-4]|1\\d|[1-9]|)\\d)\\.?\\b){4}$",
+                                    "description": "The host that the debug adapter is listening to (default: 127.0.0.1)"
+                                },
+                                "timeout": {
+                                    "type": "integer",
+                                    "description": "The max amount of time in milliseconds to connect to a tcp DAP before returning an error (default: 2000ms)"
+                                }
+                            }
+                        }
+                    },
+                    "allOf": adapter_conditions
+                }
             },
             "$defs": generator.take_definitions(true),
         })
@@ -408,7 +489,7 @@ impl DebugTaskFile {
 
 #[cfg(test)]
 mod tests {
-    use crate::DebugScenario;
+    use crate::{DebugCompound, DebugScenario, DebugTaskEntry, DebugTaskFile};
     use serde_json::json;
 
     #[test]
@@ -531,4 +612,43 @@ mod tests {
             _ => panic!("Expected Template variant"),
         }
     }
+
+    #[test]
+    fn test_debug_task_file_with_compounds() {
+        let json = r#"[
+            {
+                "label": "Server",
+                "adapter": "CodeLLDB",
+                "request": "launch",
+                "program": "target/debug/server"
+            },
+            {
+                "label": "Server + Client",
+                "configurations": ["Server", "Client"],
+                "stop_all": true
+            }
+        ]"#;
+
+        let file: DebugTaskFile = serde_json::from_str(json).unwrap();
+        assert_eq!(file.scenarios().count(), 1);
+        assert_eq!(
+            file.compounds().cloned().collect::<Vec<_>>(),
+            vec![DebugCompound {
+                label: "Server + Client".into(),
+                configurations: vec!["Server".into(), "Client".into()],
+                stop_all: true,
+                sequential: false,
+            }]
+        );
+
+        let round_tripped: DebugTaskFile =
+            serde_json::from_value(serde_json::to_value(&file).unwrap()).unwrap();
+        assert_eq!(round_tripped, file);
+
+        let entry: DebugTaskEntry = serde_json::from_str(
+            r#"{ "label": "Client", "adapter": "Delve", "configurations": "not a compound" }"#,
+        )
+        .unwrap();
+        assert!(matches!(entry, DebugTaskEntry::Scenario(_)));
+    }
 }
@@ -19,8 +19,8 @@ use std::str::FromStr;
 
 pub use adapter_schema::{AdapterSchema, AdapterSchemas};
 pub use debug_format::{
-    AttachRequest, BuildTaskDefinition, DebugRequest, DebugScenario, DebugTaskFile, LaunchRequest,
-    Request, TcpArgumentsTemplate, ZedDebugConfig,
+    AttachRequest, BuildTaskDefinition, DebugCompound, DebugRequest, DebugScenario, DebugTaskEntry,
+    DebugTaskFile, LaunchRequest, Request, TcpArgumentsTemplate, ZedDebugConfig,
 };
 pub use shell_builder::{DEFAULT_REMOTE_SHELL, ShellBuilder};
 pub use task_template::{
@@ -3,7 +3,8 @@ use serde::Deserialize;
 use util::ResultExt as _;
 
 use crate::{
-    DebugScenario, DebugTaskFile, EnvVariableReplacer, TcpArgumentsTemplate, VariableName,
+    DebugCompound, DebugScenario, DebugTaskEntry, DebugTaskFile, EnvVariableReplacer,
+    TcpArgumentsTemplate, VariableName,
 };
 
 #[derive(Clone, Debug, Deserialize, PartialEq)]
@@ -53,12 +54,51 @@ impl VsCodeDebugTaskDefinition {
     }
 }
 
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodeCompoundConfiguration {
+    Name(String),
+    // Multi-root workspaces scope configurations to a folder; Zed matches them by name only.
+    Folder { name: String },
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct VsCodeDebugCompound {
+    name: String,
+    configurations: Vec<VsCodeCompoundConfiguration>,
+    #[serde(default)]
+    stop_all: bool,
+}
+
+impl VsCodeDebugCompound {
+    fn into_zed(self, replacer: &EnvVariableReplacer) -> DebugCompound {
+        DebugCompound {
+            label: replacer.replace(&self.name).into(),
+            configurations: self
+                .configurations
+                .into_iter()
+                .map(|configuration| match configuration {
+                    VsCodeCompoundConfiguration::Name(name)
+                    | VsCodeCompoundConfiguration::Folder { name } => {
+                        replacer.replace(&name).into()
+                    }
+                })
+                .collect(),
+            stop_all: self.stop_all,
+            sequential: false,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct VsCodeDebugTaskFile {
     #[serde(default)]
     version: Option<String>,
     configurations: Vec<VsCodeDebugTaskDefinition>,
+    #[serde(default)]
+    compounds: Vec<VsCodeDebugCompound>,
 }
 
 impl TryFrom<VsCodeDebugTaskFile> for DebugTaskFile {
@@ -80,6 +120,12 @@ impl TryFrom<VsCodeDebugTaskFile> for DebugTaskFile {
             .configurations
             .into_iter()
             .filter_map(|config| config.try_to_zed(&replacer).log_err())
+            .map(DebugTaskEntry::Scenario)
+            .chain(
+                file.compounds
+                    .into_iter()
+                    .map(|compound| DebugTaskEntry::Compound(compound.into_zed(&replacer))),
+            )
             .collect::<Vec<_>>();
         Ok(DebugTaskFile(templates))
     }
@@ -103,7 +149,7 @@ fn task_type_to_adapter_name(task_type: &str) -> String {
 mod tests {
     use serde_json::json;
 
-    use crate::{DebugScenario, DebugTaskFile};
+    use crate::{DebugCompound, DebugScenario, DebugTaskEntry, DebugTaskFile};
 
     use super::VsCodeDebugTaskFile;
 
@@ -135,7 +181,7 @@ mod tests {
         let zed = DebugTaskFile::try_from(parsed).expect("converting to Zed debug templates");
         pretty_assertions::assert_eq!(
             zed,
-            DebugTaskFile(vec![DebugScenario {
+            DebugTaskFile(vec![DebugTaskEntry::Scenario(DebugScenario {
                 label: "Debug my JS app".into(),
                 adapter: "JavaScript".into(),
                 config: json!({
@@ -156,7 +202,56 @@ mod tests {
                 }),
                 tcp_connection: None,
                 build: None
-            }])
+            })])
+        );
+    }
+
+    #[test]
+    fn test_parsing_vscode_launch_json_compounds() {
+        let raw = r#"
+            {
+                "version": "0.2.0",
+                "configurations": [
+                    {
+                        "name": "Server",
+                        "request": "launch",
+                        "type": "go",
+                        "program": "${workspaceFolder}/server",
+                    },
+                    {
+                        "name": "Client",
+                        "request": "launch",
+                        "type": "debugpy",
+                        "program": "${workspaceFolder}/client.py",
+                    },
+                ],
+                "compounds": [
+                    {
+                        "name": "Server/Client",
+                        "configurations": ["Server", { "name": "Client", "folder": "client" }],
+                        "stopAll": true,
+                        "preLaunchTask": "build",
+                    },
+                ]
+            }
+        "#;
+        let parsed: VsCodeDebugTaskFile =
+            serde_json_lenient::from_str(&raw).expect("deserializing launch.json");
+        let zed = DebugTaskFile::try_from(parsed).expect("converting to Zed debug templates");
+        assert_eq!(
+            zed.scenarios()
+                .map(|scenario| scenario.label.as_ref())
+                .collect::<Vec<_>>(),
+            vec!["Server", "Client"]
+        );
+        pretty_assertions::assert_eq!(
+            zed.compounds().cloned().collect::<Vec<_>>(),
+            vec![DebugCompound {
+                label: "Server/Client".into(),
+                configurations: vec!["Server".into(), "Client".into()],
+                stop_all: true,
+                sequential: false,
+            }]
         );
     }
 }
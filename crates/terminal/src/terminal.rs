@@ -57,6 +57,7 @@ use std::{
     ops::{Deref, RangeInclusive},
     path::PathBuf,
     process::ExitStatus,
+    rc::Rc,
     sync::Arc,
     time::Instant,
 };
@@ -335,6 +336,18 @@ impl Display for TerminalError {
 // https://github.com/alacritty/alacritty/blob/cb3a79dbf6472740daca8440d5166c1d4af5029e/extra/man/alacritty.5.scd?plain=1#L207-L213
 const DEFAULT_SCROLL_HISTORY_LINES: usize = 10_000;
 pub const MAX_SCROLL_HISTORY_LINES: usize = 100_000;
+/// The exit code `ssh` reports when the connection to the host was lost, but also when the
+/// command it ran exited with 255 itself.
+const SSH_CONNECTION_LOST_EXIT_CODE: i32 = 255;
+
+/// A session on an ssh host that keeps running when the connection to the host drops.
+#[derive(Clone)]
+pub struct PersistentSshSession {
+    pub name: String,
+    /// Resolves to whether the connection to the host has been lost, telling a dropped connection
+    /// apart from a command that exited with [`SSH_CONNECTION_LOST_EXIT_CODE`].
+    pub connection_lost: Rc<dyn Fn(&mut App) -> Task<bool>>,
+}
 
 pub struct TerminalBuilder {
     terminal: Terminal,
@@ -517,6 +530,9 @@ impl TerminalBuilder {
             hyperlink_regex_searches: RegexSearches::new(),
             vi_mode_enabled: false,
             is_ssh_terminal,
+            persistent_ssh_session: None,
+            detached: false,
+            exits_pending_connection_check: None,
             python_venv_directory,
             last_mouse_move_time: Instant::now(),
             last_hyperlink_search_position: None,
@@ -703,6 +719,13 @@ pub struct Terminal {
     task: Option<TaskState>,
     vi_mode_enabled: bool,
     is_ssh_terminal: bool,
+    /// The session on the ssh host this terminal runs in, if it survives reconnects.
+    persistent_ssh_session: Option<PersistentSshSession>,
+    /// Whether the connection to the persistent ssh session has been lost.
+    detached: bool,
+    /// Exits reported while checking whether the connection to the persistent ssh session was
+    /// lost, which are handled once that's known.
+    exits_pending_connection_check: Option<Vec<Option<i32>>>,
     last_mouse_move_time: Instant,
     last_hyperlink_search_position: Option<Point<Pixels>>,
     #[cfg(windows)]
@@ -1863,6 +1886,36 @@ impl Terminal {
         }
     }
 
+    pub fn set_persistent_ssh_session(&mut self, session: PersistentSshSession) {
+        self.persistent_ssh_session = Some(session);
+    }
+
+    pub fn persistent_ssh_session(&self) -> Option<&str> {
+        self.persistent_ssh_session
+            .as_ref()
+            .map(|session| session.name.as_str())
+    }
+
+    /// Whether the ssh connection of this terminal has been lost, while the
+    /// session it was attached to keeps running on the remote host.
+    pub fn is_detached(&self) -> bool {
+        self.detached
+    }
+
+    /// Replaces the pty of a detached terminal with the one from `builder`,
+    /// which is expected to attach to the same persistent ssh session.
+    pub fn reattach(&mut self, builder: TerminalBuilder, cx: &mut Context<Self>) {
+        let mut terminal = builder.subscribe(cx);
+        terminal.task = self.task.take();
+        terminal.completion_tx = self.completion_tx.clone();
+        terminal.title_override = self.title_override.take();
+        terminal.persistent_ssh_session = self.persistent_ssh_session.take();
+        terminal.python_venv_directory = self.python_venv_directory.take();
+        *self = terminal;
+        cx.emit(Event::TitleChanged);
+        cx.notify();
+    }
+
     pub fn kill_active_task(&mut self) {
         if let Some(task) = self.task() {
             if task.status == TaskStatus::Running {
@@ -1888,6 +1941,46 @@ impl Terminal {
     }
 
     fn register_task_finished(&mut self, error_code: Option<i32>, cx: &mut Context<Terminal>) {
+        if let Some(session) = &self.persistent_ssh_session {
+            if self.detached {
+                return;
+            }
+            if let Some(exits) = &mut self.exits_pending_connection_check {
+                exits.push(error_code);
+                return;
+            }
+            if error_code == Some(SSH_CONNECTION_LOST_EXIT_CODE) {
+                self.exits_pending_connection_check = Some(vec![error_code]);
+                let connection_lost = (session.connection_lost)(cx);
+                cx.spawn(async move |this, cx| {
+                    let connection_lost = connection_lost.await;
+                    this.update(cx, |this, cx| {
+                        let exits = this
+                            .exits_pending_connection_check
+                            .take()
+                            .unwrap_or_default();
+                        if connection_lost {
+                            // Only the connection to the host is gone: the session keeps running
+                            // there and gets reattached once the connection is re-established.
+                            this.detached = true;
+                            cx.emit(Event::TitleChanged);
+                        } else {
+                            for error_code in exits {
+                                this.finish_task(error_code, cx);
+                            }
+                        }
+                    })
+                    .ok();
+                })
+                .detach();
+                return;
+            }
+        }
+
+        self.finish_task(error_code, cx);
+    }
+
+    fn finish_task(&mut self, error_code: Option<i32>, cx: &mut Context<Terminal>) {
         let e: Option<ExitStatus> = error_code.map(|code| {
             #[cfg(unix)]
             {
@@ -2159,6 +2252,7 @@ mod tests {
     use collections::HashMap;
     use gpui::{Pixels, Point, TestAppContext, bounds, point, size};
     use rand::{Rng, distributions::Alphanumeric, rngs::ThreadRng, thread_rng};
+    use std::time::Duration;
 
     #[cfg_attr(windows, ignore = "TODO: fix on windows")]
     #[gpui::test]
@@ -2198,6 +2292,53 @@ mod tests {
         );
     }
 
+    #[cfg_attr(windows, ignore = "TODO: fix on windows")]
+    #[gpui::test]
+    async fn test_persistent_ssh_session_exit_code(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        for connection_lost in [false, true] {
+            let (completion_tx, completion_rx) = smol::channel::unbounded();
+            let terminal = cx.new(|cx| {
+                let mut terminal = TerminalBuilder::new(
+                    None,
+                    None,
+                    None,
+                    task::Shell::WithArguments {
+                        program: "sh".into(),
+                        args: vec!["-c".into(), "exit 255".into()],
+                        title_override: None,
+                    },
+                    HashMap::default(),
+                    CursorShape::default(),
+                    AlternateScroll::On,
+                    None,
+                    true,
+                    0,
+                    completion_tx,
+                    cx,
+                )
+                .unwrap()
+                .subscribe(cx);
+                terminal.set_persistent_ssh_session(PersistentSshSession {
+                    name: "zed-1-0".into(),
+                    connection_lost: Rc::new(move |_| Task::ready(connection_lost)),
+                });
+                terminal
+            });
+
+            if connection_lost {
+                while !terminal.read_with(cx, |terminal, _| terminal.is_detached()) {
+                    cx.executor().timer(Duration::from_millis(10)).await;
+                }
+                assert!(completion_rx.is_empty());
+            } else {
+                assert!(completion_rx.recv().await.unwrap().is_some());
+                assert!(!terminal.read_with(cx, |terminal, _| terminal.is_detached()));
+            }
+        }
+    }
+
     #[test]
     fn test_rgb_for_index() {
         // Test every possible value in the color cube.
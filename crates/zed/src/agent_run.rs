@@ -0,0 +1,320 @@
+//! `zed --agent-run`, which runs the agent on a project without opening any windows, for
+//! scripts and CI.
+
+use agent_settings::{AgentProfileId, AgentSettings};
+use anyhow::{Context as _, Result, anyhow};
+use client::{Client, ProxySettings, UserStore};
+use extension::ExtensionHostProxy;
+use fs::{Fs, RealFs};
+use gpui::{App, AppContext as _, Application, AsyncApp, Entity, http_client::read_proxy_from_env};
+use gpui_tokio::Tokio;
+use language::LanguageRegistry;
+use language_extension::LspAccess;
+use language_model::{LanguageModel, LanguageModelRegistry, SelectedModel};
+use node_runtime::{NodeBinaryOptions, NodeRuntime};
+use project::{Project, project_settings::ProjectSettings};
+use release_channel::AppVersion;
+use reqwest_client::ReqwestClient;
+use settings::{Settings, SettingsStore};
+use std::{
+    io::Read as _,
+    path::{Path, PathBuf},
+    process,
+    str::FromStr,
+    sync::Arc,
+};
+use util::ResultExt as _;
+
+/// The agent ended its turn normally.
+const EXIT_SUCCESS: i32 = 0;
+/// The agent ran, but failed, was refused, hit a limit, had a tool call fail or rejected, or
+/// its edits couldn't be saved.
+const EXIT_AGENT_FAILED: i32 = 1;
+/// The run couldn't start, e.g. because the model isn't configured.
+const EXIT_SETUP_FAILED: i32 = 2;
+
+pub struct AgentRunArgs {
+    /// A file containing the prompt, or `-` to read it from stdin.
+    pub prompt: String,
+    pub paths: Vec<String>,
+    /// `provider_id/model_id`. Defaults to the agent's default model in the user's settings.
+    pub model: Option<String>,
+    pub profile: Option<String>,
+    pub allow_tools: bool,
+}
+
+/// Runs the agent and prints its report as JSON to stdout, then exits the process.
+pub fn main(args: AgentRunArgs) -> ! {
+    let prompt = match read_prompt(&args.prompt) {
+        Ok(prompt) => prompt,
+        Err(err) => {
+            eprintln!("Error: {err:#}");
+            process::exit(EXIT_SETUP_FAILED);
+        }
+    };
+
+    Application::headless().run(move |cx| {
+        let app_state = init(cx);
+        cx.spawn(async move |cx| {
+            let exit_code = match run(args, prompt, app_state, cx).await {
+                Ok(report) => {
+                    match serde_json::to_string_pretty(&report) {
+                        Ok(json) => println!("{json}"),
+                        Err(err) => eprintln!("Error: {err}"),
+                    }
+                    if report.success {
+                        EXIT_SUCCESS
+                    } else {
+                        EXIT_AGENT_FAILED
+                    }
+                }
+                Err(err) => {
+                    eprintln!("Error: {err:#}");
+                    EXIT_SETUP_FAILED
+                }
+            };
+            process::exit(exit_code);
+        })
+        .detach();
+    });
+    process::exit(EXIT_SETUP_FAILED);
+}
+
+fn read_prompt(path: &str) -> Result<String> {
+    let prompt = if path == "-" {
+        let mut prompt = String::new();
+        std::io::stdin()
+            .read_to_string(&mut prompt)
+            .context("reading the prompt from stdin")?;
+        prompt
+    } else {
+        std::fs::read_to_string(path).with_context(|| format!("reading the prompt from {path}"))?
+    };
+    anyhow::ensure!(!prompt.trim().is_empty(), "the prompt is empty");
+    Ok(prompt)
+}
+
+struct AppState {
+    client: Arc<Client>,
+    user_store: Entity<UserStore>,
+    fs: Arc<dyn Fs>,
+    languages: Arc<LanguageRegistry>,
+    node_runtime: NodeRuntime,
+}
+
+fn init(cx: &mut App) -> AppState {
+    let app_version = AppVersion::load(env!("CARGO_PKG_VERSION"));
+    release_channel::init(app_version, cx);
+    gpui_tokio::init(cx);
+
+    let mut settings_store = SettingsStore::new(cx);
+    settings_store
+        .set_default_settings(settings::default_settings().as_ref(), cx)
+        .unwrap();
+    if let Ok(user_settings) = std::fs::read_to_string(paths::settings_file()) {
+        settings_store
+            .set_user_settings(&user_settings, cx)
+            .log_err();
+    }
+    cx.set_global(settings_store);
+    init_settings(cx);
+
+    let user_agent = format!(
+        "Zed/{} ({}; {})",
+        app_version,
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    let proxy_str = ProxySettings::get_global(cx).proxy.to_owned();
+    let proxy_url = proxy_str
+        .as_ref()
+        .and_then(|input| input.parse().ok())
+        .or_else(read_proxy_from_env);
+    let http = {
+        let _guard = Tokio::handle(cx).enter();
+        ReqwestClient::proxy_and_user_agent(proxy_url, &user_agent)
+            .expect("could not start HTTP client")
+    };
+    cx.set_http_client(Arc::new(http));
+
+    let client = Client::production(cx);
+    cx.set_http_client(client.http_client());
+    let fs: Arc<dyn Fs> = Arc::new(RealFs::new(None, cx.background_executor().clone()));
+    <dyn Fs>::set_global(fs.clone(), cx);
+
+    let mut languages = LanguageRegistry::new(cx.background_executor().clone());
+    languages.set_language_server_download_dir(paths::languages_dir().clone());
+    let languages = Arc::new(languages);
+    let user_store = cx.new(|cx| UserStore::new(client.clone(), cx));
+
+    extension::init(cx);
+    let extension_host_proxy = ExtensionHostProxy::global(cx);
+
+    let (mut tx, rx) = watch::channel(None);
+    cx.observe_global::<SettingsStore>(move |cx| {
+        let settings = &ProjectSettings::get_global(cx).node;
+        let options = NodeBinaryOptions {
+            allow_path_lookup: !settings.ignore_system_version,
+            allow_binary_download: true,
+            use_paths: settings.path.as_ref().map(|node_path| {
+                let node_path = PathBuf::from(shellexpand::tilde(node_path).as_ref());
+                let npm_path = settings
+                    .npm_path
+                    .as_ref()
+                    .map(|path| PathBuf::from(shellexpand::tilde(&path).as_ref()));
+                (
+                    node_path.clone(),
+                    npm_path.unwrap_or_else(|| {
+                        let base_path = PathBuf::new();
+                        node_path.parent().unwrap_or(&base_path).join("npm")
+                    }),
+                )
+            }),
+        };
+        tx.send(Some(options)).log_err();
+    })
+    .detach();
+    let node_runtime = NodeRuntime::new(client.http_client(), None, rx);
+
+    language::init(cx);
+    debug_adapter_extension::init(extension_host_proxy.clone(), cx);
+    language_extension::init(
+        LspAccess::Noop,
+        extension_host_proxy.clone(),
+        languages.clone(),
+    );
+    language_model::init(client.clone(), cx);
+    language_models::init(user_store.clone(), client.clone(), cx);
+    languages::init(languages.clone(), node_runtime.clone(), cx);
+    prompt_store::init(cx);
+    // Load installed extensions, so the agent has the same language servers as the editor.
+    extension_host::init(
+        extension_host_proxy,
+        fs.clone(),
+        client.clone(),
+        node_runtime.clone(),
+        cx,
+    );
+
+    AppState {
+        client,
+        user_store,
+        fs,
+        languages,
+        node_runtime,
+    }
+}
+
+/// Registers the settings that the agent and its tools read.
+fn init_settings(cx: &mut App) {
+    client::init_settings(cx);
+    Project::init_settings(cx);
+    agent_settings::init(cx);
+    terminal::init(cx);
+}
+
+async fn run(
+    args: AgentRunArgs,
+    prompt: String,
+    app_state: AppState,
+    cx: &mut AsyncApp,
+) -> Result<agent2::HeadlessRunReport> {
+    let model = cx.update(|cx| selected_model(args.model.as_deref(), cx))??;
+    let provider = cx
+        .update(|cx| LanguageModelRegistry::read_global(cx).provider(&model.provider))?
+        .with_context(|| format!("unknown language model provider `{}`", model.provider.0))?;
+    cx.update(|cx| provider.authenticate(cx))?
+        .await
+        .with_context(|| format!("authenticating with `{}`", model.provider.0))?;
+    let model: Arc<dyn LanguageModel> = cx
+        .update(|cx| {
+            provider
+                .provided_models(cx)
+                .into_iter()
+                .find(|provided| provided.id() == model.model)
+        })?
+        .with_context(|| {
+            format!(
+                "`{}` doesn't provide a model called `{}`",
+                model.provider.0, model.model.0
+            )
+        })?;
+
+    let project = cx.update(|cx| {
+        Project::local(
+            app_state.client,
+            app_state.node_runtime,
+            app_state.user_store,
+            app_state.languages,
+            app_state.fs,
+            None,
+            cx,
+        )
+    })?;
+    let paths = if args.paths.is_empty() {
+        vec![std::env::current_dir()?]
+    } else {
+        args.paths.iter().map(PathBuf::from).collect()
+    };
+    for path in paths {
+        let path =
+            std::fs::canonicalize(&path).with_context(|| format!("opening {}", path.display()))?;
+        open_worktree(&project, &path, cx).await?;
+    }
+
+    let options = agent2::HeadlessRunOptions {
+        prompt,
+        profile: args.profile.map(|profile| AgentProfileId(profile.into())),
+        allow_tools: args.allow_tools,
+    };
+    cx.update(|cx| agent2::run_headless(project, model, options, cx))?
+        .await
+}
+
+fn selected_model(model: Option<&str>, cx: &App) -> Result<SelectedModel> {
+    if let Some(model) = model {
+        return SelectedModel::from_str(model).map_err(|err| anyhow!(err));
+    }
+    let selection = AgentSettings::get_global(cx)
+        .default_model
+        .as_ref()
+        .context("no model configured; pass --agent-model or set agent.default_model")?;
+    Ok(SelectedModel {
+        provider: selection.provider.0.clone().into(),
+        model: selection.model.clone().into(),
+    })
+}
+
+async fn open_worktree(project: &Entity<Project>, path: &Path, cx: &mut AsyncApp) -> Result<()> {
+    let worktree = project
+        .update(cx, |project, cx| project.create_worktree(path, true, cx))?
+        .await?;
+    worktree
+        .read_with(cx, |worktree, _| {
+            worktree.as_local().map(|worktree| worktree.scan_complete())
+        })?
+        .context("not a local worktree")?
+        .await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use terminal::terminal_settings::TerminalSettings;
+
+    #[gpui::test]
+    fn test_init_settings(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            init_settings(cx);
+
+            // The agent's tools read these, and would panic if they weren't registered.
+            AgentSettings::get_global(cx);
+            ProjectSettings::get_global(cx);
+            TerminalSettings::get_global(cx);
+        });
+    }
+}
@@ -1,3 +1,4 @@
+mod agent_run;
 mod reliability;
 mod zed;
 
@@ -212,6 +213,17 @@ pub fn main() {
         paths::set_custom_data_dir(dir);
     }
 
+    // `zed --agent-run` Runs the agent on the given paths without opening any windows
+    if let Some(prompt) = args.agent_run {
+        agent_run::main(agent_run::AgentRunArgs {
+            prompt,
+            paths: args.paths_or_urls,
+            model: args.agent_model,
+            profile: args.agent_profile,
+            allow_tools: args.agent_allow_tools,
+        });
+    }
+
     #[cfg(all(not(debug_assertions), target_os = "windows"))]
     unsafe {
         use windows::Win32::System::Console::{ATTACH_PARENT_PROCESS, AttachConsole};
@@ -1220,6 +1232,24 @@ struct Args {
     /// Output current environment variables as JSON to stdout
     #[arg(long, hide = true)]
     printenv: bool,
+
+    /// Runs the agent on the given paths without opening any windows, using the prompt in
+    /// this file (or stdin, if `-`), and prints a JSON report of what it did.
+    #[arg(long, value_name = "PROMPT_FILE")]
+    agent_run: Option<String>,
+
+    /// The model for `--agent-run`, as `provider_id/model_id`. Defaults to the agent's default model.
+    #[arg(long, value_name = "MODEL", requires = "agent_run")]
+    agent_model: Option<String>,
+
+    /// The agent profile for `--agent-run`. Defaults to the agent's default profile.
+    #[arg(long, value_name = "PROFILE", requires = "agent_run")]
+    agent_profile: Option<String>,
+
+    /// Allows the tool calls in `--agent-run` that would otherwise ask for permission.
+    /// Without it, they are rejected.
+    #[arg(long, requires = "agent_run")]
+    agent_allow_tools: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -572,6 +572,9 @@ fn initialize_panels(
             workspace.add_panel(chat_panel, window, cx);
             workspace.add_panel(notification_panel, window, cx);
             workspace.add_panel(debug_panel, window, cx);
+            let ports_panel =
+                cx.new(|cx| recent_projects::ports_panel::PortsPanel::new(workspace, window, cx));
+            workspace.add_panel(ports_panel, window, cx);
         })?;
 
         let is_assistant2_enabled = !cfg!(test);
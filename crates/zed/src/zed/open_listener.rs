@@ -620,8 +620,10 @@ mod tests {
                 password: None,
                 args: None,
                 port_forwards: None,
+                jump_hosts: None,
                 nickname: None,
                 upload_binary_over_ssh: false,
+                persistent_terminals: false,
             }
         );
         assert_eq!(request.open_paths, vec!["/"]);
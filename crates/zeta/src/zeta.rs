@@ -31,7 +31,10 @@ use input_excerpt::excerpt_for_cursor_position;
 use language::{
     Anchor, Buffer, BufferSnapshot, EditPreview, OffsetRangeExt, ToOffset, ToPoint, text_diff,
 };
-use language_model::{LlmApiToken, RefreshLlmTokenListener};
+use language_model::{
+    LlmApiToken, RefreshLlmTokenListener, TokenUsage, UsageLedger, UsageRecord, UsageSource,
+    ZED_CLOUD_PROVIDER_ID,
+};
 use project::{Project, ProjectPath};
 use release_channel::AppVersion;
 use settings::WorktreeId;
@@ -66,6 +69,8 @@ const MAX_CONTEXT_TOKENS: usize = 150;
 const MAX_REWRITE_TOKENS: usize = 350;
 const MAX_EVENT_TOKENS: usize = 500;
 const MAX_DIAGNOSTIC_GROUPS: usize = 10;
+/// The model edit predictions are recorded against in the usage ledger.
+const EDIT_PREDICTION_MODEL_ID: &str = "zeta";
 
 /// Maximum number of events to track.
 const MAX_EVENT_COUNT: usize = 16;
@@ -485,6 +490,21 @@ impl Zeta {
             let received_response_at = Instant::now();
             log::debug!("completion response: {}", &response.output_excerpt);
 
+            // The prediction endpoint doesn't report token counts, so only the request is counted.
+            cx.update(|cx| {
+                UsageLedger::record_global(
+                    UsageRecord {
+                        source: UsageSource::EditPrediction,
+                        thread_id: None,
+                        provider: ZED_CLOUD_PROVIDER_ID,
+                        model: EDIT_PREDICTION_MODEL_ID.to_string().into(),
+                        usage: TokenUsage::default(),
+                    },
+                    cx,
+                )
+            })
+            .ok();
+
             if let Some(usage) = usage {
                 this.update(cx, |this, cx| {
                     this.user_store.update(cx, |user_store, cx| {